@@ -5,18 +5,26 @@ mod keycloak_setup;
 
 use clap::{Arg, ArgAction, Command};
 use secrecy::Secret;
-use sqlx::MySqlPool;
 use std::sync::Arc;
 use tracing_subscriber::EnvFilter;
 
 use user_api::keycloak::{KeycloakClient, KeycloakConfig};
-use user_lib::repository::{RoleRepository, UserRepository, UserRoleRepository};
-use user_lib::rootuser::{initialize_root_user, RootUserConfig};
+use user_lib::entities::TenantId;
+use user_lib::rootuser::{initialize_root_user_in_transaction, RootUserConfig};
+use user_lib::util::{connect, DbPool};
 
 // NOTE: sqlx::migrate!(...) paths are resolved relative to this crate's directory (apps/backcli)
 static USER_LIB_MIGRATOR: sqlx::migrate::Migrator =
     sqlx::migrate!("../../libs/user-lib/migrations");
 
+#[cfg(feature = "postgres")]
+static USER_LIB_POSTGRES_MIGRATOR: sqlx::migrate::Migrator =
+    sqlx::migrate!("../../libs/user-lib/migrations_postgres");
+
+#[cfg(feature = "sqlite")]
+static USER_LIB_SQLITE_MIGRATOR: sqlx::migrate::Migrator =
+    sqlx::migrate!("../../libs/user-lib/migrations_sqlite");
+
 #[tokio::main]
 async fn main() {
     // Initialize tracing
@@ -145,39 +153,54 @@ async fn main() {
     }
 }
 
-async fn run_user_lib_migrations() -> Result<(), String> {
+/// Connect to `DATABASE_URL`; the URL scheme selects MySQL, PostgreSQL or SQLite
+async fn connect_database() -> Result<DbPool, String> {
     let db_url =
         std::env::var("DATABASE_URL").map_err(|_| "DATABASE_URL must be set".to_string())?;
 
-    let pool = MySqlPool::connect(&db_url)
+    connect(&db_url, 0)
         .await
-        .map_err(|e| format!("Failed to connect to database: {e}"))?;
+        .map_err(|e| format!("Failed to connect to database: {e}"))
+}
+
+fn missing_migrations(pool: &DbPool) -> String {
+    let backend = pool.kind().as_str();
+    format!("backcli was built without {backend} migrations; rebuild with `--features {backend}`")
+}
+
+async fn run_user_lib_migrations() -> Result<(), String> {
+    let pool = connect_database().await?;
 
     println!("Running migrations for user-lib...");
-    USER_LIB_MIGRATOR
-        .run(&pool)
-        .await
-        .map_err(|e| format!("Migration failed: {e}"))?;
+    match &pool {
+        DbPool::MySql(pool) => USER_LIB_MIGRATOR.run(pool).await,
+        #[cfg(feature = "postgres")]
+        DbPool::Postgres(pool) => USER_LIB_POSTGRES_MIGRATOR.run(pool).await,
+        #[cfg(feature = "sqlite")]
+        DbPool::Sqlite(pool) => USER_LIB_SQLITE_MIGRATOR.run(pool).await,
+        other => return Err(missing_migrations(other)),
+    }
+    .map_err(|e| format!("Migration failed: {e}"))?;
 
     println!("Migrations applied successfully.");
     Ok(())
 }
 
 async fn run_user_lib_delete() -> Result<(), String> {
-    let db_url =
-        std::env::var("DATABASE_URL").map_err(|_| "DATABASE_URL must be set".to_string())?;
-
-    let pool = MySqlPool::connect(&db_url)
-        .await
-        .map_err(|e| format!("Failed to connect to database: {e}"))?;
+    let pool = connect_database().await?;
 
     println!("Reverting all migrations for user-lib...");
     // SQLx Migrator supports down migrations via `undo(target_version)`.
     // Passing `0` reverts all applied migrations because migration versions are positive timestamps.
-    USER_LIB_MIGRATOR
-        .undo(&pool, 0)
-        .await
-        .map_err(|e| format!("Revert failed: {e}"))?;
+    match &pool {
+        DbPool::MySql(pool) => USER_LIB_MIGRATOR.undo(pool, 0).await,
+        #[cfg(feature = "postgres")]
+        DbPool::Postgres(pool) => USER_LIB_POSTGRES_MIGRATOR.undo(pool, 0).await,
+        #[cfg(feature = "sqlite")]
+        DbPool::Sqlite(pool) => USER_LIB_SQLITE_MIGRATOR.undo(pool, 0).await,
+        other => return Err(missing_migrations(other)),
+    }
+    .map_err(|e| format!("Revert failed: {e}"))?;
 
     println!("Data deleted successfully.");
     Ok(())
@@ -206,9 +229,10 @@ async fn init_root_user() -> Result<(), String> {
 
     println!("Creating user in Keycloak...");
 
-    // Step 2: Create user in Keycloak
+    // Step 2: Create user in Keycloak (the root user belongs to the default tenant)
     let keycloak_id = keycloak
         .create_user(
+            TenantId::DEFAULT,
             &config.email,
             Some(&config.first_name),
             Some(&config.last_name),
@@ -223,29 +247,19 @@ async fn init_root_user() -> Result<(), String> {
     config.keycloak_id = keycloak_id.clone();
 
     // Step 3: Connect to database
-    let db_url =
-        std::env::var("DATABASE_URL").map_err(|_| "DATABASE_URL must be set".to_string())?;
-
-    let pool = MySqlPool::connect(&db_url)
-        .await
-        .map_err(|e| format!("Failed to connect to database: {e}"))?;
-
-    // Step 4: Initialize repositories
-    let user_repo = UserRepository::new(pool.clone());
-    let role_repo = RoleRepository::new(pool.clone());
-    let user_role_repo = UserRoleRepository::new(pool.clone());
+    let pool = connect_database().await?;
 
     println!("Creating user in database and assigning admin role...");
 
-    // Step 5: Initialize root user in database
-    let user = initialize_root_user(&user_repo, &role_repo, &user_role_repo, &config)
+    // Step 4: Initialize root user in database (user + admin role in one transaction)
+    let user = initialize_root_user_in_transaction(&pool, &config)
         .await
         .map_err(|e| {
             // If DB creation fails, we should delete from Keycloak
             let kc_clone = keycloak.clone();
             let kc_id = keycloak_id.clone();
             tokio::spawn(async move {
-                if let Err(del_err) = kc_clone.delete_user(&kc_id).await {
+                if let Err(del_err) = kc_clone.delete_user(TenantId::DEFAULT, &kc_id).await {
                     eprintln!("WARNING: Failed to rollback Keycloak user: {del_err}");
                     eprintln!("Manual cleanup required for Keycloak user: {kc_id}");
                 } else {
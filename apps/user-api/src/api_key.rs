@@ -0,0 +1,298 @@
+//! API keys of service accounts (machine clients).
+//!
+//! Batch jobs that have no Keycloak login call the v1 API with `X-API-Key: <key>`. A key
+//! belongs to one tenant and grants a fixed set of scopes. Only the SHA-256 hash of a key
+//! is stored, so the key itself is shown once, when it is created or rotated. Requests
+//! without the header must carry a bearer token signed by one of the configured Keycloak
+//! realms; requests with neither are refused.
+
+use axum::{
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderMap, Method},
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use secrecy::Secret;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+use user_lib::entities::ApiKey;
+use user_lib::repository::traits::{
+    RoleRepositoryTrait, UserRepositoryTrait, UserRoleRepositoryTrait,
+};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::keycloak::AccessToken;
+use crate::methods::routes::API_V1_PREFIX;
+use crate::services::integrated_user_service::IntegratedServiceError;
+use crate::state::AppState;
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Start of every key, so leaked keys are easy to recognize and scan for
+const KEY_MARKER: &str = "uak_";
+
+/// Characters of a key kept in clear for listings, the marker included
+const DISPLAY_PREFIX_LEN: usize = 12;
+
+/// `last_used_at` is only written when the stored value is older than this, so a busy
+/// key does not cost a database write per request
+pub const LAST_USED_RESOLUTION: Duration = Duration::from_secs(60);
+
+/// What an API key may do: read or write one kind of resource of its tenant. Granting
+/// roles and setting credentials are scopes of their own, so a key that manages users
+/// cannot make itself or anyone else an admin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+pub enum ApiKeyScope {
+    #[serde(rename = "users:read")]
+    UsersRead,
+    #[serde(rename = "users:write")]
+    UsersWrite,
+    #[serde(rename = "users:credentials")]
+    UsersCredentials,
+    #[serde(rename = "roles:read")]
+    RolesRead,
+    #[serde(rename = "roles:write")]
+    RolesWrite,
+    #[serde(rename = "roles:grant")]
+    RolesGrant,
+    #[serde(rename = "groups:read")]
+    GroupsRead,
+    #[serde(rename = "groups:write")]
+    GroupsWrite,
+}
+
+impl ApiKeyScope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ApiKeyScope::UsersRead => "users:read",
+            ApiKeyScope::UsersWrite => "users:write",
+            ApiKeyScope::UsersCredentials => "users:credentials",
+            ApiKeyScope::RolesRead => "roles:read",
+            ApiKeyScope::RolesWrite => "roles:write",
+            ApiKeyScope::RolesGrant => "roles:grant",
+            ApiKeyScope::GroupsRead => "groups:read",
+            ApiKeyScope::GroupsWrite => "groups:write",
+        }
+    }
+
+    /// Scope a v1 request needs, from the first segment of its path: `GET` and `HEAD`
+    /// need the resource's read scope, everything else its write scope. Changing the
+    /// roles of a user or group needs `roles:grant`, and setting a user's password or
+    /// sending it account actions `users:credentials`. `None` for paths outside the
+    /// scoped resources, which API keys cannot call.
+    pub fn required_for(method: &Method, path: &str) -> Option<Self> {
+        let segments: Vec<&str> = path
+            .strip_prefix(API_V1_PREFIX)
+            .unwrap_or(path)
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        let read = method == Method::GET || method == Method::HEAD;
+
+        match (segments.as_slice(), read) {
+            (["users" | "groups", _, "roles", ..], false) => return Some(ApiKeyScope::RolesGrant),
+            (["users", _, "password" | "actions", ..], false) => {
+                return Some(ApiKeyScope::UsersCredentials)
+            }
+            _ => {}
+        }
+        match (*segments.first()?, read) {
+            ("users", true) => Some(ApiKeyScope::UsersRead),
+            ("users", false) => Some(ApiKeyScope::UsersWrite),
+            ("roles", true) => Some(ApiKeyScope::RolesRead),
+            ("roles", false) => Some(ApiKeyScope::RolesWrite),
+            ("groups", true) => Some(ApiKeyScope::GroupsRead),
+            ("groups", false) => Some(ApiKeyScope::GroupsWrite),
+            _ => None,
+        }
+    }
+}
+
+/// A new key, with what is stored of it
+pub struct GeneratedApiKey {
+    pub key: Secret<String>,
+    pub prefix: String,
+    pub hash: String,
+}
+
+/// Generate a key from 244 random bits: two v4 UUIDs, which come from the OS RNG
+pub fn generate_api_key() -> GeneratedApiKey {
+    let mut bytes = [0u8; 32];
+    bytes[..16].copy_from_slice(Uuid::new_v4().as_bytes());
+    bytes[16..].copy_from_slice(Uuid::new_v4().as_bytes());
+    let key = format!("{KEY_MARKER}{}", URL_SAFE_NO_PAD.encode(bytes));
+
+    GeneratedApiKey {
+        prefix: key[..DISPLAY_PREFIX_LEN].to_string(),
+        hash: hash_api_key(&key),
+        key: Secret::new(key),
+    }
+}
+
+/// Hex SHA-256 of a key. Keys are random and long, so a plain hash is enough to keep
+/// them from being recovered from the database.
+pub fn hash_api_key(key: &str) -> String {
+    format!("{:x}", Sha256::digest(key.as_bytes()))
+}
+
+/// Who is calling the v1 API
+#[derive(Debug, Clone)]
+pub enum Caller {
+    /// User with a verified bearer token and no API key
+    User(AccessToken),
+    /// Service account authenticated by an active API key
+    ServiceAccount(ApiKey),
+}
+
+impl Caller {
+    /// Stable identity of the caller: the ID of the API key, or the realm and subject of
+    /// the token
+    pub fn identity(&self) -> String {
+        match self {
+            Caller::User(token) => format!("user:{}:{}", token.realm, token.subject),
+            Caller::ServiceAccount(key) => format!("api-key:{}", key.id),
+        }
+    }
+}
+
+impl<U, R, UR> FromRequestParts<AppState<U, R, UR>> for Caller
+where
+    U: UserRepositoryTrait + Send + Sync + 'static,
+    R: RoleRepositoryTrait + Send + Sync + 'static,
+    UR: UserRoleRepositoryTrait + Send + Sync + 'static,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState<U, R, UR>,
+    ) -> Result<Self, Self::Rejection> {
+        if let Some(caller) = parts.extensions.get::<Caller>() {
+            return Ok(caller.clone());
+        }
+
+        let caller = match parts.headers.get(API_KEY_HEADER) {
+            None => {
+                let token = bearer_token(&parts.headers).ok_or_else(|| {
+                    ApiError::Unauthorized("send a bearer token or an X-API-Key header".to_string())
+                })?;
+                let token = state
+                    .user_service
+                    .keycloak()
+                    .verify_access_token(token)
+                    .await
+                    .map_err(|e| {
+                        handle_integrated_service_error(
+                            IntegratedServiceError::Keycloak(e),
+                            &state.env,
+                            "verify_access_token",
+                        )
+                    })?;
+                Caller::User(token)
+            }
+            Some(value) => {
+                let presented = value.to_str().map(str::trim).unwrap_or_default();
+                let key = state
+                    .user_service
+                    .authenticate_api_key(presented)
+                    .await
+                    .map_err(|e| {
+                        handle_integrated_service_error(e, &state.env, "authenticate_api_key")
+                    })?
+                    .ok_or_else(|| {
+                        ApiError::Unauthorized("invalid, expired or revoked API key".to_string())
+                    })?;
+                Caller::ServiceAccount(key)
+            }
+        };
+
+        parts.extensions.insert(caller.clone());
+        Ok(caller)
+    }
+}
+
+/// Non-empty token of an `Authorization: Bearer` header
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_keys_are_distinct_and_hashed() {
+        let first = generate_api_key();
+        let second = generate_api_key();
+        let key = secrecy::ExposeSecret::expose_secret(&first.key);
+
+        assert!(key.starts_with(KEY_MARKER));
+        assert_eq!(key.len(), KEY_MARKER.len() + 43);
+        assert!(key.starts_with(&first.prefix));
+        assert_eq!(first.hash, hash_api_key(key));
+        assert_eq!(first.hash.len(), 64);
+        assert_ne!(first.hash, second.hash);
+    }
+
+    #[test]
+    fn test_required_scope_follows_resource_and_method() {
+        assert_eq!(
+            ApiKeyScope::required_for(&Method::GET, "/v1/users/{id}"),
+            Some(ApiKeyScope::UsersRead)
+        );
+        assert_eq!(
+            ApiKeyScope::required_for(&Method::POST, "/users/{id}/suspend"),
+            Some(ApiKeyScope::UsersWrite)
+        );
+        assert_eq!(
+            ApiKeyScope::required_for(&Method::DELETE, "/v1/groups/{group_id}/members/{user_id}"),
+            Some(ApiKeyScope::GroupsWrite)
+        );
+        assert_eq!(
+            ApiKeyScope::required_for(&Method::PUT, "/v1/groups/{group_id}/roles/{role_id}"),
+            Some(ApiKeyScope::RolesGrant)
+        );
+        assert_eq!(
+            ApiKeyScope::required_for(&Method::POST, "/v1/users/{user_id}/roles/{role_id}"),
+            Some(ApiKeyScope::RolesGrant)
+        );
+        assert_eq!(
+            ApiKeyScope::required_for(&Method::POST, "/v1/users/{id}/password"),
+            Some(ApiKeyScope::UsersCredentials)
+        );
+        assert_eq!(
+            ApiKeyScope::required_for(&Method::POST, "/v1/users/{id}/actions"),
+            Some(ApiKeyScope::UsersCredentials)
+        );
+        assert_eq!(
+            ApiKeyScope::required_for(&Method::HEAD, "/v1/roles"),
+            Some(ApiKeyScope::RolesRead)
+        );
+        assert_eq!(ApiKeyScope::required_for(&Method::GET, "/v1/tenants"), None);
+    }
+
+    #[test]
+    fn test_scope_names_round_trip() {
+        for scope in [
+            ApiKeyScope::UsersRead,
+            ApiKeyScope::UsersWrite,
+            ApiKeyScope::UsersCredentials,
+            ApiKeyScope::RolesRead,
+            ApiKeyScope::RolesWrite,
+            ApiKeyScope::RolesGrant,
+            ApiKeyScope::GroupsRead,
+            ApiKeyScope::GroupsWrite,
+        ] {
+            let json = serde_json::to_string(&scope).unwrap();
+            assert_eq!(json, format!("\"{}\"", scope.as_str()));
+            assert_eq!(serde_json::from_str::<ApiKeyScope>(&json).unwrap(), scope);
+        }
+    }
+}
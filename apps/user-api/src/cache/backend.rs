@@ -0,0 +1,43 @@
+use async_trait::async_trait;
+use std::time::Duration;
+
+use super::keys::CacheNamespace;
+
+#[derive(Debug)]
+pub enum CacheError {
+    /// The backend cannot be reached right now
+    Unavailable,
+    /// The backend rejected or failed a command
+    Backend(String),
+}
+
+impl std::fmt::Display for CacheError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CacheError::Unavailable => write!(f, "cache backend unavailable"),
+            CacheError::Backend(msg) => write!(f, "cache backend error: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for CacheError {}
+
+/// Raw key/value storage behind the typed [`Cache`](super::Cache).
+/// Values are opaque strings; serialization and stats live in the caller.
+#[async_trait]
+pub trait CacheBackend: Send + Sync {
+    /// Whether the backend should be used right now (e.g. not in a reconnect backoff)
+    fn is_available(&self) -> bool;
+
+    async fn get(&self, key: &str) -> Result<Option<String>, CacheError>;
+
+    /// Fetch several keys at once; the result has one slot per requested key
+    async fn mget(&self, keys: &[String]) -> Result<Vec<Option<String>>, CacheError>;
+
+    async fn set(&self, key: &str, value: &str, ttl: Duration) -> Result<(), CacheError>;
+
+    async fn delete(&self, key: &str) -> Result<(), CacheError>;
+
+    /// Delete every key in `namespace`, returning how many were removed
+    async fn delete_namespace(&self, namespace: CacheNamespace) -> Result<u64, CacheError>;
+}
@@ -1,174 +1,429 @@
-use deadpool_redis::{Config, Connection, Pool, Runtime};
-use redis::AsyncCommands;
-use serde::{de::DeserializeOwned, Serialize};
-use std::time::Duration;
+use async_trait::async_trait;
+use deadpool_redis::{sentinel, Config, Connection, Pool, PoolConfig, Runtime, Timeouts};
+use redis::aio::ConnectionLike;
+use redis::sentinel::SentinelNodeConnectionInfo;
+use redis::{
+    AsyncCommands, Cmd, ConnectionAddr, ConnectionInfo, Pipeline, RedisConnectionInfo, RedisFuture,
+    TlsMode, Value,
+};
+use secrecy::ExposeSecret;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use super::backend::{CacheBackend, CacheError};
 use super::config::CacheConfig;
+use super::keys::{self, CacheNamespace};
 
-#[derive(Clone)]
+const DEFAULT_SENTINEL_PORT: u16 = 26379;
+
+/// Pooled Redis connection, either to a standalone server or to a Sentinel-discovered master
+enum PooledConnection {
+    Standalone(Connection),
+    Sentinel(sentinel::Connection),
+}
+
+impl ConnectionLike for PooledConnection {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        match self {
+            PooledConnection::Standalone(conn) => conn.req_packed_command(cmd),
+            PooledConnection::Sentinel(conn) => conn.req_packed_command(cmd),
+        }
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        cmd: &'a Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisFuture<'a, Vec<Value>> {
+        match self {
+            PooledConnection::Standalone(conn) => conn.req_packed_commands(cmd, offset, count),
+            PooledConnection::Sentinel(conn) => conn.req_packed_commands(cmd, offset, count),
+        }
+    }
+
+    fn get_db(&self) -> i64 {
+        match self {
+            PooledConnection::Standalone(conn) => conn.get_db(),
+            PooledConnection::Sentinel(conn) => conn.get_db(),
+        }
+    }
+}
+
+enum RedisPool {
+    Standalone(Pool),
+    Sentinel(sentinel::Pool),
+}
+
+impl RedisPool {
+    fn from_config(config: &CacheConfig) -> Result<Self, String> {
+        let pool_config = PoolConfig {
+            timeouts: Timeouts {
+                wait: Some(config.connect_timeout),
+                create: Some(config.connect_timeout),
+                recycle: Some(config.connect_timeout),
+            },
+            ..PoolConfig::default()
+        };
+        let redis_info = RedisConnectionInfo {
+            db: config.redis_db,
+            username: config.redis_username.clone(),
+            password: config
+                .redis_password
+                .as_ref()
+                .map(|p| p.expose_secret().clone()),
+            ..RedisConnectionInfo::default()
+        };
+
+        if config.uses_sentinel() {
+            let nodes = config
+                .redis_sentinel_nodes
+                .iter()
+                .map(|node| ConnectionInfo {
+                    addr: connection_addr(node, DEFAULT_SENTINEL_PORT, config.redis_tls),
+                    redis: RedisConnectionInfo::default(),
+                })
+                .collect();
+            let master_info = SentinelNodeConnectionInfo {
+                tls_mode: config.redis_tls.then_some(TlsMode::Secure),
+                redis_connection_info: Some(redis_info),
+            };
+            let manager = sentinel::Manager::new(
+                nodes,
+                config.redis_sentinel_master.clone(),
+                Some(master_info),
+                sentinel::SentinelServerType::Master,
+            )
+            .map_err(|e| e.to_string())?;
+            sentinel::Pool::builder(manager)
+                .config(pool_config)
+                .runtime(Runtime::Tokio1)
+                .build()
+                .map(RedisPool::Sentinel)
+                .map_err(|e| e.to_string())
+        } else {
+            let info = ConnectionInfo {
+                addr: tcp_addr(
+                    config.redis_host.clone(),
+                    config.redis_port,
+                    config.redis_tls,
+                ),
+                redis: redis_info,
+            };
+            let mut cfg = Config::from_connection_info(info);
+            cfg.pool = Some(pool_config);
+            cfg.create_pool(Some(Runtime::Tokio1))
+                .map(RedisPool::Standalone)
+                .map_err(|e| e.to_string())
+        }
+    }
+
+    async fn get(&self) -> Result<PooledConnection, String> {
+        match self {
+            RedisPool::Standalone(pool) => pool
+                .get()
+                .await
+                .map(PooledConnection::Standalone)
+                .map_err(|e| e.to_string()),
+            RedisPool::Sentinel(pool) => pool
+                .get()
+                .await
+                .map(PooledConnection::Sentinel)
+                .map_err(|e| e.to_string()),
+        }
+    }
+}
+
+fn tcp_addr(host: String, port: u16, tls: bool) -> ConnectionAddr {
+    if tls {
+        ConnectionAddr::TcpTls {
+            host,
+            port,
+            insecure: false,
+            tls_params: None,
+        }
+    } else {
+        ConnectionAddr::Tcp(host, port)
+    }
+}
+
+/// Parse a `host:port` node address, falling back to `default_port`
+fn connection_addr(node: &str, default_port: u16, tls: bool) -> ConnectionAddr {
+    match node.rsplit_once(':') {
+        Some((host, port)) => match port.parse() {
+            Ok(port) => tcp_addr(host.to_string(), port, tls),
+            Err(_) => tcp_addr(node.to_string(), default_port, tls),
+        },
+        None => tcp_addr(node.to_string(), default_port, tls),
+    }
+}
+
+/// Delay before the next reconnect attempt after `failures` consecutive failures
+fn reconnect_backoff(failures: u32, min: Duration, max: Duration) -> Duration {
+    let exponent = failures.saturating_sub(1).min(16);
+    min.saturating_mul(1 << exponent).min(max)
+}
+
+/// Get current time as milliseconds since UNIX epoch
+fn current_time_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Connection health of a `RedisCache`
+struct RedisState {
+    pool: RedisPool,
+    healthy: AtomicBool,
+    consecutive_failures: AtomicU32,
+    retry_at_millis: AtomicU64,
+    min_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl RedisState {
+    /// Whether Redis is healthy or a reconnect attempt is due
+    fn is_available(&self) -> bool {
+        self.healthy.load(Ordering::SeqCst)
+            || current_time_millis() >= self.retry_at_millis.load(Ordering::SeqCst)
+    }
+
+    /// Claim the next reconnect attempt so concurrent callers don't all probe a dead server
+    fn try_begin_attempt(&self) -> bool {
+        if self.healthy.load(Ordering::SeqCst) {
+            return true;
+        }
+
+        let retry_at = self.retry_at_millis.load(Ordering::SeqCst);
+        let now = current_time_millis();
+        if now < retry_at {
+            return false;
+        }
+
+        let hold = now + self.max_backoff.as_millis() as u64;
+        self.retry_at_millis
+            .compare_exchange(retry_at, hold, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+    }
+
+    /// Mark Redis healthy, returning `true` if this ends an outage
+    fn record_success(&self) -> bool {
+        self.healthy.store(true, Ordering::SeqCst);
+        self.consecutive_failures.swap(0, Ordering::SeqCst) > 0
+    }
+
+    /// Mark Redis unhealthy and schedule the next reconnect attempt
+    fn record_failure(&self) -> (u32, Duration) {
+        let failures = self.consecutive_failures.fetch_add(1, Ordering::SeqCst) + 1;
+        let backoff = reconnect_backoff(failures, self.min_backoff, self.max_backoff);
+        self.retry_at_millis.store(
+            current_time_millis() + backoff.as_millis() as u64,
+            Ordering::SeqCst,
+        );
+        self.healthy.store(false, Ordering::SeqCst);
+        (failures, backoff)
+    }
+}
+
+/// Redis (standalone or Sentinel) cache backend
 pub struct RedisCache {
-    pool: Option<Pool>,
+    state: RedisState,
 }
 
 impl std::fmt::Debug for RedisCache {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("RedisCache")
-            .field("connected", &self.pool.is_some())
+            .field("healthy", &self.state.healthy.load(Ordering::SeqCst))
             .finish()
     }
 }
 
 impl RedisCache {
-    pub async fn new(config: &CacheConfig) -> Self {
-        if !config.enabled {
-            tracing::info!("Cache disabled by configuration");
-            return Self { pool: None };
-        }
+    /// Build the connection pool and probe Redis once. Returns `None` only if the
+    /// configuration is unusable; an unreachable server is retried lazily.
+    pub async fn connect(config: &CacheConfig) -> Option<Self> {
+        tracing::info!(
+            redis_host = %config.redis_host,
+            redis_port = config.redis_port,
+            redis_db = config.redis_db,
+            redis_tls = config.redis_tls,
+            sentinel_nodes = ?config.redis_sentinel_nodes,
+            "Connecting to Redis"
+        );
 
-        let redis_url = config.redis_url();
-        tracing::info!(redis_url = %redis_url, "Connecting to Redis");
-
-        let cfg = Config::from_url(&redis_url);
-        match cfg.create_pool(Some(Runtime::Tokio1)) {
-            Ok(pool) => {
-                // Test connection
-                match pool.get().await {
-                    Ok(mut conn) => {
-                        let ping_result: Result<String, _> =
-                            redis::cmd("PING").query_async(&mut conn).await;
-                        match ping_result {
-                            Ok(_) => {
-                                tracing::info!("Redis connection established");
-                                Self { pool: Some(pool) }
-                            }
-                            Err(e) => {
-                                tracing::warn!(
-                                    error = %e,
-                                    "Redis PING failed, cache disabled"
-                                );
-                                Self { pool: None }
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        tracing::warn!(
-                            error = %e,
-                            "Failed to get Redis connection, cache disabled"
-                        );
-                        Self { pool: None }
-                    }
-                }
-            }
+        let pool = match RedisPool::from_config(config) {
+            Ok(pool) => pool,
             Err(e) => {
                 tracing::warn!(
                     error = %e,
                     "Failed to create Redis pool, cache disabled"
                 );
-                Self { pool: None }
+                return None;
             }
-        }
-    }
+        };
 
-    pub fn is_enabled(&self) -> bool {
-        self.pool.is_some()
-    }
+        let cache = Self {
+            state: RedisState {
+                pool,
+                healthy: AtomicBool::new(false),
+                consecutive_failures: AtomicU32::new(0),
+                retry_at_millis: AtomicU64::new(0),
+                min_backoff: config.reconnect_min_backoff,
+                max_backoff: config.reconnect_max_backoff,
+            },
+        };
 
-    async fn get_conn(&self) -> Option<Connection> {
-        let pool = self.pool.as_ref()?;
-        match pool.get().await {
-            Ok(conn) => Some(conn),
-            Err(e) => {
-                tracing::error!(error = %e, "Failed to get Redis connection from pool");
-                None
-            }
+        // Probe once so startup logs reflect reachability; later failures are retried lazily
+        if cache.get_conn().await.is_ok() {
+            tracing::info!("Redis connection established");
+        } else {
+            tracing::warn!("Redis unreachable at startup, will reconnect with backoff");
         }
+
+        Some(cache)
     }
 
-    pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
-        let mut conn = self.get_conn().await?;
+    async fn get_conn(&self) -> Result<PooledConnection, CacheError> {
+        let state = &self.state;
+        if !state.try_begin_attempt() {
+            return Err(CacheError::Unavailable);
+        }
 
-        let result: Result<Option<String>, _> = conn.get(key).await;
-        match result {
-            Ok(Some(data)) => match serde_json::from_str(&data) {
-                Ok(value) => {
-                    tracing::debug!(key = %key, "Cache hit");
-                    Some(value)
-                }
-                Err(e) => {
-                    tracing::error!(key = %key, error = %e, "Cache deserialize error - data corrupted");
-                    None
+        match state.pool.get().await {
+            Ok(mut conn) => {
+                if state.record_success() {
+                    tracing::info!("Redis connection recovered");
+                    purge_after_outage(&mut conn).await;
                 }
-            },
-            Ok(None) => {
-                tracing::debug!(key = %key, "Cache miss");
-                None
+                Ok(conn)
             }
             Err(e) => {
-                tracing::error!(key = %key, error = %e, "Redis GET command failed");
-                None
+                let (failures, backoff) = state.record_failure();
+                tracing::error!(
+                    error = %e,
+                    consecutive_failures = failures,
+                    retry_in_ms = backoff.as_millis() as u64,
+                    "Failed to get Redis connection from pool"
+                );
+                Err(CacheError::Unavailable)
             }
         }
     }
+}
 
-    pub async fn set<T: Serialize>(&self, key: &str, value: &T, ttl: Duration) {
-        let Some(mut conn) = self.get_conn().await else {
-            return;
-        };
+fn backend_error(e: redis::RedisError) -> CacheError {
+    CacheError::Backend(e.to_string())
+}
 
-        let data = match serde_json::to_string(value) {
-            Ok(d) => d,
-            Err(e) => {
-                tracing::error!(key = %key, error = %e, "Cache serialize error - failed to encode value");
-                return;
-            }
-        };
+#[async_trait]
+impl CacheBackend for RedisCache {
+    /// Redis is healthy or due for a reconnect attempt
+    fn is_available(&self) -> bool {
+        self.state.is_available()
+    }
 
-        let ttl_secs = ttl.as_secs() as i64;
-        let result: Result<(), _> = conn.set_ex(key, data, ttl_secs as u64).await;
-        if let Err(e) = result {
-            tracing::error!(key = %key, error = %e, "Redis SETEX command failed");
-        } else {
-            tracing::debug!(key = %key, ttl_secs = ttl_secs, "Cache set");
+    async fn get(&self, key: &str) -> Result<Option<String>, CacheError> {
+        let mut conn = self.get_conn().await?;
+        conn.get(key).await.map_err(backend_error)
+    }
+
+    async fn mget(&self, keys: &[String]) -> Result<Vec<Option<String>>, CacheError> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
         }
+        let mut conn = self.get_conn().await?;
+        // Explicit MGET: the `mget` helper sends GET for a single key, whose reply isn't a list
+        redis::cmd("MGET")
+            .arg(keys)
+            .query_async(&mut conn)
+            .await
+            .map_err(backend_error)
     }
 
-    pub async fn delete(&self, key: &str) {
-        let Some(mut conn) = self.get_conn().await else {
-            return;
-        };
+    async fn set(&self, key: &str, value: &str, ttl: Duration) -> Result<(), CacheError> {
+        let mut conn = self.get_conn().await?;
+        conn.set_ex(key, value, ttl.as_secs())
+            .await
+            .map_err(backend_error)
+    }
 
-        let result: Result<i64, _> = conn.del(key).await;
-        if let Err(e) = result {
-            tracing::error!(key = %key, error = %e, "Redis DEL command failed");
-        } else {
-            tracing::debug!(key = %key, "Cache key deleted");
-        }
+    async fn delete(&self, key: &str) -> Result<(), CacheError> {
+        let mut conn = self.get_conn().await?;
+        let _: i64 = conn.del(key).await.map_err(backend_error)?;
+        Ok(())
     }
 
-    pub async fn delete_pattern(&self, pattern: &str) {
-        let Some(mut conn) = self.get_conn().await else {
-            return;
-        };
+    async fn delete_namespace(&self, namespace: CacheNamespace) -> Result<u64, CacheError> {
+        let mut conn = self.get_conn().await?;
+        let keys: Vec<String> = conn
+            .keys(namespace.pattern())
+            .await
+            .map_err(backend_error)?;
+        if keys.is_empty() {
+            return Ok(0);
+        }
+        conn.del(&keys).await.map_err(backend_error)
+    }
+}
 
-        let keys: Result<Vec<String>, _> = conn.keys(pattern).await;
-        match keys {
-            Ok(keys) if !keys.is_empty() => {
-                let result: Result<i64, _> = conn.del(&keys).await;
-                match result {
-                    Ok(count) => {
-                        tracing::debug!(pattern = %pattern, count = count, "Cache pattern deleted");
-                    }
-                    Err(e) => {
-                        tracing::error!(pattern = %pattern, error = %e, "Redis DEL command failed for pattern keys");
-                    }
+/// Drop everything this service cached before an outage.
+/// Invalidations issued while Redis was unreachable were lost, so surviving entries may be stale.
+async fn purge_after_outage(conn: &mut PooledConnection) {
+    let pattern = keys::all_pattern();
+    let keys: Result<Vec<String>, _> = conn.keys(&pattern).await;
+    match keys {
+        Ok(keys) if !keys.is_empty() => {
+            let result: Result<i64, _> = conn.del(&keys).await;
+            match result {
+                Ok(count) => {
+                    tracing::info!(
+                        count = count,
+                        "Purged cache entries written before Redis outage"
+                    );
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Failed to purge cache entries after Redis outage");
                 }
-            }
-            Ok(_) => {
-                tracing::debug!(pattern = %pattern, "No keys matched pattern");
-            }
-            Err(e) => {
-                tracing::error!(pattern = %pattern, error = %e, "Redis KEYS command failed");
             }
         }
+        Ok(_) => {}
+        Err(e) => {
+            tracing::error!(error = %e, "Redis KEYS command failed while purging after outage");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconnect_backoff_grows_exponentially() {
+        let min = Duration::from_millis(500);
+        let max = Duration::from_secs(30);
+        assert_eq!(reconnect_backoff(1, min, max), Duration::from_millis(500));
+        assert_eq!(reconnect_backoff(2, min, max), Duration::from_millis(1000));
+        assert_eq!(reconnect_backoff(3, min, max), Duration::from_millis(2000));
+    }
+
+    #[test]
+    fn test_reconnect_backoff_is_capped() {
+        let min = Duration::from_millis(500);
+        let max = Duration::from_secs(30);
+        assert_eq!(reconnect_backoff(10, min, max), max);
+        assert_eq!(reconnect_backoff(u32::MAX, min, max), max);
+    }
+
+    #[test]
+    fn test_connection_addr_parses_host_and_port() {
+        assert_eq!(
+            connection_addr("sentinel-1:26380", DEFAULT_SENTINEL_PORT, false),
+            ConnectionAddr::Tcp("sentinel-1".to_string(), 26380)
+        );
+        assert_eq!(
+            connection_addr("sentinel-2", DEFAULT_SENTINEL_PORT, false),
+            ConnectionAddr::Tcp("sentinel-2".to_string(), DEFAULT_SENTINEL_PORT)
+        );
     }
 }
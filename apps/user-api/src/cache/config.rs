@@ -1,26 +1,110 @@
 use crate::constants::{
-    CACHE_ENABLED, CACHE_LIST_TTL_SECS, CACHE_ROLE_TTL_SECS, CACHE_USER_TTL_SECS, REDIS_DB,
-    REDIS_HOST, REDIS_PORT,
+    CACHE_BACKEND, CACHE_ENABLED, CACHE_LIST_TTL_SECS, CACHE_MEMORY_MAX_ENTRIES,
+    CACHE_NEGATIVE_TTL_SECS, CACHE_ROLE_TTL_SECS, CACHE_USER_TTL_SECS, CACHE_WARMUP_CONCURRENCY,
+    CACHE_WARMUP_DEADLINE_SECS, CACHE_WARMUP_ENABLED, CACHE_WARMUP_USER_PAGES,
+    REDIS_CONNECT_TIMEOUT_MS, REDIS_DB, REDIS_HOST, REDIS_PASSWORD, REDIS_PORT,
+    REDIS_RECONNECT_MAX_BACKOFF_MS, REDIS_RECONNECT_MIN_BACKOFF_MS, REDIS_SENTINEL_MASTER,
+    REDIS_SENTINEL_NODES, REDIS_TLS, REDIS_USERNAME,
 };
+use secrecy::Secret;
+use secrets::SecretsClient;
 use std::time::Duration;
 
+const DEFAULT_SENTINEL_MASTER: &str = "mymaster";
+
+/// Which store backs the cache
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CacheBackendKind {
+    Redis,
+    /// Process-local map; only suitable for a single instance
+    Memory,
+}
+
+impl CacheBackendKind {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "redis" => Some(CacheBackendKind::Redis),
+            "memory" => Some(CacheBackendKind::Memory),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CacheBackendKind::Redis => "redis",
+            CacheBackendKind::Memory => "memory",
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct CacheConfig {
     pub enabled: bool,
+    pub backend: CacheBackendKind,
+    /// Upper bound on entries held by the in-memory backend
+    pub memory_max_entries: usize,
     pub redis_host: String,
     pub redis_port: u16,
     pub redis_db: i64,
+    /// ACL username (Redis 6+), if the server requires one
+    pub redis_username: Option<String>,
+    pub redis_password: Option<Secret<String>>,
+    /// Connect to Redis (and Sentinel nodes) over TLS
+    pub redis_tls: bool,
+    /// Sentinel nodes as `host:port`; when non-empty, the master is discovered through them
+    /// and `redis_host`/`redis_port` are ignored
+    pub redis_sentinel_nodes: Vec<String>,
+    pub redis_sentinel_master: String,
+    /// Upper bound for creating or waiting for a pooled connection
+    pub connect_timeout: Duration,
+    /// Delay before the first reconnect attempt after Redis becomes unreachable
+    pub reconnect_min_backoff: Duration,
+    /// Maximum delay between reconnect attempts
+    pub reconnect_max_backoff: Duration,
     pub user_ttl: Duration,
     pub role_ttl: Duration,
     pub list_ttl: Duration,
+    /// TTL for negative entries (lookups of IDs that do not exist)
+    pub negative_ttl: Duration,
+    /// Preload roles, the first user pages and their Keycloak profiles at startup
+    pub warmup_enabled: bool,
+    /// Number of user list pages (default page size) to preload
+    pub warmup_user_pages: u32,
+    /// Maximum user pages warmed in parallel
+    pub warmup_concurrency: usize,
+    /// How long startup waits for warm-up before serving; the rest continues in the background
+    pub warmup_deadline: Duration,
 }
 
 impl CacheConfig {
+    /// Load cache configuration, resolving the Redis password through the secrets client
+    pub async fn from_secrets(secrets: &SecretsClient) -> Self {
+        let mut config = Self::from_env();
+        config.redis_password = secrets
+            .get_secret_optional(REDIS_PASSWORD)
+            .await
+            .or(config.redis_password);
+        config
+    }
+
     pub fn from_env() -> Self {
         let enabled = std::env::var(CACHE_ENABLED)
             .map(|v| v.to_lowercase() == "true")
             .unwrap_or(false);
 
+        let backend = match std::env::var(CACHE_BACKEND) {
+            Ok(v) => CacheBackendKind::parse(&v).unwrap_or_else(|| {
+                tracing::warn!(value = %v, "unknown {CACHE_BACKEND}, using redis");
+                CacheBackendKind::Redis
+            }),
+            Err(_) => CacheBackendKind::Redis,
+        };
+
+        let memory_max_entries = std::env::var(CACHE_MEMORY_MAX_ENTRIES)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(10_000);
+
         let redis_host = std::env::var(REDIS_HOST).unwrap_or_else(|_| "localhost".to_string());
 
         let redis_port = std::env::var(REDIS_PORT)
@@ -33,6 +117,45 @@ impl CacheConfig {
             .and_then(|v| v.parse().ok())
             .unwrap_or(0);
 
+        let redis_username = std::env::var(REDIS_USERNAME).ok().filter(|v| !v.is_empty());
+
+        let redis_password = std::env::var(REDIS_PASSWORD)
+            .ok()
+            .filter(|v| !v.is_empty())
+            .map(Secret::new);
+
+        let redis_tls = std::env::var(REDIS_TLS)
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false);
+
+        let redis_sentinel_nodes = std::env::var(REDIS_SENTINEL_NODES)
+            .ok()
+            .map(|v| {
+                v.split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let redis_sentinel_master = std::env::var(REDIS_SENTINEL_MASTER)
+            .unwrap_or_else(|_| DEFAULT_SENTINEL_MASTER.to_string());
+
+        let connect_timeout_ms = std::env::var(REDIS_CONNECT_TIMEOUT_MS)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1_000);
+
+        let reconnect_min_backoff_ms = std::env::var(REDIS_RECONNECT_MIN_BACKOFF_MS)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(500);
+
+        let reconnect_max_backoff_ms = std::env::var(REDIS_RECONNECT_MAX_BACKOFF_MS)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(30_000);
+
         let user_ttl_secs = std::env::var(CACHE_USER_TTL_SECS)
             .ok()
             .and_then(|v| v.parse().ok())
@@ -48,21 +171,58 @@ impl CacheConfig {
             .and_then(|v| v.parse().ok())
             .unwrap_or(60);
 
+        let negative_ttl_secs = std::env::var(CACHE_NEGATIVE_TTL_SECS)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(30);
+
+        let warmup_enabled = std::env::var(CACHE_WARMUP_ENABLED)
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false);
+
+        let warmup_user_pages = std::env::var(CACHE_WARMUP_USER_PAGES)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(5);
+
+        let warmup_concurrency = std::env::var(CACHE_WARMUP_CONCURRENCY)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(4);
+
+        let warmup_deadline_secs = std::env::var(CACHE_WARMUP_DEADLINE_SECS)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(10);
+
         Self {
             enabled,
+            backend,
+            memory_max_entries,
             redis_host,
             redis_port,
             redis_db,
+            redis_username,
+            redis_password,
+            redis_tls,
+            redis_sentinel_nodes,
+            redis_sentinel_master,
+            connect_timeout: Duration::from_millis(connect_timeout_ms),
+            reconnect_min_backoff: Duration::from_millis(reconnect_min_backoff_ms),
+            reconnect_max_backoff: Duration::from_millis(reconnect_max_backoff_ms),
             user_ttl: Duration::from_secs(user_ttl_secs),
             role_ttl: Duration::from_secs(role_ttl_secs),
             list_ttl: Duration::from_secs(list_ttl_secs),
+            negative_ttl: Duration::from_secs(negative_ttl_secs),
+            warmup_enabled,
+            warmup_user_pages,
+            warmup_concurrency,
+            warmup_deadline: Duration::from_secs(warmup_deadline_secs),
         }
     }
 
-    pub fn redis_url(&self) -> String {
-        format!(
-            "redis://{}:{}/{}",
-            self.redis_host, self.redis_port, self.redis_db
-        )
+    pub fn uses_sentinel(&self) -> bool {
+        !self.redis_sentinel_nodes.is_empty()
     }
 }
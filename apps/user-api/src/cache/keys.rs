@@ -1,21 +1,39 @@
+//! Cache key layout. Every key that holds tenant data carries the tenant right after its
+//! namespace segment (`user-api:user:{tenant}:{id}`), so tenants never share an entry.
+//! Namespace flushes still span all tenants: they over-invalidate but never serve stale data.
+
+use user_lib::entities::TenantId;
 use uuid::Uuid;
 
 const PREFIX: &str = "user-api";
 
-pub fn user_key(user_id: Uuid) -> String {
-    format!("{PREFIX}:user:{user_id}")
+/// Stored idempotent responses live outside `PREFIX`, so namespace flushes and the
+/// purge after a Redis outage never make a retried request run twice
+const IDEMPOTENCY_PREFIX: &str = "user-api-idempotency";
+
+pub fn user_key(tenant: TenantId, user_id: Uuid) -> String {
+    format!("{PREFIX}:user:{tenant}:{user_id}")
+}
+
+pub fn users_list_key(tenant: TenantId, page: u32, page_size: u32) -> String {
+    format!("{PREFIX}:users:{tenant}:page:{page}:size:{page_size}")
+}
+
+pub fn role_key(tenant: TenantId, role_id: Uuid) -> String {
+    format!("{PREFIX}:role:{tenant}:{role_id}")
 }
 
-pub fn users_list_key(page: u32, page_size: u32) -> String {
-    format!("{PREFIX}:users:page:{page}:size:{page_size}")
+pub fn roles_list_key(tenant: TenantId, page: u32, page_size: u32) -> String {
+    format!("{PREFIX}:roles:{tenant}:page:{page}:size:{page_size}")
 }
 
-pub fn role_key(role_id: Uuid) -> String {
-    format!("{PREFIX}:role:{role_id}")
+/// `key` is already scoped to its tenant by the idempotency middleware
+pub fn idempotency_key(key: &str) -> String {
+    format!("{IDEMPOTENCY_PREFIX}:{key}")
 }
 
-pub fn roles_list_key(page: u32, page_size: u32) -> String {
-    format!("{PREFIX}:roles:page:{page}:size:{page_size}")
+pub fn all_pattern() -> String {
+    format!("{PREFIX}:*")
 }
 
 pub fn users_pattern() -> String {
@@ -30,12 +48,136 @@ pub fn roles_pattern() -> String {
     format!("{PREFIX}:roles:*")
 }
 
-#[allow(dead_code)]
-pub fn keycloak_profile_key(keycloak_id: &str) -> String {
-    format!("{PREFIX}:kc:profile:{keycloak_id}")
+pub fn role_pattern() -> String {
+    format!("{PREFIX}:role:*")
+}
+
+pub fn keycloak_profile_key(tenant: TenantId, keycloak_id: &str) -> String {
+    format!("{PREFIX}:kc:profile:{tenant}:{keycloak_id}")
 }
 
-#[allow(dead_code)]
 pub fn keycloak_profiles_pattern() -> String {
     format!("{PREFIX}:kc:*")
 }
+
+/// Top-level key segment under `PREFIX`, used to group stats and bulk invalidation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheNamespace {
+    User,
+    Users,
+    Role,
+    Roles,
+    Keycloak,
+}
+
+impl CacheNamespace {
+    pub const ALL: [CacheNamespace; 5] = [
+        CacheNamespace::User,
+        CacheNamespace::Users,
+        CacheNamespace::Role,
+        CacheNamespace::Roles,
+        CacheNamespace::Keycloak,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CacheNamespace::User => "user",
+            CacheNamespace::Users => "users",
+            CacheNamespace::Role => "role",
+            CacheNamespace::Roles => "roles",
+            CacheNamespace::Keycloak => "kc",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|ns| ns.as_str() == value)
+    }
+
+    /// Namespace a full cache key belongs to, if it was built by this module
+    pub fn of_key(key: &str) -> Option<Self> {
+        let rest = key.strip_prefix(PREFIX)?.strip_prefix(':')?;
+        let segment = rest.split(':').next()?;
+        Self::parse(segment)
+    }
+
+    pub fn pattern(self) -> String {
+        match self {
+            CacheNamespace::User => user_pattern(),
+            CacheNamespace::Users => users_pattern(),
+            CacheNamespace::Role => role_pattern(),
+            CacheNamespace::Roles => roles_pattern(),
+            CacheNamespace::Keycloak => keycloak_profiles_pattern(),
+        }
+    }
+
+    pub(crate) fn index(self) -> usize {
+        self as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_namespace_parse_round_trip() {
+        for ns in CacheNamespace::ALL {
+            assert_eq!(CacheNamespace::parse(ns.as_str()), Some(ns));
+        }
+        assert_eq!(CacheNamespace::parse("sessions"), None);
+    }
+
+    #[test]
+    fn test_namespace_of_key() {
+        let id = Uuid::new_v4();
+        let tenant = TenantId::DEFAULT;
+        assert_eq!(
+            CacheNamespace::of_key(&user_key(tenant, id)),
+            Some(CacheNamespace::User)
+        );
+        assert_eq!(
+            CacheNamespace::of_key(&users_list_key(tenant, 1, 20)),
+            Some(CacheNamespace::Users)
+        );
+        assert_eq!(
+            CacheNamespace::of_key(&role_key(tenant, id)),
+            Some(CacheNamespace::Role)
+        );
+        assert_eq!(
+            CacheNamespace::of_key(&roles_list_key(tenant, 1, 20)),
+            Some(CacheNamespace::Roles)
+        );
+        assert_eq!(
+            CacheNamespace::of_key(&keycloak_profile_key(tenant, "kc-1")),
+            Some(CacheNamespace::Keycloak)
+        );
+        assert_eq!(CacheNamespace::of_key("other-app:user:1"), None);
+        assert_eq!(CacheNamespace::of_key(&idempotency_key("abc")), None);
+    }
+
+    #[test]
+    fn test_keys_differ_between_tenants() {
+        let id = Uuid::new_v4();
+        let a = TenantId::DEFAULT;
+        let b = TenantId(Uuid::new_v4());
+        assert_ne!(user_key(a, id), user_key(b, id));
+        assert_ne!(users_list_key(a, 1, 20), users_list_key(b, 1, 20));
+        assert_ne!(role_key(a, id), role_key(b, id));
+        assert_ne!(roles_list_key(a, 1, 20), roles_list_key(b, 1, 20));
+        assert_ne!(
+            keycloak_profile_key(a, "kc-1"),
+            keycloak_profile_key(b, "kc-1")
+        );
+    }
+
+    #[test]
+    fn test_namespace_patterns_match_their_keys() {
+        let id = Uuid::new_v4();
+        let user = user_key(TenantId::DEFAULT, id);
+        let users = users_list_key(TenantId::DEFAULT, 1, 20);
+        // `user:*` must not sweep list pages and vice versa
+        assert!(user.starts_with(CacheNamespace::User.pattern().trim_end_matches('*')));
+        assert!(!users.starts_with(CacheNamespace::User.pattern().trim_end_matches('*')));
+        assert!(users.starts_with(CacheNamespace::Users.pattern().trim_end_matches('*')));
+    }
+}
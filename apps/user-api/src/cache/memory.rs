@@ -0,0 +1,187 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::backend::{CacheBackend, CacheError};
+use super::keys::CacheNamespace;
+
+struct Entry {
+    value: String,
+    expires_at: Instant,
+}
+
+/// Process-local cache backend for tests and single-instance deployments without Redis.
+/// Entries are not shared between replicas, so invalidations only reach this process.
+pub struct InMemoryCache {
+    entries: Mutex<HashMap<String, Entry>>,
+    max_entries: usize,
+}
+
+impl InMemoryCache {
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            max_entries: max_entries.max(1),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Entry>> {
+        // A panic while holding the lock cannot leave the map half-updated
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn live_value(entries: &HashMap<String, Entry>, key: &str, now: Instant) -> Option<String> {
+        entries
+            .get(key)
+            .filter(|e| e.expires_at > now)
+            .map(|e| e.value.clone())
+    }
+
+    fn insert(
+        &self,
+        entries: &mut HashMap<String, Entry>,
+        key: &str,
+        value: &str,
+        ttl: Duration,
+        now: Instant,
+    ) {
+        if entries.len() >= self.max_entries && !entries.contains_key(key) {
+            entries.retain(|_, e| e.expires_at > now);
+            if entries.len() >= self.max_entries {
+                // Still full: drop the entry closest to expiry
+                if let Some(oldest) = entries
+                    .iter()
+                    .min_by_key(|(_, e)| e.expires_at)
+                    .map(|(k, _)| k.clone())
+                {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+
+        entries.insert(
+            key.to_string(),
+            Entry {
+                value: value.to_string(),
+                expires_at: now + ttl,
+            },
+        );
+    }
+}
+
+impl std::fmt::Debug for InMemoryCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InMemoryCache")
+            .field("entries", &self.lock().len())
+            .field("max_entries", &self.max_entries)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl CacheBackend for InMemoryCache {
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<String>, CacheError> {
+        Ok(Self::live_value(&self.lock(), key, Instant::now()))
+    }
+
+    async fn mget(&self, keys: &[String]) -> Result<Vec<Option<String>>, CacheError> {
+        let entries = self.lock();
+        let now = Instant::now();
+        Ok(keys
+            .iter()
+            .map(|key| Self::live_value(&entries, key, now))
+            .collect())
+    }
+
+    async fn set(&self, key: &str, value: &str, ttl: Duration) -> Result<(), CacheError> {
+        self.insert(&mut self.lock(), key, value, ttl, Instant::now());
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), CacheError> {
+        self.lock().remove(key);
+        Ok(())
+    }
+
+    async fn delete_namespace(&self, namespace: CacheNamespace) -> Result<u64, CacheError> {
+        let mut entries = self.lock();
+        let before = entries.len();
+        entries.retain(|key, _| CacheNamespace::of_key(key) != Some(namespace));
+        Ok((before - entries.len()) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::keys;
+    use user_lib::entities::TenantId;
+    use uuid::Uuid;
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    #[tokio::test]
+    async fn test_set_get_delete() {
+        let cache = InMemoryCache::new(10);
+        cache.set("k", "v", TTL).await.unwrap();
+        assert_eq!(cache.get("k").await.unwrap().as_deref(), Some("v"));
+
+        cache.delete("k").await.unwrap();
+        assert_eq!(cache.get("k").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_expired_entries_are_not_returned() {
+        let cache = InMemoryCache::new(10);
+        cache.set("k", "v", Duration::ZERO).await.unwrap();
+        assert_eq!(cache.get("k").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_mget_preserves_order() {
+        let cache = InMemoryCache::new(10);
+        cache.set("a", "1", TTL).await.unwrap();
+        cache.set("c", "3", TTL).await.unwrap();
+
+        let keys = ["a", "b", "c"].map(String::from);
+        assert_eq!(
+            cache.mget(&keys).await.unwrap(),
+            vec![Some("1".to_string()), None, Some("3".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_namespace_only_touches_that_namespace() {
+        let cache = InMemoryCache::new(10);
+        let user = keys::user_key(TenantId::DEFAULT, Uuid::new_v4());
+        let page = keys::users_list_key(TenantId::DEFAULT, 1, 20);
+        cache.set(&user, "u", TTL).await.unwrap();
+        cache.set(&page, "p", TTL).await.unwrap();
+
+        let deleted = cache.delete_namespace(CacheNamespace::Users).await.unwrap();
+        assert_eq!(deleted, 1);
+        assert!(cache.get(&user).await.unwrap().is_some());
+        assert!(cache.get(&page).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_capacity_is_bounded() {
+        let cache = InMemoryCache::new(2);
+        cache.set("a", "1", TTL).await.unwrap();
+        cache
+            .set("b", "2", TTL + Duration::from_secs(1))
+            .await
+            .unwrap();
+        cache.set("c", "3", TTL).await.unwrap();
+
+        assert_eq!(cache.lock().len(), 2);
+        // "a" expired soonest, so it was evicted
+        assert_eq!(cache.get("a").await.unwrap(), None);
+        assert!(cache.get("c").await.unwrap().is_some());
+    }
+}
@@ -1,8 +1,16 @@
+mod backend;
 mod client;
 mod config;
-mod keys;
+pub mod keys;
+mod memory;
 mod service;
+mod stats;
+mod store;
 
-pub use client::RedisCache;
+pub use backend::CacheBackend;
 pub use config::CacheConfig;
+pub use keys::CacheNamespace;
+pub use memory::InMemoryCache;
 pub use service::CachedUserService;
+pub use stats::NamespaceStats;
+pub use store::{Cache, CacheLookup};
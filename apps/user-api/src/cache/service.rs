@@ -1,16 +1,20 @@
+use chrono::{DateTime, Utc};
 use std::sync::Arc;
 use uuid::Uuid;
 
-use user_lib::entities::{PaginatedResult, PaginationParams, Role, User};
+use user_lib::entities::{
+    ApiKey, Group, NewApiKey, PaginatedResult, PaginationParams, Role, RoleGrant, Tenant, TenantId,
+    User, UserAttributesPatch, UserFilter,
+};
 use user_lib::errors_service::UserServiceError;
 use user_lib::repository::traits::{
     RoleRepositoryTrait, UserRepositoryTrait, UserRoleRepositoryTrait,
 };
 use user_lib::user_service::UserService;
 
-use super::client::RedisCache;
 use super::config::CacheConfig;
-use super::keys;
+use super::keys::{self, CacheNamespace};
+use super::store::{Cache, CacheLookup};
 
 #[derive(Clone, Debug)]
 pub struct CachedUserService<U, R, UR>
@@ -20,7 +24,7 @@ where
     UR: UserRoleRepositoryTrait + Send + Sync + 'static,
 {
     inner: Arc<UserService<U, R, UR>>,
-    cache: RedisCache,
+    cache: Cache,
     config: CacheConfig,
 }
 
@@ -30,7 +34,7 @@ where
     R: RoleRepositoryTrait + Send + Sync + 'static,
     UR: UserRoleRepositoryTrait + Send + Sync + 'static,
 {
-    pub fn new(inner: Arc<UserService<U, R, UR>>, cache: RedisCache, config: CacheConfig) -> Self {
+    pub fn new(inner: Arc<UserService<U, R, UR>>, cache: Cache, config: CacheConfig) -> Self {
         Self {
             inner,
             cache,
@@ -39,58 +43,85 @@ where
     }
 
     #[allow(dead_code)]
-    pub fn cache(&self) -> &RedisCache {
+    pub fn cache(&self) -> &Cache {
         &self.cache
     }
 
-    #[allow(dead_code)]
+    /// Whether deletes are soft, so deleted users and roles can be restored
+    pub fn soft_delete(&self) -> bool {
+        self.inner.soft_delete
+    }
+
     pub fn config(&self) -> &CacheConfig {
         &self.config
     }
 
     // ========== User Read Operations ==========
 
-    pub async fn get_user(&self, user_id: Uuid) -> Result<Option<User>, UserServiceError> {
+    pub async fn get_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<Option<User>, UserServiceError> {
         if !self.cache.is_enabled() {
-            return self.inner.get_user(user_id).await;
+            return self.inner.get_user(tenant, user_id).await;
         }
 
-        let cache_key = keys::user_key(user_id);
+        let cache_key = keys::user_key(tenant, user_id);
 
         // Try cache first
-        if let Some(user) = self.cache.get::<User>(&cache_key).await {
-            return Ok(Some(user));
+        match self.cache.lookup::<User>(&cache_key).await {
+            CacheLookup::Hit(user) => return Ok(Some(user)),
+            CacheLookup::Negative => return Ok(None),
+            CacheLookup::Miss => {}
         }
 
         // Cache miss - fetch from DB
-        let result = self.inner.get_user(user_id).await?;
-
-        // Cache the result if found
-        if let Some(ref user) = result {
-            self.cache.set(&cache_key, user, self.config.user_ttl).await;
+        let result = self.inner.get_user(tenant, user_id).await?;
+
+        // Cache the result, remembering missing users for a short time
+        match result {
+            Some(ref user) => self.cache.set(&cache_key, user, self.config.user_ttl).await,
+            None => {
+                self.cache
+                    .set_negative(&cache_key, self.config.negative_ttl)
+                    .await
+            }
         }
 
         Ok(result)
     }
 
-    #[allow(dead_code)]
     pub async fn get_user_by_keycloak_id(
         &self,
+        tenant: TenantId,
         keycloak_id: &str,
     ) -> Result<Option<User>, UserServiceError> {
         // No caching for keycloak_id lookups - they're used for syncing
-        self.inner.get_user_by_keycloak_id(keycloak_id).await
+        self.inner
+            .get_user_by_keycloak_id(tenant, keycloak_id)
+            .await
+    }
+
+    pub async fn get_user_tenant_by_keycloak_id(
+        &self,
+        keycloak_id: &str,
+    ) -> Result<Option<TenantId>, UserServiceError> {
+        self.inner.get_user_tenant_by_keycloak_id(keycloak_id).await
     }
 
     pub async fn get_users(
         &self,
+        tenant: TenantId,
         pagination: PaginationParams,
+        filter: &UserFilter,
     ) -> Result<PaginatedResult<User>, UserServiceError> {
-        if !self.cache.is_enabled() {
-            return self.inner.get_users(pagination).await;
+        // Filtered pages are not cached: their keys would multiply with every filter value
+        if !self.cache.is_enabled() || !filter.is_empty() {
+            return self.inner.get_users(tenant, pagination, filter).await;
         }
 
-        let cache_key = keys::users_list_key(pagination.page, pagination.page_size);
+        let cache_key = keys::users_list_key(tenant, pagination.page, pagination.page_size);
 
         // Try cache first
         if let Some(result) = self.cache.get::<PaginatedResult<User>>(&cache_key).await {
@@ -98,7 +129,7 @@ where
         }
 
         // Cache miss - fetch from DB
-        let result = self.inner.get_users(pagination).await?;
+        let result = self.inner.get_users(tenant, pagination, filter).await?;
 
         // Cache the result
         self.cache
@@ -110,49 +141,241 @@ where
 
     // ========== User Write Operations ==========
 
-    pub async fn create_user(&self, keycloak_id: &str) -> Result<User, UserServiceError> {
-        let user = self.inner.create_user(keycloak_id).await?;
+    pub async fn create_user(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+    ) -> Result<User, UserServiceError> {
+        let user = self.inner.create_user(tenant, keycloak_id).await?;
 
-        // Invalidate users list cache
+        // Invalidate any negative entry for the new user and the users list cache
         if self.cache.is_enabled() {
-            self.cache.delete_pattern(&keys::users_pattern()).await;
+            self.cache.delete(&keys::user_key(tenant, user.id)).await;
+            self.cache.delete_namespace(CacheNamespace::Users).await;
         }
 
         Ok(user)
     }
 
-    pub async fn delete_user(&self, user_id: Uuid) -> Result<(), UserServiceError> {
-        self.inner.delete_user(user_id).await?;
+    pub async fn delete_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<(), UserServiceError> {
+        self.inner
+            .delete_user(tenant, user_id, expected_version)
+            .await?;
 
         // Invalidate specific user and users list cache
         if self.cache.is_enabled() {
-            self.cache.delete(&keys::user_key(user_id)).await;
-            self.cache.delete_pattern(&keys::users_pattern()).await;
+            self.cache.delete(&keys::user_key(tenant, user_id)).await;
+            self.cache.delete_namespace(CacheNamespace::Users).await;
         }
 
         Ok(())
     }
 
+    /// Soft-delete a user regardless of the soft delete setting
+    pub async fn soft_delete_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<bool, UserServiceError> {
+        let deleted = self
+            .inner
+            .soft_delete_user(tenant, user_id, expected_version)
+            .await?;
+
+        if self.cache.is_enabled() {
+            self.cache.delete(&keys::user_key(tenant, user_id)).await;
+            self.cache.delete_namespace(CacheNamespace::Users).await;
+        }
+
+        Ok(deleted)
+    }
+
+    /// Permanently remove a user regardless of the soft delete setting
+    pub async fn purge_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<(), UserServiceError> {
+        self.inner.purge_user(tenant, user_id).await?;
+
+        if self.cache.is_enabled() {
+            self.cache.delete(&keys::user_key(tenant, user_id)).await;
+            self.cache.delete_namespace(CacheNamespace::Users).await;
+        }
+
+        Ok(())
+    }
+
+    pub async fn record_user_login(
+        &self,
+        tenant: TenantId,
+        user: &User,
+        at: DateTime<Utc>,
+    ) -> Result<(), UserServiceError> {
+        self.inner
+            .record_user_login(tenant, &user.keycloak_id, at)
+            .await?;
+
+        // Only the user's own entry: logins are too frequent to drop the list pages on
+        // each one, and they catch up within their TTL
+        if self.cache.is_enabled() {
+            self.cache.delete(&keys::user_key(tenant, user.id)).await;
+        }
+
+        Ok(())
+    }
+
+    pub async fn touch_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<User, UserServiceError> {
+        let user = self
+            .inner
+            .touch_user(tenant, user_id, expected_version)
+            .await?;
+
+        // The cached record carries the old version
+        if self.cache.is_enabled() {
+            self.cache.delete(&keys::user_key(tenant, user_id)).await;
+            self.cache.delete_namespace(CacheNamespace::Users).await;
+        }
+
+        Ok(user)
+    }
+
+    pub async fn update_user_attributes(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        patch: &UserAttributesPatch,
+        expected_version: Option<i64>,
+    ) -> Result<User, UserServiceError> {
+        let user = self
+            .inner
+            .update_user_attributes(tenant, user_id, patch, expected_version)
+            .await?;
+
+        if self.cache.is_enabled() {
+            self.cache.delete(&keys::user_key(tenant, user_id)).await;
+            self.cache.delete_namespace(CacheNamespace::Users).await;
+        }
+
+        Ok(user)
+    }
+
+    pub async fn suspend_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        reason: Option<&str>,
+        expected_version: Option<i64>,
+    ) -> Result<User, UserServiceError> {
+        let user = self
+            .inner
+            .suspend_user(tenant, user_id, reason, expected_version)
+            .await?;
+
+        if self.cache.is_enabled() {
+            self.cache.delete(&keys::user_key(tenant, user_id)).await;
+            self.cache.delete_namespace(CacheNamespace::Users).await;
+        }
+
+        Ok(user)
+    }
+
+    pub async fn reactivate_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<User, UserServiceError> {
+        let user = self
+            .inner
+            .reactivate_user(tenant, user_id, expected_version)
+            .await?;
+
+        if self.cache.is_enabled() {
+            self.cache.delete(&keys::user_key(tenant, user_id)).await;
+            self.cache.delete_namespace(CacheNamespace::Users).await;
+        }
+
+        Ok(user)
+    }
+
+    pub async fn restore_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<User, UserServiceError> {
+        let user = self.inner.restore_user(tenant, user_id).await?;
+
+        // Drop the negative entry cached while the user was deleted, and the list pages
+        if self.cache.is_enabled() {
+            self.cache.delete(&keys::user_key(tenant, user_id)).await;
+            self.cache.delete_namespace(CacheNamespace::Users).await;
+        }
+
+        Ok(user)
+    }
+
+    /// Drop a user's cached entry and the users list pages, returning the user's
+    /// Keycloak ID (read from the database) so dependent entries can be evicted too
+    pub async fn evict_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<Option<String>, UserServiceError> {
+        if self.cache.is_enabled() {
+            self.cache.delete(&keys::user_key(tenant, user_id)).await;
+            self.cache.delete_namespace(CacheNamespace::Users).await;
+        }
+
+        Ok(self
+            .inner
+            .get_user(tenant, user_id)
+            .await?
+            .map(|user| user.keycloak_id))
+    }
+
     // ========== Role Read Operations ==========
 
-    pub async fn get_role(&self, role_id: Uuid) -> Result<Option<Role>, UserServiceError> {
+    pub async fn get_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+    ) -> Result<Option<Role>, UserServiceError> {
         if !self.cache.is_enabled() {
-            return self.inner.get_role(role_id).await;
+            return self.inner.get_role(tenant, role_id).await;
         }
 
-        let cache_key = keys::role_key(role_id);
+        let cache_key = keys::role_key(tenant, role_id);
 
         // Try cache first
-        if let Some(role) = self.cache.get::<Role>(&cache_key).await {
-            return Ok(Some(role));
+        match self.cache.lookup::<Role>(&cache_key).await {
+            CacheLookup::Hit(role) => return Ok(Some(role)),
+            CacheLookup::Negative => return Ok(None),
+            CacheLookup::Miss => {}
         }
 
         // Cache miss - fetch from DB
-        let result = self.inner.get_role(role_id).await?;
-
-        // Cache the result if found
-        if let Some(ref role) = result {
-            self.cache.set(&cache_key, role, self.config.role_ttl).await;
+        let result = self.inner.get_role(tenant, role_id).await?;
+
+        // Cache the result, remembering missing roles for a short time
+        match result {
+            Some(ref role) => self.cache.set(&cache_key, role, self.config.role_ttl).await,
+            None => {
+                self.cache
+                    .set_negative(&cache_key, self.config.negative_ttl)
+                    .await
+            }
         }
 
         Ok(result)
@@ -160,13 +383,14 @@ where
 
     pub async fn get_roles(
         &self,
+        tenant: TenantId,
         pagination: PaginationParams,
     ) -> Result<PaginatedResult<Role>, UserServiceError> {
         if !self.cache.is_enabled() {
-            return self.inner.get_roles(pagination).await;
+            return self.inner.get_roles(tenant, pagination).await;
         }
 
-        let cache_key = keys::roles_list_key(pagination.page, pagination.page_size);
+        let cache_key = keys::roles_list_key(tenant, pagination.page, pagination.page_size);
 
         // Try cache first
         if let Some(result) = self.cache.get::<PaginatedResult<Role>>(&cache_key).await {
@@ -174,7 +398,7 @@ where
         }
 
         // Cache miss - fetch from DB
-        let result = self.inner.get_roles(pagination).await?;
+        let result = self.inner.get_roles(tenant, pagination).await?;
 
         // Cache the result
         self.cache
@@ -186,56 +410,105 @@ where
 
     // ========== Role Write Operations ==========
 
-    pub async fn create_role(&self, name: &str) -> Result<Role, UserServiceError> {
-        let role = self.inner.create_role(name).await?;
+    pub async fn create_role(
+        &self,
+        tenant: TenantId,
+        name: &str,
+        description: Option<&str>,
+    ) -> Result<Role, UserServiceError> {
+        let role = self.inner.create_role(tenant, name, description).await?;
 
-        // Invalidate roles list cache
+        // Invalidate any negative entry for the new role and the roles list cache
         if self.cache.is_enabled() {
-            self.cache.delete_pattern(&keys::roles_pattern()).await;
+            self.cache.delete(&keys::role_key(tenant, role.id)).await;
+            self.cache.delete_namespace(CacheNamespace::Roles).await;
         }
 
         Ok(role)
     }
 
-    pub async fn update_role(&self, role_id: Uuid, name: &str) -> Result<Role, UserServiceError> {
-        let role = self.inner.update_role(role_id, name).await?;
+    pub async fn update_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+        name: &str,
+        description: Option<&str>,
+        expected_version: Option<i64>,
+    ) -> Result<Role, UserServiceError> {
+        let role = self
+            .inner
+            .update_role(tenant, role_id, name, description, expected_version)
+            .await?;
 
         // Invalidate role-related caches (role changes affect users who have this role)
         if self.cache.is_enabled() {
-            self.cache.delete(&keys::role_key(role_id)).await;
-            self.cache.delete_pattern(&keys::roles_pattern()).await;
+            self.cache.delete(&keys::role_key(tenant, role_id)).await;
+            self.cache.delete_namespace(CacheNamespace::Roles).await;
             // User caches might contain stale role data
-            self.cache.delete_pattern(&keys::user_pattern()).await;
-            self.cache.delete_pattern(&keys::users_pattern()).await;
+            self.cache.delete_namespace(CacheNamespace::User).await;
+            self.cache.delete_namespace(CacheNamespace::Users).await;
         }
 
         Ok(role)
     }
 
-    pub async fn delete_role(&self, role_id: Uuid) -> Result<(), UserServiceError> {
-        self.inner.delete_role(role_id).await?;
+    pub async fn delete_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<(), UserServiceError> {
+        self.inner
+            .delete_role(tenant, role_id, expected_version)
+            .await?;
 
         // Invalidate role-related caches (role deletion affects users who had this role)
         if self.cache.is_enabled() {
-            self.cache.delete(&keys::role_key(role_id)).await;
-            self.cache.delete_pattern(&keys::roles_pattern()).await;
+            self.cache.delete(&keys::role_key(tenant, role_id)).await;
+            self.cache.delete_namespace(CacheNamespace::Roles).await;
             // User caches might contain stale role data
-            self.cache.delete_pattern(&keys::user_pattern()).await;
-            self.cache.delete_pattern(&keys::users_pattern()).await;
+            self.cache.delete_namespace(CacheNamespace::User).await;
+            self.cache.delete_namespace(CacheNamespace::Users).await;
         }
 
         Ok(())
     }
 
+    pub async fn restore_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+    ) -> Result<Role, UserServiceError> {
+        let role = self.inner.restore_role(tenant, role_id).await?;
+
+        // The role reappears on the users that held it, so user caches are stale too
+        if self.cache.is_enabled() {
+            self.cache.delete(&keys::role_key(tenant, role_id)).await;
+            self.cache.delete_namespace(CacheNamespace::Roles).await;
+            self.cache.delete_namespace(CacheNamespace::User).await;
+            self.cache.delete_namespace(CacheNamespace::Users).await;
+        }
+
+        Ok(role)
+    }
+
     // ========== Role Assignment Operations ==========
 
-    pub async fn assign_role(&self, user_id: Uuid, role_id: Uuid) -> Result<(), UserServiceError> {
-        self.inner.assign_role(user_id, role_id).await?;
+    pub async fn assign_role(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        role_id: Uuid,
+        grant: &RoleGrant,
+    ) -> Result<(), UserServiceError> {
+        self.inner
+            .assign_role(tenant, user_id, role_id, grant)
+            .await?;
 
         // Invalidate user cache (user's roles changed)
         if self.cache.is_enabled() {
-            self.cache.delete(&keys::user_key(user_id)).await;
-            self.cache.delete_pattern(&keys::users_pattern()).await;
+            self.cache.delete(&keys::user_key(tenant, user_id)).await;
+            self.cache.delete_namespace(CacheNamespace::Users).await;
         }
 
         Ok(())
@@ -243,17 +516,450 @@ where
 
     pub async fn unassign_role(
         &self,
+        tenant: TenantId,
         user_id: Uuid,
         role_id: Uuid,
     ) -> Result<(), UserServiceError> {
-        self.inner.unassign_role(user_id, role_id).await?;
+        self.inner.unassign_role(tenant, user_id, role_id).await?;
 
         // Invalidate user cache (user's roles changed)
         if self.cache.is_enabled() {
-            self.cache.delete(&keys::user_key(user_id)).await;
-            self.cache.delete_pattern(&keys::users_pattern()).await;
+            self.cache.delete(&keys::user_key(tenant, user_id)).await;
+            self.cache.delete_namespace(CacheNamespace::Users).await;
         }
 
         Ok(())
     }
+    /// Roles granted to the user itself, without those of its groups; not cached
+    pub async fn get_granted_roles_for_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<Vec<Role>, UserServiceError> {
+        self.inner.get_granted_roles_for_user(tenant, user_id).await
+    }
+
+    /// Users holding the role through a direct grant; not cached
+    pub async fn get_users_by_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+        pagination: PaginationParams,
+    ) -> Result<PaginatedResult<User>, UserServiceError> {
+        self.inner
+            .get_users_by_role(tenant, role_id, pagination)
+            .await
+    }
+
+    pub async fn get_users_with_expired_role_grants(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<(TenantId, Uuid)>, UserServiceError> {
+        self.inner.get_users_with_expired_role_grants(now).await
+    }
+
+    /// Delete expired role grants and drop the cached entries of the users that lost a role
+    pub async fn remove_expired_role_grants(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<(TenantId, Uuid)>, UserServiceError> {
+        let users = self.inner.remove_expired_role_grants(now).await?;
+
+        if self.cache.is_enabled() && !users.is_empty() {
+            for (tenant, user_id) in &users {
+                self.cache.delete(&keys::user_key(*tenant, *user_id)).await;
+            }
+            self.cache.delete_namespace(CacheNamespace::Users).await;
+        }
+
+        Ok(users)
+    }
+
+    // ========== Tenant Operations ==========
+
+    pub async fn get_tenant(&self, tenant: TenantId) -> Result<Option<Tenant>, UserServiceError> {
+        self.inner.get_tenant(tenant).await
+    }
+
+    pub async fn get_tenants(&self) -> Result<Vec<Tenant>, UserServiceError> {
+        self.inner.get_tenants().await
+    }
+
+    pub async fn create_tenant(&self, slug: &str, name: &str) -> Result<Tenant, UserServiceError> {
+        self.inner.create_tenant(slug, name).await
+    }
+
+    // ========== API Key Operations ==========
+    // Keys are never cached: a revoked or rotated key must stop working at once.
+
+    pub async fn create_api_key(
+        &self,
+        tenant: TenantId,
+        new_key: &NewApiKey,
+        key_prefix: &str,
+        key_hash: &str,
+    ) -> Result<ApiKey, UserServiceError> {
+        self.inner
+            .create_api_key(tenant, new_key, key_prefix, key_hash)
+            .await
+    }
+
+    pub async fn get_api_keys(&self, tenant: TenantId) -> Result<Vec<ApiKey>, UserServiceError> {
+        self.inner.get_api_keys(tenant).await
+    }
+
+    pub async fn get_api_key_by_hash(
+        &self,
+        key_hash: &str,
+    ) -> Result<Option<ApiKey>, UserServiceError> {
+        self.inner.get_api_key_by_hash(key_hash).await
+    }
+
+    pub async fn rotate_api_key(
+        &self,
+        tenant: TenantId,
+        key_id: Uuid,
+        key_prefix: &str,
+        key_hash: &str,
+    ) -> Result<ApiKey, UserServiceError> {
+        self.inner
+            .rotate_api_key(tenant, key_id, key_prefix, key_hash)
+            .await
+    }
+
+    pub async fn revoke_api_key(
+        &self,
+        tenant: TenantId,
+        key_id: Uuid,
+    ) -> Result<(), UserServiceError> {
+        self.inner.revoke_api_key(tenant, key_id).await
+    }
+
+    pub async fn record_api_key_use(
+        &self,
+        key_id: Uuid,
+        used_at: DateTime<Utc>,
+    ) -> Result<(), UserServiceError> {
+        self.inner.record_api_key_use(key_id, used_at).await
+    }
+
+    // ========== Group Operations ==========
+    // Groups are read straight from the database. Writes that change who inherits which
+    // roles evict the affected cached users.
+
+    pub async fn get_group(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+    ) -> Result<Option<Group>, UserServiceError> {
+        self.inner.get_group(tenant, group_id).await
+    }
+
+    pub async fn get_groups(
+        &self,
+        tenant: TenantId,
+        pagination: PaginationParams,
+    ) -> Result<PaginatedResult<Group>, UserServiceError> {
+        self.inner.get_groups(tenant, pagination).await
+    }
+
+    pub async fn get_group_members(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        pagination: PaginationParams,
+    ) -> Result<PaginatedResult<User>, UserServiceError> {
+        self.inner
+            .get_group_members(tenant, group_id, pagination)
+            .await
+    }
+
+    pub async fn create_group(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+        name: &str,
+    ) -> Result<Group, UserServiceError> {
+        self.inner.create_group(tenant, keycloak_id, name).await
+    }
+
+    pub async fn rename_group(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        name: &str,
+    ) -> Result<Group, UserServiceError> {
+        self.inner.rename_group(tenant, group_id, name).await
+    }
+
+    pub async fn delete_group(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+    ) -> Result<(), UserServiceError> {
+        self.inner.delete_group(tenant, group_id).await?;
+
+        // Members lose the inherited roles
+        if self.cache.is_enabled() {
+            self.cache.delete_namespace(CacheNamespace::User).await;
+            self.cache.delete_namespace(CacheNamespace::Users).await;
+        }
+
+        Ok(())
+    }
+
+    pub async fn add_group_member(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), UserServiceError> {
+        self.inner
+            .add_group_member(tenant, group_id, user_id)
+            .await?;
+
+        if self.cache.is_enabled() {
+            self.cache.delete(&keys::user_key(tenant, user_id)).await;
+            self.cache.delete_namespace(CacheNamespace::Users).await;
+        }
+
+        Ok(())
+    }
+
+    pub async fn remove_group_member(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), UserServiceError> {
+        self.inner
+            .remove_group_member(tenant, group_id, user_id)
+            .await?;
+
+        if self.cache.is_enabled() {
+            self.cache.delete(&keys::user_key(tenant, user_id)).await;
+            self.cache.delete_namespace(CacheNamespace::Users).await;
+        }
+
+        Ok(())
+    }
+
+    pub async fn assign_group_role(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        role_id: Uuid,
+    ) -> Result<(), UserServiceError> {
+        self.inner
+            .assign_group_role(tenant, group_id, role_id)
+            .await?;
+
+        // Every member's roles changed
+        if self.cache.is_enabled() {
+            self.cache.delete_namespace(CacheNamespace::User).await;
+            self.cache.delete_namespace(CacheNamespace::Users).await;
+        }
+
+        Ok(())
+    }
+
+    pub async fn unassign_group_role(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        role_id: Uuid,
+    ) -> Result<(), UserServiceError> {
+        self.inner
+            .unassign_group_role(tenant, group_id, role_id)
+            .await?;
+
+        // Every member's roles changed
+        if self.cache.is_enabled() {
+            self.cache.delete_namespace(CacheNamespace::User).await;
+            self.cache.delete_namespace(CacheNamespace::Users).await;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::InMemoryCache;
+    use async_trait::async_trait;
+    use mockall::{mock, Sequence};
+    use user_lib::entities::UserSuspension;
+    use user_lib::repository::errors::UserRepositoryError;
+    use user_lib::repository::models::{RoleRow, UserAttributesRow, UserRoleMapping, UserRow};
+
+    mock! {
+        UserRepo {}
+
+        #[async_trait]
+        impl UserRepositoryTrait for UserRepo {
+            async fn create_user(&self, tenant: TenantId, keycloak_id: &str) -> Result<UserRow, UserRepositoryError>;
+            async fn get_user(&self, tenant: TenantId, user_id: Uuid) -> Result<Option<UserRow>, UserRepositoryError>;
+            async fn get_user_by_keycloak_id(&self, tenant: TenantId, keycloak_id: &str) -> Result<Option<UserRow>, UserRepositoryError>;
+            async fn get_user_tenant_by_keycloak_id(&self, keycloak_id: &str) -> Result<Option<TenantId>, UserRepositoryError>;
+            async fn delete_user(&self, tenant: TenantId, user_id: Uuid, expected_version: Option<i64>) -> Result<(), UserRepositoryError>;
+            async fn soft_delete_user(&self, tenant: TenantId, user_id: Uuid, expected_version: Option<i64>) -> Result<bool, UserRepositoryError>;
+            async fn restore_user(&self, tenant: TenantId, user_id: Uuid) -> Result<bool, UserRepositoryError>;
+            async fn touch_user(&self, tenant: TenantId, user_id: Uuid, expected_version: Option<i64>) -> Result<UserRow, UserRepositoryError>;
+            async fn set_user_suspension(&self, tenant: TenantId, user_id: Uuid, suspension: Option<UserSuspension>, expected_version: Option<i64>) -> Result<UserRow, UserRepositoryError>;
+            async fn record_user_login(&self, tenant: TenantId, keycloak_id: &str, at: DateTime<Utc>) -> Result<(), UserRepositoryError>;
+            async fn get_users_paginated(&self, tenant: TenantId, pagination: PaginationParams, filter: &UserFilter) -> Result<(Vec<UserRow>, u64), UserRepositoryError>;
+            async fn get_users_by_role_paginated(&self, tenant: TenantId, role_id: Uuid, pagination: PaginationParams) -> Result<(Vec<UserRow>, u64), UserRepositoryError>;
+            async fn get_user_attributes(&self, tenant: TenantId, user_ids: &[String]) -> Result<Vec<UserAttributesRow>, UserRepositoryError>;
+            async fn upsert_user_attributes(&self, tenant: TenantId, attributes: &UserAttributesRow) -> Result<(), UserRepositoryError>;
+        }
+    }
+
+    mock! {
+        RoleRepo {}
+
+        #[async_trait]
+        impl RoleRepositoryTrait for RoleRepo {
+            async fn create_role(&self, tenant: TenantId, name: &str, description: Option<String>) -> Result<RoleRow, UserRepositoryError>;
+            async fn get_role(&self, tenant: TenantId, role_id: Uuid) -> Result<Option<RoleRow>, UserRepositoryError>;
+            async fn get_system_role(&self, tenant: TenantId, name: &str) -> Result<Option<RoleRow>, UserRepositoryError>;
+            async fn update_role(&self, tenant: TenantId, role_id: Uuid, name: &str, description: Option<String>, expected_version: Option<i64>) -> Result<RoleRow, UserRepositoryError>;
+            async fn delete_role(&self, tenant: TenantId, role_id: Uuid, expected_version: Option<i64>) -> Result<(), UserRepositoryError>;
+            async fn soft_delete_role(&self, tenant: TenantId, role_id: Uuid, expected_version: Option<i64>) -> Result<bool, UserRepositoryError>;
+            async fn restore_role(&self, tenant: TenantId, role_id: Uuid) -> Result<bool, UserRepositoryError>;
+            async fn get_roles_for_user(&self, tenant: TenantId, user_id: Uuid) -> Result<Vec<RoleRow>, UserRepositoryError>;
+            async fn get_granted_roles_for_user(&self, tenant: TenantId, user_id: Uuid) -> Result<Vec<RoleRow>, UserRepositoryError>;
+            async fn get_roles_for_users(&self, tenant: TenantId, user_ids: &[String]) -> Result<Vec<UserRoleMapping>, UserRepositoryError>;
+            async fn get_roles_paginated(&self, tenant: TenantId, pagination: PaginationParams) -> Result<(Vec<RoleRow>, u64), UserRepositoryError>;
+        }
+    }
+
+    mock! {
+        UserRoleRepo {}
+
+        #[async_trait]
+        impl UserRoleRepositoryTrait for UserRoleRepo {
+            async fn assign_role(&self, tenant: TenantId, user_id: &str, role_id: &str, grant: &RoleGrant) -> Result<(), UserRepositoryError>;
+            async fn unassign_role(&self, tenant: TenantId, user_id: &str, role_id: &str) -> Result<(), UserRepositoryError>;
+            async fn get_users_with_expired_role_grants(&self, now: DateTime<Utc>) -> Result<Vec<(String, String)>, UserRepositoryError>;
+            async fn delete_expired_role_grants(&self, now: DateTime<Utc>) -> Result<Vec<(String, String)>, UserRepositoryError>;
+        }
+    }
+
+    fn cached_service(
+        user_repo: MockUserRepo,
+        role_repo: MockRoleRepo,
+    ) -> CachedUserService<MockUserRepo, MockRoleRepo, MockUserRoleRepo> {
+        let inner = UserService::with_repos(
+            Arc::new(user_repo),
+            Arc::new(role_repo),
+            Arc::new(MockUserRoleRepo::new()),
+        );
+        CachedUserService::new(
+            Arc::new(inner),
+            Cache::new(Arc::new(InMemoryCache::new(100))),
+            CacheConfig::from_env(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_creating_a_user_evicts_its_negative_entry() {
+        let tenant = TenantId::DEFAULT;
+        let user_id = Uuid::new_v4();
+        let user_row = move || UserRow {
+            id: user_id.to_string(),
+            keycloak_id: "keycloak-new".to_string(),
+            ..Default::default()
+        };
+        let mut user_repo = MockUserRepo::new();
+        let mut role_repo = MockRoleRepo::new();
+        let mut seq = Sequence::new();
+
+        user_repo
+            .expect_get_user()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _| Ok(None));
+        user_repo
+            .expect_create_user()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(move |_, _| Ok(user_row()));
+        user_repo
+            .expect_get_user()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(move |_, _| Ok(Some(user_row())));
+        user_repo
+            .expect_get_user_attributes()
+            .returning(|_, _| Ok(vec![]));
+        role_repo
+            .expect_get_roles_for_user()
+            .returning(|_, _| Ok(vec![]));
+
+        let service = cached_service(user_repo, role_repo);
+        let key = keys::user_key(tenant, user_id);
+
+        assert!(service.get_user(tenant, user_id).await.unwrap().is_none());
+        assert!(matches!(
+            service.cache().lookup::<User>(&key).await,
+            CacheLookup::Negative
+        ));
+
+        service.create_user(tenant, "keycloak-new").await.unwrap();
+        assert!(matches!(
+            service.cache().lookup::<User>(&key).await,
+            CacheLookup::Miss
+        ));
+
+        let user = service.get_user(tenant, user_id).await.unwrap().unwrap();
+        assert_eq!(user.keycloak_id, "keycloak-new");
+    }
+
+    #[tokio::test]
+    async fn test_creating_a_role_evicts_its_negative_entry() {
+        let tenant = TenantId::DEFAULT;
+        let role_id = Uuid::new_v4();
+        let role_row = move || RoleRow {
+            id: role_id.to_string(),
+            name: "editor".to_string(),
+            ..Default::default()
+        };
+        let mut role_repo = MockRoleRepo::new();
+        let mut seq = Sequence::new();
+
+        role_repo
+            .expect_get_role()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _| Ok(None));
+        role_repo
+            .expect_create_role()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(move |_, _, _| Ok(role_row()));
+        role_repo
+            .expect_get_role()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(move |_, _| Ok(Some(role_row())));
+
+        let service = cached_service(MockUserRepo::new(), role_repo);
+        let key = keys::role_key(tenant, role_id);
+
+        assert!(service.get_role(tenant, role_id).await.unwrap().is_none());
+        assert!(matches!(
+            service.cache().lookup::<Role>(&key).await,
+            CacheLookup::Negative
+        ));
+
+        service.create_role(tenant, "editor", None).await.unwrap();
+        assert!(matches!(
+            service.cache().lookup::<Role>(&key).await,
+            CacheLookup::Miss
+        ));
+
+        let role = service.get_role(tenant, role_id).await.unwrap().unwrap();
+        assert_eq!(role.name, "editor");
+    }
 }
@@ -0,0 +1,107 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::keys::CacheNamespace;
+
+#[derive(Debug, Default)]
+struct NamespaceCounters {
+    hits: AtomicU64,
+    negative_hits: AtomicU64,
+    misses: AtomicU64,
+    errors: AtomicU64,
+}
+
+/// Process-local hit/miss/error counters, one set per cache namespace
+#[derive(Debug, Default)]
+pub struct CacheStats {
+    counters: [NamespaceCounters; CacheNamespace::ALL.len()],
+}
+
+/// Point-in-time view of one namespace's counters
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct NamespaceStats {
+    pub namespace: &'static str,
+    pub hits: u64,
+    pub negative_hits: u64,
+    pub misses: u64,
+    pub errors: u64,
+}
+
+impl CacheStats {
+    fn counters_for(&self, key: &str) -> Option<&NamespaceCounters> {
+        CacheNamespace::of_key(key).map(|ns| &self.counters[ns.index()])
+    }
+
+    pub fn record_hit(&self, key: &str) {
+        if let Some(c) = self.counters_for(key) {
+            c.hits.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn record_negative_hit(&self, key: &str) {
+        if let Some(c) = self.counters_for(key) {
+            c.negative_hits.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn record_miss(&self, key: &str) {
+        if let Some(c) = self.counters_for(key) {
+            c.misses.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn record_error(&self, key: &str) {
+        if let Some(c) = self.counters_for(key) {
+            c.errors.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn snapshot(&self) -> Vec<NamespaceStats> {
+        CacheNamespace::ALL
+            .into_iter()
+            .map(|ns| {
+                let c = &self.counters[ns.index()];
+                NamespaceStats {
+                    namespace: ns.as_str(),
+                    hits: c.hits.load(Ordering::Relaxed),
+                    negative_hits: c.negative_hits.load(Ordering::Relaxed),
+                    misses: c.misses.load(Ordering::Relaxed),
+                    errors: c.errors.load(Ordering::Relaxed),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::keys;
+    use user_lib::entities::TenantId;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_counters_are_tracked_per_namespace() {
+        let stats = CacheStats::default();
+        let user = keys::user_key(TenantId::DEFAULT, Uuid::new_v4());
+        let role = keys::role_key(TenantId::DEFAULT, Uuid::new_v4());
+
+        stats.record_hit(&user);
+        stats.record_hit(&user);
+        stats.record_negative_hit(&user);
+        stats.record_miss(&role);
+        stats.record_error(&role);
+        stats.record_hit("unrelated:key");
+
+        let snapshot = stats.snapshot();
+        let user_stats = snapshot.iter().find(|s| s.namespace == "user").unwrap();
+        let role_stats = snapshot.iter().find(|s| s.namespace == "role").unwrap();
+
+        assert_eq!(
+            (user_stats.hits, user_stats.negative_hits, user_stats.misses),
+            (2, 1, 0)
+        );
+        assert_eq!((role_stats.misses, role_stats.errors), (1, 1));
+        assert_eq!(snapshot.len(), CacheNamespace::ALL.len());
+    }
+}
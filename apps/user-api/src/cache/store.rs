@@ -0,0 +1,330 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::backend::CacheBackend;
+use super::client::RedisCache;
+use super::config::{CacheBackendKind, CacheConfig};
+use super::keys::CacheNamespace;
+use super::stats::{CacheStats, NamespaceStats};
+use super::InMemoryCache;
+
+/// Value stored for negative entries. It is not valid JSON, so it can never
+/// collide with a serialized cached value.
+const NEGATIVE_SENTINEL: &str = "__user-api:none__";
+
+/// Result of a cache lookup that distinguishes negative entries from misses
+#[derive(Debug)]
+pub enum CacheLookup<T> {
+    /// A cached value was found
+    Hit(T),
+    /// The key holds a negative entry: the resource is known not to exist
+    Negative,
+    /// Nothing usable is cached for the key
+    Miss,
+}
+
+/// Typed cache used by the services: JSON (de)serialization, negative entries and
+/// per-namespace stats on top of a [`CacheBackend`]. Backend failures are logged and
+/// treated as misses so callers fall back to the source of truth.
+#[derive(Clone)]
+pub struct Cache {
+    backend: Option<Arc<dyn CacheBackend>>,
+    stats: Arc<CacheStats>,
+}
+
+impl std::fmt::Debug for Cache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Cache")
+            .field("configured", &self.is_configured())
+            .field("available", &self.is_enabled())
+            .finish()
+    }
+}
+
+impl Cache {
+    pub fn new(backend: Arc<dyn CacheBackend>) -> Self {
+        Self {
+            backend: Some(backend),
+            stats: Arc::default(),
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self {
+            backend: None,
+            stats: Arc::default(),
+        }
+    }
+
+    /// Build the backend selected by `CACHE_BACKEND`
+    pub async fn from_config(config: &CacheConfig) -> Self {
+        if !config.enabled {
+            tracing::info!("Cache disabled by configuration");
+            return Self::disabled();
+        }
+
+        match config.backend {
+            CacheBackendKind::Memory => {
+                tracing::info!(
+                    max_entries = config.memory_max_entries,
+                    "Using in-memory cache backend"
+                );
+                Self::new(Arc::new(InMemoryCache::new(config.memory_max_entries)))
+            }
+            CacheBackendKind::Redis => match RedisCache::connect(config).await {
+                Some(redis) => Self::new(Arc::new(redis)),
+                None => Self::disabled(),
+            },
+        }
+    }
+
+    /// Whether the cache should be used: a backend is configured and currently available
+    pub fn is_enabled(&self) -> bool {
+        self.backend.as_ref().is_some_and(|b| b.is_available())
+    }
+
+    /// Whether a backend is configured at all, regardless of its current health
+    pub fn is_configured(&self) -> bool {
+        self.backend.is_some()
+    }
+
+    /// Raw backend, for callers that need its error reporting (e.g. idempotency records)
+    pub fn backend(&self) -> Option<Arc<dyn CacheBackend>> {
+        self.backend.clone()
+    }
+
+    /// Per-namespace hit/miss/error counters since process start
+    pub fn stats(&self) -> Vec<NamespaceStats> {
+        self.stats.snapshot()
+    }
+
+    pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        match self.lookup(key).await {
+            CacheLookup::Hit(value) => Some(value),
+            CacheLookup::Negative | CacheLookup::Miss => None,
+        }
+    }
+
+    /// Look up a key, reporting negative entries separately from misses
+    pub async fn lookup<T: DeserializeOwned>(&self, key: &str) -> CacheLookup<T> {
+        let Some(backend) = &self.backend else {
+            return CacheLookup::Miss;
+        };
+
+        match backend.get(key).await {
+            Ok(data) => self.decode(key, data),
+            Err(e) => {
+                tracing::error!(key = %key, error = %e, "Cache GET failed");
+                self.stats.record_error(key);
+                CacheLookup::Miss
+            }
+        }
+    }
+
+    /// Look up several keys in one round trip; the result is in `keys` order
+    pub async fn lookup_many<T: DeserializeOwned>(&self, keys: &[String]) -> Vec<CacheLookup<T>> {
+        let misses = || keys.iter().map(|_| CacheLookup::Miss).collect();
+        let Some(backend) = &self.backend else {
+            return misses();
+        };
+        if keys.is_empty() {
+            return Vec::new();
+        }
+
+        match backend.mget(keys).await {
+            Ok(values) => keys
+                .iter()
+                .zip(values)
+                .map(|(key, data)| self.decode(key, data))
+                .collect(),
+            Err(e) => {
+                tracing::error!(count = keys.len(), error = %e, "Cache MGET failed");
+                for key in keys {
+                    self.stats.record_error(key);
+                }
+                misses()
+            }
+        }
+    }
+
+    fn decode<T: DeserializeOwned>(&self, key: &str, data: Option<String>) -> CacheLookup<T> {
+        match data {
+            Some(data) if data == NEGATIVE_SENTINEL => {
+                tracing::debug!(key = %key, "Cache negative hit");
+                self.stats.record_negative_hit(key);
+                CacheLookup::Negative
+            }
+            Some(data) => match serde_json::from_str(&data) {
+                Ok(value) => {
+                    tracing::debug!(key = %key, "Cache hit");
+                    self.stats.record_hit(key);
+                    CacheLookup::Hit(value)
+                }
+                Err(e) => {
+                    tracing::error!(key = %key, error = %e, "Cache deserialize error - data corrupted");
+                    self.stats.record_error(key);
+                    CacheLookup::Miss
+                }
+            },
+            None => {
+                tracing::debug!(key = %key, "Cache miss");
+                self.stats.record_miss(key);
+                CacheLookup::Miss
+            }
+        }
+    }
+
+    pub async fn set<T: Serialize>(&self, key: &str, value: &T, ttl: Duration) {
+        let Some(backend) = &self.backend else {
+            return;
+        };
+
+        let data = match serde_json::to_string(value) {
+            Ok(d) => d,
+            Err(e) => {
+                tracing::error!(key = %key, error = %e, "Cache serialize error - failed to encode value");
+                self.stats.record_error(key);
+                return;
+            }
+        };
+
+        match backend.set(key, &data, ttl).await {
+            Ok(()) => tracing::debug!(key = %key, ttl_secs = ttl.as_secs(), "Cache set"),
+            Err(e) => {
+                tracing::error!(key = %key, error = %e, "Cache SET failed");
+                self.stats.record_error(key);
+            }
+        }
+    }
+
+    /// Record that the resource behind `key` does not exist
+    pub async fn set_negative(&self, key: &str, ttl: Duration) {
+        let Some(backend) = &self.backend else {
+            return;
+        };
+
+        match backend.set(key, NEGATIVE_SENTINEL, ttl).await {
+            Ok(()) => {
+                tracing::debug!(key = %key, ttl_secs = ttl.as_secs(), "Cache negative entry set")
+            }
+            Err(e) => {
+                tracing::error!(key = %key, error = %e, "Cache SET failed for negative entry");
+                self.stats.record_error(key);
+            }
+        }
+    }
+
+    pub async fn delete(&self, key: &str) {
+        let Some(backend) = &self.backend else {
+            return;
+        };
+
+        match backend.delete(key).await {
+            Ok(()) => tracing::debug!(key = %key, "Cache key deleted"),
+            Err(e) => {
+                tracing::error!(key = %key, error = %e, "Cache DEL failed");
+                self.stats.record_error(key);
+            }
+        }
+    }
+
+    /// Delete every key in `namespace`, returning how many were removed
+    pub async fn delete_namespace(&self, namespace: CacheNamespace) -> u64 {
+        let Some(backend) = &self.backend else {
+            return 0;
+        };
+
+        match backend.delete_namespace(namespace).await {
+            Ok(count) => {
+                tracing::debug!(
+                    namespace = namespace.as_str(),
+                    count = count,
+                    "Cache namespace deleted"
+                );
+                count
+            }
+            Err(e) => {
+                tracing::error!(namespace = namespace.as_str(), error = %e, "Cache namespace delete failed");
+                self.stats.record_error(&namespace.pattern());
+                0
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::keys;
+    use user_lib::entities::TenantId;
+    use uuid::Uuid;
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    fn memory_cache() -> Cache {
+        Cache::new(Arc::new(InMemoryCache::new(100)))
+    }
+
+    #[tokio::test]
+    async fn test_lookup_distinguishes_hit_negative_and_miss() {
+        let cache = memory_cache();
+        let hit = keys::user_key(TenantId::DEFAULT, Uuid::new_v4());
+        let negative = keys::user_key(TenantId::DEFAULT, Uuid::new_v4());
+        let missing = keys::user_key(TenantId::DEFAULT, Uuid::new_v4());
+
+        cache.set(&hit, &"value", TTL).await;
+        cache.set_negative(&negative, TTL).await;
+
+        assert!(matches!(cache.lookup::<String>(&hit).await, CacheLookup::Hit(v) if v == "value"));
+        assert!(matches!(
+            cache.lookup::<String>(&negative).await,
+            CacheLookup::Negative
+        ));
+        assert!(matches!(
+            cache.lookup::<String>(&missing).await,
+            CacheLookup::Miss
+        ));
+
+        let user_stats = cache
+            .stats()
+            .into_iter()
+            .find(|s| s.namespace == "user")
+            .unwrap();
+        assert_eq!(
+            (user_stats.hits, user_stats.negative_hits, user_stats.misses),
+            (1, 1, 1)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lookup_many_keeps_key_order() {
+        let cache = memory_cache();
+        let keys = vec![
+            keys::keycloak_profile_key(TenantId::DEFAULT, "a"),
+            keys::keycloak_profile_key(TenantId::DEFAULT, "b"),
+            keys::keycloak_profile_key(TenantId::DEFAULT, "c"),
+        ];
+        cache.set(&keys[0], &1u32, TTL).await;
+        cache.set_negative(&keys[2], TTL).await;
+
+        let results = cache.lookup_many::<u32>(&keys).await;
+        assert!(matches!(results[0], CacheLookup::Hit(1)));
+        assert!(matches!(results[1], CacheLookup::Miss));
+        assert!(matches!(results[2], CacheLookup::Negative));
+    }
+
+    #[tokio::test]
+    async fn test_disabled_cache_always_misses() {
+        let cache = Cache::disabled();
+        let key = keys::role_key(TenantId::DEFAULT, Uuid::new_v4());
+        cache.set(&key, &"value", TTL).await;
+
+        assert!(!cache.is_enabled());
+        assert!(matches!(
+            cache.lookup::<String>(&key).await,
+            CacheLookup::Miss
+        ));
+        assert_eq!(cache.delete_namespace(CacheNamespace::Role).await, 0);
+    }
+}
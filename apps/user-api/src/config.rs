@@ -2,8 +2,8 @@ use std::net::IpAddr;
 use std::time::Duration;
 
 use crate::constants::{
-    CORS_ALLOWED_ORIGINS, IP_ALLOWLIST, IP_BLOCKLIST, MAX_BODY_SIZE_BYTES, RATE_LIMIT_BURST,
-    RATE_LIMIT_PER_MINUTE, REQUEST_TIMEOUT_SECS, SHUTDOWN_TIMEOUT_SECS,
+    CORS_ALLOWED_ORIGINS, IDEMPOTENCY_TTL_SECS, IP_ALLOWLIST, IP_BLOCKLIST, MAX_BODY_SIZE_BYTES,
+    RATE_LIMIT_BURST, RATE_LIMIT_PER_MINUTE, REQUEST_TIMEOUT_SECS, SHUTDOWN_TIMEOUT_SECS,
 };
 
 /// Validate and parse IP addresses from a comma-separated string.
@@ -38,6 +38,8 @@ pub struct MiddlewareConfig {
     pub cors_allowed_origins: Vec<String>,
     pub ip_allowlist: Vec<String>,
     pub ip_blocklist: Vec<String>,
+    /// How long responses to `Idempotency-Key` requests are replayed
+    pub idempotency_ttl: Duration,
 }
 
 impl Default for MiddlewareConfig {
@@ -51,6 +53,7 @@ impl Default for MiddlewareConfig {
             cors_allowed_origins: vec!["*".to_string()],
             ip_allowlist: vec![],
             ip_blocklist: vec![],
+            idempotency_ttl: Duration::from_secs(86_400),
         }
     }
 }
@@ -99,6 +102,12 @@ impl MiddlewareConfig {
             .map(|v| parse_ip_list(IP_BLOCKLIST, &v))
             .unwrap_or(default.ip_blocklist);
 
+        let idempotency_ttl = std::env::var(IDEMPOTENCY_TTL_SECS)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(default.idempotency_ttl);
+
         Self {
             rate_limit_per_minute,
             rate_limit_burst,
@@ -108,6 +117,7 @@ impl MiddlewareConfig {
             cors_allowed_origins,
             ip_allowlist,
             ip_blocklist,
+            idempotency_ttl,
         }
     }
 
@@ -11,16 +11,43 @@ pub const ELASTIC_URL: &str = "ELASTIC_URL";
 
 pub const USER_API_PORT: &str = "USER_API_PORT";
 
+// Keep deleted users and roles restorable instead of removing them
+pub const SOFT_DELETE_ENABLED: &str = "SOFT_DELETE_ENABLED";
+
+// Mirror roles and role assignments to Keycloak realm roles
+pub const KEYCLOAK_ROLE_SYNC_ENABLED: &str = "KEYCLOAK_ROLE_SYNC_ENABLED";
+
+// Seconds between sweeps of expired role grants (0 disables the sweeper)
+pub const ROLE_GRANT_SWEEP_INTERVAL_SECS: &str = "ROLE_GRANT_SWEEP_INTERVAL_SECS";
+
+// Seconds between polls of Keycloak's admin and user events (0 disables the poller)
+pub const KEYCLOAK_EVENT_POLL_INTERVAL_SECS: &str = "KEYCLOAK_EVENT_POLL_INTERVAL_SECS";
+
 // Redis configuration
 pub const REDIS_HOST: &str = "REDIS_HOST";
 pub const REDIS_PORT: &str = "REDIS_PORT";
 pub const REDIS_DB: &str = "REDIS_DB";
+pub const REDIS_USERNAME: &str = "REDIS_USERNAME";
+pub const REDIS_PASSWORD: &str = "REDIS_PASSWORD";
+pub const REDIS_TLS: &str = "REDIS_TLS";
+pub const REDIS_SENTINEL_NODES: &str = "REDIS_SENTINEL_NODES";
+pub const REDIS_SENTINEL_MASTER: &str = "REDIS_SENTINEL_MASTER";
+pub const REDIS_CONNECT_TIMEOUT_MS: &str = "REDIS_CONNECT_TIMEOUT_MS";
+pub const REDIS_RECONNECT_MIN_BACKOFF_MS: &str = "REDIS_RECONNECT_MIN_BACKOFF_MS";
+pub const REDIS_RECONNECT_MAX_BACKOFF_MS: &str = "REDIS_RECONNECT_MAX_BACKOFF_MS";
 
 // Cache configuration
 pub const CACHE_ENABLED: &str = "CACHE_ENABLED";
+pub const CACHE_BACKEND: &str = "CACHE_BACKEND";
+pub const CACHE_MEMORY_MAX_ENTRIES: &str = "CACHE_MEMORY_MAX_ENTRIES";
 pub const CACHE_USER_TTL_SECS: &str = "CACHE_USER_TTL_SECS";
 pub const CACHE_ROLE_TTL_SECS: &str = "CACHE_ROLE_TTL_SECS";
 pub const CACHE_LIST_TTL_SECS: &str = "CACHE_LIST_TTL_SECS";
+pub const CACHE_NEGATIVE_TTL_SECS: &str = "CACHE_NEGATIVE_TTL_SECS";
+pub const CACHE_WARMUP_ENABLED: &str = "CACHE_WARMUP_ENABLED";
+pub const CACHE_WARMUP_USER_PAGES: &str = "CACHE_WARMUP_USER_PAGES";
+pub const CACHE_WARMUP_CONCURRENCY: &str = "CACHE_WARMUP_CONCURRENCY";
+pub const CACHE_WARMUP_DEADLINE_SECS: &str = "CACHE_WARMUP_DEADLINE_SECS";
 
 // Middleware configuration
 pub const RATE_LIMIT_PER_MINUTE: &str = "RATE_LIMIT_PER_MINUTE";
@@ -31,3 +58,7 @@ pub const MAX_BODY_SIZE_BYTES: &str = "MAX_BODY_SIZE_BYTES";
 pub const IP_ALLOWLIST: &str = "IP_ALLOWLIST";
 pub const IP_BLOCKLIST: &str = "IP_BLOCKLIST";
 pub const SHUTDOWN_TIMEOUT_SECS: &str = "SHUTDOWN_TIMEOUT_SECS";
+pub const IDEMPOTENCY_TTL_SECS: &str = "IDEMPOTENCY_TTL_SECS";
+
+// Admin endpoints
+pub const ADMIN_API_TOKEN: &str = "ADMIN_API_TOKEN";
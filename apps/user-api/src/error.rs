@@ -22,6 +22,14 @@ pub enum ApiError {
     BadRequest(String),
     NotFound(String),
     Conflict(String),
+    /// Credentials were presented but are not valid (401)
+    Unauthorized(String),
+    /// The target is protected against this operation (403)
+    Forbidden(String),
+    /// `If-Match` does not match the current version (412)
+    PreconditionFailed(String),
+    /// A write arrived without `If-Match` (428)
+    PreconditionRequired(String),
     Internal(String),
 }
 
@@ -45,6 +53,18 @@ impl ApiError {
     pub fn role_not_found() -> Self {
         ApiError::NotFound("role not found".to_string())
     }
+
+    pub fn invalid_group_uuid() -> Self {
+        ApiError::BadRequest("invalid group uuid".to_string())
+    }
+
+    pub fn group_not_found() -> Self {
+        ApiError::NotFound("group not found".to_string())
+    }
+
+    pub fn unknown_tenant() -> Self {
+        ApiError::BadRequest("unknown tenant".to_string())
+    }
 }
 
 impl IntoResponse for ApiError {
@@ -53,6 +73,18 @@ impl IntoResponse for ApiError {
             ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, "bad_request", Some(msg)),
             ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, "not_found", Some(msg)),
             ApiError::Conflict(msg) => (StatusCode::CONFLICT, "conflict", Some(msg)),
+            ApiError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, "unauthorized", Some(msg)),
+            ApiError::Forbidden(msg) => (StatusCode::FORBIDDEN, "forbidden", Some(msg)),
+            ApiError::PreconditionFailed(msg) => (
+                StatusCode::PRECONDITION_FAILED,
+                "precondition_failed",
+                Some(msg),
+            ),
+            ApiError::PreconditionRequired(msg) => (
+                StatusCode::PRECONDITION_REQUIRED,
+                "precondition_required",
+                Some(msg),
+            ),
             ApiError::Internal(msg) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "internal_error",
@@ -80,9 +112,21 @@ impl From<UserServiceError> for ApiError {
             UserServiceError::RoleNameAlreadyExists => {
                 ApiError::Conflict("role name already exists".to_string())
             }
+            UserServiceError::TenantSlugAlreadyExists => {
+                ApiError::Conflict("tenant slug already exists".to_string())
+            }
+            UserServiceError::GroupNameAlreadyExists => {
+                ApiError::Conflict("group name already exists".to_string())
+            }
             UserServiceError::UserAlreadyHasRole => {
                 ApiError::Conflict("user already has this role".to_string())
             }
+            UserServiceError::VersionConflict => ApiError::PreconditionFailed(
+                "resource was modified, fetch it again and retry".to_string(),
+            ),
+            UserServiceError::SystemRole => {
+                ApiError::Forbidden("system roles cannot be renamed or deleted".to_string())
+            }
             UserServiceError::InvalidUuid(msg) => {
                 ApiError::BadRequest(format!("invalid uuid: {msg}"))
             }
@@ -101,9 +145,39 @@ impl From<KeycloakError> for ApiError {
             KeycloakError::UserAlreadyExists(email) => {
                 ApiError::Conflict(format!("user already exists: {email}"))
             }
+            KeycloakError::EmailAlreadyExists(email) => {
+                ApiError::Conflict(format!("email already exists: {email}"))
+            }
+            KeycloakError::GroupNotFound(id) => {
+                ApiError::NotFound(format!("group not found in keycloak: {id}"))
+            }
+            KeycloakError::GroupAlreadyExists(name) => {
+                ApiError::Conflict(format!("group already exists: {name}"))
+            }
+            KeycloakError::RoleNotFound(name) => {
+                ApiError::NotFound(format!("realm role not found in keycloak: {name}"))
+            }
+            KeycloakError::RoleAlreadyExists(name) => {
+                ApiError::Conflict(format!("realm role already exists: {name}"))
+            }
+            KeycloakError::SessionNotFound(id) => {
+                ApiError::NotFound(format!("session not found: {id}"))
+            }
+            KeycloakError::InvalidPassword(msg) => {
+                ApiError::BadRequest(format!("invalid password: {msg}"))
+            }
+            KeycloakError::ActionsRejected(msg) => {
+                ApiError::BadRequest(format!("required actions email rejected: {msg}"))
+            }
             KeycloakError::NotConfigured => {
                 ApiError::Internal("keycloak is not configured".to_string())
             }
+            KeycloakError::InvalidRealmConfig(msg) => {
+                ApiError::Internal(format!("invalid keycloak realms: {msg}"))
+            }
+            KeycloakError::InvalidToken(msg) => {
+                ApiError::Unauthorized(format!("invalid access token: {msg}"))
+            }
             KeycloakError::TokenError(msg) => {
                 ApiError::Internal(format!("keycloak token error: {msg}"))
             }
@@ -182,6 +256,7 @@ pub fn handle_integrated_service_error(
         | IntegratedServiceError::Keycloak(KeycloakError::RequestFailed(_))
         | IntegratedServiceError::Keycloak(KeycloakError::InvalidResponse(_))
         | IntegratedServiceError::Keycloak(KeycloakError::NotConfigured)
+        | IntegratedServiceError::Keycloak(KeycloakError::InvalidRealmConfig(_))
         | IntegratedServiceError::Keycloak(KeycloakError::Internal(_)) => {
             tracing::error!(env = %env, error = ?err, operation = %operation, "integrated service error");
             if is_prod_like(env) {
@@ -1,13 +1,21 @@
+use chrono::NaiveDate;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
 use reqwest::{Client, StatusCode};
 use secrecy::{ExposeSecret, Secret};
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock as StdRwLock};
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
+use user_lib::entities::TenantId;
 
-use super::config::KeycloakConfig;
+use super::config::{load_realms, KeycloakConfig};
 use super::errors::KeycloakError;
 use super::models::{
-    CreateKeycloakUserRequest, KeycloakCredential, KeycloakUser, TokenResponse,
+    AccessToken, AccessTokenClaims, CreateKeycloakUserRequest, KeycloakAdminEvent,
+    KeycloakCredential, KeycloakErrorResponse, KeycloakEvent, KeycloakGroupRequest, KeycloakRole,
+    KeycloakRoleRequest, KeycloakUser, KeycloakUserSession, RequiredAction, TokenResponse,
     UpdateKeycloakUserRequest,
 };
 
@@ -38,10 +46,55 @@ impl CachedToken {
     }
 }
 
-pub struct KeycloakClient {
+/// Algorithms Keycloak signs access tokens with. Symmetric ones are left out: their key
+/// would be the realm's secret, which this service does not hold.
+const SIGNING_ALGORITHMS: [Algorithm; 8] = [
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+];
+
+/// Signing keys are fetched again for an unknown key ID at most this often, so tokens
+/// with made-up key IDs cannot make every request call Keycloak
+const SIGNING_KEYS_REFRESH: Duration = Duration::from_secs(60);
+
+/// A realm's public signing keys
+struct CachedKeys {
+    keys: Arc<JwkSet>,
+    fetched_at: Instant,
+}
+
+/// One realm's endpoints, its service-account token and its signing keys
+struct Realm {
     config: KeycloakConfig,
+    token: RwLock<Option<CachedToken>>,
+    keys: RwLock<Option<CachedKeys>>,
+}
+
+impl Realm {
+    fn new(config: KeycloakConfig) -> Arc<Self> {
+        Arc::new(Self {
+            config,
+            token: RwLock::new(None),
+            keys: RwLock::new(None),
+        })
+    }
+}
+
+/// Keycloak admin client. Each tenant maps to a realm. Without a realms file every tenant
+/// uses the default realm from `KEYCLOAK_REALM`; with one, only the default tenant does,
+/// and other tenants without an entry are refused unless the shared realm fallback is on.
+pub struct KeycloakClient {
+    default: Arc<Realm>,
+    tenants: StdRwLock<HashMap<TenantId, Arc<Realm>>>,
+    realms_file: Option<PathBuf>,
+    shared_realm_fallback: bool,
     http: Client,
-    token: Arc<RwLock<Option<CachedToken>>>,
 }
 
 impl KeycloakClient {
@@ -52,29 +105,257 @@ impl KeycloakClient {
             .expect("failed to create HTTP client");
 
         Self {
-            config,
+            default: Realm::new(config),
+            tenants: StdRwLock::new(HashMap::new()),
+            realms_file: None,
+            shared_realm_fallback: false,
             http,
-            token: Arc::new(RwLock::new(None)),
         }
     }
 
-    pub fn is_configured(&self) -> bool {
-        self.config.is_configured()
+    /// Let tenants without an entry in the realms file use the default realm
+    pub fn with_shared_realm_fallback(mut self, enabled: bool) -> Self {
+        self.shared_realm_fallback = enabled;
+        self
+    }
+
+    /// Load tenant realms from `path`, which `reload_realms` reads again later
+    pub fn with_realms_file(mut self, path: PathBuf) -> Result<Self, KeycloakError> {
+        self.realms_file = Some(path);
+        self.reload_realms()?;
+        Ok(self)
+    }
+
+    /// Re-read the realms file, returning the tenants whose realm changed. Realms whose
+    /// settings did not change keep their cached token; on error the current realms stay
+    /// in place.
+    pub fn reload_realms(&self) -> Result<Vec<TenantId>, KeycloakError> {
+        let Some(path) = &self.realms_file else {
+            return Ok(Vec::new());
+        };
+        let configs = load_realms(path, &self.default.config)?;
+        let mut tenants = self.tenants.write().unwrap_or_else(|e| e.into_inner());
+        let realms: HashMap<_, _> = configs
+            .into_iter()
+            .map(|(tenant, config)| {
+                let realm = match tenants.get(&tenant) {
+                    Some(current) if current.config == config => current.clone(),
+                    _ => Realm::new(config),
+                };
+                (tenant, realm)
+            })
+            .collect();
+
+        let mut changed: Vec<_> = tenants
+            .keys()
+            .chain(realms.keys())
+            .filter(|tenant| {
+                tenants.get(tenant).map(|realm| &realm.config)
+                    != realms.get(tenant).map(|realm| &realm.config)
+            })
+            .copied()
+            .collect();
+        changed.sort();
+        changed.dedup();
+
+        *tenants = realms;
+        Ok(changed)
+    }
+
+    /// Realm name of every tenant with its own realm, plus the default tenant
+    pub fn realms(&self) -> Vec<(TenantId, String)> {
+        let tenants = self.tenants.read().unwrap_or_else(|e| e.into_inner());
+        let mut realms: Vec<_> = tenants
+            .iter()
+            .map(|(tenant, realm)| (*tenant, realm.config.realm.clone()))
+            .collect();
+        if !tenants.contains_key(&TenantId::DEFAULT) {
+            realms.push((TenantId::DEFAULT, self.default.config.realm.clone()));
+        }
+        realms.sort();
+        realms
+    }
+
+    /// Realm of `tenant`; `NotConfigured` for a tenant the realms file leaves out, unless
+    /// the shared realm fallback is on
+    fn realm(&self, tenant: TenantId) -> Result<Arc<Realm>, KeycloakError> {
+        if let Some(realm) = self
+            .tenants
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&tenant)
+        {
+            return Ok(realm.clone());
+        }
+        if self.realms_file.is_none() || self.shared_realm_fallback || tenant == TenantId::DEFAULT {
+            Ok(self.default.clone())
+        } else {
+            Err(KeycloakError::NotConfigured)
+        }
+    }
+
+    /// Whether both tenants' users live in the same Keycloak realm
+    pub fn shares_realm(&self, a: TenantId, b: TenantId) -> bool {
+        match (self.realm(a), self.realm(b)) {
+            (Ok(a), Ok(b)) => {
+                a.config.base_url == b.config.base_url && a.config.realm == b.config.realm
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether `tenant` has a realm of its own in the realms file, which no other tenant
+    /// shares. Tokens of such a realm identify the tenant without a `tenant_id` claim.
+    pub fn has_own_realm(&self, tenant: TenantId) -> bool {
+        let tenants = self.tenants.read().unwrap_or_else(|e| e.into_inner());
+        let Some(own) = tenants.get(&tenant) else {
+            return false;
+        };
+        let same_realm = |realm: &Realm| {
+            realm.config.base_url == own.config.base_url && realm.config.realm == own.config.realm
+        };
+        // The default realm serves the default tenant and, with the fallback, unmapped ones
+        !same_realm(&self.default)
+            && !tenants
+                .iter()
+                .any(|(other, realm)| *other != tenant && same_realm(realm))
+    }
+
+    /// Whether `token` was issued by the realm of `tenant`
+    pub fn issued_by_realm_of(&self, tenant: TenantId, token: &AccessToken) -> bool {
+        self.realm(tenant)
+            .is_ok_and(|realm| realm.config.realm == token.realm)
+    }
+
+    pub fn is_configured(&self, tenant: TenantId) -> bool {
+        self.realm(tenant)
+            .is_ok_and(|realm| realm.config.is_configured())
     }
 
     pub fn profile_cache_ttl(&self) -> Duration {
-        self.config.profile_cache_ttl
+        self.default.config.profile_cache_ttl
+    }
+
+    /// Known realm with this name: the default realm or one from the realms file
+    fn realm_named(&self, name: &str) -> Option<Arc<Realm>> {
+        if self.default.config.realm == name {
+            return Some(self.default.clone());
+        }
+        self.tenants
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .values()
+            .find(|realm| realm.config.realm == name)
+            .cloned()
+    }
+
+    /// Check a user's access token: it has to be unexpired and signed with a key of the
+    /// realm named in its issuer, which has to be one of the realms this client knows
+    pub async fn verify_access_token(&self, token: &str) -> Result<AccessToken, KeycloakError> {
+        let invalid = |e: jsonwebtoken::errors::Error| KeycloakError::InvalidToken(e.to_string());
+
+        let header = jsonwebtoken::decode_header(token).map_err(invalid)?;
+        if !SIGNING_ALGORITHMS.contains(&header.alg) {
+            return Err(KeycloakError::InvalidToken(format!(
+                "unsupported algorithm {:?}",
+                header.alg
+            )));
+        }
+
+        // The issuer only picks the keys to check the signature with
+        let mut unverified = Validation::new(header.alg);
+        unverified.insecure_disable_signature_validation();
+        unverified.validate_exp = false;
+        unverified.validate_aud = false;
+        let issuer = jsonwebtoken::decode::<AccessTokenClaims>(
+            token,
+            &DecodingKey::from_secret(&[]),
+            &unverified,
+        )
+        .map_err(invalid)?
+        .claims
+        .iss;
+        let realm = issuer
+            .rsplit_once("/realms/")
+            .and_then(|(_, name)| self.realm_named(name.trim_end_matches('/')))
+            .ok_or_else(|| KeycloakError::InvalidToken(format!("unknown issuer {issuer}")))?;
+
+        let keys = self.signing_keys(&realm, header.kid.as_deref()).await?;
+        let jwk = match header.kid.as_deref() {
+            Some(kid) => keys.find(kid),
+            None => keys.keys.first(),
+        }
+        .ok_or_else(|| KeycloakError::InvalidToken("unknown signing key".to_string()))?;
+        let key = DecodingKey::from_jwk(jwk).map_err(invalid)?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.validate_aud = false;
+        validation.set_issuer(&[issuer.as_str()]);
+        let claims = jsonwebtoken::decode::<AccessTokenClaims>(token, &key, &validation)
+            .map_err(invalid)?
+            .claims;
+        let tenant = claims
+            .tenant_id
+            .map(|claim| {
+                claim
+                    .parse::<TenantId>()
+                    .map_err(|_| KeycloakError::InvalidToken("invalid tenant claim".to_string()))
+            })
+            .transpose()?;
+
+        Ok(AccessToken {
+            subject: claims.sub,
+            realm: realm.config.realm.clone(),
+            tenant,
+        })
+    }
+
+    /// Signing keys of the realm, fetched again when `kid` is not among them
+    async fn signing_keys(
+        &self,
+        realm: &Realm,
+        kid: Option<&str>,
+    ) -> Result<Arc<JwkSet>, KeycloakError> {
+        {
+            let cached = realm.keys.read().await;
+            if let Some(cached) = cached.as_ref() {
+                let known = kid.is_none_or(|kid| cached.keys.find(kid).is_some());
+                if known || cached.fetched_at.elapsed() < SIGNING_KEYS_REFRESH {
+                    return Ok(cached.keys.clone());
+                }
+            }
+        }
+
+        let response = self.http.get(realm.config.certs_url()).send().await?;
+        if !response.status().is_success() {
+            return Err(KeycloakError::RequestFailed(format!(
+                "fetching signing keys failed with status {}",
+                response.status()
+            )));
+        }
+        let keys = Arc::new(
+            response
+                .json::<JwkSet>()
+                .await
+                .map_err(|e| KeycloakError::InvalidResponse(e.to_string()))?,
+        );
+
+        *realm.keys.write().await = Some(CachedKeys {
+            keys: keys.clone(),
+            fetched_at: Instant::now(),
+        });
+        Ok(keys)
     }
 
-    /// Get a valid access token, refreshing if necessary
-    async fn get_token(&self) -> Result<String, KeycloakError> {
-        if !self.is_configured() {
+    /// Get a valid access token for the realm, refreshing if necessary
+    async fn get_token(&self, realm: &Realm) -> Result<String, KeycloakError> {
+        if !realm.config.is_configured() {
             return Err(KeycloakError::NotConfigured);
         }
 
         // Check if we have a valid cached token
         {
-            let token_guard = self.token.read().await;
+            let token_guard = realm.token.read().await;
             if let Some(ref cached) = *token_guard {
                 if cached.is_valid() {
                     return Ok(cached.access_token.expose_secret().clone());
@@ -83,11 +364,11 @@ impl KeycloakClient {
         }
 
         // Need to refresh token
-        let new_token = self.fetch_token().await?;
+        let new_token = self.fetch_token(&realm.config).await?;
         let token_string = new_token.access_token.clone();
 
         {
-            let mut token_guard = self.token.write().await;
+            let mut token_guard = realm.token.write().await;
             *token_guard = Some(CachedToken::new(
                 new_token.access_token,
                 new_token.expires_in,
@@ -98,14 +379,14 @@ impl KeycloakClient {
     }
 
     /// Fetch a new token from Keycloak
-    async fn fetch_token(&self) -> Result<TokenResponse, KeycloakError> {
+    async fn fetch_token(&self, config: &KeycloakConfig) -> Result<TokenResponse, KeycloakError> {
         let response = self
             .http
-            .post(self.config.token_url())
+            .post(config.token_url())
             .form(&[
                 ("grant_type", "client_credentials"),
-                ("client_id", &self.config.client_id),
-                ("client_secret", &self.config.client_secret),
+                ("client_id", &config.client_id),
+                ("client_secret", &config.client_secret),
             ])
             .send()
             .await?;
@@ -127,13 +408,15 @@ impl KeycloakClient {
     /// Get a user by Keycloak ID
     pub async fn get_user_by_id(
         &self,
+        tenant: TenantId,
         keycloak_id: &str,
     ) -> Result<Option<KeycloakUser>, KeycloakError> {
-        let token = self.get_token().await?;
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
 
         let response = self
             .http
-            .get(self.config.admin_user_url(keycloak_id))
+            .get(realm.config.admin_user_url(keycloak_id))
             .bearer_auth(&token)
             .send()
             .await?;
@@ -159,12 +442,14 @@ impl KeycloakClient {
     /// Create a new user in Keycloak
     pub async fn create_user(
         &self,
+        tenant: TenantId,
         email: &str,
         first_name: Option<&str>,
         last_name: Option<&str>,
         password: Option<&Secret<String>>,
     ) -> Result<String, KeycloakError> {
-        let token = self.get_token().await?;
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
 
         let credentials = password.map(|pwd| {
             vec![KeycloakCredential {
@@ -185,7 +470,7 @@ impl KeycloakClient {
 
         let response = self
             .http
-            .post(self.config.admin_users_url())
+            .post(realm.config.admin_users_url())
             .bearer_auth(&token)
             .json(&request)
             .send()
@@ -218,21 +503,62 @@ impl KeycloakClient {
     /// Update a user in Keycloak
     pub async fn update_user(
         &self,
+        tenant: TenantId,
         keycloak_id: &str,
         first_name: Option<&str>,
         last_name: Option<&str>,
+        email: Option<&str>,
+    ) -> Result<(), KeycloakError> {
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
+
+        let request = UpdateKeycloakUserRequest::profile(first_name, last_name, email);
+
+        let response = self
+            .http
+            .put(realm.config.admin_user_url(keycloak_id))
+            .bearer_auth(&token)
+            .json(&request)
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::NO_CONTENT => Ok(()),
+            StatusCode::NOT_FOUND => Err(KeycloakError::UserNotFound(keycloak_id.to_string())),
+            StatusCode::CONFLICT => Err(KeycloakError::EmailAlreadyExists(
+                email.unwrap_or_default().to_string(),
+            )),
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                Err(KeycloakError::RequestFailed(format!(
+                    "update user failed with status {status}: {body}"
+                )))
+            }
+        }
+    }
+
+    /// Enable or disable a user's login in Keycloak
+    pub async fn set_user_enabled(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+        enabled: bool,
     ) -> Result<(), KeycloakError> {
-        let token = self.get_token().await?;
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
 
         let request = UpdateKeycloakUserRequest {
-            first_name: first_name.map(String::from),
-            last_name: last_name.map(String::from),
+            username: None,
+            first_name: None,
+            last_name: None,
             email: None,
+            email_verified: None,
+            enabled: Some(enabled),
         };
 
         let response = self
             .http
-            .put(self.config.admin_user_url(keycloak_id))
+            .put(realm.config.admin_user_url(keycloak_id))
             .bearer_auth(&token)
             .json(&request)
             .send()
@@ -244,19 +570,198 @@ impl KeycloakClient {
             status => {
                 let body = response.text().await.unwrap_or_default();
                 Err(KeycloakError::RequestFailed(format!(
-                    "update user failed with status {status}: {body}"
+                    "set user enabled failed with status {status}: {body}"
+                )))
+            }
+        }
+    }
+
+    /// End all of a user's sessions, revoking their refresh tokens
+    pub async fn logout_user(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+    ) -> Result<(), KeycloakError> {
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
+
+        let response = self
+            .http
+            .post(realm.config.admin_user_logout_url(keycloak_id))
+            .bearer_auth(&token)
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::NO_CONTENT => Ok(()),
+            StatusCode::NOT_FOUND => Err(KeycloakError::UserNotFound(keycloak_id.to_string())),
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                Err(KeycloakError::RequestFailed(format!(
+                    "logout user failed with status {status}: {body}"
+                )))
+            }
+        }
+    }
+
+    /// Active sessions of a user
+    pub async fn get_user_sessions(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+    ) -> Result<Vec<KeycloakUserSession>, KeycloakError> {
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
+
+        let response = self
+            .http
+            .get(realm.config.admin_user_sessions_url(keycloak_id))
+            .bearer_auth(&token)
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::OK => response
+                .json::<Vec<KeycloakUserSession>>()
+                .await
+                .map_err(|e| KeycloakError::InvalidResponse(e.to_string())),
+            StatusCode::NOT_FOUND => Err(KeycloakError::UserNotFound(keycloak_id.to_string())),
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                Err(KeycloakError::RequestFailed(format!(
+                    "get user sessions failed with status {status}: {body}"
+                )))
+            }
+        }
+    }
+
+    /// End one session of the realm
+    pub async fn delete_session(
+        &self,
+        tenant: TenantId,
+        session_id: &str,
+    ) -> Result<(), KeycloakError> {
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
+
+        let response = self
+            .http
+            .delete(realm.config.admin_session_url(session_id))
+            .bearer_auth(&token)
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::NO_CONTENT => Ok(()),
+            StatusCode::NOT_FOUND => Err(KeycloakError::SessionNotFound(session_id.to_string())),
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                Err(KeycloakError::RequestFailed(format!(
+                    "delete session failed with status {status}: {body}"
+                )))
+            }
+        }
+    }
+
+    /// Set a user's password. A temporary password must be changed at the next login.
+    pub async fn reset_password(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+        password: &Secret<String>,
+        temporary: bool,
+    ) -> Result<(), KeycloakError> {
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
+
+        let credential = KeycloakCredential {
+            credential_type: "password".to_string(),
+            value: password.expose_secret().clone(),
+            temporary,
+        };
+
+        let response = self
+            .http
+            .put(realm.config.admin_user_reset_password_url(keycloak_id))
+            .bearer_auth(&token)
+            .json(&credential)
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::NO_CONTENT => Ok(()),
+            StatusCode::NOT_FOUND => Err(KeycloakError::UserNotFound(keycloak_id.to_string())),
+            StatusCode::BAD_REQUEST => {
+                let body = response.text().await.unwrap_or_default();
+                Err(KeycloakError::InvalidPassword(
+                    KeycloakErrorResponse::message(&body),
+                ))
+            }
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                Err(KeycloakError::RequestFailed(format!(
+                    "reset password failed with status {status}: {body}"
+                )))
+            }
+        }
+    }
+
+    /// Email the user a link to complete `actions`. The link is valid for `lifespan`, or
+    /// the realm default when `None`.
+    pub async fn execute_actions_email(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+        actions: &[RequiredAction],
+        lifespan: Option<Duration>,
+    ) -> Result<(), KeycloakError> {
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
+
+        let mut request = self
+            .http
+            .put(
+                realm
+                    .config
+                    .admin_user_execute_actions_email_url(keycloak_id),
+            )
+            .bearer_auth(&token)
+            .json(actions);
+        if let Some(lifespan) = lifespan {
+            request = request.query(&[("lifespan", lifespan.as_secs())]);
+        }
+        let response = request.send().await?;
+
+        match response.status() {
+            StatusCode::NO_CONTENT => Ok(()),
+            StatusCode::NOT_FOUND => Err(KeycloakError::UserNotFound(keycloak_id.to_string())),
+            StatusCode::BAD_REQUEST => {
+                let body = response.text().await.unwrap_or_default();
+                Err(KeycloakError::ActionsRejected(
+                    KeycloakErrorResponse::message(&body),
+                ))
+            }
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                Err(KeycloakError::RequestFailed(format!(
+                    "execute actions email failed with status {status}: {body}"
                 )))
             }
         }
     }
 
     /// Delete a user from Keycloak
-    pub async fn delete_user(&self, keycloak_id: &str) -> Result<(), KeycloakError> {
-        let token = self.get_token().await?;
+    pub async fn delete_user(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+    ) -> Result<(), KeycloakError> {
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
 
         let response = self
             .http
-            .delete(self.config.admin_user_url(keycloak_id))
+            .delete(realm.config.admin_user_url(keycloak_id))
             .bearer_auth(&token)
             .send()
             .await?;
@@ -276,33 +781,734 @@ impl KeycloakClient {
         }
     }
 
-    /// Get users by email (for lookup during sync)
-    #[allow(dead_code)]
-    pub async fn get_users_by_email(
+    /// Create a group in Keycloak, returning its id
+    pub async fn create_group(
         &self,
-        email: &str,
-    ) -> Result<Vec<KeycloakUser>, KeycloakError> {
-        let token = self.get_token().await?;
+        tenant: TenantId,
+        name: &str,
+    ) -> Result<String, KeycloakError> {
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
 
-        let url = format!(
-            "{}?email={}&exact=true",
-            self.config.admin_users_url(),
-            email
-        );
+        let response = self
+            .http
+            .post(realm.config.admin_groups_url())
+            .bearer_auth(&token)
+            .json(&KeycloakGroupRequest {
+                name: name.to_string(),
+            })
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::CREATED => {
+                // Location format: http://host/admin/realms/{realm}/groups/{id}
+                if let Some(id) = response
+                    .headers()
+                    .get("location")
+                    .and_then(|l| l.to_str().ok())
+                    .and_then(|l| l.rsplit('/').next())
+                {
+                    return Ok(id.to_string());
+                }
+                Err(KeycloakError::InvalidResponse(
+                    "missing Location header in create response".to_string(),
+                ))
+            }
+            StatusCode::CONFLICT => Err(KeycloakError::GroupAlreadyExists(name.to_string())),
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                Err(KeycloakError::RequestFailed(format!(
+                    "create group failed with status {status}: {body}"
+                )))
+            }
+        }
+    }
 
-        let response = self.http.get(&url).bearer_auth(&token).send().await?;
+    /// Rename a group in Keycloak
+    pub async fn rename_group(
+        &self,
+        tenant: TenantId,
+        group_id: &str,
+        name: &str,
+    ) -> Result<(), KeycloakError> {
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(KeycloakError::RequestFailed(format!(
-                "search users failed with status {status}: {body}"
-            )));
+        let response = self
+            .http
+            .put(realm.config.admin_group_url(group_id))
+            .bearer_auth(&token)
+            .json(&KeycloakGroupRequest {
+                name: name.to_string(),
+            })
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::NO_CONTENT => Ok(()),
+            StatusCode::NOT_FOUND => Err(KeycloakError::GroupNotFound(group_id.to_string())),
+            StatusCode::CONFLICT => Err(KeycloakError::GroupAlreadyExists(name.to_string())),
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                Err(KeycloakError::RequestFailed(format!(
+                    "rename group failed with status {status}: {body}"
+                )))
+            }
         }
+    }
 
-        response
-            .json::<Vec<KeycloakUser>>()
-            .await
+    /// Delete a group from Keycloak; a group that is already gone counts as deleted
+    pub async fn delete_group(
+        &self,
+        tenant: TenantId,
+        group_id: &str,
+    ) -> Result<(), KeycloakError> {
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
+
+        let response = self
+            .http
+            .delete(realm.config.admin_group_url(group_id))
+            .bearer_auth(&token)
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::NO_CONTENT | StatusCode::NOT_FOUND => Ok(()),
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                Err(KeycloakError::RequestFailed(format!(
+                    "delete group failed with status {status}: {body}"
+                )))
+            }
+        }
+    }
+
+    /// Add a user to a group in Keycloak; adding an existing member succeeds
+    pub async fn add_user_to_group(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+        group_id: &str,
+    ) -> Result<(), KeycloakError> {
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
+
+        let response = self
+            .http
+            .put(realm.config.admin_user_group_url(keycloak_id, group_id))
+            .bearer_auth(&token)
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::NO_CONTENT => Ok(()),
+            // Keycloak does not say which side is missing
+            StatusCode::NOT_FOUND => Err(KeycloakError::GroupNotFound(group_id.to_string())),
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                Err(KeycloakError::RequestFailed(format!(
+                    "add user to group failed with status {status}: {body}"
+                )))
+            }
+        }
+    }
+
+    /// Remove a user from a group in Keycloak; a missing membership counts as removed
+    pub async fn remove_user_from_group(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+        group_id: &str,
+    ) -> Result<(), KeycloakError> {
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
+
+        let response = self
+            .http
+            .delete(realm.config.admin_user_group_url(keycloak_id, group_id))
+            .bearer_auth(&token)
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::NO_CONTENT | StatusCode::NOT_FOUND => Ok(()),
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                Err(KeycloakError::RequestFailed(format!(
+                    "remove user from group failed with status {status}: {body}"
+                )))
+            }
+        }
+    }
+
+    /// Create a realm role. Returns false if a role with this name already existed, in
+    /// which case it is left untouched.
+    pub async fn create_realm_role(
+        &self,
+        tenant: TenantId,
+        name: &str,
+        description: Option<&str>,
+    ) -> Result<bool, KeycloakError> {
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
+
+        let response = self
+            .http
+            .post(realm.config.admin_roles_url())
+            .bearer_auth(&token)
+            .json(&KeycloakRoleRequest {
+                name: name.to_string(),
+                description: description.map(String::from),
+            })
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::CREATED => Ok(true),
+            StatusCode::CONFLICT => Ok(false),
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                Err(KeycloakError::RequestFailed(format!(
+                    "create realm role failed with status {status}: {body}"
+                )))
+            }
+        }
+    }
+
+    /// Get a realm role by name
+    pub async fn get_realm_role(
+        &self,
+        tenant: TenantId,
+        name: &str,
+    ) -> Result<Option<KeycloakRole>, KeycloakError> {
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
+
+        let response = self
+            .http
+            .get(realm.config.admin_role_url(name))
+            .bearer_auth(&token)
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::OK => response
+                .json::<KeycloakRole>()
+                .await
+                .map(Some)
+                .map_err(|e| KeycloakError::InvalidResponse(e.to_string())),
+            StatusCode::NOT_FOUND => Ok(None),
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                Err(KeycloakError::RequestFailed(format!(
+                    "get realm role failed with status {status}: {body}"
+                )))
+            }
+        }
+    }
+
+    /// Get a realm role by name, creating it first if it does not exist yet
+    pub async fn ensure_realm_role(
+        &self,
+        tenant: TenantId,
+        name: &str,
+        description: Option<&str>,
+    ) -> Result<KeycloakRole, KeycloakError> {
+        if let Some(role) = self.get_realm_role(tenant, name).await? {
+            return Ok(role);
+        }
+        self.create_realm_role(tenant, name, description).await?;
+        self.get_realm_role(tenant, name)
+            .await?
+            .ok_or_else(|| KeycloakError::RoleNotFound(name.to_string()))
+    }
+
+    /// Rename a realm role and set its description. Role mappings follow the role.
+    pub async fn update_realm_role(
+        &self,
+        tenant: TenantId,
+        current_name: &str,
+        name: &str,
+        description: Option<&str>,
+    ) -> Result<(), KeycloakError> {
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
+
+        let response = self
+            .http
+            .put(realm.config.admin_role_url(current_name))
+            .bearer_auth(&token)
+            .json(&KeycloakRoleRequest {
+                name: name.to_string(),
+                description: Some(description.unwrap_or_default().to_string()),
+            })
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::NO_CONTENT => Ok(()),
+            StatusCode::NOT_FOUND => Err(KeycloakError::RoleNotFound(current_name.to_string())),
+            StatusCode::CONFLICT => Err(KeycloakError::RoleAlreadyExists(name.to_string())),
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                Err(KeycloakError::RequestFailed(format!(
+                    "update realm role failed with status {status}: {body}"
+                )))
+            }
+        }
+    }
+
+    /// Delete a realm role, and with it every mapping of the role; a role that is already
+    /// gone counts as deleted
+    pub async fn delete_realm_role(
+        &self,
+        tenant: TenantId,
+        name: &str,
+    ) -> Result<(), KeycloakError> {
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
+
+        let response = self
+            .http
+            .delete(realm.config.admin_role_url(name))
+            .bearer_auth(&token)
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::NO_CONTENT | StatusCode::NOT_FOUND => Ok(()),
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                Err(KeycloakError::RequestFailed(format!(
+                    "delete realm role failed with status {status}: {body}"
+                )))
+            }
+        }
+    }
+
+    /// Realm roles mapped directly to a user (not those it gets through groups or
+    /// composite roles)
+    pub async fn get_user_realm_roles(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+    ) -> Result<Vec<KeycloakRole>, KeycloakError> {
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
+
+        let response = self
+            .http
+            .get(realm.config.admin_user_role_mappings_url(keycloak_id))
+            .bearer_auth(&token)
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::OK => response
+                .json::<Vec<KeycloakRole>>()
+                .await
+                .map_err(|e| KeycloakError::InvalidResponse(e.to_string())),
+            StatusCode::NOT_FOUND => Err(KeycloakError::UserNotFound(keycloak_id.to_string())),
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                Err(KeycloakError::RequestFailed(format!(
+                    "get user realm roles failed with status {status}: {body}"
+                )))
+            }
+        }
+    }
+
+    /// Map realm roles to a user; roles it already has are kept
+    pub async fn add_user_realm_roles(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+        roles: &[KeycloakRole],
+    ) -> Result<(), KeycloakError> {
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
+
+        let response = self
+            .http
+            .post(realm.config.admin_user_role_mappings_url(keycloak_id))
+            .bearer_auth(&token)
+            .json(roles)
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::NO_CONTENT => Ok(()),
+            StatusCode::NOT_FOUND => Err(KeycloakError::UserNotFound(keycloak_id.to_string())),
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                Err(KeycloakError::RequestFailed(format!(
+                    "add user realm roles failed with status {status}: {body}"
+                )))
+            }
+        }
+    }
+
+    /// Remove realm role mappings from a user; a user that is gone has none left
+    pub async fn remove_user_realm_roles(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+        roles: &[KeycloakRole],
+    ) -> Result<(), KeycloakError> {
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
+
+        let response = self
+            .http
+            .delete(realm.config.admin_user_role_mappings_url(keycloak_id))
+            .bearer_auth(&token)
+            .json(roles)
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::NO_CONTENT | StatusCode::NOT_FOUND => Ok(()),
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                Err(KeycloakError::RequestFailed(format!(
+                    "remove user realm roles failed with status {status}: {body}"
+                )))
+            }
+        }
+    }
+
+    /// Map realm roles to a group, so its members' tokens carry them
+    pub async fn add_group_realm_roles(
+        &self,
+        tenant: TenantId,
+        group_id: &str,
+        roles: &[KeycloakRole],
+    ) -> Result<(), KeycloakError> {
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
+
+        let response = self
+            .http
+            .post(realm.config.admin_group_role_mappings_url(group_id))
+            .bearer_auth(&token)
+            .json(roles)
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::NO_CONTENT => Ok(()),
+            StatusCode::NOT_FOUND => Err(KeycloakError::GroupNotFound(group_id.to_string())),
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                Err(KeycloakError::RequestFailed(format!(
+                    "add group realm roles failed with status {status}: {body}"
+                )))
+            }
+        }
+    }
+
+    /// Remove realm role mappings from a group; a group that is gone has none left
+    pub async fn remove_group_realm_roles(
+        &self,
+        tenant: TenantId,
+        group_id: &str,
+        roles: &[KeycloakRole],
+    ) -> Result<(), KeycloakError> {
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
+
+        let response = self
+            .http
+            .delete(realm.config.admin_group_role_mappings_url(group_id))
+            .bearer_auth(&token)
+            .json(roles)
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::NO_CONTENT | StatusCode::NOT_FOUND => Ok(()),
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                Err(KeycloakError::RequestFailed(format!(
+                    "remove group realm roles failed with status {status}: {body}"
+                )))
+            }
+        }
+    }
+
+    /// Get the users whose email is exactly `email`
+    pub async fn get_users_by_email(
+        &self,
+        tenant: TenantId,
+        email: &str,
+    ) -> Result<Vec<KeycloakUser>, KeycloakError> {
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
+
+        let response = self
+            .http
+            .get(realm.config.admin_users_url())
+            .query(&[("email", email), ("exact", "true")])
+            .bearer_auth(&token)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(KeycloakError::RequestFailed(format!(
+                "search users failed with status {status}: {body}"
+            )));
+        }
+
+        response
+            .json::<Vec<KeycloakUser>>()
+            .await
+            .map_err(|e| KeycloakError::InvalidResponse(e.to_string()))
+    }
+
+    /// One page of the realm's stored admin events about users, newest first, from
+    /// `date_from` on. The realm must have admin events enabled and saved.
+    pub async fn get_admin_events(
+        &self,
+        tenant: TenantId,
+        date_from: NaiveDate,
+        first: usize,
+        max: usize,
+    ) -> Result<Vec<KeycloakAdminEvent>, KeycloakError> {
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
+
+        let query = [
+            ("resourceTypes", "USER".to_string()),
+            ("dateFrom", date_from.format("%Y-%m-%d").to_string()),
+            ("first", first.to_string()),
+            ("max", max.to_string()),
+        ];
+        let response = self
+            .http
+            .get(realm.config.admin_events_url())
+            .query(&query)
+            .bearer_auth(&token)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(KeycloakError::RequestFailed(format!(
+                "get admin events failed with status {status}: {body}"
+            )));
+        }
+
+        response
+            .json::<Vec<KeycloakAdminEvent>>()
+            .await
+            .map_err(|e| KeycloakError::InvalidResponse(e.to_string()))
+    }
+
+    /// One page of the realm's stored user events of the given types, newest first, from
+    /// `date_from` on. The realm must have user events enabled and saved.
+    pub async fn get_events(
+        &self,
+        tenant: TenantId,
+        types: &[&str],
+        date_from: NaiveDate,
+        first: usize,
+        max: usize,
+    ) -> Result<Vec<KeycloakEvent>, KeycloakError> {
+        let realm = self.realm(tenant)?;
+        let token = self.get_token(&realm).await?;
+
+        let mut query: Vec<(&str, String)> =
+            types.iter().map(|t| ("type", t.to_string())).collect();
+        query.push(("dateFrom", date_from.format("%Y-%m-%d").to_string()));
+        query.push(("first", first.to_string()));
+        query.push(("max", max.to_string()));
+        let response = self
+            .http
+            .get(realm.config.events_url())
+            .query(&query)
+            .bearer_auth(&token)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(KeycloakError::RequestFailed(format!(
+                "get events failed with status {status}: {body}"
+            )));
+        }
+
+        response
+            .json::<Vec<KeycloakEvent>>()
+            .await
             .map_err(|e| KeycloakError::InvalidResponse(e.to_string()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACME: &str = "6f1c1d52-3a4e-4c43-9a0b-2f8f1b3c9d10";
+
+    fn default_config() -> KeycloakConfig {
+        KeycloakConfig {
+            base_url: "http://keycloak:8080".to_string(),
+            realm: "master".to_string(),
+            client_id: "user-api-service".to_string(),
+            client_secret: String::new(),
+            profile_cache_ttl: Duration::from_secs(300),
+        }
+    }
+
+    fn realms_file(name: &str, json: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("{name}-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, json).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_tenants_resolve_to_their_realm() {
+        let path = realms_file(
+            "realms",
+            &format!(r#"{{"{ACME}": {{"realm": "acme", "client_secret": "acme-secret"}}}}"#),
+        );
+        let client = KeycloakClient::new(default_config())
+            .with_realms_file(path.clone())
+            .unwrap();
+        let acme: TenantId = ACME.parse().unwrap();
+
+        assert_eq!(client.realm(acme).unwrap().config.realm, "acme");
+        assert!(client.is_configured(acme));
+        assert_eq!(
+            client.realm(TenantId::DEFAULT).unwrap().config.realm,
+            "master"
+        );
+        assert!(!client.is_configured(TenantId::DEFAULT));
+        assert_eq!(
+            client.realms(),
+            vec![
+                (TenantId::DEFAULT, "master".to_string()),
+                (acme, "acme".to_string())
+            ]
+        );
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_tenants_missing_from_the_realms_file_are_refused() {
+        let path = realms_file("missing", &format!(r#"{{"{ACME}": {{"realm": "acme"}}}}"#));
+        let other: TenantId = "0b6f7a3e-6f0e-4a53-8f39-2f4a8c2d1e55".parse().unwrap();
+        let client = KeycloakClient::new(default_config())
+            .with_realms_file(path.clone())
+            .unwrap();
+
+        assert!(matches!(
+            client.realm(other),
+            Err(KeycloakError::NotConfigured)
+        ));
+        assert!(!client.is_configured(other));
+        assert!(!client.shares_realm(other, TenantId::DEFAULT));
+
+        // Opting in lets them share the default realm
+        let client = KeycloakClient::new(default_config())
+            .with_shared_realm_fallback(true)
+            .with_realms_file(path.clone())
+            .unwrap();
+        assert_eq!(client.realm(other).unwrap().config.realm, "master");
+        assert!(client.shares_realm(other, TenantId::DEFAULT));
+
+        // Without a realms file there is only the default realm
+        let client = KeycloakClient::new(default_config());
+        assert_eq!(client.realm(other).unwrap().config.realm, "master");
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_reload_keeps_unchanged_realms_and_survives_bad_files() {
+        let acme: TenantId = ACME.parse().unwrap();
+        let other = "0b6f7a3e-6f0e-4a53-8f39-2f4a8c2d1e55";
+        let path = realms_file("reload", &format!(r#"{{"{ACME}": {{"realm": "acme"}}}}"#));
+        let client = KeycloakClient::new(default_config())
+            .with_realms_file(path.clone())
+            .unwrap();
+        let other: TenantId = other.parse().unwrap();
+        let before = client.realm(acme).unwrap();
+
+        std::fs::write(
+            &path,
+            format!(r#"{{"{ACME}": {{"realm": "acme"}}, "{other}": {{"realm": "other"}}}}"#),
+        )
+        .unwrap();
+        assert_eq!(client.reload_realms().unwrap(), vec![other]);
+        assert_eq!(client.realms().len(), 3);
+        assert!(Arc::ptr_eq(&before, &client.realm(acme).unwrap()));
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(matches!(
+            client.reload_realms(),
+            Err(KeycloakError::InvalidRealmConfig(_))
+        ));
+        assert_eq!(client.realm(other).unwrap().config.realm, "other");
+
+        // Dropping a tenant's entry changes its realm too
+        std::fs::write(&path, format!(r#"{{"{ACME}": {{"realm": "acme"}}}}"#)).unwrap();
+        assert_eq!(client.reload_realms().unwrap(), vec![other]);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_keycloak_error_message_prefers_description() {
+        let body = r#"{"error": "invalidPasswordMinLengthMessage", "error_description": "Invalid password: minimum length 12."}"#;
+        assert_eq!(
+            KeycloakErrorResponse::message(body),
+            "Invalid password: minimum length 12."
+        );
+        assert_eq!(
+            KeycloakErrorResponse::message(r#"{"errorMessage": "User without email"}"#),
+            "User without email"
+        );
+        assert_eq!(KeycloakErrorResponse::message("Bad Gateway"), "Bad Gateway");
+    }
+
+    #[test]
+    fn test_new_email_is_sent_unverified_as_the_username() {
+        let request = UpdateKeycloakUserRequest::profile(None, None, Some("new@example.com"));
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({
+                "username": "new@example.com",
+                "email": "new@example.com",
+                "emailVerified": false
+            })
+        );
+
+        let request = UpdateKeycloakUserRequest::profile(Some("Ann"), None, None);
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({"firstName": "Ann"})
+        );
+    }
+
+    #[test]
+    fn test_admin_events_are_parsed_with_their_user() {
+        let body = r#"[{"time": 1700000000000, "realmId": "r", "operationType": "DELETE",
+            "resourceType": "USER", "resourcePath": "users/kc-1", "authDetails": {}}]"#;
+        let events: Vec<KeycloakAdminEvent> = serde_json::from_str(body).unwrap();
+        assert_eq!(events[0].user_id(), Some("kc-1"));
+        assert!(events[0].is_user_deletion());
+
+        let body = r#"[{"time": 1, "type": "LOGIN", "userId": "kc-1", "ipAddress": "10.0.0.7"}]"#;
+        let events: Vec<KeycloakEvent> = serde_json::from_str(body).unwrap();
+        assert_eq!(events[0].event_type, "LOGIN");
+        assert_eq!(events[0].user_id.as_deref(), Some("kc-1"));
+    }
+}
@@ -1,18 +1,26 @@
 use secrets::SecretsClient;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
+use user_lib::entities::TenantId;
+
+use super::errors::KeycloakError;
 
 const KEYCLOAK_URL: &str = "KEYCLOAK_URL";
 const KEYCLOAK_REALM: &str = "KEYCLOAK_REALM";
 const KEYCLOAK_CLIENT_ID: &str = "KEYCLOAK_CLIENT_ID";
 const KEYCLOAK_CLIENT_SECRET: &str = "KEYCLOAK_CLIENT_SECRET";
 const KEYCLOAK_PROFILE_CACHE_TTL_SECS: &str = "KEYCLOAK_PROFILE_CACHE_TTL_SECS";
+const KEYCLOAK_REALMS_FILE: &str = "KEYCLOAK_REALMS_FILE";
+const KEYCLOAK_SHARED_REALM_FALLBACK: &str = "KEYCLOAK_SHARED_REALM_FALLBACK";
 
 const DEFAULT_KEYCLOAK_URL: &str = "http://localhost:18080";
 const DEFAULT_KEYCLOAK_REALM: &str = "master";
 const DEFAULT_KEYCLOAK_CLIENT_ID: &str = "user-api-service";
 const DEFAULT_PROFILE_CACHE_TTL_SECS: u64 = 300;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct KeycloakConfig {
     pub base_url: String,
     pub realm: String,
@@ -89,6 +97,14 @@ impl KeycloakConfig {
         )
     }
 
+    /// Public keys the realm signs its tokens with
+    pub fn certs_url(&self) -> String {
+        format!(
+            "{}/realms/{}/protocol/openid-connect/certs",
+            self.base_url, self.realm
+        )
+    }
+
     pub fn admin_users_url(&self) -> String {
         format!("{}/admin/realms/{}/users", self.base_url, self.realm)
     }
@@ -100,7 +116,245 @@ impl KeycloakConfig {
         )
     }
 
+    pub fn admin_user_logout_url(&self, keycloak_id: &str) -> String {
+        format!(
+            "{}/admin/realms/{}/users/{}/logout",
+            self.base_url, self.realm, keycloak_id
+        )
+    }
+
+    pub fn admin_user_sessions_url(&self, keycloak_id: &str) -> String {
+        format!(
+            "{}/admin/realms/{}/users/{}/sessions",
+            self.base_url, self.realm, keycloak_id
+        )
+    }
+
+    pub fn admin_session_url(&self, session_id: &str) -> String {
+        with_segment(
+            &format!("{}/admin/realms/{}/sessions", self.base_url, self.realm),
+            session_id,
+        )
+    }
+
+    pub fn admin_events_url(&self) -> String {
+        format!("{}/admin/realms/{}/admin-events", self.base_url, self.realm)
+    }
+
+    pub fn events_url(&self) -> String {
+        format!("{}/admin/realms/{}/events", self.base_url, self.realm)
+    }
+
+    pub fn admin_user_reset_password_url(&self, keycloak_id: &str) -> String {
+        format!(
+            "{}/admin/realms/{}/users/{}/reset-password",
+            self.base_url, self.realm, keycloak_id
+        )
+    }
+
+    pub fn admin_user_execute_actions_email_url(&self, keycloak_id: &str) -> String {
+        format!(
+            "{}/admin/realms/{}/users/{}/execute-actions-email",
+            self.base_url, self.realm, keycloak_id
+        )
+    }
+
+    pub fn admin_groups_url(&self) -> String {
+        format!("{}/admin/realms/{}/groups", self.base_url, self.realm)
+    }
+
+    pub fn admin_group_url(&self, group_id: &str) -> String {
+        format!(
+            "{}/admin/realms/{}/groups/{}",
+            self.base_url, self.realm, group_id
+        )
+    }
+
+    pub fn admin_user_group_url(&self, keycloak_id: &str, group_id: &str) -> String {
+        format!(
+            "{}/admin/realms/{}/users/{}/groups/{}",
+            self.base_url, self.realm, keycloak_id, group_id
+        )
+    }
+
+    pub fn admin_roles_url(&self) -> String {
+        format!("{}/admin/realms/{}/roles", self.base_url, self.realm)
+    }
+
+    /// Role names may contain characters that are not allowed in a path segment, so the
+    /// name is percent-encoded
+    pub fn admin_role_url(&self, role_name: &str) -> String {
+        with_segment(&self.admin_roles_url(), role_name)
+    }
+
+    pub fn admin_user_role_mappings_url(&self, keycloak_id: &str) -> String {
+        format!(
+            "{}/admin/realms/{}/users/{}/role-mappings/realm",
+            self.base_url, self.realm, keycloak_id
+        )
+    }
+
+    pub fn admin_group_role_mappings_url(&self, group_id: &str) -> String {
+        format!(
+            "{}/admin/realms/{}/groups/{}/role-mappings/realm",
+            self.base_url, self.realm, group_id
+        )
+    }
+
     pub fn is_configured(&self) -> bool {
         !self.client_secret.is_empty()
     }
+
+    /// Path of the tenant realms file from `KEYCLOAK_REALMS_FILE`, if set
+    pub fn realms_file() -> Option<PathBuf> {
+        std::env::var(KEYCLOAK_REALMS_FILE)
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .map(PathBuf::from)
+    }
+
+    /// Whether tenants missing from the realms file use `KEYCLOAK_REALM`, from
+    /// `KEYCLOAK_SHARED_REALM_FALLBACK` (off by default)
+    pub fn shared_realm_fallback() -> bool {
+        std::env::var(KEYCLOAK_SHARED_REALM_FALLBACK)
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false)
+    }
+
+    /// This configuration pointed at another realm; fields the entry leaves out are kept
+    fn for_realm(&self, entry: RealmEntry) -> Self {
+        Self {
+            base_url: entry.base_url.unwrap_or_else(|| self.base_url.clone()),
+            realm: entry.realm,
+            client_id: entry.client_id.unwrap_or_else(|| self.client_id.clone()),
+            client_secret: entry
+                .client_secret
+                .unwrap_or_else(|| self.client_secret.clone()),
+            profile_cache_ttl: self.profile_cache_ttl,
+        }
+    }
+}
+
+/// One tenant's realm in the realms file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RealmEntry {
+    realm: String,
+    base_url: Option<String>,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+}
+
+/// Read the realms file: a JSON object from tenant ID to realm, e.g.
+/// `{"<tenant uuid>": {"realm": "acme", "client_secret": "..."}}`.
+/// `base_url`, `client_id` and `client_secret` default to those of `default`.
+pub fn load_realms(
+    path: &Path,
+    default: &KeycloakConfig,
+) -> Result<HashMap<TenantId, KeycloakConfig>, KeycloakError> {
+    let json = std::fs::read_to_string(path).map_err(|e| {
+        KeycloakError::InvalidRealmConfig(format!("cannot read {}: {e}", path.display()))
+    })?;
+    parse_realms(&json, default)
+}
+
+fn parse_realms(
+    json: &str,
+    default: &KeycloakConfig,
+) -> Result<HashMap<TenantId, KeycloakConfig>, KeycloakError> {
+    let entries: HashMap<String, RealmEntry> =
+        serde_json::from_str(json).map_err(|e| KeycloakError::InvalidRealmConfig(e.to_string()))?;
+
+    entries
+        .into_iter()
+        .map(|(tenant, entry)| {
+            let tenant = tenant.parse::<TenantId>().map_err(|_| {
+                KeycloakError::InvalidRealmConfig(format!("invalid tenant id: {tenant}"))
+            })?;
+            if entry.realm.trim().is_empty() {
+                return Err(KeycloakError::InvalidRealmConfig(format!(
+                    "empty realm for tenant {tenant}"
+                )));
+            }
+            Ok((tenant, default.for_realm(entry)))
+        })
+        .collect()
+}
+
+/// `base` with `segment` appended as one percent-encoded path segment
+fn with_segment(base: &str, segment: &str) -> String {
+    match reqwest::Url::parse(base) {
+        Ok(mut url) => {
+            if let Ok(mut segments) = url.path_segments_mut() {
+                segments.push(segment);
+            }
+            url.to_string()
+        }
+        Err(_) => format!("{base}/{segment}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TENANT: &str = "6f1c1d52-3a4e-4c43-9a0b-2f8f1b3c9d10";
+
+    fn default_config() -> KeycloakConfig {
+        KeycloakConfig {
+            base_url: "http://keycloak:8080".to_string(),
+            realm: "master".to_string(),
+            client_id: "user-api-service".to_string(),
+            client_secret: "default-secret".to_string(),
+            profile_cache_ttl: Duration::from_secs(300),
+        }
+    }
+
+    #[test]
+    fn test_realm_entries_inherit_missing_fields() {
+        let json =
+            format!(r#"{{"{TENANT}": {{"realm": "acme", "client_secret": "acme-secret"}}}}"#);
+        let realms = parse_realms(&json, &default_config()).unwrap();
+
+        let acme = &realms[&TENANT.parse().unwrap()];
+        assert_eq!(acme.realm, "acme");
+        assert_eq!(acme.client_secret, "acme-secret");
+        assert_eq!(acme.base_url, "http://keycloak:8080");
+        assert_eq!(acme.client_id, "user-api-service");
+        assert_eq!(
+            acme.admin_users_url(),
+            "http://keycloak:8080/admin/realms/acme/users"
+        );
+    }
+
+    #[test]
+    fn test_role_names_are_encoded_in_role_urls() {
+        assert_eq!(
+            default_config().admin_role_url("billing admin/eu"),
+            "http://keycloak:8080/admin/realms/master/roles/billing%20admin%2Feu"
+        );
+    }
+
+    #[test]
+    fn test_session_ids_are_encoded_in_session_urls() {
+        assert_eq!(
+            default_config().admin_session_url("../users"),
+            "http://keycloak:8080/admin/realms/master/sessions/..%2Fusers"
+        );
+    }
+
+    #[test]
+    fn test_invalid_realm_files_are_rejected() {
+        for json in [
+            r#"{"acme": {"realm": "acme"}}"#.to_string(),
+            format!(r#"{{"{TENANT}": {{"realm": " "}}}}"#),
+            format!(r#"{{"{TENANT}": {{"realm": "acme", "secret": "typo"}}}}"#),
+            "[]".to_string(),
+        ] {
+            assert!(matches!(
+                parse_realms(&json, &default_config()),
+                Err(KeycloakError::InvalidRealmConfig(_))
+            ));
+        }
+    }
 }
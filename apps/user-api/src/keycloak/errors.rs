@@ -8,12 +8,32 @@ pub enum KeycloakError {
     UserNotFound(String),
     /// User already exists in Keycloak
     UserAlreadyExists(String),
+    /// Another user of the realm already has this email
+    EmailAlreadyExists(String),
+    /// Group not found in Keycloak
+    GroupNotFound(String),
+    /// A group with this name already exists in Keycloak
+    GroupAlreadyExists(String),
+    /// Realm role not found in Keycloak
+    RoleNotFound(String),
+    /// A realm role with this name already exists in Keycloak
+    RoleAlreadyExists(String),
+    /// No such session for the user
+    SessionNotFound(String),
+    /// Keycloak rejected a new password, e.g. for violating the password policy
+    InvalidPassword(String),
+    /// Keycloak refused to send the required actions email, e.g. for a user without email
+    ActionsRejected(String),
     /// HTTP request failed
     RequestFailed(String),
     /// Invalid response from Keycloak
     InvalidResponse(String),
     /// Keycloak is not configured
     NotConfigured,
+    /// The tenant realms file could not be loaded
+    InvalidRealmConfig(String),
+    /// A user's access token is malformed, expired or not signed by a known realm
+    InvalidToken(String),
     /// Internal error
     #[allow(dead_code)]
     Internal(String),
@@ -24,14 +44,34 @@ impl fmt::Display for KeycloakError {
         match self {
             KeycloakError::TokenError(msg) => write!(f, "token error: {msg}"),
             KeycloakError::UserNotFound(id) => write!(f, "user not found in keycloak: {id}"),
+            KeycloakError::EmailAlreadyExists(email) => {
+                write!(f, "email already exists: {email}")
+            }
             KeycloakError::UserAlreadyExists(email) => {
                 write!(f, "user already exists in keycloak: {email}")
             }
+            KeycloakError::GroupNotFound(id) => write!(f, "group not found in keycloak: {id}"),
+            KeycloakError::GroupAlreadyExists(name) => {
+                write!(f, "group already exists in keycloak: {name}")
+            }
+            KeycloakError::RoleNotFound(name) => {
+                write!(f, "realm role not found in keycloak: {name}")
+            }
+            KeycloakError::RoleAlreadyExists(name) => {
+                write!(f, "realm role already exists in keycloak: {name}")
+            }
+            KeycloakError::SessionNotFound(id) => write!(f, "session not found: {id}"),
+            KeycloakError::InvalidPassword(msg) => write!(f, "invalid password: {msg}"),
+            KeycloakError::ActionsRejected(msg) => {
+                write!(f, "required actions email rejected: {msg}")
+            }
             KeycloakError::RequestFailed(msg) => write!(f, "keycloak request failed: {msg}"),
             KeycloakError::InvalidResponse(msg) => {
                 write!(f, "invalid response from keycloak: {msg}")
             }
             KeycloakError::NotConfigured => write!(f, "keycloak is not configured"),
+            KeycloakError::InvalidRealmConfig(msg) => write!(f, "invalid keycloak realms: {msg}"),
+            KeycloakError::InvalidToken(msg) => write!(f, "invalid access token: {msg}"),
             KeycloakError::Internal(msg) => write!(f, "internal keycloak error: {msg}"),
         }
     }
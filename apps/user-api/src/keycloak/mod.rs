@@ -6,4 +6,7 @@ mod models;
 pub use client::KeycloakClient;
 pub use config::KeycloakConfig;
 pub use errors::KeycloakError;
-pub use models::{FullUser, KeycloakUser};
+pub use models::{
+    AccessToken, FullUser, KeycloakAdminEvent, KeycloakEvent, KeycloakRole, KeycloakUser,
+    KeycloakUserSession, RequiredAction,
+};
@@ -1,5 +1,8 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use user_lib::entities::Role;
+use std::collections::HashMap;
+use user_lib::entities::{Role, TenantId, UserAttributes, UserSuspension};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 /// User representation from Keycloak Admin API
@@ -58,12 +61,152 @@ pub struct KeycloakCredential {
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateKeycloakUserRequest {
+    /// Kept equal to the email, as users are created with their email as username.
+    /// Keycloak only applies it with "Edit username" enabled on the realm; with "Email as
+    /// username" it follows the email by itself.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub first_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email_verified: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+}
+
+impl UpdateKeycloakUserRequest {
+    /// Profile change; a new email is unverified until the user confirms it
+    pub fn profile(first_name: Option<&str>, last_name: Option<&str>, email: Option<&str>) -> Self {
+        Self {
+            username: email.map(String::from),
+            first_name: first_name.map(String::from),
+            last_name: last_name.map(String::from),
+            email: email.map(String::from),
+            email_verified: email.map(|_| false),
+            enabled: None,
+        }
+    }
+}
+
+/// Active session of a user, from the Keycloak Admin API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeycloakUserSession {
+    pub id: String,
+    #[serde(default)]
+    pub ip_address: Option<String>,
+    /// Epoch milliseconds
+    pub start: i64,
+    /// Epoch milliseconds
+    #[serde(default)]
+    pub last_access: i64,
+    /// Client ids keyed by the clients' internal ids
+    #[serde(default)]
+    pub clients: HashMap<String, String>,
+}
+
+/// Admin event from the Keycloak Admin API: a change made through the admin console or
+/// the Admin REST API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeycloakAdminEvent {
+    /// Epoch milliseconds
+    pub time: i64,
+    /// `CREATE`, `UPDATE`, `DELETE` or `ACTION`
+    pub operation_type: String,
+    #[serde(default)]
+    pub resource_type: Option<String>,
+    /// Path below the realm, e.g. `users/{id}` or `users/{id}/role-mappings/realm`
+    #[serde(default)]
+    pub resource_path: Option<String>,
+}
+
+impl KeycloakAdminEvent {
+    /// Keycloak ID of the user the event is about, if its resource is a user or below one
+    pub fn user_id(&self) -> Option<&str> {
+        let mut segments = self.resource_path.as_deref()?.split('/');
+        match (segments.next(), segments.next()) {
+            (Some("users"), Some(id)) if !id.is_empty() => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Whether the event deleted a user itself, rather than one of its sub-resources
+    pub fn is_user_deletion(&self) -> bool {
+        self.operation_type == "DELETE"
+            && self
+                .resource_path
+                .as_deref()
+                .is_some_and(|path| path.matches('/').count() == 1)
+            && self.user_id().is_some()
+    }
+}
+
+/// User event from the Keycloak Admin API, e.g. a login or a profile update by the user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeycloakEvent {
+    /// Epoch milliseconds
+    pub time: i64,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    #[serde(default)]
+    pub user_id: Option<String>,
+}
+
+/// Action a user must complete at next login, sent by `execute-actions-email`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RequiredAction {
+    UpdatePassword,
+    VerifyEmail,
+    ConfigureTotp,
+}
+
+/// Error body of the Keycloak Admin API
+#[derive(Debug, Deserialize)]
+pub struct KeycloakErrorResponse {
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub error_description: Option<String>,
+    #[serde(default, rename = "errorMessage")]
+    pub error_message: Option<String>,
+}
+
+impl KeycloakErrorResponse {
+    /// The most specific message in `body`, or the body itself if it is not an error object
+    pub fn message(body: &str) -> String {
+        serde_json::from_str::<Self>(body)
+            .ok()
+            .and_then(|e| e.error_description.or(e.error_message).or(e.error))
+            .unwrap_or_else(|| body.to_string())
+    }
+}
+
+/// Request body for creating or renaming a group in Keycloak
+#[derive(Debug, Serialize)]
+pub struct KeycloakGroupRequest {
+    pub name: String,
+}
+
+/// Realm role from the Keycloak Admin API. Role mappings are sent as lists of these.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeycloakRole {
+    pub id: String,
+    pub name: String,
+}
+
+/// Request body for creating or updating a realm role in Keycloak
+#[derive(Debug, Serialize)]
+pub struct KeycloakRoleRequest {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
 /// Token response from Keycloak
@@ -78,6 +221,26 @@ pub struct TokenResponse {
     pub token_type: String,
 }
 
+/// Claims of a user's access token that this service reads
+#[derive(Debug, Deserialize)]
+pub struct AccessTokenClaims {
+    pub iss: String,
+    pub sub: String,
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+}
+
+/// User's access token, checked against the signing keys of the realm that issued it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessToken {
+    /// Keycloak ID of the user
+    pub subject: String,
+    /// Realm that issued the token
+    pub realm: String,
+    /// `tenant_id` claim, for realms that put one in their tokens
+    pub tenant: Option<TenantId>,
+}
+
 /// Merged user data (local DB + Keycloak profile)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FullUser {
@@ -86,6 +249,15 @@ pub struct FullUser {
     pub name: String,
     pub email: Option<String>,
     pub roles: Vec<Role>,
+    pub attributes: UserAttributes,
     pub email_verified: bool,
     pub enabled: bool,
+    /// Locally recorded suspension; `None` while the user is active
+    #[serde(default)]
+    pub suspension: Option<UserSuspension>,
+    #[serde(default)]
+    pub last_login_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub version: i64,
 }
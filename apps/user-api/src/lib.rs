@@ -1,3 +1,4 @@
+pub mod api_key;
 pub mod cache;
 pub mod config;
 pub mod constants;
@@ -8,3 +9,4 @@ pub mod middleware;
 pub mod services;
 pub mod shutdown;
 pub mod state;
+pub mod tenant;
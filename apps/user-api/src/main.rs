@@ -1,3 +1,4 @@
+mod api_key;
 mod cache;
 mod config;
 mod constants;
@@ -8,15 +9,17 @@ mod middleware;
 mod services;
 mod shutdown;
 mod state;
+mod tenant;
 
 use axum::{
     http::{header, HeaderName, Method, StatusCode},
-    middleware::from_fn,
-    routing::{get, post},
+    middleware::{from_fn, from_fn_with_state},
+    routing::{delete, get, post, put},
     Extension, Router,
 };
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::TcpListener;
 use tower_governor::{governor::GovernorConfigBuilder, GovernorLayer};
 use tower_http::{
@@ -33,69 +36,173 @@ use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
 use secrets::SecretsConfig;
-use user_lib::repository::role_repository::RoleRepository;
-use user_lib::repository::user_repository::UserRepository;
-use user_lib::repository::user_role_repository::UserRoleRepository;
+use user_lib::entities::TenantId;
 use user_lib::user_service::UserService;
-use user_lib::util::connect_with_retry;
+use user_lib::util::connect;
 
-use crate::cache::{CacheConfig, CachedUserService, RedisCache};
+use crate::api_key::{ApiKeyScope, API_KEY_HEADER};
+use crate::cache::{Cache, CacheConfig, CachedUserService};
 use crate::config::MiddlewareConfig;
-use crate::constants::{DATABASE_URL, ELASTIC_URL, ENV, LOCAL_ENV, SERVICE, USER_API_PORT};
-use crate::keycloak::{KeycloakClient, KeycloakConfig};
+use crate::constants::{
+    ADMIN_API_TOKEN, DATABASE_URL, ELASTIC_URL, ENV, KEYCLOAK_EVENT_POLL_INTERVAL_SECS,
+    KEYCLOAK_ROLE_SYNC_ENABLED, LOCAL_ENV, ROLE_GRANT_SWEEP_INTERVAL_SECS, SERVICE,
+    SOFT_DELETE_ENABLED, USER_API_PORT,
+};
+use crate::keycloak::{KeycloakClient, KeycloakConfig, RequiredAction};
+use crate::methods::add_group_member::__path_add_group_member;
+use crate::methods::add_group_member::add_group_member;
+use crate::methods::assign_group_role::__path_assign_group_role;
+use crate::methods::assign_group_role::assign_group_role;
 use crate::methods::assign_role::__path_assign_role;
 use crate::methods::assign_role::assign_role;
+use crate::methods::create_api_key::__path_create_api_key;
+use crate::methods::create_api_key::create_api_key;
+use crate::methods::create_group::__path_create_group;
+use crate::methods::create_group::create_group;
 use crate::methods::create_role::__path_create_role;
 use crate::methods::create_role::create_role;
+use crate::methods::create_tenant::__path_create_tenant;
+use crate::methods::create_tenant::create_tenant;
 use crate::methods::create_user::__path_create_user;
 use crate::methods::create_user::create_user;
+use crate::methods::delete_group::__path_delete_group;
+use crate::methods::delete_group::delete_group;
 use crate::methods::delete_role::__path_delete_role;
 use crate::methods::delete_role::delete_role;
 use crate::methods::delete_user::__path_delete_user;
 use crate::methods::delete_user::delete_user;
+use crate::methods::delete_user_session::__path_delete_user_session;
+use crate::methods::delete_user_session::delete_user_session;
 use crate::methods::entities::{
-    CreateRoleRequest, CreateUserRequest, PaginatedResponse, RoleResponse, UpdateRoleRequest,
-    UpdateUserRequest, UserResponse,
+    ApiKeyResponse, AssignRoleRequest, CacheFlushResponse, CacheNamespaceStatsResponse,
+    CacheStatsResponse, CreateApiKeyRequest, CreateGroupRequest, CreateRoleRequest,
+    CreateTenantRequest, CreateUserRequest, ExecuteUserActionsRequest, GroupResponse,
+    IssuedApiKeyResponse, KeycloakRealmResponse, PaginatedResponse, RoleResponse,
+    SetUserPasswordRequest, SuspendUserRequest, TenantResponse, UpdateGroupRequest,
+    UpdateRoleRequest, UpdateUserAttributesRequest, UpdateUserRequest, UserAttributesResponse,
+    UserResponse, UserSessionResponse,
 };
+use crate::methods::execute_user_actions::__path_execute_user_actions;
+use crate::methods::execute_user_actions::execute_user_actions;
+use crate::methods::flush_cache_namespace::__path_flush_cache_namespace;
+use crate::methods::flush_cache_namespace::flush_cache_namespace;
+use crate::methods::flush_user_cache::__path_flush_user_cache;
+use crate::methods::flush_user_cache::flush_user_cache;
+use crate::methods::get_api_keys::__path_get_api_keys;
+use crate::methods::get_api_keys::get_api_keys;
+use crate::methods::get_cache_stats::__path_get_cache_stats;
+use crate::methods::get_cache_stats::get_cache_stats;
+use crate::methods::get_group_by_id::__path_get_group_by_id;
+use crate::methods::get_group_by_id::get_group_by_id;
+use crate::methods::get_group_members::__path_get_group_members;
+use crate::methods::get_group_members::get_group_members;
+use crate::methods::get_groups::__path_get_groups;
+use crate::methods::get_groups::get_groups;
+use crate::methods::get_keycloak_realms::__path_get_keycloak_realms;
+use crate::methods::get_keycloak_realms::get_keycloak_realms;
 use crate::methods::get_role_by_id::__path_get_role_by_id;
 use crate::methods::get_role_by_id::get_role_by_id;
 use crate::methods::get_roles::__path_get_roles;
 use crate::methods::get_roles::get_roles;
+use crate::methods::get_tenants::__path_get_tenants;
+use crate::methods::get_tenants::get_tenants;
 use crate::methods::get_user_by_id::__path_get_user_by_id;
 use crate::methods::get_user_by_id::get_user_by_id;
+use crate::methods::get_user_sessions::__path_get_user_sessions;
+use crate::methods::get_user_sessions::get_user_sessions;
 use crate::methods::get_users::__path_get_users;
 use crate::methods::get_users::get_users;
 use crate::methods::health_check::health_check;
+use crate::methods::logout_user_sessions::__path_logout_user_sessions;
+use crate::methods::logout_user_sessions::logout_user_sessions;
+use crate::methods::reactivate_user::__path_reactivate_user;
+use crate::methods::reactivate_user::reactivate_user;
+use crate::methods::reload_keycloak_realms::__path_reload_keycloak_realms;
+use crate::methods::reload_keycloak_realms::reload_keycloak_realms;
+use crate::methods::remove_group_member::__path_remove_group_member;
+use crate::methods::remove_group_member::remove_group_member;
+use crate::methods::restore_role::__path_restore_role;
+use crate::methods::restore_role::restore_role;
+use crate::methods::restore_user::__path_restore_user;
+use crate::methods::restore_user::restore_user;
+use crate::methods::revoke_api_key::__path_revoke_api_key;
+use crate::methods::revoke_api_key::revoke_api_key;
+use crate::methods::rotate_api_key::__path_rotate_api_key;
+use crate::methods::rotate_api_key::rotate_api_key;
 use crate::methods::routes::{
-    API_V1_PREFIX, ROLES_BY_ID_PATH, ROLES_PATH, SERVICE_DOCS_PATH, SERVICE_HEALTH_PATH,
-    USERS_BY_ID_PATH, USERS_PATH, USER_ROLES_PATH,
+    ADMIN_API_KEYS_PATH, ADMIN_API_KEY_PATH, ADMIN_API_KEY_ROTATE_PATH, ADMIN_CACHE_NAMESPACE_PATH,
+    ADMIN_CACHE_STATS_PATH, ADMIN_CACHE_USER_PATH, ADMIN_KEYCLOAK_REALMS_PATH,
+    ADMIN_KEYCLOAK_REALMS_RELOAD_PATH, ADMIN_ROLE_RESTORE_PATH, ADMIN_TENANTS_PATH,
+    ADMIN_USER_RESTORE_PATH, API_V1_PREFIX, GROUPS_BY_ID_PATH, GROUPS_PATH, GROUP_MEMBERS_PATH,
+    GROUP_MEMBER_PATH, GROUP_ROLES_PATH, ROLES_BY_ID_PATH, ROLES_PATH, SERVICE_DOCS_PATH,
+    SERVICE_HEALTH_PATH, USERS_BY_ID_PATH, USERS_PATH, USER_ACTIONS_PATH, USER_PASSWORD_PATH,
+    USER_REACTIVATE_PATH, USER_ROLES_PATH, USER_SESSIONS_PATH, USER_SESSION_PATH,
+    USER_SUSPEND_PATH,
 };
+use crate::methods::set_user_password::__path_set_user_password;
+use crate::methods::set_user_password::set_user_password;
+use crate::methods::suspend_user::__path_suspend_user;
+use crate::methods::suspend_user::suspend_user;
+use crate::methods::unassign_group_role::__path_unassign_group_role;
+use crate::methods::unassign_group_role::unassign_group_role;
 use crate::methods::unassign_role::__path_unassign_role;
 use crate::methods::unassign_role::unassign_role;
+use crate::methods::update_group::__path_update_group;
+use crate::methods::update_group::update_group;
 use crate::methods::update_role::__path_update_role;
 use crate::methods::update_role::update_role;
 use crate::methods::update_user::__path_update_user;
 use crate::methods::update_user::update_user;
+use crate::methods::update_user_attributes::__path_update_user_attributes;
+use crate::methods::update_user_attributes::update_user_attributes;
+use crate::middleware::admin_auth::{admin_auth_middleware, AdminAuthConfig};
+use crate::middleware::api_key_auth::api_key_auth_middleware;
+use crate::middleware::idempotency::{
+    idempotency_middleware, IdempotencyConfig, IDEMPOTENCY_KEY_HEADER, IDEMPOTENT_REPLAYED_HEADER,
+};
 use crate::middleware::ip_filter::{ip_filter_middleware, IpFilterConfig};
+use crate::services::cache_warmup::warm_up_with_deadline;
+use crate::services::keycloak_event_poller::spawn_keycloak_event_poller;
+use crate::services::role_grant_sweeper::spawn_role_grant_sweeper;
 use crate::services::IntegratedUserService;
 use crate::shutdown::shutdown_signal;
 use crate::state::AppState;
+use crate::tenant::TENANT_HEADER;
 
 #[derive(OpenApi)]
 #[openapi(
     paths(
-        create_user, get_user_by_id, get_users, update_user, delete_user,
+        create_user, get_user_by_id, get_users, update_user, update_user_attributes, delete_user,
+        suspend_user, reactivate_user, set_user_password, execute_user_actions,
+        get_user_sessions, logout_user_sessions, delete_user_session,
         create_role, get_role_by_id, get_roles, update_role, delete_role,
-        assign_role, unassign_role
+        assign_role, unassign_role,
+        create_group, get_group_by_id, get_groups, update_group, delete_group,
+        get_group_members, add_group_member, remove_group_member,
+        assign_group_role, unassign_group_role,
+        get_cache_stats, flush_cache_namespace, flush_user_cache,
+        restore_user, restore_role,
+        create_tenant, get_tenants,
+        get_keycloak_realms, reload_keycloak_realms,
+        get_api_keys, create_api_key, rotate_api_key, revoke_api_key
     ),
     components(schemas(
-        CreateUserRequest, UpdateUserRequest, UserResponse,
-        CreateRoleRequest, UpdateRoleRequest, RoleResponse,
-        PaginatedResponse<UserResponse>, PaginatedResponse<RoleResponse>
+        CreateUserRequest, UpdateUserRequest, UpdateUserAttributesRequest,
+        UserResponse, UserAttributesResponse, UserSessionResponse,
+        SuspendUserRequest, SetUserPasswordRequest, ExecuteUserActionsRequest, RequiredAction,
+        CreateRoleRequest, UpdateRoleRequest, RoleResponse, AssignRoleRequest,
+        CreateGroupRequest, UpdateGroupRequest, GroupResponse,
+        PaginatedResponse<UserResponse>, PaginatedResponse<RoleResponse>,
+        PaginatedResponse<GroupResponse>,
+        CacheStatsResponse, CacheNamespaceStatsResponse, CacheFlushResponse,
+        CreateTenantRequest, TenantResponse, KeycloakRealmResponse,
+        CreateApiKeyRequest, ApiKeyResponse, IssuedApiKeyResponse, ApiKeyScope
     )),
     tags(
         (name = "users", description = "User management endpoints"),
-        (name = "roles", description = "Role management endpoints")
+        (name = "roles", description = "Role management endpoints"),
+        (name = "groups", description = "Group management endpoints, mirrored in Keycloak"),
+        (name = "admin", description = "Operational endpoints guarded by the admin token")
     )
 )]
 struct ApiDoc;
@@ -154,6 +261,7 @@ async fn run() -> Result<(), Box<dyn std::error::Error>> {
         max_body_size = middleware_config.max_body_size,
         cors_origins = ?middleware_config.cors_allowed_origins,
         ip_filter_enabled = middleware_config.has_ip_filter(),
+        idempotency_ttl_secs = middleware_config.idempotency_ttl.as_secs(),
         "middleware configuration loaded"
     );
 
@@ -161,23 +269,32 @@ async fn run() -> Result<(), Box<dyn std::error::Error>> {
     let database_url = std::env::var(DATABASE_URL)
         .map_err(|_| format!("{DATABASE_URL} environment variable must be set"))?;
 
-    let pool = connect_with_retry(&database_url, 10).await?;
+    // The URL scheme picks the backend (mysql:// or postgres://)
+    let pool = connect(&database_url, 10).await?;
+    tracing::info!(database = pool.kind().as_str(), "database connected");
 
     // Setup cache
-    let cache_config = CacheConfig::from_env();
+    let cache_config = CacheConfig::from_secrets(&secrets_client).await;
     tracing::info!(
         cache_enabled = cache_config.enabled,
+        cache_backend = cache_config.backend.as_str(),
         redis_host = %cache_config.redis_host,
         redis_port = cache_config.redis_port,
+        redis_tls = cache_config.redis_tls,
+        redis_sentinel = cache_config.uses_sentinel(),
         user_ttl_secs = cache_config.user_ttl.as_secs(),
         role_ttl_secs = cache_config.role_ttl.as_secs(),
         list_ttl_secs = cache_config.list_ttl.as_secs(),
+        negative_ttl_secs = cache_config.negative_ttl.as_secs(),
+        warmup_enabled = cache_config.warmup_enabled,
         "cache configuration loaded"
     );
 
-    let redis_cache = RedisCache::new(&cache_config).await;
-    if cache_config.enabled && !redis_cache.is_enabled() {
-        tracing::warn!("Cache was enabled but Redis connection failed - running in DB-only mode");
+    let cache = Cache::from_config(&cache_config).await;
+    if cache_config.enabled && !cache.is_enabled() {
+        tracing::warn!(
+            "Cache was enabled but Redis connection failed - running in DB-only mode until Redis is reachable"
+        );
     }
 
     // Setup Keycloak client (secrets loaded via secrets client)
@@ -190,28 +307,102 @@ async fn run() -> Result<(), Box<dyn std::error::Error>> {
         "keycloak configuration loaded"
     );
 
-    let keycloak_client = Arc::new(KeycloakClient::new(keycloak_config));
+    let mut keycloak_client = KeycloakClient::new(keycloak_config);
+    if let Some(path) = KeycloakConfig::realms_file() {
+        let shared_realm_fallback = KeycloakConfig::shared_realm_fallback();
+        keycloak_client = keycloak_client
+            .with_shared_realm_fallback(shared_realm_fallback)
+            .with_realms_file(path)?;
+        tracing::info!(
+            realms = keycloak_client.realms().len(),
+            shared_realm_fallback,
+            "keycloak tenant realms loaded"
+        );
+    }
+    let keycloak_client = Arc::new(keycloak_client);
 
-    if !keycloak_client.is_configured() {
+    if !keycloak_client.is_configured(TenantId::DEFAULT) {
         tracing::warn!("Keycloak client secret not set - user creation/update will fail");
     }
 
+    let soft_delete = std::env::var(SOFT_DELETE_ENABLED)
+        .map(|v| v.to_lowercase() == "true")
+        .unwrap_or(false);
+    tracing::info!(soft_delete, "delete mode configured");
+
+    let role_sync = std::env::var(KEYCLOAK_ROLE_SYNC_ENABLED)
+        .map(|v| v.to_lowercase() == "true")
+        .unwrap_or(false);
+    tracing::info!(role_sync, "keycloak role sync configured");
+
+    // Idempotency records go to the database; completed responses are also cached when there
+    // is a cache backend.
+    // A reservation is dropped after the request timeout, when its request can't finish anymore.
+    let idempotency_config = IdempotencyConfig::new(cache.backend(), pool.idempotency_repository())
+        .with_ttl(middleware_config.idempotency_ttl)
+        .with_pending_ttl(middleware_config.request_timeout)
+        .with_max_body_size(middleware_config.max_body_size);
+
     // Create shared service
-    let user_service = UserService::new(
-        UserRepository::new(pool.clone()),
-        RoleRepository::new(pool.clone()),
-        UserRoleRepository::new(pool.clone()),
-    );
+    let (user_repo, role_repo, user_role_repo) = pool.repositories();
+    let user_service = UserService::with_repos(
+        Arc::new(user_repo),
+        Arc::new(role_repo),
+        Arc::new(user_role_repo),
+    )
+    .with_unit_of_work(Arc::new(pool.clone()))
+    .with_tenant_repository(pool.tenant_repository())
+    .with_group_repository(pool.group_repository())
+    .with_api_key_repository(pool.api_key_repository())
+    .with_soft_delete(soft_delete);
 
     let cached_service =
-        CachedUserService::new(Arc::new(user_service), redis_cache.clone(), cache_config);
+        CachedUserService::new(Arc::new(user_service), cache.clone(), cache_config.clone());
 
     // Create integrated service that wraps cached service + keycloak
-    let integrated_service =
-        IntegratedUserService::new(Arc::new(cached_service), keycloak_client, redis_cache);
+    let integrated_service = Arc::new(
+        IntegratedUserService::new(Arc::new(cached_service), keycloak_client, cache)
+            .with_role_sync(role_sync),
+    );
+
+    // Preload hot keys before accepting traffic (bounded by the warm-up deadline)
+    warm_up_with_deadline(integrated_service.clone(), &cache_config).await;
+
+    // Delete expired role grants in the background and evict the affected users
+    let sweep_interval_secs: u64 = std::env::var(ROLE_GRANT_SWEEP_INTERVAL_SECS)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(60);
+    if sweep_interval_secs > 0 {
+        spawn_role_grant_sweeper(
+            integrated_service.clone(),
+            Duration::from_secs(sweep_interval_secs),
+        );
+        tracing::info!(sweep_interval_secs, "role grant sweeper started");
+    } else {
+        tracing::info!("{ROLE_GRANT_SWEEP_INTERVAL_SECS} is 0 - expired role grants are not swept");
+    }
+
+    // Apply changes made directly in Keycloak (profile edits, deletions, logins)
+    let event_poll_interval_secs: u64 = std::env::var(KEYCLOAK_EVENT_POLL_INTERVAL_SECS)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(30);
+    if event_poll_interval_secs > 0 {
+        spawn_keycloak_event_poller(
+            integrated_service.clone(),
+            pool.event_checkpoint_repository(),
+            Duration::from_secs(event_poll_interval_secs),
+        );
+        tracing::info!(event_poll_interval_secs, "Keycloak event poller started");
+    } else {
+        tracing::info!(
+            "{KEYCLOAK_EVENT_POLL_INTERVAL_SECS} is 0 - Keycloak events are not consumed"
+        );
+    }
 
     let app_state = AppState {
-        user_service: Arc::new(integrated_service),
+        user_service: integrated_service,
         env: env.clone(),
     };
 
@@ -221,8 +412,23 @@ async fn run() -> Result<(), Box<dyn std::error::Error>> {
         .route(USERS_PATH, get(get_users).post(create_user))
         .route(
             USERS_BY_ID_PATH,
-            get(get_user_by_id).put(update_user).delete(delete_user),
+            get(get_user_by_id)
+                .put(update_user)
+                .patch(update_user_attributes)
+                .delete(delete_user),
         )
+        // Suspension endpoints
+        .route(USER_SUSPEND_PATH, post(suspend_user))
+        .route(USER_REACTIVATE_PATH, post(reactivate_user))
+        // Credential endpoints
+        .route(USER_PASSWORD_PATH, post(set_user_password))
+        .route(USER_ACTIONS_PATH, post(execute_user_actions))
+        // Session endpoints
+        .route(
+            USER_SESSIONS_PATH,
+            get(get_user_sessions).delete(logout_user_sessions),
+        )
+        .route(USER_SESSION_PATH, delete(delete_user_session))
         // Role endpoints
         .route(ROLES_PATH, get(get_roles).post(create_role))
         .route(
@@ -230,7 +436,54 @@ async fn run() -> Result<(), Box<dyn std::error::Error>> {
             get(get_role_by_id).put(update_role).delete(delete_role),
         )
         // User-role assignment endpoints
-        .route(USER_ROLES_PATH, post(assign_role).delete(unassign_role));
+        .route(USER_ROLES_PATH, post(assign_role).delete(unassign_role))
+        // Group endpoints
+        .route(GROUPS_PATH, get(get_groups).post(create_group))
+        .route(
+            GROUPS_BY_ID_PATH,
+            get(get_group_by_id).put(update_group).delete(delete_group),
+        )
+        .route(GROUP_MEMBERS_PATH, get(get_group_members))
+        .route(
+            GROUP_MEMBER_PATH,
+            put(add_group_member).delete(remove_group_member),
+        )
+        .route(
+            GROUP_ROLES_PATH,
+            put(assign_group_role).delete(unassign_group_role),
+        )
+        // Replay POST responses for retried Idempotency-Key requests
+        .route_layer(from_fn(idempotency_middleware))
+        .route_layer(Extension(idempotency_config))
+        // Service accounts (X-API-Key) only reach what their key's scopes cover
+        .route_layer(from_fn_with_state(
+            app_state.clone(),
+            api_key_auth_middleware,
+        ));
+
+    // Build admin routes (root level, require the admin token)
+    let admin_config =
+        AdminAuthConfig::new(secrets_client.get_secret_optional(ADMIN_API_TOKEN).await);
+    if !admin_config.is_enabled() {
+        tracing::info!("{ADMIN_API_TOKEN} not set - admin endpoints are disabled");
+    }
+    let admin_routes = Router::new()
+        .route(ADMIN_CACHE_STATS_PATH, get(get_cache_stats))
+        .route(ADMIN_CACHE_NAMESPACE_PATH, delete(flush_cache_namespace))
+        .route(ADMIN_CACHE_USER_PATH, delete(flush_user_cache))
+        .route(ADMIN_USER_RESTORE_PATH, post(restore_user))
+        .route(ADMIN_ROLE_RESTORE_PATH, post(restore_role))
+        .route(ADMIN_TENANTS_PATH, get(get_tenants).post(create_tenant))
+        .route(ADMIN_KEYCLOAK_REALMS_PATH, get(get_keycloak_realms))
+        .route(
+            ADMIN_KEYCLOAK_REALMS_RELOAD_PATH,
+            post(reload_keycloak_realms),
+        )
+        .route(ADMIN_API_KEYS_PATH, get(get_api_keys).post(create_api_key))
+        .route(ADMIN_API_KEY_PATH, delete(revoke_api_key))
+        .route(ADMIN_API_KEY_ROTATE_PATH, post(rotate_api_key))
+        .route_layer(from_fn(admin_auth_middleware))
+        .route_layer(Extension(admin_config));
 
     // Build root-level routes (health, docs)
     let root_routes = Router::new()
@@ -241,6 +494,7 @@ async fn run() -> Result<(), Box<dyn std::error::Error>> {
     let mut app = Router::new()
         .nest(API_V1_PREFIX, v1_routes)
         .merge(root_routes)
+        .merge(admin_routes)
         .with_state(app_state);
 
     // ============================================
@@ -278,10 +532,23 @@ async fn run() -> Result<(), Box<dyn std::error::Error>> {
                 Method::GET,
                 Method::POST,
                 Method::PUT,
+                Method::PATCH,
                 Method::DELETE,
                 Method::OPTIONS,
             ])
-            .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION, x_request_id])
+            .allow_headers([
+                header::CONTENT_TYPE,
+                header::AUTHORIZATION,
+                header::IF_MATCH,
+                HeaderName::from_static(IDEMPOTENCY_KEY_HEADER),
+                HeaderName::from_static(TENANT_HEADER),
+                HeaderName::from_static(API_KEY_HEADER),
+                x_request_id,
+            ])
+            .expose_headers([
+                header::ETAG,
+                HeaderName::from_static(IDEMPOTENT_REPLAYED_HEADER),
+            ])
     } else {
         let origins: Vec<_> = middleware_config
             .cors_allowed_origins
@@ -294,14 +561,23 @@ async fn run() -> Result<(), Box<dyn std::error::Error>> {
                 Method::GET,
                 Method::POST,
                 Method::PUT,
+                Method::PATCH,
                 Method::DELETE,
                 Method::OPTIONS,
             ])
             .allow_headers([
                 header::CONTENT_TYPE,
                 header::AUTHORIZATION,
+                header::IF_MATCH,
+                HeaderName::from_static(IDEMPOTENCY_KEY_HEADER),
+                HeaderName::from_static(TENANT_HEADER),
+                HeaderName::from_static(API_KEY_HEADER),
                 HeaderName::from_static("x-request-id"),
             ])
+            .expose_headers([
+                header::ETAG,
+                HeaderName::from_static(IDEMPOTENT_REPLAYED_HEADER),
+            ])
     };
     app = app.layer(cors_layer);
 
@@ -0,0 +1,43 @@
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::routes::GROUP_ROLES_PATH;
+use crate::state::AppState;
+use crate::tenant::Tenant;
+use axum::http::StatusCode;
+use uuid::Uuid;
+
+#[derive(serde::Deserialize)]
+pub struct GroupRolePath {
+    pub group_id: String,
+    pub role_id: String,
+}
+
+#[utoipa::path(
+    put,
+    path = GROUP_ROLES_PATH,
+    tag = "groups",
+    params(
+        ("group_id" = String, Path, description = "Group ID (UUID)"),
+        ("role_id" = String, Path, description = "Role ID (UUID)")
+    ),
+    responses(
+        (status = 204, description = "Role is attached to the group; members inherit it"),
+        (status = 400, description = "Invalid UUID"),
+        (status = 404, description = "Group or role not found"),
+        (status = 500, description = "Internal server error"),
+    )
+)]
+pub async fn assign_group_role(
+    axum::extract::Path(path): axum::extract::Path<GroupRolePath>,
+    axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+) -> Result<StatusCode, ApiError> {
+    let group_id = Uuid::parse_str(&path.group_id).map_err(|_| ApiError::invalid_group_uuid())?;
+    let role_id = Uuid::parse_str(&path.role_id).map_err(|_| ApiError::invalid_role_uuid())?;
+
+    state
+        .user_service
+        .assign_group_role(tenant, group_id, role_id)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(|e| handle_integrated_service_error(e, &state.env, "assign_group_role"))
+}
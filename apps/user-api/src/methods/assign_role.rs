@@ -1,8 +1,12 @@
 use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::entities::AssignRoleRequest;
 use crate::methods::routes::USER_ROLES_PATH;
 use crate::state::AppState;
+use crate::tenant::Tenant;
 use axum::http::StatusCode;
+use axum::Json;
 use uuid::Uuid;
+use validator::Validate;
 
 #[derive(serde::Deserialize)]
 pub struct UserRolePath {
@@ -18,9 +22,10 @@ pub struct UserRolePath {
         ("user_id" = String, Path, description = "User ID (UUID)"),
         ("role_id" = String, Path, description = "Role ID (UUID)")
     ),
+    request_body(content = Option<AssignRoleRequest>, description = "Omit for a permanent grant"),
     responses(
         (status = 204, description = "Role assigned successfully"),
-        (status = 400, description = "Invalid UUID"),
+        (status = 400, description = "Invalid UUID, or expiry not in the future"),
         (status = 404, description = "User or role not found"),
         (status = 409, description = "User already has this role"),
         (status = 500, description = "Internal server error"),
@@ -29,13 +34,17 @@ pub struct UserRolePath {
 pub async fn assign_role(
     axum::extract::Path(path): axum::extract::Path<UserRolePath>,
     axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+    payload: Option<Json<AssignRoleRequest>>,
 ) -> Result<StatusCode, ApiError> {
     let user_id = Uuid::parse_str(&path.user_id).map_err(|_| ApiError::invalid_user_uuid())?;
     let role_id = Uuid::parse_str(&path.role_id).map_err(|_| ApiError::invalid_role_uuid())?;
+    let payload = payload.map(|Json(payload)| payload).unwrap_or_default();
+    payload.validate()?;
 
     state
         .user_service
-        .assign_role(user_id, role_id)
+        .assign_role(tenant, user_id, role_id, &payload.into())
         .await
         .map(|_| StatusCode::NO_CONTENT)
         .map_err(|e| handle_integrated_service_error(e, &state.env, "assign_role"))
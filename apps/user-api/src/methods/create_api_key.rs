@@ -0,0 +1,42 @@
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::entities::{CreateApiKeyRequest, IssuedApiKeyResponse};
+use crate::methods::routes::ADMIN_API_KEYS_PATH;
+use crate::state::AppState;
+use crate::tenant::Tenant;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use validator::Validate;
+
+#[utoipa::path(
+    post,
+    path = ADMIN_API_KEYS_PATH,
+    tag = "admin",
+    request_body = CreateApiKeyRequest,
+    responses(
+        (status = 201, description = "API key created; the key is shown only in this response", body = IssuedApiKeyResponse),
+        (status = 400, description = "Validation error"),
+        (status = 401, description = "Missing or invalid admin token"),
+        (status = 403, description = "Admin API disabled"),
+        (status = 500, description = "Internal server error"),
+    )
+)]
+pub async fn create_api_key(
+    axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+    Json(payload): Json<CreateApiKeyRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    payload.validate()?;
+
+    state
+        .user_service
+        .create_api_key(tenant, &payload.into())
+        .await
+        .map(|issued| {
+            (
+                StatusCode::CREATED,
+                Json(IssuedApiKeyResponse::from(issued)),
+            )
+        })
+        .map_err(|e| handle_integrated_service_error(e, &state.env, "create_api_key"))
+}
@@ -0,0 +1,37 @@
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::entities::{CreateGroupRequest, GroupResponse};
+use crate::methods::routes::GROUPS_PATH;
+use crate::state::AppState;
+use crate::tenant::Tenant;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use validator::Validate;
+
+#[utoipa::path(
+    post,
+    path = GROUPS_PATH,
+    tag = "groups",
+    request_body = CreateGroupRequest,
+    responses(
+        (status = 201, description = "Group created in Keycloak and locally", body = GroupResponse),
+        (status = 400, description = "Validation error"),
+        (status = 409, description = "Group name already exists"),
+        (status = 500, description = "Internal server error"),
+    )
+)]
+pub async fn create_group(
+    axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+    Json(payload): Json<CreateGroupRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    // Validate input
+    payload.validate()?;
+
+    state
+        .user_service
+        .create_group(tenant, &payload.name)
+        .await
+        .map(|group| (StatusCode::CREATED, Json(GroupResponse::from(group))))
+        .map_err(|e| handle_integrated_service_error(e, &state.env, "create_group"))
+}
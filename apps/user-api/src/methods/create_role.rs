@@ -2,6 +2,7 @@ use crate::error::{handle_integrated_service_error, ApiError};
 use crate::methods::entities::{CreateRoleRequest, RoleResponse};
 use crate::methods::routes::ROLES_PATH;
 use crate::state::AppState;
+use crate::tenant::Tenant;
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::Json;
@@ -21,6 +22,7 @@ use validator::Validate;
 )]
 pub async fn create_role(
     axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
     Json(payload): Json<CreateRoleRequest>,
 ) -> Result<impl IntoResponse, ApiError> {
     // Validate input
@@ -28,7 +30,7 @@ pub async fn create_role(
 
     state
         .user_service
-        .create_role(&payload.name)
+        .create_role(tenant, &payload.name, payload.description.as_deref())
         .await
         .map(|role| (StatusCode::CREATED, Json(RoleResponse::from(role))))
         .map_err(|e| handle_integrated_service_error(e, &state.env, "create_role"))
@@ -0,0 +1,36 @@
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::entities::{CreateTenantRequest, TenantResponse};
+use crate::methods::routes::ADMIN_TENANTS_PATH;
+use crate::state::AppState;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use validator::Validate;
+
+#[utoipa::path(
+    post,
+    path = ADMIN_TENANTS_PATH,
+    tag = "admin",
+    request_body = CreateTenantRequest,
+    responses(
+        (status = 201, description = "Tenant created with its own system roles", body = TenantResponse),
+        (status = 400, description = "Validation error"),
+        (status = 401, description = "Missing or invalid admin token"),
+        (status = 403, description = "Admin API disabled"),
+        (status = 409, description = "Tenant slug already exists"),
+        (status = 500, description = "Internal server error"),
+    )
+)]
+pub async fn create_tenant(
+    axum::extract::State(state): axum::extract::State<AppState>,
+    Json(payload): Json<CreateTenantRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    payload.validate()?;
+
+    state
+        .user_service
+        .create_tenant(&payload.slug, &payload.name)
+        .await
+        .map(|tenant| (StatusCode::CREATED, Json(TenantResponse::from(tenant))))
+        .map_err(|e| handle_integrated_service_error(e, &state.env, "create_tenant"))
+}
@@ -3,6 +3,7 @@ use crate::methods::entities::{CreateUserRequest, UserResponse};
 use crate::methods::routes::USERS_PATH;
 use crate::services::integrated_user_service::CreateUserRequest as ServiceCreateUserRequest;
 use crate::state::AppState;
+use crate::tenant::Tenant;
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::Json;
@@ -22,6 +23,7 @@ use validator::Validate;
 )]
 pub async fn create_user(
     axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
     Json(payload): Json<CreateUserRequest>,
 ) -> Result<impl IntoResponse, ApiError> {
     // Validate input
@@ -36,7 +38,7 @@ pub async fn create_user(
 
     state
         .user_service
-        .create_user(request)
+        .create_user(tenant, request)
         .await
         .map(|user| (StatusCode::CREATED, Json(UserResponse::from(user))))
         .map_err(|e| handle_integrated_service_error(e, &state.env, "create_user"))
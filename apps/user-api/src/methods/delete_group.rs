@@ -0,0 +1,35 @@
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::routes::GROUPS_BY_ID_PATH;
+use crate::state::AppState;
+use crate::tenant::Tenant;
+use axum::http::StatusCode;
+use uuid::Uuid;
+
+#[utoipa::path(
+    delete,
+    path = GROUPS_BY_ID_PATH,
+    tag = "groups",
+    params(
+        ("id" = String, Path, description = "Group ID (UUID)")
+    ),
+    responses(
+        (status = 204, description = "Group deleted; members lose the roles it granted"),
+        (status = 400, description = "Invalid UUID"),
+        (status = 404, description = "Group not found"),
+        (status = 500, description = "Internal server error"),
+    )
+)]
+pub async fn delete_group(
+    axum::extract::Path(id): axum::extract::Path<String>,
+    axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+) -> Result<StatusCode, ApiError> {
+    let parsed_id = Uuid::parse_str(&id).map_err(|_| ApiError::invalid_uuid())?;
+
+    state
+        .user_service
+        .delete_group(tenant, parsed_id)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(|e| handle_integrated_service_error(e, &state.env, "delete_group"))
+}
@@ -1,7 +1,9 @@
 use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::etag::expected_version;
 use crate::methods::routes::ROLES_BY_ID_PATH;
 use crate::state::AppState;
-use axum::http::StatusCode;
+use crate::tenant::Tenant;
+use axum::http::{HeaderMap, StatusCode};
 use uuid::Uuid;
 
 #[utoipa::path(
@@ -9,24 +11,31 @@ use uuid::Uuid;
     path = ROLES_BY_ID_PATH,
     tag = "roles",
     params(
-        ("id" = String, Path, description = "Role ID (UUID)")
+        ("id" = String, Path, description = "Role ID (UUID)"),
+        ("If-Match" = String, Header, description = "ETag from the last read, or `*` to skip the check")
     ),
     responses(
         (status = 204, description = "Role deleted successfully"),
         (status = 400, description = "Invalid UUID"),
+        (status = 403, description = "System roles cannot be deleted"),
         (status = 404, description = "Role not found"),
+        (status = 412, description = "If-Match does not match the current ETag"),
+        (status = 428, description = "If-Match header missing"),
         (status = 500, description = "Internal server error"),
     )
 )]
 pub async fn delete_role(
     axum::extract::Path(id): axum::extract::Path<String>,
     axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+    headers: HeaderMap,
 ) -> Result<StatusCode, ApiError> {
     let parsed_id = Uuid::parse_str(&id).map_err(|_| ApiError::invalid_uuid())?;
+    let expected_version = expected_version(&headers)?;
 
     state
         .user_service
-        .delete_role(parsed_id)
+        .delete_role(tenant, parsed_id, expected_version)
         .await
         .map(|_| StatusCode::NO_CONTENT)
         .map_err(|e| handle_integrated_service_error(e, &state.env, "delete_role"))
@@ -1,7 +1,9 @@
 use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::etag::expected_version;
 use crate::methods::routes::USERS_BY_ID_PATH;
 use crate::state::AppState;
-use axum::http::StatusCode;
+use crate::tenant::Tenant;
+use axum::http::{HeaderMap, StatusCode};
 use uuid::Uuid;
 
 #[utoipa::path(
@@ -9,24 +11,30 @@ use uuid::Uuid;
     path = USERS_BY_ID_PATH,
     tag = "users",
     params(
-        ("id" = String, Path, description = "User ID (UUID)")
+        ("id" = String, Path, description = "User ID (UUID)"),
+        ("If-Match" = String, Header, description = "ETag from the last read, or `*` to skip the check")
     ),
     responses(
         (status = 204, description = "User deleted successfully"),
         (status = 400, description = "Invalid UUID"),
         (status = 404, description = "User not found"),
+        (status = 412, description = "If-Match does not match the current ETag"),
+        (status = 428, description = "If-Match header missing"),
         (status = 500, description = "Internal server error"),
     )
 )]
 pub async fn delete_user(
     axum::extract::Path(id): axum::extract::Path<String>,
     axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+    headers: HeaderMap,
 ) -> Result<StatusCode, ApiError> {
     let parsed_id = Uuid::parse_str(&id).map_err(|_| ApiError::invalid_uuid())?;
+    let expected_version = expected_version(&headers)?;
 
     state
         .user_service
-        .delete_user(parsed_id)
+        .delete_user(tenant, parsed_id, expected_version)
         .await
         .map(|_| StatusCode::NO_CONTENT)
         .map_err(|e| handle_integrated_service_error(e, &state.env, "delete_user"))
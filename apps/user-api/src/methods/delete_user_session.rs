@@ -0,0 +1,42 @@
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::routes::USER_SESSION_PATH;
+use crate::state::AppState;
+use crate::tenant::Tenant;
+use axum::http::StatusCode;
+use uuid::Uuid;
+
+#[derive(serde::Deserialize)]
+pub struct UserSessionPath {
+    pub user_id: String,
+    pub session_id: String,
+}
+
+#[utoipa::path(
+    delete,
+    path = USER_SESSION_PATH,
+    tag = "users",
+    params(
+        ("user_id" = String, Path, description = "User ID (UUID)"),
+        ("session_id" = String, Path, description = "Keycloak session ID")
+    ),
+    responses(
+        (status = 204, description = "Session ended"),
+        (status = 400, description = "Invalid UUID"),
+        (status = 404, description = "User or session not found"),
+        (status = 500, description = "Internal server error"),
+    )
+)]
+pub async fn delete_user_session(
+    axum::extract::Path(path): axum::extract::Path<UserSessionPath>,
+    axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+) -> Result<StatusCode, ApiError> {
+    let user_id = Uuid::parse_str(&path.user_id).map_err(|_| ApiError::invalid_user_uuid())?;
+
+    state
+        .user_service
+        .delete_user_session(tenant, user_id, &path.session_id)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(|e| handle_integrated_service_error(e, &state.env, "delete_user_session"))
+}
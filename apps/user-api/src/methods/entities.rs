@@ -1,11 +1,18 @@
-use secrecy::Secret;
-use serde::{Deserialize, Serialize};
-use user_lib::entities::{PaginatedResult, PaginationParams, Role};
+use chrono::{DateTime, Utc};
+use secrecy::{ExposeSecret, Secret};
+use serde::{Deserialize, Deserializer, Serialize};
+use user_lib::entities::{
+    ApiKey, Group, NewApiKey, PaginatedResult, PaginationParams, Role, RoleGrant, Tenant, TenantId,
+    UserAttributes, UserAttributesPatch, UserFilter, UserStatus,
+};
 use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 use validator::Validate;
 
-use crate::keycloak::FullUser;
+use crate::api_key::ApiKeyScope;
+use crate::cache::NamespaceStats;
+use crate::keycloak::{FullUser, KeycloakUserSession, RequiredAction};
+use crate::services::integrated_user_service::IssuedApiKey;
 
 #[derive(Debug, Deserialize, Serialize, ToSchema, Validate)]
 pub struct CreateUserRequest {
@@ -28,6 +35,15 @@ pub struct CreateUserRequest {
 
 #[derive(Debug, Deserialize, Serialize, ToSchema, Validate)]
 pub struct UpdateUserRequest {
+    /// New email; the user has to verify it again
+    #[serde(default)]
+    #[validate(email(message = "Invalid email format"))]
+    #[validate(length(
+        min = 1,
+        max = 254,
+        message = "Email must be between 1 and 254 characters"
+    ))]
+    pub email: Option<String>,
     #[serde(default)]
     #[validate(length(max = 100, message = "First name cannot exceed 100 characters"))]
     pub first_name: Option<String>,
@@ -36,6 +52,151 @@ pub struct UpdateUserRequest {
     pub last_name: Option<String>,
 }
 
+/// Optional body of a suspension
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct SuspendUserRequest {
+    /// Why the user is suspended, recorded locally for auditing
+    #[serde(default)]
+    #[validate(length(max = 1000, message = "Reason cannot exceed 1000 characters"))]
+    pub reason: Option<String>,
+    /// Also end all of the user's Keycloak sessions
+    #[serde(default)]
+    pub logout_sessions: bool,
+}
+
+/// Password set by an administrator
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SetUserPasswordRequest {
+    #[schema(value_type = String, format = Password)]
+    pub password: Secret<String>,
+    /// Require the user to choose a new password at the next login
+    #[serde(default)]
+    pub temporary: bool,
+}
+
+// Written by hand: the derive needs `Serialize` fields and the password must never serialize
+impl Validate for SetUserPasswordRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        let len = self.password.expose_secret().chars().count();
+        if (1..=256).contains(&len) {
+            return Ok(());
+        }
+        let mut errors = validator::ValidationErrors::new();
+        errors.add(
+            "password",
+            validator::ValidationError::new("length")
+                .with_message("Password must be between 1 and 256 characters".into()),
+        );
+        Err(errors)
+    }
+}
+
+/// Actions the user is emailed a link to complete
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct ExecuteUserActionsRequest {
+    #[validate(length(min = 1, max = 3, message = "Between 1 and 3 actions are required"))]
+    pub actions: Vec<RequiredAction>,
+    /// How long the link stays valid, in seconds; the realm default applies when omitted
+    #[serde(default)]
+    #[validate(range(
+        min = 60,
+        max = 604800,
+        message = "lifespan_secs must be between 60 and 604800"
+    ))]
+    pub lifespan_secs: Option<u32>,
+}
+
+/// Keeps an explicit `null` apart from a missing field: `Some(None)` vs `None`
+fn present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+/// Merge patch of the locally stored attributes: omitted fields are kept and `null`
+/// clears a field. `metadata` is replaced as a whole.
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateUserAttributesRequest {
+    #[serde(default, deserialize_with = "present")]
+    #[schema(value_type = Option<String>, max_length = 255)]
+    pub department: Option<Option<String>>,
+    /// Letters, digits, `-`, `_` and `.`
+    #[serde(default, deserialize_with = "present")]
+    #[schema(value_type = Option<String>, max_length = 64)]
+    pub cost_center: Option<Option<String>>,
+    /// BCP 47 language tag such as `en-US`
+    #[serde(default, deserialize_with = "present")]
+    #[schema(value_type = Option<String>, max_length = 35)]
+    pub locale: Option<Option<String>>,
+    /// JSON object of at most 16 KiB
+    #[serde(default, deserialize_with = "present")]
+    #[schema(value_type = Option<Object>)]
+    pub metadata: Option<Option<serde_json::Map<String, serde_json::Value>>>,
+}
+
+impl From<UpdateUserAttributesRequest> for UserAttributesPatch {
+    fn from(request: UpdateUserAttributesRequest) -> Self {
+        UserAttributesPatch {
+            department: request.department,
+            cost_center: request.cost_center,
+            locale: request.locale,
+            metadata: request.metadata,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserAttributesResponse {
+    pub department: Option<String>,
+    pub cost_center: Option<String>,
+    pub locale: Option<String>,
+    #[schema(value_type = Option<Object>)]
+    pub metadata: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+impl From<UserAttributes> for UserAttributesResponse {
+    fn from(attributes: UserAttributes) -> Self {
+        UserAttributesResponse {
+            department: attributes.department,
+            cost_center: attributes.cost_center,
+            locale: attributes.locale,
+            metadata: attributes.metadata,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserSessionResponse {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip_address: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub last_access_at: DateTime<Utc>,
+    /// Client ids the session has signed in to
+    pub clients: Vec<String>,
+}
+
+impl From<KeycloakUserSession> for UserSessionResponse {
+    fn from(session: KeycloakUserSession) -> Self {
+        let mut clients: Vec<String> = session.clients.into_values().collect();
+        clients.sort();
+        UserSessionResponse {
+            id: session.id,
+            ip_address: session.ip_address,
+            started_at: DateTime::from_timestamp_millis(session.start).unwrap_or_default(),
+            last_access_at: DateTime::from_timestamp_millis(session.last_access)
+                .unwrap_or_default(),
+            clients,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, ToSchema)]
 pub struct UserResponse {
     pub id: Uuid,
@@ -44,8 +205,22 @@ pub struct UserResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub email: Option<String>,
     pub roles: Vec<RoleResponse>,
+    /// Application-specific attributes stored locally
+    pub attributes: UserAttributesResponse,
     pub email_verified: bool,
     pub enabled: bool,
+    /// When the user was suspended; absent while the user is active
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suspended_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suspension_reason: Option<String>,
+    /// Most recent Keycloak login seen by the event poller
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_login_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Current version, also sent as the `ETag` header
+    pub version: i64,
 }
 
 impl From<FullUser> for UserResponse {
@@ -56,8 +231,15 @@ impl From<FullUser> for UserResponse {
             name: user.name,
             email: user.email,
             roles: user.roles.into_iter().map(RoleResponse::from).collect(),
+            attributes: UserAttributesResponse::from(user.attributes),
             email_verified: user.email_verified,
             enabled: user.enabled,
+            suspended_at: user.suspension.as_ref().map(|s| s.suspended_at),
+            suspension_reason: user.suspension.and_then(|s| s.reason),
+            last_login_at: user.last_login_at,
+            created_at: user.created_at,
+            updated_at: user.updated_at,
+            version: user.version,
         }
     }
 }
@@ -70,6 +252,9 @@ pub struct CreateRoleRequest {
         message = "Role name must be between 1 and 50 characters"
     ))]
     pub name: String,
+    #[serde(default)]
+    #[validate(length(max = 1000, message = "Description cannot exceed 1000 characters"))]
+    pub description: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, ToSchema, Validate)]
@@ -80,12 +265,24 @@ pub struct UpdateRoleRequest {
         message = "Role name must be between 1 and 50 characters"
     ))]
     pub name: String,
+    /// Omit to keep the current description, send an empty string to clear it
+    #[serde(default)]
+    #[validate(length(max = 1000, message = "Description cannot exceed 1000 characters"))]
+    pub description: Option<String>,
 }
 
 #[derive(Debug, Serialize, ToSchema, Clone)]
 pub struct RoleResponse {
     pub id: Uuid,
     pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Seeded role the service relies on; it cannot be renamed or deleted
+    pub is_system: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Current version, also sent as the `ETag` header
+    pub version: i64,
 }
 
 impl From<Role> for RoleResponse {
@@ -93,6 +290,219 @@ impl From<Role> for RoleResponse {
         RoleResponse {
             id: role.id,
             name: role.name,
+            description: role.description,
+            is_system: role.is_system,
+            created_at: role.created_at,
+            updated_at: role.updated_at,
+            version: role.version,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema, Validate)]
+pub struct CreateGroupRequest {
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "Group name must be between 1 and 255 characters"
+    ))]
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema, Validate)]
+pub struct UpdateGroupRequest {
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "Group name must be between 1 and 255 characters"
+    ))]
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, ToSchema, Clone)]
+pub struct GroupResponse {
+    pub id: Uuid,
+    /// Id of the mirrored group in Keycloak
+    pub keycloak_id: String,
+    pub name: String,
+    /// Roles every member inherits
+    pub roles: Vec<RoleResponse>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Group> for GroupResponse {
+    fn from(group: Group) -> Self {
+        GroupResponse {
+            id: group.id,
+            keycloak_id: group.keycloak_id,
+            name: group.name,
+            roles: group.roles.into_iter().map(RoleResponse::from).collect(),
+            created_at: group.created_at,
+            updated_at: group.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema, Validate)]
+pub struct CreateTenantRequest {
+    /// Lowercase letters, digits and inner hyphens
+    #[validate(length(
+        min = 1,
+        max = 63,
+        message = "Tenant slug must be between 1 and 63 characters"
+    ))]
+    pub slug: String,
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "Tenant name must be between 1 and 255 characters"
+    ))]
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TenantResponse {
+    /// Value to send as `X-Tenant-ID`
+    pub id: Uuid,
+    pub slug: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Service-account API key to create in the request's tenant
+#[derive(Debug, Deserialize, Serialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateApiKeyRequest {
+    /// What the key is for, e.g. the batch job that uses it
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "API key name must be between 1 and 255 characters"
+    ))]
+    pub name: String,
+    #[validate(length(min = 1, message = "An API key needs at least one scope"))]
+    pub scopes: Vec<ApiKeyScope>,
+    /// The key stops working at this instant; without one it never expires
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl From<CreateApiKeyRequest> for NewApiKey {
+    fn from(request: CreateApiKeyRequest) -> Self {
+        NewApiKey {
+            name: request.name,
+            scopes: request
+                .scopes
+                .into_iter()
+                .map(|scope| scope.as_str().to_string())
+                .collect(),
+            expires_at: request.expires_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiKeyResponse {
+    pub id: Uuid,
+    pub name: String,
+    /// First characters of the key, to tell keys apart
+    pub prefix: String,
+    pub scopes: Vec<String>,
+    /// Neither revoked nor expired
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Accurate to about a minute
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rotated_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl From<ApiKey> for ApiKeyResponse {
+    fn from(api_key: ApiKey) -> Self {
+        ApiKeyResponse {
+            id: api_key.id,
+            active: api_key.is_active_at(Utc::now()),
+            name: api_key.name,
+            prefix: api_key.prefix,
+            scopes: api_key.scopes,
+            created_at: api_key.created_at,
+            expires_at: api_key.expires_at,
+            last_used_at: api_key.last_used_at,
+            rotated_at: api_key.rotated_at,
+            revoked_at: api_key.revoked_at,
+        }
+    }
+}
+
+/// API key together with the key itself, returned once on creation and rotation
+#[derive(Debug, Serialize, ToSchema)]
+pub struct IssuedApiKeyResponse {
+    #[serde(flatten)]
+    pub api_key: ApiKeyResponse,
+    /// Value to send as `X-API-Key`. It is not stored and cannot be shown again.
+    pub key: String,
+}
+
+impl From<IssuedApiKey> for IssuedApiKeyResponse {
+    fn from(issued: IssuedApiKey) -> Self {
+        IssuedApiKeyResponse {
+            api_key: ApiKeyResponse::from(issued.api_key),
+            key: issued.key.expose_secret().clone(),
+        }
+    }
+}
+
+/// Keycloak realm serving a tenant
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct KeycloakRealmResponse {
+    pub tenant_id: Uuid,
+    pub realm: String,
+}
+
+impl From<(TenantId, String)> for KeycloakRealmResponse {
+    fn from((tenant, realm): (TenantId, String)) -> Self {
+        KeycloakRealmResponse {
+            tenant_id: tenant.0,
+            realm,
+        }
+    }
+}
+
+impl From<Tenant> for TenantResponse {
+    fn from(tenant: Tenant) -> Self {
+        TenantResponse {
+            id: tenant.id.0,
+            slug: tenant.slug,
+            name: tenant.name,
+            created_at: tenant.created_at,
+        }
+    }
+}
+
+/// Optional body of a role assignment; without it the role is granted permanently
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct AssignRoleRequest {
+    /// When the assignment expires (RFC 3339); must be in the future
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Who granted the role, recorded for auditing
+    #[serde(default)]
+    #[validate(length(max = 255, message = "granted_by cannot exceed 255 characters"))]
+    pub granted_by: Option<String>,
+}
+
+impl From<AssignRoleRequest> for RoleGrant {
+    fn from(request: AssignRoleRequest) -> Self {
+        RoleGrant {
+            expires_at: request.expires_at,
+            granted_by: request.granted_by,
         }
     }
 }
@@ -109,6 +519,29 @@ impl From<PaginationQuery> for PaginationParams {
     }
 }
 
+/// Exact-match filters on user attributes and status; omitted ones match every user
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct UserFilterQuery {
+    pub department: Option<String>,
+    pub cost_center: Option<String>,
+    /// BCP 47 language tag, compared case-insensitively
+    pub locale: Option<String>,
+    /// `active` or `suspended`
+    #[param(value_type = Option<String>)]
+    pub status: Option<UserStatus>,
+}
+
+impl From<UserFilterQuery> for UserFilter {
+    fn from(query: UserFilterQuery) -> Self {
+        UserFilter {
+            department: query.department,
+            cost_center: query.cost_center,
+            locale: query.locale,
+            status: query.status,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, ToSchema)]
 pub struct PaginatedResponse<T> {
     pub items: Vec<T>,
@@ -132,3 +565,37 @@ where
         }
     }
 }
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CacheNamespaceStatsResponse {
+    pub namespace: String,
+    pub hits: u64,
+    pub negative_hits: u64,
+    pub misses: u64,
+    pub errors: u64,
+}
+
+impl From<NamespaceStats> for CacheNamespaceStatsResponse {
+    fn from(stats: NamespaceStats) -> Self {
+        CacheNamespaceStatsResponse {
+            namespace: stats.namespace.to_string(),
+            hits: stats.hits,
+            negative_hits: stats.negative_hits,
+            misses: stats.misses,
+            errors: stats.errors,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CacheStatsResponse {
+    /// Redis is configured and currently usable
+    pub enabled: bool,
+    pub namespaces: Vec<CacheNamespaceStatsResponse>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CacheFlushResponse {
+    pub namespace: String,
+    pub deleted: u64,
+}
@@ -0,0 +1,80 @@
+//! ETags for optimistic concurrency: the ETag of a user or role is its row version.
+
+use axum::http::header::{ETAG, IF_MATCH};
+use axum::http::{HeaderMap, HeaderName, HeaderValue};
+
+use crate::error::ApiError;
+
+/// `ETag` header carrying `version`
+pub fn etag_header(version: i64) -> [(HeaderName, HeaderValue); 1] {
+    let value = HeaderValue::from_str(&format!("\"{version}\""))
+        .expect("quoted integer is a valid header value");
+    [(ETAG, value)]
+}
+
+/// Version the client expects from its `If-Match` header; `None` for `If-Match: *`.
+/// Writes without the header are rejected so clients can't overwrite changes blindly.
+pub fn expected_version(headers: &HeaderMap) -> Result<Option<i64>, ApiError> {
+    let value = headers
+        .get(IF_MATCH)
+        .ok_or_else(|| ApiError::PreconditionRequired("If-Match header is required".to_string()))?
+        .to_str()
+        .map_err(|_| ApiError::BadRequest("invalid If-Match header".to_string()))?
+        .trim();
+
+    if value == "*" {
+        return Ok(None);
+    }
+
+    // Weak ETags never match under If-Match, and neither does a value we never issued
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .and_then(|v| v.parse().ok())
+        .map(Some)
+        .ok_or_else(|| {
+            ApiError::PreconditionFailed("If-Match does not match the current ETag".to_string())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(if_match: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(IF_MATCH, HeaderValue::from_str(if_match).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_etag_is_quoted_version() {
+        let [(name, value)] = etag_header(7);
+        assert_eq!(name, ETAG);
+        assert_eq!(value, "\"7\"");
+    }
+
+    #[test]
+    fn test_expected_version_parses_strong_etag() {
+        assert_eq!(expected_version(&headers("\"7\"")).unwrap(), Some(7));
+        assert_eq!(expected_version(&headers("*")).unwrap(), None);
+    }
+
+    #[test]
+    fn test_missing_if_match_is_precondition_required() {
+        assert!(matches!(
+            expected_version(&HeaderMap::new()),
+            Err(ApiError::PreconditionRequired(_))
+        ));
+    }
+
+    #[test]
+    fn test_weak_or_unknown_etag_is_precondition_failed() {
+        for value in ["W/\"7\"", "7", "\"abc\""] {
+            assert!(matches!(
+                expected_version(&headers(value)),
+                Err(ApiError::PreconditionFailed(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::entities::ExecuteUserActionsRequest;
+use crate::methods::routes::USER_ACTIONS_PATH;
+use crate::state::AppState;
+use crate::tenant::Tenant;
+use axum::http::StatusCode;
+use axum::Json;
+use std::time::Duration;
+use uuid::Uuid;
+use validator::Validate;
+
+#[utoipa::path(
+    post,
+    path = USER_ACTIONS_PATH,
+    tag = "users",
+    params(
+        ("id" = String, Path, description = "User ID (UUID)")
+    ),
+    request_body = ExecuteUserActionsRequest,
+    responses(
+        (status = 204, description = "Keycloak emailed the user a link to complete the actions"),
+        (status = 400, description = "Invalid UUID, validation error or email rejected by Keycloak"),
+        (status = 404, description = "User not found"),
+        (status = 500, description = "Internal server error"),
+    )
+)]
+pub async fn execute_user_actions(
+    axum::extract::Path(id): axum::extract::Path<String>,
+    axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+    Json(payload): Json<ExecuteUserActionsRequest>,
+) -> Result<StatusCode, ApiError> {
+    let parsed_id = Uuid::parse_str(&id).map_err(|_| ApiError::invalid_uuid())?;
+
+    // Validate input
+    payload.validate()?;
+
+    let lifespan = payload.lifespan_secs.map(|s| Duration::from_secs(s.into()));
+
+    state
+        .user_service
+        .execute_user_actions(tenant, parsed_id, &payload.actions, lifespan)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(|e| handle_integrated_service_error(e, &state.env, "execute_user_actions"))
+}
@@ -0,0 +1,40 @@
+use crate::cache::CacheNamespace;
+use crate::error::ApiError;
+use crate::methods::entities::CacheFlushResponse;
+use crate::methods::routes::ADMIN_CACHE_NAMESPACE_PATH;
+use crate::state::AppState;
+use axum::Json;
+
+#[utoipa::path(
+    delete,
+    path = ADMIN_CACHE_NAMESPACE_PATH,
+    tag = "admin",
+    params(
+        ("namespace" = String, Path, description = "Cache namespace: user, users, role, roles or kc")
+    ),
+    responses(
+        (status = 200, description = "Namespace flushed", body = CacheFlushResponse),
+        (status = 400, description = "Unknown namespace"),
+        (status = 401, description = "Missing or invalid admin token"),
+        (status = 403, description = "Admin API disabled"),
+    )
+)]
+pub async fn flush_cache_namespace(
+    axum::extract::Path(namespace): axum::extract::Path<String>,
+    axum::extract::State(state): axum::extract::State<AppState>,
+) -> Result<Json<CacheFlushResponse>, ApiError> {
+    let parsed = CacheNamespace::parse(&namespace)
+        .ok_or_else(|| ApiError::BadRequest(format!("unknown cache namespace: {namespace}")))?;
+
+    let deleted = state.user_service.cache().delete_namespace(parsed).await;
+    tracing::info!(
+        namespace = parsed.as_str(),
+        deleted = deleted,
+        "Cache namespace flushed"
+    );
+
+    Ok(Json(CacheFlushResponse {
+        namespace: parsed.as_str().to_string(),
+        deleted,
+    }))
+}
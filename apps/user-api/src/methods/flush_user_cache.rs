@@ -0,0 +1,36 @@
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::routes::ADMIN_CACHE_USER_PATH;
+use crate::state::AppState;
+use crate::tenant::Tenant;
+use axum::http::StatusCode;
+use uuid::Uuid;
+
+#[utoipa::path(
+    delete,
+    path = ADMIN_CACHE_USER_PATH,
+    tag = "admin",
+    params(
+        ("id" = String, Path, description = "User ID (UUID)")
+    ),
+    responses(
+        (status = 204, description = "User cache entries evicted"),
+        (status = 400, description = "Invalid UUID"),
+        (status = 401, description = "Missing or invalid admin token"),
+        (status = 403, description = "Admin API disabled"),
+        (status = 500, description = "Internal server error"),
+    )
+)]
+pub async fn flush_user_cache(
+    axum::extract::Path(id): axum::extract::Path<String>,
+    axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+) -> Result<StatusCode, ApiError> {
+    let parsed_id = Uuid::parse_str(&id).map_err(|_| ApiError::invalid_uuid())?;
+
+    state
+        .user_service
+        .evict_user_cache(tenant, parsed_id)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(|e| handle_integrated_service_error(e, &state.env, "flush_user_cache"))
+}
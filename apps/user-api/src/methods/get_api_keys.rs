@@ -0,0 +1,29 @@
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::entities::ApiKeyResponse;
+use crate::methods::routes::ADMIN_API_KEYS_PATH;
+use crate::state::AppState;
+use crate::tenant::Tenant;
+use axum::Json;
+
+#[utoipa::path(
+    get,
+    path = ADMIN_API_KEYS_PATH,
+    tag = "admin",
+    responses(
+        (status = 200, description = "API keys of the tenant, revoked and expired ones included, newest first", body = Vec<ApiKeyResponse>),
+        (status = 401, description = "Missing or invalid admin token"),
+        (status = 403, description = "Admin API disabled"),
+        (status = 500, description = "Internal server error"),
+    )
+)]
+pub async fn get_api_keys(
+    axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+) -> Result<Json<Vec<ApiKeyResponse>>, ApiError> {
+    state
+        .user_service
+        .get_api_keys(tenant)
+        .await
+        .map(|keys| Json(keys.into_iter().map(ApiKeyResponse::from).collect()))
+        .map_err(|e| handle_integrated_service_error(e, &state.env, "get_api_keys"))
+}
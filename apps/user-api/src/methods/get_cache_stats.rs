@@ -0,0 +1,29 @@
+use crate::methods::entities::{CacheNamespaceStatsResponse, CacheStatsResponse};
+use crate::methods::routes::ADMIN_CACHE_STATS_PATH;
+use crate::state::AppState;
+use axum::Json;
+
+#[utoipa::path(
+    get,
+    path = ADMIN_CACHE_STATS_PATH,
+    tag = "admin",
+    responses(
+        (status = 200, description = "Cache counters per namespace", body = CacheStatsResponse),
+        (status = 401, description = "Missing or invalid admin token"),
+        (status = 403, description = "Admin API disabled"),
+    )
+)]
+pub async fn get_cache_stats(
+    axum::extract::State(state): axum::extract::State<AppState>,
+) -> Json<CacheStatsResponse> {
+    let cache = state.user_service.cache();
+
+    Json(CacheStatsResponse {
+        enabled: cache.is_enabled(),
+        namespaces: cache
+            .stats()
+            .into_iter()
+            .map(CacheNamespaceStatsResponse::from)
+            .collect(),
+    })
+}
@@ -0,0 +1,37 @@
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::entities::GroupResponse;
+use crate::methods::routes::GROUPS_BY_ID_PATH;
+use crate::state::AppState;
+use crate::tenant::Tenant;
+use axum::Json;
+use uuid::Uuid;
+
+#[utoipa::path(
+    get,
+    path = GROUPS_BY_ID_PATH,
+    tag = "groups",
+    params(
+        ("id" = String, Path, description = "Group ID (UUID)")
+    ),
+    responses(
+        (status = 200, description = "Group found", body = GroupResponse),
+        (status = 400, description = "Invalid UUID"),
+        (status = 404, description = "Group not found"),
+        (status = 500, description = "Internal server error"),
+    )
+)]
+pub async fn get_group_by_id(
+    axum::extract::Path(id): axum::extract::Path<String>,
+    axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+) -> Result<Json<GroupResponse>, ApiError> {
+    let parsed_id = Uuid::parse_str(&id).map_err(|_| ApiError::invalid_uuid())?;
+
+    state
+        .user_service
+        .get_group(tenant, parsed_id)
+        .await
+        .map_err(|e| handle_integrated_service_error(e, &state.env, "get_group"))?
+        .map(|group| Json(GroupResponse::from(group)))
+        .ok_or_else(ApiError::group_not_found)
+}
@@ -0,0 +1,38 @@
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::entities::{PaginatedResponse, PaginationQuery, UserResponse};
+use crate::methods::routes::GROUP_MEMBERS_PATH;
+use crate::state::AppState;
+use crate::tenant::Tenant;
+use axum::{extract::Query, Json};
+use uuid::Uuid;
+
+#[utoipa::path(
+    get,
+    path = GROUP_MEMBERS_PATH,
+    tag = "groups",
+    params(
+        ("id" = String, Path, description = "Group ID (UUID)"),
+        PaginationQuery
+    ),
+    responses(
+        (status = 200, description = "Members of the group", body = PaginatedResponse<UserResponse>),
+        (status = 400, description = "Invalid UUID"),
+        (status = 404, description = "Group not found"),
+        (status = 500, description = "Internal server error"),
+    )
+)]
+pub async fn get_group_members(
+    axum::extract::Path(id): axum::extract::Path<String>,
+    axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+    Query(pagination): Query<PaginationQuery>,
+) -> Result<Json<PaginatedResponse<UserResponse>>, ApiError> {
+    let parsed_id = Uuid::parse_str(&id).map_err(|_| ApiError::invalid_uuid())?;
+
+    state
+        .user_service
+        .get_group_members(tenant, parsed_id, pagination.into())
+        .await
+        .map(|result| Json(PaginatedResponse::from(result)))
+        .map_err(|e| handle_integrated_service_error(e, &state.env, "get_group_members"))
+}
@@ -0,0 +1,29 @@
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::entities::{GroupResponse, PaginatedResponse, PaginationQuery};
+use crate::methods::routes::GROUPS_PATH;
+use crate::state::AppState;
+use crate::tenant::Tenant;
+use axum::{extract::Query, Json};
+
+#[utoipa::path(
+    get,
+    path = GROUPS_PATH,
+    tag = "groups",
+    params(PaginationQuery),
+    responses(
+        (status = 200, description = "List of groups", body = PaginatedResponse<GroupResponse>),
+        (status = 500, description = "Internal server error"),
+    )
+)]
+pub async fn get_groups(
+    axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+    Query(pagination): Query<PaginationQuery>,
+) -> Result<Json<PaginatedResponse<GroupResponse>>, ApiError> {
+    state
+        .user_service
+        .get_groups(tenant, pagination.into())
+        .await
+        .map(|result| Json(PaginatedResponse::from(result)))
+        .map_err(|e| handle_integrated_service_error(e, &state.env, "get_groups"))
+}
@@ -0,0 +1,26 @@
+use crate::methods::entities::KeycloakRealmResponse;
+use crate::methods::routes::ADMIN_KEYCLOAK_REALMS_PATH;
+use crate::state::AppState;
+use axum::Json;
+
+#[utoipa::path(
+    get,
+    path = ADMIN_KEYCLOAK_REALMS_PATH,
+    tag = "admin",
+    responses(
+        (status = 200, description = "Realm of the default tenant and of every tenant with its own realm", body = Vec<KeycloakRealmResponse>),
+        (status = 401, description = "Missing or invalid admin token"),
+        (status = 403, description = "Admin API disabled"),
+    )
+)]
+pub async fn get_keycloak_realms(
+    axum::extract::State(state): axum::extract::State<AppState>,
+) -> Json<Vec<KeycloakRealmResponse>> {
+    let realms = state.user_service.keycloak().realms();
+    Json(
+        realms
+            .into_iter()
+            .map(KeycloakRealmResponse::from)
+            .collect(),
+    )
+}
@@ -1,7 +1,10 @@
 use crate::error::{handle_integrated_service_error, ApiError};
 use crate::methods::entities::RoleResponse;
+use crate::methods::etag::etag_header;
 use crate::methods::routes::ROLES_BY_ID_PATH;
 use crate::state::AppState;
+use crate::tenant::Tenant;
+use axum::response::IntoResponse;
 use axum::Json;
 use uuid::Uuid;
 
@@ -13,7 +16,8 @@ use uuid::Uuid;
         ("id" = String, Path, description = "Role ID (UUID)")
     ),
     responses(
-        (status = 200, description = "Role found", body = RoleResponse),
+        (status = 200, description = "Role found", body = RoleResponse,
+            headers(("ETag" = String, description = "Current version of the role"))),
         (status = 400, description = "Invalid UUID"),
         (status = 404, description = "Role not found"),
         (status = 500, description = "Internal server error"),
@@ -22,14 +26,15 @@ use uuid::Uuid;
 pub async fn get_role_by_id(
     axum::extract::Path(id): axum::extract::Path<String>,
     axum::extract::State(state): axum::extract::State<AppState>,
-) -> Result<Json<RoleResponse>, ApiError> {
+    Tenant(tenant): Tenant,
+) -> Result<impl IntoResponse, ApiError> {
     let parsed_id = Uuid::parse_str(&id).map_err(|_| ApiError::invalid_uuid())?;
 
     state
         .user_service
-        .get_role(parsed_id)
+        .get_role(tenant, parsed_id)
         .await
         .map_err(|e| handle_integrated_service_error(e, &state.env, "get_role"))?
-        .map(|role| Json(RoleResponse::from(role)))
+        .map(|role| (etag_header(role.version), Json(RoleResponse::from(role))))
         .ok_or_else(ApiError::role_not_found)
 }
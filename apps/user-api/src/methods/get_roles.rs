@@ -2,6 +2,7 @@ use crate::error::{handle_integrated_service_error, ApiError};
 use crate::methods::entities::{PaginatedResponse, PaginationQuery, RoleResponse};
 use crate::methods::routes::ROLES_PATH;
 use crate::state::AppState;
+use crate::tenant::Tenant;
 use axum::{extract::Query, Json};
 
 #[utoipa::path(
@@ -16,11 +17,12 @@ use axum::{extract::Query, Json};
 )]
 pub async fn get_roles(
     axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
     Query(pagination): Query<PaginationQuery>,
 ) -> Result<Json<PaginatedResponse<RoleResponse>>, ApiError> {
     state
         .user_service
-        .get_roles(pagination.into())
+        .get_roles(tenant, pagination.into())
         .await
         .map(|result| Json(PaginatedResponse::from(result)))
         .map_err(|e| handle_integrated_service_error(e, &state.env, "get_roles"))
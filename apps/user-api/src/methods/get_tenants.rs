@@ -0,0 +1,27 @@
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::entities::TenantResponse;
+use crate::methods::routes::ADMIN_TENANTS_PATH;
+use crate::state::AppState;
+use axum::Json;
+
+#[utoipa::path(
+    get,
+    path = ADMIN_TENANTS_PATH,
+    tag = "admin",
+    responses(
+        (status = 200, description = "All tenants, ordered by slug", body = Vec<TenantResponse>),
+        (status = 401, description = "Missing or invalid admin token"),
+        (status = 403, description = "Admin API disabled"),
+        (status = 500, description = "Internal server error"),
+    )
+)]
+pub async fn get_tenants(
+    axum::extract::State(state): axum::extract::State<AppState>,
+) -> Result<Json<Vec<TenantResponse>>, ApiError> {
+    state
+        .user_service
+        .get_tenants()
+        .await
+        .map(|tenants| Json(tenants.into_iter().map(TenantResponse::from).collect()))
+        .map_err(|e| handle_integrated_service_error(e, &state.env, "get_tenants"))
+}
@@ -1,7 +1,10 @@
 use crate::error::{handle_integrated_service_error, ApiError};
 use crate::methods::entities::UserResponse;
+use crate::methods::etag::etag_header;
 use crate::methods::routes::USERS_BY_ID_PATH;
 use crate::state::AppState;
+use crate::tenant::Tenant;
+use axum::response::IntoResponse;
 use axum::Json;
 use uuid::Uuid;
 
@@ -13,7 +16,8 @@ use uuid::Uuid;
         ("id" = String, Path, description = "User ID (UUID)")
     ),
     responses(
-        (status = 200, description = "User found", body = UserResponse),
+        (status = 200, description = "User found", body = UserResponse,
+            headers(("ETag" = String, description = "Current version of the user"))),
         (status = 400, description = "Invalid UUID"),
         (status = 404, description = "User not found"),
         (status = 500, description = "Internal server error"),
@@ -22,14 +26,15 @@ use uuid::Uuid;
 pub async fn get_user_by_id(
     axum::extract::Path(id): axum::extract::Path<String>,
     axum::extract::State(state): axum::extract::State<AppState>,
-) -> Result<Json<UserResponse>, ApiError> {
+    Tenant(tenant): Tenant,
+) -> Result<impl IntoResponse, ApiError> {
     let parsed_id = Uuid::parse_str(&id).map_err(|_| ApiError::invalid_uuid())?;
 
     state
         .user_service
-        .get_user(parsed_id)
+        .get_user(tenant, parsed_id)
         .await
         .map_err(|e| handle_integrated_service_error(e, &state.env, "get_user"))?
-        .map(|user| Json(UserResponse::from(user)))
+        .map(|user| (etag_header(user.version), Json(UserResponse::from(user))))
         .ok_or_else(ApiError::user_not_found)
 }
@@ -0,0 +1,43 @@
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::entities::UserSessionResponse;
+use crate::methods::routes::USER_SESSIONS_PATH;
+use crate::state::AppState;
+use crate::tenant::Tenant;
+use axum::Json;
+use uuid::Uuid;
+
+#[utoipa::path(
+    get,
+    path = USER_SESSIONS_PATH,
+    tag = "users",
+    params(
+        ("id" = String, Path, description = "User ID (UUID)")
+    ),
+    responses(
+        (status = 200, description = "Active Keycloak sessions of the user", body = Vec<UserSessionResponse>),
+        (status = 400, description = "Invalid UUID"),
+        (status = 404, description = "User not found"),
+        (status = 500, description = "Internal server error"),
+    )
+)]
+pub async fn get_user_sessions(
+    axum::extract::Path(id): axum::extract::Path<String>,
+    axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+) -> Result<Json<Vec<UserSessionResponse>>, ApiError> {
+    let parsed_id = Uuid::parse_str(&id).map_err(|_| ApiError::invalid_uuid())?;
+
+    state
+        .user_service
+        .get_user_sessions(tenant, parsed_id)
+        .await
+        .map(|sessions| {
+            Json(
+                sessions
+                    .into_iter()
+                    .map(UserSessionResponse::from)
+                    .collect(),
+            )
+        })
+        .map_err(|e| handle_integrated_service_error(e, &state.env, "get_user_sessions"))
+}
@@ -0,0 +1,35 @@
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::routes::USER_SESSIONS_PATH;
+use crate::state::AppState;
+use crate::tenant::Tenant;
+use axum::http::StatusCode;
+use uuid::Uuid;
+
+#[utoipa::path(
+    delete,
+    path = USER_SESSIONS_PATH,
+    tag = "users",
+    params(
+        ("id" = String, Path, description = "User ID (UUID)")
+    ),
+    responses(
+        (status = 204, description = "All sessions of the user ended"),
+        (status = 400, description = "Invalid UUID"),
+        (status = 404, description = "User not found"),
+        (status = 500, description = "Internal server error"),
+    )
+)]
+pub async fn logout_user_sessions(
+    axum::extract::Path(id): axum::extract::Path<String>,
+    axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+) -> Result<StatusCode, ApiError> {
+    let parsed_id = Uuid::parse_str(&id).map_err(|_| ApiError::invalid_uuid())?;
+
+    state
+        .user_service
+        .logout_user_sessions(tenant, parsed_id)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(|e| handle_integrated_service_error(e, &state.env, "logout_user_sessions"))
+}
@@ -1,15 +1,47 @@
+pub mod add_group_member;
+pub mod assign_group_role;
 pub mod assign_role;
+pub mod create_api_key;
+pub mod create_group;
 pub mod create_role;
+pub mod create_tenant;
 pub mod create_user;
+pub mod delete_group;
 pub mod delete_role;
 pub mod delete_user;
+pub mod delete_user_session;
 pub mod entities;
+pub mod etag;
+pub mod execute_user_actions;
+pub mod flush_cache_namespace;
+pub mod flush_user_cache;
+pub mod get_api_keys;
+pub mod get_cache_stats;
+pub mod get_group_by_id;
+pub mod get_group_members;
+pub mod get_groups;
+pub mod get_keycloak_realms;
 pub mod get_role_by_id;
 pub mod get_roles;
+pub mod get_tenants;
 pub mod get_user_by_id;
+pub mod get_user_sessions;
 pub mod get_users;
 pub mod health_check;
+pub mod logout_user_sessions;
+pub mod reactivate_user;
+pub mod reload_keycloak_realms;
+pub mod remove_group_member;
+pub mod restore_role;
+pub mod restore_user;
+pub mod revoke_api_key;
+pub mod rotate_api_key;
 pub mod routes;
+pub mod set_user_password;
+pub mod suspend_user;
+pub mod unassign_group_role;
 pub mod unassign_role;
+pub mod update_group;
 pub mod update_role;
 pub mod update_user;
+pub mod update_user_attributes;
@@ -0,0 +1,34 @@
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::entities::KeycloakRealmResponse;
+use crate::methods::routes::ADMIN_KEYCLOAK_REALMS_RELOAD_PATH;
+use crate::state::AppState;
+use axum::Json;
+
+#[utoipa::path(
+    post,
+    path = ADMIN_KEYCLOAK_REALMS_RELOAD_PATH,
+    tag = "admin",
+    responses(
+        (status = 200, description = "Realms file reloaded", body = Vec<KeycloakRealmResponse>),
+        (status = 401, description = "Missing or invalid admin token"),
+        (status = 403, description = "Admin API disabled"),
+        (status = 500, description = "Realms file could not be loaded; the previous realms stay active"),
+    )
+)]
+pub async fn reload_keycloak_realms(
+    axum::extract::State(state): axum::extract::State<AppState>,
+) -> Result<Json<Vec<KeycloakRealmResponse>>, ApiError> {
+    let realms = state
+        .user_service
+        .reload_keycloak_realms()
+        .await
+        .map_err(|e| handle_integrated_service_error(e, &state.env, "reload_realms"))?;
+    tracing::info!(realms = realms.len(), "Keycloak realms reloaded");
+
+    Ok(Json(
+        realms
+            .into_iter()
+            .map(KeycloakRealmResponse::from)
+            .collect(),
+    ))
+}
@@ -0,0 +1,38 @@
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::add_group_member::GroupMemberPath;
+use crate::methods::routes::GROUP_MEMBER_PATH;
+use crate::state::AppState;
+use crate::tenant::Tenant;
+use axum::http::StatusCode;
+use uuid::Uuid;
+
+#[utoipa::path(
+    delete,
+    path = GROUP_MEMBER_PATH,
+    tag = "groups",
+    params(
+        ("group_id" = String, Path, description = "Group ID (UUID)"),
+        ("user_id" = String, Path, description = "User ID (UUID)")
+    ),
+    responses(
+        (status = 204, description = "User is not a member of the group"),
+        (status = 400, description = "Invalid UUID"),
+        (status = 404, description = "Group not found"),
+        (status = 500, description = "Internal server error"),
+    )
+)]
+pub async fn remove_group_member(
+    axum::extract::Path(path): axum::extract::Path<GroupMemberPath>,
+    axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+) -> Result<StatusCode, ApiError> {
+    let group_id = Uuid::parse_str(&path.group_id).map_err(|_| ApiError::invalid_group_uuid())?;
+    let user_id = Uuid::parse_str(&path.user_id).map_err(|_| ApiError::invalid_user_uuid())?;
+
+    state
+        .user_service
+        .remove_group_member(tenant, group_id, user_id)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(|e| handle_integrated_service_error(e, &state.env, "remove_group_member"))
+}
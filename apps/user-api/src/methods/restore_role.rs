@@ -0,0 +1,38 @@
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::entities::RoleResponse;
+use crate::methods::routes::ADMIN_ROLE_RESTORE_PATH;
+use crate::state::AppState;
+use crate::tenant::Tenant;
+use axum::Json;
+use uuid::Uuid;
+
+#[utoipa::path(
+    post,
+    path = ADMIN_ROLE_RESTORE_PATH,
+    tag = "admin",
+    params(
+        ("id" = String, Path, description = "Role ID (UUID)")
+    ),
+    responses(
+        (status = 200, description = "Role restored", body = RoleResponse),
+        (status = 400, description = "Invalid UUID"),
+        (status = 401, description = "Missing or invalid admin token"),
+        (status = 403, description = "Admin API disabled"),
+        (status = 404, description = "No soft-deleted role with this ID"),
+        (status = 500, description = "Internal server error"),
+    )
+)]
+pub async fn restore_role(
+    axum::extract::Path(id): axum::extract::Path<String>,
+    axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+) -> Result<Json<RoleResponse>, ApiError> {
+    let parsed_id = Uuid::parse_str(&id).map_err(|_| ApiError::invalid_uuid())?;
+
+    state
+        .user_service
+        .restore_role(tenant, parsed_id)
+        .await
+        .map(|role| Json(RoleResponse::from(role)))
+        .map_err(|e| handle_integrated_service_error(e, &state.env, "restore_role"))
+}
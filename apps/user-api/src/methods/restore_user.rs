@@ -0,0 +1,38 @@
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::entities::UserResponse;
+use crate::methods::routes::ADMIN_USER_RESTORE_PATH;
+use crate::state::AppState;
+use crate::tenant::Tenant;
+use axum::Json;
+use uuid::Uuid;
+
+#[utoipa::path(
+    post,
+    path = ADMIN_USER_RESTORE_PATH,
+    tag = "admin",
+    params(
+        ("id" = String, Path, description = "User ID (UUID)")
+    ),
+    responses(
+        (status = 200, description = "User restored and re-enabled in Keycloak", body = UserResponse),
+        (status = 400, description = "Invalid UUID"),
+        (status = 401, description = "Missing or invalid admin token"),
+        (status = 403, description = "Admin API disabled"),
+        (status = 404, description = "No soft-deleted user with this ID"),
+        (status = 500, description = "Internal server error"),
+    )
+)]
+pub async fn restore_user(
+    axum::extract::Path(id): axum::extract::Path<String>,
+    axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+) -> Result<Json<UserResponse>, ApiError> {
+    let parsed_id = Uuid::parse_str(&id).map_err(|_| ApiError::invalid_uuid())?;
+
+    state
+        .user_service
+        .restore_user(tenant, parsed_id)
+        .await
+        .map(|user| Json(UserResponse::from(user)))
+        .map_err(|e| handle_integrated_service_error(e, &state.env, "restore_user"))
+}
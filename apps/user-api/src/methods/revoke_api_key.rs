@@ -0,0 +1,37 @@
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::routes::ADMIN_API_KEY_PATH;
+use crate::state::AppState;
+use crate::tenant::Tenant;
+use axum::http::StatusCode;
+use uuid::Uuid;
+
+#[utoipa::path(
+    delete,
+    path = ADMIN_API_KEY_PATH,
+    tag = "admin",
+    params(
+        ("id" = String, Path, description = "API key ID (UUID)")
+    ),
+    responses(
+        (status = 204, description = "API key revoked; it is kept for auditing but stops working"),
+        (status = 400, description = "Invalid UUID"),
+        (status = 401, description = "Missing or invalid admin token"),
+        (status = 403, description = "Admin API disabled"),
+        (status = 404, description = "No unrevoked API key with this ID"),
+        (status = 500, description = "Internal server error"),
+    )
+)]
+pub async fn revoke_api_key(
+    axum::extract::Path(id): axum::extract::Path<String>,
+    axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+) -> Result<StatusCode, ApiError> {
+    let parsed_id = Uuid::parse_str(&id).map_err(|_| ApiError::invalid_uuid())?;
+
+    state
+        .user_service
+        .revoke_api_key(tenant, parsed_id)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(|e| handle_integrated_service_error(e, &state.env, "revoke_api_key"))
+}
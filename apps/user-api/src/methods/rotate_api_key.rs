@@ -0,0 +1,38 @@
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::entities::IssuedApiKeyResponse;
+use crate::methods::routes::ADMIN_API_KEY_ROTATE_PATH;
+use crate::state::AppState;
+use crate::tenant::Tenant;
+use axum::Json;
+use uuid::Uuid;
+
+#[utoipa::path(
+    post,
+    path = ADMIN_API_KEY_ROTATE_PATH,
+    tag = "admin",
+    params(
+        ("id" = String, Path, description = "API key ID (UUID)")
+    ),
+    responses(
+        (status = 200, description = "New key issued and the old one stopped working; the key is shown only in this response", body = IssuedApiKeyResponse),
+        (status = 400, description = "Invalid UUID"),
+        (status = 401, description = "Missing or invalid admin token"),
+        (status = 403, description = "Admin API disabled"),
+        (status = 404, description = "No unrevoked API key with this ID"),
+        (status = 500, description = "Internal server error"),
+    )
+)]
+pub async fn rotate_api_key(
+    axum::extract::Path(id): axum::extract::Path<String>,
+    axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+) -> Result<Json<IssuedApiKeyResponse>, ApiError> {
+    let parsed_id = Uuid::parse_str(&id).map_err(|_| ApiError::invalid_uuid())?;
+
+    state
+        .user_service
+        .rotate_api_key(tenant, parsed_id)
+        .await
+        .map(|issued| Json(IssuedApiKeyResponse::from(issued)))
+        .map_err(|e| handle_integrated_service_error(e, &state.env, "rotate_api_key"))
+}
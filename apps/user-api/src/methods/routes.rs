@@ -1,9 +1,20 @@
 // API v1 routes (nested under /v1)
 pub const USERS_PATH: &str = "/users";
 pub const USERS_BY_ID_PATH: &str = "/users/{id}";
+pub const USER_PASSWORD_PATH: &str = "/users/{id}/password";
+pub const USER_ACTIONS_PATH: &str = "/users/{id}/actions";
+pub const USER_SUSPEND_PATH: &str = "/users/{id}/suspend";
+pub const USER_REACTIVATE_PATH: &str = "/users/{id}/reactivate";
+pub const USER_SESSIONS_PATH: &str = "/users/{id}/sessions";
+pub const USER_SESSION_PATH: &str = "/users/{user_id}/sessions/{session_id}";
 pub const USER_ROLES_PATH: &str = "/users/{user_id}/roles/{role_id}";
 pub const ROLES_PATH: &str = "/roles";
 pub const ROLES_BY_ID_PATH: &str = "/roles/{id}";
+pub const GROUPS_PATH: &str = "/groups";
+pub const GROUPS_BY_ID_PATH: &str = "/groups/{id}";
+pub const GROUP_MEMBERS_PATH: &str = "/groups/{id}/members";
+pub const GROUP_MEMBER_PATH: &str = "/groups/{group_id}/members/{user_id}";
+pub const GROUP_ROLES_PATH: &str = "/groups/{group_id}/roles/{role_id}";
 
 // Root-level service routes (not versioned)
 pub const SERVICE_HEALTH_PATH: &str = "/health";
@@ -11,3 +22,16 @@ pub const SERVICE_DOCS_PATH: &str = "/docs";
 
 // API version prefix
 pub const API_V1_PREFIX: &str = "/v1";
+
+// Admin routes (root level, guarded by the admin token)
+pub const ADMIN_CACHE_STATS_PATH: &str = "/admin/cache/stats";
+pub const ADMIN_CACHE_NAMESPACE_PATH: &str = "/admin/cache/{namespace}";
+pub const ADMIN_CACHE_USER_PATH: &str = "/admin/cache/users/{id}";
+pub const ADMIN_USER_RESTORE_PATH: &str = "/admin/users/{id}/restore";
+pub const ADMIN_ROLE_RESTORE_PATH: &str = "/admin/roles/{id}/restore";
+pub const ADMIN_TENANTS_PATH: &str = "/admin/tenants";
+pub const ADMIN_KEYCLOAK_REALMS_PATH: &str = "/admin/keycloak/realms";
+pub const ADMIN_KEYCLOAK_REALMS_RELOAD_PATH: &str = "/admin/keycloak/realms/reload";
+pub const ADMIN_API_KEYS_PATH: &str = "/admin/api-keys";
+pub const ADMIN_API_KEY_PATH: &str = "/admin/api-keys/{id}";
+pub const ADMIN_API_KEY_ROTATE_PATH: &str = "/admin/api-keys/{id}/rotate";
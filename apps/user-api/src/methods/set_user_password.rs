@@ -0,0 +1,43 @@
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::entities::SetUserPasswordRequest;
+use crate::methods::routes::USER_PASSWORD_PATH;
+use crate::state::AppState;
+use crate::tenant::Tenant;
+use axum::http::StatusCode;
+use axum::Json;
+use uuid::Uuid;
+use validator::Validate;
+
+#[utoipa::path(
+    post,
+    path = USER_PASSWORD_PATH,
+    tag = "users",
+    params(
+        ("id" = String, Path, description = "User ID (UUID)")
+    ),
+    request_body = SetUserPasswordRequest,
+    responses(
+        (status = 204, description = "Password set in Keycloak"),
+        (status = 400, description = "Invalid UUID, validation error or password rejected by the realm policy"),
+        (status = 404, description = "User not found"),
+        (status = 500, description = "Internal server error"),
+    )
+)]
+pub async fn set_user_password(
+    axum::extract::Path(id): axum::extract::Path<String>,
+    axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+    Json(payload): Json<SetUserPasswordRequest>,
+) -> Result<StatusCode, ApiError> {
+    let parsed_id = Uuid::parse_str(&id).map_err(|_| ApiError::invalid_uuid())?;
+
+    // Validate input
+    payload.validate()?;
+
+    state
+        .user_service
+        .set_user_password(tenant, parsed_id, &payload.password, payload.temporary)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(|e| handle_integrated_service_error(e, &state.env, "set_user_password"))
+}
@@ -0,0 +1,56 @@
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::entities::{SuspendUserRequest, UserResponse};
+use crate::methods::etag::{etag_header, expected_version};
+use crate::methods::routes::USER_SUSPEND_PATH;
+use crate::state::AppState;
+use crate::tenant::Tenant;
+use axum::http::HeaderMap;
+use axum::response::IntoResponse;
+use axum::Json;
+use uuid::Uuid;
+use validator::Validate;
+
+#[utoipa::path(
+    post,
+    path = USER_SUSPEND_PATH,
+    tag = "users",
+    params(
+        ("id" = String, Path, description = "User ID (UUID)"),
+        ("If-Match" = String, Header, description = "ETag from the last read, or `*` to skip the check")
+    ),
+    request_body(content = Option<SuspendUserRequest>, description = "Omit to suspend without a reason"),
+    responses(
+        (status = 200, description = "User disabled in Keycloak and marked suspended", body = UserResponse,
+            headers(("ETag" = String, description = "New version of the user"))),
+        (status = 400, description = "Invalid UUID or validation error"),
+        (status = 404, description = "User not found"),
+        (status = 412, description = "If-Match does not match the current ETag"),
+        (status = 428, description = "If-Match header missing"),
+        (status = 500, description = "Internal server error"),
+    )
+)]
+pub async fn suspend_user(
+    axum::extract::Path(id): axum::extract::Path<String>,
+    axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+    headers: HeaderMap,
+    payload: Option<Json<SuspendUserRequest>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let parsed_id = Uuid::parse_str(&id).map_err(|_| ApiError::invalid_uuid())?;
+    let expected_version = expected_version(&headers)?;
+    let payload = payload.map(|Json(payload)| payload).unwrap_or_default();
+    payload.validate()?;
+
+    state
+        .user_service
+        .suspend_user(
+            tenant,
+            parsed_id,
+            payload.reason.as_deref(),
+            payload.logout_sessions,
+            expected_version,
+        )
+        .await
+        .map(|user| (etag_header(user.version), Json(UserResponse::from(user))))
+        .map_err(|e| handle_integrated_service_error(e, &state.env, "suspend_user"))
+}
@@ -2,6 +2,7 @@ use crate::error::{handle_integrated_service_error, ApiError};
 use crate::methods::assign_role::UserRolePath;
 use crate::methods::routes::USER_ROLES_PATH;
 use crate::state::AppState;
+use crate::tenant::Tenant;
 use axum::http::StatusCode;
 use uuid::Uuid;
 
@@ -23,13 +24,14 @@ use uuid::Uuid;
 pub async fn unassign_role(
     axum::extract::Path(path): axum::extract::Path<UserRolePath>,
     axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
 ) -> Result<StatusCode, ApiError> {
     let user_id = Uuid::parse_str(&path.user_id).map_err(|_| ApiError::invalid_user_uuid())?;
     let role_id = Uuid::parse_str(&path.role_id).map_err(|_| ApiError::invalid_role_uuid())?;
 
     state
         .user_service
-        .unassign_role(user_id, role_id)
+        .unassign_role(tenant, user_id, role_id)
         .await
         .map(|_| StatusCode::NO_CONTENT)
         .map_err(|e| handle_integrated_service_error(e, &state.env, "unassign_role"))
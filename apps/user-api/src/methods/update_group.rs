@@ -0,0 +1,43 @@
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::entities::{GroupResponse, UpdateGroupRequest};
+use crate::methods::routes::GROUPS_BY_ID_PATH;
+use crate::state::AppState;
+use crate::tenant::Tenant;
+use axum::Json;
+use uuid::Uuid;
+use validator::Validate;
+
+#[utoipa::path(
+    put,
+    path = GROUPS_BY_ID_PATH,
+    tag = "groups",
+    params(
+        ("id" = String, Path, description = "Group ID (UUID)")
+    ),
+    request_body = UpdateGroupRequest,
+    responses(
+        (status = 200, description = "Group renamed in Keycloak and locally", body = GroupResponse),
+        (status = 400, description = "Invalid UUID or validation error"),
+        (status = 404, description = "Group not found"),
+        (status = 409, description = "Group name already exists"),
+        (status = 500, description = "Internal server error"),
+    )
+)]
+pub async fn update_group(
+    axum::extract::Path(id): axum::extract::Path<String>,
+    axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+    Json(payload): Json<UpdateGroupRequest>,
+) -> Result<Json<GroupResponse>, ApiError> {
+    // Validate input
+    payload.validate()?;
+
+    let parsed_id = Uuid::parse_str(&id).map_err(|_| ApiError::invalid_uuid())?;
+
+    state
+        .user_service
+        .rename_group(tenant, parsed_id, &payload.name)
+        .await
+        .map(|group| Json(GroupResponse::from(group)))
+        .map_err(|e| handle_integrated_service_error(e, &state.env, "update_group"))
+}
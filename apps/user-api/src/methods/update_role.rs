@@ -1,7 +1,11 @@
 use crate::error::{handle_integrated_service_error, ApiError};
 use crate::methods::entities::{RoleResponse, UpdateRoleRequest};
+use crate::methods::etag::{etag_header, expected_version};
 use crate::methods::routes::ROLES_BY_ID_PATH;
 use crate::state::AppState;
+use crate::tenant::Tenant;
+use axum::http::HeaderMap;
+use axum::response::IntoResponse;
 use axum::Json;
 use uuid::Uuid;
 use validator::Validate;
@@ -11,31 +15,45 @@ use validator::Validate;
     path = ROLES_BY_ID_PATH,
     tag = "roles",
     params(
-        ("id" = String, Path, description = "Role ID (UUID)")
+        ("id" = String, Path, description = "Role ID (UUID)"),
+        ("If-Match" = String, Header, description = "ETag from the last read, or `*` to skip the check")
     ),
     request_body = UpdateRoleRequest,
     responses(
-        (status = 200, description = "Role updated successfully", body = RoleResponse),
+        (status = 200, description = "Role updated successfully", body = RoleResponse,
+            headers(("ETag" = String, description = "New version of the role"))),
         (status = 400, description = "Invalid UUID or validation error"),
+        (status = 403, description = "System roles cannot be renamed"),
         (status = 404, description = "Role not found"),
         (status = 409, description = "Role name already exists"),
+        (status = 412, description = "If-Match does not match the current ETag"),
+        (status = 428, description = "If-Match header missing"),
         (status = 500, description = "Internal server error"),
     )
 )]
 pub async fn update_role(
     axum::extract::Path(id): axum::extract::Path<String>,
     axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+    headers: HeaderMap,
     Json(payload): Json<UpdateRoleRequest>,
-) -> Result<Json<RoleResponse>, ApiError> {
+) -> Result<impl IntoResponse, ApiError> {
     // Validate input
     payload.validate()?;
 
     let parsed_id = Uuid::parse_str(&id).map_err(|_| ApiError::invalid_uuid())?;
+    let expected_version = expected_version(&headers)?;
 
     state
         .user_service
-        .update_role(parsed_id, &payload.name)
+        .update_role(
+            tenant,
+            parsed_id,
+            &payload.name,
+            payload.description.as_deref(),
+            expected_version,
+        )
         .await
-        .map(|role| Json(RoleResponse::from(role)))
+        .map(|role| (etag_header(role.version), Json(RoleResponse::from(role))))
         .map_err(|e| handle_integrated_service_error(e, &state.env, "update_role"))
 }
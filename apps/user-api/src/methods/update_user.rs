@@ -1,8 +1,12 @@
 use crate::error::{handle_integrated_service_error, ApiError};
 use crate::methods::entities::{UpdateUserRequest, UserResponse};
+use crate::methods::etag::{etag_header, expected_version};
 use crate::methods::routes::USERS_BY_ID_PATH;
 use crate::services::integrated_user_service::UpdateUserRequest as ServiceUpdateUserRequest;
 use crate::state::AppState;
+use crate::tenant::Tenant;
+use axum::http::HeaderMap;
+use axum::response::IntoResponse;
 use axum::Json;
 use uuid::Uuid;
 use validator::Validate;
@@ -12,35 +16,44 @@ use validator::Validate;
     path = USERS_BY_ID_PATH,
     tag = "users",
     params(
-        ("id" = String, Path, description = "User ID (UUID)")
+        ("id" = String, Path, description = "User ID (UUID)"),
+        ("If-Match" = String, Header, description = "ETag from the last read, or `*` to skip the check")
     ),
     request_body = UpdateUserRequest,
     responses(
-        (status = 200, description = "User updated successfully", body = UserResponse),
+        (status = 200, description = "User updated successfully", body = UserResponse,
+            headers(("ETag" = String, description = "New version of the user"))),
         (status = 400, description = "Invalid UUID or validation error"),
         (status = 404, description = "User not found"),
+        (status = 409, description = "Email already used by another user"),
+        (status = 412, description = "If-Match does not match the current ETag"),
+        (status = 428, description = "If-Match header missing"),
         (status = 500, description = "Internal server error"),
     )
 )]
 pub async fn update_user(
     axum::extract::Path(id): axum::extract::Path<String>,
     axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+    headers: HeaderMap,
     Json(payload): Json<UpdateUserRequest>,
-) -> Result<Json<UserResponse>, ApiError> {
+) -> Result<impl IntoResponse, ApiError> {
     // Validate input
     payload.validate()?;
 
     let parsed_id = Uuid::parse_str(&id).map_err(|_| ApiError::invalid_uuid())?;
+    let expected_version = expected_version(&headers)?;
 
     let request = ServiceUpdateUserRequest {
         first_name: payload.first_name,
         last_name: payload.last_name,
+        email: payload.email,
     };
 
     state
         .user_service
-        .update_user(parsed_id, request)
+        .update_user(tenant, parsed_id, request, expected_version)
         .await
-        .map(|user| Json(UserResponse::from(user)))
+        .map(|user| (etag_header(user.version), Json(UserResponse::from(user))))
         .map_err(|e| handle_integrated_service_error(e, &state.env, "update_user"))
 }
@@ -0,0 +1,47 @@
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::methods::entities::{UpdateUserAttributesRequest, UserResponse};
+use crate::methods::etag::{etag_header, expected_version};
+use crate::methods::routes::USERS_BY_ID_PATH;
+use crate::state::AppState;
+use crate::tenant::Tenant;
+use axum::http::HeaderMap;
+use axum::response::IntoResponse;
+use axum::Json;
+use uuid::Uuid;
+
+#[utoipa::path(
+    patch,
+    path = USERS_BY_ID_PATH,
+    tag = "users",
+    params(
+        ("id" = String, Path, description = "User ID (UUID)"),
+        ("If-Match" = String, Header, description = "ETag from the last read, or `*` to skip the check")
+    ),
+    request_body = UpdateUserAttributesRequest,
+    responses(
+        (status = 200, description = "Attributes updated successfully", body = UserResponse,
+            headers(("ETag" = String, description = "New version of the user"))),
+        (status = 400, description = "Invalid UUID or attribute value"),
+        (status = 404, description = "User not found"),
+        (status = 412, description = "If-Match does not match the current ETag"),
+        (status = 428, description = "If-Match header missing"),
+        (status = 500, description = "Internal server error"),
+    )
+)]
+pub async fn update_user_attributes(
+    axum::extract::Path(id): axum::extract::Path<String>,
+    axum::extract::State(state): axum::extract::State<AppState>,
+    Tenant(tenant): Tenant,
+    headers: HeaderMap,
+    Json(payload): Json<UpdateUserAttributesRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let parsed_id = Uuid::parse_str(&id).map_err(|_| ApiError::invalid_uuid())?;
+    let expected_version = expected_version(&headers)?;
+
+    state
+        .user_service
+        .update_user_attributes(tenant, parsed_id, &payload.into(), expected_version)
+        .await
+        .map(|user| (etag_header(user.version), Json(UserResponse::from(user))))
+        .map_err(|e| handle_integrated_service_error(e, &state.env, "update_user_attributes"))
+}
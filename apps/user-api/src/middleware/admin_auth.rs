@@ -0,0 +1,139 @@
+use axum::{
+    body::Body,
+    http::{header, HeaderMap, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use secrecy::{ExposeSecret, Secret};
+use serde::Serialize;
+use std::sync::Arc;
+
+const ADMIN_TOKEN_HEADER: &str = "x-admin-token";
+
+#[derive(Debug, Serialize)]
+struct AdminAuthErrorResponse {
+    error: &'static str,
+    message: &'static str,
+}
+
+#[derive(Clone, Debug)]
+pub struct AdminAuthConfig {
+    token: Option<Arc<Secret<String>>>,
+}
+
+impl AdminAuthConfig {
+    pub fn new(token: Option<Secret<String>>) -> Self {
+        Self {
+            token: token
+                .filter(|t| !t.expose_secret().is_empty())
+                .map(Arc::new),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.token.is_some()
+    }
+
+    pub fn is_authorized(&self, presented: &str) -> bool {
+        match &self.token {
+            Some(token) => constant_time_eq(token.expose_secret().as_bytes(), presented.as_bytes()),
+            None => false,
+        }
+    }
+}
+
+/// Compare without short-circuiting so response timing does not leak the token prefix
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Token from `Authorization: Bearer <token>` or `X-Admin-Token: <token>`
+fn presented_token(headers: &HeaderMap) -> Option<&str> {
+    if let Some(bearer) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+    {
+        return Some(bearer.trim());
+    }
+
+    headers
+        .get(ADMIN_TOKEN_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+}
+
+pub async fn admin_auth_middleware(request: Request<Body>, next: Next) -> Response {
+    let config = request.extensions().get::<AdminAuthConfig>().cloned();
+
+    let Some(config) = config.filter(AdminAuthConfig::is_enabled) else {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(AdminAuthErrorResponse {
+                error: "forbidden",
+                message: "admin API is disabled",
+            }),
+        )
+            .into_response();
+    };
+
+    let authorized = presented_token(request.headers()).is_some_and(|t| config.is_authorized(t));
+    if !authorized {
+        tracing::warn!(path = %request.uri().path(), "Rejected admin request with missing or invalid token");
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(AdminAuthErrorResponse {
+                error: "unauthorized",
+                message: "missing or invalid admin token",
+            }),
+        )
+            .into_response();
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_admin_auth_disabled_without_token() {
+        let config = AdminAuthConfig::new(None);
+        assert!(!config.is_enabled());
+        assert!(!config.is_authorized(""));
+
+        let config = AdminAuthConfig::new(Some(Secret::new(String::new())));
+        assert!(!config.is_enabled());
+    }
+
+    #[test]
+    fn test_admin_auth_checks_token() {
+        let config = AdminAuthConfig::new(Some(Secret::new("s3cret".to_string())));
+        assert!(config.is_authorized("s3cret"));
+        assert!(!config.is_authorized("s3cre"));
+        assert!(!config.is_authorized("s3cret!"));
+        assert!(!config.is_authorized("S3CRET"));
+    }
+
+    #[test]
+    fn test_presented_token_sources() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(presented_token(&headers), None);
+
+        headers.insert(ADMIN_TOKEN_HEADER, HeaderValue::from_static("from-header"));
+        assert_eq!(presented_token(&headers), Some("from-header"));
+
+        // Bearer takes precedence over the custom header
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer from-bearer"),
+        );
+        assert_eq!(presented_token(&headers), Some("from-bearer"));
+    }
+}
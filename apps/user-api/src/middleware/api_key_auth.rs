@@ -0,0 +1,261 @@
+//! Scope and tenant checks for callers of the v1 routes.
+
+use axum::{
+    body::Body,
+    extract::{MatchedPath, State},
+    http::{HeaderValue, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use user_lib::repository::traits::{
+    RoleRepositoryTrait, UserRepositoryTrait, UserRoleRepositoryTrait,
+};
+
+use crate::api_key::{ApiKeyScope, Caller};
+use crate::error::ApiError;
+use crate::state::AppState;
+use crate::tenant::{caller_tenant, TENANT_HEADER};
+
+/// Let a service account through to the routes its key's scopes cover, and pin every
+/// request to its caller's tenant: a request naming another tenant is refused, and
+/// `X-Tenant-ID` is set to the caller's tenant for what runs after. Requests with neither
+/// an API key nor a valid bearer token are refused by the [`Caller`] extractor.
+pub async fn api_key_auth_middleware<U, R, UR>(
+    State(state): State<AppState<U, R, UR>>,
+    caller: Caller,
+    mut request: Request<Body>,
+    next: Next,
+) -> Response
+where
+    U: UserRepositoryTrait + Send + Sync + 'static,
+    R: RoleRepositoryTrait + Send + Sync + 'static,
+    UR: UserRoleRepositoryTrait + Send + Sync + 'static,
+{
+    if let Caller::ServiceAccount(key) = &caller {
+        let path = request
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|p| p.as_str().to_string())
+            .unwrap_or_else(|| request.uri().path().to_string());
+        let Some(scope) = ApiKeyScope::required_for(request.method(), &path) else {
+            return ApiError::Forbidden("API keys cannot call this endpoint".to_string())
+                .into_response();
+        };
+        if !key.has_scope(scope.as_str()) {
+            return ApiError::Forbidden(format!("API key lacks the {} scope", scope.as_str()))
+                .into_response();
+        }
+    }
+
+    let tenant = match caller_tenant(&caller, request.headers(), state.user_service.keycloak()) {
+        Ok(tenant) => tenant,
+        Err(e) => return e.into_response(),
+    };
+    if let Ok(value) = HeaderValue::from_str(&tenant.to_string()) {
+        request.headers_mut().insert(TENANT_HEADER, value);
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::to_bytes,
+        http::StatusCode,
+        routing::{get, post},
+        Router,
+    };
+    use secrecy::ExposeSecret;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+    use user_lib::entities::{NewApiKey, TenantId};
+    use user_lib::repository::{
+        InMemoryApiKeyRepository, InMemoryRoleRepository, InMemoryStore, InMemoryUserRepository,
+        InMemoryUserRoleRepository,
+    };
+    use user_lib::user_service::UserService;
+
+    use crate::api_key::API_KEY_HEADER;
+    use crate::cache::{Cache, CacheConfig, CachedUserService};
+    use crate::keycloak::{KeycloakClient, KeycloakConfig};
+    use crate::services::IntegratedUserService;
+    use crate::state::AppState;
+
+    type TestState =
+        AppState<InMemoryUserRepository, InMemoryRoleRepository, InMemoryUserRoleRepository>;
+
+    fn state() -> TestState {
+        let (users, roles, user_roles) = InMemoryStore::new().repositories();
+        let service =
+            UserService::with_repos(Arc::new(users), Arc::new(roles), Arc::new(user_roles))
+                .with_api_key_repository(Arc::new(InMemoryApiKeyRepository::new()));
+        let cached = CachedUserService::new(
+            Arc::new(service),
+            Cache::disabled(),
+            CacheConfig::from_env(),
+        );
+        let keycloak = KeycloakClient::new(KeycloakConfig {
+            base_url: "http://keycloak:8080".to_string(),
+            realm: "master".to_string(),
+            client_id: "user-api-service".to_string(),
+            client_secret: String::new(),
+            profile_cache_ttl: std::time::Duration::from_secs(300),
+        });
+        AppState {
+            user_service: Arc::new(IntegratedUserService::new(
+                Arc::new(cached),
+                Arc::new(keycloak),
+                Cache::disabled(),
+            )),
+            env: "local".to_string(),
+        }
+    }
+
+    /// Routes answering with the tenant header they received
+    fn app(state: TestState) -> Router {
+        let echo_tenant = |request: Request<Body>| async move {
+            request
+                .headers()
+                .get(TENANT_HEADER)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default()
+                .to_string()
+        };
+        Router::new()
+            .route("/users", get(echo_tenant).post(echo_tenant))
+            .route("/users/{user_id}/roles/{role_id}", post(echo_tenant))
+            .route("/tenants", get(echo_tenant))
+            .route_layer(axum::middleware::from_fn_with_state(
+                state.clone(),
+                api_key_auth_middleware,
+            ))
+            .with_state(state)
+    }
+
+    async fn issue_key(state: &TestState, tenant: TenantId, scopes: &[&str]) -> String {
+        let new_key = NewApiKey {
+            name: "batch".to_string(),
+            scopes: scopes.iter().map(|s| s.to_string()).collect(),
+            expires_at: None,
+        };
+        let issued = state
+            .user_service
+            .create_api_key(tenant, &new_key)
+            .await
+            .unwrap();
+        issued.key.expose_secret().clone()
+    }
+
+    async fn send(
+        router: &Router,
+        method: &str,
+        path: &str,
+        headers: &[(&str, &str)],
+    ) -> (StatusCode, String) {
+        let mut request = Request::builder().method(method).uri(path);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let response = router
+            .clone()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, String::from_utf8(bytes.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_requests_without_a_key_need_a_valid_bearer_token() {
+        let router = app(state());
+
+        let (status, _) = send(
+            &router,
+            "POST",
+            "/users",
+            &[("authorization", "Bearer opaque-token")],
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, _) = send(&router, "POST", "/users", &[]).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = send(&router, "POST", "/users", &[("authorization", "Bearer ")]).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_key_reaches_only_its_scopes_in_its_tenant() {
+        let state = state();
+        let tenant = TenantId(uuid::Uuid::new_v4());
+        let key = issue_key(&state, tenant, &["users:read"]).await;
+        let router = app(state);
+
+        let (status, seen) = send(&router, "GET", "/users", &[(API_KEY_HEADER, &key)]).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(seen, tenant.to_string());
+
+        let (status, _) = send(&router, "POST", "/users", &[(API_KEY_HEADER, &key)]).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let (status, _) = send(&router, "GET", "/tenants", &[(API_KEY_HEADER, &key)]).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let other = TenantId::DEFAULT.to_string();
+        let (status, _) = send(
+            &router,
+            "GET",
+            "/users",
+            &[(API_KEY_HEADER, &key), (TENANT_HEADER, &other)],
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_granting_roles_needs_its_own_scope() {
+        let state = state();
+        let tenant = TenantId(uuid::Uuid::new_v4());
+        let users_key = issue_key(&state, tenant, &["users:read", "users:write"]).await;
+        let grant_key = issue_key(&state, tenant, &["roles:grant"]).await;
+        let router = app(state);
+        let path = format!(
+            "/users/{}/roles/{}",
+            uuid::Uuid::new_v4(),
+            uuid::Uuid::new_v4()
+        );
+
+        let (status, body) = send(&router, "POST", &path, &[(API_KEY_HEADER, &users_key)]).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert!(body.contains("roles:grant"));
+
+        let (status, _) = send(&router, "POST", &path, &[(API_KEY_HEADER, &grant_key)]).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_and_revoked_keys_are_unauthorized() {
+        let state = state();
+        let key = issue_key(&state, TenantId::DEFAULT, &["users:read"]).await;
+        let router = app(state.clone());
+
+        let (status, _) = send(&router, "GET", "/users", &[(API_KEY_HEADER, "uak_nope")]).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let key_id = state
+            .user_service
+            .get_api_keys(TenantId::DEFAULT)
+            .await
+            .unwrap()[0]
+            .id;
+        state
+            .user_service
+            .revoke_api_key(TenantId::DEFAULT, key_id)
+            .await
+            .unwrap();
+        let (status, _) = send(&router, "GET", "/users", &[(API_KEY_HEADER, &key)]).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+}
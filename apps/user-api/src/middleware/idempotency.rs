@@ -0,0 +1,701 @@
+//! `Idempotency-Key` support for POST requests. The first response for a key is stored
+//! and replayed on retries, so a client retrying after a timeout cannot create a user twice.
+//!
+//! Records live in the `idempotency_keys` table, which alone decides which request holds a
+//! key. With a cache backend, completed responses are copied there too, so retries are
+//! usually answered without a database read. A reservation only lasts as long as one
+//! request may run; a completed response is kept for the configured TTL.
+//!
+//! Keys are scoped to the request's tenant and caller, so two callers may use the same
+//! key without getting each other's responses.
+
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    http::{header, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use user_lib::entities::TenantId;
+use user_lib::repository::models::IdempotencyRow;
+use user_lib::repository::traits::IdempotencyRepositoryTrait;
+
+use crate::api_key::Caller;
+use crate::cache::{keys, CacheBackend};
+use crate::error::ErrorResponse;
+use crate::tenant::tenant_from_header;
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+/// Set to `true` on responses replayed from a stored record
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+const MAX_KEY_LENGTH: usize = 255;
+
+/// Headers stored with a response and replayed with it; the others are set again by the
+/// layers around the handler
+const REPLAYED_HEADERS: [header::HeaderName; 2] = [header::LOCATION, header::ETAG];
+
+#[derive(Clone)]
+pub struct IdempotencyConfig {
+    cache: Option<Arc<dyn CacheBackend>>,
+    db: Arc<dyn IdempotencyRepositoryTrait>,
+    ttl: Duration,
+    pending_ttl: Duration,
+    max_body_size: usize,
+}
+
+impl std::fmt::Debug for IdempotencyConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IdempotencyConfig")
+            .field("cache", &self.cache.is_some())
+            .field("ttl", &self.ttl)
+            .field("pending_ttl", &self.pending_ttl)
+            .field("max_body_size", &self.max_body_size)
+            .finish()
+    }
+}
+
+impl IdempotencyConfig {
+    pub fn new(
+        cache: Option<Arc<dyn CacheBackend>>,
+        db: Arc<dyn IdempotencyRepositoryTrait>,
+    ) -> Self {
+        Self {
+            cache,
+            db,
+            ttl: Duration::from_secs(86_400),
+            pending_ttl: Duration::from_secs(30),
+            max_body_size: 1_048_576,
+        }
+    }
+
+    /// How long a completed response is replayed
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// How long a key stays reserved by a request that never completes (e.g. timed out).
+    /// Should match the request timeout.
+    pub fn with_pending_ttl(mut self, pending_ttl: Duration) -> Self {
+        self.pending_ttl = pending_ttl;
+        self
+    }
+
+    pub fn with_max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+
+    /// Completed record copied to the cache backend; a cache failure just means a
+    /// database read
+    async fn cached_record(&self, key: &str) -> Option<Record> {
+        let cache = self.cache.as_ref().filter(|cache| cache.is_available())?;
+        match cache.get(&keys::idempotency_key(key)).await {
+            Ok(data) => data.and_then(|d| serde_json::from_str(&d).ok()),
+            Err(e) => {
+                tracing::debug!(error = %e, "Failed to read cached idempotent response");
+                None
+            }
+        }
+    }
+
+    /// Copy a completed record to the cache backend until the database record expires
+    async fn cache_record(&self, key: &str, record: &Record, expires_at: DateTime<Utc>) {
+        let Some(cache) = self.cache.as_ref().filter(|cache| cache.is_available()) else {
+            return;
+        };
+        let Ok(ttl) = (expires_at - Utc::now()).to_std() else {
+            return;
+        };
+        let Ok(data) = serde_json::to_string(record) else {
+            return;
+        };
+        if let Err(e) = cache.set(&keys::idempotency_key(key), &data, ttl).await {
+            tracing::debug!(error = %e, "Failed to cache idempotent response");
+        }
+    }
+
+    async fn reserve(&self, key: &str, fingerprint: &str) -> Result<Reservation, String> {
+        if let Some(record) = self.cached_record(key).await {
+            return Ok(Reservation::Existing(record));
+        }
+        // A record can expire between the failed claim and the lookup; claim it again then
+        for _ in 0..2 {
+            let claimed = self
+                .db
+                .reserve_idempotency_key(key, fingerprint, expires_at(self.pending_ttl))
+                .await
+                .map_err(|e| e.to_string())?;
+            if claimed {
+                return Ok(Reservation::Reserved);
+            }
+            let row = self
+                .db
+                .get_idempotency_key(key)
+                .await
+                .map_err(|e| e.to_string())?;
+            if let Some(row) = row {
+                let expires_at = row.expires_at;
+                let record = Record::from(row);
+                if record.response.is_some() {
+                    self.cache_record(key, &record, expires_at).await;
+                }
+                return Ok(Reservation::Existing(record));
+            }
+        }
+        Err("idempotency key is contended".to_string())
+    }
+
+    async fn complete(
+        &self,
+        key: &str,
+        fingerprint: &str,
+        response: StoredResponse,
+    ) -> Result<(), String> {
+        let expires_at = expires_at(self.ttl);
+        let headers = serde_json::to_string(&response.headers).map_err(|e| e.to_string())?;
+        self.db
+            .complete_idempotency_key(
+                key,
+                i32::from(response.status),
+                &response.body,
+                &headers,
+                expires_at,
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let record = Record {
+            fingerprint: fingerprint.to_string(),
+            response: Some(response),
+        };
+        self.cache_record(key, &record, expires_at).await;
+        Ok(())
+    }
+
+    /// Drop our reservation; stored responses are never released
+    async fn release(&self, key: &str) -> Result<(), String> {
+        self.db
+            .release_idempotency_key(key)
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Response kept for replay
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct StoredResponse {
+    status: u16,
+    body: String,
+    /// Values of the `REPLAYED_HEADERS` the response carried
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+}
+
+/// What a key holds: the request it was reserved for and, once done, its response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Record {
+    fingerprint: String,
+    response: Option<StoredResponse>,
+}
+
+impl From<IdempotencyRow> for Record {
+    fn from(row: IdempotencyRow) -> Self {
+        Self {
+            fingerprint: row.fingerprint,
+            response: row.status_code.map(|status| StoredResponse {
+                status: status as u16,
+                body: row.response_body.unwrap_or_default(),
+                headers: row
+                    .response_headers
+                    .and_then(|headers| serde_json::from_str(&headers).ok())
+                    .unwrap_or_default(),
+            }),
+        }
+    }
+}
+
+enum Reservation {
+    /// The key is ours: run the request, then complete or release the key
+    Reserved,
+    /// An earlier request holds the key
+    Existing(Record),
+}
+
+fn expires_at(ttl: Duration) -> DateTime<Utc> {
+    chrono::Duration::from_std(ttl)
+        .ok()
+        .and_then(|ttl| Utc::now().checked_add_signed(ttl))
+        .unwrap_or(DateTime::<Utc>::MAX_UTC)
+}
+
+fn is_valid_key(key: &str) -> bool {
+    !key.is_empty() && key.len() <= MAX_KEY_LENGTH && key.bytes().all(|b| b.is_ascii_graphic())
+}
+
+/// Hash of what makes two requests "the same": method, path and body
+/// Key of the record for `key` sent by `caller` in `tenant`. The caller's identity and
+/// the key are hashed together, which keeps the record key within the column's length.
+fn record_key(tenant: TenantId, caller: Option<&Caller>, key: &str) -> String {
+    let mut hasher = Sha256::new();
+    if let Some(caller) = caller {
+        hasher.update(caller.identity().as_bytes());
+    }
+    hasher.update(b"\n");
+    hasher.update(key.as_bytes());
+    format!("{tenant}:{:x}", hasher.finalize())
+}
+
+/// Hash of what makes two requests the same: method, path with query, and body
+fn fingerprint(method: &str, path_and_query: &str, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(path_and_query.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(body);
+    format!("{:x}", hasher.finalize())
+}
+
+fn error_response(status: StatusCode, error: &str, message: &str) -> Response {
+    (
+        status,
+        Json(ErrorResponse {
+            error: error.to_string(),
+            message: Some(message.to_string()),
+        }),
+    )
+        .into_response()
+}
+
+fn replay(response: StoredResponse) -> Response {
+    let status = StatusCode::from_u16(response.status).unwrap_or(StatusCode::OK);
+    let has_body = !response.body.is_empty();
+    let mut replayed = (status, response.body).into_response();
+    let headers = replayed.headers_mut();
+    for (name, value) in &response.headers {
+        if let (Ok(name), Ok(value)) = (
+            header::HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            headers.insert(name, value);
+        }
+    }
+    headers.insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+    if has_body {
+        // Every handler behind this middleware answers with JSON
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        );
+    } else {
+        headers.remove(header::CONTENT_TYPE);
+    }
+    replayed
+}
+
+pub async fn idempotency_middleware(request: Request<Body>, next: Next) -> Response {
+    let config = request.extensions().get::<IdempotencyConfig>().cloned();
+    let key = request.headers().get(IDEMPOTENCY_KEY_HEADER).cloned();
+
+    let (Some(config), Some(key)) = (config, key) else {
+        return next.run(request).await;
+    };
+    if request.method() != axum::http::Method::POST {
+        return next.run(request).await;
+    }
+    let Some(key) = key
+        .to_str()
+        .ok()
+        .filter(|k| is_valid_key(k))
+        .map(str::to_string)
+    else {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "bad_request",
+            "Idempotency-Key must be 1-255 visible ASCII characters",
+        );
+    };
+    // A request with an unusable tenant is rejected by the handler; there is nothing to record
+    let Ok(tenant) = tenant_from_header(request.headers()) else {
+        return next.run(request).await;
+    };
+    let tenant = tenant.unwrap_or(TenantId::DEFAULT);
+    let key = record_key(tenant, request.extensions().get::<Caller>(), &key);
+
+    // The body is part of the fingerprint, so it has to be buffered before the handler runs
+    let (parts, body) = request.into_parts();
+    let Ok(bytes) = to_bytes(body, config.max_body_size).await else {
+        return error_response(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "payload_too_large",
+            "request body is too large",
+        );
+    };
+    let path_and_query = parts
+        .uri
+        .path_and_query()
+        .map_or_else(|| parts.uri.path(), |p| p.as_str());
+    let fingerprint = fingerprint(parts.method.as_str(), path_and_query, &bytes);
+
+    let reservation = match config.reserve(&key, &fingerprint).await {
+        Ok(reservation) => reservation,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to reserve idempotency key");
+            return error_response(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "service_unavailable",
+                "idempotency records are unavailable, retry later",
+            );
+        }
+    };
+
+    match reservation {
+        Reservation::Existing(record) if record.fingerprint != fingerprint => error_response(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "idempotency_key_reused",
+            "Idempotency-Key was already used for a different request",
+        ),
+        Reservation::Existing(Record {
+            response: Some(response),
+            ..
+        }) => {
+            tracing::debug!(key = %key, "Replaying stored idempotent response");
+            replay(response)
+        }
+        Reservation::Existing(Record { response: None, .. }) => error_response(
+            StatusCode::CONFLICT,
+            "conflict",
+            "a request with this Idempotency-Key is still in progress",
+        ),
+        Reservation::Reserved => {
+            let response = next
+                .run(Request::from_parts(parts, Body::from(bytes)))
+                .await;
+            record_response(&config, &key, &fingerprint, response).await
+        }
+    }
+}
+
+/// Store the handler's response for replay. Server errors are not stored: the key is
+/// released so the client's retry runs the request again.
+async fn record_response(
+    config: &IdempotencyConfig,
+    key: &str,
+    fingerprint: &str,
+    response: Response,
+) -> Response {
+    let release = || async {
+        if let Err(e) = config.release(key).await {
+            tracing::warn!(error = %e, "Failed to release idempotency key");
+        }
+    };
+
+    if response.status().is_server_error() {
+        release().await;
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let bytes: Bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to read response body for idempotency record");
+            release().await;
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "internal_error",
+                "failed to read response body",
+            );
+        }
+    };
+
+    match std::str::from_utf8(&bytes) {
+        Ok(body) => {
+            let headers = REPLAYED_HEADERS
+                .iter()
+                .filter_map(|name| {
+                    let value = parts.headers.get(name)?.to_str().ok()?;
+                    Some((name.as_str().to_string(), value.to_string()))
+                })
+                .collect();
+            let stored = StoredResponse {
+                status: parts.status.as_u16(),
+                body: body.to_string(),
+                headers,
+            };
+            if let Err(e) = config.complete(key, fingerprint, stored).await {
+                tracing::warn!(error = %e, "Failed to store idempotent response");
+            }
+        }
+        Err(_) => release().await,
+    }
+
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::InMemoryCache;
+    use crate::keycloak::AccessToken;
+    use axum::{routing::post, Extension, Router};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tower::ServiceExt;
+    use user_lib::entities::ApiKey;
+    use user_lib::repository::InMemoryIdempotencyRepository;
+
+    /// Router whose handler counts its calls and answers with `status`, pointing
+    /// `Location` at the call
+    fn app(config: IdempotencyConfig, status: StatusCode) -> (Router, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let router = Router::new()
+            .route(
+                "/users",
+                post(move |body: String| async move {
+                    let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                    (
+                        status,
+                        [
+                            (header::LOCATION, format!("/users/{n}")),
+                            (header::ETAG, format!("\"{n}\"")),
+                            (header::CACHE_CONTROL, "no-store".to_string()),
+                        ],
+                        Json(serde_json::json!({ "call": n, "body": body })),
+                    )
+                }),
+            )
+            .route_layer(axum::middleware::from_fn(idempotency_middleware))
+            .route_layer(Extension(config));
+        (router, calls)
+    }
+
+    fn cache_config() -> IdempotencyConfig {
+        IdempotencyConfig::new(
+            Some(Arc::new(InMemoryCache::new(100))),
+            Arc::new(InMemoryIdempotencyRepository::new()),
+        )
+    }
+
+    fn database_config() -> IdempotencyConfig {
+        IdempotencyConfig::new(None, Arc::new(InMemoryIdempotencyRepository::new()))
+    }
+
+    async fn send(router: &Router, key: Option<&str>, body: &str) -> (StatusCode, Response) {
+        send_as(router, None, key, body).await
+    }
+
+    async fn send_as(
+        router: &Router,
+        tenant: Option<&str>,
+        key: Option<&str>,
+        body: &str,
+    ) -> (StatusCode, Response) {
+        let mut request = Request::post("/users");
+        if let Some(tenant) = tenant {
+            request = request.header(crate::tenant::TENANT_HEADER, tenant);
+        }
+        if let Some(key) = key {
+            request = request.header(IDEMPOTENCY_KEY_HEADER, key);
+        }
+        let response = router
+            .clone()
+            .oneshot(request.body(Body::from(body.to_string())).unwrap())
+            .await
+            .unwrap();
+        (response.status(), response)
+    }
+
+    async fn body_of(response: Response) -> String {
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_retry_replays_stored_response() {
+        for config in [cache_config(), database_config()] {
+            let (router, calls) = app(config, StatusCode::CREATED);
+
+            let (status, first) = send(&router, Some("key-1"), "payload").await;
+            assert_eq!(status, StatusCode::CREATED);
+            let first_body = body_of(first).await;
+
+            let (status, retry) = send(&router, Some("key-1"), "payload").await;
+            assert_eq!(status, StatusCode::CREATED);
+            assert_eq!(retry.headers()[IDEMPOTENT_REPLAYED_HEADER], "true");
+            assert_eq!(retry.headers()[header::CONTENT_TYPE], "application/json");
+            assert_eq!(retry.headers()[header::LOCATION], "/users/1");
+            assert_eq!(retry.headers()[header::ETAG], "\"1\"");
+            assert!(!retry.headers().contains_key(header::CACHE_CONTROL));
+            assert_eq!(body_of(retry).await, first_body);
+            assert_eq!(calls.load(Ordering::SeqCst), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_records_are_shared_through_the_database() {
+        let db = Arc::new(InMemoryIdempotencyRepository::new());
+        let replica = || {
+            let cache: Arc<dyn CacheBackend> = Arc::new(InMemoryCache::new(100));
+            IdempotencyConfig::new(Some(cache), db.clone())
+        };
+        let (router, _) = app(replica(), StatusCode::CREATED);
+        send(&router, Some("key-1"), "payload").await;
+
+        // A replica with its own (or a flushed) cache finds the response in the database
+        let (other, other_calls) = app(replica(), StatusCode::CREATED);
+        let (status, retry) = send(&other, Some("key-1"), "payload").await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(retry.headers()[IDEMPOTENT_REPLAYED_HEADER], "true");
+        assert_eq!(retry.headers()[header::LOCATION], "/users/1");
+        assert_eq!(other_calls.load(Ordering::SeqCst), 0);
+
+        // and so does a request still running on another replica
+        db.reserve_idempotency_key(
+            &record_key(TenantId::DEFAULT, None, "key-2"),
+            &fingerprint("POST", "/users", b"payload"),
+            expires_at(Duration::from_secs(30)),
+        )
+        .await
+        .unwrap();
+        let (status, _) = send(&other, Some("key-2"), "payload").await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(other_calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_keys_are_scoped_to_the_tenant() {
+        for config in [cache_config(), database_config()] {
+            let (router, calls) = app(config, StatusCode::CREATED);
+
+            send(&router, Some("key-1"), "payload").await;
+            let (status, other) = send_as(
+                &router,
+                Some("6f1c1d52-3a4e-4c43-9a0b-2f8f1b3c9d10"),
+                Some("key-1"),
+                "other payload",
+            )
+            .await;
+
+            assert_eq!(status, StatusCode::CREATED);
+            assert!(!other.headers().contains_key(IDEMPOTENT_REPLAYED_HEADER));
+            assert_eq!(calls.load(Ordering::SeqCst), 2);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_keys_are_scoped_to_the_caller() {
+        let (router, calls) = app(database_config(), StatusCode::CREATED);
+        let send_by = |subject: &str| {
+            let caller = Caller::User(AccessToken {
+                subject: subject.to_string(),
+                realm: "master".to_string(),
+                tenant: None,
+            });
+            let request = Request::post("/users")
+                .header(IDEMPOTENCY_KEY_HEADER, "key-1")
+                .extension(caller)
+                .body(Body::from("payload"))
+                .unwrap();
+            router.clone().oneshot(request)
+        };
+
+        send_by("kc-alice").await.unwrap();
+        let other = send_by("kc-bob").await.unwrap();
+        assert!(!other.headers().contains_key(IDEMPOTENT_REPLAYED_HEADER));
+        let retry = send_by("kc-alice").await.unwrap();
+        assert_eq!(retry.headers()[IDEMPOTENT_REPLAYED_HEADER], "true");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_key_reuse_with_different_body_is_rejected() {
+        let (router, calls) = app(cache_config(), StatusCode::CREATED);
+
+        send(&router, Some("key-1"), "payload").await;
+        let (status, _) = send(&router, Some("key-1"), "other payload").await;
+
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_server_errors_are_not_replayed() {
+        let (router, calls) = app(database_config(), StatusCode::BAD_GATEWAY);
+
+        send(&router, Some("key-1"), "payload").await;
+        let (status, retry) = send(&router, Some("key-1"), "payload").await;
+
+        assert_eq!(status, StatusCode::BAD_GATEWAY);
+        assert!(!retry.headers().contains_key(IDEMPOTENT_REPLAYED_HEADER));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_requests_without_key_are_not_recorded() {
+        let (router, calls) = app(cache_config(), StatusCode::CREATED);
+
+        send(&router, None, "payload").await;
+        send(&router, None, "payload").await;
+        let (status, _) = send(&router, Some("not a key"), "payload").await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_key_validation() {
+        assert!(is_valid_key("3f1c9e1a-retry-1"));
+        assert!(!is_valid_key(""));
+        assert!(!is_valid_key("has space"));
+        assert!(!is_valid_key(&"k".repeat(MAX_KEY_LENGTH + 1)));
+    }
+
+    #[test]
+    fn test_fingerprint_covers_method_path_and_body() {
+        let base = fingerprint("POST", "/users", b"{}");
+        assert_eq!(base, fingerprint("POST", "/users", b"{}"));
+        assert_ne!(base, fingerprint("POST", "/roles", b"{}"));
+        assert_ne!(base, fingerprint("POST", "/users", b"{\"a\":1}"));
+        assert_ne!(base, fingerprint("POST", "/users?notify=true", b"{}"));
+        assert_eq!(base.len(), 64);
+    }
+
+    #[test]
+    fn test_record_keys_are_scoped_to_the_caller() {
+        let key = |id: u128| {
+            Caller::ServiceAccount(ApiKey {
+                id: uuid::Uuid::from_u128(id),
+                tenant: TenantId::DEFAULT,
+                name: "batch".to_string(),
+                prefix: "uak_abcdefgh".to_string(),
+                scopes: vec!["users:write".to_string()],
+                created_at: Utc::now(),
+                expires_at: None,
+                last_used_at: None,
+                rotated_at: None,
+                revoked_at: None,
+            })
+        };
+        let user = Caller::User(AccessToken {
+            subject: "kc-user-1".to_string(),
+            realm: "master".to_string(),
+            tenant: None,
+        });
+
+        let base = record_key(TenantId::DEFAULT, Some(&key(1)), "key-1");
+        assert_eq!(base, record_key(TenantId::DEFAULT, Some(&key(1)), "key-1"));
+        assert_ne!(base, record_key(TenantId::DEFAULT, Some(&key(2)), "key-1"));
+        assert_ne!(base, record_key(TenantId::DEFAULT, Some(&user), "key-1"));
+        assert_ne!(base, record_key(TenantId::DEFAULT, None, "key-1"));
+        assert!(base.len() <= 36 + 1 + 64);
+    }
+}
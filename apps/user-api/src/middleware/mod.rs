@@ -1,2 +1,5 @@
+pub mod admin_auth;
+pub mod api_key_auth;
 pub mod circuit_breaker;
+pub mod idempotency;
 pub mod ip_filter;
@@ -0,0 +1,137 @@
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use user_lib::entities::{PaginationParams, TenantId, UserFilter};
+use user_lib::repository::traits::{
+    RoleRepositoryTrait, UserRepositoryTrait, UserRoleRepositoryTrait,
+};
+
+use crate::cache::CacheConfig;
+use crate::services::IntegratedUserService;
+
+/// Run the cache warm-up, waiting at most `warmup_deadline` before returning.
+/// Work still pending at the deadline keeps running in the background.
+pub async fn warm_up_with_deadline<U, R, UR>(
+    service: Arc<IntegratedUserService<U, R, UR>>,
+    config: &CacheConfig,
+) where
+    U: UserRepositoryTrait + Send + Sync + 'static,
+    R: RoleRepositoryTrait + Send + Sync + 'static,
+    UR: UserRoleRepositoryTrait + Send + Sync + 'static,
+{
+    if !config.warmup_enabled {
+        return;
+    }
+    if !service.cache().is_enabled() {
+        tracing::info!("Skipping cache warm-up: Redis is not available");
+        return;
+    }
+
+    let task = tokio::spawn(warm_up(
+        service,
+        config.warmup_user_pages,
+        config.warmup_concurrency,
+    ));
+
+    if tokio::time::timeout(config.warmup_deadline, task)
+        .await
+        .is_err()
+    {
+        tracing::warn!(
+            deadline_secs = config.warmup_deadline.as_secs(),
+            "Cache warm-up exceeded its deadline, continuing in the background"
+        );
+    }
+}
+
+/// Preload the roles list, the first `user_pages` user pages and their Keycloak profiles
+/// of the default tenant
+async fn warm_up<U, R, UR>(
+    service: Arc<IntegratedUserService<U, R, UR>>,
+    user_pages: u32,
+    concurrency: usize,
+) where
+    U: UserRepositoryTrait + Send + Sync + 'static,
+    R: RoleRepositoryTrait + Send + Sync + 'static,
+    UR: UserRoleRepositoryTrait + Send + Sync + 'static,
+{
+    let started = Instant::now();
+
+    if let Err(e) = service
+        .get_roles(TenantId::DEFAULT, PaginationParams::default())
+        .await
+    {
+        tracing::warn!(error = %e, "Cache warm-up failed to load roles");
+    }
+
+    if user_pages == 0 {
+        return;
+    }
+
+    // The first page tells us how many pages actually exist
+    let total_pages = match service
+        .get_users(
+            TenantId::DEFAULT,
+            PaginationParams::new(Some(1), None),
+            &UserFilter::default(),
+        )
+        .await
+    {
+        Ok(page) => page.total_pages,
+        Err(e) => {
+            tracing::warn!(error = %e, "Cache warm-up failed to load users");
+            return;
+        }
+    };
+
+    let permits = Arc::new(Semaphore::new(concurrency.max(1)));
+    let mut tasks = JoinSet::new();
+    for page in remaining_pages(total_pages, user_pages) {
+        let service = service.clone();
+        let permits = permits.clone();
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            if let Err(e) = service
+                .get_users(
+                    TenantId::DEFAULT,
+                    PaginationParams::new(Some(page), None),
+                    &UserFilter::default(),
+                )
+                .await
+            {
+                tracing::warn!(page = page, error = %e, "Cache warm-up failed to load user page");
+            }
+        });
+    }
+    while tasks.join_next().await.is_some() {}
+
+    tracing::info!(
+        user_pages = total_pages.min(user_pages),
+        elapsed_ms = started.elapsed().as_millis() as u64,
+        "Cache warm-up completed"
+    );
+}
+
+/// Pages after the first that should be warmed, capped by what exists
+fn remaining_pages(total_pages: u32, max_pages: u32) -> std::ops::RangeInclusive<u32> {
+    2..=total_pages.min(max_pages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remaining_pages_capped_by_config() {
+        assert_eq!(remaining_pages(10, 3).collect::<Vec<_>>(), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_remaining_pages_capped_by_total() {
+        assert_eq!(remaining_pages(2, 5).collect::<Vec<_>>(), vec![2]);
+        assert!(remaining_pages(1, 5).next().is_none());
+        assert!(remaining_pages(0, 5).next().is_none());
+    }
+}
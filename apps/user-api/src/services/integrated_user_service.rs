@@ -1,26 +1,25 @@
+use chrono::{DateTime, Utc};
 use secrecy::Secret;
 use std::sync::Arc;
+use std::time::Duration;
 use uuid::Uuid;
 
-use user_lib::entities::{PaginatedResult, PaginationParams, Role};
+use user_lib::entities::{
+    ApiKey, Group, NewApiKey, PaginatedResult, PaginationParams, Role, RoleGrant, Tenant, TenantId,
+    User, UserAttributesPatch, UserFilter, MAX_PAGE_SIZE,
+};
 use user_lib::errors_service::UserServiceError;
 use user_lib::repository::traits::{
     RoleRepositoryTrait, UserRepositoryTrait, UserRoleRepositoryTrait,
 };
 
-use crate::cache::{CachedUserService, RedisCache};
-use crate::keycloak::{FullUser, KeycloakClient, KeycloakError, KeycloakUser};
-
-/// Cache key for Keycloak profiles
-fn keycloak_profile_key(keycloak_id: &str) -> String {
-    format!("user-api:kc:profile:{keycloak_id}")
-}
-
-/// Pattern for all Keycloak profile keys
-#[allow(dead_code)]
-pub fn keycloak_profiles_pattern() -> String {
-    "user-api:kc:profile:*".to_string()
-}
+use crate::api_key::{generate_api_key, hash_api_key, LAST_USED_RESOLUTION};
+use crate::cache::keys::keycloak_profile_key;
+use crate::cache::{Cache, CacheLookup, CacheNamespace, CachedUserService};
+use crate::keycloak::{
+    FullUser, KeycloakClient, KeycloakError, KeycloakRole, KeycloakUser, KeycloakUserSession,
+    RequiredAction,
+};
 
 /// Request for creating a user
 pub struct CreateUserRequest {
@@ -34,6 +33,14 @@ pub struct CreateUserRequest {
 pub struct UpdateUserRequest {
     pub first_name: Option<String>,
     pub last_name: Option<String>,
+    /// New email; it must be unused in the realm and starts out unverified
+    pub email: Option<String>,
+}
+
+/// API key with the key itself, which is only available when it is created or rotated
+pub struct IssuedApiKey {
+    pub api_key: ApiKey,
+    pub key: Secret<String>,
 }
 
 /// Service error that combines user service and keycloak errors
@@ -66,6 +73,20 @@ impl From<KeycloakError> for IntegratedServiceError {
     }
 }
 
+/// Separates a tenant's slug from the role name in realm role names
+const REALM_ROLE_PREFIX_SEPARATOR: char = ':';
+
+/// With role sync, role names cannot contain the separator, so no role of the default
+/// tenant can take the realm role name of another tenant's role
+fn check_synced_role_name(name: &str) -> Result<(), UserServiceError> {
+    if name.contains(REALM_ROLE_PREFIX_SEPARATOR) {
+        return Err(UserServiceError::Validation(format!(
+            "role names cannot contain '{REALM_ROLE_PREFIX_SEPARATOR}' while roles are synced to Keycloak"
+        )));
+    }
+    Ok(())
+}
+
 /// Integrated user service that wraps CachedUserService and KeycloakClient
 pub struct IntegratedUserService<U, R, UR>
 where
@@ -75,7 +96,9 @@ where
 {
     inner: Arc<CachedUserService<U, R, UR>>,
     keycloak: Arc<KeycloakClient>,
-    redis: RedisCache,
+    cache: Cache,
+    /// Mirror roles and direct role assignments to Keycloak realm roles
+    sync_roles: bool,
 }
 
 impl<U, R, UR> IntegratedUserService<U, R, UR>
@@ -87,53 +110,151 @@ where
     pub fn new(
         inner: Arc<CachedUserService<U, R, UR>>,
         keycloak: Arc<KeycloakClient>,
-        redis: RedisCache,
+        cache: Cache,
     ) -> Self {
         Self {
             inner,
             keycloak,
-            redis,
+            cache,
+            sync_roles: false,
+        }
+    }
+
+    /// Mirror roles to Keycloak realm roles, and the roles granted to users and attached
+    /// to groups to realm role mappings, so issued tokens carry them in
+    /// `realm_access.roles`
+    pub fn with_role_sync(mut self, enabled: bool) -> Self {
+        self.sync_roles = enabled;
+        self
+    }
+
+    pub fn cache(&self) -> &Cache {
+        &self.cache
+    }
+
+    pub fn keycloak(&self) -> &KeycloakClient {
+        &self.keycloak
+    }
+
+    /// Re-read the Keycloak realms file. Profiles cached for a tenant whose realm changed
+    /// came from its old realm; keys can't be flushed per tenant, so the whole profile
+    /// namespace goes.
+    pub async fn reload_keycloak_realms(
+        &self,
+    ) -> Result<Vec<(TenantId, String)>, IntegratedServiceError> {
+        let changed = self.keycloak.reload_realms()?;
+        if !changed.is_empty() {
+            tracing::info!(tenants = ?changed, "Keycloak realms changed");
+            self.cache.delete_namespace(CacheNamespace::Keycloak).await;
+        }
+        Ok(self.keycloak.realms())
+    }
+
+    /// Evict everything cached for one user: the local record, list pages and Keycloak profile
+    pub async fn evict_user_cache(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<(), IntegratedServiceError> {
+        if let Some(keycloak_id) = self.inner.evict_user(tenant, user_id).await? {
+            self.invalidate_keycloak_cache(tenant, &keycloak_id).await;
         }
+        Ok(())
     }
 
     /// Get cached Keycloak profile or fetch from Keycloak
     async fn get_keycloak_profile(
         &self,
+        tenant: TenantId,
         keycloak_id: &str,
     ) -> Result<Option<KeycloakUser>, KeycloakError> {
-        if !self.keycloak.is_configured() {
+        if !self.keycloak.is_configured(tenant) {
             return Ok(None);
         }
 
-        let cache_key = keycloak_profile_key(keycloak_id);
+        let cache_key = keycloak_profile_key(tenant, keycloak_id);
 
         // Try cache first
-        if self.redis.is_enabled() {
-            if let Some(profile) = self.redis.get::<KeycloakUser>(&cache_key).await {
-                return Ok(Some(profile));
+        if self.cache.is_enabled() {
+            match self.cache.lookup::<KeycloakUser>(&cache_key).await {
+                CacheLookup::Hit(profile) => return Ok(Some(profile)),
+                CacheLookup::Negative => return Ok(None),
+                CacheLookup::Miss => {}
             }
         }
 
-        // Fetch from Keycloak
-        let profile = self.keycloak.get_user_by_id(keycloak_id).await?;
+        self.fetch_keycloak_profile(tenant, keycloak_id).await
+    }
 
-        // Cache if found
-        if let Some(ref p) = profile {
-            if self.redis.is_enabled() {
-                self.redis
-                    .set(&cache_key, p, self.keycloak.profile_cache_ttl())
-                    .await;
+    /// Fetch a profile from Keycloak and cache it, remembering missing profiles for a short time
+    async fn fetch_keycloak_profile(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+    ) -> Result<Option<KeycloakUser>, KeycloakError> {
+        let profile = self.keycloak.get_user_by_id(tenant, keycloak_id).await?;
+
+        if self.cache.is_enabled() {
+            let cache_key = keycloak_profile_key(tenant, keycloak_id);
+            match profile {
+                Some(ref p) => {
+                    self.cache
+                        .set(&cache_key, p, self.keycloak.profile_cache_ttl())
+                        .await
+                }
+                None => {
+                    self.cache
+                        .set_negative(&cache_key, self.inner.config().negative_ttl)
+                        .await
+                }
             }
         }
 
         Ok(profile)
     }
 
+    /// Resolve profiles for a page of users with a single cache round trip,
+    /// fetching only the misses from Keycloak
+    async fn get_keycloak_profiles(
+        &self,
+        tenant: TenantId,
+        keycloak_ids: &[String],
+    ) -> Vec<Option<KeycloakUser>> {
+        if !self.keycloak.is_configured(tenant) {
+            return keycloak_ids.iter().map(|_| None).collect();
+        }
+
+        let lookups: Vec<CacheLookup<KeycloakUser>> = if self.cache.is_enabled() {
+            let keys: Vec<String> = keycloak_ids
+                .iter()
+                .map(|id| keycloak_profile_key(tenant, id))
+                .collect();
+            self.cache.lookup_many(&keys).await
+        } else {
+            keycloak_ids.iter().map(|_| CacheLookup::Miss).collect()
+        };
+
+        let mut profiles = Vec::with_capacity(keycloak_ids.len());
+        for (keycloak_id, lookup) in keycloak_ids.iter().zip(lookups) {
+            let profile = match lookup {
+                CacheLookup::Hit(profile) => Some(profile),
+                CacheLookup::Negative => None,
+                CacheLookup::Miss => self
+                    .fetch_keycloak_profile(tenant, keycloak_id)
+                    .await
+                    .ok()
+                    .flatten(),
+            };
+            profiles.push(profile);
+        }
+        profiles
+    }
+
     /// Invalidate Keycloak profile cache
-    async fn invalidate_keycloak_cache(&self, keycloak_id: &str) {
-        if self.redis.is_enabled() {
-            let cache_key = keycloak_profile_key(keycloak_id);
-            self.redis.delete(&cache_key).await;
+    async fn invalidate_keycloak_cache(&self, tenant: TenantId, keycloak_id: &str) {
+        if self.cache.is_enabled() {
+            let cache_key = keycloak_profile_key(tenant, keycloak_id);
+            self.cache.delete(&cache_key).await;
         }
     }
 
@@ -150,8 +271,14 @@ where
                 name: kc.display_name(),
                 email: kc.email,
                 roles: local.roles,
+                attributes: local.attributes,
                 email_verified: kc.email_verified,
                 enabled: kc.enabled,
+                suspension: local.suspension,
+                last_login_at: local.last_login_at,
+                created_at: local.created_at,
+                updated_at: local.updated_at,
+                version: local.version,
             },
             None => FullUser {
                 id: local.id,
@@ -162,8 +289,14 @@ where
                 ),
                 email: None,
                 roles: local.roles,
+                attributes: local.attributes,
                 email_verified: false,
-                enabled: true,
+                enabled: local.suspension.is_none(),
+                suspension: local.suspension,
+                last_login_at: local.last_login_at,
+                created_at: local.created_at,
+                updated_at: local.updated_at,
+                version: local.version,
             },
         }
     }
@@ -173,14 +306,15 @@ where
     /// Get a user by ID with merged Keycloak profile
     pub async fn get_user(
         &self,
+        tenant: TenantId,
         user_id: Uuid,
     ) -> Result<Option<FullUser>, IntegratedServiceError> {
-        let local = self.inner.get_user(user_id).await?;
+        let local = self.inner.get_user(tenant, user_id).await?;
 
         match local {
             Some(user) => {
                 let kc_profile = self
-                    .get_keycloak_profile(&user.keycloak_id)
+                    .get_keycloak_profile(tenant, &user.keycloak_id)
                     .await
                     .ok()
                     .flatten();
@@ -190,22 +324,25 @@ where
         }
     }
 
-    /// Get all users with merged Keycloak profiles
+    /// Get the users matching `filter` with merged Keycloak profiles
     pub async fn get_users(
         &self,
+        tenant: TenantId,
         pagination: PaginationParams,
+        filter: &UserFilter,
     ) -> Result<PaginatedResult<FullUser>, IntegratedServiceError> {
-        let result = self.inner.get_users(pagination).await?;
+        let result = self.inner.get_users(tenant, pagination, filter).await?;
 
-        let mut full_users = Vec::with_capacity(result.items.len());
-        for user in result.items {
-            let kc_profile = self
-                .get_keycloak_profile(&user.keycloak_id)
-                .await
-                .ok()
-                .flatten();
-            full_users.push(self.merge_user(user, kc_profile));
-        }
+        let keycloak_ids: Vec<String> =
+            result.items.iter().map(|u| u.keycloak_id.clone()).collect();
+        let profiles = self.get_keycloak_profiles(tenant, &keycloak_ids).await;
+
+        let full_users = result
+            .items
+            .into_iter()
+            .zip(profiles)
+            .map(|(user, kc_profile)| self.merge_user(user, kc_profile))
+            .collect();
 
         Ok(PaginatedResult {
             items: full_users,
@@ -220,12 +357,14 @@ where
     /// Implements compensation transaction: if local DB creation fails, rolls back Keycloak user
     pub async fn create_user(
         &self,
+        tenant: TenantId,
         request: CreateUserRequest,
     ) -> Result<FullUser, IntegratedServiceError> {
         // Create in Keycloak first
         let keycloak_id = self
             .keycloak
             .create_user(
+                tenant,
                 &request.email,
                 request.first_name.as_deref(),
                 request.last_name.as_deref(),
@@ -234,7 +373,7 @@ where
             .await?;
 
         // Create local record with compensation on failure
-        let local = match self.inner.create_user(&keycloak_id).await {
+        let local = match self.inner.create_user(tenant, &keycloak_id).await {
             Ok(user) => user,
             Err(e) => {
                 // CRITICAL: Rollback Keycloak user creation
@@ -246,7 +385,7 @@ where
                 );
 
                 // Attempt to delete the Keycloak user to prevent orphaned records
-                if let Err(rollback_err) = self.keycloak.delete_user(&keycloak_id).await {
+                if let Err(rollback_err) = self.keycloak.delete_user(tenant, &keycloak_id).await {
                     tracing::error!(
                         keycloak_id = %keycloak_id,
                         error = ?rollback_err,
@@ -263,40 +402,103 @@ where
             }
         };
 
+        // Drop any negative entry cached before the profile existed
+        self.invalidate_keycloak_cache(tenant, &keycloak_id).await;
+
         // Fetch the profile from Keycloak
-        let kc_profile = self.get_keycloak_profile(&keycloak_id).await.ok().flatten();
+        let kc_profile = self
+            .get_keycloak_profile(tenant, &keycloak_id)
+            .await
+            .ok()
+            .flatten();
 
         Ok(self.merge_user(local, kc_profile))
     }
 
-    /// Update a user's profile in Keycloak
+    /// Update a user's profile in Keycloak. The local record's version is checked and
+    /// bumped first, so concurrent updates with the same `expected_version` fail.
     pub async fn update_user(
         &self,
+        tenant: TenantId,
         user_id: Uuid,
         request: UpdateUserRequest,
+        expected_version: Option<i64>,
     ) -> Result<FullUser, IntegratedServiceError> {
-        // Get local user to find keycloak_id
+        let new_email = match request.email.as_deref() {
+            Some(email) => {
+                self.changed_email(tenant, user_id, email, expected_version)
+                    .await?
+            }
+            None => None,
+        };
+
+        // Claim the next version; also gives us the keycloak_id
         let local = self
             .inner
-            .get_user(user_id)
-            .await?
-            .ok_or(IntegratedServiceError::User(UserServiceError::NotFound))?;
+            .touch_user(tenant, user_id, expected_version)
+            .await?;
 
         // Update in Keycloak
         self.keycloak
             .update_user(
+                tenant,
                 &local.keycloak_id,
                 request.first_name.as_deref(),
                 request.last_name.as_deref(),
+                new_email.as_deref(),
             )
             .await?;
 
         // Invalidate KC cache
-        self.invalidate_keycloak_cache(&local.keycloak_id).await;
+        self.invalidate_keycloak_cache(tenant, &local.keycloak_id)
+            .await;
+
+        // The change stands even if the email can't be sent; the user can ask again
+        if let Some(email) = &new_email {
+            if let Err(e) = self
+                .keycloak
+                .execute_actions_email(
+                    tenant,
+                    &local.keycloak_id,
+                    &[RequiredAction::VerifyEmail],
+                    None,
+                )
+                .await
+            {
+                tracing::warn!(
+                    keycloak_id = %local.keycloak_id,
+                    email = %email,
+                    error = %e,
+                    "Email changed but the verification email could not be sent"
+                );
+            }
+        }
 
         // Fetch fresh profile
         let kc_profile = self
-            .get_keycloak_profile(&local.keycloak_id)
+            .get_keycloak_profile(tenant, &local.keycloak_id)
+            .await
+            .ok()
+            .flatten();
+
+        Ok(self.merge_user(local, kc_profile))
+    }
+
+    /// Update the locally stored attributes of a user; Keycloak is not involved
+    pub async fn update_user_attributes(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        patch: &UserAttributesPatch,
+        expected_version: Option<i64>,
+    ) -> Result<FullUser, IntegratedServiceError> {
+        let local = self
+            .inner
+            .update_user_attributes(tenant, user_id, patch, expected_version)
+            .await?;
+
+        let kc_profile = self
+            .get_keycloak_profile(tenant, &local.keycloak_id)
             .await
             .ok()
             .flatten();
@@ -304,106 +506,1557 @@ where
         Ok(self.merge_user(local, kc_profile))
     }
 
-    /// Delete a user from both Keycloak and local DB
-    pub async fn delete_user(&self, user_id: Uuid) -> Result<(), IntegratedServiceError> {
+    /// `email` if it differs from the user's current one, after checking that no other
+    /// user of the realm has it; `None` if it is unchanged
+    async fn changed_email(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        email: &str,
+        expected_version: Option<i64>,
+    ) -> Result<Option<String>, IntegratedServiceError> {
+        let local = self
+            .inner
+            .get_user(tenant, user_id)
+            .await?
+            .ok_or(IntegratedServiceError::User(UserServiceError::NotFound))?;
+
+        // Check the version before the lookups below; `touch_user` checks it again
+        if expected_version.is_some_and(|v| v != local.version) {
+            return Err(UserServiceError::VersionConflict.into());
+        }
+
+        let email = email.trim();
+        let current = self
+            .keycloak
+            .get_user_by_id(tenant, &local.keycloak_id)
+            .await?
+            .ok_or_else(|| KeycloakError::UserNotFound(local.keycloak_id.clone()))?;
+        if current
+            .email
+            .is_some_and(|current| current.eq_ignore_ascii_case(email))
+        {
+            return Ok(None);
+        }
+
+        let taken = self
+            .keycloak
+            .get_users_by_email(tenant, email)
+            .await?
+            .iter()
+            .any(|user| user.id != local.keycloak_id);
+        if taken {
+            return Err(KeycloakError::EmailAlreadyExists(email.to_string()).into());
+        }
+
+        Ok(Some(email.to_string()))
+    }
+
+    /// Delete a user from both Keycloak and local DB. With soft delete the Keycloak
+    /// account is only disabled, so a restore can bring the user back, and it is
+    /// enabled again if the local delete fails.
+    pub async fn delete_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<(), IntegratedServiceError> {
         // Get local user to find keycloak_id
         let local = self
             .inner
-            .get_user(user_id)
+            .get_user(tenant, user_id)
             .await?
             .ok_or(IntegratedServiceError::User(UserServiceError::NotFound))?;
 
-        // Delete from Keycloak
-        self.keycloak.delete_user(&local.keycloak_id).await?;
+        // Check the version before touching Keycloak, which can't be rolled back;
+        // the local delete checks it again in the same statement
+        if expected_version.is_some_and(|v| v != local.version) {
+            return Err(UserServiceError::VersionConflict.into());
+        }
+
+        let was_enabled = if self.inner.soft_delete() {
+            let was_enabled = self.keycloak_enabled(tenant, &local.keycloak_id).await?;
+            self.keycloak
+                .set_user_enabled(tenant, &local.keycloak_id, false)
+                .await?;
+            Some(was_enabled)
+        } else {
+            self.keycloak
+                .delete_user(tenant, &local.keycloak_id)
+                .await?;
+            None
+        };
 
         // Invalidate KC cache
-        self.invalidate_keycloak_cache(&local.keycloak_id).await;
+        self.invalidate_keycloak_cache(tenant, &local.keycloak_id)
+            .await;
 
         // Delete from local DB
-        self.inner.delete_user(user_id).await?;
+        if let Err(e) = self
+            .inner
+            .delete_user(tenant, user_id, expected_version)
+            .await
+        {
+            match was_enabled {
+                Some(enabled) => {
+                    self.restore_keycloak_enabled(tenant, &local.keycloak_id, enabled)
+                        .await
+                }
+                None => tracing::error!(
+                    user_id = %user_id,
+                    keycloak_id = %local.keycloak_id,
+                    error = %e,
+                    "CRITICAL: User deleted in Keycloak but not locally"
+                ),
+            }
+            return Err(e.into());
+        }
 
         Ok(())
     }
 
-    /// Sync a user from Keycloak - creates local record if not exists
-    #[allow(dead_code)]
-    pub async fn sync_from_keycloak(
+    /// Whether the user's Keycloak account is enabled, read before changing it so a
+    /// failed operation can put it back as it was
+    async fn keycloak_enabled(
         &self,
+        tenant: TenantId,
         keycloak_id: &str,
+    ) -> Result<bool, IntegratedServiceError> {
+        let user = self
+            .keycloak
+            .get_user_by_id(tenant, keycloak_id)
+            .await?
+            .ok_or_else(|| KeycloakError::UserNotFound(keycloak_id.to_string()))?;
+        Ok(user.enabled)
+    }
+
+    /// Compensation: set the Keycloak account back to `enabled`, as it was before a
+    /// failed operation
+    async fn restore_keycloak_enabled(&self, tenant: TenantId, keycloak_id: &str, enabled: bool) {
+        if let Err(e) = self
+            .keycloak
+            .set_user_enabled(tenant, keycloak_id, enabled)
+            .await
+        {
+            tracing::error!(
+                keycloak_id = %keycloak_id,
+                enabled,
+                error = %e,
+                "CRITICAL: Failed to restore the Keycloak account's enabled flag"
+            );
+        }
+        self.invalidate_keycloak_cache(tenant, keycloak_id).await;
+    }
+
+    /// Restore a soft-deleted user and re-enable its Keycloak account. If Keycloak fails,
+    /// the user is deleted again.
+    pub async fn restore_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
     ) -> Result<FullUser, IntegratedServiceError> {
-        // Check if local record exists
-        let existing = self.inner.get_user_by_keycloak_id(keycloak_id).await?;
+        let local = self.inner.restore_user(tenant, user_id).await?;
 
-        let local = match existing {
-            Some(user) => user,
-            None => {
-                // Create local record
-                self.inner.create_user(keycloak_id).await?
+        if let Err(e) = self
+            .keycloak
+            .set_user_enabled(tenant, &local.keycloak_id, true)
+            .await
+        {
+            tracing::error!(
+                user_id = %user_id,
+                keycloak_id = %local.keycloak_id,
+                error = ?e,
+                "Failed to enable Keycloak user - deleting restored user again"
+            );
+            // Only this restore's own write is taken back
+            if let Err(rollback_err) = self
+                .inner
+                .soft_delete_user(tenant, user_id, Some(local.version))
+                .await
+            {
+                tracing::error!(
+                    user_id = %user_id,
+                    error = ?rollback_err,
+                    "CRITICAL: Failed to delete restored user - user is live but disabled in Keycloak"
+                );
             }
-        };
+            return Err(e.into());
+        }
+        self.invalidate_keycloak_cache(tenant, &local.keycloak_id)
+            .await;
 
-        // Fetch Keycloak profile
-        let kc_profile = self.get_keycloak_profile(keycloak_id).await.ok().flatten();
+        let kc_profile = self
+            .get_keycloak_profile(tenant, &local.keycloak_id)
+            .await
+            .ok()
+            .flatten();
 
         Ok(self.merge_user(local, kc_profile))
     }
 
-    // ========== Role Operations (passthrough) ==========
+    /// Suspend a user: disable it in Keycloak, record `reason` locally, and optionally end
+    /// its sessions. If the local update fails, the Keycloak account is set back to the
+    /// enabled state it had before. A failed logout does not undo the suspension: the
+    /// account stays disabled, so its sessions cannot be refreshed, and the failure is
+    /// logged for `DELETE /v1/users/{id}/sessions` to be retried.
+    pub async fn suspend_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        reason: Option<&str>,
+        logout_sessions: bool,
+        expected_version: Option<i64>,
+    ) -> Result<FullUser, IntegratedServiceError> {
+        let local = self
+            .inner
+            .get_user(tenant, user_id)
+            .await?
+            .ok_or(IntegratedServiceError::User(UserServiceError::NotFound))?;
+
+        // Check the version before touching Keycloak; the local write checks it again
+        if expected_version.is_some_and(|v| v != local.version) {
+            return Err(UserServiceError::VersionConflict.into());
+        }
+
+        let was_enabled = self.keycloak_enabled(tenant, &local.keycloak_id).await?;
+        self.keycloak
+            .set_user_enabled(tenant, &local.keycloak_id, false)
+            .await?;
+        self.invalidate_keycloak_cache(tenant, &local.keycloak_id)
+            .await;
+
+        let user = match self
+            .inner
+            .suspend_user(tenant, user_id, reason, expected_version)
+            .await
+        {
+            Ok(user) => user,
+            Err(e) => {
+                tracing::error!(
+                    user_id = %user_id,
+                    keycloak_id = %local.keycloak_id,
+                    error = %e,
+                    "Failed to suspend user locally, restoring its Keycloak account"
+                );
+                self.restore_keycloak_enabled(tenant, &local.keycloak_id, was_enabled)
+                    .await;
+                return Err(e.into());
+            }
+        };
+
+        if logout_sessions {
+            if let Err(e) = self.keycloak.logout_user(tenant, &local.keycloak_id).await {
+                tracing::error!(
+                    user_id = %user_id,
+                    keycloak_id = %local.keycloak_id,
+                    error = %e,
+                    "User suspended but its sessions could not be ended"
+                );
+            }
+        }
+
+        let kc_profile = self
+            .get_keycloak_profile(tenant, &user.keycloak_id)
+            .await
+            .ok()
+            .flatten();
 
-    pub async fn get_role(&self, role_id: Uuid) -> Result<Option<Role>, IntegratedServiceError> {
-        Ok(self.inner.get_role(role_id).await?)
+        Ok(self.merge_user(user, kc_profile))
     }
 
-    pub async fn get_roles(
+    /// Lift a user's suspension: enable it in Keycloak, then clear the local record.
+    /// If the local update fails, the Keycloak account is set back to the enabled state
+    /// it had before.
+    pub async fn reactivate_user(
         &self,
-        pagination: PaginationParams,
-    ) -> Result<PaginatedResult<Role>, IntegratedServiceError> {
-        Ok(self.inner.get_roles(pagination).await?)
-    }
+        tenant: TenantId,
+        user_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<FullUser, IntegratedServiceError> {
+        let local = self
+            .inner
+            .get_user(tenant, user_id)
+            .await?
+            .ok_or(IntegratedServiceError::User(UserServiceError::NotFound))?;
+
+        // Check the version before touching Keycloak; the local write checks it again
+        if expected_version.is_some_and(|v| v != local.version) {
+            return Err(UserServiceError::VersionConflict.into());
+        }
+
+        let was_enabled = self.keycloak_enabled(tenant, &local.keycloak_id).await?;
+        self.keycloak
+            .set_user_enabled(tenant, &local.keycloak_id, true)
+            .await?;
+        self.invalidate_keycloak_cache(tenant, &local.keycloak_id)
+            .await;
+
+        let user = match self
+            .inner
+            .reactivate_user(tenant, user_id, expected_version)
+            .await
+        {
+            Ok(user) => user,
+            Err(e) => {
+                tracing::error!(
+                    user_id = %user_id,
+                    keycloak_id = %local.keycloak_id,
+                    error = %e,
+                    "Failed to reactivate user locally, restoring its Keycloak account"
+                );
+                self.restore_keycloak_enabled(tenant, &local.keycloak_id, was_enabled)
+                    .await;
+                return Err(e.into());
+            }
+        };
+
+        let kc_profile = self
+            .get_keycloak_profile(tenant, &user.keycloak_id)
+            .await
+            .ok()
+            .flatten();
 
-    pub async fn create_role(&self, name: &str) -> Result<Role, IntegratedServiceError> {
-        Ok(self.inner.create_role(name).await?)
+        Ok(self.merge_user(user, kc_profile))
     }
 
-    pub async fn update_role(
+    /// Set a user's Keycloak password; a temporary one must be changed at the next login
+    pub async fn set_user_password(
         &self,
-        role_id: Uuid,
-        name: &str,
-    ) -> Result<Role, IntegratedServiceError> {
-        Ok(self.inner.update_role(role_id, name).await?)
+        tenant: TenantId,
+        user_id: Uuid,
+        password: &Secret<String>,
+        temporary: bool,
+    ) -> Result<(), IntegratedServiceError> {
+        let local = self
+            .inner
+            .get_user(tenant, user_id)
+            .await?
+            .ok_or(IntegratedServiceError::User(UserServiceError::NotFound))?;
+
+        self.keycloak
+            .reset_password(tenant, &local.keycloak_id, password, temporary)
+            .await?;
+
+        Ok(())
     }
 
-    pub async fn delete_role(&self, role_id: Uuid) -> Result<(), IntegratedServiceError> {
-        Ok(self.inner.delete_role(role_id).await?)
+    /// Active Keycloak sessions of a user
+    pub async fn get_user_sessions(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<Vec<KeycloakUserSession>, IntegratedServiceError> {
+        let local = self
+            .inner
+            .get_user(tenant, user_id)
+            .await?
+            .ok_or(IntegratedServiceError::User(UserServiceError::NotFound))?;
+
+        Ok(self
+            .keycloak
+            .get_user_sessions(tenant, &local.keycloak_id)
+            .await?)
     }
 
-    pub async fn assign_role(
+    /// End every Keycloak session of a user
+    pub async fn logout_user_sessions(
         &self,
+        tenant: TenantId,
         user_id: Uuid,
-        role_id: Uuid,
     ) -> Result<(), IntegratedServiceError> {
-        self.inner.assign_role(user_id, role_id).await?;
+        let local = self
+            .inner
+            .get_user(tenant, user_id)
+            .await?
+            .ok_or(IntegratedServiceError::User(UserServiceError::NotFound))?;
 
-        // Also invalidate user caches since role assignment affects the user
-        if let Ok(Some(user)) = self.inner.get_user(user_id).await {
-            self.invalidate_keycloak_cache(&user.keycloak_id).await;
-        }
+        self.keycloak
+            .logout_user(tenant, &local.keycloak_id)
+            .await?;
 
         Ok(())
     }
 
-    pub async fn unassign_role(
+    /// End one Keycloak session of a user. Sessions are realm-wide in
+    /// Keycloak, so the id is checked against the user's own sessions first
+    pub async fn delete_user_session(
         &self,
+        tenant: TenantId,
         user_id: Uuid,
-        role_id: Uuid,
+        session_id: &str,
     ) -> Result<(), IntegratedServiceError> {
-        self.inner.unassign_role(user_id, role_id).await?;
-
-        // Also invalidate user caches since role unassignment affects the user
-        if let Ok(Some(user)) = self.inner.get_user(user_id).await {
-            self.invalidate_keycloak_cache(&user.keycloak_id).await;
+        let sessions = self.get_user_sessions(tenant, user_id).await?;
+        if !sessions.iter().any(|s| s.id == session_id) {
+            return Err(KeycloakError::SessionNotFound(session_id.to_string()).into());
         }
 
+        self.keycloak.delete_session(tenant, session_id).await?;
+
         Ok(())
     }
+
+    /// Email a user a Keycloak link to complete `actions`
+    pub async fn execute_user_actions(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        actions: &[RequiredAction],
+        lifespan: Option<Duration>,
+    ) -> Result<(), IntegratedServiceError> {
+        let local = self
+            .inner
+            .get_user(tenant, user_id)
+            .await?
+            .ok_or(IntegratedServiceError::User(UserServiceError::NotFound))?;
+
+        self.keycloak
+            .execute_actions_email(tenant, &local.keycloak_id, actions, lifespan)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Sync a user from Keycloak - creates local record if not exists
+    #[allow(dead_code)]
+    pub async fn sync_from_keycloak(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+    ) -> Result<FullUser, IntegratedServiceError> {
+        // Check if local record exists
+        let existing = self
+            .inner
+            .get_user_by_keycloak_id(tenant, keycloak_id)
+            .await?;
+
+        let local = match existing {
+            Some(user) => user,
+            None => {
+                // Create local record
+                self.inner.create_user(tenant, keycloak_id).await?
+            }
+        };
+
+        // Fetch Keycloak profile
+        let kc_profile = self
+            .get_keycloak_profile(tenant, keycloak_id)
+            .await
+            .ok()
+            .flatten();
+
+        Ok(self.merge_user(local, kc_profile))
+    }
+
+    // ========== Keycloak Event Operations ==========
+
+    /// Drop the cached profile of a user changed directly in Keycloak
+    pub async fn apply_keycloak_profile_change(&self, tenant: TenantId, keycloak_id: &str) {
+        self.invalidate_keycloak_cache(tenant, keycloak_id).await;
+    }
+
+    /// Tenant of the local user with this Keycloak ID, if it is one of the tenants whose
+    /// users live in `realm_tenant`'s realm. Tenants can share a realm, so an event of
+    /// that realm may concern any of them.
+    pub async fn keycloak_user_tenant(
+        &self,
+        realm_tenant: TenantId,
+        keycloak_id: &str,
+    ) -> Result<Option<TenantId>, IntegratedServiceError> {
+        let owner = self
+            .inner
+            .get_user_tenant_by_keycloak_id(keycloak_id)
+            .await?;
+        Ok(owner.filter(|owner| self.keycloak.shares_realm(realm_tenant, *owner)))
+    }
+
+    /// Remove the local record of a user deleted directly in Keycloak. The row is purged
+    /// even with soft delete on, as there is no Keycloak account left to restore.
+    /// Returns false if there was no local user.
+    pub async fn remove_user_deleted_in_keycloak(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+    ) -> Result<bool, IntegratedServiceError> {
+        self.invalidate_keycloak_cache(tenant, keycloak_id).await;
+
+        let Some(local) = self
+            .inner
+            .get_user_by_keycloak_id(tenant, keycloak_id)
+            .await?
+        else {
+            return Ok(false);
+        };
+
+        self.inner.purge_user(tenant, local.id).await?;
+        Ok(true)
+    }
+
+    /// Record a Keycloak login of a user; logins of unknown users are ignored
+    pub async fn record_user_login(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+        at: DateTime<Utc>,
+    ) -> Result<(), IntegratedServiceError> {
+        if let Some(local) = self
+            .inner
+            .get_user_by_keycloak_id(tenant, keycloak_id)
+            .await?
+        {
+            self.inner.record_user_login(tenant, &local, at).await?;
+        }
+        Ok(())
+    }
+
+    // ========== Role Operations ==========
+
+    pub async fn get_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+    ) -> Result<Option<Role>, IntegratedServiceError> {
+        Ok(self.inner.get_role(tenant, role_id).await?)
+    }
+
+    pub async fn get_roles(
+        &self,
+        tenant: TenantId,
+        pagination: PaginationParams,
+    ) -> Result<PaginatedResult<Role>, IntegratedServiceError> {
+        Ok(self.inner.get_roles(tenant, pagination).await?)
+    }
+
+    /// Create a role. With role sync the realm role is created first and deleted again
+    /// if the local role cannot be created; an existing realm role of the same name is
+    /// adopted and left alone on failure.
+    pub async fn create_role(
+        &self,
+        tenant: TenantId,
+        name: &str,
+        description: Option<&str>,
+    ) -> Result<Role, IntegratedServiceError> {
+        if !self.sync_roles {
+            return Ok(self.inner.create_role(tenant, name, description).await?);
+        }
+
+        check_synced_role_name(name)?;
+        let realm_name = self.realm_role_name(tenant, name).await?;
+        let created = self
+            .keycloak
+            .create_realm_role(tenant, &realm_name, description)
+            .await?;
+
+        match self.inner.create_role(tenant, name, description).await {
+            Ok(role) => Ok(role),
+            Err(e) => {
+                if created {
+                    tracing::error!(
+                        name = %name,
+                        error = ?e,
+                        "Failed to create local role - rolling back Keycloak realm role"
+                    );
+                    if let Err(rollback_err) =
+                        self.keycloak.delete_realm_role(tenant, &realm_name).await
+                    {
+                        tracing::error!(
+                            name = %name,
+                            error = ?rollback_err,
+                            "CRITICAL: Failed to rollback Keycloak realm role - ORPHANED ROLE requires manual cleanup"
+                        );
+                    }
+                }
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Update a role locally, then rename the realm role. If Keycloak fails the local
+    /// change is reverted.
+    pub async fn update_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+        name: &str,
+        description: Option<&str>,
+        expected_version: Option<i64>,
+    ) -> Result<Role, IntegratedServiceError> {
+        if !self.sync_roles {
+            return Ok(self
+                .inner
+                .update_role(tenant, role_id, name, description, expected_version)
+                .await?);
+        }
+
+        check_synced_role_name(name)?;
+        let previous = self
+            .inner
+            .get_role(tenant, role_id)
+            .await?
+            .ok_or(IntegratedServiceError::User(UserServiceError::NotFound))?;
+        let updated = self
+            .inner
+            .update_role(tenant, role_id, name, description, expected_version)
+            .await?;
+
+        if let Err(e) = self.sync_realm_role(tenant, &previous, &updated).await {
+            tracing::error!(
+                role_id = %role_id,
+                error = ?e,
+                "Failed to update Keycloak realm role - reverting local role"
+            );
+            if let Err(revert_err) = self
+                .inner
+                .update_role(
+                    tenant,
+                    role_id,
+                    &previous.name,
+                    Some(previous.description.as_deref().unwrap_or_default()),
+                    Some(updated.version),
+                )
+                .await
+            {
+                tracing::error!(
+                    role_id = %role_id,
+                    error = ?revert_err,
+                    "CRITICAL: Failed to revert local role - role and realm role differ"
+                );
+            }
+            return Err(e);
+        }
+
+        Ok(updated)
+    }
+
+    /// Rename the realm role of `previous` to match `updated`, creating it if it predates
+    /// role sync
+    async fn sync_realm_role(
+        &self,
+        tenant: TenantId,
+        previous: &Role,
+        updated: &Role,
+    ) -> Result<(), IntegratedServiceError> {
+        let previous_name = self.realm_role_name(tenant, &previous.name).await?;
+        let updated_name = self.realm_role_name(tenant, &updated.name).await?;
+        match self
+            .keycloak
+            .update_realm_role(
+                tenant,
+                &previous_name,
+                &updated_name,
+                updated.description.as_deref(),
+            )
+            .await
+        {
+            Err(KeycloakError::RoleNotFound(_)) => self
+                .keycloak
+                .create_realm_role(tenant, &updated_name, updated.description.as_deref())
+                .await
+                .map(|_| ())
+                .map_err(Into::into),
+            result => result.map_err(Into::into),
+        }
+    }
+
+    /// Delete a role. With role sync the realm role, and with it every mapping, is deleted
+    /// first; if the local delete then fails, the realm role is created again without its
+    /// mappings.
+    pub async fn delete_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<(), IntegratedServiceError> {
+        if !self.sync_roles {
+            return Ok(self
+                .inner
+                .delete_role(tenant, role_id, expected_version)
+                .await?);
+        }
+
+        let role = self
+            .inner
+            .get_role(tenant, role_id)
+            .await?
+            .ok_or(IntegratedServiceError::User(UserServiceError::NotFound))?;
+
+        // Check what the local delete would reject before touching Keycloak
+        if role.is_system {
+            return Err(UserServiceError::SystemRole.into());
+        }
+        if expected_version.is_some_and(|v| v != role.version) {
+            return Err(UserServiceError::VersionConflict.into());
+        }
+
+        let realm_name = self.realm_role_name(tenant, &role.name).await?;
+        self.keycloak.delete_realm_role(tenant, &realm_name).await?;
+
+        if let Err(e) = self
+            .inner
+            .delete_role(tenant, role_id, expected_version)
+            .await
+        {
+            tracing::error!(
+                role_id = %role_id,
+                error = ?e,
+                "Failed to delete local role - recreating Keycloak realm role"
+            );
+            if let Err(rollback_err) = self
+                .keycloak
+                .create_realm_role(tenant, &realm_name, role.description.as_deref())
+                .await
+            {
+                tracing::error!(
+                    role_id = %role_id,
+                    error = ?rollback_err,
+                    "CRITICAL: Failed to recreate Keycloak realm role - role exists only locally"
+                );
+            } else {
+                tracing::warn!(
+                    role_id = %role_id,
+                    "Recreated Keycloak realm role without its user mappings"
+                );
+            }
+            return Err(e.into());
+        }
+
+        Ok(())
+    }
+
+    /// Restore a soft-deleted role. With role sync the realm role is created again and
+    /// mapped to the users that hold the role directly and to the groups it is attached
+    /// to; if that fails the role is deleted again.
+    pub async fn restore_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+    ) -> Result<Role, IntegratedServiceError> {
+        let role = self.inner.restore_role(tenant, role_id).await?;
+        if !self.sync_roles {
+            return Ok(role);
+        }
+
+        if let Err(e) = self.restore_realm_role(tenant, &role).await {
+            tracing::error!(
+                role_id = %role_id,
+                error = ?e,
+                "Failed to restore Keycloak realm role - deleting local role again"
+            );
+            if let Err(rollback_err) = self.inner.delete_role(tenant, role_id, None).await {
+                tracing::error!(
+                    role_id = %role_id,
+                    error = ?rollback_err,
+                    "CRITICAL: Failed to delete restored role - role is missing from Keycloak"
+                );
+            }
+            return Err(e);
+        }
+
+        Ok(role)
+    }
+
+    async fn restore_realm_role(
+        &self,
+        tenant: TenantId,
+        role: &Role,
+    ) -> Result<(), IntegratedServiceError> {
+        let realm_role = self
+            .keycloak
+            .ensure_realm_role(
+                tenant,
+                &self.realm_role_name(tenant, &role.name).await?,
+                role.description.as_deref(),
+            )
+            .await?;
+
+        let mut page = 1;
+        loop {
+            let holders = self
+                .inner
+                .get_users_by_role(
+                    tenant,
+                    role.id,
+                    PaginationParams::new(Some(page), Some(MAX_PAGE_SIZE)),
+                )
+                .await?;
+            for user in &holders.items {
+                self.keycloak
+                    .add_user_realm_roles(
+                        tenant,
+                        &user.keycloak_id,
+                        std::slice::from_ref(&realm_role),
+                    )
+                    .await?;
+            }
+            if page >= holders.total_pages {
+                break;
+            }
+            page += 1;
+        }
+
+        let mut page = 1;
+        loop {
+            let groups = self
+                .inner
+                .get_groups(
+                    tenant,
+                    PaginationParams::new(Some(page), Some(MAX_PAGE_SIZE)),
+                )
+                .await?;
+            for group in &groups.items {
+                if group.roles.iter().any(|r| r.id == role.id) {
+                    self.keycloak
+                        .add_group_realm_roles(
+                            tenant,
+                            &group.keycloak_id,
+                            std::slice::from_ref(&realm_role),
+                        )
+                        .await?;
+                }
+            }
+            if page >= groups.total_pages {
+                return Ok(());
+            }
+            page += 1;
+        }
+    }
+
+    /// Grant a role. With role sync the realm role is then mapped to the user, creating it
+    /// if it predates role sync; if that fails the grant is taken back.
+    pub async fn assign_role(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        role_id: Uuid,
+        grant: &RoleGrant,
+    ) -> Result<(), IntegratedServiceError> {
+        self.inner
+            .assign_role(tenant, user_id, role_id, grant)
+            .await?;
+
+        let user = self.inner.get_user(tenant, user_id).await.ok().flatten();
+
+        if self.sync_roles {
+            if let Err(e) = self.map_realm_role(tenant, user_id, role_id).await {
+                tracing::error!(
+                    user_id = %user_id,
+                    role_id = %role_id,
+                    error = ?e,
+                    "Failed to map Keycloak realm role - taking back the role grant"
+                );
+                if let Err(rollback_err) = self.inner.unassign_role(tenant, user_id, role_id).await
+                {
+                    tracing::error!(
+                        user_id = %user_id,
+                        role_id = %role_id,
+                        error = ?rollback_err,
+                        "CRITICAL: Failed to take back role grant - user lacks the realm role"
+                    );
+                }
+                return Err(e);
+            }
+        }
+
+        // Also invalidate user caches since role assignment affects the user
+        if let Some(user) = user {
+            self.invalidate_keycloak_cache(tenant, &user.keycloak_id)
+                .await;
+        }
+
+        Ok(())
+    }
+
+    async fn map_realm_role(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        role_id: Uuid,
+    ) -> Result<(), IntegratedServiceError> {
+        let (keycloak_id, role) = self.require_user_and_role(tenant, user_id, role_id).await?;
+        let realm_role = self
+            .keycloak
+            .ensure_realm_role(
+                tenant,
+                &self.realm_role_name(tenant, &role.name).await?,
+                role.description.as_deref(),
+            )
+            .await?;
+        self.keycloak
+            .add_user_realm_roles(tenant, &keycloak_id, &[realm_role])
+            .await?;
+        Ok(())
+    }
+
+    /// Take a role back. With role sync the realm role mapping is removed first and
+    /// restored if the local grant cannot be removed.
+    pub async fn unassign_role(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        role_id: Uuid,
+    ) -> Result<(), IntegratedServiceError> {
+        let user = self.inner.get_user(tenant, user_id).await.ok().flatten();
+
+        // A missing user or role has no mapping left in Keycloak
+        let mapping = match (self.sync_roles, &user) {
+            (true, Some(user)) => match self.inner.get_role(tenant, role_id).await? {
+                Some(role) => self
+                    .keycloak
+                    .get_realm_role(tenant, &self.realm_role_name(tenant, &role.name).await?)
+                    .await?
+                    .map(|realm_role| (user.keycloak_id.clone(), realm_role)),
+                None => None,
+            },
+            _ => None,
+        };
+
+        if let Some((keycloak_id, realm_role)) = &mapping {
+            self.keycloak
+                .remove_user_realm_roles(tenant, keycloak_id, std::slice::from_ref(realm_role))
+                .await?;
+        }
+
+        if let Err(e) = self.inner.unassign_role(tenant, user_id, role_id).await {
+            if let Some((keycloak_id, realm_role)) = mapping {
+                if let Err(rollback_err) = self
+                    .keycloak
+                    .add_user_realm_roles(tenant, &keycloak_id, &[realm_role])
+                    .await
+                {
+                    tracing::error!(
+                        user_id = %user_id,
+                        role_id = %role_id,
+                        error = ?rollback_err,
+                        "CRITICAL: Failed to restore Keycloak realm role mapping"
+                    );
+                }
+            }
+            return Err(e.into());
+        }
+
+        // Also invalidate user caches since role unassignment affects the user
+        if let Some(user) = user {
+            self.invalidate_keycloak_cache(tenant, &user.keycloak_id)
+                .await;
+        }
+
+        Ok(())
+    }
+
+    /// Prefix of the realm role names of `tenant`'s roles. Tenants sharing a realm would
+    /// otherwise share realm roles of the same name, so their roles are prefixed with the
+    /// tenant's slug; the default tenant and tenants with a realm of their own use the
+    /// bare role names.
+    async fn realm_role_prefix(&self, tenant: TenantId) -> Result<String, IntegratedServiceError> {
+        if tenant == TenantId::DEFAULT || self.keycloak.has_own_realm(tenant) {
+            return Ok(String::new());
+        }
+        let slug = self
+            .inner
+            .get_tenant(tenant)
+            .await?
+            .ok_or(IntegratedServiceError::User(UserServiceError::NotFound))?
+            .slug;
+        Ok(format!("{slug}{REALM_ROLE_PREFIX_SEPARATOR}"))
+    }
+
+    /// Name of the realm role mirroring the role `name` of `tenant`
+    async fn realm_role_name(
+        &self,
+        tenant: TenantId,
+        name: &str,
+    ) -> Result<String, IntegratedServiceError> {
+        Ok(format!(
+            "{}{}",
+            self.realm_role_prefix(tenant).await?,
+            name.trim()
+        ))
+    }
+
+    async fn require_user_and_role(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        role_id: Uuid,
+    ) -> Result<(String, Role), IntegratedServiceError> {
+        let user = self
+            .inner
+            .get_user(tenant, user_id)
+            .await?
+            .ok_or(IntegratedServiceError::User(UserServiceError::NotFound))?;
+        let role = self
+            .inner
+            .get_role(tenant, role_id)
+            .await?
+            .ok_or(IntegratedServiceError::User(UserServiceError::NotFound))?;
+        Ok((user.keycloak_id, role))
+    }
+
+    /// Delete expired role grants and evict the affected users' cached entries,
+    /// returning how many users lost a role. With role sync the users' realm role
+    /// mappings for roles they no longer hold are removed first; if that fails for any
+    /// user the grants are kept, so the next sweep tries again.
+    pub async fn remove_expired_role_grants(&self) -> Result<usize, IntegratedServiceError> {
+        let now = Utc::now();
+
+        if self.sync_roles {
+            let mut failure = None;
+            for (tenant, user_id) in self.inner.get_users_with_expired_role_grants(now).await? {
+                // Expired grants no longer count when a user is read, so a fresh read
+                // lists only the roles the user keeps
+                self.inner.evict_user(tenant, user_id).await?;
+                let Some(user) = self.inner.get_user(tenant, user_id).await? else {
+                    continue;
+                };
+                match self.remove_stale_realm_roles(tenant, &user).await {
+                    Ok(())
+                    | Err(IntegratedServiceError::Keycloak(KeycloakError::UserNotFound(_))) => {}
+                    Err(e) => {
+                        tracing::warn!(
+                            user_id = %user_id,
+                            error = %e,
+                            "Failed to remove expired Keycloak realm role mappings - keeping the expired grants"
+                        );
+                        failure.get_or_insert(e);
+                    }
+                }
+            }
+            if let Some(e) = failure {
+                return Err(e);
+            }
+        }
+
+        let users = self.inner.remove_expired_role_grants(now).await?;
+        for (tenant, user_id) in &users {
+            if let Ok(Some(user)) = self.inner.get_user(*tenant, *user_id).await {
+                self.invalidate_keycloak_cache(*tenant, &user.keycloak_id)
+                    .await;
+            }
+        }
+
+        Ok(users.len())
+    }
+
+    /// Remove the user's realm role mappings for roles of the tenant that are no longer
+    /// granted to the user itself. Roles inherited from groups reach the user through the
+    /// groups' mappings and do not keep a direct mapping alive. Realm roles that are not
+    /// local roles are left alone.
+    async fn remove_stale_realm_roles(
+        &self,
+        tenant: TenantId,
+        user: &User,
+    ) -> Result<(), IntegratedServiceError> {
+        let mapped = self
+            .keycloak
+            .get_user_realm_roles(tenant, &user.keycloak_id)
+            .await?;
+        if mapped.is_empty() {
+            return Ok(());
+        }
+
+        let prefix = self.realm_role_prefix(tenant).await?;
+        let granted = self
+            .inner
+            .get_granted_roles_for_user(tenant, user.id)
+            .await?;
+        let mut local_names = std::collections::HashSet::new();
+        let mut page = 1;
+        loop {
+            let roles = self
+                .inner
+                .get_roles(
+                    tenant,
+                    PaginationParams::new(Some(page), Some(MAX_PAGE_SIZE)),
+                )
+                .await?;
+            local_names.extend(
+                roles
+                    .items
+                    .into_iter()
+                    .map(|r| format!("{prefix}{}", r.name)),
+            );
+            if page >= roles.total_pages {
+                break;
+            }
+            page += 1;
+        }
+
+        let stale: Vec<KeycloakRole> = mapped
+            .into_iter()
+            .filter(|r| local_names.contains(&r.name))
+            .filter(|r| {
+                !granted
+                    .iter()
+                    .any(|held| format!("{prefix}{}", held.name) == r.name)
+            })
+            .collect();
+        if !stale.is_empty() {
+            self.keycloak
+                .remove_user_realm_roles(tenant, &user.keycloak_id, &stale)
+                .await?;
+        }
+        Ok(())
+    }
+
+    // ========== Tenant Operations (passthrough) ==========
+
+    pub async fn get_tenant(
+        &self,
+        tenant: TenantId,
+    ) -> Result<Option<Tenant>, IntegratedServiceError> {
+        Ok(self.inner.get_tenant(tenant).await?)
+    }
+
+    pub async fn get_tenants(&self) -> Result<Vec<Tenant>, IntegratedServiceError> {
+        Ok(self.inner.get_tenants().await?)
+    }
+
+    pub async fn create_tenant(
+        &self,
+        slug: &str,
+        name: &str,
+    ) -> Result<Tenant, IntegratedServiceError> {
+        Ok(self.inner.create_tenant(slug, name).await?)
+    }
+
+    // ========== API Key Operations ==========
+    // API keys are local to this service; Keycloak knows nothing of service accounts.
+
+    /// Create an API key; the returned key is not stored and cannot be shown again
+    pub async fn create_api_key(
+        &self,
+        tenant: TenantId,
+        new_key: &NewApiKey,
+    ) -> Result<IssuedApiKey, IntegratedServiceError> {
+        let generated = generate_api_key();
+        let api_key = self
+            .inner
+            .create_api_key(tenant, new_key, &generated.prefix, &generated.hash)
+            .await?;
+        Ok(IssuedApiKey {
+            api_key,
+            key: generated.key,
+        })
+    }
+
+    pub async fn get_api_keys(
+        &self,
+        tenant: TenantId,
+    ) -> Result<Vec<ApiKey>, IntegratedServiceError> {
+        Ok(self.inner.get_api_keys(tenant).await?)
+    }
+
+    /// Replace the key of an API key, keeping its name, scopes and expiry
+    pub async fn rotate_api_key(
+        &self,
+        tenant: TenantId,
+        key_id: Uuid,
+    ) -> Result<IssuedApiKey, IntegratedServiceError> {
+        let generated = generate_api_key();
+        let api_key = self
+            .inner
+            .rotate_api_key(tenant, key_id, &generated.prefix, &generated.hash)
+            .await?;
+        Ok(IssuedApiKey {
+            api_key,
+            key: generated.key,
+        })
+    }
+
+    pub async fn revoke_api_key(
+        &self,
+        tenant: TenantId,
+        key_id: Uuid,
+    ) -> Result<(), IntegratedServiceError> {
+        Ok(self.inner.revoke_api_key(tenant, key_id).await?)
+    }
+
+    /// The active API key matching `presented`, if any. Its use is recorded when the
+    /// stored `last_used_at` is older than [`LAST_USED_RESOLUTION`]; failing to record
+    /// it does not fail the request.
+    pub async fn authenticate_api_key(
+        &self,
+        presented: &str,
+    ) -> Result<Option<ApiKey>, IntegratedServiceError> {
+        if presented.is_empty() {
+            return Ok(None);
+        }
+        let now = Utc::now();
+        let Some(mut api_key) = self
+            .inner
+            .get_api_key_by_hash(&hash_api_key(presented))
+            .await?
+            .filter(|k| k.is_active_at(now))
+        else {
+            return Ok(None);
+        };
+
+        let stale = api_key.last_used_at.is_none_or(|last_used_at| {
+            (now - last_used_at).to_std().unwrap_or_default() >= LAST_USED_RESOLUTION
+        });
+        if stale {
+            match self.inner.record_api_key_use(api_key.id, now).await {
+                Ok(()) => api_key.last_used_at = Some(now),
+                Err(e) => {
+                    tracing::warn!(api_key_id = %api_key.id, error = %e, "Failed to record API key use")
+                }
+            }
+        }
+        Ok(Some(api_key))
+    }
+
+    // ========== Group Operations ==========
+    // Keycloak is written first and the local tables follow. If the local write fails,
+    // the Keycloak change is undone; a deleted Keycloak group cannot be brought back, so
+    // that case is logged. With role sync the roles attached to a group are mapped to the
+    // Keycloak group, so members' tokens carry them.
+
+    pub async fn get_group(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+    ) -> Result<Option<Group>, IntegratedServiceError> {
+        Ok(self.inner.get_group(tenant, group_id).await?)
+    }
+
+    pub async fn get_groups(
+        &self,
+        tenant: TenantId,
+        pagination: PaginationParams,
+    ) -> Result<PaginatedResult<Group>, IntegratedServiceError> {
+        Ok(self.inner.get_groups(tenant, pagination).await?)
+    }
+
+    /// Get a group's members with merged Keycloak profiles
+    pub async fn get_group_members(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        pagination: PaginationParams,
+    ) -> Result<PaginatedResult<FullUser>, IntegratedServiceError> {
+        self.require_group(tenant, group_id).await?;
+        let result = self
+            .inner
+            .get_group_members(tenant, group_id, pagination)
+            .await?;
+
+        let keycloak_ids: Vec<String> =
+            result.items.iter().map(|u| u.keycloak_id.clone()).collect();
+        let profiles = self.get_keycloak_profiles(tenant, &keycloak_ids).await;
+
+        Ok(PaginatedResult {
+            items: result
+                .items
+                .into_iter()
+                .zip(profiles)
+                .map(|(user, kc_profile)| self.merge_user(user, kc_profile))
+                .collect(),
+            total: result.total,
+            page: result.page,
+            page_size: result.page_size,
+            total_pages: result.total_pages,
+        })
+    }
+
+    /// Create a group in Keycloak and record it locally, deleting the Keycloak group
+    /// again if the local record cannot be created
+    pub async fn create_group(
+        &self,
+        tenant: TenantId,
+        name: &str,
+    ) -> Result<Group, IntegratedServiceError> {
+        let keycloak_id = self.keycloak.create_group(tenant, name.trim()).await?;
+
+        match self.inner.create_group(tenant, &keycloak_id, name).await {
+            Ok(group) => Ok(group),
+            Err(e) => {
+                tracing::error!(
+                    keycloak_id = %keycloak_id,
+                    name = %name,
+                    error = ?e,
+                    "Failed to create local group record - rolling back Keycloak group"
+                );
+                if let Err(rollback_err) = self.keycloak.delete_group(tenant, &keycloak_id).await {
+                    tracing::error!(
+                        keycloak_id = %keycloak_id,
+                        error = ?rollback_err,
+                        "CRITICAL: Failed to rollback Keycloak group - ORPHANED GROUP requires manual cleanup"
+                    );
+                }
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Rename a group in Keycloak and locally, giving the Keycloak group its old name
+    /// back if the local rename fails
+    pub async fn rename_group(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        name: &str,
+    ) -> Result<Group, IntegratedServiceError> {
+        let group = self.require_group(tenant, group_id).await?;
+        self.keycloak
+            .rename_group(tenant, &group.keycloak_id, name.trim())
+            .await?;
+
+        match self.inner.rename_group(tenant, group_id, name).await {
+            Ok(renamed) => Ok(renamed),
+            Err(e) => {
+                tracing::error!(
+                    group_id = %group_id,
+                    error = ?e,
+                    "Failed to rename local group - restoring Keycloak group name"
+                );
+                if let Err(rollback_err) = self
+                    .keycloak
+                    .rename_group(tenant, &group.keycloak_id, &group.name)
+                    .await
+                {
+                    tracing::error!(
+                        group_id = %group_id,
+                        keycloak_id = %group.keycloak_id,
+                        error = ?rollback_err,
+                        "CRITICAL: Failed to restore Keycloak group name - names differ"
+                    );
+                }
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Delete a group from Keycloak, then locally. A Keycloak group cannot be brought
+    /// back with its id and members, so a failed local delete is only logged.
+    pub async fn delete_group(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+    ) -> Result<(), IntegratedServiceError> {
+        let group = self.require_group(tenant, group_id).await?;
+        self.keycloak
+            .delete_group(tenant, &group.keycloak_id)
+            .await?;
+
+        if let Err(e) = self.inner.delete_group(tenant, group_id).await {
+            tracing::error!(
+                group_id = %group_id,
+                keycloak_id = %group.keycloak_id,
+                error = ?e,
+                "CRITICAL: Group deleted in Keycloak but not locally - delete it again to clean up"
+            );
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    pub async fn add_group_member(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), IntegratedServiceError> {
+        let group = self.require_group(tenant, group_id).await?;
+        let user = self
+            .inner
+            .get_user(tenant, user_id)
+            .await?
+            .ok_or(IntegratedServiceError::User(UserServiceError::NotFound))?;
+
+        self.keycloak
+            .add_user_to_group(tenant, &user.keycloak_id, &group.keycloak_id)
+            .await?;
+
+        if let Err(e) = self.inner.add_group_member(tenant, group_id, user_id).await {
+            if let Err(rollback_err) = self
+                .keycloak
+                .remove_user_from_group(tenant, &user.keycloak_id, &group.keycloak_id)
+                .await
+            {
+                tracing::error!(
+                    group_id = %group_id,
+                    user_id = %user_id,
+                    error = ?rollback_err,
+                    "CRITICAL: Failed to remove Keycloak group membership - user is a member only in Keycloak"
+                );
+            }
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    pub async fn remove_group_member(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), IntegratedServiceError> {
+        let group = self.require_group(tenant, group_id).await?;
+        // A deleted user has no membership left to remove in Keycloak
+        let user = self.inner.get_user(tenant, user_id).await?;
+        if let Some(user) = &user {
+            self.keycloak
+                .remove_user_from_group(tenant, &user.keycloak_id, &group.keycloak_id)
+                .await?;
+        }
+
+        if let Err(e) = self
+            .inner
+            .remove_group_member(tenant, group_id, user_id)
+            .await
+        {
+            if let Some(user) = user {
+                if let Err(rollback_err) = self
+                    .keycloak
+                    .add_user_to_group(tenant, &user.keycloak_id, &group.keycloak_id)
+                    .await
+                {
+                    tracing::error!(
+                        group_id = %group_id,
+                        user_id = %user_id,
+                        error = ?rollback_err,
+                        "CRITICAL: Failed to restore Keycloak group membership"
+                    );
+                }
+            }
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    /// Attach a role to a group. With role sync the realm role is then mapped to the
+    /// Keycloak group, creating it if it predates role sync; if that fails the role is
+    /// detached again.
+    pub async fn assign_group_role(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        role_id: Uuid,
+    ) -> Result<(), IntegratedServiceError> {
+        self.inner
+            .assign_group_role(tenant, group_id, role_id)
+            .await?;
+
+        if self.sync_roles {
+            if let Err(e) = self.map_group_realm_role(tenant, group_id, role_id).await {
+                tracing::error!(
+                    group_id = %group_id,
+                    role_id = %role_id,
+                    error = ?e,
+                    "Failed to map Keycloak realm role to group - detaching the role"
+                );
+                if let Err(rollback_err) = self
+                    .inner
+                    .unassign_group_role(tenant, group_id, role_id)
+                    .await
+                {
+                    tracing::error!(
+                        group_id = %group_id,
+                        role_id = %role_id,
+                        error = ?rollback_err,
+                        "CRITICAL: Failed to detach group role - members lack the realm role"
+                    );
+                }
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn map_group_realm_role(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        role_id: Uuid,
+    ) -> Result<(), IntegratedServiceError> {
+        let group = self.require_group(tenant, group_id).await?;
+        let role = self
+            .inner
+            .get_role(tenant, role_id)
+            .await?
+            .ok_or(IntegratedServiceError::User(UserServiceError::NotFound))?;
+        let realm_role = self
+            .keycloak
+            .ensure_realm_role(
+                tenant,
+                &self.realm_role_name(tenant, &role.name).await?,
+                role.description.as_deref(),
+            )
+            .await?;
+        self.keycloak
+            .add_group_realm_roles(tenant, &group.keycloak_id, &[realm_role])
+            .await?;
+        Ok(())
+    }
+
+    /// Detach a role from a group. With role sync the group's realm role mapping is
+    /// removed first and restored if the role cannot be detached locally.
+    pub async fn unassign_group_role(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        role_id: Uuid,
+    ) -> Result<(), IntegratedServiceError> {
+        // A missing group or role has no mapping left in Keycloak
+        let mapping = if self.sync_roles {
+            match (
+                self.inner.get_group(tenant, group_id).await?,
+                self.inner.get_role(tenant, role_id).await?,
+            ) {
+                (Some(group), Some(role)) => self
+                    .keycloak
+                    .get_realm_role(tenant, &self.realm_role_name(tenant, &role.name).await?)
+                    .await?
+                    .map(|realm_role| (group.keycloak_id, realm_role)),
+                _ => None,
+            }
+        } else {
+            None
+        };
+
+        if let Some((group_keycloak_id, realm_role)) = &mapping {
+            self.keycloak
+                .remove_group_realm_roles(
+                    tenant,
+                    group_keycloak_id,
+                    std::slice::from_ref(realm_role),
+                )
+                .await?;
+        }
+
+        if let Err(e) = self
+            .inner
+            .unassign_group_role(tenant, group_id, role_id)
+            .await
+        {
+            if let Some((group_keycloak_id, realm_role)) = mapping {
+                if let Err(rollback_err) = self
+                    .keycloak
+                    .add_group_realm_roles(tenant, &group_keycloak_id, &[realm_role])
+                    .await
+                {
+                    tracing::error!(
+                        group_id = %group_id,
+                        role_id = %role_id,
+                        error = ?rollback_err,
+                        "CRITICAL: Failed to restore Keycloak group realm role mapping"
+                    );
+                }
+            }
+            return Err(e.into());
+        }
+
+        Ok(())
+    }
+
+    async fn require_group(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+    ) -> Result<Group, IntegratedServiceError> {
+        self.inner
+            .get_group(tenant, group_id)
+            .await?
+            .ok_or(IntegratedServiceError::User(UserServiceError::NotFound))
+    }
 }
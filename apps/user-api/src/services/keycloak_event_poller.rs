@@ -0,0 +1,460 @@
+use chrono::{DateTime, Days, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+use user_lib::entities::TenantId;
+use user_lib::repository::traits::{
+    EventCheckpointRepositoryTrait, RoleRepositoryTrait, UserRepositoryTrait,
+    UserRoleRepositoryTrait,
+};
+
+use crate::keycloak::{KeycloakAdminEvent, KeycloakEvent};
+use crate::services::integrated_user_service::IntegratedServiceError;
+use crate::services::IntegratedUserService;
+
+/// Checkpoint source of the realm's admin events
+pub const ADMIN_EVENTS_SOURCE: &str = "admin-events";
+/// Checkpoint source of the realm's user events
+pub const USER_EVENTS_SOURCE: &str = "events";
+
+/// User event types that are consumed; the rest are never fetched
+const USER_EVENT_TYPES: [&str; 5] = [
+    "LOGIN",
+    "UPDATE_PROFILE",
+    "UPDATE_EMAIL",
+    "VERIFY_EMAIL",
+    "DELETE_ACCOUNT",
+];
+
+const PAGE_SIZE: usize = 100;
+
+/// What a Keycloak event means for the local state of a user
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UserChange<'a> {
+    /// The profile changed: drop its cached copy
+    Updated(&'a str),
+    /// The user is gone from Keycloak: remove the local record
+    Deleted(&'a str),
+    LoggedIn(&'a str, DateTime<Utc>),
+}
+
+impl<'a> UserChange<'a> {
+    pub fn keycloak_id(&self) -> &'a str {
+        match self {
+            Self::Updated(keycloak_id) | Self::Deleted(keycloak_id) => keycloak_id,
+            Self::LoggedIn(keycloak_id, _) => keycloak_id,
+        }
+    }
+
+    pub fn from_admin_event(event: &'a KeycloakAdminEvent) -> Option<Self> {
+        let keycloak_id = event.user_id()?;
+        if event.is_user_deletion() {
+            Some(Self::Deleted(keycloak_id))
+        } else {
+            Some(Self::Updated(keycloak_id))
+        }
+    }
+
+    pub fn from_user_event(event: &'a KeycloakEvent) -> Option<Self> {
+        let keycloak_id = event.user_id.as_deref()?;
+        match event.event_type.as_str() {
+            "LOGIN" => Some(Self::LoggedIn(
+                keycloak_id,
+                DateTime::from_timestamp_millis(event.time)?,
+            )),
+            "DELETE_ACCOUNT" => Some(Self::Deleted(keycloak_id)),
+            "UPDATE_PROFILE" | "UPDATE_EMAIL" | "VERIFY_EMAIL" => Some(Self::Updated(keycloak_id)),
+            _ => None,
+        }
+    }
+}
+
+/// Poll every realm's Keycloak events every `interval` for as long as the process runs.
+/// Changes made directly in Keycloak then reach the cache and the local records without
+/// waiting for cached profiles to expire.
+pub fn spawn_keycloak_event_poller<U, R, UR>(
+    service: Arc<IntegratedUserService<U, R, UR>>,
+    checkpoints: Arc<dyn EventCheckpointRepositoryTrait>,
+    interval: Duration,
+) -> JoinHandle<()>
+where
+    U: UserRepositoryTrait + Send + Sync + 'static,
+    R: RoleRepositoryTrait + Send + Sync + 'static,
+    UR: UserRoleRepositoryTrait + Send + Sync + 'static,
+{
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            poll_keycloak_events(&service, checkpoints.as_ref()).await;
+        }
+    })
+}
+
+/// One poll of every configured realm; failures are logged and retried on the next tick
+pub async fn poll_keycloak_events<U, R, UR>(
+    service: &IntegratedUserService<U, R, UR>,
+    checkpoints: &dyn EventCheckpointRepositoryTrait,
+) where
+    U: UserRepositoryTrait + Send + Sync + 'static,
+    R: RoleRepositoryTrait + Send + Sync + 'static,
+    UR: UserRoleRepositoryTrait + Send + Sync + 'static,
+{
+    for (tenant, realm) in service.keycloak().realms() {
+        if !service.keycloak().is_configured(tenant) {
+            continue;
+        }
+        match poll_admin_events(service, checkpoints, tenant).await {
+            Ok(0) => {}
+            Ok(events) => tracing::info!(%tenant, realm, events, "Consumed Keycloak admin events"),
+            Err(e) => {
+                tracing::warn!(%tenant, realm, error = %e, "Failed to consume Keycloak admin events")
+            }
+        }
+        match poll_user_events(service, checkpoints, tenant).await {
+            Ok(0) => {}
+            Ok(events) => tracing::info!(%tenant, realm, events, "Consumed Keycloak events"),
+            Err(e) => {
+                tracing::warn!(%tenant, realm, error = %e, "Failed to consume Keycloak events")
+            }
+        }
+    }
+}
+
+/// Apply the admin events since the checkpoint, returning how many were applied
+pub async fn poll_admin_events<U, R, UR>(
+    service: &IntegratedUserService<U, R, UR>,
+    checkpoints: &dyn EventCheckpointRepositoryTrait,
+    tenant: TenantId,
+) -> Result<usize, IntegratedServiceError>
+where
+    U: UserRepositoryTrait + Send + Sync + 'static,
+    R: RoleRepositoryTrait + Send + Sync + 'static,
+    UR: UserRoleRepositoryTrait + Send + Sync + 'static,
+{
+    let Some(since) = checkpoint(checkpoints, tenant, ADMIN_EVENTS_SOURCE).await? else {
+        return Ok(0);
+    };
+
+    let mut events = Vec::new();
+    let mut first = 0;
+    loop {
+        let page = service
+            .keycloak()
+            .get_admin_events(tenant, date_from(since), first, PAGE_SIZE)
+            .await?;
+        let done = page.len() < PAGE_SIZE || page.iter().any(|e| e.time <= since);
+        events.extend(page.into_iter().filter(|e| e.time > since));
+        if done {
+            break;
+        }
+        first += PAGE_SIZE;
+    }
+    events.sort_by_key(|e| e.time);
+
+    let changes = events
+        .iter()
+        .map(|e| (e.time, UserChange::from_admin_event(e)));
+    apply_changes(service, checkpoints, tenant, ADMIN_EVENTS_SOURCE, changes).await
+}
+
+/// Apply the user events since the checkpoint, returning how many were applied
+pub async fn poll_user_events<U, R, UR>(
+    service: &IntegratedUserService<U, R, UR>,
+    checkpoints: &dyn EventCheckpointRepositoryTrait,
+    tenant: TenantId,
+) -> Result<usize, IntegratedServiceError>
+where
+    U: UserRepositoryTrait + Send + Sync + 'static,
+    R: RoleRepositoryTrait + Send + Sync + 'static,
+    UR: UserRoleRepositoryTrait + Send + Sync + 'static,
+{
+    let Some(since) = checkpoint(checkpoints, tenant, USER_EVENTS_SOURCE).await? else {
+        return Ok(0);
+    };
+
+    let mut events = Vec::new();
+    let mut first = 0;
+    loop {
+        let page = service
+            .keycloak()
+            .get_events(
+                tenant,
+                &USER_EVENT_TYPES,
+                date_from(since),
+                first,
+                PAGE_SIZE,
+            )
+            .await?;
+        let done = page.len() < PAGE_SIZE || page.iter().any(|e| e.time <= since);
+        events.extend(page.into_iter().filter(|e| e.time > since));
+        if done {
+            break;
+        }
+        first += PAGE_SIZE;
+    }
+    events.sort_by_key(|e| e.time);
+
+    let changes = events
+        .iter()
+        .map(|e| (e.time, UserChange::from_user_event(e)));
+    apply_changes(service, checkpoints, tenant, USER_EVENTS_SOURCE, changes).await
+}
+
+/// The saved checkpoint of `source`. Without one, the feed starts now rather than
+/// replaying the realm's history, and `None` is returned.
+async fn checkpoint(
+    checkpoints: &dyn EventCheckpointRepositoryTrait,
+    tenant: TenantId,
+    source: &str,
+) -> Result<Option<i64>, IntegratedServiceError> {
+    let saved = checkpoints
+        .get_event_checkpoint(tenant, source)
+        .await
+        .map_err(|e| IntegratedServiceError::User(e.into()))?;
+    if saved.is_none() {
+        checkpoints
+            .save_event_checkpoint(tenant, source, Utc::now().timestamp_millis())
+            .await
+            .map_err(|e| IntegratedServiceError::User(e.into()))?;
+    }
+    Ok(saved)
+}
+
+/// Keycloak filters by whole days; the day before also covers clock and time zone skew
+fn date_from(since: i64) -> chrono::NaiveDate {
+    let day = DateTime::from_timestamp_millis(since)
+        .unwrap_or_default()
+        .date_naive();
+    day.checked_sub_days(Days::new(1)).unwrap_or(day)
+}
+
+/// Apply `changes` oldest first, saving the checkpoint after the last one applied so a
+/// failed change is retried on the next poll
+async fn apply_changes<'a, U, R, UR>(
+    service: &IntegratedUserService<U, R, UR>,
+    checkpoints: &dyn EventCheckpointRepositoryTrait,
+    tenant: TenantId,
+    source: &str,
+    changes: impl Iterator<Item = (i64, Option<UserChange<'a>>)>,
+) -> Result<usize, IntegratedServiceError>
+where
+    U: UserRepositoryTrait + Send + Sync + 'static,
+    R: RoleRepositoryTrait + Send + Sync + 'static,
+    UR: UserRoleRepositoryTrait + Send + Sync + 'static,
+{
+    let mut applied = 0;
+    let mut last_time = None;
+    let mut result = Ok(());
+    for (time, change) in changes {
+        let outcome = match change {
+            Some(change) => apply_change(service, tenant, change).await,
+            None => Ok(()),
+        };
+        if let Err(e) = outcome {
+            result = Err(e);
+            break;
+        }
+        applied += 1;
+        last_time = Some(time);
+    }
+
+    if let Some(time) = last_time {
+        checkpoints
+            .save_event_checkpoint(tenant, source, time)
+            .await
+            .map_err(|e| IntegratedServiceError::User(e.into()))?;
+    }
+    result.map(|_| applied)
+}
+
+/// Apply one change of `realm_tenant`'s realm to the local user it concerns, in
+/// whichever tenant sharing that realm the user belongs to
+async fn apply_change<U, R, UR>(
+    service: &IntegratedUserService<U, R, UR>,
+    realm_tenant: TenantId,
+    change: UserChange<'_>,
+) -> Result<(), IntegratedServiceError>
+where
+    U: UserRepositoryTrait + Send + Sync + 'static,
+    R: RoleRepositoryTrait + Send + Sync + 'static,
+    UR: UserRoleRepositoryTrait + Send + Sync + 'static,
+{
+    // Without a local user only a cached profile can be left, under the realm's tenant
+    let tenant = service
+        .keycloak_user_tenant(realm_tenant, change.keycloak_id())
+        .await?
+        .unwrap_or(realm_tenant);
+
+    match change {
+        UserChange::Updated(keycloak_id) => {
+            service
+                .apply_keycloak_profile_change(tenant, keycloak_id)
+                .await;
+            Ok(())
+        }
+        UserChange::Deleted(keycloak_id) => service
+            .remove_user_deleted_in_keycloak(tenant, keycloak_id)
+            .await
+            .map(|_| ()),
+        UserChange::LoggedIn(keycloak_id, at) => {
+            service.record_user_login(tenant, keycloak_id, at).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn admin_event(operation_type: &str, resource_path: &str) -> KeycloakAdminEvent {
+        KeycloakAdminEvent {
+            time: 1_700_000_000_000,
+            operation_type: operation_type.to_string(),
+            resource_type: Some("USER".to_string()),
+            resource_path: Some(resource_path.to_string()),
+        }
+    }
+
+    fn user_event(event_type: &str) -> KeycloakEvent {
+        KeycloakEvent {
+            time: 1_700_000_000_000,
+            event_type: event_type.to_string(),
+            user_id: Some("kc-1".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_admin_events_on_users_map_to_changes() {
+        let delete = admin_event("DELETE", "users/kc-1");
+        assert_eq!(
+            UserChange::from_admin_event(&delete),
+            Some(UserChange::Deleted("kc-1"))
+        );
+
+        let update = admin_event("UPDATE", "users/kc-1");
+        assert_eq!(
+            UserChange::from_admin_event(&update),
+            Some(UserChange::Updated("kc-1"))
+        );
+
+        // Deleting a sub-resource only changes the user
+        let unmap = admin_event("DELETE", "users/kc-1/role-mappings/realm");
+        assert_eq!(
+            UserChange::from_admin_event(&unmap),
+            Some(UserChange::Updated("kc-1"))
+        );
+
+        let group = admin_event("DELETE", "groups/g-1");
+        assert_eq!(UserChange::from_admin_event(&group), None);
+    }
+
+    #[test]
+    fn test_user_events_map_to_changes() {
+        let login = user_event("LOGIN");
+        assert_eq!(
+            UserChange::from_user_event(&login),
+            Some(UserChange::LoggedIn(
+                "kc-1",
+                DateTime::from_timestamp_millis(1_700_000_000_000).unwrap()
+            ))
+        );
+        assert_eq!(
+            UserChange::from_user_event(&user_event("UPDATE_EMAIL")),
+            Some(UserChange::Updated("kc-1"))
+        );
+        assert_eq!(
+            UserChange::from_user_event(&user_event("DELETE_ACCOUNT")),
+            Some(UserChange::Deleted("kc-1"))
+        );
+        assert_eq!(UserChange::from_user_event(&user_event("LOGOUT")), None);
+    }
+
+    #[tokio::test]
+    async fn test_events_reach_users_of_every_tenant_sharing_the_realm() {
+        use crate::cache::{Cache, CacheConfig, CachedUserService};
+        use crate::keycloak::{KeycloakClient, KeycloakConfig};
+        use user_lib::repository::{InMemoryEventCheckpointRepository, InMemoryStore};
+        use user_lib::user_service::UserService;
+
+        let (users, roles, user_roles) = InMemoryStore::new().repositories();
+        let inner = Arc::new(UserService::with_repos(
+            Arc::new(users),
+            Arc::new(roles),
+            Arc::new(user_roles),
+        ));
+        // Neither tenant has an entry in a realms file: both use the default realm
+        let acme = TenantId(uuid::Uuid::new_v4());
+        let default_user = inner.create_user(TenantId::DEFAULT, "kc-1").await.unwrap();
+        let acme_user = inner.create_user(acme, "kc-2").await.unwrap();
+
+        let cached =
+            CachedUserService::new(inner.clone(), Cache::disabled(), CacheConfig::from_env());
+        let keycloak = KeycloakClient::new(KeycloakConfig {
+            base_url: "http://keycloak:8080".to_string(),
+            realm: "master".to_string(),
+            client_id: "user-api-service".to_string(),
+            client_secret: String::new(),
+            profile_cache_ttl: Duration::from_secs(300),
+        });
+        let service =
+            IntegratedUserService::new(Arc::new(cached), Arc::new(keycloak), Cache::disabled());
+        let checkpoints = InMemoryEventCheckpointRepository::new();
+
+        let logged_in = DateTime::from_timestamp_millis(1_700_000_000_000).unwrap();
+        let changes = vec![
+            (1, Some(UserChange::LoggedIn("kc-2", logged_in))),
+            (2, Some(UserChange::Deleted("kc-1"))),
+        ];
+        let applied = apply_changes(
+            &service,
+            &checkpoints,
+            TenantId::DEFAULT,
+            USER_EVENTS_SOURCE,
+            changes.into_iter(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(applied, 2);
+
+        let acme_user = inner.get_user(acme, acme_user.id).await.unwrap().unwrap();
+        assert_eq!(acme_user.last_login_at, Some(logged_in));
+        assert!(inner
+            .get_user(TenantId::DEFAULT, default_user.id)
+            .await
+            .unwrap()
+            .is_none());
+
+        // The acme user's deletion arrives through the default tenant's feed too
+        let changes = vec![(3, Some(UserChange::Deleted("kc-2")))];
+        apply_changes(
+            &service,
+            &checkpoints,
+            TenantId::DEFAULT,
+            USER_EVENTS_SOURCE,
+            changes.into_iter(),
+        )
+        .await
+        .unwrap();
+        assert!(inner.get_user(acme, acme_user.id).await.unwrap().is_none());
+        assert_eq!(
+            checkpoints
+                .get_event_checkpoint(TenantId::DEFAULT, USER_EVENTS_SOURCE)
+                .await
+                .unwrap(),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn test_date_from_starts_the_day_before_the_checkpoint() {
+        // 2023-11-14T22:13:20Z
+        assert_eq!(
+            date_from(1_700_000_000_000),
+            chrono::NaiveDate::from_ymd_opt(2023, 11, 13).unwrap()
+        );
+    }
+}
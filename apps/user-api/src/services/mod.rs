@@ -1,3 +1,6 @@
+pub mod cache_warmup;
 pub mod integrated_user_service;
+pub mod keycloak_event_poller;
+pub mod role_grant_sweeper;
 
 pub use integrated_user_service::IntegratedUserService;
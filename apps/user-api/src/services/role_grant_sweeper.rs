@@ -0,0 +1,46 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+use user_lib::repository::traits::{
+    RoleRepositoryTrait, UserRepositoryTrait, UserRoleRepositoryTrait,
+};
+
+use crate::services::IntegratedUserService;
+
+/// Remove expired role grants every `interval` for as long as the process runs.
+/// Expired grants already stop counting when they expire; the sweep deletes them and
+/// evicts the cached users that still list the role.
+pub fn spawn_role_grant_sweeper<U, R, UR>(
+    service: Arc<IntegratedUserService<U, R, UR>>,
+    interval: Duration,
+) -> JoinHandle<()>
+where
+    U: UserRepositoryTrait + Send + Sync + 'static,
+    R: RoleRepositoryTrait + Send + Sync + 'static,
+    UR: UserRoleRepositoryTrait + Send + Sync + 'static,
+{
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            sweep_expired_role_grants(&service).await;
+        }
+    })
+}
+
+/// One sweep; failures are logged and retried on the next tick
+pub async fn sweep_expired_role_grants<U, R, UR>(service: &IntegratedUserService<U, R, UR>)
+where
+    U: UserRepositoryTrait + Send + Sync + 'static,
+    R: RoleRepositoryTrait + Send + Sync + 'static,
+    UR: UserRoleRepositoryTrait + Send + Sync + 'static,
+{
+    match service.remove_expired_role_grants().await {
+        Ok(0) => {}
+        Ok(users) => tracing::info!(users, "Removed expired role grants"),
+        Err(e) => tracing::warn!(error = %e, "Failed to remove expired role grants"),
+    }
+}
@@ -1,16 +1,15 @@
 use std::sync::Arc;
-use user_lib::repository::role_repository::RoleRepository;
 use user_lib::repository::traits::{
     RoleRepositoryTrait, UserRepositoryTrait, UserRoleRepositoryTrait,
 };
-use user_lib::repository::user_repository::UserRepository;
-use user_lib::repository::user_role_repository::UserRoleRepository;
 
 use crate::services::IntegratedUserService;
 
-#[derive(Clone)]
-pub struct AppState<U = UserRepository, R = RoleRepository, UR = UserRoleRepository>
-where
+pub struct AppState<
+    U = Arc<dyn UserRepositoryTrait>,
+    R = Arc<dyn RoleRepositoryTrait>,
+    UR = Arc<dyn UserRoleRepositoryTrait>,
+> where
     U: UserRepositoryTrait + Send + Sync + 'static,
     R: RoleRepositoryTrait + Send + Sync + 'static,
     UR: UserRoleRepositoryTrait + Send + Sync + 'static,
@@ -18,3 +17,18 @@ where
     pub user_service: Arc<IntegratedUserService<U, R, UR>>,
     pub env: String,
 }
+
+// Not derived: the repositories sit behind `Arc`s and need not be `Clone` themselves
+impl<U, R, UR> Clone for AppState<U, R, UR>
+where
+    U: UserRepositoryTrait + Send + Sync + 'static,
+    R: RoleRepositoryTrait + Send + Sync + 'static,
+    UR: UserRoleRepositoryTrait + Send + Sync + 'static,
+{
+    fn clone(&self) -> Self {
+        Self {
+            user_service: Arc::clone(&self.user_service),
+            env: self.env.clone(),
+        }
+    }
+}
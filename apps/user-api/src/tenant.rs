@@ -0,0 +1,240 @@
+//! Tenant resolution for incoming requests.
+//!
+//! On the v1 API the tenant is the caller's: a service account acts in its key's tenant,
+//! a user in the tenant of the `tenant_id` claim of their verified token. A token without
+//! the claim identifies its tenant only through a realm that serves that tenant alone;
+//! otherwise it reaches the default tenant. `X-Tenant-ID` may repeat the caller's tenant
+//! but never picks another one. Routes without a caller, the admin routes behind the
+//! admin token, take the tenant from the header. Requests naming none use the default
+//! tenant.
+
+use axum::{
+    extract::FromRequestParts,
+    http::{request::Parts, HeaderMap},
+};
+use user_lib::entities::TenantId;
+use user_lib::repository::traits::{
+    RoleRepositoryTrait, UserRepositoryTrait, UserRoleRepositoryTrait,
+};
+
+use crate::api_key::Caller;
+use crate::error::{handle_integrated_service_error, ApiError};
+use crate::keycloak::KeycloakClient;
+use crate::state::AppState;
+
+pub const TENANT_HEADER: &str = "x-tenant-id";
+
+/// Tenant of the current request, validated against the tenants table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tenant(pub TenantId);
+
+/// Tenant named by the `X-Tenant-ID` header, without checking that it exists
+pub fn tenant_from_header(headers: &HeaderMap) -> Result<Option<TenantId>, ApiError> {
+    headers
+        .get(TENANT_HEADER)
+        .map(|value| {
+            value
+                .to_str()
+                .ok()
+                .and_then(|v| v.trim().parse::<TenantId>().ok())
+                .ok_or_else(|| ApiError::BadRequest("invalid tenant id".to_string()))
+        })
+        .transpose()
+}
+
+/// Tenant the caller acts in, without checking that it exists. A header naming another
+/// tenant is refused.
+pub fn caller_tenant(
+    caller: &Caller,
+    headers: &HeaderMap,
+    keycloak: &KeycloakClient,
+) -> Result<TenantId, ApiError> {
+    let requested = tenant_from_header(headers)?;
+    let token = match caller {
+        Caller::ServiceAccount(key) if requested.is_some_and(|t| t != key.tenant) => {
+            return Err(ApiError::Forbidden(
+                "tenant does not match API key".to_string(),
+            ))
+        }
+        Caller::ServiceAccount(key) => return Ok(key.tenant),
+        Caller::User(token) => token,
+    };
+
+    let tenant = match token.tenant {
+        Some(claim) if requested.is_some_and(|t| t != claim) => {
+            return Err(ApiError::Forbidden(
+                "tenant header does not match token".to_string(),
+            ))
+        }
+        Some(claim) => claim,
+        None => {
+            let tenant = requested.unwrap_or(TenantId::DEFAULT);
+            if tenant != TenantId::DEFAULT && !keycloak.has_own_realm(tenant) {
+                return Err(ApiError::Forbidden(
+                    "token has no tenant_id claim".to_string(),
+                ));
+            }
+            tenant
+        }
+    };
+    if !keycloak.issued_by_realm_of(tenant, token) {
+        return Err(ApiError::Forbidden(
+            "token was not issued by the tenant's realm".to_string(),
+        ));
+    }
+    Ok(tenant)
+}
+
+impl<U, R, UR> FromRequestParts<AppState<U, R, UR>> for Tenant
+where
+    U: UserRepositoryTrait + Send + Sync + 'static,
+    R: RoleRepositoryTrait + Send + Sync + 'static,
+    UR: UserRoleRepositoryTrait + Send + Sync + 'static,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState<U, R, UR>,
+    ) -> Result<Self, Self::Rejection> {
+        let tenant = match parts.extensions.get::<Caller>() {
+            Some(caller) => caller_tenant(caller, &parts.headers, state.user_service.keycloak())?,
+            None => tenant_from_header(&parts.headers)?.unwrap_or(TenantId::DEFAULT),
+        };
+        if tenant == TenantId::DEFAULT {
+            return Ok(Tenant(tenant));
+        }
+
+        match state.user_service.get_tenant(tenant).await {
+            Ok(Some(_)) => Ok(Tenant(tenant)),
+            Ok(None) => Err(ApiError::unknown_tenant()),
+            Err(e) => Err(handle_integrated_service_error(
+                e,
+                &state.env,
+                "resolve_tenant",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use crate::keycloak::{AccessToken, KeycloakConfig};
+
+    const ACME: &str = "6f1c1d52-3a4e-4c43-9a0b-2f8f1b3c9d10";
+    const GLOBEX: &str = "0b6f7a3e-6f0e-4a53-8f39-2f4a8c2d1e55";
+    const INITECH: &str = "9a4d2c1e-7b3f-4e8a-b5c6-d7e8f9a0b1c2";
+
+    /// Acme has the `acme` realm to itself, Globex and Initech share `shared`
+    fn keycloak() -> (KeycloakClient, PathBuf) {
+        let path = std::env::temp_dir().join(format!("tenants-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            format!(
+                r#"{{"{ACME}": {{"realm": "acme"}},
+                    "{GLOBEX}": {{"realm": "shared"}},
+                    "{INITECH}": {{"realm": "shared"}}}}"#
+            ),
+        )
+        .unwrap();
+        let client = KeycloakClient::new(KeycloakConfig {
+            base_url: "http://keycloak:8080".to_string(),
+            realm: "master".to_string(),
+            client_id: "user-api-service".to_string(),
+            client_secret: String::new(),
+            profile_cache_ttl: Duration::from_secs(300),
+        })
+        .with_realms_file(path.clone())
+        .unwrap();
+        (client, path)
+    }
+
+    fn user(realm: &str, tenant: Option<&str>) -> Caller {
+        Caller::User(AccessToken {
+            subject: "u1".to_string(),
+            realm: realm.to_string(),
+            tenant: tenant.map(|t| t.parse().unwrap()),
+        })
+    }
+
+    fn header(tenant: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(TENANT_HEADER, HeaderValue::from_static(tenant));
+        headers
+    }
+
+    #[test]
+    fn test_header_alone_names_the_tenant() {
+        assert_eq!(tenant_from_header(&HeaderMap::new()).unwrap(), None);
+        assert_eq!(
+            tenant_from_header(&header(ACME)).unwrap(),
+            Some(ACME.parse().unwrap())
+        );
+        assert!(matches!(
+            tenant_from_header(&header("acme")),
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_claim_names_the_tenant() {
+        let (keycloak, path) = keycloak();
+        let caller = user("shared", Some(GLOBEX));
+
+        assert_eq!(
+            caller_tenant(&caller, &HeaderMap::new(), &keycloak).unwrap(),
+            GLOBEX.parse().unwrap()
+        );
+        assert_eq!(
+            caller_tenant(&caller, &header(GLOBEX), &keycloak).unwrap(),
+            GLOBEX.parse().unwrap()
+        );
+        assert!(matches!(
+            caller_tenant(&caller, &header(INITECH), &keycloak),
+            Err(ApiError::Forbidden(_))
+        ));
+        // The claim has to name a tenant of the realm that signed the token
+        assert!(matches!(
+            caller_tenant(&user("shared", Some(ACME)), &HeaderMap::new(), &keycloak),
+            Err(ApiError::Forbidden(_))
+        ));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_token_without_claim_cannot_pick_a_tenant() {
+        let (keycloak, path) = keycloak();
+
+        // A realm of its own identifies the tenant
+        assert_eq!(
+            caller_tenant(&user("acme", None), &header(ACME), &keycloak).unwrap(),
+            ACME.parse().unwrap()
+        );
+        // A shared realm does not
+        assert!(matches!(
+            caller_tenant(&user("shared", None), &header(GLOBEX), &keycloak),
+            Err(ApiError::Forbidden(_))
+        ));
+        // Without a header the token reaches the default tenant, if its realm is the default's
+        assert_eq!(
+            caller_tenant(&user("master", None), &HeaderMap::new(), &keycloak).unwrap(),
+            TenantId::DEFAULT
+        );
+        assert!(matches!(
+            caller_tenant(&user("acme", None), &HeaderMap::new(), &keycloak),
+            Err(ApiError::Forbidden(_))
+        ));
+        assert!(matches!(
+            caller_tenant(&user("master", None), &header(ACME), &keycloak),
+            Err(ApiError::Forbidden(_))
+        ));
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -1,12 +1,13 @@
 use async_trait::async_trait;
 use axum::{http::StatusCode, response::IntoResponse};
+use chrono::{DateTime, Utc};
 use mockall::mock;
 use std::sync::Arc;
 use uuid::Uuid;
 
-use user_lib::entities::PaginationParams;
+use user_lib::entities::{PaginationParams, RoleGrant, TenantId, UserFilter, UserSuspension};
 use user_lib::repository::errors::UserRepositoryError;
-use user_lib::repository::models::{RoleRow, UserRoleMapping, UserRow};
+use user_lib::repository::models::{RoleRow, UserAttributesRow, UserRoleMapping, UserRow};
 use user_lib::repository::traits::{
     RoleRepositoryTrait, UserRepositoryTrait, UserRoleRepositoryTrait,
 };
@@ -19,12 +20,20 @@ mock! {
 
     #[async_trait]
     impl UserRepositoryTrait for UserRepo {
-        async fn create_user(&self, keycloak_id: &str) -> Result<UserRow, UserRepositoryError>;
-        async fn get_user(&self, user_id: Uuid) -> Result<Option<UserRow>, UserRepositoryError>;
-        async fn get_user_by_keycloak_id(&self, keycloak_id: &str) -> Result<Option<UserRow>, UserRepositoryError>;
-        async fn delete_user(&self, user_id: Uuid) -> Result<(), UserRepositoryError>;
-        async fn get_users_paginated(&self, pagination: PaginationParams) -> Result<(Vec<UserRow>, u64), UserRepositoryError>;
-        async fn get_users_by_role_paginated(&self, role_id: Uuid, pagination: PaginationParams) -> Result<(Vec<UserRow>, u64), UserRepositoryError>;
+        async fn create_user(&self, tenant: TenantId, keycloak_id: &str) -> Result<UserRow, UserRepositoryError>;
+        async fn get_user(&self, tenant: TenantId, user_id: Uuid) -> Result<Option<UserRow>, UserRepositoryError>;
+        async fn get_user_by_keycloak_id(&self, tenant: TenantId, keycloak_id: &str) -> Result<Option<UserRow>, UserRepositoryError>;
+        async fn get_user_tenant_by_keycloak_id(&self, keycloak_id: &str) -> Result<Option<TenantId>, UserRepositoryError>;
+        async fn delete_user(&self, tenant: TenantId, user_id: Uuid, expected_version: Option<i64>) -> Result<(), UserRepositoryError>;
+        async fn soft_delete_user(&self, tenant: TenantId, user_id: Uuid, expected_version: Option<i64>) -> Result<bool, UserRepositoryError>;
+        async fn restore_user(&self, tenant: TenantId, user_id: Uuid) -> Result<bool, UserRepositoryError>;
+        async fn touch_user(&self, tenant: TenantId, user_id: Uuid, expected_version: Option<i64>) -> Result<UserRow, UserRepositoryError>;
+        async fn set_user_suspension(&self, tenant: TenantId, user_id: Uuid, suspension: Option<UserSuspension>, expected_version: Option<i64>) -> Result<UserRow, UserRepositoryError>;
+        async fn record_user_login(&self, tenant: TenantId, keycloak_id: &str, at: DateTime<Utc>) -> Result<(), UserRepositoryError>;
+        async fn get_users_paginated(&self, tenant: TenantId, pagination: PaginationParams, filter: &UserFilter) -> Result<(Vec<UserRow>, u64), UserRepositoryError>;
+        async fn get_users_by_role_paginated(&self, tenant: TenantId, role_id: Uuid, pagination: PaginationParams) -> Result<(Vec<UserRow>, u64), UserRepositoryError>;
+        async fn get_user_attributes(&self, tenant: TenantId, user_ids: &[String]) -> Result<Vec<UserAttributesRow>, UserRepositoryError>;
+        async fn upsert_user_attributes(&self, tenant: TenantId, attributes: &UserAttributesRow) -> Result<(), UserRepositoryError>;
     }
 }
 
@@ -33,13 +42,17 @@ mock! {
 
     #[async_trait]
     impl RoleRepositoryTrait for RoleRepo {
-        async fn create_role(&self, name: &str) -> Result<RoleRow, UserRepositoryError>;
-        async fn get_role(&self, role_id: Uuid) -> Result<Option<RoleRow>, UserRepositoryError>;
-        async fn update_role(&self, role_id: Uuid, name: &str) -> Result<RoleRow, UserRepositoryError>;
-        async fn delete_role(&self, role_id: Uuid) -> Result<(), UserRepositoryError>;
-        async fn get_roles_for_user(&self, user_id: Uuid) -> Result<Vec<RoleRow>, UserRepositoryError>;
-        async fn get_roles_for_users(&self, user_ids: &[String]) -> Result<Vec<UserRoleMapping>, UserRepositoryError>;
-        async fn get_roles_paginated(&self, pagination: PaginationParams) -> Result<(Vec<RoleRow>, u64), UserRepositoryError>;
+        async fn create_role(&self, tenant: TenantId, name: &str, description: Option<String>) -> Result<RoleRow, UserRepositoryError>;
+        async fn get_role(&self, tenant: TenantId, role_id: Uuid) -> Result<Option<RoleRow>, UserRepositoryError>;
+        async fn get_system_role(&self, tenant: TenantId, name: &str) -> Result<Option<RoleRow>, UserRepositoryError>;
+        async fn update_role(&self, tenant: TenantId, role_id: Uuid, name: &str, description: Option<String>, expected_version: Option<i64>) -> Result<RoleRow, UserRepositoryError>;
+        async fn delete_role(&self, tenant: TenantId, role_id: Uuid, expected_version: Option<i64>) -> Result<(), UserRepositoryError>;
+        async fn soft_delete_role(&self, tenant: TenantId, role_id: Uuid, expected_version: Option<i64>) -> Result<bool, UserRepositoryError>;
+        async fn restore_role(&self, tenant: TenantId, role_id: Uuid) -> Result<bool, UserRepositoryError>;
+        async fn get_roles_for_user(&self, tenant: TenantId, user_id: Uuid) -> Result<Vec<RoleRow>, UserRepositoryError>;
+        async fn get_granted_roles_for_user(&self, tenant: TenantId, user_id: Uuid) -> Result<Vec<RoleRow>, UserRepositoryError>;
+        async fn get_roles_for_users(&self, tenant: TenantId, user_ids: &[String]) -> Result<Vec<UserRoleMapping>, UserRepositoryError>;
+        async fn get_roles_paginated(&self, tenant: TenantId, pagination: PaginationParams) -> Result<(Vec<RoleRow>, u64), UserRepositoryError>;
     }
 }
 
@@ -48,8 +61,10 @@ mock! {
 
     #[async_trait]
     impl UserRoleRepositoryTrait for UserRoleRepo {
-        async fn assign_role(&self, user_id: &str, role_id: &str) -> Result<(), UserRepositoryError>;
-        async fn unassign_role(&self, user_id: &str, role_id: &str) -> Result<(), UserRepositoryError>;
+        async fn assign_role(&self, tenant: TenantId, user_id: &str, role_id: &str, grant: &RoleGrant) -> Result<(), UserRepositoryError>;
+        async fn unassign_role(&self, tenant: TenantId, user_id: &str, role_id: &str) -> Result<(), UserRepositoryError>;
+        async fn get_users_with_expired_role_grants(&self, now: DateTime<Utc>) -> Result<Vec<(String, String)>, UserRepositoryError>;
+        async fn delete_expired_role_grants(&self, now: DateTime<Utc>) -> Result<Vec<(String, String)>, UserRepositoryError>;
     }
 }
 
@@ -79,18 +94,19 @@ async fn test_create_user_success() {
     let keycloak_id = "keycloak-123";
     user_repo
         .expect_create_user()
-        .withf(move |kc_id| kc_id == keycloak_id)
+        .withf(move |_, kc_id| kc_id == keycloak_id)
         .times(1)
-        .returning(move |kc_id| {
+        .returning(move |_, kc_id| {
             Ok(UserRow {
                 id: user_id.to_string(),
                 keycloak_id: kc_id.to_string(),
+                ..Default::default()
             })
         });
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
 
-    let result = service.create_user(keycloak_id).await;
+    let result = service.create_user(TenantId::DEFAULT, keycloak_id).await;
 
     assert!(result.is_ok());
     let user = result.unwrap();
@@ -103,6 +119,9 @@ async fn test_create_user_success() {
 #[tokio::test]
 async fn test_get_user_by_id_handler_success() {
     let mut user_repo = MockUserRepo::new();
+    user_repo
+        .expect_get_user_attributes()
+        .returning(|_, _| Ok(vec![]));
     let mut role_repo = MockRoleRepo::new();
     let user_role_repo = MockUserRoleRepo::new();
 
@@ -110,26 +129,28 @@ async fn test_get_user_by_id_handler_success() {
     let role_id = Uuid::new_v4();
     let keycloak_id = "keycloak-456";
 
-    user_repo.expect_get_user().times(1).returning(move |_| {
+    user_repo.expect_get_user().times(1).returning(move |_, _| {
         Ok(Some(UserRow {
             id: user_id.to_string(),
             keycloak_id: keycloak_id.to_string(),
+            ..Default::default()
         }))
     });
 
     role_repo
         .expect_get_roles_for_user()
         .times(1)
-        .returning(move |_| {
+        .returning(move |_, _| {
             Ok(vec![RoleRow {
                 id: role_id.to_string(),
                 name: "admin".to_string(),
+                ..Default::default()
             }])
         });
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
 
-    let result = service.get_user(user_id).await;
+    let result = service.get_user(TenantId::DEFAULT, user_id).await;
 
     assert!(result.is_ok());
     let user = result.unwrap().unwrap();
@@ -144,12 +165,15 @@ async fn test_get_user_by_id_handler_not_found() {
     let role_repo = MockRoleRepo::new();
     let user_role_repo = MockUserRoleRepo::new();
 
-    user_repo.expect_get_user().times(1).returning(|_| Ok(None));
+    user_repo
+        .expect_get_user()
+        .times(1)
+        .returning(|_, _| Ok(None));
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
     let user_id = Uuid::new_v4();
 
-    let result = service.get_user(user_id).await;
+    let result = service.get_user(TenantId::DEFAULT, user_id).await;
 
     assert!(result.is_ok());
     assert!(result.unwrap().is_none());
@@ -160,6 +184,9 @@ async fn test_get_user_by_id_handler_not_found() {
 #[tokio::test]
 async fn test_get_users_handler_success() {
     let mut user_repo = MockUserRepo::new();
+    user_repo
+        .expect_get_user_attributes()
+        .returning(|_, _| Ok(vec![]));
     let mut role_repo = MockRoleRepo::new();
     let user_role_repo = MockUserRoleRepo::new();
 
@@ -169,16 +196,18 @@ async fn test_get_users_handler_success() {
     user_repo
         .expect_get_users_paginated()
         .times(1)
-        .returning(move |_| {
+        .returning(move |_, _, _| {
             Ok((
                 vec![
                     UserRow {
                         id: user1_id.to_string(),
                         keycloak_id: "kc-user1".to_string(),
+                        ..Default::default()
                     },
                     UserRow {
                         id: user2_id.to_string(),
                         keycloak_id: "kc-user2".to_string(),
+                        ..Default::default()
                     },
                 ],
                 5,
@@ -188,7 +217,7 @@ async fn test_get_users_handler_success() {
     role_repo
         .expect_get_roles_for_users()
         .times(1)
-        .returning(|_| Ok(vec![]));
+        .returning(|_, _| Ok(vec![]));
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
     let pagination = PaginationParams {
@@ -196,7 +225,9 @@ async fn test_get_users_handler_success() {
         page_size: 2,
     };
 
-    let result = service.get_users(pagination).await;
+    let result = service
+        .get_users(TenantId::DEFAULT, pagination, &UserFilter::default())
+        .await;
 
     assert!(result.is_ok());
     let paginated = result.unwrap();
@@ -216,12 +247,14 @@ async fn test_get_users_handler_empty() {
     user_repo
         .expect_get_users_paginated()
         .times(1)
-        .returning(|_| Ok((vec![], 0)));
+        .returning(|_, _, _| Ok((vec![], 0)));
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
     let pagination = PaginationParams::default();
 
-    let result = service.get_users(pagination).await;
+    let result = service
+        .get_users(TenantId::DEFAULT, pagination, &UserFilter::default())
+        .await;
 
     assert!(result.is_ok());
     let paginated = result.unwrap();
@@ -240,12 +273,12 @@ async fn test_delete_user_handler_success() {
     user_repo
         .expect_delete_user()
         .times(1)
-        .returning(|_| Ok(()));
+        .returning(|_, _, _| Ok(()));
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
     let user_id = Uuid::new_v4();
 
-    let result = service.delete_user(user_id).await;
+    let result = service.delete_user(TenantId::DEFAULT, user_id, None).await;
 
     assert!(result.is_ok());
 }
@@ -262,18 +295,19 @@ async fn test_create_role_handler_success() {
 
     role_repo
         .expect_create_role()
-        .withf(|name| name == "admin")
+        .withf(|_, name, _| name == "admin")
         .times(1)
-        .returning(move |name| {
+        .returning(move |_, name, _| {
             Ok(RoleRow {
                 id: role_id.to_string(),
                 name: name.to_string(),
+                ..Default::default()
             })
         });
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
 
-    let result = service.create_role("admin").await;
+    let result = service.create_role(TenantId::DEFAULT, "admin", None).await;
 
     assert!(result.is_ok());
     let role = result.unwrap();
@@ -290,11 +324,11 @@ async fn test_create_role_handler_name_conflict() {
     role_repo
         .expect_create_role()
         .times(1)
-        .returning(|_| Err(UserRepositoryError::RoleNameAlreadyExists));
+        .returning(|_, _, _| Err(UserRepositoryError::RoleNameAlreadyExists));
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
 
-    let result = service.create_role("admin").await;
+    let result = service.create_role(TenantId::DEFAULT, "admin", None).await;
 
     assert!(result.is_err());
     assert!(matches!(
@@ -311,7 +345,7 @@ async fn test_create_role_handler_validation_error() {
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
 
-    let result = service.create_role("").await;
+    let result = service.create_role(TenantId::DEFAULT, "", None).await;
 
     assert!(result.is_err());
     assert!(matches!(
@@ -330,16 +364,17 @@ async fn test_get_role_by_id_handler_success() {
 
     let role_id = Uuid::new_v4();
 
-    role_repo.expect_get_role().times(1).returning(move |_| {
+    role_repo.expect_get_role().times(1).returning(move |_, _| {
         Ok(Some(RoleRow {
             id: role_id.to_string(),
             name: "editor".to_string(),
+            ..Default::default()
         }))
     });
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
 
-    let result = service.get_role(role_id).await;
+    let result = service.get_role(TenantId::DEFAULT, role_id).await;
 
     assert!(result.is_ok());
     let role = result.unwrap().unwrap();
@@ -353,12 +388,15 @@ async fn test_get_role_by_id_handler_not_found() {
     let mut role_repo = MockRoleRepo::new();
     let user_role_repo = MockUserRoleRepo::new();
 
-    role_repo.expect_get_role().times(1).returning(|_| Ok(None));
+    role_repo
+        .expect_get_role()
+        .times(1)
+        .returning(|_, _| Ok(None));
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
     let role_id = Uuid::new_v4();
 
-    let result = service.get_role(role_id).await;
+    let result = service.get_role(TenantId::DEFAULT, role_id).await;
 
     assert!(result.is_ok());
     assert!(result.unwrap().is_none());
@@ -378,16 +416,18 @@ async fn test_get_roles_handler_success() {
     role_repo
         .expect_get_roles_paginated()
         .times(1)
-        .returning(move |_| {
+        .returning(move |_, _| {
             Ok((
                 vec![
                     RoleRow {
                         id: role1_id.to_string(),
                         name: "admin".to_string(),
+                        ..Default::default()
                     },
                     RoleRow {
                         id: role2_id.to_string(),
                         name: "editor".to_string(),
+                        ..Default::default()
                     },
                 ],
                 2,
@@ -397,7 +437,7 @@ async fn test_get_roles_handler_success() {
     let service = create_test_service(user_repo, role_repo, user_role_repo);
     let pagination = PaginationParams::default();
 
-    let result = service.get_roles(pagination).await;
+    let result = service.get_roles(TenantId::DEFAULT, pagination).await;
 
     assert!(result.is_ok());
     let paginated = result.unwrap();
@@ -415,19 +455,29 @@ async fn test_update_role_handler_success() {
 
     let role_id = Uuid::new_v4();
 
+    role_repo.expect_get_role().returning(move |_, id| {
+        Ok(Some(RoleRow {
+            id: id.to_string(),
+            name: "editor".to_string(),
+            ..Default::default()
+        }))
+    });
     role_repo
         .expect_update_role()
         .times(1)
-        .returning(move |_, name| {
+        .returning(move |_, _, name, _, _| {
             Ok(RoleRow {
                 id: role_id.to_string(),
                 name: name.to_string(),
+                ..Default::default()
             })
         });
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
 
-    let result = service.update_role(role_id, "super-admin").await;
+    let result = service
+        .update_role(TenantId::DEFAULT, role_id, "super-admin", None, None)
+        .await;
 
     assert!(result.is_ok());
     let role = result.unwrap();
@@ -440,15 +490,24 @@ async fn test_update_role_handler_name_conflict() {
     let mut role_repo = MockRoleRepo::new();
     let user_role_repo = MockUserRoleRepo::new();
 
+    role_repo.expect_get_role().returning(move |_, id| {
+        Ok(Some(RoleRow {
+            id: id.to_string(),
+            name: "editor".to_string(),
+            ..Default::default()
+        }))
+    });
     role_repo
         .expect_update_role()
         .times(1)
-        .returning(|_, _| Err(UserRepositoryError::RoleNameAlreadyExists));
+        .returning(|_, _, _, _, _| Err(UserRepositoryError::RoleNameAlreadyExists));
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
     let role_id = Uuid::new_v4();
 
-    let result = service.update_role(role_id, "taken-name").await;
+    let result = service
+        .update_role(TenantId::DEFAULT, role_id, "taken-name", None, None)
+        .await;
 
     assert!(result.is_err());
     assert!(matches!(
@@ -466,7 +525,9 @@ async fn test_update_role_handler_validation_error() {
     let service = create_test_service(user_repo, role_repo, user_role_repo);
     let role_id = Uuid::new_v4();
 
-    let result = service.update_role(role_id, "   ").await;
+    let result = service
+        .update_role(TenantId::DEFAULT, role_id, "   ", None, None)
+        .await;
 
     assert!(result.is_err());
     assert!(matches!(
@@ -483,19 +544,88 @@ async fn test_delete_role_handler_success() {
     let mut role_repo = MockRoleRepo::new();
     let user_role_repo = MockUserRoleRepo::new();
 
+    role_repo.expect_get_role().returning(move |_, id| {
+        Ok(Some(RoleRow {
+            id: id.to_string(),
+            name: "editor".to_string(),
+            ..Default::default()
+        }))
+    });
     role_repo
         .expect_delete_role()
         .times(1)
-        .returning(|_| Ok(()));
+        .returning(|_, _, _| Ok(()));
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
     let role_id = Uuid::new_v4();
 
-    let result = service.delete_role(role_id).await;
+    let result = service.delete_role(TenantId::DEFAULT, role_id, None).await;
 
     assert!(result.is_ok());
 }
 
+#[tokio::test]
+async fn test_delete_role_handler_stale_version() {
+    let user_repo = MockUserRepo::new();
+    let mut role_repo = MockRoleRepo::new();
+    let user_role_repo = MockUserRoleRepo::new();
+    let role_id = Uuid::new_v4();
+
+    role_repo.expect_get_role().times(1).returning(move |_, _| {
+        Ok(Some(RoleRow {
+            id: role_id.to_string(),
+            name: "admin".to_string(),
+            version: 3,
+            ..Default::default()
+        }))
+    });
+    // The version check is part of the delete itself, not a read before it
+    role_repo
+        .expect_delete_role()
+        .withf(|_, _, expected| *expected == Some(2))
+        .times(1)
+        .returning(|_, _, _| Err(UserRepositoryError::VersionConflict));
+
+    let service = create_test_service(user_repo, role_repo, user_role_repo);
+
+    let err = service
+        .delete_role(TenantId::DEFAULT, role_id, Some(2))
+        .await
+        .unwrap_err();
+    let response = user_api::error::ApiError::from(err).into_response();
+
+    assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+}
+
+#[tokio::test]
+async fn test_delete_role_handler_system_role_is_forbidden() {
+    let user_repo = MockUserRepo::new();
+    let mut role_repo = MockRoleRepo::new();
+    let user_role_repo = MockUserRoleRepo::new();
+    let role_id = Uuid::new_v4();
+
+    role_repo.expect_get_role().times(1).returning(move |_, _| {
+        Ok(Some(RoleRow {
+            id: role_id.to_string(),
+            name: "admin".to_string(),
+            is_system: true,
+            ..Default::default()
+        }))
+    });
+    role_repo.expect_delete_role().never();
+    role_repo.expect_soft_delete_role().never();
+
+    let service = create_test_service(user_repo, role_repo, user_role_repo);
+
+    let err = service
+        .delete_role(TenantId::DEFAULT, role_id, None)
+        .await
+        .unwrap_err();
+    let response = user_api::error::ApiError::from(err).into_response();
+
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
 // ==================== ASSIGN ROLE HANDLER TESTS ====================
 
 #[tokio::test]
@@ -507,13 +637,15 @@ async fn test_assign_role_handler_success() {
     user_role_repo
         .expect_assign_role()
         .times(1)
-        .returning(|_, _| Ok(()));
+        .returning(|_, _, _, _| Ok(()));
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
     let user_id = Uuid::new_v4();
     let role_id = Uuid::new_v4();
 
-    let result = service.assign_role(user_id, role_id).await;
+    let result = service
+        .assign_role(TenantId::DEFAULT, user_id, role_id, &RoleGrant::default())
+        .await;
 
     assert!(result.is_ok());
 }
@@ -527,13 +659,15 @@ async fn test_assign_role_handler_already_assigned() {
     user_role_repo
         .expect_assign_role()
         .times(1)
-        .returning(|_, _| Err(UserRepositoryError::UserAlreadyHasRole));
+        .returning(|_, _, _, _| Err(UserRepositoryError::UserAlreadyHasRole));
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
     let user_id = Uuid::new_v4();
     let role_id = Uuid::new_v4();
 
-    let result = service.assign_role(user_id, role_id).await;
+    let result = service
+        .assign_role(TenantId::DEFAULT, user_id, role_id, &RoleGrant::default())
+        .await;
 
     assert!(result.is_err());
     assert!(matches!(
@@ -553,13 +687,15 @@ async fn test_unassign_role_handler_success() {
     user_role_repo
         .expect_unassign_role()
         .times(1)
-        .returning(|_, _| Ok(()));
+        .returning(|_, _, _| Ok(()));
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
     let user_id = Uuid::new_v4();
     let role_id = Uuid::new_v4();
 
-    let result = service.unassign_role(user_id, role_id).await;
+    let result = service
+        .unassign_role(TenantId::DEFAULT, user_id, role_id)
+        .await;
 
     assert!(result.is_ok());
 }
@@ -596,6 +732,16 @@ async fn test_api_error_conflict() {
     assert_eq!(response.status(), StatusCode::CONFLICT);
 }
 
+#[tokio::test]
+async fn test_api_error_precondition_required() {
+    use user_api::error::ApiError;
+
+    let error = ApiError::PreconditionRequired("If-Match header is required".to_string());
+    let response = error.into_response();
+
+    assert_eq!(response.status(), StatusCode::PRECONDITION_REQUIRED);
+}
+
 #[tokio::test]
 async fn test_api_error_internal() {
     use user_api::error::ApiError;
@@ -751,7 +897,9 @@ async fn test_user_entity_structure() {
         roles: vec![Role {
             id: role_id,
             name: "admin".to_string(),
+            ..Default::default()
         }],
+        ..Default::default()
     };
 
     assert_eq!(user.id, user_id);
@@ -769,6 +917,7 @@ async fn test_role_entity_structure() {
     let role = Role {
         id: role_id,
         name: "editor".to_string(),
+        ..Default::default()
     };
 
     assert_eq!(role.id, role_id);
@@ -777,6 +926,25 @@ async fn test_role_entity_structure() {
 
 // ==================== INPUT VALIDATION TESTS ====================
 
+#[tokio::test]
+async fn test_update_user_attributes_request_tells_null_from_missing() {
+    use user_api::methods::entities::UpdateUserAttributesRequest;
+    use user_lib::entities::UserAttributesPatch;
+
+    let request: UpdateUserAttributesRequest =
+        serde_json::from_str(r#"{"department": "Sales", "locale": null}"#).unwrap();
+    let patch = UserAttributesPatch::from(request);
+
+    assert_eq!(patch.department, Some(Some("Sales".to_string())));
+    assert_eq!(patch.locale, Some(None));
+    assert_eq!(patch.cost_center, None);
+    assert_eq!(patch.metadata, None);
+
+    // Typos are rejected rather than silently ignored, and metadata must be an object
+    assert!(serde_json::from_str::<UpdateUserAttributesRequest>(r#"{"departmnt": "x"}"#).is_err());
+    assert!(serde_json::from_str::<UpdateUserAttributesRequest>(r#"{"metadata": [1]}"#).is_err());
+}
+
 #[tokio::test]
 async fn test_create_user_request_valid_email() {
     use user_api::methods::entities::CreateUserRequest;
@@ -872,6 +1040,7 @@ async fn test_create_role_request_valid() {
 
     let request = CreateRoleRequest {
         name: "admin".to_string(),
+        description: None,
     };
 
     let result = request.validate();
@@ -885,6 +1054,7 @@ async fn test_create_role_request_empty_name() {
 
     let request = CreateRoleRequest {
         name: "".to_string(),
+        description: None,
     };
 
     let result = request.validate();
@@ -898,7 +1068,10 @@ async fn test_create_role_request_name_too_long() {
 
     let long_name = "a".repeat(51);
 
-    let request = CreateRoleRequest { name: long_name };
+    let request = CreateRoleRequest {
+        name: long_name,
+        description: None,
+    };
 
     let result = request.validate();
     assert!(
@@ -938,3 +1111,1952 @@ async fn test_validation_error_to_api_error() {
         "Validation errors should return 400 Bad Request"
     );
 }
+
+// ==================== CACHED SERVICE (IN-MEMORY BACKEND) TESTS ====================
+
+fn create_cached_service(
+    user_repo: MockUserRepo,
+    role_repo: MockRoleRepo,
+    user_role_repo: MockUserRoleRepo,
+) -> user_api::cache::CachedUserService<MockUserRepo, MockRoleRepo, MockUserRoleRepo> {
+    use user_api::cache::{Cache, CacheConfig, CachedUserService, InMemoryCache};
+
+    let cache = Cache::new(Arc::new(InMemoryCache::new(100)));
+    CachedUserService::new(
+        Arc::new(create_test_service(user_repo, role_repo, user_role_repo)),
+        cache,
+        CacheConfig::from_env(),
+    )
+}
+
+#[tokio::test]
+async fn test_cached_get_user_hits_repository_once() {
+    let mut user_repo = MockUserRepo::new();
+    user_repo
+        .expect_get_user_attributes()
+        .returning(|_, _| Ok(vec![]));
+    let mut role_repo = MockRoleRepo::new();
+    let user_id = Uuid::new_v4();
+
+    user_repo.expect_get_user().times(1).returning(move |_, _| {
+        Ok(Some(UserRow {
+            id: user_id.to_string(),
+            keycloak_id: "keycloak-cached".to_string(),
+            ..Default::default()
+        }))
+    });
+    role_repo
+        .expect_get_roles_for_user()
+        .times(1)
+        .returning(|_, _| Ok(vec![]));
+
+    let service = create_cached_service(user_repo, role_repo, MockUserRoleRepo::new());
+
+    let first = service
+        .get_user(TenantId::DEFAULT, user_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let second = service
+        .get_user(TenantId::DEFAULT, user_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(first.keycloak_id, second.keycloak_id);
+}
+
+#[tokio::test]
+async fn test_cached_missing_role_is_remembered_until_created() {
+    let mut role_repo = MockRoleRepo::new();
+    let role_id = Uuid::new_v4();
+    let role_row = move || RoleRow {
+        id: role_id.to_string(),
+        name: "editor".to_string(),
+        ..Default::default()
+    };
+    let mut seq = mockall::Sequence::new();
+
+    role_repo
+        .expect_get_role()
+        .times(1)
+        .in_sequence(&mut seq)
+        .returning(|_, _| Ok(None));
+    role_repo
+        .expect_create_role()
+        .times(1)
+        .in_sequence(&mut seq)
+        .returning(move |_, _, _| Ok(role_row()));
+    role_repo
+        .expect_get_role()
+        .times(1)
+        .in_sequence(&mut seq)
+        .returning(move |_, _| Ok(Some(role_row())));
+
+    let service = create_cached_service(MockUserRepo::new(), role_repo, MockUserRoleRepo::new());
+
+    assert!(service
+        .get_role(TenantId::DEFAULT, role_id)
+        .await
+        .unwrap()
+        .is_none());
+    // Served from the negative entry, so the repository is not queried again
+    assert!(service
+        .get_role(TenantId::DEFAULT, role_id)
+        .await
+        .unwrap()
+        .is_none());
+
+    // Creating the role evicts the negative entry
+    service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+    let role = service
+        .get_role(TenantId::DEFAULT, role_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(role.name, "editor");
+}
+
+#[tokio::test]
+async fn test_assign_role_handler_expired_grant_is_bad_request() {
+    let user_repo = MockUserRepo::new();
+    let role_repo = MockRoleRepo::new();
+    let mut user_role_repo = MockUserRoleRepo::new();
+    user_role_repo.expect_assign_role().never();
+
+    let service = create_test_service(user_repo, role_repo, user_role_repo);
+    let grant = RoleGrant {
+        expires_at: Some(Utc::now() - chrono::Duration::seconds(1)),
+        granted_by: None,
+    };
+
+    let err = service
+        .assign_role(TenantId::DEFAULT, Uuid::new_v4(), Uuid::new_v4(), &grant)
+        .await
+        .unwrap_err();
+    let response = user_api::error::ApiError::from(err).into_response();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_assign_role_request_rejects_unknown_fields() {
+    use user_api::methods::entities::AssignRoleRequest;
+
+    let request: AssignRoleRequest =
+        serde_json::from_str(r#"{"expires_at": "2030-01-01T00:00:00Z", "granted_by": "ops"}"#)
+            .unwrap();
+    let grant = RoleGrant::from(request);
+    assert_eq!(grant.granted_by.as_deref(), Some("ops"));
+    assert!(grant.expires_at.is_some());
+
+    assert!(serde_json::from_str::<AssignRoleRequest>(r#"{"expires": "2030-01-01"}"#).is_err());
+}
+
+// ==================== KEYCLOAK ROLE SYNC TESTS ====================
+
+/// Integrated service with role sync on and a Keycloak client without credentials,
+/// so every Keycloak call fails with `NotConfigured`
+fn create_role_sync_service(
+    user_repo: MockUserRepo,
+    role_repo: MockRoleRepo,
+    user_role_repo: MockUserRoleRepo,
+) -> user_api::services::IntegratedUserService<MockUserRepo, MockRoleRepo, MockUserRoleRepo> {
+    use user_api::cache::{Cache, CacheConfig, CachedUserService};
+    use user_api::keycloak::{KeycloakClient, KeycloakConfig};
+
+    let cached = CachedUserService::new(
+        Arc::new(create_test_service(user_repo, role_repo, user_role_repo)),
+        Cache::disabled(),
+        CacheConfig::from_env(),
+    );
+    let keycloak = KeycloakClient::new(KeycloakConfig {
+        base_url: "http://keycloak:8080".to_string(),
+        realm: "master".to_string(),
+        client_id: "user-api-service".to_string(),
+        client_secret: String::new(),
+        profile_cache_ttl: std::time::Duration::from_secs(300),
+    });
+    user_api::services::IntegratedUserService::new(
+        Arc::new(cached),
+        Arc::new(keycloak),
+        Cache::disabled(),
+    )
+    .with_role_sync(true)
+}
+
+#[tokio::test]
+async fn test_role_sync_create_role_stops_when_keycloak_fails() {
+    let mut role_repo = MockRoleRepo::new();
+    role_repo.expect_create_role().times(0);
+
+    let service = create_role_sync_service(MockUserRepo::new(), role_repo, MockUserRoleRepo::new());
+
+    let result = service.create_role(TenantId::DEFAULT, "editor", None).await;
+    assert!(matches!(
+        result,
+        Err(
+            user_api::services::integrated_user_service::IntegratedServiceError::Keycloak(
+                user_api::keycloak::KeycloakError::NotConfigured
+            )
+        )
+    ));
+}
+
+#[tokio::test]
+async fn test_role_sync_assign_role_is_taken_back_when_keycloak_fails() {
+    let user_id = Uuid::new_v4();
+    let role_id = Uuid::new_v4();
+
+    let mut user_repo = MockUserRepo::new();
+    user_repo.expect_get_user().returning(move |_, _| {
+        Ok(Some(UserRow {
+            id: user_id.to_string(),
+            keycloak_id: "keycloak-sync".to_string(),
+            ..Default::default()
+        }))
+    });
+    user_repo
+        .expect_get_user_attributes()
+        .returning(|_, _| Ok(vec![]));
+    let mut role_repo = MockRoleRepo::new();
+    role_repo
+        .expect_get_roles_for_user()
+        .returning(|_, _| Ok(vec![]));
+    role_repo.expect_get_role().returning(move |_, _| {
+        Ok(Some(RoleRow {
+            id: role_id.to_string(),
+            name: "editor".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            version: 1,
+            deleted_at: None,
+            description: None,
+            is_system: false,
+        }))
+    });
+    let mut user_role_repo = MockUserRoleRepo::new();
+    user_role_repo
+        .expect_assign_role()
+        .times(1)
+        .returning(|_, _, _, _| Ok(()));
+    user_role_repo
+        .expect_unassign_role()
+        .withf(move |_, u, r| u == user_id.to_string() && r == role_id.to_string())
+        .times(1)
+        .returning(|_, _, _| Ok(()));
+
+    let service = create_role_sync_service(user_repo, role_repo, user_role_repo);
+
+    let result = service
+        .assign_role(TenantId::DEFAULT, user_id, role_id, &RoleGrant::default())
+        .await;
+    assert!(result.is_err());
+}
+
+// ==================== FAKE KEYCLOAK ====================
+
+/// Keycloak stand-in on a local port. Users are kept as JSON and updated by `PUT`s,
+/// realm roles and groups are created by `POST`s, users' realm role mappings are added
+/// and removed, every admin request is logged as `METHOD path`, and requests listed in
+/// `failing` (same form) are answered with 500.
+#[derive(Clone, Default)]
+struct FakeKeycloak {
+    users: Arc<std::sync::Mutex<std::collections::HashMap<String, serde_json::Value>>>,
+    roles: Arc<std::sync::Mutex<std::collections::HashSet<String>>>,
+    /// realm role names mapped directly to each user, keyed by Keycloak ID
+    user_role_mappings: Arc<
+        std::sync::Mutex<std::collections::HashMap<String, std::collections::BTreeSet<String>>>,
+    >,
+    /// group names keyed by group id
+    groups: Arc<std::sync::Mutex<std::collections::HashMap<String, String>>>,
+    requests: Arc<std::sync::Mutex<Vec<String>>>,
+    failing: Arc<std::sync::Mutex<std::collections::HashSet<String>>>,
+}
+
+impl FakeKeycloak {
+    const REALM_PATH: &'static str = "/admin/realms/master";
+
+    fn add_user(&self, keycloak_id: &str, enabled: bool) {
+        self.users.lock().unwrap().insert(
+            keycloak_id.to_string(),
+            serde_json::json!({
+                "id": keycloak_id,
+                "username": format!("{keycloak_id}@example.com"),
+                "email": format!("{keycloak_id}@example.com"),
+                "enabled": enabled,
+            }),
+        );
+    }
+
+    fn user_enabled(&self, keycloak_id: &str) -> bool {
+        self.users.lock().unwrap()[keycloak_id]["enabled"] == true
+    }
+
+    /// Names of the groups that exist, sorted
+    fn group_names(&self) -> Vec<String> {
+        let mut names: Vec<_> = self.groups.lock().unwrap().values().cloned().collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Make `METHOD path` (path below the realm's admin URL) answer 500
+    fn fail(&self, method: &str, path: &str) {
+        self.failing
+            .lock()
+            .unwrap()
+            .insert(format!("{method} {}{path}", Self::REALM_PATH));
+    }
+
+    fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Serve on a free local port; returns the base URL
+    async fn start(&self) -> String {
+        use axum::{extract::State, http::Method, http::Uri, Router};
+
+        async fn handle(
+            State(fake): State<FakeKeycloak>,
+            method: Method,
+            uri: Uri,
+            body: axum::body::Bytes,
+        ) -> axum::response::Response {
+            let path = uri.path().to_string();
+            if path.ends_with("/protocol/openid-connect/certs") {
+                return axum::Json(serde_json::json!({ "keys": [signing_jwk()] })).into_response();
+            }
+            if path.ends_with("/protocol/openid-connect/token") {
+                return axum::Json(serde_json::json!({
+                    "access_token": "fake-token",
+                    "expires_in": 300,
+                    "token_type": "Bearer",
+                }))
+                .into_response();
+            }
+            let request = format!("{method} {path}");
+            fake.requests.lock().unwrap().push(request.clone());
+            if fake.failing.lock().unwrap().contains(&request) {
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+
+            let roles_path = format!("{}/roles", FakeKeycloak::REALM_PATH);
+            if path == roles_path && method == Method::POST {
+                let role: serde_json::Value = serde_json::from_slice(&body).unwrap_or_default();
+                let name = role["name"].as_str().unwrap_or_default().to_string();
+                return match fake.roles.lock().unwrap().insert(name) {
+                    true => StatusCode::CREATED.into_response(),
+                    false => StatusCode::CONFLICT.into_response(),
+                };
+            }
+            if let Some(name) = path.strip_prefix(&format!("{roles_path}/")) {
+                let mut roles = fake.roles.lock().unwrap();
+                match method {
+                    Method::GET => {
+                        return match roles.contains(name) {
+                            true => axum::Json(
+                                serde_json::json!({"id": format!("role-{name}"), "name": name}),
+                            )
+                            .into_response(),
+                            false => StatusCode::NOT_FOUND.into_response(),
+                        };
+                    }
+                    Method::PUT => {
+                        let role: serde_json::Value =
+                            serde_json::from_slice(&body).unwrap_or_default();
+                        if !roles.remove(name) {
+                            return StatusCode::NOT_FOUND.into_response();
+                        }
+                        roles.insert(role["name"].as_str().unwrap_or_default().to_string());
+                        return StatusCode::NO_CONTENT.into_response();
+                    }
+                    Method::DELETE => {
+                        roles.remove(name);
+                        return StatusCode::NO_CONTENT.into_response();
+                    }
+                    _ => {}
+                }
+            }
+
+            let groups_path = format!("{}/groups", FakeKeycloak::REALM_PATH);
+            let group: serde_json::Value = serde_json::from_slice(&body).unwrap_or_default();
+            let group_name = group["name"].as_str().unwrap_or_default().to_string();
+            if path == groups_path && method == Method::POST {
+                let id = Uuid::new_v4().to_string();
+                fake.groups.lock().unwrap().insert(id.clone(), group_name);
+                let location = format!("{groups_path}/{id}");
+                return (StatusCode::CREATED, [("location", location)]).into_response();
+            }
+            if let Some(id) = path
+                .strip_prefix(&format!("{groups_path}/"))
+                .filter(|rest| !rest.contains('/'))
+            {
+                let mut groups = fake.groups.lock().unwrap();
+                match method {
+                    Method::PUT => {
+                        groups.insert(id.to_string(), group_name);
+                    }
+                    Method::DELETE => {
+                        groups.remove(id);
+                    }
+                    _ => {}
+                }
+                return StatusCode::NO_CONTENT.into_response();
+            }
+
+            let user_path = format!("{}/users/", FakeKeycloak::REALM_PATH);
+            if let Some(id) = path
+                .strip_prefix(&user_path)
+                .and_then(|rest| rest.strip_suffix("/role-mappings/realm"))
+            {
+                let roles: Vec<serde_json::Value> =
+                    serde_json::from_slice(&body).unwrap_or_default();
+                let names = roles.iter().filter_map(|r| r["name"].as_str());
+                let mut mappings = fake.user_role_mappings.lock().unwrap();
+                let mapped = mappings.entry(id.to_string()).or_default();
+                match method {
+                    Method::GET => {
+                        let roles: Vec<_> = mapped
+                            .iter()
+                            .map(|name| serde_json::json!({"id": format!("role-{name}"), "name": name}))
+                            .collect();
+                        return axum::Json(roles).into_response();
+                    }
+                    Method::POST => mapped.extend(names.map(str::to_string)),
+                    Method::DELETE => names.for_each(|name| {
+                        mapped.remove(name);
+                    }),
+                    _ => {}
+                }
+                return StatusCode::NO_CONTENT.into_response();
+            }
+            let user_id = path
+                .strip_prefix(&user_path)
+                .filter(|rest| !rest.contains('/'));
+            match (method, user_id) {
+                (Method::GET, Some(id)) => match fake.users.lock().unwrap().get(id) {
+                    Some(user) => axum::Json(user.clone()).into_response(),
+                    None => StatusCode::NOT_FOUND.into_response(),
+                },
+                (Method::PUT, Some(id)) => {
+                    let changes: serde_json::Value =
+                        serde_json::from_slice(&body).unwrap_or_default();
+                    let mut users = fake.users.lock().unwrap();
+                    let Some(user) = users.get_mut(id) else {
+                        return StatusCode::NOT_FOUND.into_response();
+                    };
+                    for (key, value) in changes.as_object().into_iter().flatten() {
+                        user[key] = value.clone();
+                    }
+                    StatusCode::NO_CONTENT.into_response()
+                }
+                (Method::GET, None) => axum::Json(serde_json::json!([])).into_response(),
+                // Role mappings answer 204; creating anything else answers 201
+                (Method::POST, None) if !path.ends_with("/role-mappings/realm") => {
+                    StatusCode::CREATED.into_response()
+                }
+                _ => StatusCode::NO_CONTENT.into_response(),
+            }
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let app = Router::new().fallback(handle).with_state(self.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{address}")
+    }
+}
+
+/// Integrated service talking to `fake`, with role sync off
+async fn create_keycloak_service(
+    fake: &FakeKeycloak,
+    user_repo: MockUserRepo,
+    role_repo: MockRoleRepo,
+    user_role_repo: MockUserRoleRepo,
+) -> user_api::services::IntegratedUserService<MockUserRepo, MockRoleRepo, MockUserRoleRepo> {
+    keycloak_service(
+        fake,
+        create_test_service(user_repo, role_repo, user_role_repo),
+    )
+    .await
+}
+
+/// Integrated service over `service` talking to `fake`, with role sync off
+/// Key ID of the fake realm's signing key
+const SIGNING_KEY_ID: &str = "fake-realm-key";
+
+/// Public half of `fixtures/token_signing_key.pem`, as the fake realm publishes it
+fn signing_jwk() -> serde_json::Value {
+    serde_json::json!({
+        "kid": SIGNING_KEY_ID,
+        "kty": "RSA",
+        "alg": "RS256",
+        "use": "sig",
+        "n": "t0TWVRjmMs3SJ7TchRNQTLAKmqnHY0ezLk9-WNSmWMVmRJoH2kswRk9P-A34sXWd6Ys6a1JdIRPcb8kDq_Q7evfkSZLU07a40gYX0MQZHTn-Ku6-2YNf0gqirgJQZH2Ypk-IOuNgvP0iHpJj4Wgd96coy1AjT5j1s5bEvmH1ZR_Psf4Hi1EL88vqWHenbei3nWZjnhVHqUfZj3TmFWWLKgeSd4AIQSZEAQAB3A2sDw9u_MJV_K8EXcVWA1czC7d9uebdkH-xsRw3fGt7NsipN5REA1HhJalqzhxZeVnefQ1iBVzUAO-DvYeQ3INQKrLYC__D08hE40Oavmo3U_grbw",
+        "e": "AQAB",
+    })
+}
+
+/// Access token of the fake `realm`, signed with the fixture key under `kid`
+fn access_token(realm: &str, kid: &str, claims: serde_json::Value) -> String {
+    use jsonwebtoken::{Algorithm, EncodingKey, Header};
+
+    let mut payload = serde_json::json!({
+        "iss": format!("http://keycloak:8080/realms/{realm}"),
+        "sub": "kc-user-1",
+        "exp": Utc::now().timestamp() + 300,
+    });
+    payload
+        .as_object_mut()
+        .unwrap()
+        .extend(claims.as_object().unwrap().clone());
+    let mut header = Header::new(Algorithm::RS256);
+    header.kid = Some(kid.to_string());
+    let key = EncodingKey::from_rsa_pem(include_bytes!("fixtures/token_signing_key.pem")).unwrap();
+    jsonwebtoken::encode(&header, &payload, &key).unwrap()
+}
+
+async fn keycloak_service<U, R, UR>(
+    fake: &FakeKeycloak,
+    service: UserService<U, R, UR>,
+) -> user_api::services::IntegratedUserService<U, R, UR>
+where
+    U: UserRepositoryTrait + Send + Sync + 'static,
+    R: RoleRepositoryTrait + Send + Sync + 'static,
+    UR: UserRoleRepositoryTrait + Send + Sync + 'static,
+{
+    use user_api::cache::{Cache, CacheConfig, CachedUserService};
+    use user_api::keycloak::{KeycloakClient, KeycloakConfig};
+
+    let cached = CachedUserService::new(
+        Arc::new(service),
+        Cache::disabled(),
+        CacheConfig::from_env(),
+    );
+    let keycloak = KeycloakClient::new(KeycloakConfig {
+        base_url: fake.start().await,
+        realm: "master".to_string(),
+        client_id: "user-api-service".to_string(),
+        client_secret: "fake-secret".to_string(),
+        profile_cache_ttl: std::time::Duration::from_secs(300),
+    });
+    user_api::services::IntegratedUserService::new(
+        Arc::new(cached),
+        Arc::new(keycloak),
+        Cache::disabled(),
+    )
+}
+
+// ==================== CREDENTIAL TESTS ====================
+
+#[tokio::test]
+async fn test_set_user_password_request_validation() {
+    use user_api::methods::entities::SetUserPasswordRequest;
+    use validator::Validate;
+
+    let request: SetUserPasswordRequest =
+        serde_json::from_str(r#"{"password": "s3cret!", "temporary": true}"#).unwrap();
+    assert!(request.temporary);
+    assert!(request.validate().is_ok());
+
+    let empty: SetUserPasswordRequest = serde_json::from_str(r#"{"password": ""}"#).unwrap();
+    assert!(!empty.temporary);
+    let errors = empty.validate().unwrap_err();
+    assert!(errors.field_errors().contains_key("password"));
+
+    assert!(serde_json::from_str::<SetUserPasswordRequest>(r#"{"pasword": "x"}"#).is_err());
+}
+
+#[tokio::test]
+async fn test_execute_user_actions_request_validation() {
+    use user_api::keycloak::RequiredAction;
+    use user_api::methods::entities::ExecuteUserActionsRequest;
+    use validator::Validate;
+
+    let request: ExecuteUserActionsRequest =
+        serde_json::from_str(r#"{"actions": ["UPDATE_PASSWORD", "CONFIGURE_TOTP"]}"#).unwrap();
+    assert_eq!(
+        request.actions,
+        vec![
+            RequiredAction::UpdatePassword,
+            RequiredAction::ConfigureTotp
+        ]
+    );
+    assert!(request.validate().is_ok());
+
+    let empty: ExecuteUserActionsRequest = serde_json::from_str(r#"{"actions": []}"#).unwrap();
+    assert!(empty.validate().is_err());
+
+    let short: ExecuteUserActionsRequest =
+        serde_json::from_str(r#"{"actions": ["VERIFY_EMAIL"], "lifespan_secs": 5}"#).unwrap();
+    assert!(short.validate().is_err());
+
+    assert!(serde_json::from_str::<ExecuteUserActionsRequest>(
+        r#"{"actions": ["DELETE_ACCOUNT"]}"#
+    )
+    .is_err());
+}
+
+#[tokio::test]
+async fn test_set_user_password_for_missing_user_is_not_found() {
+    let mut user_repo = MockUserRepo::new();
+    user_repo.expect_get_user().returning(|_, _| Ok(None));
+
+    let service = create_role_sync_service(user_repo, MockRoleRepo::new(), MockUserRoleRepo::new());
+
+    let password = secrecy::Secret::new("s3cret!".to_string());
+    let result = service
+        .set_user_password(TenantId::DEFAULT, Uuid::new_v4(), &password, false)
+        .await;
+    assert!(matches!(
+        result,
+        Err(
+            user_api::services::integrated_user_service::IntegratedServiceError::User(
+                user_lib::errors_service::UserServiceError::NotFound
+            )
+        )
+    ));
+}
+
+// ==================== SUSPENSION TESTS ====================
+
+#[tokio::test]
+async fn test_suspend_user_is_not_recorded_when_keycloak_fails() {
+    let user_id = Uuid::new_v4();
+
+    let mut user_repo = MockUserRepo::new();
+    user_repo.expect_get_user().returning(move |_, _| {
+        Ok(Some(UserRow {
+            id: user_id.to_string(),
+            keycloak_id: "keycloak-suspend".to_string(),
+            version: 3,
+            ..Default::default()
+        }))
+    });
+    user_repo
+        .expect_get_user_attributes()
+        .returning(|_, _| Ok(vec![]));
+    user_repo.expect_set_user_suspension().times(0);
+    let mut role_repo = MockRoleRepo::new();
+    role_repo
+        .expect_get_roles_for_user()
+        .returning(|_, _| Ok(vec![]));
+
+    let service = create_role_sync_service(user_repo, role_repo, MockUserRoleRepo::new());
+
+    // A stale version is rejected before Keycloak is called
+    let result = service
+        .suspend_user(TenantId::DEFAULT, user_id, None, false, Some(2))
+        .await;
+    assert!(matches!(
+        result,
+        Err(
+            user_api::services::integrated_user_service::IntegratedServiceError::User(
+                user_lib::errors_service::UserServiceError::VersionConflict
+            )
+        )
+    ));
+
+    let result = service
+        .suspend_user(TenantId::DEFAULT, user_id, Some("audit"), true, Some(3))
+        .await;
+    assert!(matches!(
+        result,
+        Err(
+            user_api::services::integrated_user_service::IntegratedServiceError::Keycloak(
+                user_api::keycloak::KeycloakError::NotConfigured
+            )
+        )
+    ));
+}
+
+/// User repository whose only user is `user_id`, linked to `keycloak_id`
+fn single_user_repo(user_id: Uuid, keycloak_id: &'static str) -> MockUserRepo {
+    let mut user_repo = MockUserRepo::new();
+    user_repo.expect_get_user().returning(move |_, _| {
+        Ok(Some(UserRow {
+            id: user_id.to_string(),
+            keycloak_id: keycloak_id.to_string(),
+            version: 3,
+            ..Default::default()
+        }))
+    });
+    user_repo
+        .expect_get_user_attributes()
+        .returning(|_, _| Ok(vec![]));
+    user_repo
+}
+
+fn no_roles_repo() -> MockRoleRepo {
+    let mut role_repo = MockRoleRepo::new();
+    role_repo
+        .expect_get_roles_for_user()
+        .returning(|_, _| Ok(vec![]));
+    role_repo
+}
+
+#[tokio::test]
+async fn test_failed_suspension_restores_the_previous_keycloak_state() {
+    let user_id = Uuid::new_v4();
+    let mut user_repo = single_user_repo(user_id, "kc-suspended");
+    user_repo
+        .expect_set_user_suspension()
+        .times(2)
+        .returning(|_, _, _, _| Err(UserRepositoryError::VersionConflict));
+
+    let fake = FakeKeycloak::default();
+    // Already suspended: disabled in Keycloak
+    fake.add_user("kc-suspended", false);
+    let service =
+        create_keycloak_service(&fake, user_repo, no_roles_repo(), MockUserRoleRepo::new()).await;
+
+    let result = service
+        .suspend_user(TenantId::DEFAULT, user_id, Some("again"), false, None)
+        .await;
+    assert!(result.is_err());
+    // The rollback must not enable an account that was disabled before
+    assert!(!fake.user_enabled("kc-suspended"));
+
+    fake.add_user("kc-suspended", true);
+    let result = service
+        .reactivate_user(TenantId::DEFAULT, user_id, None)
+        .await;
+    assert!(result.is_err());
+    assert!(fake.user_enabled("kc-suspended"));
+}
+
+#[tokio::test]
+async fn test_failed_logout_does_not_undo_the_suspension() {
+    let user_id = Uuid::new_v4();
+    let mut user_repo = single_user_repo(user_id, "kc-compromised");
+    user_repo
+        .expect_set_user_suspension()
+        .times(1)
+        .returning(move |_, _, suspension, _| {
+            Ok(UserRow {
+                id: user_id.to_string(),
+                keycloak_id: "kc-compromised".to_string(),
+                version: 4,
+                suspended_at: suspension.as_ref().map(|s| s.suspended_at),
+                suspension_reason: suspension.and_then(|s| s.reason),
+                ..Default::default()
+            })
+        });
+
+    let fake = FakeKeycloak::default();
+    fake.add_user("kc-compromised", true);
+    fake.fail("POST", "/users/kc-compromised/logout");
+    let service =
+        create_keycloak_service(&fake, user_repo, no_roles_repo(), MockUserRoleRepo::new()).await;
+
+    let user = service
+        .suspend_user(TenantId::DEFAULT, user_id, Some("leaked"), true, None)
+        .await
+        .unwrap();
+    assert!(user.suspension.is_some());
+    assert!(!fake.user_enabled("kc-compromised"));
+    assert!(fake
+        .requests()
+        .contains(&"POST /admin/realms/master/users/kc-compromised/logout".to_string()));
+}
+
+#[tokio::test]
+async fn test_failed_keycloak_enable_deletes_the_restored_user_again() {
+    let user_id = Uuid::new_v4();
+    let mut user_repo = single_user_repo(user_id, "kc-restored");
+    user_repo
+        .expect_restore_user()
+        .times(1)
+        .returning(|_, _| Ok(true));
+    user_repo
+        .expect_soft_delete_user()
+        .withf(move |_, id, version| *id == user_id && *version == Some(3))
+        .times(1)
+        .returning(|_, _, _| Ok(true));
+
+    let fake = FakeKeycloak::default();
+    fake.add_user("kc-restored", false);
+    fake.fail("PUT", "/users/kc-restored");
+    let service =
+        create_keycloak_service(&fake, user_repo, no_roles_repo(), MockUserRoleRepo::new()).await;
+
+    assert!(service
+        .restore_user(TenantId::DEFAULT, user_id)
+        .await
+        .is_err());
+    assert!(!fake.user_enabled("kc-restored"));
+}
+
+#[tokio::test]
+async fn test_expired_grants_are_kept_until_keycloak_mappings_are_removed() {
+    let user_id = Uuid::new_v4();
+    let mut user_role_repo = MockUserRoleRepo::new();
+    user_role_repo
+        .expect_get_users_with_expired_role_grants()
+        .times(2)
+        .returning(move |_| Ok(vec![(TenantId::DEFAULT.to_string(), user_id.to_string())]));
+    // Only the second sweep, after Keycloak succeeded, deletes the grants
+    user_role_repo
+        .expect_delete_expired_role_grants()
+        .times(1)
+        .returning(move |_| Ok(vec![(TenantId::DEFAULT.to_string(), user_id.to_string())]));
+
+    let fake = FakeKeycloak::default();
+    fake.fail("GET", "/users/kc-expired/role-mappings/realm");
+    let service = create_keycloak_service(
+        &fake,
+        single_user_repo(user_id, "kc-expired"),
+        no_roles_repo(),
+        user_role_repo,
+    )
+    .await
+    .with_role_sync(true);
+
+    assert!(service.remove_expired_role_grants().await.is_err());
+
+    fake.failing.lock().unwrap().clear();
+    assert_eq!(service.remove_expired_role_grants().await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_expired_grants_lose_their_mapping_even_when_a_group_gives_the_role() {
+    use user_lib::repository::traits::UserRoleRepositoryTrait as _;
+    use user_lib::repository::InMemoryStore;
+
+    let store = InMemoryStore::new();
+    let (users, roles, user_roles) = store.repositories();
+    let inner = UserService::with_repos(Arc::new(users), Arc::new(roles), Arc::new(user_roles))
+        .with_group_repository(Arc::new(store.group_repository()));
+    let alice = inner
+        .create_user(TenantId::DEFAULT, "kc-alice")
+        .await
+        .unwrap();
+    let editor = inner
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+    let group = inner
+        .create_group(TenantId::DEFAULT, "kc-group", "Editors")
+        .await
+        .unwrap();
+    inner
+        .add_group_member(TenantId::DEFAULT, group.id, alice.id)
+        .await
+        .unwrap();
+    inner
+        .assign_group_role(TenantId::DEFAULT, group.id, editor.id)
+        .await
+        .unwrap();
+    // A direct grant that has run out, written past the service's check on `expires_at`
+    let (_, _, user_roles) = store.repositories();
+    user_roles
+        .assign_role(
+            TenantId::DEFAULT,
+            &alice.id.to_string(),
+            &editor.id.to_string(),
+            &RoleGrant {
+                expires_at: Some(Utc::now() - chrono::Duration::minutes(1)),
+                granted_by: None,
+            },
+        )
+        .await
+        .unwrap();
+
+    let fake = FakeKeycloak::default();
+    fake.user_role_mappings
+        .lock()
+        .unwrap()
+        .insert("kc-alice".to_string(), ["editor".to_string()].into());
+    let service = keycloak_service(&fake, inner).await.with_role_sync(true);
+
+    assert_eq!(service.remove_expired_role_grants().await.unwrap(), 1);
+    // The group keeps giving the role; the user's own mapping is gone
+    assert!(fake.user_role_mappings.lock().unwrap()["kc-alice"].is_empty());
+    let roles = service
+        .get_user(TenantId::DEFAULT, alice.id)
+        .await
+        .unwrap()
+        .unwrap()
+        .roles;
+    assert_eq!(roles.len(), 1);
+    assert_eq!(roles[0].name, "editor");
+}
+
+#[tokio::test]
+async fn test_group_roles_are_mapped_to_the_keycloak_group() {
+    use user_lib::repository::{InMemoryGroupRepository, InMemoryStore};
+
+    let store = InMemoryStore::new();
+    let (users, roles, user_roles) = store.repositories();
+    let inner = UserService::with_repos(Arc::new(users), Arc::new(roles), Arc::new(user_roles))
+        .with_group_repository(Arc::new(InMemoryGroupRepository::new(store)));
+    let editor = inner
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+    let group = inner
+        .create_group(TenantId::DEFAULT, "kc-group", "Editors")
+        .await
+        .unwrap();
+
+    let fake = FakeKeycloak::default();
+    let service = keycloak_service(&fake, inner).await.with_role_sync(true);
+    let mappings = "/admin/realms/master/groups/kc-group/role-mappings/realm";
+
+    // A failed mapping detaches the role again
+    fake.fail("POST", "/groups/kc-group/role-mappings/realm");
+    assert!(service
+        .assign_group_role(TenantId::DEFAULT, group.id, editor.id)
+        .await
+        .is_err());
+    let stored = service
+        .get_group(TenantId::DEFAULT, group.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(stored.roles.is_empty());
+
+    fake.failing.lock().unwrap().clear();
+    service
+        .assign_group_role(TenantId::DEFAULT, group.id, editor.id)
+        .await
+        .unwrap();
+    // The realm role did not exist yet, so it was created
+    assert!(fake.roles.lock().unwrap().contains("editor"));
+    assert_eq!(
+        fake.requests()
+            .iter()
+            .filter(|r| *r == &format!("POST {mappings}"))
+            .count(),
+        2
+    );
+
+    service
+        .unassign_group_role(TenantId::DEFAULT, group.id, editor.id)
+        .await
+        .unwrap();
+    assert!(fake.requests().contains(&format!("DELETE {mappings}")));
+}
+
+#[tokio::test]
+async fn test_tenants_sharing_a_realm_keep_their_realm_roles_apart() {
+    use user_lib::repository::traits::TenantRepositoryTrait;
+    use user_lib::repository::InMemoryStore;
+
+    let store = InMemoryStore::new();
+    let tenants = store.tenant_repository();
+    let acme: TenantId = tenants
+        .create_tenant("acme", "Acme")
+        .await
+        .unwrap()
+        .id
+        .parse()
+        .unwrap();
+    let globex: TenantId = tenants
+        .create_tenant("globex", "Globex")
+        .await
+        .unwrap()
+        .id
+        .parse()
+        .unwrap();
+    let (users, roles, user_roles) = store.repositories();
+    let inner = UserService::with_repos(Arc::new(users), Arc::new(roles), Arc::new(user_roles))
+        .with_tenant_repository(Arc::new(tenants));
+
+    // Without a realms file every tenant lives in the one realm
+    let fake = FakeKeycloak::default();
+    let service = keycloak_service(&fake, inner).await.with_role_sync(true);
+    let realm_roles = || {
+        let mut names: Vec<_> = fake.roles.lock().unwrap().iter().cloned().collect();
+        names.sort_unstable();
+        names
+    };
+
+    service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+    let acme_editor = service.create_role(acme, "editor", None).await.unwrap();
+    let globex_editor = service.create_role(globex, "editor", None).await.unwrap();
+    assert_eq!(realm_roles(), ["acme:editor", "editor", "globex:editor"]);
+
+    // Renaming or deleting one tenant's role leaves the others' realm roles alone
+    service
+        .update_role(acme, acme_editor.id, "writer", None, None)
+        .await
+        .unwrap();
+    assert_eq!(realm_roles(), ["acme:writer", "editor", "globex:editor"]);
+    service
+        .delete_role(globex, globex_editor.id, None)
+        .await
+        .unwrap();
+    assert_eq!(realm_roles(), ["acme:writer", "editor"]);
+
+    // A name that could pass for another tenant's realm role is refused
+    assert!(matches!(
+        service
+            .create_role(TenantId::DEFAULT, "acme:writer", None)
+            .await,
+        Err(
+            user_api::services::integrated_user_service::IntegratedServiceError::User(
+                user_lib::errors_service::UserServiceError::Validation(_)
+            )
+        )
+    ));
+}
+
+#[tokio::test]
+async fn test_user_response_shows_suspension_only_when_suspended() {
+    use user_api::keycloak::FullUser;
+    use user_api::methods::entities::UserResponse;
+
+    let mut user = FullUser {
+        id: Uuid::new_v4(),
+        keycloak_id: "keycloak-1".to_string(),
+        name: "Alice".to_string(),
+        email: None,
+        roles: vec![],
+        attributes: Default::default(),
+        email_verified: true,
+        enabled: true,
+        suspension: None,
+        last_login_at: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        version: 1,
+    };
+    let json = serde_json::to_value(UserResponse::from(user.clone())).unwrap();
+    assert!(json.get("suspended_at").is_none());
+    assert!(json.get("suspension_reason").is_none());
+
+    user.enabled = false;
+    user.suspension = Some(UserSuspension {
+        suspended_at: Utc::now(),
+        reason: Some("left the company".to_string()),
+    });
+    let json = serde_json::to_value(UserResponse::from(user)).unwrap();
+    assert_eq!(json["enabled"], false);
+    assert!(json["suspended_at"].is_string());
+    assert_eq!(json["suspension_reason"], "left the company");
+}
+
+// ==================== EMAIL CHANGE TESTS ====================
+
+#[tokio::test]
+async fn test_update_user_request_validates_email() {
+    use user_api::methods::entities::UpdateUserRequest;
+    use validator::Validate;
+
+    let request: UpdateUserRequest =
+        serde_json::from_str(r#"{"email": "new@example.com"}"#).unwrap();
+    assert!(request.validate().is_ok());
+
+    let request: UpdateUserRequest = serde_json::from_str(r#"{"email": "not-an-email"}"#).unwrap();
+    let errors = request.validate().unwrap_err();
+    assert!(errors.field_errors().contains_key("email"));
+
+    let request: UpdateUserRequest = serde_json::from_str(r#"{"first_name": "Ann"}"#).unwrap();
+    assert!(request.email.is_none());
+    assert!(request.validate().is_ok());
+}
+
+#[tokio::test]
+async fn test_email_change_with_stale_version_touches_nothing() {
+    use user_api::services::integrated_user_service::UpdateUserRequest;
+
+    let user_id = Uuid::new_v4();
+
+    let mut user_repo = MockUserRepo::new();
+    user_repo.expect_get_user().returning(move |_, _| {
+        Ok(Some(UserRow {
+            id: user_id.to_string(),
+            keycloak_id: "keycloak-email".to_string(),
+            version: 5,
+            ..Default::default()
+        }))
+    });
+    user_repo
+        .expect_get_user_attributes()
+        .returning(|_, _| Ok(vec![]));
+    user_repo.expect_touch_user().times(0);
+    let mut role_repo = MockRoleRepo::new();
+    role_repo
+        .expect_get_roles_for_user()
+        .returning(|_, _| Ok(vec![]));
+
+    let service = create_role_sync_service(user_repo, role_repo, MockUserRoleRepo::new());
+
+    let request = UpdateUserRequest {
+        first_name: None,
+        last_name: None,
+        email: Some("new@example.com".to_string()),
+    };
+    let result = service
+        .update_user(TenantId::DEFAULT, user_id, request, Some(4))
+        .await;
+    assert!(matches!(
+        result,
+        Err(
+            user_api::services::integrated_user_service::IntegratedServiceError::User(
+                user_lib::errors_service::UserServiceError::VersionConflict
+            )
+        )
+    ));
+}
+
+#[tokio::test]
+async fn test_email_already_exists_is_conflict() {
+    let err = user_api::keycloak::KeycloakError::EmailAlreadyExists("taken@example.com".into());
+    let response = user_api::error::ApiError::from(err).into_response();
+
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+// ==================== SESSION TESTS ====================
+
+#[tokio::test]
+async fn test_delete_session_of_missing_user_is_not_found() {
+    let mut user_repo = MockUserRepo::new();
+    user_repo.expect_get_user().returning(|_, _| Ok(None));
+
+    let service = create_role_sync_service(user_repo, MockRoleRepo::new(), MockUserRoleRepo::new());
+
+    let result = service
+        .delete_user_session(TenantId::DEFAULT, Uuid::new_v4(), "session-1")
+        .await;
+    assert!(matches!(
+        result,
+        Err(
+            user_api::services::integrated_user_service::IntegratedServiceError::User(
+                user_lib::errors_service::UserServiceError::NotFound
+            )
+        )
+    ));
+}
+
+#[test]
+fn test_user_session_response_converts_keycloak_session() {
+    use user_api::keycloak::KeycloakUserSession;
+    use user_api::methods::entities::UserSessionResponse;
+
+    let session = KeycloakUserSession {
+        id: "session-1".to_string(),
+        ip_address: Some("10.0.0.7".to_string()),
+        start: 1_700_000_000_000,
+        last_access: 1_700_000_060_000,
+        clients: [
+            ("c2".to_string(), "web-app".to_string()),
+            ("c1".to_string(), "admin-console".to_string()),
+        ]
+        .into_iter()
+        .collect(),
+    };
+
+    let response = UserSessionResponse::from(session);
+    assert_eq!(response.id, "session-1");
+    assert_eq!(response.ip_address.as_deref(), Some("10.0.0.7"));
+    assert_eq!(response.started_at.timestamp(), 1_700_000_000);
+    assert_eq!(response.last_access_at.timestamp(), 1_700_000_060);
+    assert_eq!(response.clients, vec!["admin-console", "web-app"]);
+}
+
+#[test]
+fn test_session_not_found_maps_to_404() {
+    let err = user_api::keycloak::KeycloakError::SessionNotFound("session-1".into());
+    let response = user_api::error::ApiError::from(err).into_response();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+// ==================== KEYCLOAK EVENT TESTS ====================
+
+#[tokio::test]
+async fn test_user_deleted_in_keycloak_is_purged_despite_soft_delete() {
+    use user_api::cache::{Cache, CacheConfig, CachedUserService};
+    use user_api::keycloak::{KeycloakClient, KeycloakConfig};
+
+    let user_id = Uuid::new_v4();
+    let mut user_repo = MockUserRepo::new();
+    user_repo
+        .expect_get_user_by_keycloak_id()
+        .returning(move |_, keycloak_id| {
+            Ok((keycloak_id == "kc-gone").then(|| UserRow {
+                id: user_id.to_string(),
+                keycloak_id: keycloak_id.to_string(),
+                ..Default::default()
+            }))
+        });
+    user_repo
+        .expect_get_user_attributes()
+        .returning(|_, _| Ok(vec![]));
+    user_repo.expect_soft_delete_user().times(0);
+    user_repo
+        .expect_delete_user()
+        .withf(move |_, id, _| *id == user_id)
+        .times(1)
+        .returning(|_, _, _| Ok(()));
+    let mut role_repo = MockRoleRepo::new();
+    role_repo
+        .expect_get_roles_for_user()
+        .returning(|_, _| Ok(vec![]));
+
+    let cached = CachedUserService::new(
+        Arc::new(
+            create_test_service(user_repo, role_repo, MockUserRoleRepo::new())
+                .with_soft_delete(true),
+        ),
+        Cache::disabled(),
+        CacheConfig::from_env(),
+    );
+    let keycloak = KeycloakClient::new(KeycloakConfig {
+        base_url: "http://keycloak:8080".to_string(),
+        realm: "master".to_string(),
+        client_id: "user-api-service".to_string(),
+        client_secret: String::new(),
+        profile_cache_ttl: std::time::Duration::from_secs(300),
+    });
+    let service = user_api::services::IntegratedUserService::new(
+        Arc::new(cached),
+        Arc::new(keycloak),
+        Cache::disabled(),
+    );
+
+    assert!(service
+        .remove_user_deleted_in_keycloak(TenantId::DEFAULT, "kc-gone")
+        .await
+        .unwrap());
+    assert!(!service
+        .remove_user_deleted_in_keycloak(TenantId::DEFAULT, "kc-unknown")
+        .await
+        .unwrap());
+}
+
+#[tokio::test]
+async fn test_keycloak_profile_change_clears_a_cached_missing_profile() {
+    use user_api::cache::{Cache, CacheConfig, CachedUserService, InMemoryCache};
+    use user_api::keycloak::{KeycloakClient, KeycloakConfig};
+
+    let user_id = Uuid::new_v4();
+    let fake = FakeKeycloak::default();
+    let cached = CachedUserService::new(
+        Arc::new(create_test_service(
+            single_user_repo(user_id, "kc-late"),
+            no_roles_repo(),
+            MockUserRoleRepo::new(),
+        )),
+        Cache::disabled(),
+        CacheConfig::from_env(),
+    );
+    let keycloak = KeycloakClient::new(KeycloakConfig {
+        base_url: fake.start().await,
+        realm: "master".to_string(),
+        client_id: "user-api-service".to_string(),
+        client_secret: "fake-secret".to_string(),
+        profile_cache_ttl: std::time::Duration::from_secs(300),
+    });
+    let service = user_api::services::IntegratedUserService::new(
+        Arc::new(cached),
+        Arc::new(keycloak),
+        Cache::new(Arc::new(InMemoryCache::new(100))),
+    );
+
+    // No Keycloak account yet: the missing profile is remembered
+    let user = service.get_user(TenantId::DEFAULT, user_id).await.unwrap();
+    assert_eq!(user.unwrap().email, None);
+    fake.add_user("kc-late", true);
+    let user = service.get_user(TenantId::DEFAULT, user_id).await.unwrap();
+    assert_eq!(user.unwrap().email, None);
+
+    // The profile event drops the negative entry
+    service
+        .apply_keycloak_profile_change(TenantId::DEFAULT, "kc-late")
+        .await;
+    let user = service.get_user(TenantId::DEFAULT, user_id).await.unwrap();
+    assert_eq!(user.unwrap().email.as_deref(), Some("kc-late@example.com"));
+
+    let profile_reads = fake
+        .requests()
+        .iter()
+        .filter(|r| r.as_str() == "GET /admin/realms/master/users/kc-late")
+        .count();
+    assert_eq!(profile_reads, 2);
+}
+
+#[tokio::test]
+async fn test_login_of_unknown_user_is_ignored() {
+    let mut user_repo = MockUserRepo::new();
+    user_repo
+        .expect_get_user_by_keycloak_id()
+        .returning(|_, _| Ok(None));
+    user_repo.expect_record_user_login().times(0);
+
+    let service = create_role_sync_service(user_repo, MockRoleRepo::new(), MockUserRoleRepo::new());
+
+    service
+        .record_user_login(TenantId::DEFAULT, "kc-unknown", Utc::now())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_realm_reload_flushes_cached_keycloak_profiles() {
+    use user_api::cache::keys::keycloak_profile_key;
+    use user_api::cache::{Cache, CacheConfig, CacheLookup, CachedUserService, InMemoryCache};
+    use user_api::keycloak::{KeycloakClient, KeycloakConfig};
+
+    let acme: TenantId = "6f1c1d52-3a4e-4c43-9a0b-2f8f1b3c9d10".parse().unwrap();
+    let path = std::env::temp_dir().join(format!("realms-{}.json", Uuid::new_v4()));
+    std::fs::write(&path, format!(r#"{{"{acme}": {{"realm": "acme"}}}}"#)).unwrap();
+
+    let cache = Cache::new(Arc::new(InMemoryCache::new(100)));
+    let cached = CachedUserService::new(
+        Arc::new(create_test_service(
+            MockUserRepo::new(),
+            MockRoleRepo::new(),
+            MockUserRoleRepo::new(),
+        )),
+        cache.clone(),
+        CacheConfig::from_env(),
+    );
+    let keycloak = KeycloakClient::new(KeycloakConfig {
+        base_url: "http://keycloak:8080".to_string(),
+        realm: "master".to_string(),
+        client_id: "user-api-service".to_string(),
+        client_secret: String::new(),
+        profile_cache_ttl: std::time::Duration::from_secs(300),
+    })
+    .with_realms_file(path.clone())
+    .unwrap();
+    let service = user_api::services::IntegratedUserService::new(
+        Arc::new(cached),
+        Arc::new(keycloak),
+        cache.clone(),
+    );
+    let key = keycloak_profile_key(acme, "kc-1");
+    let cached_profile = || async {
+        matches!(
+            cache.lookup::<serde_json::Value>(&key).await,
+            CacheLookup::Negative
+        )
+    };
+
+    cache
+        .set_negative(&key, std::time::Duration::from_secs(300))
+        .await;
+    // An unchanged file keeps the cached profiles
+    service.reload_keycloak_realms().await.unwrap();
+    assert!(cached_profile().await);
+
+    std::fs::write(&path, format!(r#"{{"{acme}": {{"realm": "acme-2"}}}}"#)).unwrap();
+    let realms = service.reload_keycloak_realms().await.unwrap();
+    assert!(realms.contains(&(acme, "acme-2".to_string())));
+    assert!(!cached_profile().await);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_user_response_includes_last_login_when_known() {
+    use user_api::keycloak::FullUser;
+    use user_api::methods::entities::UserResponse;
+
+    let mut user = FullUser {
+        id: Uuid::new_v4(),
+        keycloak_id: "keycloak-1".to_string(),
+        name: "Alice".to_string(),
+        email: None,
+        roles: vec![],
+        attributes: Default::default(),
+        email_verified: true,
+        enabled: true,
+        suspension: None,
+        last_login_at: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        version: 1,
+    };
+    let json = serde_json::to_value(UserResponse::from(user.clone())).unwrap();
+    assert!(json.get("last_login_at").is_none());
+
+    user.last_login_at = Some(Utc::now());
+    let json = serde_json::to_value(UserResponse::from(user)).unwrap();
+    assert!(json["last_login_at"].is_string());
+}
+
+// ==================== API KEY TESTS ====================
+
+fn create_api_key_service(
+) -> user_api::services::IntegratedUserService<MockUserRepo, MockRoleRepo, MockUserRoleRepo> {
+    use user_api::cache::{Cache, CacheConfig, CachedUserService};
+    use user_api::keycloak::{KeycloakClient, KeycloakConfig};
+    use user_lib::repository::InMemoryApiKeyRepository;
+
+    let service = create_test_service(
+        MockUserRepo::new(),
+        MockRoleRepo::new(),
+        MockUserRoleRepo::new(),
+    )
+    .with_api_key_repository(Arc::new(InMemoryApiKeyRepository::new()));
+    let cached = CachedUserService::new(
+        Arc::new(service),
+        Cache::disabled(),
+        CacheConfig::from_env(),
+    );
+    let keycloak = KeycloakClient::new(KeycloakConfig {
+        base_url: "http://keycloak:8080".to_string(),
+        realm: "master".to_string(),
+        client_id: "user-api-service".to_string(),
+        client_secret: String::new(),
+        profile_cache_ttl: std::time::Duration::from_secs(300),
+    });
+    user_api::services::IntegratedUserService::new(
+        Arc::new(cached),
+        Arc::new(keycloak),
+        Cache::disabled(),
+    )
+}
+
+#[tokio::test]
+async fn test_rotated_api_key_replaces_the_old_one() {
+    use secrecy::ExposeSecret;
+    use user_api::methods::entities::IssuedApiKeyResponse;
+    use user_lib::entities::NewApiKey;
+
+    let service = create_api_key_service();
+    let new_key = NewApiKey {
+        name: "nightly-sync".to_string(),
+        scopes: vec!["users:read".to_string()],
+        expires_at: None,
+    };
+
+    let issued = service
+        .create_api_key(TenantId::DEFAULT, &new_key)
+        .await
+        .unwrap();
+    let old_key = issued.key.expose_secret().clone();
+    let key_id = issued.api_key.id;
+
+    let json = serde_json::to_value(IssuedApiKeyResponse::from(issued)).unwrap();
+    assert_eq!(json["key"], old_key.as_str());
+    assert!(old_key.starts_with(json["prefix"].as_str().unwrap()));
+    assert_eq!(json["active"], true);
+    assert!(json.get("last_used_at").is_none());
+
+    let authenticated = service.authenticate_api_key(&old_key).await.unwrap();
+    assert!(authenticated.unwrap().last_used_at.is_some());
+
+    let rotated = service
+        .rotate_api_key(TenantId::DEFAULT, key_id)
+        .await
+        .unwrap();
+    assert_eq!(rotated.api_key.id, key_id);
+    assert!(service
+        .authenticate_api_key(&old_key)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(service
+        .authenticate_api_key(rotated.key.expose_secret())
+        .await
+        .unwrap()
+        .is_some());
+    // Keys used within the last minute keep their recorded time
+    let listed = service.get_api_keys(TenantId::DEFAULT).await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert!(listed[0].last_used_at.is_some());
+}
+
+#[tokio::test]
+async fn test_v1_request_without_credentials_is_unauthorized() {
+    use axum::{body::Body, http::Request, middleware::from_fn_with_state, routing::get, Router};
+    use secrecy::ExposeSecret;
+    use tower::ServiceExt;
+    use user_api::api_key::API_KEY_HEADER;
+    use user_api::middleware::api_key_auth::api_key_auth_middleware;
+    use user_api::state::AppState;
+    use user_lib::entities::NewApiKey;
+
+    let service = Arc::new(create_api_key_service());
+    let new_key = NewApiKey {
+        name: "nightly-sync".to_string(),
+        scopes: vec!["users:read".to_string()],
+        expires_at: None,
+    };
+    let issued = service
+        .create_api_key(TenantId::DEFAULT, &new_key)
+        .await
+        .unwrap();
+    let key = issued.key.expose_secret().clone();
+
+    let state = AppState {
+        user_service: service,
+        env: "local".to_string(),
+    };
+    let router = Router::new()
+        .route("/users", get(|| async { "ok" }).post(|| async { "ok" }))
+        .route_layer(from_fn_with_state(state.clone(), api_key_auth_middleware))
+        .with_state(state);
+    let send = |method: &str, headers: &[(&str, &str)]| {
+        let mut request = Request::builder().method(method).uri("/users");
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        router.clone().oneshot(request.body(Body::empty()).unwrap())
+    };
+
+    // Dropping the key must not lift its scopes
+    let response = send("POST", &[]).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = send("POST", &[(API_KEY_HEADER, key.as_str())])
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = send("GET", &[(API_KEY_HEADER, key.as_str())])
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    // A bearer token is only accepted once its signature is checked
+    let response = send("POST", &[("authorization", "Bearer user-token")])
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_v1_bearer_tokens_are_verified_and_keep_their_tenant() {
+    use axum::{body::Body, http::Request, middleware::from_fn_with_state, routing::get, Router};
+    use tower::ServiceExt;
+    use user_api::middleware::api_key_auth::api_key_auth_middleware;
+
+    let fake = FakeKeycloak::default();
+    let state = group_state(&fake, in_memory_group_service()).await;
+    let router = Router::new()
+        .route(
+            "/users",
+            get(|request: Request<Body>| async move {
+                request.headers()["x-tenant-id"]
+                    .to_str()
+                    .unwrap()
+                    .to_string()
+            }),
+        )
+        .route_layer(from_fn_with_state(state.clone(), api_key_auth_middleware))
+        .with_state(state);
+    let send = |token: String, tenant: Option<String>| {
+        let mut request = Request::builder()
+            .uri("/users")
+            .header("authorization", format!("Bearer {token}"));
+        if let Some(tenant) = tenant {
+            request = request.header("x-tenant-id", tenant);
+        }
+        let router = router.clone();
+        async move {
+            let response = router
+                .oneshot(request.body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let status = response.status();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            (status, String::from_utf8(body.to_vec()).unwrap())
+        }
+    };
+    let acme = TenantId(Uuid::new_v4());
+
+    // The claim names the tenant, and the header may only repeat it
+    let claimed = access_token(
+        "master",
+        SIGNING_KEY_ID,
+        serde_json::json!({ "tenant_id": acme.to_string() }),
+    );
+    assert_eq!(
+        send(claimed.clone(), None).await,
+        (StatusCode::OK, acme.to_string())
+    );
+    let (status, _) = send(claimed, Some(TenantId::DEFAULT.to_string())).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Without the claim, a token of the shared realm cannot pick a tenant
+    let unclaimed = access_token("master", SIGNING_KEY_ID, serde_json::json!({}));
+    assert_eq!(
+        send(unclaimed.clone(), None).await,
+        (StatusCode::OK, TenantId::DEFAULT.to_string())
+    );
+    let (status, _) = send(unclaimed.clone(), Some(acme.to_string())).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Tampered, foreign, unknown-key and expired tokens are refused
+    let (head, rest) = unclaimed.split_once('.').unwrap();
+    let (_, signature) = rest.split_once('.').unwrap();
+    let forged_claims = base64::Engine::encode(
+        &base64::engine::general_purpose::URL_SAFE_NO_PAD,
+        serde_json::json!({
+            "iss": "http://keycloak:8080/realms/master",
+            "sub": "kc-user-1",
+            "exp": Utc::now().timestamp() + 300,
+            "tenant_id": acme.to_string(),
+        })
+        .to_string(),
+    );
+    for token in [
+        format!("{head}.{forged_claims}.{signature}"),
+        access_token("elsewhere", SIGNING_KEY_ID, serde_json::json!({})),
+        access_token("master", "rotated-away", serde_json::json!({})),
+        access_token(
+            "master",
+            SIGNING_KEY_ID,
+            serde_json::json!({ "exp": Utc::now().timestamp() - 3600 }),
+        ),
+    ] {
+        let (status, _) = send(token, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+}
+
+#[test]
+fn test_create_api_key_request_rejects_unknown_scopes() {
+    use user_api::methods::entities::CreateApiKeyRequest;
+    use validator::Validate;
+
+    let request: CreateApiKeyRequest = serde_json::from_value(serde_json::json!({
+        "name": "batch",
+        "scopes": ["users:read", "groups:write"]
+    }))
+    .unwrap();
+    assert!(request.validate().is_ok());
+
+    assert!(
+        serde_json::from_value::<CreateApiKeyRequest>(serde_json::json!({
+            "name": "batch",
+            "scopes": ["tenants:write"]
+        }))
+        .is_err()
+    );
+
+    let no_scopes: CreateApiKeyRequest = serde_json::from_value(serde_json::json!({
+        "name": "batch",
+        "scopes": []
+    }))
+    .unwrap();
+    assert!(no_scopes.validate().is_err());
+}
+
+// ==================== GROUP HANDLER TESTS ====================
+
+type DynUserService = UserService<
+    Arc<dyn UserRepositoryTrait>,
+    Arc<dyn RoleRepositoryTrait>,
+    Arc<dyn UserRoleRepositoryTrait>,
+>;
+
+/// Service over a fresh in-memory store, with the repository types `AppState` uses
+fn in_memory_group_service() -> DynUserService {
+    use user_lib::repository::InMemoryStore;
+
+    let store = InMemoryStore::new();
+    let (users, roles, user_roles) = store.repositories();
+    let users: Arc<dyn UserRepositoryTrait> = Arc::new(users);
+    let roles: Arc<dyn RoleRepositoryTrait> = Arc::new(roles);
+    let user_roles: Arc<dyn UserRoleRepositoryTrait> = Arc::new(user_roles);
+    UserService::with_repos(Arc::new(users), Arc::new(roles), Arc::new(user_roles))
+        .with_group_repository(Arc::new(store.group_repository()))
+}
+
+async fn group_state(fake: &FakeKeycloak, service: DynUserService) -> user_api::state::AppState {
+    user_api::state::AppState {
+        user_service: Arc::new(keycloak_service(fake, service).await),
+        env: "local".to_string(),
+    }
+}
+
+async fn response_json(response: axum::response::Response) -> serde_json::Value {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+fn no_pagination() -> axum::extract::Query<user_api::methods::entities::PaginationQuery> {
+    axum::extract::Query(user_api::methods::entities::PaginationQuery {
+        page: None,
+        page_size: None,
+    })
+}
+
+#[tokio::test]
+async fn test_group_handlers_manage_groups_members_and_roles() {
+    use axum::extract::{Path, State};
+    use axum::Json;
+    use user_api::methods::entities::{CreateGroupRequest, UpdateGroupRequest};
+    use user_api::methods::{
+        add_group_member::{add_group_member, GroupMemberPath},
+        assign_group_role::{assign_group_role, GroupRolePath},
+        create_group::create_group,
+        delete_group::delete_group,
+        get_group_by_id::get_group_by_id,
+        get_group_members::get_group_members,
+        get_groups::get_groups,
+        remove_group_member::remove_group_member,
+        unassign_group_role::unassign_group_role,
+        update_group::update_group,
+    };
+    use user_api::tenant::Tenant;
+
+    let inner = in_memory_group_service();
+    let alice = inner
+        .create_user(TenantId::DEFAULT, "kc-alice")
+        .await
+        .unwrap();
+    let editor = inner
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+    let fake = FakeKeycloak::default();
+    fake.add_user("kc-alice", true);
+    let state = group_state(&fake, inner).await;
+    let tenant = || Tenant(TenantId::DEFAULT);
+
+    let response = create_group(
+        State(state.clone()),
+        tenant(),
+        Json(CreateGroupRequest {
+            name: "Editors".to_string(),
+        }),
+    )
+    .await
+    .unwrap()
+    .into_response();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let created = response_json(response).await;
+    let group_id = created["id"].as_str().unwrap().to_string();
+    assert_eq!(fake.group_names(), vec!["Editors"]);
+
+    let Json(groups) = get_groups(State(state.clone()), tenant(), no_pagination())
+        .await
+        .unwrap();
+    assert_eq!(groups.total, 1);
+
+    let Json(renamed) = update_group(
+        Path(group_id.clone()),
+        State(state.clone()),
+        tenant(),
+        Json(UpdateGroupRequest {
+            name: "Writers".to_string(),
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(renamed.name, "Writers");
+    assert_eq!(fake.group_names(), vec!["Writers"]);
+
+    let member = || {
+        Path(GroupMemberPath {
+            group_id: group_id.clone(),
+            user_id: alice.id.to_string(),
+        })
+    };
+    let role = || {
+        Path(GroupRolePath {
+            group_id: group_id.clone(),
+            role_id: editor.id.to_string(),
+        })
+    };
+    assert_eq!(
+        add_group_member(member(), State(state.clone()), tenant())
+            .await
+            .unwrap(),
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(
+        assign_group_role(role(), State(state.clone()), tenant())
+            .await
+            .unwrap(),
+        StatusCode::NO_CONTENT
+    );
+    let Json(members) = get_group_members(
+        Path(group_id.clone()),
+        State(state.clone()),
+        tenant(),
+        no_pagination(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(members.total, 1);
+    assert_eq!(members.items[0].id, alice.id);
+    // Members inherit the group's roles
+    assert_eq!(members.items[0].roles[0].name, "editor");
+
+    remove_group_member(member(), State(state.clone()), tenant())
+        .await
+        .unwrap();
+    unassign_group_role(role(), State(state.clone()), tenant())
+        .await
+        .unwrap();
+    let Json(group) = get_group_by_id(Path(group_id.clone()), State(state.clone()), tenant())
+        .await
+        .unwrap();
+    assert!(group.roles.is_empty());
+    let Json(members) = get_group_members(
+        Path(group_id.clone()),
+        State(state.clone()),
+        tenant(),
+        no_pagination(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(members.total, 0);
+
+    assert_eq!(
+        delete_group(Path(group_id.clone()), State(state.clone()), tenant())
+            .await
+            .unwrap(),
+        StatusCode::NO_CONTENT
+    );
+    assert!(fake.group_names().is_empty());
+    let missing = get_group_by_id(Path(group_id), State(state.clone()), tenant())
+        .await
+        .unwrap_err();
+    assert_eq!(missing.into_response().status(), StatusCode::NOT_FOUND);
+
+    let invalid = get_group_by_id(Path("not-a-uuid".to_string()), State(state), tenant())
+        .await
+        .unwrap_err();
+    assert_eq!(invalid.into_response().status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_failed_local_group_writes_undo_the_keycloak_change() {
+    let inner = in_memory_group_service();
+    let fake = FakeKeycloak::default();
+    let service = keycloak_service(&fake, inner).await;
+
+    service
+        .create_group(TenantId::DEFAULT, "Editors")
+        .await
+        .unwrap();
+    let writers = service
+        .create_group(TenantId::DEFAULT, "Writers")
+        .await
+        .unwrap();
+
+    // The local name check fails after Keycloak created the group: it is deleted again
+    assert!(matches!(
+        service.create_group(TenantId::DEFAULT, "editors").await,
+        Err(
+            user_api::services::integrated_user_service::IntegratedServiceError::User(
+                user_lib::errors_service::UserServiceError::GroupNameAlreadyExists
+            )
+        )
+    ));
+    assert_eq!(fake.group_names(), vec!["Editors", "Writers"]);
+
+    // A rename the local table refuses gives the Keycloak group its old name back
+    assert!(service
+        .rename_group(TenantId::DEFAULT, writers.id, "EDITORS")
+        .await
+        .is_err());
+    assert_eq!(fake.group_names(), vec!["Editors", "Writers"]);
+}
+
+// ==================== ADMIN AUTH TESTS ====================
+
+/// Admin cache routes guarded the way `main` guards them, with `token` as ADMIN_API_TOKEN
+async fn admin_router(token: Option<&str>) -> axum::Router {
+    use axum::{
+        middleware::from_fn,
+        routing::{delete, get},
+        Extension, Router,
+    };
+    use user_api::methods::flush_cache_namespace::flush_cache_namespace;
+    use user_api::methods::get_cache_stats::get_cache_stats;
+    use user_api::methods::routes::{ADMIN_CACHE_NAMESPACE_PATH, ADMIN_CACHE_STATS_PATH};
+    use user_api::middleware::admin_auth::{admin_auth_middleware, AdminAuthConfig};
+
+    let config = AdminAuthConfig::new(token.map(|t| secrecy::Secret::new(t.to_string())));
+    let state = group_state(&FakeKeycloak::default(), in_memory_group_service()).await;
+    Router::new()
+        .route(ADMIN_CACHE_STATS_PATH, get(get_cache_stats))
+        .route(ADMIN_CACHE_NAMESPACE_PATH, delete(flush_cache_namespace))
+        .route_layer(from_fn(admin_auth_middleware))
+        .route_layer(Extension(config))
+        .with_state(state)
+}
+
+async fn send_admin(
+    router: &axum::Router,
+    method: &str,
+    uri: &str,
+    headers: &[(&str, &str)],
+) -> StatusCode {
+    use axum::{body::Body, http::Request};
+    use tower::ServiceExt;
+
+    let mut request = Request::builder().method(method).uri(uri);
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    let response = router
+        .clone()
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    response.status()
+}
+
+#[tokio::test]
+async fn test_admin_routes_are_forbidden_without_a_configured_token() {
+    let router = admin_router(None).await;
+
+    let status = send_admin(&router, "GET", "/admin/cache/stats", &[]).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let status = send_admin(
+        &router,
+        "DELETE",
+        "/admin/cache/users",
+        &[("authorization", "Bearer anything")],
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_admin_routes_reject_a_missing_or_wrong_token() {
+    let router = admin_router(Some("s3cret")).await;
+
+    let status = send_admin(&router, "GET", "/admin/cache/stats", &[]).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let status = send_admin(
+        &router,
+        "GET",
+        "/admin/cache/stats",
+        &[("authorization", "Bearer wrong")],
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let status = send_admin(
+        &router,
+        "DELETE",
+        "/admin/cache/users",
+        &[("x-admin-token", "wrong")],
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_admin_routes_accept_the_token_in_either_header() {
+    let router = admin_router(Some("s3cret")).await;
+
+    for headers in [
+        [("authorization", "Bearer s3cret")],
+        [("x-admin-token", "s3cret")],
+    ] {
+        let status = send_admin(&router, "GET", "/admin/cache/stats", &headers).await;
+        assert_eq!(status, StatusCode::OK);
+        let status = send_admin(&router, "DELETE", "/admin/cache/users", &headers).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
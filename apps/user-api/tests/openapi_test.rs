@@ -1,8 +1,15 @@
+use user_api::api_key::ApiKeyScope;
+use user_api::keycloak::RequiredAction;
 use utoipa::OpenApi;
 
 use user_api::methods::entities::{
-    CreateRoleRequest, CreateUserRequest, PaginatedResponse, RoleResponse, UpdateRoleRequest,
-    UpdateUserRequest, UserResponse,
+    ApiKeyResponse, AssignRoleRequest, CacheFlushResponse, CacheNamespaceStatsResponse,
+    CacheStatsResponse, CreateApiKeyRequest, CreateGroupRequest, CreateRoleRequest,
+    CreateTenantRequest, CreateUserRequest, ExecuteUserActionsRequest, GroupResponse,
+    IssuedApiKeyResponse, KeycloakRealmResponse, PaginatedResponse, RoleResponse,
+    SetUserPasswordRequest, SuspendUserRequest, TenantResponse, UpdateGroupRequest,
+    UpdateRoleRequest, UpdateUserAttributesRequest, UpdateUserRequest, UserAttributesResponse,
+    UserResponse, UserSessionResponse,
 };
 
 #[derive(OpenApi)]
@@ -12,23 +19,63 @@ use user_api::methods::entities::{
         user_api::methods::get_user_by_id::get_user_by_id,
         user_api::methods::get_users::get_users,
         user_api::methods::update_user::update_user,
+        user_api::methods::update_user_attributes::update_user_attributes,
         user_api::methods::delete_user::delete_user,
+        user_api::methods::suspend_user::suspend_user,
+        user_api::methods::reactivate_user::reactivate_user,
+        user_api::methods::set_user_password::set_user_password,
+        user_api::methods::execute_user_actions::execute_user_actions,
+        user_api::methods::get_user_sessions::get_user_sessions,
+        user_api::methods::logout_user_sessions::logout_user_sessions,
+        user_api::methods::delete_user_session::delete_user_session,
         user_api::methods::create_role::create_role,
         user_api::methods::get_role_by_id::get_role_by_id,
         user_api::methods::get_roles::get_roles,
         user_api::methods::update_role::update_role,
         user_api::methods::delete_role::delete_role,
         user_api::methods::assign_role::assign_role,
-        user_api::methods::unassign_role::unassign_role
+        user_api::methods::unassign_role::unassign_role,
+        user_api::methods::create_group::create_group,
+        user_api::methods::get_group_by_id::get_group_by_id,
+        user_api::methods::get_groups::get_groups,
+        user_api::methods::update_group::update_group,
+        user_api::methods::delete_group::delete_group,
+        user_api::methods::get_group_members::get_group_members,
+        user_api::methods::add_group_member::add_group_member,
+        user_api::methods::remove_group_member::remove_group_member,
+        user_api::methods::assign_group_role::assign_group_role,
+        user_api::methods::unassign_group_role::unassign_group_role,
+        user_api::methods::get_cache_stats::get_cache_stats,
+        user_api::methods::flush_cache_namespace::flush_cache_namespace,
+        user_api::methods::flush_user_cache::flush_user_cache,
+        user_api::methods::restore_user::restore_user,
+        user_api::methods::restore_role::restore_role,
+        user_api::methods::create_tenant::create_tenant,
+        user_api::methods::get_tenants::get_tenants,
+        user_api::methods::get_keycloak_realms::get_keycloak_realms,
+        user_api::methods::reload_keycloak_realms::reload_keycloak_realms,
+        user_api::methods::get_api_keys::get_api_keys,
+        user_api::methods::create_api_key::create_api_key,
+        user_api::methods::rotate_api_key::rotate_api_key,
+        user_api::methods::revoke_api_key::revoke_api_key
     ),
     components(schemas(
-        CreateUserRequest, UpdateUserRequest, UserResponse,
-        CreateRoleRequest, UpdateRoleRequest, RoleResponse,
-        PaginatedResponse<UserResponse>, PaginatedResponse<RoleResponse>
+        CreateUserRequest, UpdateUserRequest, UpdateUserAttributesRequest,
+        UserResponse, UserAttributesResponse, UserSessionResponse,
+        SuspendUserRequest, SetUserPasswordRequest, ExecuteUserActionsRequest, RequiredAction,
+        CreateRoleRequest, UpdateRoleRequest, RoleResponse, AssignRoleRequest,
+        CreateGroupRequest, UpdateGroupRequest, GroupResponse,
+        PaginatedResponse<UserResponse>, PaginatedResponse<RoleResponse>,
+        PaginatedResponse<GroupResponse>,
+        CacheStatsResponse, CacheNamespaceStatsResponse, CacheFlushResponse,
+        CreateTenantRequest, TenantResponse, KeycloakRealmResponse,
+        CreateApiKeyRequest, ApiKeyResponse, IssuedApiKeyResponse, ApiKeyScope
     )),
     tags(
         (name = "users", description = "User management endpoints"),
-        (name = "roles", description = "Role management endpoints")
+        (name = "roles", description = "Role management endpoints"),
+        (name = "groups", description = "Group management endpoints, mirrored in Keycloak"),
+        (name = "admin", description = "Operational endpoints guarded by the admin token")
     )
 )]
 struct ApiDoc;
@@ -102,6 +149,136 @@ fn test_openapi_spec_has_all_endpoints() {
         "Missing DELETE /users/{{user_id}}/roles/{{role_id}}"
     );
 
+    // Suspension endpoints
+    assert!(
+        paths.get("/users/{id}/suspend").unwrap().post.is_some(),
+        "Missing POST /users/{{id}}/suspend"
+    );
+    assert!(
+        paths.get("/users/{id}/reactivate").unwrap().post.is_some(),
+        "Missing POST /users/{{id}}/reactivate"
+    );
+
+    // Credential endpoints
+    assert!(
+        paths.get("/users/{id}/password").unwrap().post.is_some(),
+        "Missing POST /users/{{id}}/password"
+    );
+    assert!(
+        paths.get("/users/{id}/actions").unwrap().post.is_some(),
+        "Missing POST /users/{{id}}/actions"
+    );
+
+    // Session endpoints
+    let sessions_path = paths.get("/users/{id}/sessions").unwrap();
+    assert!(
+        sessions_path.get.is_some(),
+        "Missing GET /users/{{id}}/sessions"
+    );
+    assert!(
+        sessions_path.delete.is_some(),
+        "Missing DELETE /users/{{id}}/sessions"
+    );
+    assert!(
+        paths
+            .get("/users/{user_id}/sessions/{session_id}")
+            .unwrap()
+            .delete
+            .is_some(),
+        "Missing DELETE /users/{{user_id}}/sessions/{{session_id}}"
+    );
+
+    // Group endpoints
+    let groups_path = paths.get("/groups").unwrap();
+    assert!(groups_path.get.is_some(), "Missing GET /groups");
+    assert!(groups_path.post.is_some(), "Missing POST /groups");
+    let group_by_id_path = paths.get("/groups/{id}").unwrap();
+    assert!(group_by_id_path.get.is_some(), "Missing GET /groups/{{id}}");
+    assert!(group_by_id_path.put.is_some(), "Missing PUT /groups/{{id}}");
+    assert!(
+        group_by_id_path.delete.is_some(),
+        "Missing DELETE /groups/{{id}}"
+    );
+    assert!(
+        paths.get("/groups/{id}/members").unwrap().get.is_some(),
+        "Missing GET /groups/{{id}}/members"
+    );
+    let group_member_path = paths.get("/groups/{group_id}/members/{user_id}").unwrap();
+    assert!(
+        group_member_path.put.is_some() && group_member_path.delete.is_some(),
+        "Missing PUT or DELETE /groups/{{group_id}}/members/{{user_id}}"
+    );
+    let group_role_path = paths.get("/groups/{group_id}/roles/{role_id}").unwrap();
+    assert!(
+        group_role_path.put.is_some() && group_role_path.delete.is_some(),
+        "Missing PUT or DELETE /groups/{{group_id}}/roles/{{role_id}}"
+    );
+
+    // Admin cache endpoints
+    let cache_stats_path = paths.get("/admin/cache/stats").unwrap();
+    assert!(
+        cache_stats_path.get.is_some(),
+        "Missing GET /admin/cache/stats"
+    );
+    let cache_namespace_path = paths.get("/admin/cache/{namespace}").unwrap();
+    assert!(
+        cache_namespace_path.delete.is_some(),
+        "Missing DELETE /admin/cache/{{namespace}}"
+    );
+    let cache_user_path = paths.get("/admin/cache/users/{id}").unwrap();
+    assert!(
+        cache_user_path.delete.is_some(),
+        "Missing DELETE /admin/cache/users/{{id}}"
+    );
+
+    // Admin restore endpoints
+    let restore_user_path = paths.get("/admin/users/{id}/restore").unwrap();
+    assert!(
+        restore_user_path.post.is_some(),
+        "Missing POST /admin/users/{{id}}/restore"
+    );
+    let restore_role_path = paths.get("/admin/roles/{id}/restore").unwrap();
+    assert!(
+        restore_role_path.post.is_some(),
+        "Missing POST /admin/roles/{{id}}/restore"
+    );
+
+    // Admin tenant endpoints
+    let tenants_path = paths.get("/admin/tenants").unwrap();
+    assert!(tenants_path.get.is_some(), "Missing GET /admin/tenants");
+    assert!(tenants_path.post.is_some(), "Missing POST /admin/tenants");
+
+    // Admin Keycloak realm endpoints
+    assert!(
+        paths.get("/admin/keycloak/realms").unwrap().get.is_some(),
+        "Missing GET /admin/keycloak/realms"
+    );
+    assert!(
+        paths
+            .get("/admin/keycloak/realms/reload")
+            .unwrap()
+            .post
+            .is_some(),
+        "Missing POST /admin/keycloak/realms/reload"
+    );
+
+    // Admin API key endpoints
+    let api_keys_path = paths.get("/admin/api-keys").unwrap();
+    assert!(api_keys_path.get.is_some(), "Missing GET /admin/api-keys");
+    assert!(api_keys_path.post.is_some(), "Missing POST /admin/api-keys");
+    assert!(
+        paths.get("/admin/api-keys/{id}").unwrap().delete.is_some(),
+        "Missing DELETE /admin/api-keys/{{id}}"
+    );
+    assert!(
+        paths
+            .get("/admin/api-keys/{id}/rotate")
+            .unwrap()
+            .post
+            .is_some(),
+        "Missing POST /admin/api-keys/{{id}}/rotate"
+    );
+
     // Verify schemas exist
     let schemas = &spec.components.as_ref().unwrap().schemas;
     assert!(
@@ -128,6 +305,31 @@ fn test_openapi_spec_has_all_endpoints() {
         schemas.contains_key("RoleResponse"),
         "Missing RoleResponse schema"
     );
+    assert!(
+        schemas.contains_key("SuspendUserRequest"),
+        "Missing SuspendUserRequest schema"
+    );
+    assert!(
+        schemas.contains_key("SetUserPasswordRequest"),
+        "Missing SetUserPasswordRequest schema"
+    );
+    assert!(
+        schemas.contains_key("RequiredAction"),
+        "Missing RequiredAction schema"
+    );
+    assert!(
+        schemas.contains_key("GroupResponse"),
+        "Missing GroupResponse schema"
+    );
+
+    assert!(
+        schemas.contains_key("IssuedApiKeyResponse"),
+        "Missing IssuedApiKeyResponse schema"
+    );
+    assert!(
+        schemas.contains_key("ApiKeyScope"),
+        "Missing ApiKeyScope schema"
+    );
 
     // Print the full spec for manual verification
     println!("OpenAPI Spec:\n{json}");
@@ -143,4 +345,5 @@ fn test_openapi_json_contains_tags() {
     // Check tags are present in the JSON
     assert!(json.contains("\"users\""), "Missing 'users' tag in JSON");
     assert!(json.contains("\"roles\""), "Missing 'roles' tag in JSON");
+    assert!(json.contains("\"admin\""), "Missing 'admin' tag in JSON");
 }
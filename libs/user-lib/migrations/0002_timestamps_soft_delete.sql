@@ -0,0 +1,11 @@
+-- Track when users and roles were created/updated, and allow soft deletes.
+-- deleted_at is NULL for live rows; soft-deleted rows keep their role assignments.
+ALTER TABLE users
+    ADD COLUMN created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    ADD COLUMN updated_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    ADD COLUMN deleted_at DATETIME(6) NULL;
+
+ALTER TABLE roles
+    ADD COLUMN created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    ADD COLUMN updated_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    ADD COLUMN deleted_at DATETIME(6) NULL;
@@ -0,0 +1,4 @@
+-- Row version for optimistic concurrency control, bumped on every write
+-- and exposed by the API as the ETag.
+ALTER TABLE users ADD COLUMN version BIGINT NOT NULL DEFAULT 1;
+ALTER TABLE roles ADD COLUMN version BIGINT NOT NULL DEFAULT 1;
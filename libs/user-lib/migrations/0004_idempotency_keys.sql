@@ -0,0 +1,12 @@
+-- Responses stored for `Idempotency-Key` retries when no cache backend is configured.
+-- status_code and response_body stay NULL while the first request is still running.
+CREATE TABLE idempotency_keys (
+    idempotency_key VARCHAR(255) PRIMARY KEY,
+    fingerprint CHAR(64) NOT NULL,
+    status_code INT NULL,
+    response_body MEDIUMTEXT NULL,
+    created_at DATETIME(6) NOT NULL,
+    expires_at DATETIME(6) NOT NULL
+);
+
+CREATE INDEX idx_idempotency_keys_expires_at ON idempotency_keys(expires_at);
@@ -0,0 +1,15 @@
+-- Application-specific user attributes kept alongside the Keycloak profile.
+-- One row per user; metadata holds a JSON object as text.
+CREATE TABLE user_attributes (
+    user_id CHAR(36) PRIMARY KEY,
+    department VARCHAR(255) NULL,
+    cost_center VARCHAR(64) NULL,
+    locale VARCHAR(35) NULL,
+    metadata MEDIUMTEXT NULL,
+    updated_at DATETIME(6) NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_user_attributes_department ON user_attributes(department);
+CREATE INDEX idx_user_attributes_cost_center ON user_attributes(cost_center);
+CREATE INDEX idx_user_attributes_locale ON user_attributes(locale);
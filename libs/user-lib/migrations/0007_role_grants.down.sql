@@ -0,0 +1,6 @@
+DROP INDEX idx_user_roles_expires_at ON user_roles;
+
+ALTER TABLE user_roles
+    DROP COLUMN granted_by,
+    DROP COLUMN expires_at,
+    DROP COLUMN granted_at;
@@ -0,0 +1,8 @@
+-- Time-bound role assignments. expires_at is NULL for permanent grants; expired grants
+-- are ignored by role reads and removed by the user-api sweeper.
+ALTER TABLE user_roles
+    ADD COLUMN granted_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    ADD COLUMN expires_at DATETIME(6) NULL,
+    ADD COLUMN granted_by VARCHAR(255) NULL;
+
+CREATE INDEX idx_user_roles_expires_at ON user_roles(expires_at);
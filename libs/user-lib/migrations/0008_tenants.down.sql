@@ -0,0 +1,25 @@
+-- Only the default tenant's data fits the single-tenant schema; everything else is dropped
+DELETE FROM idempotency_keys;
+ALTER TABLE idempotency_keys MODIFY idempotency_key VARCHAR(255);
+
+DELETE FROM users WHERE tenant_id <> '00000000-0000-0000-0000-000000000000';
+DELETE FROM roles WHERE tenant_id <> '00000000-0000-0000-0000-000000000000';
+
+DROP INDEX idx_user_roles_tenant_id ON user_roles;
+DROP INDEX idx_users_tenant_id ON users;
+
+ALTER TABLE user_roles
+    DROP FOREIGN KEY fk_user_roles_tenant,
+    DROP COLUMN tenant_id;
+
+ALTER TABLE roles DROP INDEX role_name_unique;
+ALTER TABLE roles
+    DROP FOREIGN KEY fk_roles_tenant,
+    DROP COLUMN tenant_id,
+    ADD CONSTRAINT `role_name_unique` UNIQUE (name);
+
+ALTER TABLE users
+    DROP FOREIGN KEY fk_users_tenant,
+    DROP COLUMN tenant_id;
+
+DROP TABLE tenants;
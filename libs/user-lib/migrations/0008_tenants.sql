@@ -0,0 +1,37 @@
+-- Multi-tenancy. Users, roles and role assignments belong to exactly one tenant; existing
+-- rows move to the default tenant, which also serves requests that don't name a tenant.
+-- Role names are unique per tenant, so every tenant can have its own admin and user roles.
+CREATE TABLE tenants (
+    id CHAR(36) PRIMARY KEY,
+    slug VARCHAR(63) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    CONSTRAINT `tenant_slug_unique` UNIQUE (slug)
+);
+
+INSERT INTO tenants (id, slug, name) VALUES
+    ('00000000-0000-0000-0000-000000000000', 'default', 'Default');
+
+-- The default only backfills existing rows; the repositories always set the tenant
+ALTER TABLE users
+    ADD COLUMN tenant_id CHAR(36) NOT NULL DEFAULT '00000000-0000-0000-0000-000000000000',
+    ADD CONSTRAINT fk_users_tenant FOREIGN KEY (tenant_id) REFERENCES tenants(id);
+ALTER TABLE users ALTER COLUMN tenant_id DROP DEFAULT;
+
+ALTER TABLE roles
+    ADD COLUMN tenant_id CHAR(36) NOT NULL DEFAULT '00000000-0000-0000-0000-000000000000',
+    ADD CONSTRAINT fk_roles_tenant FOREIGN KEY (tenant_id) REFERENCES tenants(id);
+ALTER TABLE roles ALTER COLUMN tenant_id DROP DEFAULT;
+ALTER TABLE roles DROP INDEX role_name_unique;
+ALTER TABLE roles ADD CONSTRAINT `role_name_unique` UNIQUE (tenant_id, name);
+
+ALTER TABLE user_roles
+    ADD COLUMN tenant_id CHAR(36) NOT NULL DEFAULT '00000000-0000-0000-0000-000000000000',
+    ADD CONSTRAINT fk_user_roles_tenant FOREIGN KEY (tenant_id) REFERENCES tenants(id);
+ALTER TABLE user_roles ALTER COLUMN tenant_id DROP DEFAULT;
+
+CREATE INDEX idx_users_tenant_id ON users(tenant_id);
+CREATE INDEX idx_user_roles_tenant_id ON user_roles(tenant_id);
+
+-- Stored keys are prefixed with the tenant so retries never replay across tenants
+ALTER TABLE idempotency_keys MODIFY idempotency_key VARCHAR(292);
@@ -0,0 +1,39 @@
+-- Groups mirrored from Keycloak. The table only caches the group's name and Keycloak id;
+-- membership and attached roles are local, and members inherit the group's roles.
+-- GROUPS is a reserved word in MySQL 8, hence user_groups.
+CREATE TABLE user_groups (
+    id CHAR(36) PRIMARY KEY,
+    tenant_id CHAR(36) NOT NULL,
+    keycloak_id VARCHAR(255) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    updated_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    CONSTRAINT `group_name_unique` UNIQUE (tenant_id, name),
+    CONSTRAINT fk_user_groups_tenant FOREIGN KEY (tenant_id) REFERENCES tenants(id)
+);
+
+CREATE TABLE group_members (
+    group_id CHAR(36) NOT NULL,
+    user_id CHAR(36) NOT NULL,
+    tenant_id CHAR(36) NOT NULL,
+    added_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    CONSTRAINT `group_members_pk` PRIMARY KEY (group_id, user_id),
+    CONSTRAINT fk_group_members_group FOREIGN KEY (group_id)
+        REFERENCES user_groups(id) ON DELETE CASCADE,
+    CONSTRAINT fk_group_members_user FOREIGN KEY (user_id)
+        REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE TABLE group_roles (
+    group_id CHAR(36) NOT NULL,
+    role_id CHAR(36) NOT NULL,
+    tenant_id CHAR(36) NOT NULL,
+    CONSTRAINT `group_roles_pk` PRIMARY KEY (group_id, role_id),
+    CONSTRAINT fk_group_roles_group FOREIGN KEY (group_id)
+        REFERENCES user_groups(id) ON DELETE CASCADE,
+    CONSTRAINT fk_group_roles_role FOREIGN KEY (role_id)
+        REFERENCES roles(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_group_members_user_id ON group_members(user_id);
+CREATE INDEX idx_group_roles_role_id ON group_roles(role_id);
@@ -0,0 +1,2 @@
+DROP INDEX idx_users_suspended_at ON users;
+ALTER TABLE users DROP COLUMN suspension_reason, DROP COLUMN suspended_at;
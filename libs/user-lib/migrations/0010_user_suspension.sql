@@ -0,0 +1,7 @@
+-- Suspended users keep their data but are disabled in Keycloak.
+-- suspended_at is NULL for active users; the reason is free text for auditing.
+ALTER TABLE users
+    ADD COLUMN suspended_at DATETIME(6) NULL,
+    ADD COLUMN suspension_reason VARCHAR(1000) NULL;
+
+CREATE INDEX idx_users_suspended_at ON users(suspended_at);
@@ -0,0 +1,20 @@
+-- API keys of service accounts (machine clients). Only the SHA-256 hash of a key is
+-- stored; key_prefix keeps its first characters so admins can tell keys apart. scopes is
+-- a space-separated list. Revoked and expired keys stay around for auditing.
+CREATE TABLE api_keys (
+    id CHAR(36) PRIMARY KEY,
+    tenant_id CHAR(36) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    key_prefix VARCHAR(16) NOT NULL,
+    key_hash CHAR(64) NOT NULL,
+    scopes VARCHAR(1000) NOT NULL,
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    expires_at DATETIME(6) NULL,
+    last_used_at DATETIME(6) NULL,
+    rotated_at DATETIME(6) NULL,
+    revoked_at DATETIME(6) NULL,
+    CONSTRAINT `api_key_hash_unique` UNIQUE (key_hash),
+    CONSTRAINT fk_api_keys_tenant FOREIGN KEY (tenant_id) REFERENCES tenants(id)
+);
+
+CREATE INDEX idx_api_keys_tenant_id ON api_keys(tenant_id);
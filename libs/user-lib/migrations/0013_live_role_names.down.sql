@@ -0,0 +1,4 @@
+-- Fails while a deleted role shares its name with a live role of the same tenant
+ALTER TABLE roles DROP INDEX role_name_unique;
+ALTER TABLE roles ADD CONSTRAINT `role_name_unique` UNIQUE (tenant_id, name);
+ALTER TABLE roles DROP COLUMN live_name;
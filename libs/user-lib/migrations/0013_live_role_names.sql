@@ -0,0 +1,7 @@
+-- Role names are unique among live roles only, so a soft-deleted role no longer blocks its
+-- name; restoring it fails while a live role has taken the name. live_name is the name of
+-- a live role and NULL once it is deleted, and a unique index allows any number of NULLs.
+ALTER TABLE roles
+    ADD COLUMN live_name VARCHAR(255) AS (IF(deleted_at IS NULL, name, NULL)) STORED;
+ALTER TABLE roles DROP INDEX role_name_unique;
+ALTER TABLE roles ADD CONSTRAINT `role_name_unique` UNIQUE (tenant_id, live_name);
@@ -0,0 +1,33 @@
+-- Create users table (Keycloak-integrated schema)
+-- User profile data (name, email) is stored in Keycloak
+-- Local DB only stores user ID and Keycloak reference
+CREATE TABLE users (
+    id VARCHAR(36) PRIMARY KEY,
+    keycloak_id VARCHAR(255) NOT NULL,
+    CONSTRAINT user_keycloak_id_unique UNIQUE (keycloak_id)
+);
+
+-- Create roles table
+CREATE TABLE roles (
+    id VARCHAR(36) PRIMARY KEY,
+    name VARCHAR(255) NOT NULL
+);
+
+-- Role names are unique case-insensitively, matching MySQL's default collation
+CREATE UNIQUE INDEX role_name_unique ON roles (LOWER(name));
+
+-- Create join table for user-role relationship
+CREATE TABLE user_roles (
+    user_id VARCHAR(36) NOT NULL,
+    role_id VARCHAR(36) NOT NULL,
+    CONSTRAINT user_roles_pk PRIMARY KEY (user_id, role_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (role_id) REFERENCES roles(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_user_roles_role_id ON user_roles(role_id);
+
+-- Seed default roles
+INSERT INTO roles (id, name) VALUES
+    ('00000000-0000-0000-0000-000000000001', 'admin'),
+    ('00000000-0000-0000-0000-000000000002', 'user');
@@ -0,0 +1,11 @@
+-- Track when users and roles were created/updated, and allow soft deletes.
+-- deleted_at is NULL for live rows; soft-deleted rows keep their role assignments.
+ALTER TABLE users
+    ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    ADD COLUMN deleted_at TIMESTAMPTZ NULL;
+
+ALTER TABLE roles
+    ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    ADD COLUMN deleted_at TIMESTAMPTZ NULL;
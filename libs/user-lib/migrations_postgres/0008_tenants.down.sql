@@ -0,0 +1,18 @@
+-- Only the default tenant's data fits the single-tenant schema; everything else is dropped
+DELETE FROM idempotency_keys;
+ALTER TABLE idempotency_keys ALTER COLUMN idempotency_key TYPE VARCHAR(255);
+
+DELETE FROM users WHERE tenant_id <> '00000000-0000-0000-0000-000000000000';
+DELETE FROM roles WHERE tenant_id <> '00000000-0000-0000-0000-000000000000';
+
+DROP INDEX IF EXISTS idx_user_roles_tenant_id;
+DROP INDEX IF EXISTS idx_users_tenant_id;
+
+DROP INDEX role_name_unique;
+CREATE UNIQUE INDEX role_name_unique ON roles (LOWER(name));
+
+ALTER TABLE user_roles DROP COLUMN tenant_id;
+ALTER TABLE roles DROP COLUMN tenant_id;
+ALTER TABLE users DROP COLUMN tenant_id;
+
+DROP TABLE tenants;
@@ -0,0 +1,38 @@
+-- Multi-tenancy. Users, roles and role assignments belong to exactly one tenant; existing
+-- rows move to the default tenant, which also serves requests that don't name a tenant.
+-- Role names are unique per tenant, so every tenant can have its own admin and user roles.
+CREATE TABLE tenants (
+    id VARCHAR(36) PRIMARY KEY,
+    slug VARCHAR(63) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CONSTRAINT tenant_slug_unique UNIQUE (slug)
+);
+
+INSERT INTO tenants (id, slug, name) VALUES
+    ('00000000-0000-0000-0000-000000000000', 'default', 'Default');
+
+-- The default only backfills existing rows; the repositories always set the tenant
+ALTER TABLE users
+    ADD COLUMN tenant_id VARCHAR(36) NOT NULL DEFAULT '00000000-0000-0000-0000-000000000000'
+        REFERENCES tenants(id);
+ALTER TABLE users ALTER COLUMN tenant_id DROP DEFAULT;
+
+ALTER TABLE roles
+    ADD COLUMN tenant_id VARCHAR(36) NOT NULL DEFAULT '00000000-0000-0000-0000-000000000000'
+        REFERENCES tenants(id);
+ALTER TABLE roles ALTER COLUMN tenant_id DROP DEFAULT;
+
+ALTER TABLE user_roles
+    ADD COLUMN tenant_id VARCHAR(36) NOT NULL DEFAULT '00000000-0000-0000-0000-000000000000'
+        REFERENCES tenants(id);
+ALTER TABLE user_roles ALTER COLUMN tenant_id DROP DEFAULT;
+
+DROP INDEX role_name_unique;
+CREATE UNIQUE INDEX role_name_unique ON roles (tenant_id, LOWER(name));
+
+CREATE INDEX idx_users_tenant_id ON users(tenant_id);
+CREATE INDEX idx_user_roles_tenant_id ON user_roles(tenant_id);
+
+-- Stored keys are prefixed with the tenant so retries never replay across tenants
+ALTER TABLE idempotency_keys ALTER COLUMN idempotency_key TYPE VARCHAR(292);
@@ -0,0 +1,36 @@
+-- Groups mirrored from Keycloak. The table only caches the group's name and Keycloak id;
+-- membership and attached roles are local, and members inherit the group's roles.
+-- Named user_groups to match the MySQL schema, where GROUPS is a reserved word.
+CREATE TABLE user_groups (
+    id VARCHAR(36) PRIMARY KEY,
+    tenant_id VARCHAR(36) NOT NULL REFERENCES tenants(id),
+    keycloak_id VARCHAR(255) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Group names are unique per tenant, case-insensitively like role names
+CREATE UNIQUE INDEX group_name_unique ON user_groups (tenant_id, LOWER(name));
+
+CREATE TABLE group_members (
+    group_id VARCHAR(36) NOT NULL,
+    user_id VARCHAR(36) NOT NULL,
+    tenant_id VARCHAR(36) NOT NULL,
+    added_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CONSTRAINT group_members_pk PRIMARY KEY (group_id, user_id),
+    FOREIGN KEY (group_id) REFERENCES user_groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE TABLE group_roles (
+    group_id VARCHAR(36) NOT NULL,
+    role_id VARCHAR(36) NOT NULL,
+    tenant_id VARCHAR(36) NOT NULL,
+    CONSTRAINT group_roles_pk PRIMARY KEY (group_id, role_id),
+    FOREIGN KEY (group_id) REFERENCES user_groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (role_id) REFERENCES roles(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_group_members_user_id ON group_members(user_id);
+CREATE INDEX idx_group_roles_role_id ON group_roles(role_id);
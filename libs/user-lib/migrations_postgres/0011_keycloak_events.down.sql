@@ -0,0 +1,2 @@
+DROP TABLE keycloak_event_checkpoints;
+ALTER TABLE users DROP COLUMN last_login_at;
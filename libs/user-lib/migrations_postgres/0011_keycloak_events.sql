@@ -0,0 +1,12 @@
+-- State fed from the Keycloak event poller. last_login_at is the time of the user's
+-- most recent LOGIN event; checkpoints hold the time (epoch milliseconds, as Keycloak
+-- reports it) of the newest event consumed per tenant and event source.
+ALTER TABLE users ADD COLUMN last_login_at TIMESTAMPTZ NULL;
+
+CREATE TABLE keycloak_event_checkpoints (
+    tenant_id VARCHAR(36) NOT NULL,
+    source VARCHAR(32) NOT NULL,
+    event_time BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    CONSTRAINT keycloak_event_checkpoints_pk PRIMARY KEY (tenant_id, source)
+);
@@ -0,0 +1,19 @@
+-- API keys of service accounts (machine clients). Only the SHA-256 hash of a key is
+-- stored; key_prefix keeps its first characters so admins can tell keys apart. scopes is
+-- a space-separated list. Revoked and expired keys stay around for auditing.
+CREATE TABLE api_keys (
+    id VARCHAR(36) PRIMARY KEY,
+    tenant_id VARCHAR(36) NOT NULL REFERENCES tenants(id),
+    name VARCHAR(255) NOT NULL,
+    key_prefix VARCHAR(16) NOT NULL,
+    key_hash VARCHAR(64) NOT NULL,
+    scopes VARCHAR(1000) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at TIMESTAMPTZ NULL,
+    last_used_at TIMESTAMPTZ NULL,
+    rotated_at TIMESTAMPTZ NULL,
+    revoked_at TIMESTAMPTZ NULL,
+    CONSTRAINT api_key_hash_unique UNIQUE (key_hash)
+);
+
+CREATE INDEX idx_api_keys_tenant_id ON api_keys(tenant_id);
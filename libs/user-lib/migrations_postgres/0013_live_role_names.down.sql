@@ -0,0 +1,3 @@
+-- Fails while a deleted role shares its name with a live role of the same tenant
+DROP INDEX role_name_unique;
+CREATE UNIQUE INDEX role_name_unique ON roles (tenant_id, LOWER(name));
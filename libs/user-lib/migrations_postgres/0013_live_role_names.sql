@@ -0,0 +1,4 @@
+-- Role names are unique among live roles only, so a soft-deleted role no longer blocks its
+-- name; restoring it fails while a live role has taken the name.
+DROP INDEX role_name_unique;
+CREATE UNIQUE INDEX role_name_unique ON roles (tenant_id, LOWER(name)) WHERE deleted_at IS NULL;
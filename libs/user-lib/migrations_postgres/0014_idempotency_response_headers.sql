@@ -0,0 +1,3 @@
+-- Headers replayed with a stored response (e.g. Location, ETag), serialized by the caller.
+-- NULL for records completed before they were kept.
+ALTER TABLE idempotency_keys ADD COLUMN response_headers TEXT NULL;
@@ -0,0 +1,4 @@
+-- Drop in reverse order due to foreign key constraints
+DROP TABLE IF EXISTS user_roles;
+DROP TABLE IF EXISTS users;
+DROP TABLE IF EXISTS roles;
\ No newline at end of file
@@ -0,0 +1,32 @@
+-- Create users table (Keycloak-integrated schema)
+-- User profile data (name, email) is stored in Keycloak
+-- Local DB only stores user ID and Keycloak reference
+CREATE TABLE users (
+    id TEXT PRIMARY KEY NOT NULL,
+    keycloak_id TEXT NOT NULL,
+    CONSTRAINT user_keycloak_id_unique UNIQUE (keycloak_id)
+);
+
+-- Create roles table
+-- NOCASE keeps role names unique case-insensitively, matching MySQL's default collation
+CREATE TABLE roles (
+    id TEXT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL COLLATE NOCASE,
+    CONSTRAINT role_name_unique UNIQUE (name)
+);
+
+-- Create join table for user-role relationship
+CREATE TABLE user_roles (
+    user_id TEXT NOT NULL,
+    role_id TEXT NOT NULL,
+    CONSTRAINT user_roles_pk PRIMARY KEY (user_id, role_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (role_id) REFERENCES roles(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_user_roles_role_id ON user_roles(role_id);
+
+-- Seed default roles
+INSERT INTO roles (id, name) VALUES
+    ('00000000-0000-0000-0000-000000000001', 'admin'),
+    ('00000000-0000-0000-0000-000000000002', 'user');
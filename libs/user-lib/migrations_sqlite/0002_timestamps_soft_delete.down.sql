@@ -0,0 +1,7 @@
+ALTER TABLE roles DROP COLUMN deleted_at;
+ALTER TABLE roles DROP COLUMN updated_at;
+ALTER TABLE roles DROP COLUMN created_at;
+
+ALTER TABLE users DROP COLUMN deleted_at;
+ALTER TABLE users DROP COLUMN updated_at;
+ALTER TABLE users DROP COLUMN created_at;
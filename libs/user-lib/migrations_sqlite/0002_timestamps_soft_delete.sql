@@ -0,0 +1,14 @@
+-- Track when users and roles were created/updated, and allow soft deletes.
+-- deleted_at is NULL for live rows; soft-deleted rows keep their role assignments.
+-- SQLite cannot add a column with a non-constant default, so existing rows are
+-- backfilled and new rows get their timestamps from the repositories.
+ALTER TABLE users ADD COLUMN created_at TEXT NOT NULL DEFAULT '1970-01-01T00:00:00Z';
+ALTER TABLE users ADD COLUMN updated_at TEXT NOT NULL DEFAULT '1970-01-01T00:00:00Z';
+ALTER TABLE users ADD COLUMN deleted_at TEXT NULL;
+
+ALTER TABLE roles ADD COLUMN created_at TEXT NOT NULL DEFAULT '1970-01-01T00:00:00Z';
+ALTER TABLE roles ADD COLUMN updated_at TEXT NOT NULL DEFAULT '1970-01-01T00:00:00Z';
+ALTER TABLE roles ADD COLUMN deleted_at TEXT NULL;
+
+UPDATE users SET created_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now');
+UPDATE roles SET created_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now');
@@ -0,0 +1,12 @@
+-- Responses stored for `Idempotency-Key` retries when no cache backend is configured.
+-- status_code and response_body stay NULL while the first request is still running.
+CREATE TABLE idempotency_keys (
+    idempotency_key TEXT PRIMARY KEY NOT NULL,
+    fingerprint TEXT NOT NULL,
+    status_code INTEGER NULL,
+    response_body TEXT NULL,
+    created_at TEXT NOT NULL,
+    expires_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_idempotency_keys_expires_at ON idempotency_keys(expires_at);
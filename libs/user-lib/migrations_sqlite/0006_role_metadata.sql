@@ -0,0 +1,9 @@
+-- Role metadata. System roles are the ones seeded by 0001_init; the API refuses to
+-- rename or delete them because bootstrap code relies on them.
+ALTER TABLE roles ADD COLUMN description TEXT NULL;
+ALTER TABLE roles ADD COLUMN is_system BOOLEAN NOT NULL DEFAULT FALSE;
+
+UPDATE roles SET is_system = TRUE, description = 'Full administrative access'
+WHERE id = '00000000-0000-0000-0000-000000000001';
+UPDATE roles SET is_system = TRUE, description = 'Default role for regular users'
+WHERE id = '00000000-0000-0000-0000-000000000002';
@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS idx_user_roles_expires_at;
+
+ALTER TABLE user_roles DROP COLUMN granted_by;
+ALTER TABLE user_roles DROP COLUMN expires_at;
+ALTER TABLE user_roles DROP COLUMN granted_at;
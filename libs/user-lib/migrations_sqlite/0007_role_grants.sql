@@ -0,0 +1,10 @@
+-- Time-bound role assignments. expires_at is NULL for permanent grants; expired grants
+-- are ignored by role reads and removed by the user-api sweeper.
+-- As in 0002, existing rows are backfilled and new rows get granted_at from the repository.
+ALTER TABLE user_roles ADD COLUMN granted_at TEXT NOT NULL DEFAULT '1970-01-01T00:00:00Z';
+ALTER TABLE user_roles ADD COLUMN expires_at TEXT NULL;
+ALTER TABLE user_roles ADD COLUMN granted_by TEXT NULL;
+
+UPDATE user_roles SET granted_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now');
+
+CREATE INDEX idx_user_roles_expires_at ON user_roles(expires_at);
@@ -0,0 +1,52 @@
+-- Only the default tenant's data fits the single-tenant schema; everything else is dropped
+DELETE FROM idempotency_keys;
+DELETE FROM users WHERE tenant_id <> '00000000-0000-0000-0000-000000000000';
+DELETE FROM roles WHERE tenant_id <> '00000000-0000-0000-0000-000000000000';
+
+CREATE TABLE user_roles_old AS SELECT * FROM user_roles;
+DROP TABLE user_roles;
+
+CREATE TABLE roles_old (
+    id TEXT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL COLLATE NOCASE,
+    created_at TEXT NOT NULL DEFAULT '1970-01-01T00:00:00Z',
+    updated_at TEXT NOT NULL DEFAULT '1970-01-01T00:00:00Z',
+    deleted_at TEXT NULL,
+    version INTEGER NOT NULL DEFAULT 1,
+    description TEXT NULL,
+    is_system BOOLEAN NOT NULL DEFAULT FALSE,
+    CONSTRAINT role_name_unique UNIQUE (name)
+);
+
+INSERT INTO roles_old
+    (id, name, created_at, updated_at, deleted_at, version, description, is_system)
+SELECT id, name, created_at, updated_at, deleted_at, version, description, is_system
+FROM roles;
+
+DROP TABLE roles;
+ALTER TABLE roles_old RENAME TO roles;
+
+CREATE TABLE user_roles (
+    user_id TEXT NOT NULL,
+    role_id TEXT NOT NULL,
+    granted_at TEXT NOT NULL DEFAULT '1970-01-01T00:00:00Z',
+    expires_at TEXT NULL,
+    granted_by TEXT NULL,
+    CONSTRAINT user_roles_pk PRIMARY KEY (user_id, role_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (role_id) REFERENCES roles(id) ON DELETE CASCADE
+);
+
+INSERT INTO user_roles (user_id, role_id, granted_at, expires_at, granted_by)
+SELECT user_id, role_id, granted_at, expires_at, granted_by
+FROM user_roles_old;
+
+DROP TABLE user_roles_old;
+
+CREATE INDEX idx_user_roles_role_id ON user_roles(role_id);
+CREATE INDEX idx_user_roles_expires_at ON user_roles(expires_at);
+
+DROP INDEX idx_users_tenant_id;
+ALTER TABLE users DROP COLUMN tenant_id;
+
+DROP TABLE tenants;
@@ -0,0 +1,69 @@
+-- Multi-tenancy. Users, roles and role assignments belong to exactly one tenant; existing
+-- rows move to the default tenant, which also serves requests that don't name a tenant.
+-- Role names are unique per tenant, so every tenant can have its own admin and user roles.
+CREATE TABLE tenants (
+    id TEXT PRIMARY KEY NOT NULL,
+    slug TEXT NOT NULL,
+    name TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    CONSTRAINT tenant_slug_unique UNIQUE (slug)
+);
+
+INSERT INTO tenants (id, slug, name, created_at) VALUES
+    ('00000000-0000-0000-0000-000000000000', 'default', 'Default',
+     strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+
+-- SQLite cannot add a foreign key column with a non-NULL default, so users.tenant_id
+-- is a plain column; the repositories always set it.
+ALTER TABLE users ADD COLUMN tenant_id TEXT NOT NULL DEFAULT '00000000-0000-0000-0000-000000000000';
+CREATE INDEX idx_users_tenant_id ON users(tenant_id);
+
+-- The inline role_name_unique constraint can only change by rebuilding roles. user_roles
+-- is rebuilt with it: dropping roles would otherwise cascade to the assignments.
+CREATE TABLE user_roles_old AS SELECT * FROM user_roles;
+DROP TABLE user_roles;
+
+CREATE TABLE roles_new (
+    id TEXT PRIMARY KEY NOT NULL,
+    tenant_id TEXT NOT NULL REFERENCES tenants(id),
+    name TEXT NOT NULL COLLATE NOCASE,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    deleted_at TEXT NULL,
+    version INTEGER NOT NULL DEFAULT 1,
+    description TEXT NULL,
+    is_system BOOLEAN NOT NULL DEFAULT FALSE,
+    CONSTRAINT role_name_unique UNIQUE (tenant_id, name)
+);
+
+INSERT INTO roles_new
+    (id, tenant_id, name, created_at, updated_at, deleted_at, version, description, is_system)
+SELECT id, '00000000-0000-0000-0000-000000000000', name, created_at, updated_at, deleted_at,
+       version, description, is_system
+FROM roles;
+
+DROP TABLE roles;
+ALTER TABLE roles_new RENAME TO roles;
+
+CREATE TABLE user_roles (
+    user_id TEXT NOT NULL,
+    role_id TEXT NOT NULL,
+    tenant_id TEXT NOT NULL REFERENCES tenants(id),
+    granted_at TEXT NOT NULL,
+    expires_at TEXT NULL,
+    granted_by TEXT NULL,
+    CONSTRAINT user_roles_pk PRIMARY KEY (user_id, role_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (role_id) REFERENCES roles(id) ON DELETE CASCADE
+);
+
+INSERT INTO user_roles (user_id, role_id, tenant_id, granted_at, expires_at, granted_by)
+SELECT user_id, role_id, '00000000-0000-0000-0000-000000000000', granted_at, expires_at,
+       granted_by
+FROM user_roles_old;
+
+DROP TABLE user_roles_old;
+
+CREATE INDEX idx_user_roles_role_id ON user_roles(role_id);
+CREATE INDEX idx_user_roles_expires_at ON user_roles(expires_at);
+CREATE INDEX idx_user_roles_tenant_id ON user_roles(tenant_id);
@@ -0,0 +1,34 @@
+-- Groups mirrored from Keycloak. The table only caches the group's name and Keycloak id;
+-- membership and attached roles are local, and members inherit the group's roles.
+-- Named user_groups to match the MySQL schema, where GROUPS is a reserved word.
+CREATE TABLE user_groups (
+    id TEXT PRIMARY KEY NOT NULL,
+    tenant_id TEXT NOT NULL REFERENCES tenants(id),
+    keycloak_id TEXT NOT NULL,
+    name TEXT NOT NULL COLLATE NOCASE,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    CONSTRAINT group_name_unique UNIQUE (tenant_id, name)
+);
+
+CREATE TABLE group_members (
+    group_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    tenant_id TEXT NOT NULL,
+    added_at TEXT NOT NULL,
+    CONSTRAINT group_members_pk PRIMARY KEY (group_id, user_id),
+    FOREIGN KEY (group_id) REFERENCES user_groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE TABLE group_roles (
+    group_id TEXT NOT NULL,
+    role_id TEXT NOT NULL,
+    tenant_id TEXT NOT NULL,
+    CONSTRAINT group_roles_pk PRIMARY KEY (group_id, role_id),
+    FOREIGN KEY (group_id) REFERENCES user_groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (role_id) REFERENCES roles(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_group_members_user_id ON group_members(user_id);
+CREATE INDEX idx_group_roles_role_id ON group_roles(role_id);
@@ -0,0 +1,3 @@
+DROP INDEX idx_users_suspended_at;
+ALTER TABLE users DROP COLUMN suspension_reason;
+ALTER TABLE users DROP COLUMN suspended_at;
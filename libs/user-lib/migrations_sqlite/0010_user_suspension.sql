@@ -0,0 +1,6 @@
+-- Suspended users keep their data but are disabled in Keycloak.
+-- suspended_at is NULL for active users; the reason is free text for auditing.
+ALTER TABLE users ADD COLUMN suspended_at TEXT NULL;
+ALTER TABLE users ADD COLUMN suspension_reason TEXT NULL;
+
+CREATE INDEX idx_users_suspended_at ON users(suspended_at);
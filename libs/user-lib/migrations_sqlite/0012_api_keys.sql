@@ -0,0 +1,19 @@
+-- API keys of service accounts (machine clients). Only the SHA-256 hash of a key is
+-- stored; key_prefix keeps its first characters so admins can tell keys apart. scopes is
+-- a space-separated list. Revoked and expired keys stay around for auditing.
+CREATE TABLE api_keys (
+    id TEXT PRIMARY KEY NOT NULL,
+    tenant_id TEXT NOT NULL REFERENCES tenants(id),
+    name TEXT NOT NULL,
+    key_prefix TEXT NOT NULL,
+    key_hash TEXT NOT NULL,
+    scopes TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    expires_at TEXT NULL,
+    last_used_at TEXT NULL,
+    rotated_at TEXT NULL,
+    revoked_at TEXT NULL,
+    CONSTRAINT api_key_hash_unique UNIQUE (key_hash)
+);
+
+CREATE INDEX idx_api_keys_tenant_id ON api_keys(tenant_id);
@@ -0,0 +1,5 @@
+-- Fails while a deleted role shares its name with a live role of the same tenant. A unique
+-- index stands in for the inline constraint the up migration dropped; it reports
+-- violations the same way.
+DROP INDEX role_name_unique;
+CREATE UNIQUE INDEX role_name_unique ON roles (tenant_id, name);
@@ -0,0 +1,69 @@
+-- Role names are unique among live roles only, so a soft-deleted role no longer blocks its
+-- name; restoring it fails while a live role has taken the name.
+-- The inline role_name_unique constraint can only change by rebuilding roles. user_roles
+-- and group_roles are rebuilt with it: dropping roles would otherwise cascade to them.
+CREATE TABLE user_roles_old AS SELECT * FROM user_roles;
+CREATE TABLE group_roles_old AS SELECT * FROM group_roles;
+DROP TABLE user_roles;
+DROP TABLE group_roles;
+
+CREATE TABLE roles_new (
+    id TEXT PRIMARY KEY NOT NULL,
+    tenant_id TEXT NOT NULL REFERENCES tenants(id),
+    name TEXT NOT NULL COLLATE NOCASE,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    deleted_at TEXT NULL,
+    version INTEGER NOT NULL DEFAULT 1,
+    description TEXT NULL,
+    is_system BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+INSERT INTO roles_new
+    (id, tenant_id, name, created_at, updated_at, deleted_at, version, description, is_system)
+SELECT id, tenant_id, name, created_at, updated_at, deleted_at, version, description, is_system
+FROM roles;
+
+DROP TABLE roles;
+ALTER TABLE roles_new RENAME TO roles;
+
+CREATE UNIQUE INDEX role_name_unique ON roles (tenant_id, name) WHERE deleted_at IS NULL;
+
+CREATE TABLE user_roles (
+    user_id TEXT NOT NULL,
+    role_id TEXT NOT NULL,
+    tenant_id TEXT NOT NULL REFERENCES tenants(id),
+    granted_at TEXT NOT NULL,
+    expires_at TEXT NULL,
+    granted_by TEXT NULL,
+    CONSTRAINT user_roles_pk PRIMARY KEY (user_id, role_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (role_id) REFERENCES roles(id) ON DELETE CASCADE
+);
+
+INSERT INTO user_roles (user_id, role_id, tenant_id, granted_at, expires_at, granted_by)
+SELECT user_id, role_id, tenant_id, granted_at, expires_at, granted_by
+FROM user_roles_old;
+
+DROP TABLE user_roles_old;
+
+CREATE INDEX idx_user_roles_role_id ON user_roles(role_id);
+CREATE INDEX idx_user_roles_expires_at ON user_roles(expires_at);
+CREATE INDEX idx_user_roles_tenant_id ON user_roles(tenant_id);
+
+CREATE TABLE group_roles (
+    group_id TEXT NOT NULL,
+    role_id TEXT NOT NULL,
+    tenant_id TEXT NOT NULL,
+    CONSTRAINT group_roles_pk PRIMARY KEY (group_id, role_id),
+    FOREIGN KEY (group_id) REFERENCES user_groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (role_id) REFERENCES roles(id) ON DELETE CASCADE
+);
+
+INSERT INTO group_roles (group_id, role_id, tenant_id)
+SELECT group_id, role_id, tenant_id
+FROM group_roles_old;
+
+DROP TABLE group_roles_old;
+
+CREATE INDEX idx_group_roles_role_id ON group_roles(role_id);
@@ -1,17 +1,205 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+/// Tenant that owns a set of users and roles; every read and write is scoped to one
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord,
+)]
+#[serde(transparent)]
+pub struct TenantId(pub Uuid);
+
+impl TenantId {
+    /// Tenant seeded by the migrations. Data from before multi-tenancy belongs to it, and
+    /// so do requests that don't name a tenant.
+    pub const DEFAULT: TenantId = TenantId(Uuid::nil());
+
+    pub fn as_uuid(&self) -> Uuid {
+        self.0
+    }
+}
+
+impl From<Uuid> for TenantId {
+    fn from(id: Uuid) -> Self {
+        Self(id)
+    }
+}
+
+impl FromStr for TenantId {
+    type Err = uuid::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Uuid::parse_str(s).map(Self)
+    }
+}
+
+impl fmt::Display for TenantId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Tenant {
+    pub id: TenantId,
+    /// Short unique handle, e.g. `acme`
+    pub slug: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Role {
     pub id: Uuid,
     pub name: String,
+    pub description: Option<String>,
+    /// Seeded role that cannot be renamed or deleted
+    pub is_system: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub version: i64,
+}
+
+/// Group of users mirrored from a Keycloak group; members inherit its roles
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Group {
+    pub id: Uuid,
+    /// Id of the group in the tenant's Keycloak realm
+    pub keycloak_id: String,
+    pub name: String,
+    pub roles: Vec<Role>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Terms of a role assignment; the default grant is permanent and anonymous
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RoleGrant {
+    /// The assignment stops counting at this instant and is swept afterwards
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Who granted the role, recorded for auditing
+    pub granted_by: Option<String>,
+}
+
+impl RoleGrant {
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+/// Credential of a service account (machine client), granting a fixed set of scopes in one
+/// tenant. Only a hash of the key is stored.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub tenant: TenantId,
+    pub name: String,
+    /// Leading characters of the key, enough to recognize it without revealing it
+    pub prefix: String,
+    pub scopes: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    /// When the key was last replaced with a new one
+    pub rotated_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl ApiKey {
+    /// Neither revoked nor expired at `now`
+    pub fn is_active_at(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+}
+
+/// What an admin chooses when creating an API key
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NewApiKey {
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Application-specific attributes stored locally next to the Keycloak profile
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct UserAttributes {
+    pub department: Option<String>,
+    pub cost_center: Option<String>,
+    /// BCP 47 language tag, e.g. `en-US`
+    pub locale: Option<String>,
+    /// Free-form JSON object
+    pub metadata: Option<serde_json::Map<String, serde_json::Value>>,
 }
 
+/// Change to a user's attributes: a `None` field is left as is, `Some(None)` clears it.
+/// `metadata` is replaced as a whole.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UserAttributesPatch {
+    pub department: Option<Option<String>>,
+    pub cost_center: Option<Option<String>>,
+    pub locale: Option<Option<String>>,
+    pub metadata: Option<Option<serde_json::Map<String, serde_json::Value>>>,
+}
+
+/// Why and since when a user is suspended
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UserSuspension {
+    pub suspended_at: DateTime<Utc>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct User {
     pub id: Uuid,
     pub keycloak_id: String,
     pub roles: Vec<Role>,
+    pub attributes: UserAttributes,
+    /// `None` while the user is active
+    #[serde(default)]
+    pub suspension: Option<UserSuspension>,
+    /// Most recent Keycloak login, known once the event poller has seen one
+    #[serde(default)]
+    pub last_login_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub version: i64,
+}
+
+/// Whether a user is suspended
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UserStatus {
+    Active,
+    Suspended,
+}
+
+/// Exact-match filters for user lists; `None` fields match every user
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UserFilter {
+    pub department: Option<String>,
+    pub cost_center: Option<String>,
+    pub locale: Option<String>,
+    pub status: Option<UserStatus>,
+}
+
+impl UserFilter {
+    pub fn is_empty(&self) -> bool {
+        self.department.is_none()
+            && self.cost_center.is_none()
+            && self.locale.is_none()
+            && self.status.is_none()
+    }
+
+    /// `status` as the bound value of `suspended_at IS NOT NULL`
+    pub fn suspended(&self) -> Option<bool> {
+        self.status.map(|status| status == UserStatus::Suspended)
+    }
 }
 
 pub const DEFAULT_PAGE: u32 = 1;
@@ -12,9 +12,21 @@ pub enum UserServiceError {
     #[error("user already has role")]
     UserAlreadyHasRole,
 
+    #[error("tenant slug already exists")]
+    TenantSlugAlreadyExists,
+
+    #[error("group name already exists")]
+    GroupNameAlreadyExists,
+
     #[error("resource not found")]
     NotFound,
 
+    #[error("resource was modified by someone else")]
+    VersionConflict,
+
+    #[error("system roles cannot be renamed or deleted")]
+    SystemRole,
+
     #[error("invalid UUID in database: {0}")]
     InvalidUuid(String),
 
@@ -31,7 +43,12 @@ impl From<UserRepositoryError> for UserServiceError {
             UserRepositoryError::EmailAlreadyExists => UserServiceError::EmailAlreadyExists,
             UserRepositoryError::RoleNameAlreadyExists => UserServiceError::RoleNameAlreadyExists,
             UserRepositoryError::UserAlreadyHasRole => UserServiceError::UserAlreadyHasRole,
+            UserRepositoryError::TenantSlugAlreadyExists => {
+                UserServiceError::TenantSlugAlreadyExists
+            }
+            UserRepositoryError::GroupNameAlreadyExists => UserServiceError::GroupNameAlreadyExists,
             UserRepositoryError::NotFound => UserServiceError::NotFound,
+            UserRepositoryError::VersionConflict => UserServiceError::VersionConflict,
             UserRepositoryError::Sqlx(e) => UserServiceError::Internal(e.into()),
         }
     }
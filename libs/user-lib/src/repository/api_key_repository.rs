@@ -0,0 +1,163 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{query, query_as, MySqlPool};
+use uuid::Uuid;
+
+use crate::entities::TenantId;
+use crate::repository::errors::{map_sqlx_error, UserRepositoryError};
+use crate::repository::models::ApiKeyRow;
+use crate::repository::traits::ApiKeyRepositoryTrait;
+
+pub(crate) const API_KEY_COLUMNS: &str = "id, tenant_id, name, key_prefix, key_hash, scopes, \
+     created_at, expires_at, last_used_at, rotated_at, revoked_at";
+
+/// Runs straight on the pool: keys are checked on every request, outside any unit of work
+#[derive(Debug, Clone)]
+pub struct ApiKeyRepository {
+    pool: MySqlPool,
+}
+
+impl ApiKeyRepository {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepositoryTrait for ApiKeyRepository {
+    async fn create_api_key(
+        &self,
+        tenant: TenantId,
+        name: &str,
+        key_prefix: &str,
+        key_hash: &str,
+        scopes: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<ApiKeyRow, UserRepositoryError> {
+        let id = Uuid::new_v4();
+        query(
+            r#"
+            INSERT INTO api_keys
+                (id, tenant_id, name, key_prefix, key_hash, scopes, created_at, expires_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(id.to_string())
+        .bind(tenant.to_string())
+        .bind(name)
+        .bind(key_prefix)
+        .bind(key_hash)
+        .bind(scopes)
+        .bind(Utc::now())
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        self.get_api_key(tenant, id)
+            .await?
+            .ok_or(UserRepositoryError::NotFound)
+    }
+
+    async fn get_api_key(
+        &self,
+        tenant: TenantId,
+        key_id: Uuid,
+    ) -> Result<Option<ApiKeyRow>, UserRepositoryError> {
+        query_as::<_, ApiKeyRow>(&format!(
+            "SELECT {API_KEY_COLUMNS} FROM api_keys WHERE id = ? AND tenant_id = ?"
+        ))
+        .bind(key_id.to_string())
+        .bind(tenant.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(map_sqlx_error)
+    }
+
+    async fn get_api_keys(&self, tenant: TenantId) -> Result<Vec<ApiKeyRow>, UserRepositoryError> {
+        query_as::<_, ApiKeyRow>(&format!(
+            "SELECT {API_KEY_COLUMNS} FROM api_keys WHERE tenant_id = ? ORDER BY created_at DESC"
+        ))
+        .bind(tenant.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_sqlx_error)
+    }
+
+    async fn get_api_key_by_hash(
+        &self,
+        key_hash: &str,
+    ) -> Result<Option<ApiKeyRow>, UserRepositoryError> {
+        query_as::<_, ApiKeyRow>(&format!(
+            "SELECT {API_KEY_COLUMNS} FROM api_keys WHERE key_hash = ?"
+        ))
+        .bind(key_hash)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(map_sqlx_error)
+    }
+
+    async fn rotate_api_key(
+        &self,
+        tenant: TenantId,
+        key_id: Uuid,
+        key_prefix: &str,
+        key_hash: &str,
+    ) -> Result<ApiKeyRow, UserRepositoryError> {
+        let result = query(
+            r#"
+            UPDATE api_keys SET key_prefix = ?, key_hash = ?, rotated_at = ?
+            WHERE id = ? AND tenant_id = ? AND revoked_at IS NULL
+            "#,
+        )
+        .bind(key_prefix)
+        .bind(key_hash)
+        .bind(Utc::now())
+        .bind(key_id.to_string())
+        .bind(tenant.to_string())
+        .execute(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+        if result.rows_affected() == 0 {
+            return Err(UserRepositoryError::NotFound);
+        }
+
+        self.get_api_key(tenant, key_id)
+            .await?
+            .ok_or(UserRepositoryError::NotFound)
+    }
+
+    async fn revoke_api_key(
+        &self,
+        tenant: TenantId,
+        key_id: Uuid,
+    ) -> Result<bool, UserRepositoryError> {
+        let result = query(
+            r#"
+            UPDATE api_keys SET revoked_at = ?
+            WHERE id = ? AND tenant_id = ? AND revoked_at IS NULL
+            "#,
+        )
+        .bind(Utc::now())
+        .bind(key_id.to_string())
+        .bind(tenant.to_string())
+        .execute(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn record_api_key_use(
+        &self,
+        key_id: Uuid,
+        used_at: DateTime<Utc>,
+    ) -> Result<(), UserRepositoryError> {
+        query("UPDATE api_keys SET last_used_at = ? WHERE id = ?")
+            .bind(used_at)
+            .bind(key_id.to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+        Ok(())
+    }
+}
@@ -3,7 +3,11 @@ pub enum UserRepositoryError {
     EmailAlreadyExists,
     RoleNameAlreadyExists,
     UserAlreadyHasRole,
+    TenantSlugAlreadyExists,
+    GroupNameAlreadyExists,
     NotFound,
+    /// The row exists but no longer has the version the caller expected
+    VersionConflict,
     Sqlx(sqlx::Error),
 }
 
@@ -13,7 +17,10 @@ impl std::fmt::Display for UserRepositoryError {
             UserRepositoryError::EmailAlreadyExists => write!(f, "email already exists"),
             UserRepositoryError::RoleNameAlreadyExists => write!(f, "role name already exists"),
             UserRepositoryError::UserAlreadyHasRole => write!(f, "user already has role"),
+            UserRepositoryError::TenantSlugAlreadyExists => write!(f, "tenant slug already exists"),
+            UserRepositoryError::GroupNameAlreadyExists => write!(f, "group name already exists"),
             UserRepositoryError::NotFound => write!(f, "not found"),
+            UserRepositoryError::VersionConflict => write!(f, "version conflict"),
             UserRepositoryError::Sqlx(e) => write!(f, "{e}"),
         }
     }
@@ -25,7 +32,10 @@ impl std::error::Error for UserRepositoryError {
             UserRepositoryError::EmailAlreadyExists => None,
             UserRepositoryError::RoleNameAlreadyExists => None,
             UserRepositoryError::UserAlreadyHasRole => None,
+            UserRepositoryError::TenantSlugAlreadyExists => None,
+            UserRepositoryError::GroupNameAlreadyExists => None,
             UserRepositoryError::NotFound => None,
+            UserRepositoryError::VersionConflict => None,
             UserRepositoryError::Sqlx(e) => Some(e),
         }
     }
@@ -46,12 +56,62 @@ fn extract_mysql_key_name(msg_lower: &str) -> Option<String> {
     Some(rest[..end].to_string())
 }
 
-pub fn map_sqlx_error(err: sqlx::Error) -> UserRepositoryError {
-    const USER_EMAIL_UNIQUE: &str = "user_email_unique";
-    const ROLE_NAME_UNIQUE: &str = "role_name_unique";
-    const USER_ROLES_PK: &str = "user_roles_pk";
+const USER_EMAIL_UNIQUE: &str = "user_email_unique";
+const ROLE_NAME_UNIQUE: &str = "role_name_unique";
+const USER_ROLES_PK: &str = "user_roles_pk";
+const TENANT_SLUG_UNIQUE: &str = "tenant_slug_unique";
+const GROUP_NAME_UNIQUE: &str = "group_name_unique";
+
+/// Map a violated constraint name to its typed error
+fn error_for_constraint(name: &str) -> Option<UserRepositoryError> {
+    if name.ends_with(USER_EMAIL_UNIQUE) {
+        Some(UserRepositoryError::EmailAlreadyExists)
+    } else if name.ends_with(ROLE_NAME_UNIQUE) {
+        Some(UserRepositoryError::RoleNameAlreadyExists)
+    } else if name.ends_with(USER_ROLES_PK) {
+        Some(UserRepositoryError::UserAlreadyHasRole)
+    } else if name.ends_with(TENANT_SLUG_UNIQUE) {
+        Some(UserRepositoryError::TenantSlugAlreadyExists)
+    } else if name.ends_with(GROUP_NAME_UNIQUE) {
+        Some(UserRepositoryError::GroupNameAlreadyExists)
+    } else {
+        None
+    }
+}
+
+/// SQLite names the violated columns instead of the constraint:
+/// "UNIQUE constraint failed: user_roles.user_id, user_roles.role_id"
+fn error_for_sqlite_columns(msg_lower: &str) -> Option<UserRepositoryError> {
+    let columns = msg_lower.strip_prefix("unique constraint failed: ")?;
+    match columns.trim() {
+        "users.email" => Some(UserRepositoryError::EmailAlreadyExists),
+        "roles.tenant_id, roles.name" => Some(UserRepositoryError::RoleNameAlreadyExists),
+        "tenants.slug" => Some(UserRepositoryError::TenantSlugAlreadyExists),
+        "user_groups.tenant_id, user_groups.name" => {
+            Some(UserRepositoryError::GroupNameAlreadyExists)
+        }
+        "user_roles.user_id, user_roles.role_id" => Some(UserRepositoryError::UserAlreadyHasRole),
+        _ => None,
+    }
+}
 
+pub fn map_sqlx_error(err: sqlx::Error) -> UserRepositoryError {
     if let sqlx::Error::Database(db_err) = &err {
+        // PostgreSQL reports unique violations as SQLSTATE 23505 and names the
+        // violated constraint (or unique index) directly.
+        if db_err.code().as_deref() == Some("23505") {
+            if let Some(mapped) = db_err.constraint().and_then(error_for_constraint) {
+                return mapped;
+            }
+        }
+
+        // SQLite reports SQLITE_CONSTRAINT_UNIQUE (2067) or SQLITE_CONSTRAINT_PRIMARYKEY (1555)
+        if matches!(db_err.code().as_deref(), Some("2067") | Some("1555")) {
+            if let Some(mapped) = error_for_sqlite_columns(&db_err.message().to_lowercase()) {
+                return mapped;
+            }
+        }
+
         // MySQL duplicate key violations typically surface as:
         // - SQLSTATE code: 23000 (integrity constraint violation)
         // - message: "Duplicate entry '...' for key '...'"
@@ -84,8 +144,152 @@ pub fn map_sqlx_error(err: sqlx::Error) -> UserRepositoryError {
             if key.ends_with(USER_ROLES_PK) || msg.contains(USER_ROLES_PK) {
                 return UserRepositoryError::UserAlreadyHasRole;
             }
+
+            if key.ends_with(TENANT_SLUG_UNIQUE) || msg.contains(TENANT_SLUG_UNIQUE) {
+                return UserRepositoryError::TenantSlugAlreadyExists;
+            }
+
+            if key.ends_with(GROUP_NAME_UNIQUE) || msg.contains(GROUP_NAME_UNIQUE) {
+                return UserRepositoryError::GroupNameAlreadyExists;
+            }
         }
     }
 
     UserRepositoryError::Sqlx(err)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::error::{DatabaseError, ErrorKind};
+    use std::borrow::Cow;
+
+    #[derive(Debug)]
+    struct FakeDbError {
+        code: &'static str,
+        message: &'static str,
+        constraint: Option<&'static str>,
+    }
+
+    impl std::fmt::Display for FakeDbError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self.message)
+        }
+    }
+
+    impl std::error::Error for FakeDbError {}
+
+    impl DatabaseError for FakeDbError {
+        fn message(&self) -> &str {
+            self.message
+        }
+
+        fn code(&self) -> Option<Cow<'_, str>> {
+            Some(Cow::Borrowed(self.code))
+        }
+
+        fn constraint(&self) -> Option<&str> {
+            self.constraint
+        }
+
+        fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            self
+        }
+
+        fn kind(&self) -> ErrorKind {
+            ErrorKind::UniqueViolation
+        }
+    }
+
+    fn db_error(
+        code: &'static str,
+        message: &'static str,
+        constraint: Option<&'static str>,
+    ) -> sqlx::Error {
+        sqlx::Error::database(FakeDbError {
+            code,
+            message,
+            constraint,
+        })
+    }
+
+    #[test]
+    fn test_maps_mysql_duplicate_entry() {
+        let err = db_error(
+            "23000",
+            "Duplicate entry 'admin' for key 'roles.role_name_unique'",
+            None,
+        );
+        assert!(matches!(
+            map_sqlx_error(err),
+            UserRepositoryError::RoleNameAlreadyExists
+        ));
+    }
+
+    #[test]
+    fn test_maps_postgres_unique_violation_by_constraint() {
+        let err = db_error(
+            "23505",
+            "duplicate key value violates unique constraint \"user_roles_pk\"",
+            Some("user_roles_pk"),
+        );
+        assert!(matches!(
+            map_sqlx_error(err),
+            UserRepositoryError::UserAlreadyHasRole
+        ));
+
+        let err = db_error(
+            "23505",
+            "duplicate key value violates unique constraint \"role_name_unique\"",
+            Some("role_name_unique"),
+        );
+        assert!(matches!(
+            map_sqlx_error(err),
+            UserRepositoryError::RoleNameAlreadyExists
+        ));
+    }
+
+    #[test]
+    fn test_maps_sqlite_unique_constraint_by_columns() {
+        let err = db_error(
+            "1555",
+            "UNIQUE constraint failed: user_roles.user_id, user_roles.role_id",
+            None,
+        );
+        assert!(matches!(
+            map_sqlx_error(err),
+            UserRepositoryError::UserAlreadyHasRole
+        ));
+
+        let err = db_error(
+            "2067",
+            "UNIQUE constraint failed: roles.tenant_id, roles.name",
+            None,
+        );
+        assert!(matches!(
+            map_sqlx_error(err),
+            UserRepositoryError::RoleNameAlreadyExists
+        ));
+
+        let err = db_error("2067", "UNIQUE constraint failed: users.keycloak_id", None);
+        assert!(matches!(map_sqlx_error(err), UserRepositoryError::Sqlx(_)));
+    }
+
+    #[test]
+    fn test_unknown_constraint_is_not_mapped() {
+        let err = db_error(
+            "23505",
+            "duplicate key value violates unique constraint \"user_keycloak_id_unique\"",
+            Some("user_keycloak_id_unique"),
+        );
+        assert!(matches!(map_sqlx_error(err), UserRepositoryError::Sqlx(_)));
+    }
+}
@@ -0,0 +1,60 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::{query, query_scalar, MySqlPool};
+
+use crate::entities::TenantId;
+use crate::repository::errors::{map_sqlx_error, UserRepositoryError};
+use crate::repository::traits::EventCheckpointRepositoryTrait;
+
+/// Runs straight on the pool: checkpoints are saved independently of any unit of work
+#[derive(Debug, Clone)]
+pub struct EventCheckpointRepository {
+    pool: MySqlPool,
+}
+
+impl EventCheckpointRepository {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl EventCheckpointRepositoryTrait for EventCheckpointRepository {
+    async fn get_event_checkpoint(
+        &self,
+        tenant: TenantId,
+        source: &str,
+    ) -> Result<Option<i64>, UserRepositoryError> {
+        query_scalar::<_, i64>(
+            "SELECT event_time FROM keycloak_event_checkpoints WHERE tenant_id = ? AND source = ?",
+        )
+        .bind(tenant.to_string())
+        .bind(source)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(map_sqlx_error)
+    }
+
+    async fn save_event_checkpoint(
+        &self,
+        tenant: TenantId,
+        source: &str,
+        event_time: i64,
+    ) -> Result<(), UserRepositoryError> {
+        query(
+            r#"
+            INSERT INTO keycloak_event_checkpoints (tenant_id, source, event_time, updated_at)
+            VALUES (?, ?, ?, ?)
+            ON DUPLICATE KEY UPDATE event_time = VALUES(event_time), updated_at = VALUES(updated_at)
+            "#,
+        )
+        .bind(tenant.to_string())
+        .bind(source)
+        .bind(event_time)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+        Ok(())
+    }
+}
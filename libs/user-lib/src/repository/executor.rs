@@ -0,0 +1,92 @@
+//! Where repository queries run: straight on the pool, or inside a transaction
+//! shared by the repositories of one unit of work.
+
+use sqlx::pool::PoolConnection;
+use sqlx::{Database, Pool, Transaction};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use tokio::sync::{MappedMutexGuard, Mutex, MutexGuard};
+
+/// Transaction shared by the repositories of a unit of work. It is taken out
+/// (set to `None`) once committed or rolled back.
+pub type SharedTransaction<DB> = Arc<Mutex<Option<Transaction<'static, DB>>>>;
+
+pub enum DbExecutor<DB: Database> {
+    Pool(Pool<DB>),
+    Transaction(SharedTransaction<DB>),
+}
+
+impl<DB: Database> Clone for DbExecutor<DB> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Pool(pool) => Self::Pool(pool.clone()),
+            Self::Transaction(tx) => Self::Transaction(tx.clone()),
+        }
+    }
+}
+
+impl<DB: Database> std::fmt::Debug for DbExecutor<DB> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pool(pool) => f.debug_tuple("Pool").field(pool).finish(),
+            Self::Transaction(_) => f.write_str("Transaction"),
+        }
+    }
+}
+
+impl<DB: Database> DbExecutor<DB> {
+    /// Connection to run the next queries on. In transaction mode this holds the
+    /// transaction lock until dropped, so statements of one unit of work never interleave.
+    pub async fn acquire(&self) -> Result<DbConnection<'_, DB>, sqlx::Error> {
+        match self {
+            Self::Pool(pool) => Ok(DbConnection::Pool(pool.acquire().await?)),
+            Self::Transaction(tx) => MutexGuard::try_map(tx.lock().await, |tx| tx.as_mut())
+                .map(DbConnection::Transaction)
+                .map_err(|_| {
+                    sqlx::Error::Protocol("transaction already committed or rolled back".into())
+                }),
+        }
+    }
+}
+
+pub enum DbConnection<'a, DB: Database> {
+    Pool(PoolConnection<DB>),
+    Transaction(MappedMutexGuard<'a, Transaction<'static, DB>>),
+}
+
+impl<DB: Database> Deref for DbConnection<'_, DB> {
+    type Target = DB::Connection;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Pool(conn) => conn,
+            Self::Transaction(tx) => tx,
+        }
+    }
+}
+
+impl<DB: Database> DerefMut for DbConnection<'_, DB> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            Self::Pool(conn) => conn,
+            Self::Transaction(tx) => tx,
+        }
+    }
+}
+
+/// Begin a transaction that several repositories can share
+pub async fn begin_shared<DB: Database>(
+    pool: &Pool<DB>,
+) -> Result<SharedTransaction<DB>, sqlx::Error> {
+    Ok(Arc::new(Mutex::new(Some(pool.begin().await?))))
+}
+
+/// Take the transaction out of its shared slot so it can be finished
+pub(crate) async fn take_shared<DB: Database>(
+    tx: &SharedTransaction<DB>,
+) -> Result<Transaction<'static, DB>, sqlx::Error> {
+    tx.lock()
+        .await
+        .take()
+        .ok_or_else(|| sqlx::Error::Protocol("transaction already committed or rolled back".into()))
+}
@@ -0,0 +1,107 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{query, query_as, MySqlPool};
+
+use crate::repository::errors::{map_sqlx_error, UserRepositoryError};
+use crate::repository::models::IdempotencyRow;
+use crate::repository::traits::IdempotencyRepositoryTrait;
+
+/// Runs straight on the pool: stored responses must outlive any unit of work
+#[derive(Debug, Clone)]
+pub struct IdempotencyRepository {
+    pool: MySqlPool,
+}
+
+impl IdempotencyRepository {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl IdempotencyRepositoryTrait for IdempotencyRepository {
+    async fn reserve_idempotency_key(
+        &self,
+        key: &str,
+        fingerprint: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<bool, UserRepositoryError> {
+        let now = Utc::now();
+        query("DELETE FROM idempotency_keys WHERE expires_at <= ?")
+            .bind(now)
+            .execute(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        // The no-op update reports 0 affected rows when the key is already taken
+        let result = query(
+            r#"
+            INSERT INTO idempotency_keys (idempotency_key, fingerprint, created_at, expires_at)
+            VALUES (?, ?, ?, ?)
+            ON DUPLICATE KEY UPDATE idempotency_key = idempotency_key
+            "#,
+        )
+        .bind(key)
+        .bind(fingerprint)
+        .bind(now)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn get_idempotency_key(
+        &self,
+        key: &str,
+    ) -> Result<Option<IdempotencyRow>, UserRepositoryError> {
+        query_as::<_, IdempotencyRow>(
+            r#"
+            SELECT idempotency_key, fingerprint, status_code, response_body, response_headers,
+                   created_at, expires_at
+            FROM idempotency_keys
+            WHERE idempotency_key = ? AND expires_at > ?
+            "#,
+        )
+        .bind(key)
+        .bind(Utc::now())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(map_sqlx_error)
+    }
+
+    async fn complete_idempotency_key(
+        &self,
+        key: &str,
+        status_code: i32,
+        response_body: &str,
+        response_headers: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), UserRepositoryError> {
+        query(
+            r#"
+            UPDATE idempotency_keys
+            SET status_code = ?, response_body = ?, response_headers = ?, expires_at = ?
+            WHERE idempotency_key = ?
+            "#,
+        )
+        .bind(status_code)
+        .bind(response_body)
+        .bind(response_headers)
+        .bind(expires_at)
+        .bind(key)
+        .execute(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+        Ok(())
+    }
+
+    async fn release_idempotency_key(&self, key: &str) -> Result<(), UserRepositoryError> {
+        query("DELETE FROM idempotency_keys WHERE idempotency_key = ? AND status_code IS NULL")
+            .bind(key)
+            .execute(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+        Ok(())
+    }
+}
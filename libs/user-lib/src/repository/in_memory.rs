@@ -0,0 +1,1590 @@
+//! In-memory implementations of the repository traits for fast tests.
+//!
+//! The user, role, user-role, tenant and group repositories share one [`InMemoryStore`] so
+//! that foreign keys and `ON DELETE CASCADE` behave as they do in MySQL. Errors mirror what the MySQL
+//! repositories return after `map_sqlx_error`: named unique constraints become typed
+//! variants, everything else surfaces as `UserRepositoryError::Sqlx`.
+//!
+//! Soft-deleted rows stay in the tables with `deleted_at` set and are skipped by
+//! every read, like the `deleted_at IS NULL` filters of the SQL repositories.
+//!
+//! A unit of work runs on a private copy of the tables and replaces the shared
+//! tables on commit. Concurrent units of work are not merged: the last commit wins.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard};
+use uuid::Uuid;
+
+use crate::entities::{PaginationParams, RoleGrant, TenantId, UserFilter, UserSuspension};
+use crate::repository::errors::UserRepositoryError;
+use crate::repository::models::{
+    ApiKeyRow, GroupRoleMapping, GroupRow, IdempotencyRow, RoleRow, TenantRow, UserAttributesRow,
+    UserRoleMapping, UserRow,
+};
+use crate::repository::tenant_repository::SYSTEM_ROLES;
+use crate::repository::traits::{
+    ApiKeyRepositoryTrait, EventCheckpointRepositoryTrait, GroupRepositoryTrait,
+    IdempotencyRepositoryTrait, RoleRepositoryTrait, TenantRepositoryTrait, UserRepositoryTrait,
+    UserRoleRepositoryTrait,
+};
+use crate::repository::unit_of_work::{UnitOfWork, UnitOfWorkFactory};
+
+/// System roles seeded by `0001_init.sql` and described by `0006_role_metadata.sql`
+const DEFAULT_ROLES: [(&str, &str, &str); 2] = [
+    (
+        "00000000-0000-0000-0000-000000000001",
+        "admin",
+        "Full administrative access",
+    ),
+    (
+        "00000000-0000-0000-0000-000000000002",
+        "user",
+        "Default role for regular users",
+    ),
+];
+
+#[derive(Debug, Clone, Default)]
+struct Tables {
+    /// users keyed by id; BTreeMap gives the same `ORDER BY id` as MySQL
+    users: BTreeMap<String, UserRow>,
+    roles: BTreeMap<String, RoleRow>,
+    /// grants keyed by the (user_id, role_id) primary key
+    user_roles: BTreeMap<(String, String), RoleGrant>,
+    /// keyed by user_id
+    user_attributes: HashMap<String, UserAttributesRow>,
+    tenants: BTreeMap<String, TenantRow>,
+    groups: BTreeMap<String, GroupRow>,
+    /// (group_id, user_id) primary keys of `group_members`
+    group_members: BTreeSet<(String, String)>,
+    /// (group_id, role_id) primary keys of `group_roles`
+    group_roles: BTreeSet<(String, String)>,
+    /// the `tenant_id` column of users, roles and groups, keyed by row id
+    owners: HashMap<String, TenantId>,
+}
+
+impl Tables {
+    /// Whether the user or role with this id belongs to `tenant`
+    fn owned_by(&self, tenant: TenantId, id: &str) -> bool {
+        self.owners.get(id) == Some(&tenant)
+    }
+
+    fn live_user(&self, tenant: TenantId, id: &str) -> Option<&UserRow> {
+        self.users
+            .get(id)
+            .filter(|u| u.deleted_at.is_none() && self.owned_by(tenant, id))
+    }
+
+    fn live_role(&self, tenant: TenantId, id: &str) -> Option<&RoleRow> {
+        self.roles
+            .get(id)
+            .filter(|r| r.deleted_at.is_none() && self.owned_by(tenant, id))
+    }
+
+    /// Whether a live role of `tenant` other than `except` has this name, like the
+    /// `role_name_unique` index, which only covers live roles
+    fn role_name_taken(&self, tenant: TenantId, name: &str, except: Option<&str>) -> bool {
+        self.roles.values().any(|r| {
+            Some(r.id.as_str()) != except
+                && r.deleted_at.is_none()
+                && self.owned_by(tenant, &r.id)
+                && same_name(&r.name, name)
+        })
+    }
+
+    fn insert_role(&mut self, tenant: TenantId, row: RoleRow) {
+        self.owners.insert(row.id.clone(), tenant);
+        self.roles.insert(row.id.clone(), row);
+    }
+
+    /// (user_id, role_id) pairs whose grant has not expired
+    fn active_user_roles(&self) -> impl Iterator<Item = &(String, String)> {
+        let now = Utc::now();
+        self.user_roles
+            .iter()
+            .filter(move |(_, grant)| !grant.is_expired_at(now))
+            .map(|(key, _)| key)
+    }
+
+    /// Ids of the roles the user holds through unexpired grants or its groups, once each
+    fn held_role_ids<'a>(&'a self, user_id: &'a str) -> Vec<&'a String> {
+        let direct = self
+            .active_user_roles()
+            .filter(move |(u, _)| u == user_id)
+            .map(|(_, r)| r);
+        let inherited = self
+            .group_members
+            .iter()
+            .filter(move |(_, u)| u == user_id)
+            .flat_map(|(g, _)| {
+                self.group_roles
+                    .iter()
+                    .filter(move |(group, _)| group == g)
+                    .map(|(_, r)| r)
+            });
+        let mut seen = BTreeSet::new();
+        direct
+            .chain(inherited)
+            .filter(|r| seen.insert(*r))
+            .collect()
+    }
+}
+
+/// Shared tables behind the in-memory repositories
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryStore {
+    tables: Arc<Mutex<Tables>>,
+}
+
+impl InMemoryStore {
+    /// Empty store, without the roles seeded by the migrations
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store seeded with the default tenant and its `admin` and `user` roles, like a freshly
+    /// migrated database
+    pub fn with_default_roles() -> Self {
+        let store = Self::new();
+        {
+            let mut tables = store.lock();
+            let default = TenantId::DEFAULT.to_string();
+            tables.tenants.insert(
+                default.clone(),
+                TenantRow {
+                    id: default,
+                    slug: "default".to_string(),
+                    name: "Default".to_string(),
+                    created_at: Utc::now(),
+                },
+            );
+            for (id, name, description) in DEFAULT_ROLES {
+                tables.insert_role(
+                    TenantId::DEFAULT,
+                    RoleRow {
+                        id: id.to_string(),
+                        name: name.to_string(),
+                        version: 1,
+                        description: Some(description.to_string()),
+                        is_system: true,
+                        ..RoleRow::default()
+                    },
+                );
+            }
+        }
+        store
+    }
+
+    /// Repositories sharing this store
+    pub fn repositories(
+        &self,
+    ) -> (
+        InMemoryUserRepository,
+        InMemoryRoleRepository,
+        InMemoryUserRoleRepository,
+    ) {
+        (
+            InMemoryUserRepository::new(self.clone()),
+            InMemoryRoleRepository::new(self.clone()),
+            InMemoryUserRoleRepository::new(self.clone()),
+        )
+    }
+
+    /// Tenant repository sharing this store
+    pub fn tenant_repository(&self) -> InMemoryTenantRepository {
+        InMemoryTenantRepository::new(self.clone())
+    }
+
+    /// Group repository sharing this store
+    pub fn group_repository(&self) -> InMemoryGroupRepository {
+        InMemoryGroupRepository::new(self.clone())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Tables> {
+        self.tables.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Tables {
+    /// Apply what changed from `base` to `working` on top of these tables, so rows that
+    /// others wrote in the meantime are kept
+    fn apply_changes(&mut self, base: &Tables, working: &Tables) {
+        self.users.apply_changes(&base.users, &working.users);
+        self.roles.apply_changes(&base.roles, &working.roles);
+        self.user_roles
+            .apply_changes(&base.user_roles, &working.user_roles);
+        self.user_attributes
+            .apply_changes(&base.user_attributes, &working.user_attributes);
+        self.tenants.apply_changes(&base.tenants, &working.tenants);
+        self.groups.apply_changes(&base.groups, &working.groups);
+        self.group_members
+            .apply_changes(&base.group_members, &working.group_members);
+        self.group_roles
+            .apply_changes(&base.group_roles, &working.group_roles);
+        self.owners.apply_changes(&base.owners, &working.owners);
+    }
+}
+
+/// A table whose rows a unit of work can write back
+trait Table {
+    /// Insert or overwrite the rows `working` added or changed since `base`, and remove
+    /// the rows it removed
+    fn apply_changes(&mut self, base: &Self, working: &Self);
+}
+
+impl<K: Ord + Clone, V: Clone + PartialEq> Table for BTreeMap<K, V> {
+    fn apply_changes(&mut self, base: &Self, working: &Self) {
+        for (key, row) in working {
+            if base.get(key) != Some(row) {
+                self.insert(key.clone(), row.clone());
+            }
+        }
+        for key in base.keys().filter(|key| !working.contains_key(*key)) {
+            self.remove(key);
+        }
+    }
+}
+
+impl<K: Eq + std::hash::Hash + Clone, V: Clone + PartialEq> Table for HashMap<K, V> {
+    fn apply_changes(&mut self, base: &Self, working: &Self) {
+        for (key, row) in working {
+            if base.get(key) != Some(row) {
+                self.insert(key.clone(), row.clone());
+            }
+        }
+        for key in base.keys().filter(|key| !working.contains_key(*key)) {
+            self.remove(key);
+        }
+    }
+}
+
+impl<K: Ord + Clone> Table for BTreeSet<K> {
+    fn apply_changes(&mut self, base: &Self, working: &Self) {
+        self.extend(working.difference(base).cloned());
+        for key in base.difference(working) {
+            self.remove(key);
+        }
+    }
+}
+
+#[async_trait]
+impl UnitOfWorkFactory for InMemoryStore {
+    async fn begin(&self) -> Result<Box<dyn UnitOfWork>, UserRepositoryError> {
+        let base = self.lock().clone();
+        let working = InMemoryStore {
+            tables: Arc::new(Mutex::new(base.clone())),
+        };
+        let (users, roles, user_roles) = working.repositories();
+        let tenants = working.tenant_repository();
+        Ok(Box::new(InMemoryUnitOfWork {
+            target: self.clone(),
+            base,
+            working,
+            users,
+            roles,
+            user_roles,
+            tenants,
+        }))
+    }
+}
+
+/// Writes go to a private copy of the tables; committing applies only what changed
+/// since `base` to the shared store
+struct InMemoryUnitOfWork {
+    target: InMemoryStore,
+    /// The tables as they were when the unit of work began
+    base: Tables,
+    working: InMemoryStore,
+    users: InMemoryUserRepository,
+    roles: InMemoryRoleRepository,
+    user_roles: InMemoryUserRoleRepository,
+    tenants: InMemoryTenantRepository,
+}
+
+#[async_trait]
+impl UnitOfWork for InMemoryUnitOfWork {
+    fn users(&self) -> &dyn UserRepositoryTrait {
+        &self.users
+    }
+
+    fn roles(&self) -> &dyn RoleRepositoryTrait {
+        &self.roles
+    }
+
+    fn user_roles(&self) -> &dyn UserRoleRepositoryTrait {
+        &self.user_roles
+    }
+
+    fn tenants(&self) -> &dyn TenantRepositoryTrait {
+        &self.tenants
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), UserRepositoryError> {
+        let working = self.working.lock();
+        self.target.lock().apply_changes(&self.base, &working);
+        Ok(())
+    }
+
+    async fn rollback(self: Box<Self>) -> Result<(), UserRepositoryError> {
+        Ok(())
+    }
+}
+
+/// Error for constraint violations MySQL reports without a dedicated mapping
+fn constraint_error(message: String) -> UserRepositoryError {
+    UserRepositoryError::Sqlx(sqlx::Error::Protocol(message))
+}
+
+/// MySQL's default collation compares role and group names case-insensitively
+fn same_name(a: &str, b: &str) -> bool {
+    a.to_lowercase() == b.to_lowercase()
+}
+
+/// Rows with the timestamp, version and soft-delete columns
+trait Tracked {
+    fn deleted_at(&mut self) -> &mut Option<DateTime<Utc>>;
+    fn updated_at(&mut self) -> &mut DateTime<Utc>;
+    fn version(&mut self) -> &mut i64;
+
+    /// Record a write, like `SET updated_at = ?, version = version + 1`
+    fn touch(&mut self) {
+        *self.updated_at() = Utc::now();
+        *self.version() += 1;
+    }
+}
+
+impl Tracked for UserRow {
+    fn deleted_at(&mut self) -> &mut Option<DateTime<Utc>> {
+        &mut self.deleted_at
+    }
+    fn updated_at(&mut self) -> &mut DateTime<Utc> {
+        &mut self.updated_at
+    }
+    fn version(&mut self) -> &mut i64 {
+        &mut self.version
+    }
+}
+
+impl Tracked for RoleRow {
+    fn deleted_at(&mut self) -> &mut Option<DateTime<Utc>> {
+        &mut self.deleted_at
+    }
+    fn updated_at(&mut self) -> &mut DateTime<Utc> {
+        &mut self.updated_at
+    }
+    fn version(&mut self) -> &mut i64 {
+        &mut self.version
+    }
+}
+
+fn mark_deleted(row: &mut impl Tracked) {
+    row.touch();
+    *row.deleted_at() = Some(*row.updated_at());
+}
+
+fn mark_restored(row: &mut impl Tracked) {
+    row.touch();
+    *row.deleted_at() = None;
+}
+
+fn paginate<T: Clone>(rows: &[T], pagination: PaginationParams) -> Vec<T> {
+    rows.iter()
+        .skip(pagination.offset() as usize)
+        .take(pagination.limit() as usize)
+        .cloned()
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct InMemoryUserRepository {
+    store: InMemoryStore,
+}
+
+impl InMemoryUserRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl UserRepositoryTrait for InMemoryUserRepository {
+    async fn create_user(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+    ) -> Result<UserRow, UserRepositoryError> {
+        let mut tables = self.store.lock();
+        if tables.users.values().any(|u| u.keycloak_id == keycloak_id) {
+            return Err(constraint_error(format!(
+                "Duplicate entry '{keycloak_id}' for key 'users.user_keycloak_id_unique'"
+            )));
+        }
+
+        let now = Utc::now();
+        let row = UserRow {
+            id: Uuid::new_v4().to_string(),
+            keycloak_id: keycloak_id.to_string(),
+            created_at: now,
+            updated_at: now,
+            version: 1,
+            deleted_at: None,
+            suspended_at: None,
+            suspension_reason: None,
+            last_login_at: None,
+        };
+        tables.owners.insert(row.id.clone(), tenant);
+        tables.users.insert(row.id.clone(), row.clone());
+        Ok(row)
+    }
+
+    async fn get_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<Option<UserRow>, UserRepositoryError> {
+        Ok(self
+            .store
+            .lock()
+            .live_user(tenant, &user_id.to_string())
+            .cloned())
+    }
+
+    async fn get_user_by_keycloak_id(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+    ) -> Result<Option<UserRow>, UserRepositoryError> {
+        let tables = self.store.lock();
+        Ok(tables
+            .users
+            .values()
+            .find(|u| {
+                u.keycloak_id == keycloak_id
+                    && u.deleted_at.is_none()
+                    && tables.owned_by(tenant, &u.id)
+            })
+            .cloned())
+    }
+
+    async fn get_user_tenant_by_keycloak_id(
+        &self,
+        keycloak_id: &str,
+    ) -> Result<Option<TenantId>, UserRepositoryError> {
+        let tables = self.store.lock();
+        Ok(tables
+            .users
+            .values()
+            .find(|u| u.keycloak_id == keycloak_id && u.deleted_at.is_none())
+            .and_then(|u| tables.owners.get(&u.id).copied()))
+    }
+
+    async fn delete_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<(), UserRepositoryError> {
+        let user_id = user_id.to_string();
+        let mut tables = self.store.lock();
+        let current = tables
+            .users
+            .get(&user_id)
+            .filter(|_| tables.owned_by(tenant, &user_id))
+            .map(|u| u.version);
+        match (current, expected_version) {
+            (None, Some(_)) => return Err(UserRepositoryError::NotFound),
+            (None, None) => return Ok(()),
+            (Some(version), Some(expected)) if version != expected => {
+                return Err(UserRepositoryError::VersionConflict)
+            }
+            _ => {}
+        }
+        tables.users.remove(&user_id);
+        tables.owners.remove(&user_id);
+        tables.user_roles.retain(|(u, _), _| *u != user_id);
+        tables.group_members.retain(|(_, u)| *u != user_id);
+        tables.user_attributes.remove(&user_id);
+        Ok(())
+    }
+
+    async fn soft_delete_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<bool, UserRepositoryError> {
+        let user_id = user_id.to_string();
+        let mut tables = self.store.lock();
+        let owned = tables.owned_by(tenant, &user_id);
+        let Some(user) = tables
+            .users
+            .get_mut(&user_id)
+            .filter(|u| owned && u.deleted_at.is_none())
+        else {
+            return Ok(false);
+        };
+        if expected_version.is_some_and(|v| v != user.version) {
+            return Err(UserRepositoryError::VersionConflict);
+        }
+        mark_deleted(user);
+        Ok(true)
+    }
+
+    async fn restore_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<bool, UserRepositoryError> {
+        let user_id = user_id.to_string();
+        let mut tables = self.store.lock();
+        let owned = tables.owned_by(tenant, &user_id);
+        let user = tables
+            .users
+            .get_mut(&user_id)
+            .filter(|u| owned && u.deleted_at.is_some());
+        Ok(user.map(mark_restored).is_some())
+    }
+
+    async fn touch_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<UserRow, UserRepositoryError> {
+        let user_id = user_id.to_string();
+        let mut tables = self.store.lock();
+        let owned = tables.owned_by(tenant, &user_id);
+        let user = tables
+            .users
+            .get_mut(&user_id)
+            .filter(|u| owned && u.deleted_at.is_none())
+            .ok_or(UserRepositoryError::NotFound)?;
+        if expected_version.is_some_and(|v| v != user.version) {
+            return Err(UserRepositoryError::VersionConflict);
+        }
+        user.touch();
+        Ok(user.clone())
+    }
+
+    async fn set_user_suspension(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        suspension: Option<UserSuspension>,
+        expected_version: Option<i64>,
+    ) -> Result<UserRow, UserRepositoryError> {
+        let user_id = user_id.to_string();
+        let mut tables = self.store.lock();
+        let owned = tables.owned_by(tenant, &user_id);
+        let user = tables
+            .users
+            .get_mut(&user_id)
+            .filter(|u| owned && u.deleted_at.is_none())
+            .ok_or(UserRepositoryError::NotFound)?;
+        if expected_version.is_some_and(|v| v != user.version) {
+            return Err(UserRepositoryError::VersionConflict);
+        }
+        user.suspended_at = suspension.as_ref().map(|s| s.suspended_at);
+        user.suspension_reason = suspension.and_then(|s| s.reason);
+        user.touch();
+        Ok(user.clone())
+    }
+
+    async fn record_user_login(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+        at: DateTime<Utc>,
+    ) -> Result<(), UserRepositoryError> {
+        let mut tables = self.store.lock();
+        let Some(user_id) = tables
+            .users
+            .values()
+            .find(|u| {
+                u.keycloak_id == keycloak_id
+                    && u.deleted_at.is_none()
+                    && tables.owned_by(tenant, &u.id)
+            })
+            .map(|u| u.id.clone())
+        else {
+            return Ok(());
+        };
+        if let Some(user) = tables.users.get_mut(&user_id) {
+            if user.last_login_at.is_none_or(|last| last < at) {
+                user.last_login_at = Some(at);
+            }
+        }
+        Ok(())
+    }
+
+    async fn get_users_paginated(
+        &self,
+        tenant: TenantId,
+        pagination: PaginationParams,
+        filter: &UserFilter,
+    ) -> Result<(Vec<UserRow>, u64), UserRepositoryError> {
+        let tables = self.store.lock();
+        let matches = |expected: &Option<String>, actual: Option<&Option<String>>| {
+            expected.is_none() || actual.is_some_and(|a| a == expected)
+        };
+        let users: Vec<UserRow> = tables
+            .users
+            .values()
+            .filter(|u| u.deleted_at.is_none() && tables.owned_by(tenant, &u.id))
+            .filter(|u| {
+                let attributes = tables.user_attributes.get(&u.id);
+                matches(&filter.department, attributes.map(|a| &a.department))
+                    && matches(&filter.cost_center, attributes.map(|a| &a.cost_center))
+                    && matches(&filter.locale, attributes.map(|a| &a.locale))
+                    && filter
+                        .suspended()
+                        .is_none_or(|suspended| suspended == u.suspended_at.is_some())
+            })
+            .cloned()
+            .collect();
+        Ok((paginate(&users, pagination), users.len() as u64))
+    }
+
+    async fn get_users_by_role_paginated(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+        pagination: PaginationParams,
+    ) -> Result<(Vec<UserRow>, u64), UserRepositoryError> {
+        let role_id = role_id.to_string();
+        let now = Utc::now();
+        let tables = self.store.lock();
+        let users: Vec<UserRow> = tables
+            .users
+            .values()
+            .filter(|u| u.deleted_at.is_none() && tables.owned_by(tenant, &u.id))
+            .filter(|u| {
+                tables
+                    .user_roles
+                    .get(&(u.id.clone(), role_id.clone()))
+                    .is_some_and(|grant| !grant.is_expired_at(now))
+            })
+            .cloned()
+            .collect();
+        Ok((paginate(&users, pagination), users.len() as u64))
+    }
+
+    async fn get_user_attributes(
+        &self,
+        tenant: TenantId,
+        user_ids: &[String],
+    ) -> Result<Vec<UserAttributesRow>, UserRepositoryError> {
+        let tables = self.store.lock();
+        Ok(user_ids
+            .iter()
+            .filter(|id| tables.owned_by(tenant, id))
+            .filter_map(|id| tables.user_attributes.get(id))
+            .cloned()
+            .collect())
+    }
+
+    async fn upsert_user_attributes(
+        &self,
+        tenant: TenantId,
+        attributes: &UserAttributesRow,
+    ) -> Result<(), UserRepositoryError> {
+        let mut tables = self.store.lock();
+        if !tables.users.contains_key(&attributes.user_id)
+            || !tables.owned_by(tenant, &attributes.user_id)
+        {
+            return Err(UserRepositoryError::NotFound);
+        }
+        tables
+            .user_attributes
+            .insert(attributes.user_id.clone(), attributes.clone());
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InMemoryRoleRepository {
+    store: InMemoryStore,
+}
+
+impl InMemoryRoleRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl RoleRepositoryTrait for InMemoryRoleRepository {
+    async fn create_role(
+        &self,
+        tenant: TenantId,
+        name: &str,
+        description: Option<String>,
+    ) -> Result<RoleRow, UserRepositoryError> {
+        let mut tables = self.store.lock();
+        if tables.role_name_taken(tenant, name, None) {
+            return Err(UserRepositoryError::RoleNameAlreadyExists);
+        }
+
+        let now = Utc::now();
+        let row = RoleRow {
+            id: Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            created_at: now,
+            updated_at: now,
+            version: 1,
+            deleted_at: None,
+            description,
+            is_system: false,
+        };
+        tables.insert_role(tenant, row.clone());
+        Ok(row)
+    }
+
+    async fn get_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+    ) -> Result<Option<RoleRow>, UserRepositoryError> {
+        Ok(self
+            .store
+            .lock()
+            .live_role(tenant, &role_id.to_string())
+            .cloned())
+    }
+
+    async fn get_system_role(
+        &self,
+        tenant: TenantId,
+        name: &str,
+    ) -> Result<Option<RoleRow>, UserRepositoryError> {
+        let tables = self.store.lock();
+        Ok(tables
+            .roles
+            .values()
+            .find(|r| {
+                r.is_system
+                    && r.deleted_at.is_none()
+                    && tables.owned_by(tenant, &r.id)
+                    && same_name(&r.name, name)
+            })
+            .cloned())
+    }
+
+    async fn update_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+        name: &str,
+        description: Option<String>,
+        expected_version: Option<i64>,
+    ) -> Result<RoleRow, UserRepositoryError> {
+        let role_id = role_id.to_string();
+        let mut tables = self.store.lock();
+
+        // MySQL updates zero rows and the follow-up SELECT finds nothing
+        let current = tables
+            .live_role(tenant, &role_id)
+            .ok_or(UserRepositoryError::Sqlx(sqlx::Error::RowNotFound))?;
+        if expected_version.is_some_and(|v| v != current.version) {
+            return Err(UserRepositoryError::VersionConflict);
+        }
+        if tables.role_name_taken(tenant, name, Some(&role_id)) {
+            return Err(UserRepositoryError::RoleNameAlreadyExists);
+        }
+
+        let role = tables
+            .roles
+            .get_mut(&role_id)
+            .ok_or(UserRepositoryError::Sqlx(sqlx::Error::RowNotFound))?;
+        role.name = name.to_string();
+        role.description = description;
+        role.touch();
+        Ok(role.clone())
+    }
+
+    async fn delete_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<(), UserRepositoryError> {
+        let role_id = role_id.to_string();
+        let mut tables = self.store.lock();
+        let current = tables
+            .roles
+            .get(&role_id)
+            .filter(|_| tables.owned_by(tenant, &role_id))
+            .map(|r| r.version);
+        match (current, expected_version) {
+            (None, Some(_)) => return Err(UserRepositoryError::NotFound),
+            (None, None) => return Ok(()),
+            (Some(version), Some(expected)) if version != expected => {
+                return Err(UserRepositoryError::VersionConflict)
+            }
+            _ => {}
+        }
+        tables.roles.remove(&role_id);
+        tables.owners.remove(&role_id);
+        tables.user_roles.retain(|(_, r), _| *r != role_id);
+        tables.group_roles.retain(|(_, r)| *r != role_id);
+        Ok(())
+    }
+
+    async fn soft_delete_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<bool, UserRepositoryError> {
+        let role_id = role_id.to_string();
+        let mut tables = self.store.lock();
+        let owned = tables.owned_by(tenant, &role_id);
+        let Some(role) = tables
+            .roles
+            .get_mut(&role_id)
+            .filter(|r| owned && r.deleted_at.is_none())
+        else {
+            return Ok(false);
+        };
+        if expected_version.is_some_and(|v| v != role.version) {
+            return Err(UserRepositoryError::VersionConflict);
+        }
+        mark_deleted(role);
+        Ok(true)
+    }
+
+    async fn restore_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+    ) -> Result<bool, UserRepositoryError> {
+        let role_id = role_id.to_string();
+        let mut tables = self.store.lock();
+        let owned = tables.owned_by(tenant, &role_id);
+        let Some(name) = tables
+            .roles
+            .get(&role_id)
+            .filter(|r| owned && r.deleted_at.is_some())
+            .map(|r| r.name.clone())
+        else {
+            return Ok(false);
+        };
+        if tables.role_name_taken(tenant, &name, Some(&role_id)) {
+            return Err(UserRepositoryError::RoleNameAlreadyExists);
+        }
+        let role = tables.roles.get_mut(&role_id);
+        Ok(role.map(mark_restored).is_some())
+    }
+
+    async fn get_roles_for_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<Vec<RoleRow>, UserRepositoryError> {
+        let user_id = user_id.to_string();
+        let tables = self.store.lock();
+        Ok(tables
+            .held_role_ids(&user_id)
+            .into_iter()
+            .filter_map(|r| tables.live_role(tenant, r).cloned())
+            .collect())
+    }
+
+    async fn get_granted_roles_for_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<Vec<RoleRow>, UserRepositoryError> {
+        let user_id = user_id.to_string();
+        let tables = self.store.lock();
+        Ok(tables
+            .active_user_roles()
+            .filter(|(u, _)| *u == user_id)
+            .filter_map(|(_, r)| tables.live_role(tenant, r).cloned())
+            .collect())
+    }
+
+    async fn get_roles_for_users(
+        &self,
+        tenant: TenantId,
+        user_ids: &[String],
+    ) -> Result<Vec<UserRoleMapping>, UserRepositoryError> {
+        let tables = self.store.lock();
+        Ok(user_ids
+            .iter()
+            .flat_map(|u| tables.held_role_ids(u).into_iter().map(move |r| (u, r)))
+            .filter_map(|(u, r)| {
+                tables.live_role(tenant, r).map(|role| UserRoleMapping {
+                    user_id: u.clone(),
+                    role_id: role.id.clone(),
+                    role_name: role.name.clone(),
+                    role_created_at: role.created_at,
+                    role_updated_at: role.updated_at,
+                    role_version: role.version,
+                    role_description: role.description.clone(),
+                    role_is_system: role.is_system,
+                })
+            })
+            .collect())
+    }
+
+    async fn get_roles_paginated(
+        &self,
+        tenant: TenantId,
+        pagination: PaginationParams,
+    ) -> Result<(Vec<RoleRow>, u64), UserRepositoryError> {
+        let tables = self.store.lock();
+        let mut roles: Vec<RoleRow> = tables
+            .roles
+            .values()
+            .filter(|r| r.deleted_at.is_none() && tables.owned_by(tenant, &r.id))
+            .cloned()
+            .collect();
+        roles.sort_by_key(|r| r.name.to_lowercase());
+        Ok((paginate(&roles, pagination), roles.len() as u64))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InMemoryUserRoleRepository {
+    store: InMemoryStore,
+}
+
+impl InMemoryUserRoleRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl UserRoleRepositoryTrait for InMemoryUserRoleRepository {
+    async fn assign_role(
+        &self,
+        tenant: TenantId,
+        user_id: &str,
+        role_id: &str,
+        grant: &RoleGrant,
+    ) -> Result<(), UserRepositoryError> {
+        let mut tables = self.store.lock();
+        if tables.live_user(tenant, user_id).is_none()
+            || tables.live_role(tenant, role_id).is_none()
+        {
+            return Err(UserRepositoryError::NotFound);
+        }
+        let key = (user_id.to_string(), role_id.to_string());
+        if tables.user_roles.contains_key(&key) {
+            return Err(UserRepositoryError::UserAlreadyHasRole);
+        }
+
+        tables.user_roles.insert(key, grant.clone());
+        Ok(())
+    }
+
+    async fn unassign_role(
+        &self,
+        tenant: TenantId,
+        user_id: &str,
+        role_id: &str,
+    ) -> Result<(), UserRepositoryError> {
+        let mut tables = self.store.lock();
+        if tables.owned_by(tenant, user_id) {
+            tables
+                .user_roles
+                .remove(&(user_id.to_string(), role_id.to_string()));
+        }
+        Ok(())
+    }
+
+    async fn get_users_with_expired_role_grants(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<(String, String)>, UserRepositoryError> {
+        let tables = self.store.lock();
+        let mut users: Vec<(String, String)> = tables
+            .user_roles
+            .iter()
+            .filter(|(_, grant)| grant.is_expired_at(now))
+            .map(|((user_id, _), _)| {
+                let tenant = tables.owners.get(user_id).copied().unwrap_or_default();
+                (tenant.to_string(), user_id.clone())
+            })
+            .collect();
+        users.sort_unstable();
+        users.dedup();
+        Ok(users)
+    }
+
+    async fn delete_expired_role_grants(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<(String, String)>, UserRepositoryError> {
+        let mut tables = self.store.lock();
+        let Tables {
+            user_roles, owners, ..
+        } = &mut *tables;
+        let mut users = Vec::new();
+        user_roles.retain(|(user_id, _), grant| {
+            let expired = grant.is_expired_at(now);
+            if expired {
+                let tenant = owners.get(user_id).copied().unwrap_or_default();
+                users.push((tenant.to_string(), user_id.clone()));
+            }
+            !expired
+        });
+        Ok(users)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InMemoryTenantRepository {
+    store: InMemoryStore,
+}
+
+impl InMemoryTenantRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl TenantRepositoryTrait for InMemoryTenantRepository {
+    async fn create_tenant(
+        &self,
+        slug: &str,
+        name: &str,
+    ) -> Result<TenantRow, UserRepositoryError> {
+        let mut tables = self.store.lock();
+        if tables.tenants.values().any(|t| t.slug == slug) {
+            return Err(UserRepositoryError::TenantSlugAlreadyExists);
+        }
+
+        let now = Utc::now();
+        let tenant = TenantId(Uuid::new_v4());
+        let row = TenantRow {
+            id: tenant.to_string(),
+            slug: slug.to_string(),
+            name: name.to_string(),
+            created_at: now,
+        };
+        for (role_name, description) in SYSTEM_ROLES {
+            tables.insert_role(
+                tenant,
+                RoleRow {
+                    id: Uuid::new_v4().to_string(),
+                    name: role_name.to_string(),
+                    created_at: now,
+                    updated_at: now,
+                    version: 1,
+                    deleted_at: None,
+                    description: Some(description.to_string()),
+                    is_system: true,
+                },
+            );
+        }
+        tables.tenants.insert(row.id.clone(), row.clone());
+        Ok(row)
+    }
+
+    async fn get_tenant(&self, tenant: TenantId) -> Result<Option<TenantRow>, UserRepositoryError> {
+        Ok(self.store.lock().tenants.get(&tenant.to_string()).cloned())
+    }
+
+    async fn get_tenants(&self) -> Result<Vec<TenantRow>, UserRepositoryError> {
+        let mut tenants: Vec<TenantRow> = self.store.lock().tenants.values().cloned().collect();
+        tenants.sort_by(|a, b| a.slug.cmp(&b.slug));
+        Ok(tenants)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InMemoryGroupRepository {
+    store: InMemoryStore,
+}
+
+impl InMemoryGroupRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl GroupRepositoryTrait for InMemoryGroupRepository {
+    async fn create_group(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+        name: &str,
+    ) -> Result<GroupRow, UserRepositoryError> {
+        let mut tables = self.store.lock();
+        if tables
+            .groups
+            .values()
+            .any(|g| tables.owned_by(tenant, &g.id) && same_name(&g.name, name))
+        {
+            return Err(UserRepositoryError::GroupNameAlreadyExists);
+        }
+
+        let now = Utc::now();
+        let row = GroupRow {
+            id: Uuid::new_v4().to_string(),
+            keycloak_id: keycloak_id.to_string(),
+            name: name.to_string(),
+            created_at: now,
+            updated_at: now,
+        };
+        tables.owners.insert(row.id.clone(), tenant);
+        tables.groups.insert(row.id.clone(), row.clone());
+        Ok(row)
+    }
+
+    async fn get_group(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+    ) -> Result<Option<GroupRow>, UserRepositoryError> {
+        let group_id = group_id.to_string();
+        let tables = self.store.lock();
+        Ok(tables
+            .groups
+            .get(&group_id)
+            .filter(|g| tables.owned_by(tenant, &g.id))
+            .cloned())
+    }
+
+    async fn get_groups_paginated(
+        &self,
+        tenant: TenantId,
+        pagination: PaginationParams,
+    ) -> Result<(Vec<GroupRow>, u64), UserRepositoryError> {
+        let tables = self.store.lock();
+        let mut groups: Vec<GroupRow> = tables
+            .groups
+            .values()
+            .filter(|g| tables.owned_by(tenant, &g.id))
+            .cloned()
+            .collect();
+        groups.sort_by_key(|g| g.name.to_lowercase());
+        Ok((paginate(&groups, pagination), groups.len() as u64))
+    }
+
+    async fn rename_group(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        name: &str,
+    ) -> Result<GroupRow, UserRepositoryError> {
+        let group_id = group_id.to_string();
+        let mut tables = self.store.lock();
+        if !tables.groups.contains_key(&group_id) || !tables.owned_by(tenant, &group_id) {
+            return Err(UserRepositoryError::NotFound);
+        }
+        if tables
+            .groups
+            .values()
+            .any(|g| g.id != group_id && tables.owned_by(tenant, &g.id) && same_name(&g.name, name))
+        {
+            return Err(UserRepositoryError::GroupNameAlreadyExists);
+        }
+
+        let group = tables
+            .groups
+            .get_mut(&group_id)
+            .ok_or(UserRepositoryError::NotFound)?;
+        group.name = name.to_string();
+        group.updated_at = Utc::now();
+        Ok(group.clone())
+    }
+
+    async fn delete_group(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+    ) -> Result<bool, UserRepositoryError> {
+        let group_id = group_id.to_string();
+        let mut tables = self.store.lock();
+        if !tables.owned_by(tenant, &group_id) || tables.groups.remove(&group_id).is_none() {
+            return Ok(false);
+        }
+        tables.owners.remove(&group_id);
+        tables.group_members.retain(|(g, _)| *g != group_id);
+        tables.group_roles.retain(|(g, _)| *g != group_id);
+        Ok(true)
+    }
+
+    async fn add_group_member(
+        &self,
+        tenant: TenantId,
+        group_id: &str,
+        user_id: &str,
+    ) -> Result<(), UserRepositoryError> {
+        let mut tables = self.store.lock();
+        let key = (group_id.to_string(), user_id.to_string());
+        if tables.group_members.contains(&key) {
+            return Ok(());
+        }
+        if !tables.groups.contains_key(group_id)
+            || !tables.owned_by(tenant, group_id)
+            || tables.live_user(tenant, user_id).is_none()
+        {
+            return Err(UserRepositoryError::NotFound);
+        }
+        tables.group_members.insert(key);
+        Ok(())
+    }
+
+    async fn remove_group_member(
+        &self,
+        tenant: TenantId,
+        group_id: &str,
+        user_id: &str,
+    ) -> Result<(), UserRepositoryError> {
+        let mut tables = self.store.lock();
+        if tables.owned_by(tenant, group_id) {
+            tables
+                .group_members
+                .remove(&(group_id.to_string(), user_id.to_string()));
+        }
+        Ok(())
+    }
+
+    async fn get_group_members_paginated(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        pagination: PaginationParams,
+    ) -> Result<(Vec<UserRow>, u64), UserRepositoryError> {
+        let group_id = group_id.to_string();
+        let tables = self.store.lock();
+        let users: Vec<UserRow> = tables
+            .users
+            .values()
+            .filter(|u| u.deleted_at.is_none() && tables.owned_by(tenant, &u.id))
+            .filter(|u| {
+                tables
+                    .group_members
+                    .contains(&(group_id.clone(), u.id.clone()))
+            })
+            .cloned()
+            .collect();
+        Ok((paginate(&users, pagination), users.len() as u64))
+    }
+
+    async fn assign_group_role(
+        &self,
+        tenant: TenantId,
+        group_id: &str,
+        role_id: &str,
+    ) -> Result<(), UserRepositoryError> {
+        let mut tables = self.store.lock();
+        let key = (group_id.to_string(), role_id.to_string());
+        if tables.group_roles.contains(&key) {
+            return Ok(());
+        }
+        if !tables.groups.contains_key(group_id)
+            || !tables.owned_by(tenant, group_id)
+            || tables.live_role(tenant, role_id).is_none()
+        {
+            return Err(UserRepositoryError::NotFound);
+        }
+        tables.group_roles.insert(key);
+        Ok(())
+    }
+
+    async fn unassign_group_role(
+        &self,
+        tenant: TenantId,
+        group_id: &str,
+        role_id: &str,
+    ) -> Result<(), UserRepositoryError> {
+        let mut tables = self.store.lock();
+        if tables.owned_by(tenant, group_id) {
+            tables
+                .group_roles
+                .remove(&(group_id.to_string(), role_id.to_string()));
+        }
+        Ok(())
+    }
+
+    async fn get_roles_for_groups(
+        &self,
+        tenant: TenantId,
+        group_ids: &[String],
+    ) -> Result<Vec<GroupRoleMapping>, UserRepositoryError> {
+        let tables = self.store.lock();
+        let mut mappings: Vec<GroupRoleMapping> = tables
+            .group_roles
+            .iter()
+            .filter(|(g, _)| group_ids.contains(g))
+            .filter_map(|(g, r)| {
+                tables.live_role(tenant, r).map(|role| GroupRoleMapping {
+                    group_id: g.clone(),
+                    role_id: role.id.clone(),
+                    role_name: role.name.clone(),
+                    role_created_at: role.created_at,
+                    role_updated_at: role.updated_at,
+                    role_version: role.version,
+                    role_description: role.description.clone(),
+                    role_is_system: role.is_system,
+                })
+            })
+            .collect();
+        mappings.sort_by_key(|m| m.role_name.to_lowercase());
+        Ok(mappings)
+    }
+}
+
+/// Stored `Idempotency-Key` responses. Kept apart from [`InMemoryStore`] because, like the
+/// SQL repositories, it never takes part in a unit of work.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryIdempotencyRepository {
+    records: Arc<Mutex<HashMap<String, IdempotencyRow>>>,
+}
+
+impl InMemoryIdempotencyRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, IdempotencyRow>> {
+        self.records.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl IdempotencyRepositoryTrait for InMemoryIdempotencyRepository {
+    async fn reserve_idempotency_key(
+        &self,
+        key: &str,
+        fingerprint: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<bool, UserRepositoryError> {
+        let mut records = self.lock();
+        let now = Utc::now();
+        records.retain(|_, r| r.expires_at > now);
+        if records.contains_key(key) {
+            return Ok(false);
+        }
+
+        records.insert(
+            key.to_string(),
+            IdempotencyRow {
+                idempotency_key: key.to_string(),
+                fingerprint: fingerprint.to_string(),
+                status_code: None,
+                response_body: None,
+                response_headers: None,
+                created_at: now,
+                expires_at,
+            },
+        );
+        Ok(true)
+    }
+
+    async fn get_idempotency_key(
+        &self,
+        key: &str,
+    ) -> Result<Option<IdempotencyRow>, UserRepositoryError> {
+        let now = Utc::now();
+        Ok(self.lock().get(key).filter(|r| r.expires_at > now).cloned())
+    }
+
+    async fn complete_idempotency_key(
+        &self,
+        key: &str,
+        status_code: i32,
+        response_body: &str,
+        response_headers: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), UserRepositoryError> {
+        if let Some(record) = self.lock().get_mut(key) {
+            record.status_code = Some(status_code);
+            record.response_body = Some(response_body.to_string());
+            record.response_headers = Some(response_headers.to_string());
+            record.expires_at = expires_at;
+        }
+        Ok(())
+    }
+
+    async fn release_idempotency_key(&self, key: &str) -> Result<(), UserRepositoryError> {
+        let mut records = self.lock();
+        if records.get(key).is_some_and(|r| r.status_code.is_none()) {
+            records.remove(key);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryEventCheckpointRepository {
+    checkpoints: Arc<Mutex<HashMap<(TenantId, String), i64>>>,
+}
+
+impl InMemoryEventCheckpointRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<(TenantId, String), i64>> {
+        self.checkpoints.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl EventCheckpointRepositoryTrait for InMemoryEventCheckpointRepository {
+    async fn get_event_checkpoint(
+        &self,
+        tenant: TenantId,
+        source: &str,
+    ) -> Result<Option<i64>, UserRepositoryError> {
+        Ok(self.lock().get(&(tenant, source.to_string())).copied())
+    }
+
+    async fn save_event_checkpoint(
+        &self,
+        tenant: TenantId,
+        source: &str,
+        event_time: i64,
+    ) -> Result<(), UserRepositoryError> {
+        self.lock().insert((tenant, source.to_string()), event_time);
+        Ok(())
+    }
+}
+
+/// Keys live outside the shared store, like the SQL repositories that run straight on the
+/// pool; the tenant of a key is not checked against the tenants table
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryApiKeyRepository {
+    keys: Arc<Mutex<BTreeMap<String, ApiKeyRow>>>,
+}
+
+impl InMemoryApiKeyRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<String, ApiKeyRow>> {
+        self.keys.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Unrevoked key `key_id` of `tenant`
+fn live_api_key(
+    keys: &mut BTreeMap<String, ApiKeyRow>,
+    tenant: TenantId,
+    key_id: Uuid,
+) -> Option<&mut ApiKeyRow> {
+    keys.get_mut(&key_id.to_string())
+        .filter(|k| k.tenant_id == tenant.to_string() && k.revoked_at.is_none())
+}
+
+#[async_trait]
+impl ApiKeyRepositoryTrait for InMemoryApiKeyRepository {
+    async fn create_api_key(
+        &self,
+        tenant: TenantId,
+        name: &str,
+        key_prefix: &str,
+        key_hash: &str,
+        scopes: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<ApiKeyRow, UserRepositoryError> {
+        let mut keys = self.lock();
+        if keys.values().any(|k| k.key_hash == key_hash) {
+            return Err(constraint_error(
+                "Duplicate entry for key 'api_key_hash_unique'".to_string(),
+            ));
+        }
+        let row = ApiKeyRow {
+            id: Uuid::new_v4().to_string(),
+            tenant_id: tenant.to_string(),
+            name: name.to_string(),
+            key_prefix: key_prefix.to_string(),
+            key_hash: key_hash.to_string(),
+            scopes: scopes.to_string(),
+            created_at: Utc::now(),
+            expires_at,
+            ..Default::default()
+        };
+        keys.insert(row.id.clone(), row.clone());
+        Ok(row)
+    }
+
+    async fn get_api_key(
+        &self,
+        tenant: TenantId,
+        key_id: Uuid,
+    ) -> Result<Option<ApiKeyRow>, UserRepositoryError> {
+        Ok(self
+            .lock()
+            .get(&key_id.to_string())
+            .filter(|k| k.tenant_id == tenant.to_string())
+            .cloned())
+    }
+
+    async fn get_api_keys(&self, tenant: TenantId) -> Result<Vec<ApiKeyRow>, UserRepositoryError> {
+        let mut keys: Vec<ApiKeyRow> = self
+            .lock()
+            .values()
+            .filter(|k| k.tenant_id == tenant.to_string())
+            .cloned()
+            .collect();
+        keys.sort_by_key(|k| std::cmp::Reverse(k.created_at));
+        Ok(keys)
+    }
+
+    async fn get_api_key_by_hash(
+        &self,
+        key_hash: &str,
+    ) -> Result<Option<ApiKeyRow>, UserRepositoryError> {
+        Ok(self
+            .lock()
+            .values()
+            .find(|k| k.key_hash == key_hash)
+            .cloned())
+    }
+
+    async fn rotate_api_key(
+        &self,
+        tenant: TenantId,
+        key_id: Uuid,
+        key_prefix: &str,
+        key_hash: &str,
+    ) -> Result<ApiKeyRow, UserRepositoryError> {
+        let mut keys = self.lock();
+        if keys.values().any(|k| k.key_hash == key_hash) {
+            return Err(constraint_error(
+                "Duplicate entry for key 'api_key_hash_unique'".to_string(),
+            ));
+        }
+        let key = live_api_key(&mut keys, tenant, key_id).ok_or(UserRepositoryError::NotFound)?;
+        key.key_prefix = key_prefix.to_string();
+        key.key_hash = key_hash.to_string();
+        key.rotated_at = Some(Utc::now());
+        Ok(key.clone())
+    }
+
+    async fn revoke_api_key(
+        &self,
+        tenant: TenantId,
+        key_id: Uuid,
+    ) -> Result<bool, UserRepositoryError> {
+        let mut keys = self.lock();
+        let Some(key) = live_api_key(&mut keys, tenant, key_id) else {
+            return Ok(false);
+        };
+        key.revoked_at = Some(Utc::now());
+        Ok(true)
+    }
+
+    async fn record_api_key_use(
+        &self,
+        key_id: Uuid,
+        used_at: DateTime<Utc>,
+    ) -> Result<(), UserRepositoryError> {
+        if let Some(key) = self.lock().get_mut(&key_id.to_string()) {
+            key.last_used_at = Some(used_at);
+        }
+        Ok(())
+    }
+}
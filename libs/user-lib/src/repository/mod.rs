@@ -1,12 +1,52 @@
+pub mod api_key_repository;
 pub mod errors;
+pub mod event_checkpoint_repository;
+pub mod executor;
+pub mod group_repository;
+pub mod idempotency_repository;
+#[cfg(feature = "testing")]
+pub mod in_memory;
 pub mod models;
+#[cfg(feature = "postgres")]
+pub mod postgres;
 pub mod role_repository;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod tenant_repository;
 pub mod traits;
+pub mod unit_of_work;
 pub mod user_repository;
 pub mod user_role_repository;
 
+pub use api_key_repository::ApiKeyRepository;
 pub use errors::UserRepositoryError;
+pub use event_checkpoint_repository::EventCheckpointRepository;
+pub use group_repository::GroupRepository;
+pub use idempotency_repository::IdempotencyRepository;
+#[cfg(feature = "testing")]
+pub use in_memory::{
+    InMemoryApiKeyRepository, InMemoryEventCheckpointRepository, InMemoryGroupRepository,
+    InMemoryIdempotencyRepository, InMemoryRoleRepository, InMemoryStore, InMemoryTenantRepository,
+    InMemoryUserRepository, InMemoryUserRoleRepository,
+};
+#[cfg(feature = "postgres")]
+pub use postgres::{
+    PgApiKeyRepository, PgEventCheckpointRepository, PgGroupRepository, PgIdempotencyRepository,
+    PgRoleRepository, PgTenantRepository, PgUserRepository, PgUserRoleRepository,
+};
 pub use role_repository::RoleRepository;
-pub use traits::{RoleRepositoryTrait, UserRepositoryTrait, UserRoleRepositoryTrait};
+#[cfg(feature = "sqlite")]
+pub use sqlite::{
+    SqliteApiKeyRepository, SqliteEventCheckpointRepository, SqliteGroupRepository,
+    SqliteIdempotencyRepository, SqliteRoleRepository, SqliteTenantRepository,
+    SqliteUserRepository, SqliteUserRoleRepository,
+};
+pub use tenant_repository::TenantRepository;
+pub use traits::{
+    ApiKeyRepositoryTrait, EventCheckpointRepositoryTrait, GroupRepositoryTrait,
+    IdempotencyRepositoryTrait, RoleRepositoryTrait, TenantRepositoryTrait, UserRepositoryTrait,
+    UserRoleRepositoryTrait,
+};
+pub use unit_of_work::{UnitOfWork, UnitOfWorkFactory};
 pub use user_repository::UserRepository;
 pub use user_role_repository::UserRoleRepository;
@@ -1,15 +1,67 @@
+use chrono::{DateTime, Utc};
 use sqlx::FromRow;
 
-#[derive(Debug, Clone, FromRow)]
+#[derive(Debug, Clone, Default, PartialEq, FromRow)]
+pub struct TenantRow {
+    pub id: String,
+    pub slug: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, FromRow)]
 pub struct UserRow {
     pub id: String,
     pub keycloak_id: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Bumped on every write, for optimistic concurrency control
+    pub version: i64,
+    /// Set when the user is soft-deleted; live queries only return rows where it is NULL
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Set while the user is suspended
+    pub suspended_at: Option<DateTime<Utc>>,
+    pub suspension_reason: Option<String>,
+    /// Time of the user's most recent Keycloak login, as seen by the event poller
+    pub last_login_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone, FromRow)]
+#[derive(Debug, Clone, Default, PartialEq, FromRow)]
 pub struct RoleRow {
     pub id: String,
     pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Bumped on every write, for optimistic concurrency control
+    pub version: i64,
+    /// Set when the role is soft-deleted; live queries only return rows where it is NULL
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub description: Option<String>,
+    /// Seeded role the application relies on; it cannot be renamed or deleted
+    pub is_system: bool,
+}
+
+/// Local copy of a Keycloak group
+#[derive(Debug, Clone, Default, PartialEq, FromRow)]
+pub struct GroupRow {
+    pub id: String,
+    pub keycloak_id: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Role attached to a group, with the same role columns as `UserRoleMapping`
+#[derive(Debug, Clone, Default, FromRow)]
+pub struct GroupRoleMapping {
+    pub group_id: String,
+    pub role_id: String,
+    pub role_name: String,
+    pub role_created_at: DateTime<Utc>,
+    pub role_updated_at: DateTime<Utc>,
+    pub role_version: i64,
+    pub role_description: Option<String>,
+    pub role_is_system: bool,
 }
 
 #[derive(Debug, Clone, FromRow)]
@@ -18,9 +70,60 @@ pub struct UserRoleRow {
     pub role_id: String,
 }
 
-#[derive(Debug, Clone, FromRow)]
+#[derive(Debug, Clone, Default, FromRow)]
 pub struct UserRoleMapping {
     pub user_id: String,
     pub role_id: String,
     pub role_name: String,
+    pub role_created_at: DateTime<Utc>,
+    pub role_updated_at: DateTime<Utc>,
+    pub role_version: i64,
+    pub role_description: Option<String>,
+    pub role_is_system: bool,
+}
+
+/// Response stored for an `Idempotency-Key`, replayed when a client retries the request
+#[derive(Debug, Clone, Default, FromRow)]
+pub struct IdempotencyRow {
+    pub idempotency_key: String,
+    /// Hash of the original request; a retry with a different one is rejected
+    pub fingerprint: String,
+    /// `None` while the original request is still running
+    pub status_code: Option<i32>,
+    pub response_body: Option<String>,
+    /// Headers replayed with the response, as serialized by the caller
+    pub response_headers: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Service-account API key; the key itself is only known through its hash
+#[derive(Debug, Clone, Default, FromRow)]
+pub struct ApiKeyRow {
+    pub id: String,
+    pub tenant_id: String,
+    pub name: String,
+    /// Leading characters of the key, shown so admins can tell keys apart
+    pub key_prefix: String,
+    /// Hex SHA-256 of the key
+    pub key_hash: String,
+    /// Space-separated scope names
+    pub scopes: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub rotated_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// Application-specific attributes of a user; users without a row have none set
+#[derive(Debug, Clone, Default, PartialEq, FromRow)]
+pub struct UserAttributesRow {
+    pub user_id: String,
+    pub department: Option<String>,
+    pub cost_center: Option<String>,
+    pub locale: Option<String>,
+    /// JSON object, stored as text
+    pub metadata: Option<String>,
+    pub updated_at: DateTime<Utc>,
 }
@@ -0,0 +1,152 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{query, query_as, PgPool};
+use uuid::Uuid;
+
+use crate::entities::TenantId;
+use crate::repository::api_key_repository::API_KEY_COLUMNS;
+use crate::repository::errors::{map_sqlx_error, UserRepositoryError};
+use crate::repository::models::ApiKeyRow;
+use crate::repository::traits::ApiKeyRepositoryTrait;
+
+/// Runs straight on the pool: keys are checked on every request, outside any unit of work
+#[derive(Debug, Clone)]
+pub struct PgApiKeyRepository {
+    pool: PgPool,
+}
+
+impl PgApiKeyRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepositoryTrait for PgApiKeyRepository {
+    async fn create_api_key(
+        &self,
+        tenant: TenantId,
+        name: &str,
+        key_prefix: &str,
+        key_hash: &str,
+        scopes: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<ApiKeyRow, UserRepositoryError> {
+        query_as::<_, ApiKeyRow>(&format!(
+            r#"
+            INSERT INTO api_keys
+                (id, tenant_id, name, key_prefix, key_hash, scopes, created_at, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING {API_KEY_COLUMNS}
+            "#
+        ))
+        .bind(Uuid::new_v4().to_string())
+        .bind(tenant.to_string())
+        .bind(name)
+        .bind(key_prefix)
+        .bind(key_hash)
+        .bind(scopes)
+        .bind(Utc::now())
+        .bind(expires_at)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(map_sqlx_error)
+    }
+
+    async fn get_api_key(
+        &self,
+        tenant: TenantId,
+        key_id: Uuid,
+    ) -> Result<Option<ApiKeyRow>, UserRepositoryError> {
+        query_as::<_, ApiKeyRow>(&format!(
+            "SELECT {API_KEY_COLUMNS} FROM api_keys WHERE id = $1 AND tenant_id = $2"
+        ))
+        .bind(key_id.to_string())
+        .bind(tenant.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(map_sqlx_error)
+    }
+
+    async fn get_api_keys(&self, tenant: TenantId) -> Result<Vec<ApiKeyRow>, UserRepositoryError> {
+        query_as::<_, ApiKeyRow>(&format!(
+            "SELECT {API_KEY_COLUMNS} FROM api_keys WHERE tenant_id = $1 ORDER BY created_at DESC"
+        ))
+        .bind(tenant.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_sqlx_error)
+    }
+
+    async fn get_api_key_by_hash(
+        &self,
+        key_hash: &str,
+    ) -> Result<Option<ApiKeyRow>, UserRepositoryError> {
+        query_as::<_, ApiKeyRow>(&format!(
+            "SELECT {API_KEY_COLUMNS} FROM api_keys WHERE key_hash = $1"
+        ))
+        .bind(key_hash)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(map_sqlx_error)
+    }
+
+    async fn rotate_api_key(
+        &self,
+        tenant: TenantId,
+        key_id: Uuid,
+        key_prefix: &str,
+        key_hash: &str,
+    ) -> Result<ApiKeyRow, UserRepositoryError> {
+        query_as::<_, ApiKeyRow>(&format!(
+            r#"
+            UPDATE api_keys SET key_prefix = $1, key_hash = $2, rotated_at = $3
+            WHERE id = $4 AND tenant_id = $5 AND revoked_at IS NULL
+            RETURNING {API_KEY_COLUMNS}
+            "#
+        ))
+        .bind(key_prefix)
+        .bind(key_hash)
+        .bind(Utc::now())
+        .bind(key_id.to_string())
+        .bind(tenant.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?
+        .ok_or(UserRepositoryError::NotFound)
+    }
+
+    async fn revoke_api_key(
+        &self,
+        tenant: TenantId,
+        key_id: Uuid,
+    ) -> Result<bool, UserRepositoryError> {
+        let result = query(
+            r#"
+            UPDATE api_keys SET revoked_at = $1
+            WHERE id = $2 AND tenant_id = $3 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(Utc::now())
+        .bind(key_id.to_string())
+        .bind(tenant.to_string())
+        .execute(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn record_api_key_use(
+        &self,
+        key_id: Uuid,
+        used_at: DateTime<Utc>,
+    ) -> Result<(), UserRepositoryError> {
+        query("UPDATE api_keys SET last_used_at = $1 WHERE id = $2")
+            .bind(used_at)
+            .bind(key_id.to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+        Ok(())
+    }
+}
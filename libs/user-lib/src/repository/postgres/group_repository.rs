@@ -0,0 +1,348 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::{query, query_as, query_scalar, PgPool, Postgres};
+use uuid::Uuid;
+
+use crate::entities::{PaginationParams, TenantId};
+use crate::repository::errors::{map_sqlx_error, UserRepositoryError};
+use crate::repository::executor::{DbExecutor, SharedTransaction};
+use crate::repository::models::{GroupRoleMapping, GroupRow, UserRow};
+use crate::repository::traits::GroupRepositoryTrait;
+
+#[derive(Debug, Clone)]
+pub struct PgGroupRepository {
+    db: DbExecutor<Postgres>,
+}
+
+impl PgGroupRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            db: DbExecutor::Pool(pool),
+        }
+    }
+
+    /// Repository whose queries run inside a shared transaction
+    pub fn in_transaction(tx: SharedTransaction<Postgres>) -> Self {
+        Self {
+            db: DbExecutor::Transaction(tx),
+        }
+    }
+}
+
+#[async_trait]
+impl GroupRepositoryTrait for PgGroupRepository {
+    async fn create_group(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+        name: &str,
+    ) -> Result<GroupRow, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let now = Utc::now();
+        query_as::<_, GroupRow>(
+            r#"
+            INSERT INTO user_groups (id, tenant_id, keycloak_id, name, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $5)
+            RETURNING id, keycloak_id, name, created_at, updated_at
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(tenant.to_string())
+        .bind(keycloak_id)
+        .bind(name)
+        .bind(now)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)
+    }
+
+    async fn get_group(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+    ) -> Result<Option<GroupRow>, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        query_as::<_, GroupRow>(
+            r#"
+            SELECT id, keycloak_id, name, created_at, updated_at
+            FROM user_groups
+            WHERE id = $1 AND tenant_id = $2
+            "#,
+        )
+        .bind(group_id.to_string())
+        .bind(tenant.to_string())
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)
+    }
+
+    async fn get_groups_paginated(
+        &self,
+        tenant: TenantId,
+        pagination: PaginationParams,
+    ) -> Result<(Vec<GroupRow>, u64), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let total: i64 = query_scalar("SELECT COUNT(*) FROM user_groups WHERE tenant_id = $1")
+            .bind(tenant.to_string())
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        let groups = query_as::<_, GroupRow>(
+            r#"
+            SELECT id, keycloak_id, name, created_at, updated_at
+            FROM user_groups
+            WHERE tenant_id = $1
+            ORDER BY name
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(tenant.to_string())
+        .bind(i64::from(pagination.limit()))
+        .bind(i64::from(pagination.offset()))
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        Ok((groups, total as u64))
+    }
+
+    async fn rename_group(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        name: &str,
+    ) -> Result<GroupRow, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        query_as::<_, GroupRow>(
+            r#"
+            UPDATE user_groups SET name = $1, updated_at = $2
+            WHERE id = $3 AND tenant_id = $4
+            RETURNING id, keycloak_id, name, created_at, updated_at
+            "#,
+        )
+        .bind(name)
+        .bind(Utc::now())
+        .bind(group_id.to_string())
+        .bind(tenant.to_string())
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?
+        .ok_or(UserRepositoryError::NotFound)
+    }
+
+    async fn delete_group(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+    ) -> Result<bool, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let result = query("DELETE FROM user_groups WHERE id = $1 AND tenant_id = $2")
+            .bind(group_id.to_string())
+            .bind(tenant.to_string())
+            .execute(&mut *conn)
+            .await
+            .map_err(map_sqlx_error)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn add_group_member(
+        &self,
+        tenant: TenantId,
+        group_id: &str,
+        user_id: &str,
+    ) -> Result<(), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let result = query(
+            r#"
+            INSERT INTO group_members (group_id, user_id, tenant_id, added_at)
+            SELECT $1, $2, $3, $4
+            WHERE EXISTS (SELECT 1 FROM user_groups WHERE id = $1 AND tenant_id = $3)
+              AND EXISTS (
+                SELECT 1 FROM users WHERE id = $2 AND tenant_id = $3 AND deleted_at IS NULL
+            )
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(group_id)
+        .bind(user_id)
+        .bind(tenant.to_string())
+        .bind(Utc::now())
+        .execute(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        // Nothing inserted: either the user is already a member or one side is missing
+        if result.rows_affected() == 0 {
+            let members: i64 = query_scalar(
+                r#"
+                SELECT COUNT(*) FROM group_members
+                WHERE group_id = $1 AND user_id = $2 AND tenant_id = $3
+                "#,
+            )
+            .bind(group_id)
+            .bind(user_id)
+            .bind(tenant.to_string())
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(map_sqlx_error)?;
+            if members == 0 {
+                return Err(UserRepositoryError::NotFound);
+            }
+        }
+        Ok(())
+    }
+
+    async fn remove_group_member(
+        &self,
+        tenant: TenantId,
+        group_id: &str,
+        user_id: &str,
+    ) -> Result<(), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        query("DELETE FROM group_members WHERE group_id = $1 AND user_id = $2 AND tenant_id = $3")
+            .bind(group_id)
+            .bind(user_id)
+            .bind(tenant.to_string())
+            .execute(&mut *conn)
+            .await
+            .map_err(map_sqlx_error)?;
+        Ok(())
+    }
+
+    async fn get_group_members_paginated(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        pagination: PaginationParams,
+    ) -> Result<(Vec<UserRow>, u64), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let total: i64 = query_scalar(
+            r#"
+            SELECT COUNT(*)
+            FROM users u
+            JOIN group_members gm ON u.id = gm.user_id
+            WHERE gm.group_id = $1 AND u.tenant_id = $2 AND u.deleted_at IS NULL
+            "#,
+        )
+        .bind(group_id.to_string())
+        .bind(tenant.to_string())
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        let users = query_as::<_, UserRow>(
+            r#"
+            SELECT u.id, u.keycloak_id, u.created_at, u.updated_at, u.version, u.deleted_at,
+                   u.suspended_at, u.suspension_reason, u.last_login_at
+            FROM users u
+            JOIN group_members gm ON u.id = gm.user_id
+            WHERE gm.group_id = $1 AND u.tenant_id = $2 AND u.deleted_at IS NULL
+            ORDER BY u.id
+            LIMIT $3 OFFSET $4
+            "#,
+        )
+        .bind(group_id.to_string())
+        .bind(tenant.to_string())
+        .bind(i64::from(pagination.limit()))
+        .bind(i64::from(pagination.offset()))
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        Ok((users, total as u64))
+    }
+
+    async fn assign_group_role(
+        &self,
+        tenant: TenantId,
+        group_id: &str,
+        role_id: &str,
+    ) -> Result<(), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let result = query(
+            r#"
+            INSERT INTO group_roles (group_id, role_id, tenant_id)
+            SELECT $1, $2, $3
+            WHERE EXISTS (SELECT 1 FROM user_groups WHERE id = $1 AND tenant_id = $3)
+              AND EXISTS (
+                SELECT 1 FROM roles WHERE id = $2 AND tenant_id = $3 AND deleted_at IS NULL
+            )
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(group_id)
+        .bind(role_id)
+        .bind(tenant.to_string())
+        .execute(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        // Nothing inserted: either the role is already attached or one side is missing
+        if result.rows_affected() == 0 {
+            let attached: i64 = query_scalar(
+                r#"
+                SELECT COUNT(*) FROM group_roles
+                WHERE group_id = $1 AND role_id = $2 AND tenant_id = $3
+                "#,
+            )
+            .bind(group_id)
+            .bind(role_id)
+            .bind(tenant.to_string())
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(map_sqlx_error)?;
+            if attached == 0 {
+                return Err(UserRepositoryError::NotFound);
+            }
+        }
+        Ok(())
+    }
+
+    async fn unassign_group_role(
+        &self,
+        tenant: TenantId,
+        group_id: &str,
+        role_id: &str,
+    ) -> Result<(), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        query("DELETE FROM group_roles WHERE group_id = $1 AND role_id = $2 AND tenant_id = $3")
+            .bind(group_id)
+            .bind(role_id)
+            .bind(tenant.to_string())
+            .execute(&mut *conn)
+            .await
+            .map_err(map_sqlx_error)?;
+        Ok(())
+    }
+
+    async fn get_roles_for_groups(
+        &self,
+        tenant: TenantId,
+        group_ids: &[String],
+    ) -> Result<Vec<GroupRoleMapping>, UserRepositoryError> {
+        if group_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+
+        // Postgres binds the whole list as one array parameter
+        query_as::<_, GroupRoleMapping>(
+            r#"
+            SELECT gr.group_id, r.id as role_id, r.name as role_name,
+                   r.created_at as role_created_at, r.updated_at as role_updated_at,
+                   r.version as role_version, r.description as role_description,
+                   r.is_system as role_is_system
+            FROM roles r
+            INNER JOIN group_roles gr ON gr.role_id = r.id
+            WHERE r.deleted_at IS NULL AND gr.group_id = ANY($1) AND r.tenant_id = $2
+            ORDER BY r.name
+            "#,
+        )
+        .bind(group_ids)
+        .bind(tenant.to_string())
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)
+    }
+}
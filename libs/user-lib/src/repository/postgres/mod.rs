@@ -0,0 +1,43 @@
+//! PostgreSQL implementations of the repository traits.
+//! Schema lives in `migrations_postgres/`; ids are stored as `VARCHAR(36)`.
+
+use async_trait::async_trait;
+use sqlx::{PgPool, Postgres};
+
+use crate::repository::errors::{map_sqlx_error, UserRepositoryError};
+use crate::repository::executor::begin_shared;
+use crate::repository::unit_of_work::{SqlUnitOfWork, UnitOfWork, UnitOfWorkFactory};
+
+pub mod api_key_repository;
+pub mod event_checkpoint_repository;
+pub mod group_repository;
+pub mod idempotency_repository;
+pub mod role_repository;
+pub mod tenant_repository;
+pub mod user_repository;
+pub mod user_role_repository;
+
+pub use api_key_repository::PgApiKeyRepository;
+pub use event_checkpoint_repository::PgEventCheckpointRepository;
+pub use group_repository::PgGroupRepository;
+pub use idempotency_repository::PgIdempotencyRepository;
+pub use role_repository::PgRoleRepository;
+pub use tenant_repository::PgTenantRepository;
+pub use user_repository::PgUserRepository;
+pub use user_role_repository::PgUserRoleRepository;
+
+#[async_trait]
+impl UnitOfWorkFactory for PgPool {
+    async fn begin(&self) -> Result<Box<dyn UnitOfWork>, UserRepositoryError> {
+        let tx = begin_shared::<Postgres>(self)
+            .await
+            .map_err(map_sqlx_error)?;
+        Ok(Box::new(SqlUnitOfWork::new(
+            tx.clone(),
+            PgUserRepository::in_transaction(tx.clone()),
+            PgRoleRepository::in_transaction(tx.clone()),
+            PgUserRoleRepository::in_transaction(tx.clone()),
+            PgTenantRepository::in_transaction(tx),
+        )))
+    }
+}
@@ -0,0 +1,93 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::{query, query_as, PgPool, Postgres};
+use uuid::Uuid;
+
+use crate::entities::TenantId;
+use crate::repository::errors::{map_sqlx_error, UserRepositoryError};
+use crate::repository::executor::{DbExecutor, SharedTransaction};
+use crate::repository::models::TenantRow;
+use crate::repository::tenant_repository::SYSTEM_ROLES;
+use crate::repository::traits::TenantRepositoryTrait;
+
+#[derive(Debug, Clone)]
+pub struct PgTenantRepository {
+    db: DbExecutor<Postgres>,
+}
+
+impl PgTenantRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            db: DbExecutor::Pool(pool),
+        }
+    }
+
+    /// Repository whose queries run inside a shared transaction
+    pub fn in_transaction(tx: SharedTransaction<Postgres>) -> Self {
+        Self {
+            db: DbExecutor::Transaction(tx),
+        }
+    }
+}
+
+#[async_trait]
+impl TenantRepositoryTrait for PgTenantRepository {
+    async fn create_tenant(
+        &self,
+        slug: &str,
+        name: &str,
+    ) -> Result<TenantRow, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let id = Uuid::new_v4().to_string();
+        let now = Utc::now();
+        query("INSERT INTO tenants (id, slug, name, created_at) VALUES ($1, $2, $3, $4)")
+            .bind(&id)
+            .bind(slug)
+            .bind(name)
+            .bind(now)
+            .execute(&mut *conn)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        for (role_name, description) in SYSTEM_ROLES {
+            query(
+                r#"
+                INSERT INTO roles
+                    (id, tenant_id, name, description, is_system, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, TRUE, $5, $5)
+                "#,
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(&id)
+            .bind(role_name)
+            .bind(description)
+            .bind(now)
+            .execute(&mut *conn)
+            .await
+            .map_err(map_sqlx_error)?;
+        }
+
+        query_as::<_, TenantRow>("SELECT id, slug, name, created_at FROM tenants WHERE id = $1")
+            .bind(&id)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(map_sqlx_error)
+    }
+
+    async fn get_tenant(&self, tenant: TenantId) -> Result<Option<TenantRow>, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        query_as::<_, TenantRow>("SELECT id, slug, name, created_at FROM tenants WHERE id = $1")
+            .bind(tenant.to_string())
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(map_sqlx_error)
+    }
+
+    async fn get_tenants(&self) -> Result<Vec<TenantRow>, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        query_as::<_, TenantRow>("SELECT id, slug, name, created_at FROM tenants ORDER BY slug")
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(map_sqlx_error)
+    }
+}
@@ -0,0 +1,486 @@
+use crate::entities::{PaginationParams, TenantId, UserFilter, UserSuspension};
+use crate::repository::errors::UserRepositoryError;
+use crate::repository::executor::{DbExecutor, SharedTransaction};
+use crate::repository::models::{UserAttributesRow, UserRow};
+use crate::repository::traits::UserRepositoryTrait;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{query, query_as, query_scalar, PgPool, Postgres};
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct PgUserRepository {
+    db: DbExecutor<Postgres>,
+}
+
+impl PgUserRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            db: DbExecutor::Pool(pool),
+        }
+    }
+
+    /// Repository whose queries run inside a shared transaction
+    pub fn in_transaction(tx: SharedTransaction<Postgres>) -> Self {
+        Self {
+            db: DbExecutor::Transaction(tx),
+        }
+    }
+}
+
+#[async_trait]
+impl UserRepositoryTrait for PgUserRepository {
+    async fn create_user(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+    ) -> Result<UserRow, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
+        let now = Utc::now();
+        let user = query_as::<_, UserRow>(
+            r#"
+            INSERT INTO users (id, tenant_id, keycloak_id, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $4)
+            RETURNING id, keycloak_id, created_at, updated_at, version, deleted_at,
+                   suspended_at, suspension_reason, last_login_at
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(tenant.to_string())
+        .bind(keycloak_id)
+        .bind(now)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(UserRepositoryError::from)?;
+
+        Ok(user)
+    }
+
+    async fn get_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<Option<UserRow>, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
+        let user = query_as::<_, UserRow>(
+            r#"
+            SELECT id, keycloak_id, created_at, updated_at, version, deleted_at,
+                   suspended_at, suspension_reason, last_login_at FROM users
+            WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(user_id.to_string())
+        .bind(tenant.to_string())
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(UserRepositoryError::from)?;
+
+        Ok(user)
+    }
+
+    async fn get_user_by_keycloak_id(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+    ) -> Result<Option<UserRow>, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
+        let user = query_as::<_, UserRow>(
+            r#"
+            SELECT id, keycloak_id, created_at, updated_at, version, deleted_at,
+                   suspended_at, suspension_reason, last_login_at FROM users
+            WHERE keycloak_id = $1 AND tenant_id = $2 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(keycloak_id)
+        .bind(tenant.to_string())
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(UserRepositoryError::from)?;
+
+        Ok(user)
+    }
+
+    async fn get_user_tenant_by_keycloak_id(
+        &self,
+        keycloak_id: &str,
+    ) -> Result<Option<TenantId>, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
+        let tenant: Option<String> = query_scalar(
+            r#"
+            SELECT tenant_id FROM users WHERE keycloak_id = $1 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(keycloak_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(UserRepositoryError::from)?;
+
+        tenant
+            .map(|t| t.parse())
+            .transpose()
+            .map_err(|e: uuid::Error| UserRepositoryError::Sqlx(sqlx::Error::Decode(e.into())))
+    }
+
+    async fn delete_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<(), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
+        let result = query(
+            r#"
+            DELETE FROM users WHERE id = $1 AND tenant_id = $2 AND ($3::BIGINT IS NULL OR version = $3)
+            "#,
+        )
+        .bind(user_id.to_string())
+        .bind(tenant.to_string())
+        .bind(expected_version)
+        .execute(&mut *conn)
+        .await
+        .map_err(UserRepositoryError::from)?;
+
+        if result.rows_affected() == 0 && expected_version.is_some() {
+            let exists: i64 =
+                query_scalar("SELECT COUNT(*) FROM users WHERE id = $1 AND tenant_id = $2")
+                    .bind(user_id.to_string())
+                    .bind(tenant.to_string())
+                    .fetch_one(&mut *conn)
+                    .await
+                    .map_err(UserRepositoryError::from)?;
+            return Err(if exists > 0 {
+                UserRepositoryError::VersionConflict
+            } else {
+                UserRepositoryError::NotFound
+            });
+        }
+        Ok(())
+    }
+
+    async fn soft_delete_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<bool, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
+        let result = query(
+            r#"
+            UPDATE users SET deleted_at = $1, updated_at = $1, version = version + 1
+            WHERE id = $2 AND tenant_id = $3 AND deleted_at IS NULL
+              AND ($4::BIGINT IS NULL OR version = $4)
+            "#,
+        )
+        .bind(Utc::now())
+        .bind(user_id.to_string())
+        .bind(tenant.to_string())
+        .bind(expected_version)
+        .execute(&mut *conn)
+        .await
+        .map_err(UserRepositoryError::from)?;
+        drop(conn);
+
+        if result.rows_affected() > 0 {
+            return Ok(true);
+        }
+        // Nothing deleted although the user is live: its version moved on
+        if expected_version.is_some() && self.get_user(tenant, user_id).await?.is_some() {
+            return Err(UserRepositoryError::VersionConflict);
+        }
+        Ok(false)
+    }
+
+    async fn restore_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<bool, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
+        let result = query(
+            r#"
+            UPDATE users SET deleted_at = NULL, updated_at = $1, version = version + 1
+            WHERE id = $2 AND tenant_id = $3 AND deleted_at IS NOT NULL
+            "#,
+        )
+        .bind(Utc::now())
+        .bind(user_id.to_string())
+        .bind(tenant.to_string())
+        .execute(&mut *conn)
+        .await
+        .map_err(UserRepositoryError::from)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn touch_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<UserRow, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
+        let user = query_as::<_, UserRow>(
+            r#"
+            UPDATE users SET updated_at = $1, version = version + 1
+            WHERE id = $2 AND tenant_id = $3 AND deleted_at IS NULL
+              AND ($4::BIGINT IS NULL OR version = $4)
+            RETURNING id, keycloak_id, created_at, updated_at, version, deleted_at,
+                   suspended_at, suspension_reason, last_login_at
+            "#,
+        )
+        .bind(Utc::now())
+        .bind(user_id.to_string())
+        .bind(tenant.to_string())
+        .bind(expected_version)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(UserRepositoryError::from)?;
+        drop(conn);
+
+        match user {
+            Some(user) => Ok(user),
+            // Nothing updated: either the user is gone or its version moved on
+            None => match self.get_user(tenant, user_id).await? {
+                Some(_) => Err(UserRepositoryError::VersionConflict),
+                None => Err(UserRepositoryError::NotFound),
+            },
+        }
+    }
+
+    async fn set_user_suspension(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        suspension: Option<UserSuspension>,
+        expected_version: Option<i64>,
+    ) -> Result<UserRow, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
+        let user = query_as::<_, UserRow>(
+            r#"
+            UPDATE users
+            SET suspended_at = $1, suspension_reason = $2, updated_at = $3, version = version + 1
+            WHERE id = $4 AND tenant_id = $5 AND deleted_at IS NULL
+              AND ($6::BIGINT IS NULL OR version = $6)
+            RETURNING id, keycloak_id, created_at, updated_at, version, deleted_at,
+                   suspended_at, suspension_reason, last_login_at
+            "#,
+        )
+        .bind(suspension.as_ref().map(|s| s.suspended_at))
+        .bind(suspension.and_then(|s| s.reason))
+        .bind(Utc::now())
+        .bind(user_id.to_string())
+        .bind(tenant.to_string())
+        .bind(expected_version)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(UserRepositoryError::from)?;
+        drop(conn);
+
+        match user {
+            Some(user) => Ok(user),
+            // Nothing updated: either the user is gone or its version moved on
+            None => match self.get_user(tenant, user_id).await? {
+                Some(_) => Err(UserRepositoryError::VersionConflict),
+                None => Err(UserRepositoryError::NotFound),
+            },
+        }
+    }
+
+    async fn record_user_login(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+        at: DateTime<Utc>,
+    ) -> Result<(), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
+        query(
+            r#"
+            UPDATE users SET last_login_at = $1
+            WHERE keycloak_id = $2 AND tenant_id = $3 AND deleted_at IS NULL
+              AND (last_login_at IS NULL OR last_login_at < $1)
+            "#,
+        )
+        .bind(at)
+        .bind(keycloak_id)
+        .bind(tenant.to_string())
+        .execute(&mut *conn)
+        .await
+        .map_err(UserRepositoryError::from)?;
+        Ok(())
+    }
+
+    async fn get_users_paginated(
+        &self,
+        tenant: TenantId,
+        pagination: PaginationParams,
+        filter: &UserFilter,
+    ) -> Result<(Vec<UserRow>, u64), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
+        let total: i64 = query_scalar(
+            r#"
+            SELECT COUNT(*)
+            FROM users u
+            LEFT JOIN user_attributes a ON a.user_id = u.id
+            WHERE u.tenant_id = $1 AND u.deleted_at IS NULL
+              AND ($2::TEXT IS NULL OR a.department = $2)
+              AND ($3::TEXT IS NULL OR a.cost_center = $3)
+              AND ($4::TEXT IS NULL OR a.locale = $4)
+              AND ($5::BOOLEAN IS NULL OR (u.suspended_at IS NOT NULL) = $5)
+            "#,
+        )
+        .bind(tenant.to_string())
+        .bind(&filter.department)
+        .bind(&filter.cost_center)
+        .bind(&filter.locale)
+        .bind(filter.suspended())
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(UserRepositoryError::from)?;
+
+        let users = query_as::<_, UserRow>(
+            r#"
+            SELECT u.id, u.keycloak_id, u.created_at, u.updated_at, u.version, u.deleted_at,
+                   u.suspended_at, u.suspension_reason, u.last_login_at
+            FROM users u
+            LEFT JOIN user_attributes a ON a.user_id = u.id
+            WHERE u.tenant_id = $1 AND u.deleted_at IS NULL
+              AND ($2::TEXT IS NULL OR a.department = $2)
+              AND ($3::TEXT IS NULL OR a.cost_center = $3)
+              AND ($4::TEXT IS NULL OR a.locale = $4)
+              AND ($5::BOOLEAN IS NULL OR (u.suspended_at IS NOT NULL) = $5)
+            ORDER BY u.id
+            LIMIT $6 OFFSET $7
+            "#,
+        )
+        .bind(tenant.to_string())
+        .bind(&filter.department)
+        .bind(&filter.cost_center)
+        .bind(&filter.locale)
+        .bind(filter.suspended())
+        .bind(i64::from(pagination.limit()))
+        .bind(i64::from(pagination.offset()))
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(UserRepositoryError::from)?;
+
+        Ok((users, total as u64))
+    }
+
+    async fn get_users_by_role_paginated(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+        pagination: PaginationParams,
+    ) -> Result<(Vec<UserRow>, u64), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
+        let now = Utc::now();
+        let total: i64 = query_scalar(
+            r#"
+            SELECT COUNT(*)
+            FROM users u
+            JOIN user_roles ur ON u.id = ur.user_id
+            WHERE ur.role_id = $1 AND u.tenant_id = $2 AND u.deleted_at IS NULL
+              AND (ur.expires_at IS NULL OR ur.expires_at > $3)
+            "#,
+        )
+        .bind(role_id.to_string())
+        .bind(tenant.to_string())
+        .bind(now)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(UserRepositoryError::from)?;
+
+        let users = query_as::<_, UserRow>(
+            r#"
+            SELECT u.id, u.keycloak_id, u.created_at, u.updated_at, u.version, u.deleted_at,
+                   u.suspended_at, u.suspension_reason, u.last_login_at
+            FROM users u
+            JOIN user_roles ur ON u.id = ur.user_id
+            WHERE ur.role_id = $1 AND u.tenant_id = $2 AND u.deleted_at IS NULL
+              AND (ur.expires_at IS NULL OR ur.expires_at > $3)
+            ORDER BY u.id
+            LIMIT $4 OFFSET $5
+            "#,
+        )
+        .bind(role_id.to_string())
+        .bind(tenant.to_string())
+        .bind(now)
+        .bind(i64::from(pagination.limit()))
+        .bind(i64::from(pagination.offset()))
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(UserRepositoryError::from)?;
+
+        Ok((users, total as u64))
+    }
+
+    async fn get_user_attributes(
+        &self,
+        tenant: TenantId,
+        user_ids: &[String],
+    ) -> Result<Vec<UserAttributesRow>, UserRepositoryError> {
+        if user_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
+
+        // Postgres binds the whole list as one array parameter
+        let rows = query_as::<_, UserAttributesRow>(
+            r#"
+            SELECT a.user_id, a.department, a.cost_center, a.locale, a.metadata, a.updated_at
+            FROM user_attributes a
+            JOIN users u ON u.id = a.user_id
+            WHERE u.tenant_id = $1 AND a.user_id = ANY($2)
+            "#,
+        )
+        .bind(tenant.to_string())
+        .bind(user_ids)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(UserRepositoryError::from)?;
+        Ok(rows)
+    }
+
+    async fn upsert_user_attributes(
+        &self,
+        tenant: TenantId,
+        attributes: &UserAttributesRow,
+    ) -> Result<(), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
+        let owned: Option<i32> =
+            query_scalar("SELECT 1 FROM users WHERE id = $1 AND tenant_id = $2")
+                .bind(&attributes.user_id)
+                .bind(tenant.to_string())
+                .fetch_optional(&mut *conn)
+                .await
+                .map_err(UserRepositoryError::from)?;
+        if owned.is_none() {
+            return Err(UserRepositoryError::NotFound);
+        }
+
+        query(
+            r#"
+            INSERT INTO user_attributes
+                (user_id, department, cost_center, locale, metadata, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (user_id) DO UPDATE SET
+                department = excluded.department,
+                cost_center = excluded.cost_center,
+                locale = excluded.locale,
+                metadata = excluded.metadata,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&attributes.user_id)
+        .bind(&attributes.department)
+        .bind(&attributes.cost_center)
+        .bind(&attributes.locale)
+        .bind(&attributes.metadata)
+        .bind(attributes.updated_at)
+        .execute(&mut *conn)
+        .await
+        .map_err(UserRepositoryError::from)?;
+
+        Ok(())
+    }
+}
@@ -1,106 +1,311 @@
 use async_trait::async_trait;
-use sqlx::{query, query_as, query_scalar, MySqlPool};
+use chrono::Utc;
+use sqlx::{query, query_as, query_scalar, MySql, MySqlPool};
 use uuid::Uuid;
 
-use crate::entities::PaginationParams;
+use crate::entities::{PaginationParams, TenantId};
 use crate::repository::errors::{map_sqlx_error, UserRepositoryError};
+use crate::repository::executor::{DbExecutor, SharedTransaction};
 use crate::repository::models::{RoleRow, UserRoleMapping};
 use crate::repository::traits::RoleRepositoryTrait;
 
 #[derive(Debug, Clone)]
 pub struct RoleRepository {
-    pub pool: MySqlPool,
+    db: DbExecutor<MySql>,
 }
 
 impl RoleRepository {
     pub fn new(pool: MySqlPool) -> Self {
-        Self { pool }
+        Self {
+            db: DbExecutor::Pool(pool),
+        }
+    }
+
+    /// Repository whose queries run inside a shared transaction
+    pub fn in_transaction(tx: SharedTransaction<MySql>) -> Self {
+        Self {
+            db: DbExecutor::Transaction(tx),
+        }
     }
 }
 
 #[async_trait]
 impl RoleRepositoryTrait for RoleRepository {
-    async fn create_role(&self, name: &str) -> Result<RoleRow, UserRepositoryError> {
+    async fn create_role(
+        &self,
+        tenant: TenantId,
+        name: &str,
+        description: Option<String>,
+    ) -> Result<RoleRow, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
         let id = Uuid::new_v4();
+        let now = Utc::now();
         query(
             r#"
-            INSERT INTO roles (id, name)
-            VALUES (?, ?)
+            INSERT INTO roles (id, tenant_id, name, description, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(id.to_string())
+        .bind(tenant.to_string())
         .bind(name)
-        .execute(&self.pool)
+        .bind(description)
+        .bind(now)
+        .bind(now)
+        .execute(&mut *conn)
         .await
         .map_err(map_sqlx_error)?;
 
-        let role = query_as::<_, RoleRow>(r#"SELECT id, name FROM roles WHERE id = ? "#)
-            .bind(id.to_string())
-            .fetch_one(&self.pool)
-            .await
-            .map_err(map_sqlx_error)?;
+        let role = query_as::<_, RoleRow>(
+            r#"
+            SELECT id, name, created_at, updated_at, version, deleted_at, description, is_system
+            FROM roles WHERE id = ?
+            "#,
+        )
+        .bind(id.to_string())
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
         Ok(role)
     }
 
-    async fn get_role(&self, role_id: Uuid) -> Result<Option<RoleRow>, UserRepositoryError> {
+    async fn get_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+    ) -> Result<Option<RoleRow>, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
         let role = query_as::<_, RoleRow>(
             r#"
-            SELECT id, name FROM roles WHERE id = ?
+            SELECT id, name, created_at, updated_at, version, deleted_at, description, is_system
+            FROM roles
+            WHERE id = ? AND tenant_id = ? AND deleted_at IS NULL
             "#,
         )
         .bind(role_id)
-        .fetch_optional(&self.pool)
+        .bind(tenant.to_string())
+        .fetch_optional(&mut *conn)
         .await
         .map_err(map_sqlx_error)?;
         Ok(role)
     }
 
-    async fn update_role(&self, role_id: Uuid, name: &str) -> Result<RoleRow, UserRepositoryError> {
-        query(
+    async fn get_system_role(
+        &self,
+        tenant: TenantId,
+        name: &str,
+    ) -> Result<Option<RoleRow>, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        // live_name is only set on live roles and is indexed with the tenant
+        let role = query_as::<_, RoleRow>(
+            r#"
+            SELECT id, name, created_at, updated_at, version, deleted_at, description, is_system
+            FROM roles
+            WHERE tenant_id = ? AND live_name = ? AND is_system = TRUE
+            "#,
+        )
+        .bind(tenant.to_string())
+        .bind(name)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+        Ok(role)
+    }
+
+    async fn update_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+        name: &str,
+        description: Option<String>,
+        expected_version: Option<i64>,
+    ) -> Result<RoleRow, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let result = query(
             r#"
             UPDATE roles
-            SET name = ?
-            WHERE id = ?
+            SET name = ?, description = ?, updated_at = ?, version = version + 1
+            WHERE id = ? AND tenant_id = ? AND deleted_at IS NULL AND (? IS NULL OR version = ?)
             "#,
         )
         .bind(name)
+        .bind(description)
+        .bind(Utc::now())
         .bind(role_id)
-        .execute(&self.pool)
+        .bind(tenant.to_string())
+        .bind(expected_version)
+        .bind(expected_version)
+        .execute(&mut *conn)
         .await
         .map_err(map_sqlx_error)?;
 
-        let role = query_as::<_, RoleRow>(r#"SELECT id, name FROM roles WHERE id = ? "#)
-            .bind(role_id)
-            .fetch_one(&self.pool)
-            .await
-            .map_err(map_sqlx_error)?;
+        let role = query_as::<_, RoleRow>(
+            r#"
+            SELECT id, name, created_at, updated_at, version, deleted_at, description, is_system
+            FROM roles
+            WHERE id = ? AND tenant_id = ? AND deleted_at IS NULL
+            "#,
+        )
+        .bind(role_id)
+        .bind(tenant.to_string())
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        // Nothing updated although the role is live: its version moved on
+        if result.rows_affected() == 0 {
+            return Err(UserRepositoryError::VersionConflict);
+        }
         Ok(role)
     }
 
-    async fn delete_role(&self, role_id: Uuid) -> Result<(), UserRepositoryError> {
-        sqlx::query(
+    async fn delete_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<(), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let result = sqlx::query(
             r#"
-            DELETE FROM roles WHERE id = ?
+            DELETE FROM roles WHERE id = ? AND tenant_id = ? AND (? IS NULL OR version = ?)
             "#,
         )
         .bind(role_id)
-        .execute(&self.pool)
+        .bind(tenant.to_string())
+        .bind(expected_version)
+        .bind(expected_version)
+        .execute(&mut *conn)
         .await
         .map_err(map_sqlx_error)?;
+
+        if result.rows_affected() == 0 && expected_version.is_some() {
+            let exists: i64 =
+                query_scalar("SELECT COUNT(*) FROM roles WHERE id = ? AND tenant_id = ?")
+                    .bind(role_id)
+                    .bind(tenant.to_string())
+                    .fetch_one(&mut *conn)
+                    .await
+                    .map_err(map_sqlx_error)?;
+            return Err(if exists > 0 {
+                UserRepositoryError::VersionConflict
+            } else {
+                UserRepositoryError::NotFound
+            });
+        }
         Ok(())
     }
 
-    async fn get_roles_for_user(&self, user_id: Uuid) -> Result<Vec<RoleRow>, UserRepositoryError> {
+    async fn soft_delete_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<bool, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let now = Utc::now();
+        let result = query(
+            r#"
+            UPDATE roles SET deleted_at = ?, updated_at = ?, version = version + 1
+            WHERE id = ? AND tenant_id = ? AND deleted_at IS NULL AND (? IS NULL OR version = ?)
+            "#,
+        )
+        .bind(now)
+        .bind(now)
+        .bind(role_id.to_string())
+        .bind(tenant.to_string())
+        .bind(expected_version)
+        .bind(expected_version)
+        .execute(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+        drop(conn);
+
+        if result.rows_affected() > 0 {
+            return Ok(true);
+        }
+        // Nothing deleted although the role is live: its version moved on
+        if expected_version.is_some() && self.get_role(tenant, role_id).await?.is_some() {
+            return Err(UserRepositoryError::VersionConflict);
+        }
+        Ok(false)
+    }
+
+    async fn restore_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+    ) -> Result<bool, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let result = query(
+            r#"
+            UPDATE roles SET deleted_at = NULL, updated_at = ?, version = version + 1
+            WHERE id = ? AND tenant_id = ? AND deleted_at IS NOT NULL
+            "#,
+        )
+        .bind(Utc::now())
+        .bind(role_id.to_string())
+        .bind(tenant.to_string())
+        .execute(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn get_roles_for_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<Vec<RoleRow>, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
         let roles = query_as::<_, RoleRow>(
             r#"
-            SELECT r.id, r.name
+            SELECT r.id, r.name, r.created_at, r.updated_at, r.version, r.deleted_at,
+                   r.description, r.is_system
             FROM roles r
             INNER JOIN user_roles ur ON ur.role_id = r.id
-            WHERE ur.user_id = ?
+            WHERE ur.user_id = ? AND r.tenant_id = ? AND r.deleted_at IS NULL
+              AND (ur.expires_at IS NULL OR ur.expires_at > ?)
+            UNION
+            SELECT r.id, r.name, r.created_at, r.updated_at, r.version, r.deleted_at,
+                   r.description, r.is_system
+            FROM roles r
+            INNER JOIN group_roles gr ON gr.role_id = r.id
+            INNER JOIN group_members gm ON gm.group_id = gr.group_id
+            WHERE gm.user_id = ? AND r.tenant_id = ? AND r.deleted_at IS NULL
             "#,
         )
         .bind(user_id)
-        .fetch_all(&self.pool)
+        .bind(tenant.to_string())
+        .bind(Utc::now())
+        .bind(user_id)
+        .bind(tenant.to_string())
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+        Ok(roles)
+    }
+
+    async fn get_granted_roles_for_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<Vec<RoleRow>, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let roles = query_as::<_, RoleRow>(
+            r#"
+            SELECT r.id, r.name, r.created_at, r.updated_at, r.version, r.deleted_at,
+                   r.description, r.is_system
+            FROM roles r
+            INNER JOIN user_roles ur ON ur.role_id = r.id
+            WHERE ur.user_id = ? AND r.tenant_id = ? AND r.deleted_at IS NULL
+              AND (ur.expires_at IS NULL OR ur.expires_at > ?)
+            "#,
+        )
+        .bind(user_id)
+        .bind(tenant.to_string())
+        .bind(Utc::now())
+        .fetch_all(&mut *conn)
         .await
         .map_err(map_sqlx_error)?;
         Ok(roles)
@@ -108,11 +313,13 @@ impl RoleRepositoryTrait for RoleRepository {
 
     async fn get_roles_for_users(
         &self,
+        tenant: TenantId,
         user_ids: &[String],
     ) -> Result<Vec<UserRoleMapping>, UserRepositoryError> {
         if user_ids.is_empty() {
             return Ok(vec![]);
         }
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
 
         // SAFETY: This dynamic SQL is safe from injection because:
         // 1. Only placeholder characters (?) are interpolated into the query string
@@ -121,40 +328,64 @@ impl RoleRepositoryTrait for RoleRepository {
         let placeholders = user_ids.iter().map(|_| "?").collect::<Vec<_>>().join(", ");
         let query_str = format!(
             r#"
-            SELECT ur.user_id, r.id as role_id, r.name as role_name
+            SELECT ur.user_id, r.id as role_id, r.name as role_name,
+                   r.created_at as role_created_at, r.updated_at as role_updated_at,
+                   r.version as role_version, r.description as role_description,
+                   r.is_system as role_is_system
             FROM roles r
             INNER JOIN user_roles ur ON ur.role_id = r.id
-            WHERE ur.user_id IN ({placeholders})
+            WHERE r.tenant_id = ? AND ur.user_id IN ({placeholders}) AND r.deleted_at IS NULL
+              AND (ur.expires_at IS NULL OR ur.expires_at > ?)
+            UNION
+            SELECT gm.user_id, r.id as role_id, r.name as role_name,
+                   r.created_at as role_created_at, r.updated_at as role_updated_at,
+                   r.version as role_version, r.description as role_description,
+                   r.is_system as role_is_system
+            FROM roles r
+            INNER JOIN group_roles gr ON gr.role_id = r.id
+            INNER JOIN group_members gm ON gm.group_id = gr.group_id
+            WHERE r.tenant_id = ? AND gm.user_id IN ({placeholders}) AND r.deleted_at IS NULL
             "#
         );
 
-        let mut query = sqlx::query_as::<_, UserRoleMapping>(&query_str);
+        let mut query = sqlx::query_as::<_, UserRoleMapping>(&query_str).bind(tenant.to_string());
+        for user_id in user_ids {
+            query = query.bind(user_id);
+        }
+        query = query.bind(Utc::now()).bind(tenant.to_string());
         for user_id in user_ids {
             query = query.bind(user_id);
         }
-        let mappings = query.fetch_all(&self.pool).await.map_err(map_sqlx_error)?;
+        let mappings = query.fetch_all(&mut *conn).await.map_err(map_sqlx_error)?;
         Ok(mappings)
     }
 
     async fn get_roles_paginated(
         &self,
+        tenant: TenantId,
         pagination: PaginationParams,
     ) -> Result<(Vec<RoleRow>, u64), UserRepositoryError> {
-        let total: i64 = query_scalar("SELECT COUNT(*) FROM roles")
-            .fetch_one(&self.pool)
-            .await
-            .map_err(map_sqlx_error)?;
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let total: i64 =
+            query_scalar("SELECT COUNT(*) FROM roles WHERE tenant_id = ? AND deleted_at IS NULL")
+                .bind(tenant.to_string())
+                .fetch_one(&mut *conn)
+                .await
+                .map_err(map_sqlx_error)?;
 
         let roles = query_as::<_, RoleRow>(
             r#"
-            SELECT id, name FROM roles
+            SELECT id, name, created_at, updated_at, version, deleted_at, description, is_system
+            FROM roles
+            WHERE tenant_id = ? AND deleted_at IS NULL
             ORDER BY name
             LIMIT ? OFFSET ?
             "#,
         )
+        .bind(tenant.to_string())
         .bind(pagination.limit())
         .bind(pagination.offset())
-        .fetch_all(&self.pool)
+        .fetch_all(&mut *conn)
         .await
         .map_err(map_sqlx_error)?;
 
@@ -0,0 +1,374 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::{query, query_as, query_scalar, Sqlite, SqlitePool};
+use uuid::Uuid;
+
+use crate::entities::{PaginationParams, TenantId};
+use crate::repository::errors::{map_sqlx_error, UserRepositoryError};
+use crate::repository::executor::{DbExecutor, SharedTransaction};
+use crate::repository::models::{GroupRoleMapping, GroupRow, UserRow};
+use crate::repository::traits::GroupRepositoryTrait;
+
+#[derive(Debug, Clone)]
+pub struct SqliteGroupRepository {
+    db: DbExecutor<Sqlite>,
+}
+
+impl SqliteGroupRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            db: DbExecutor::Pool(pool),
+        }
+    }
+
+    /// Repository whose queries run inside a shared transaction
+    pub fn in_transaction(tx: SharedTransaction<Sqlite>) -> Self {
+        Self {
+            db: DbExecutor::Transaction(tx),
+        }
+    }
+}
+
+#[async_trait]
+impl GroupRepositoryTrait for SqliteGroupRepository {
+    async fn create_group(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+        name: &str,
+    ) -> Result<GroupRow, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let id = Uuid::new_v4().to_string();
+        let now = Utc::now();
+        query(
+            r#"
+            INSERT INTO user_groups (id, tenant_id, keycloak_id, name, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&id)
+        .bind(tenant.to_string())
+        .bind(keycloak_id)
+        .bind(name)
+        .bind(now)
+        .bind(now)
+        .execute(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        query_as::<_, GroupRow>(
+            "SELECT id, keycloak_id, name, created_at, updated_at FROM user_groups WHERE id = ?",
+        )
+        .bind(&id)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)
+    }
+
+    async fn get_group(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+    ) -> Result<Option<GroupRow>, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        query_as::<_, GroupRow>(
+            r#"
+            SELECT id, keycloak_id, name, created_at, updated_at
+            FROM user_groups
+            WHERE id = ? AND tenant_id = ?
+            "#,
+        )
+        .bind(group_id.to_string())
+        .bind(tenant.to_string())
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)
+    }
+
+    async fn get_groups_paginated(
+        &self,
+        tenant: TenantId,
+        pagination: PaginationParams,
+    ) -> Result<(Vec<GroupRow>, u64), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let total: i64 = query_scalar("SELECT COUNT(*) FROM user_groups WHERE tenant_id = ?")
+            .bind(tenant.to_string())
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        let groups = query_as::<_, GroupRow>(
+            r#"
+            SELECT id, keycloak_id, name, created_at, updated_at
+            FROM user_groups
+            WHERE tenant_id = ?
+            ORDER BY name
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(tenant.to_string())
+        .bind(i64::from(pagination.limit()))
+        .bind(i64::from(pagination.offset()))
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        Ok((groups, total as u64))
+    }
+
+    async fn rename_group(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        name: &str,
+    ) -> Result<GroupRow, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        query("UPDATE user_groups SET name = ?, updated_at = ? WHERE id = ? AND tenant_id = ?")
+            .bind(name)
+            .bind(Utc::now())
+            .bind(group_id.to_string())
+            .bind(tenant.to_string())
+            .execute(&mut *conn)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        query_as::<_, GroupRow>(
+            r#"
+            SELECT id, keycloak_id, name, created_at, updated_at
+            FROM user_groups
+            WHERE id = ? AND tenant_id = ?
+            "#,
+        )
+        .bind(group_id.to_string())
+        .bind(tenant.to_string())
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?
+        .ok_or(UserRepositoryError::NotFound)
+    }
+
+    async fn delete_group(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+    ) -> Result<bool, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let result = query("DELETE FROM user_groups WHERE id = ? AND tenant_id = ?")
+            .bind(group_id.to_string())
+            .bind(tenant.to_string())
+            .execute(&mut *conn)
+            .await
+            .map_err(map_sqlx_error)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn add_group_member(
+        &self,
+        tenant: TenantId,
+        group_id: &str,
+        user_id: &str,
+    ) -> Result<(), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let result = query(
+            r#"
+            INSERT INTO group_members (group_id, user_id, tenant_id, added_at)
+            SELECT ?, ?, ?, ?
+            WHERE EXISTS (SELECT 1 FROM user_groups WHERE id = ? AND tenant_id = ?)
+              AND EXISTS (
+                SELECT 1 FROM users WHERE id = ? AND tenant_id = ? AND deleted_at IS NULL
+            )
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(group_id)
+        .bind(user_id)
+        .bind(tenant.to_string())
+        .bind(Utc::now())
+        .bind(group_id)
+        .bind(tenant.to_string())
+        .bind(user_id)
+        .bind(tenant.to_string())
+        .execute(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        // Nothing inserted: either the user is already a member or one side is missing
+        if result.rows_affected() == 0 {
+            let members: i64 = query_scalar(
+                r#"
+                SELECT COUNT(*) FROM group_members
+                WHERE group_id = ? AND user_id = ? AND tenant_id = ?
+                "#,
+            )
+            .bind(group_id)
+            .bind(user_id)
+            .bind(tenant.to_string())
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(map_sqlx_error)?;
+            if members == 0 {
+                return Err(UserRepositoryError::NotFound);
+            }
+        }
+        Ok(())
+    }
+
+    async fn remove_group_member(
+        &self,
+        tenant: TenantId,
+        group_id: &str,
+        user_id: &str,
+    ) -> Result<(), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        query("DELETE FROM group_members WHERE group_id = ? AND user_id = ? AND tenant_id = ?")
+            .bind(group_id)
+            .bind(user_id)
+            .bind(tenant.to_string())
+            .execute(&mut *conn)
+            .await
+            .map_err(map_sqlx_error)?;
+        Ok(())
+    }
+
+    async fn get_group_members_paginated(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        pagination: PaginationParams,
+    ) -> Result<(Vec<UserRow>, u64), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let total: i64 = query_scalar(
+            r#"
+            SELECT COUNT(*)
+            FROM users u
+            JOIN group_members gm ON u.id = gm.user_id
+            WHERE gm.group_id = ? AND u.tenant_id = ? AND u.deleted_at IS NULL
+            "#,
+        )
+        .bind(group_id.to_string())
+        .bind(tenant.to_string())
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        let users = query_as::<_, UserRow>(
+            r#"
+            SELECT u.id, u.keycloak_id, u.created_at, u.updated_at, u.version, u.deleted_at,
+                   u.suspended_at, u.suspension_reason, u.last_login_at
+            FROM users u
+            JOIN group_members gm ON u.id = gm.user_id
+            WHERE gm.group_id = ? AND u.tenant_id = ? AND u.deleted_at IS NULL
+            ORDER BY u.id
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(group_id.to_string())
+        .bind(tenant.to_string())
+        .bind(i64::from(pagination.limit()))
+        .bind(i64::from(pagination.offset()))
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        Ok((users, total as u64))
+    }
+
+    async fn assign_group_role(
+        &self,
+        tenant: TenantId,
+        group_id: &str,
+        role_id: &str,
+    ) -> Result<(), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let result = query(
+            r#"
+            INSERT INTO group_roles (group_id, role_id, tenant_id)
+            SELECT ?, ?, ?
+            WHERE EXISTS (SELECT 1 FROM user_groups WHERE id = ? AND tenant_id = ?)
+              AND EXISTS (
+                SELECT 1 FROM roles WHERE id = ? AND tenant_id = ? AND deleted_at IS NULL
+            )
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(group_id)
+        .bind(role_id)
+        .bind(tenant.to_string())
+        .bind(group_id)
+        .bind(tenant.to_string())
+        .bind(role_id)
+        .bind(tenant.to_string())
+        .execute(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        // Nothing inserted: either the role is already attached or one side is missing
+        if result.rows_affected() == 0 {
+            let attached: i64 = query_scalar(
+                r#"
+                SELECT COUNT(*) FROM group_roles
+                WHERE group_id = ? AND role_id = ? AND tenant_id = ?
+                "#,
+            )
+            .bind(group_id)
+            .bind(role_id)
+            .bind(tenant.to_string())
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(map_sqlx_error)?;
+            if attached == 0 {
+                return Err(UserRepositoryError::NotFound);
+            }
+        }
+        Ok(())
+    }
+
+    async fn unassign_group_role(
+        &self,
+        tenant: TenantId,
+        group_id: &str,
+        role_id: &str,
+    ) -> Result<(), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        query("DELETE FROM group_roles WHERE group_id = ? AND role_id = ? AND tenant_id = ?")
+            .bind(group_id)
+            .bind(role_id)
+            .bind(tenant.to_string())
+            .execute(&mut *conn)
+            .await
+            .map_err(map_sqlx_error)?;
+        Ok(())
+    }
+
+    async fn get_roles_for_groups(
+        &self,
+        tenant: TenantId,
+        group_ids: &[String],
+    ) -> Result<Vec<GroupRoleMapping>, UserRepositoryError> {
+        if group_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+
+        // Only placeholders are interpolated; the ids themselves are bound
+        let placeholders = group_ids.iter().map(|_| "?").collect::<Vec<_>>().join(", ");
+        let query_str = format!(
+            r#"
+            SELECT gr.group_id, r.id as role_id, r.name as role_name,
+                   r.created_at as role_created_at, r.updated_at as role_updated_at,
+                   r.version as role_version, r.description as role_description,
+                   r.is_system as role_is_system
+            FROM roles r
+            INNER JOIN group_roles gr ON gr.role_id = r.id
+            WHERE r.tenant_id = ? AND gr.group_id IN ({placeholders}) AND r.deleted_at IS NULL
+            ORDER BY r.name
+            "#
+        );
+
+        let mut query = query_as::<_, GroupRoleMapping>(&query_str).bind(tenant.to_string());
+        for group_id in group_ids {
+            query = query.bind(group_id);
+        }
+        query.fetch_all(&mut *conn).await.map_err(map_sqlx_error)
+    }
+}
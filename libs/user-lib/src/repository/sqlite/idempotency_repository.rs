@@ -0,0 +1,106 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{query, query_as, SqlitePool};
+
+use crate::repository::errors::{map_sqlx_error, UserRepositoryError};
+use crate::repository::models::IdempotencyRow;
+use crate::repository::traits::IdempotencyRepositoryTrait;
+
+/// Runs straight on the pool: stored responses must outlive any unit of work
+#[derive(Debug, Clone)]
+pub struct SqliteIdempotencyRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteIdempotencyRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl IdempotencyRepositoryTrait for SqliteIdempotencyRepository {
+    async fn reserve_idempotency_key(
+        &self,
+        key: &str,
+        fingerprint: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<bool, UserRepositoryError> {
+        let now = Utc::now();
+        query("DELETE FROM idempotency_keys WHERE expires_at <= $1")
+            .bind(now)
+            .execute(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        let result = query(
+            r#"
+            INSERT INTO idempotency_keys (idempotency_key, fingerprint, created_at, expires_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (idempotency_key) DO NOTHING
+            "#,
+        )
+        .bind(key)
+        .bind(fingerprint)
+        .bind(now)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn get_idempotency_key(
+        &self,
+        key: &str,
+    ) -> Result<Option<IdempotencyRow>, UserRepositoryError> {
+        query_as::<_, IdempotencyRow>(
+            r#"
+            SELECT idempotency_key, fingerprint, status_code, response_body, response_headers,
+                   created_at, expires_at
+            FROM idempotency_keys
+            WHERE idempotency_key = $1 AND expires_at > $2
+            "#,
+        )
+        .bind(key)
+        .bind(Utc::now())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(map_sqlx_error)
+    }
+
+    async fn complete_idempotency_key(
+        &self,
+        key: &str,
+        status_code: i32,
+        response_body: &str,
+        response_headers: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), UserRepositoryError> {
+        query(
+            r#"
+            UPDATE idempotency_keys
+            SET status_code = $2, response_body = $3, response_headers = $4, expires_at = $5
+            WHERE idempotency_key = $1
+            "#,
+        )
+        .bind(key)
+        .bind(status_code)
+        .bind(response_body)
+        .bind(response_headers)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+        Ok(())
+    }
+
+    async fn release_idempotency_key(&self, key: &str) -> Result<(), UserRepositoryError> {
+        query("DELETE FROM idempotency_keys WHERE idempotency_key = $1 AND status_code IS NULL")
+            .bind(key)
+            .execute(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,41 @@
+//! SQLite implementations of the repository traits.
+//! Schema lives in `migrations_sqlite/`; ids are stored as `TEXT`.
+
+use async_trait::async_trait;
+use sqlx::{Sqlite, SqlitePool};
+
+use crate::repository::errors::{map_sqlx_error, UserRepositoryError};
+use crate::repository::executor::begin_shared;
+use crate::repository::unit_of_work::{SqlUnitOfWork, UnitOfWork, UnitOfWorkFactory};
+
+pub mod api_key_repository;
+pub mod event_checkpoint_repository;
+pub mod group_repository;
+pub mod idempotency_repository;
+pub mod role_repository;
+pub mod tenant_repository;
+pub mod user_repository;
+pub mod user_role_repository;
+
+pub use api_key_repository::SqliteApiKeyRepository;
+pub use event_checkpoint_repository::SqliteEventCheckpointRepository;
+pub use group_repository::SqliteGroupRepository;
+pub use idempotency_repository::SqliteIdempotencyRepository;
+pub use role_repository::SqliteRoleRepository;
+pub use tenant_repository::SqliteTenantRepository;
+pub use user_repository::SqliteUserRepository;
+pub use user_role_repository::SqliteUserRoleRepository;
+
+#[async_trait]
+impl UnitOfWorkFactory for SqlitePool {
+    async fn begin(&self) -> Result<Box<dyn UnitOfWork>, UserRepositoryError> {
+        let tx = begin_shared::<Sqlite>(self).await.map_err(map_sqlx_error)?;
+        Ok(Box::new(SqlUnitOfWork::new(
+            tx.clone(),
+            SqliteUserRepository::in_transaction(tx.clone()),
+            SqliteRoleRepository::in_transaction(tx.clone()),
+            SqliteUserRoleRepository::in_transaction(tx.clone()),
+            SqliteTenantRepository::in_transaction(tx),
+        )))
+    }
+}
@@ -0,0 +1,374 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::{query_as, query_scalar, Sqlite, SqlitePool};
+use uuid::Uuid;
+
+use crate::entities::{PaginationParams, TenantId};
+use crate::repository::errors::{map_sqlx_error, UserRepositoryError};
+use crate::repository::executor::{DbExecutor, SharedTransaction};
+use crate::repository::models::{RoleRow, UserRoleMapping};
+use crate::repository::traits::RoleRepositoryTrait;
+
+#[derive(Debug, Clone)]
+pub struct SqliteRoleRepository {
+    db: DbExecutor<Sqlite>,
+}
+
+impl SqliteRoleRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            db: DbExecutor::Pool(pool),
+        }
+    }
+
+    /// Repository whose queries run inside a shared transaction
+    pub fn in_transaction(tx: SharedTransaction<Sqlite>) -> Self {
+        Self {
+            db: DbExecutor::Transaction(tx),
+        }
+    }
+}
+
+#[async_trait]
+impl RoleRepositoryTrait for SqliteRoleRepository {
+    async fn create_role(
+        &self,
+        tenant: TenantId,
+        name: &str,
+        description: Option<String>,
+    ) -> Result<RoleRow, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let now = Utc::now();
+        let role = query_as::<_, RoleRow>(
+            r#"
+            INSERT INTO roles (id, tenant_id, name, description, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            RETURNING id, name, created_at, updated_at, version, deleted_at,
+                description, is_system
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(tenant.to_string())
+        .bind(name)
+        .bind(description)
+        .bind(now)
+        .bind(now)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+        Ok(role)
+    }
+
+    async fn get_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+    ) -> Result<Option<RoleRow>, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let role = query_as::<_, RoleRow>(
+            r#"
+            SELECT id, name, created_at, updated_at, version, deleted_at, description, is_system
+            FROM roles
+            WHERE id = ? AND tenant_id = ? AND deleted_at IS NULL
+            "#,
+        )
+        .bind(role_id.to_string())
+        .bind(tenant.to_string())
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+        Ok(role)
+    }
+
+    async fn get_system_role(
+        &self,
+        tenant: TenantId,
+        name: &str,
+    ) -> Result<Option<RoleRow>, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        // Matches the partial role_name_unique index on (tenant_id, name) of live roles
+        let role = query_as::<_, RoleRow>(
+            r#"
+            SELECT id, name, created_at, updated_at, version, deleted_at, description, is_system
+            FROM roles
+            WHERE tenant_id = ? AND name = ? AND deleted_at IS NULL AND is_system
+            "#,
+        )
+        .bind(tenant.to_string())
+        .bind(name)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+        Ok(role)
+    }
+
+    async fn update_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+        name: &str,
+        description: Option<String>,
+        expected_version: Option<i64>,
+    ) -> Result<RoleRow, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let role = query_as::<_, RoleRow>(
+            r#"
+            UPDATE roles
+            SET name = ?, description = ?, updated_at = ?, version = version + 1
+            WHERE id = ? AND tenant_id = ? AND deleted_at IS NULL AND (? IS NULL OR version = ?)
+            RETURNING id, name, created_at, updated_at, version, deleted_at,
+                description, is_system
+            "#,
+        )
+        .bind(name)
+        .bind(description)
+        .bind(Utc::now())
+        .bind(role_id.to_string())
+        .bind(tenant.to_string())
+        .bind(expected_version)
+        .bind(expected_version)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+        drop(conn);
+
+        match role {
+            Some(role) => Ok(role),
+            // Nothing updated: either the role is gone or its version moved on
+            None => match self.get_role(tenant, role_id).await? {
+                Some(_) => Err(UserRepositoryError::VersionConflict),
+                None => Err(UserRepositoryError::Sqlx(sqlx::Error::RowNotFound)),
+            },
+        }
+    }
+
+    async fn delete_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<(), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let result = sqlx::query(
+            r#"
+            DELETE FROM roles WHERE id = ? AND tenant_id = ? AND (? IS NULL OR version = ?)
+            "#,
+        )
+        .bind(role_id.to_string())
+        .bind(tenant.to_string())
+        .bind(expected_version)
+        .bind(expected_version)
+        .execute(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        if result.rows_affected() == 0 && expected_version.is_some() {
+            let exists: i64 =
+                query_scalar("SELECT COUNT(*) FROM roles WHERE id = ? AND tenant_id = ?")
+                    .bind(role_id.to_string())
+                    .bind(tenant.to_string())
+                    .fetch_one(&mut *conn)
+                    .await
+                    .map_err(map_sqlx_error)?;
+            return Err(if exists > 0 {
+                UserRepositoryError::VersionConflict
+            } else {
+                UserRepositoryError::NotFound
+            });
+        }
+        Ok(())
+    }
+
+    async fn soft_delete_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<bool, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let now = Utc::now();
+        let result = sqlx::query(
+            r#"
+            UPDATE roles SET deleted_at = ?, updated_at = ?, version = version + 1
+            WHERE id = ? AND tenant_id = ? AND deleted_at IS NULL AND (? IS NULL OR version = ?)
+            "#,
+        )
+        .bind(now)
+        .bind(now)
+        .bind(role_id.to_string())
+        .bind(tenant.to_string())
+        .bind(expected_version)
+        .bind(expected_version)
+        .execute(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+        drop(conn);
+
+        if result.rows_affected() > 0 {
+            return Ok(true);
+        }
+        // Nothing deleted although the role is live: its version moved on
+        if expected_version.is_some() && self.get_role(tenant, role_id).await?.is_some() {
+            return Err(UserRepositoryError::VersionConflict);
+        }
+        Ok(false)
+    }
+
+    async fn restore_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+    ) -> Result<bool, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let result = sqlx::query(
+            r#"
+            UPDATE roles SET deleted_at = NULL, updated_at = ?, version = version + 1
+            WHERE id = ? AND tenant_id = ? AND deleted_at IS NOT NULL
+            "#,
+        )
+        .bind(Utc::now())
+        .bind(role_id.to_string())
+        .bind(tenant.to_string())
+        .execute(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn get_roles_for_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<Vec<RoleRow>, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let roles = query_as::<_, RoleRow>(
+            r#"
+            SELECT r.id, r.name, r.created_at, r.updated_at, r.version, r.deleted_at,
+                   r.description, r.is_system
+            FROM roles r
+            INNER JOIN user_roles ur ON ur.role_id = r.id
+            WHERE r.deleted_at IS NULL AND ur.user_id = ? AND r.tenant_id = ?
+              AND (ur.expires_at IS NULL OR ur.expires_at > ?)
+            UNION
+            SELECT r.id, r.name, r.created_at, r.updated_at, r.version, r.deleted_at,
+                   r.description, r.is_system
+            FROM roles r
+            INNER JOIN group_roles gr ON gr.role_id = r.id
+            INNER JOIN group_members gm ON gm.group_id = gr.group_id
+            WHERE r.deleted_at IS NULL AND gm.user_id = ? AND r.tenant_id = ?
+            "#,
+        )
+        .bind(user_id.to_string())
+        .bind(tenant.to_string())
+        .bind(Utc::now())
+        .bind(user_id.to_string())
+        .bind(tenant.to_string())
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+        Ok(roles)
+    }
+
+    async fn get_granted_roles_for_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<Vec<RoleRow>, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let roles = query_as::<_, RoleRow>(
+            r#"
+            SELECT r.id, r.name, r.created_at, r.updated_at, r.version, r.deleted_at,
+                   r.description, r.is_system
+            FROM roles r
+            INNER JOIN user_roles ur ON ur.role_id = r.id
+            WHERE r.deleted_at IS NULL AND ur.user_id = ? AND r.tenant_id = ?
+              AND (ur.expires_at IS NULL OR ur.expires_at > ?)
+            "#,
+        )
+        .bind(user_id.to_string())
+        .bind(tenant.to_string())
+        .bind(Utc::now())
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+        Ok(roles)
+    }
+
+    async fn get_roles_for_users(
+        &self,
+        tenant: TenantId,
+        user_ids: &[String],
+    ) -> Result<Vec<UserRoleMapping>, UserRepositoryError> {
+        if user_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+
+        // Only placeholders are interpolated; every id is bound as a parameter
+        let placeholders = user_ids.iter().map(|_| "?").collect::<Vec<_>>().join(", ");
+        let query_str = format!(
+            r#"
+            SELECT ur.user_id, r.id as role_id, r.name as role_name,
+                   r.created_at as role_created_at, r.updated_at as role_updated_at,
+                   r.version as role_version, r.description as role_description,
+                   r.is_system as role_is_system
+            FROM roles r
+            INNER JOIN user_roles ur ON ur.role_id = r.id
+            WHERE r.tenant_id = ? AND r.deleted_at IS NULL AND ur.user_id IN ({placeholders})
+              AND (ur.expires_at IS NULL OR ur.expires_at > ?)
+            UNION
+            SELECT gm.user_id, r.id as role_id, r.name as role_name,
+                   r.created_at as role_created_at, r.updated_at as role_updated_at,
+                   r.version as role_version, r.description as role_description,
+                   r.is_system as role_is_system
+            FROM roles r
+            INNER JOIN group_roles gr ON gr.role_id = r.id
+            INNER JOIN group_members gm ON gm.group_id = gr.group_id
+            WHERE r.tenant_id = ? AND r.deleted_at IS NULL AND gm.user_id IN ({placeholders})
+            "#
+        );
+
+        let mut query = query_as::<_, UserRoleMapping>(&query_str).bind(tenant.to_string());
+        for user_id in user_ids {
+            query = query.bind(user_id);
+        }
+        query = query.bind(Utc::now()).bind(tenant.to_string());
+        for user_id in user_ids {
+            query = query.bind(user_id);
+        }
+        let mappings = query.fetch_all(&mut *conn).await.map_err(map_sqlx_error)?;
+        Ok(mappings)
+    }
+
+    async fn get_roles_paginated(
+        &self,
+        tenant: TenantId,
+        pagination: PaginationParams,
+    ) -> Result<(Vec<RoleRow>, u64), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let total: i64 =
+            query_scalar("SELECT COUNT(*) FROM roles WHERE tenant_id = ? AND deleted_at IS NULL")
+                .bind(tenant.to_string())
+                .fetch_one(&mut *conn)
+                .await
+                .map_err(map_sqlx_error)?;
+
+        let roles = query_as::<_, RoleRow>(
+            r#"
+            SELECT id, name, created_at, updated_at, version, deleted_at, description, is_system
+            FROM roles
+            WHERE tenant_id = ? AND deleted_at IS NULL
+            ORDER BY name
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(tenant.to_string())
+        .bind(i64::from(pagination.limit()))
+        .bind(i64::from(pagination.offset()))
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        Ok((roles, total as u64))
+    }
+}
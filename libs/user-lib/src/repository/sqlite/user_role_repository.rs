@@ -0,0 +1,135 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{query, query_as, Sqlite, SqlitePool};
+
+use crate::entities::{RoleGrant, TenantId};
+use crate::repository::errors::{map_sqlx_error, UserRepositoryError};
+use crate::repository::executor::{DbExecutor, SharedTransaction};
+use crate::repository::traits::UserRoleRepositoryTrait;
+
+#[derive(Debug, Clone)]
+pub struct SqliteUserRoleRepository {
+    db: DbExecutor<Sqlite>,
+}
+
+impl SqliteUserRoleRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            db: DbExecutor::Pool(pool),
+        }
+    }
+
+    /// Repository whose queries run inside a shared transaction
+    pub fn in_transaction(tx: SharedTransaction<Sqlite>) -> Self {
+        Self {
+            db: DbExecutor::Transaction(tx),
+        }
+    }
+}
+
+#[async_trait]
+impl UserRoleRepositoryTrait for SqliteUserRoleRepository {
+    async fn assign_role(
+        &self,
+        tenant: TenantId,
+        user_id: &str,
+        role_id: &str,
+        grant: &RoleGrant,
+    ) -> Result<(), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        // Only insert when both sides are live, so soft-deleted rows can't gain assignments
+        let result = query(
+            r#"
+            INSERT INTO user_roles
+                (user_id, role_id, tenant_id, granted_at, expires_at, granted_by)
+            SELECT ?, ?, ?, ?, ?, ?
+            WHERE EXISTS (
+                SELECT 1 FROM users WHERE id = ? AND tenant_id = ? AND deleted_at IS NULL
+            )
+              AND EXISTS (
+                SELECT 1 FROM roles WHERE id = ? AND tenant_id = ? AND deleted_at IS NULL
+            )
+            "#,
+        )
+        .bind(user_id)
+        .bind(role_id)
+        .bind(tenant.to_string())
+        .bind(Utc::now())
+        .bind(grant.expires_at)
+        .bind(grant.granted_by.as_deref())
+        .bind(user_id)
+        .bind(tenant.to_string())
+        .bind(role_id)
+        .bind(tenant.to_string())
+        .execute(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        if result.rows_affected() == 0 {
+            return Err(UserRepositoryError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn unassign_role(
+        &self,
+        tenant: TenantId,
+        user_id: &str,
+        role_id: &str,
+    ) -> Result<(), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        query(
+            r#"
+            DELETE FROM user_roles
+            WHERE user_id = ? AND role_id = ? AND tenant_id = ?
+            "#,
+        )
+        .bind(user_id)
+        .bind(role_id)
+        .bind(tenant.to_string())
+        .execute(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        Ok(())
+    }
+
+    async fn get_users_with_expired_role_grants(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<(String, String)>, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let users: Vec<(String, String)> = query_as(
+            r#"
+            SELECT DISTINCT tenant_id, user_id FROM user_roles
+            WHERE expires_at IS NOT NULL AND expires_at <= ?
+            "#,
+        )
+        .bind(now)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        Ok(users)
+    }
+
+    async fn delete_expired_role_grants(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<(String, String)>, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let users: Vec<(String, String)> = query_as(
+            r#"
+            DELETE FROM user_roles
+            WHERE expires_at IS NOT NULL AND expires_at <= ?
+            RETURNING tenant_id, user_id
+            "#,
+        )
+        .bind(now)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        Ok(users)
+    }
+}
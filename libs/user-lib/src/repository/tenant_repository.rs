@@ -0,0 +1,99 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::{query, query_as, MySql, MySqlPool};
+use uuid::Uuid;
+
+use crate::entities::TenantId;
+use crate::repository::errors::{map_sqlx_error, UserRepositoryError};
+use crate::repository::executor::{DbExecutor, SharedTransaction};
+use crate::repository::models::TenantRow;
+use crate::repository::traits::TenantRepositoryTrait;
+
+/// Roles every tenant starts with, matching the ones seeded for the default tenant
+pub(crate) const SYSTEM_ROLES: [(&str, &str); 2] = [
+    ("admin", "Full administrative access"),
+    ("user", "Default role for regular users"),
+];
+
+#[derive(Debug, Clone)]
+pub struct TenantRepository {
+    db: DbExecutor<MySql>,
+}
+
+impl TenantRepository {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self {
+            db: DbExecutor::Pool(pool),
+        }
+    }
+
+    /// Repository whose queries run inside a shared transaction
+    pub fn in_transaction(tx: SharedTransaction<MySql>) -> Self {
+        Self {
+            db: DbExecutor::Transaction(tx),
+        }
+    }
+}
+
+#[async_trait]
+impl TenantRepositoryTrait for TenantRepository {
+    async fn create_tenant(
+        &self,
+        slug: &str,
+        name: &str,
+    ) -> Result<TenantRow, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let id = Uuid::new_v4().to_string();
+        let now = Utc::now();
+        query("INSERT INTO tenants (id, slug, name, created_at) VALUES (?, ?, ?, ?)")
+            .bind(&id)
+            .bind(slug)
+            .bind(name)
+            .bind(now)
+            .execute(&mut *conn)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        for (role_name, description) in SYSTEM_ROLES {
+            query(
+                r#"
+                INSERT INTO roles
+                    (id, tenant_id, name, description, is_system, created_at, updated_at)
+                VALUES (?, ?, ?, ?, TRUE, ?, ?)
+                "#,
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(&id)
+            .bind(role_name)
+            .bind(description)
+            .bind(now)
+            .bind(now)
+            .execute(&mut *conn)
+            .await
+            .map_err(map_sqlx_error)?;
+        }
+
+        query_as::<_, TenantRow>("SELECT id, slug, name, created_at FROM tenants WHERE id = ?")
+            .bind(&id)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(map_sqlx_error)
+    }
+
+    async fn get_tenant(&self, tenant: TenantId) -> Result<Option<TenantRow>, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        query_as::<_, TenantRow>("SELECT id, slug, name, created_at FROM tenants WHERE id = ?")
+            .bind(tenant.to_string())
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(map_sqlx_error)
+    }
+
+    async fn get_tenants(&self) -> Result<Vec<TenantRow>, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        query_as::<_, TenantRow>("SELECT id, slug, name, created_at FROM tenants ORDER BY slug")
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(map_sqlx_error)
+    }
+}
@@ -1,49 +1,757 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::entities::PaginationParams;
+use crate::entities::{PaginationParams, RoleGrant, TenantId, UserFilter, UserSuspension};
 use crate::repository::errors::UserRepositoryError;
-use crate::repository::models::{RoleRow, UserRoleMapping, UserRow};
+use crate::repository::models::{
+    ApiKeyRow, GroupRoleMapping, GroupRow, IdempotencyRow, RoleRow, TenantRow, UserAttributesRow,
+    UserRoleMapping, UserRow,
+};
 
 #[async_trait]
 pub trait UserRepositoryTrait: Send + Sync {
-    async fn create_user(&self, keycloak_id: &str) -> Result<UserRow, UserRepositoryError>;
-    async fn get_user(&self, user_id: Uuid) -> Result<Option<UserRow>, UserRepositoryError>;
+    async fn create_user(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+    ) -> Result<UserRow, UserRepositoryError>;
+    async fn get_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<Option<UserRow>, UserRepositoryError>;
     async fn get_user_by_keycloak_id(
         &self,
+        tenant: TenantId,
         keycloak_id: &str,
     ) -> Result<Option<UserRow>, UserRepositoryError>;
-    async fn delete_user(&self, user_id: Uuid) -> Result<(), UserRepositoryError>;
+    /// Tenant of the live user with this Keycloak ID, whichever tenant that is.
+    /// Keycloak IDs are unique across tenants.
+    async fn get_user_tenant_by_keycloak_id(
+        &self,
+        keycloak_id: &str,
+    ) -> Result<Option<TenantId>, UserRepositoryError>;
+    /// Permanently delete the user and its role assignments, soft-deleted or not.
+    /// With `expected_version`, only a user still at that version is deleted:
+    /// `VersionConflict` if it moved on, `NotFound` if there is no such user.
+    async fn delete_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<(), UserRepositoryError>;
+    /// Mark a live user as deleted; returns false if there was no live user.
+    /// `VersionConflict` if `expected_version` is given and the user moved on.
+    async fn soft_delete_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<bool, UserRepositoryError>;
+    /// Bring back a soft-deleted user; returns false if there was no soft-deleted user
+    async fn restore_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<bool, UserRepositoryError>;
+    /// Record a change to a live user made outside this table (e.g. its Keycloak profile)
+    /// by bumping `updated_at` and `version`. With `expected_version`, fails with
+    /// `VersionConflict` unless the user is still at that version.
+    async fn touch_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<UserRow, UserRepositoryError>;
+    /// Suspend a live user with `suspension`, or reactivate it with `None`, bumping
+    /// `updated_at` and `version`. With `expected_version`, fails with `VersionConflict`
+    /// unless the user is still at that version.
+    async fn set_user_suspension(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        suspension: Option<UserSuspension>,
+        expected_version: Option<i64>,
+    ) -> Result<UserRow, UserRepositoryError>;
+    /// Record a Keycloak login of the live user with `keycloak_id` at `at`, unless a
+    /// later one is already recorded. Not a change to the user: its version stays put.
+    async fn record_user_login(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+        at: DateTime<Utc>,
+    ) -> Result<(), UserRepositoryError>;
+    /// Live users matching every attribute set in `filter`
     async fn get_users_paginated(
         &self,
+        tenant: TenantId,
         pagination: PaginationParams,
+        filter: &UserFilter,
     ) -> Result<(Vec<UserRow>, u64), UserRepositoryError>;
     async fn get_users_by_role_paginated(
         &self,
+        tenant: TenantId,
         role_id: Uuid,
         pagination: PaginationParams,
     ) -> Result<(Vec<UserRow>, u64), UserRepositoryError>;
+    /// Attribute rows of the given users; users without attributes have no row
+    async fn get_user_attributes(
+        &self,
+        tenant: TenantId,
+        user_ids: &[String],
+    ) -> Result<Vec<UserAttributesRow>, UserRepositoryError>;
+    /// Insert or replace the attributes of `attributes.user_id`
+    async fn upsert_user_attributes(
+        &self,
+        tenant: TenantId,
+        attributes: &UserAttributesRow,
+    ) -> Result<(), UserRepositoryError>;
 }
 
 #[async_trait]
 pub trait RoleRepositoryTrait: Send + Sync {
-    async fn create_role(&self, name: &str) -> Result<RoleRow, UserRepositoryError>;
-    async fn get_role(&self, role_id: Uuid) -> Result<Option<RoleRow>, UserRepositoryError>;
-    async fn update_role(&self, role_id: Uuid, name: &str) -> Result<RoleRow, UserRepositoryError>;
-    async fn delete_role(&self, role_id: Uuid) -> Result<(), UserRepositoryError>;
-    async fn get_roles_for_user(&self, user_id: Uuid) -> Result<Vec<RoleRow>, UserRepositoryError>;
+    async fn create_role(
+        &self,
+        tenant: TenantId,
+        name: &str,
+        description: Option<String>,
+    ) -> Result<RoleRow, UserRepositoryError>;
+    async fn get_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+    ) -> Result<Option<RoleRow>, UserRepositoryError>;
+    /// Live system role of `tenant` with this name, looked up through the role name index
+    async fn get_system_role(
+        &self,
+        tenant: TenantId,
+        name: &str,
+    ) -> Result<Option<RoleRow>, UserRepositoryError>;
+    /// Set the name and description of a live role. With `expected_version`, fails
+    /// with `VersionConflict` unless the role is still at that version.
+    async fn update_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+        name: &str,
+        description: Option<String>,
+        expected_version: Option<i64>,
+    ) -> Result<RoleRow, UserRepositoryError>;
+    /// Permanently delete the role and its assignments, soft-deleted or not.
+    /// With `expected_version`, only a role still at that version is deleted:
+    /// `VersionConflict` if it moved on, `NotFound` if there is no such role.
+    async fn delete_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<(), UserRepositoryError>;
+    /// Mark a live role as deleted; returns false if there was no live role.
+    /// `VersionConflict` if `expected_version` is given and the role moved on.
+    async fn soft_delete_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<bool, UserRepositoryError>;
+    /// Bring back a soft-deleted role; returns false if there was no soft-deleted role
+    async fn restore_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+    ) -> Result<bool, UserRepositoryError>;
+    /// Live roles the user holds through unexpired grants or through its groups
+    async fn get_roles_for_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<Vec<RoleRow>, UserRepositoryError>;
+    /// Live roles granted to the user itself through unexpired grants, without those of
+    /// its groups
+    async fn get_granted_roles_for_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<Vec<RoleRow>, UserRepositoryError>;
+    /// Like `get_roles_for_user`, for several users at once; each role is listed once per
+    /// user even when it is held both directly and through a group
     async fn get_roles_for_users(
         &self,
+        tenant: TenantId,
         user_ids: &[String],
     ) -> Result<Vec<UserRoleMapping>, UserRepositoryError>;
     async fn get_roles_paginated(
         &self,
+        tenant: TenantId,
         pagination: PaginationParams,
     ) -> Result<(Vec<RoleRow>, u64), UserRepositoryError>;
 }
 
 #[async_trait]
 pub trait UserRoleRepositoryTrait: Send + Sync {
-    async fn assign_role(&self, user_id: &str, role_id: &str) -> Result<(), UserRepositoryError>;
-    async fn unassign_role(&self, user_id: &str, role_id: &str) -> Result<(), UserRepositoryError>;
+    /// Assign a live role to a live user; `NotFound` if either is missing or soft-deleted
+    async fn assign_role(
+        &self,
+        tenant: TenantId,
+        user_id: &str,
+        role_id: &str,
+        grant: &RoleGrant,
+    ) -> Result<(), UserRepositoryError>;
+    async fn unassign_role(
+        &self,
+        tenant: TenantId,
+        user_id: &str,
+        role_id: &str,
+    ) -> Result<(), UserRepositoryError>;
+    /// The `(tenant_id, user_id)` of each user, in every tenant, holding an assignment
+    /// that expired at or before `now`
+    async fn get_users_with_expired_role_grants(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<(String, String)>, UserRepositoryError>;
+    /// Remove assignments that expired at or before `now` in every tenant, returning the
+    /// `(tenant_id, user_id)` of each affected user
+    async fn delete_expired_role_grants(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<(String, String)>, UserRepositoryError>;
+}
+
+#[async_trait]
+pub trait TenantRepositoryTrait: Send + Sync + std::fmt::Debug {
+    /// Create a tenant along with its own `admin` and `user` system roles (`is_system`).
+    /// Run it in a unit of work so a failed role insert leaves no tenant behind.
+    async fn create_tenant(&self, slug: &str, name: &str)
+        -> Result<TenantRow, UserRepositoryError>;
+    async fn get_tenant(&self, tenant: TenantId) -> Result<Option<TenantRow>, UserRepositoryError>;
+    async fn get_tenants(&self) -> Result<Vec<TenantRow>, UserRepositoryError>;
+}
+
+/// Groups of users. Members inherit the live roles attached to their groups; adding a
+/// member or role that is already there, or removing one that is not, is a no-op.
+#[async_trait]
+pub trait GroupRepositoryTrait: Send + Sync + std::fmt::Debug {
+    async fn create_group(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+        name: &str,
+    ) -> Result<GroupRow, UserRepositoryError>;
+    async fn get_group(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+    ) -> Result<Option<GroupRow>, UserRepositoryError>;
+    async fn get_groups_paginated(
+        &self,
+        tenant: TenantId,
+        pagination: PaginationParams,
+    ) -> Result<(Vec<GroupRow>, u64), UserRepositoryError>;
+    /// `NotFound` if the group does not exist
+    async fn rename_group(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        name: &str,
+    ) -> Result<GroupRow, UserRepositoryError>;
+    /// Delete the group with its memberships and role attachments; returns false if
+    /// there was no group
+    async fn delete_group(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+    ) -> Result<bool, UserRepositoryError>;
+    /// Add a live user to the group; `NotFound` if either is missing
+    async fn add_group_member(
+        &self,
+        tenant: TenantId,
+        group_id: &str,
+        user_id: &str,
+    ) -> Result<(), UserRepositoryError>;
+    async fn remove_group_member(
+        &self,
+        tenant: TenantId,
+        group_id: &str,
+        user_id: &str,
+    ) -> Result<(), UserRepositoryError>;
+    /// Live members of the group
+    async fn get_group_members_paginated(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        pagination: PaginationParams,
+    ) -> Result<(Vec<UserRow>, u64), UserRepositoryError>;
+    /// Attach a live role to the group; `NotFound` if either is missing
+    async fn assign_group_role(
+        &self,
+        tenant: TenantId,
+        group_id: &str,
+        role_id: &str,
+    ) -> Result<(), UserRepositoryError>;
+    async fn unassign_group_role(
+        &self,
+        tenant: TenantId,
+        group_id: &str,
+        role_id: &str,
+    ) -> Result<(), UserRepositoryError>;
+    /// Live roles attached to the given groups
+    async fn get_roles_for_groups(
+        &self,
+        tenant: TenantId,
+        group_ids: &[String],
+    ) -> Result<Vec<GroupRoleMapping>, UserRepositoryError>;
+}
+
+/// Storage for `Idempotency-Key` responses. Keys are only ever seen through their
+/// live (unexpired) record; expired records are cleared when a key is reserved.
+#[async_trait]
+pub trait IdempotencyRepositoryTrait: Send + Sync {
+    /// Claim `key` for a new request, held until `expires_at` unless completed. Returns
+    /// false if a live record already holds the key.
+    async fn reserve_idempotency_key(
+        &self,
+        key: &str,
+        fingerprint: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<bool, UserRepositoryError>;
+    async fn get_idempotency_key(
+        &self,
+        key: &str,
+    ) -> Result<Option<IdempotencyRow>, UserRepositoryError>;
+    /// Store the response of the request holding `key`, to be replayed until `expires_at`
+    async fn complete_idempotency_key(
+        &self,
+        key: &str,
+        status_code: i32,
+        response_body: &str,
+        response_headers: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), UserRepositoryError>;
+    /// Drop a reservation so the key can be used again (the request produced nothing to replay)
+    async fn release_idempotency_key(&self, key: &str) -> Result<(), UserRepositoryError>;
+}
+
+/// Progress of the Keycloak event poller: the time of the newest event consumed, in
+/// Keycloak's epoch milliseconds, per tenant and event feed (`source`)
+#[async_trait]
+pub trait EventCheckpointRepositoryTrait: Send + Sync + std::fmt::Debug {
+    async fn get_event_checkpoint(
+        &self,
+        tenant: TenantId,
+        source: &str,
+    ) -> Result<Option<i64>, UserRepositoryError>;
+    /// Insert or replace the checkpoint of `source`
+    async fn save_event_checkpoint(
+        &self,
+        tenant: TenantId,
+        source: &str,
+        event_time: i64,
+    ) -> Result<(), UserRepositoryError>;
+}
+
+/// Hashed API keys of service accounts. Keys are looked up by hash across tenants: the
+/// hash alone identifies the key, and the key names its tenant.
+#[async_trait]
+pub trait ApiKeyRepositoryTrait: Send + Sync + std::fmt::Debug {
+    async fn create_api_key(
+        &self,
+        tenant: TenantId,
+        name: &str,
+        key_prefix: &str,
+        key_hash: &str,
+        scopes: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<ApiKeyRow, UserRepositoryError>;
+    async fn get_api_key(
+        &self,
+        tenant: TenantId,
+        key_id: Uuid,
+    ) -> Result<Option<ApiKeyRow>, UserRepositoryError>;
+    /// Every key of the tenant, revoked and expired ones included, newest first
+    async fn get_api_keys(&self, tenant: TenantId) -> Result<Vec<ApiKeyRow>, UserRepositoryError>;
+    async fn get_api_key_by_hash(
+        &self,
+        key_hash: &str,
+    ) -> Result<Option<ApiKeyRow>, UserRepositoryError>;
+    /// Replace the key of an unrevoked entry, keeping its name, scopes and expiry;
+    /// `NotFound` if there is none
+    async fn rotate_api_key(
+        &self,
+        tenant: TenantId,
+        key_id: Uuid,
+        key_prefix: &str,
+        key_hash: &str,
+    ) -> Result<ApiKeyRow, UserRepositoryError>;
+    /// Returns false if there was no unrevoked key
+    async fn revoke_api_key(
+        &self,
+        tenant: TenantId,
+        key_id: Uuid,
+    ) -> Result<bool, UserRepositoryError>;
+    /// Record that the key authenticated a request at `used_at`
+    async fn record_api_key_use(
+        &self,
+        key_id: Uuid,
+        used_at: DateTime<Utc>,
+    ) -> Result<(), UserRepositoryError>;
+}
+
+// Forwarding impls so the backend can be chosen at runtime behind `Arc<dyn Trait>`
+
+#[async_trait]
+impl<T: UserRepositoryTrait + ?Sized> UserRepositoryTrait for Arc<T> {
+    async fn create_user(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+    ) -> Result<UserRow, UserRepositoryError> {
+        (**self).create_user(tenant, keycloak_id).await
+    }
+    async fn get_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<Option<UserRow>, UserRepositoryError> {
+        (**self).get_user(tenant, user_id).await
+    }
+    async fn get_user_by_keycloak_id(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+    ) -> Result<Option<UserRow>, UserRepositoryError> {
+        (**self).get_user_by_keycloak_id(tenant, keycloak_id).await
+    }
+    async fn get_user_tenant_by_keycloak_id(
+        &self,
+        keycloak_id: &str,
+    ) -> Result<Option<TenantId>, UserRepositoryError> {
+        (**self).get_user_tenant_by_keycloak_id(keycloak_id).await
+    }
+    async fn set_user_suspension(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        suspension: Option<UserSuspension>,
+        expected_version: Option<i64>,
+    ) -> Result<UserRow, UserRepositoryError> {
+        (**self)
+            .set_user_suspension(tenant, user_id, suspension, expected_version)
+            .await
+    }
+    async fn record_user_login(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+        at: DateTime<Utc>,
+    ) -> Result<(), UserRepositoryError> {
+        (**self).record_user_login(tenant, keycloak_id, at).await
+    }
+    async fn delete_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<(), UserRepositoryError> {
+        (**self)
+            .delete_user(tenant, user_id, expected_version)
+            .await
+    }
+    async fn soft_delete_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<bool, UserRepositoryError> {
+        (**self)
+            .soft_delete_user(tenant, user_id, expected_version)
+            .await
+    }
+    async fn restore_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<bool, UserRepositoryError> {
+        (**self).restore_user(tenant, user_id).await
+    }
+    async fn touch_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<UserRow, UserRepositoryError> {
+        (**self).touch_user(tenant, user_id, expected_version).await
+    }
+    async fn get_users_paginated(
+        &self,
+        tenant: TenantId,
+        pagination: PaginationParams,
+        filter: &UserFilter,
+    ) -> Result<(Vec<UserRow>, u64), UserRepositoryError> {
+        (**self)
+            .get_users_paginated(tenant, pagination, filter)
+            .await
+    }
+    async fn get_users_by_role_paginated(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+        pagination: PaginationParams,
+    ) -> Result<(Vec<UserRow>, u64), UserRepositoryError> {
+        (**self)
+            .get_users_by_role_paginated(tenant, role_id, pagination)
+            .await
+    }
+    async fn get_user_attributes(
+        &self,
+        tenant: TenantId,
+        user_ids: &[String],
+    ) -> Result<Vec<UserAttributesRow>, UserRepositoryError> {
+        (**self).get_user_attributes(tenant, user_ids).await
+    }
+    async fn upsert_user_attributes(
+        &self,
+        tenant: TenantId,
+        attributes: &UserAttributesRow,
+    ) -> Result<(), UserRepositoryError> {
+        (**self).upsert_user_attributes(tenant, attributes).await
+    }
+}
+
+#[async_trait]
+impl<T: RoleRepositoryTrait + ?Sized> RoleRepositoryTrait for Arc<T> {
+    async fn create_role(
+        &self,
+        tenant: TenantId,
+        name: &str,
+        description: Option<String>,
+    ) -> Result<RoleRow, UserRepositoryError> {
+        (**self).create_role(tenant, name, description).await
+    }
+    async fn get_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+    ) -> Result<Option<RoleRow>, UserRepositoryError> {
+        (**self).get_role(tenant, role_id).await
+    }
+    async fn get_system_role(
+        &self,
+        tenant: TenantId,
+        name: &str,
+    ) -> Result<Option<RoleRow>, UserRepositoryError> {
+        (**self).get_system_role(tenant, name).await
+    }
+    async fn update_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+        name: &str,
+        description: Option<String>,
+        expected_version: Option<i64>,
+    ) -> Result<RoleRow, UserRepositoryError> {
+        (**self)
+            .update_role(tenant, role_id, name, description, expected_version)
+            .await
+    }
+    async fn delete_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<(), UserRepositoryError> {
+        (**self)
+            .delete_role(tenant, role_id, expected_version)
+            .await
+    }
+    async fn soft_delete_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<bool, UserRepositoryError> {
+        (**self)
+            .soft_delete_role(tenant, role_id, expected_version)
+            .await
+    }
+    async fn restore_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+    ) -> Result<bool, UserRepositoryError> {
+        (**self).restore_role(tenant, role_id).await
+    }
+    async fn get_roles_for_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<Vec<RoleRow>, UserRepositoryError> {
+        (**self).get_roles_for_user(tenant, user_id).await
+    }
+    async fn get_granted_roles_for_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<Vec<RoleRow>, UserRepositoryError> {
+        (**self).get_granted_roles_for_user(tenant, user_id).await
+    }
+    async fn get_roles_for_users(
+        &self,
+        tenant: TenantId,
+        user_ids: &[String],
+    ) -> Result<Vec<UserRoleMapping>, UserRepositoryError> {
+        (**self).get_roles_for_users(tenant, user_ids).await
+    }
+    async fn get_roles_paginated(
+        &self,
+        tenant: TenantId,
+        pagination: PaginationParams,
+    ) -> Result<(Vec<RoleRow>, u64), UserRepositoryError> {
+        (**self).get_roles_paginated(tenant, pagination).await
+    }
+}
+
+#[async_trait]
+impl<T: UserRoleRepositoryTrait + ?Sized> UserRoleRepositoryTrait for Arc<T> {
+    async fn assign_role(
+        &self,
+        tenant: TenantId,
+        user_id: &str,
+        role_id: &str,
+        grant: &RoleGrant,
+    ) -> Result<(), UserRepositoryError> {
+        (**self).assign_role(tenant, user_id, role_id, grant).await
+    }
+    async fn unassign_role(
+        &self,
+        tenant: TenantId,
+        user_id: &str,
+        role_id: &str,
+    ) -> Result<(), UserRepositoryError> {
+        (**self).unassign_role(tenant, user_id, role_id).await
+    }
+    async fn get_users_with_expired_role_grants(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<(String, String)>, UserRepositoryError> {
+        (**self).get_users_with_expired_role_grants(now).await
+    }
+    async fn delete_expired_role_grants(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<(String, String)>, UserRepositoryError> {
+        (**self).delete_expired_role_grants(now).await
+    }
+}
+
+#[async_trait]
+impl<T: TenantRepositoryTrait + ?Sized> TenantRepositoryTrait for Arc<T> {
+    async fn create_tenant(
+        &self,
+        slug: &str,
+        name: &str,
+    ) -> Result<TenantRow, UserRepositoryError> {
+        (**self).create_tenant(slug, name).await
+    }
+    async fn get_tenant(&self, tenant: TenantId) -> Result<Option<TenantRow>, UserRepositoryError> {
+        (**self).get_tenant(tenant).await
+    }
+    async fn get_tenants(&self) -> Result<Vec<TenantRow>, UserRepositoryError> {
+        (**self).get_tenants().await
+    }
+}
+
+#[async_trait]
+impl<T: GroupRepositoryTrait + ?Sized> GroupRepositoryTrait for Arc<T> {
+    async fn create_group(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+        name: &str,
+    ) -> Result<GroupRow, UserRepositoryError> {
+        (**self).create_group(tenant, keycloak_id, name).await
+    }
+    async fn get_group(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+    ) -> Result<Option<GroupRow>, UserRepositoryError> {
+        (**self).get_group(tenant, group_id).await
+    }
+    async fn get_groups_paginated(
+        &self,
+        tenant: TenantId,
+        pagination: PaginationParams,
+    ) -> Result<(Vec<GroupRow>, u64), UserRepositoryError> {
+        (**self).get_groups_paginated(tenant, pagination).await
+    }
+    async fn rename_group(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        name: &str,
+    ) -> Result<GroupRow, UserRepositoryError> {
+        (**self).rename_group(tenant, group_id, name).await
+    }
+    async fn delete_group(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+    ) -> Result<bool, UserRepositoryError> {
+        (**self).delete_group(tenant, group_id).await
+    }
+    async fn add_group_member(
+        &self,
+        tenant: TenantId,
+        group_id: &str,
+        user_id: &str,
+    ) -> Result<(), UserRepositoryError> {
+        (**self).add_group_member(tenant, group_id, user_id).await
+    }
+    async fn remove_group_member(
+        &self,
+        tenant: TenantId,
+        group_id: &str,
+        user_id: &str,
+    ) -> Result<(), UserRepositoryError> {
+        (**self)
+            .remove_group_member(tenant, group_id, user_id)
+            .await
+    }
+    async fn get_group_members_paginated(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        pagination: PaginationParams,
+    ) -> Result<(Vec<UserRow>, u64), UserRepositoryError> {
+        (**self)
+            .get_group_members_paginated(tenant, group_id, pagination)
+            .await
+    }
+    async fn assign_group_role(
+        &self,
+        tenant: TenantId,
+        group_id: &str,
+        role_id: &str,
+    ) -> Result<(), UserRepositoryError> {
+        (**self).assign_group_role(tenant, group_id, role_id).await
+    }
+    async fn unassign_group_role(
+        &self,
+        tenant: TenantId,
+        group_id: &str,
+        role_id: &str,
+    ) -> Result<(), UserRepositoryError> {
+        (**self)
+            .unassign_group_role(tenant, group_id, role_id)
+            .await
+    }
+    async fn get_roles_for_groups(
+        &self,
+        tenant: TenantId,
+        group_ids: &[String],
+    ) -> Result<Vec<GroupRoleMapping>, UserRepositoryError> {
+        (**self).get_roles_for_groups(tenant, group_ids).await
+    }
 }
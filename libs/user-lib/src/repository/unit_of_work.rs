@@ -0,0 +1,103 @@
+//! Unit of work: one database transaction shared by the user, role, user-role and
+//! tenant repositories, committed or rolled back as a whole.
+
+use async_trait::async_trait;
+use sqlx::{Database, MySql, MySqlPool};
+
+use crate::repository::errors::{map_sqlx_error, UserRepositoryError};
+use crate::repository::executor::{begin_shared, take_shared, SharedTransaction};
+use crate::repository::traits::{
+    RoleRepositoryTrait, TenantRepositoryTrait, UserRepositoryTrait, UserRoleRepositoryTrait,
+};
+use crate::repository::{RoleRepository, TenantRepository, UserRepository, UserRoleRepository};
+
+/// Repositories bound to one open transaction. Dropping it without calling
+/// [`commit`](UnitOfWork::commit) rolls the transaction back.
+#[async_trait]
+pub trait UnitOfWork: Send + Sync {
+    fn users(&self) -> &dyn UserRepositoryTrait;
+    fn roles(&self) -> &dyn RoleRepositoryTrait;
+    fn user_roles(&self) -> &dyn UserRoleRepositoryTrait;
+    fn tenants(&self) -> &dyn TenantRepositoryTrait;
+
+    async fn commit(self: Box<Self>) -> Result<(), UserRepositoryError>;
+    async fn rollback(self: Box<Self>) -> Result<(), UserRepositoryError>;
+}
+
+/// Starts units of work against a backend
+#[async_trait]
+pub trait UnitOfWorkFactory: Send + Sync + std::fmt::Debug {
+    async fn begin(&self) -> Result<Box<dyn UnitOfWork>, UserRepositoryError>;
+}
+
+/// [`UnitOfWork`] over a sqlx transaction, for any backend whose repositories
+/// can be bound to a [`SharedTransaction`]
+pub struct SqlUnitOfWork<DB: Database, U, R, UR, T> {
+    tx: SharedTransaction<DB>,
+    users: U,
+    roles: R,
+    user_roles: UR,
+    tenants: T,
+}
+
+impl<DB: Database, U, R, UR, T> SqlUnitOfWork<DB, U, R, UR, T> {
+    pub fn new(tx: SharedTransaction<DB>, users: U, roles: R, user_roles: UR, tenants: T) -> Self {
+        Self {
+            tx,
+            users,
+            roles,
+            user_roles,
+            tenants,
+        }
+    }
+}
+
+#[async_trait]
+impl<DB, U, R, UR, T> UnitOfWork for SqlUnitOfWork<DB, U, R, UR, T>
+where
+    DB: Database,
+    U: UserRepositoryTrait + 'static,
+    R: RoleRepositoryTrait + 'static,
+    UR: UserRoleRepositoryTrait + 'static,
+    T: TenantRepositoryTrait + 'static,
+{
+    fn users(&self) -> &dyn UserRepositoryTrait {
+        &self.users
+    }
+
+    fn roles(&self) -> &dyn RoleRepositoryTrait {
+        &self.roles
+    }
+
+    fn user_roles(&self) -> &dyn UserRoleRepositoryTrait {
+        &self.user_roles
+    }
+
+    fn tenants(&self) -> &dyn TenantRepositoryTrait {
+        &self.tenants
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), UserRepositoryError> {
+        let tx = take_shared(&self.tx).await.map_err(map_sqlx_error)?;
+        tx.commit().await.map_err(map_sqlx_error)
+    }
+
+    async fn rollback(self: Box<Self>) -> Result<(), UserRepositoryError> {
+        let tx = take_shared(&self.tx).await.map_err(map_sqlx_error)?;
+        tx.rollback().await.map_err(map_sqlx_error)
+    }
+}
+
+#[async_trait]
+impl UnitOfWorkFactory for MySqlPool {
+    async fn begin(&self) -> Result<Box<dyn UnitOfWork>, UserRepositoryError> {
+        let tx = begin_shared::<MySql>(self).await.map_err(map_sqlx_error)?;
+        Ok(Box::new(SqlUnitOfWork::new(
+            tx.clone(),
+            UserRepository::in_transaction(tx.clone()),
+            RoleRepository::in_transaction(tx.clone()),
+            UserRoleRepository::in_transaction(tx.clone()),
+            TenantRepository::in_transaction(tx),
+        )))
+    }
+}
@@ -1,60 +1,89 @@
-use crate::entities::PaginationParams;
+use crate::entities::{PaginationParams, TenantId, UserFilter, UserSuspension};
 use crate::repository::errors::UserRepositoryError;
-use crate::repository::models::UserRow;
+use crate::repository::executor::{DbExecutor, SharedTransaction};
+use crate::repository::models::{UserAttributesRow, UserRow};
 use crate::repository::traits::UserRepositoryTrait;
 use async_trait::async_trait;
-use sqlx::{query, query_as, query_scalar, MySqlPool};
+use chrono::{DateTime, Utc};
+use sqlx::{query, query_as, query_scalar, MySql, MySqlPool};
 use uuid::Uuid;
 
 #[derive(Debug, Clone)]
 pub struct UserRepository {
-    pub pool: MySqlPool,
+    db: DbExecutor<MySql>,
 }
 
 impl UserRepository {
     pub fn new(pool: MySqlPool) -> Self {
-        Self { pool }
+        Self {
+            db: DbExecutor::Pool(pool),
+        }
+    }
+
+    /// Repository whose queries run inside a shared transaction
+    pub fn in_transaction(tx: SharedTransaction<MySql>) -> Self {
+        Self {
+            db: DbExecutor::Transaction(tx),
+        }
     }
 }
 
 #[async_trait]
 impl UserRepositoryTrait for UserRepository {
-    async fn create_user(&self, keycloak_id: &str) -> Result<UserRow, UserRepositoryError> {
+    async fn create_user(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+    ) -> Result<UserRow, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
         let user_id = Uuid::new_v4();
+        let now = Utc::now();
 
         query(
             r#"
-            INSERT INTO users (id, keycloak_id)
-            VALUES (?, ?)
+            INSERT INTO users (id, tenant_id, keycloak_id, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?)
             "#,
         )
         .bind(user_id.to_string())
+        .bind(tenant.to_string())
         .bind(keycloak_id)
-        .execute(&self.pool)
+        .bind(now)
+        .bind(now)
+        .execute(&mut *conn)
         .await
         .map_err(UserRepositoryError::from)?;
 
         let user = query_as::<_, UserRow>(
             r#"
-            SELECT id, keycloak_id FROM users WHERE id = ?
+            SELECT id, keycloak_id, created_at, updated_at, version, deleted_at,
+                   suspended_at, suspension_reason, last_login_at FROM users WHERE id = ?
             "#,
         )
         .bind(user_id.to_string())
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *conn)
         .await
         .map_err(UserRepositoryError::from)?;
 
         Ok(user)
     }
 
-    async fn get_user(&self, user_id: Uuid) -> Result<Option<UserRow>, UserRepositoryError> {
+    async fn get_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<Option<UserRow>, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
         let user = query_as::<_, UserRow>(
             r#"
-            SELECT id, keycloak_id FROM users WHERE id = ?
+            SELECT id, keycloak_id, created_at, updated_at, version, deleted_at,
+                   suspended_at, suspension_reason, last_login_at FROM users
+            WHERE id = ? AND tenant_id = ? AND deleted_at IS NULL
             "#,
         )
         .bind(user_id.to_string())
-        .fetch_optional(&self.pool)
+        .bind(tenant.to_string())
+        .fetch_optional(&mut *conn)
         .await
         .map_err(UserRepositoryError::from)?;
 
@@ -63,54 +92,296 @@ impl UserRepositoryTrait for UserRepository {
 
     async fn get_user_by_keycloak_id(
         &self,
+        tenant: TenantId,
         keycloak_id: &str,
     ) -> Result<Option<UserRow>, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
         let user = query_as::<_, UserRow>(
             r#"
-            SELECT id, keycloak_id FROM users WHERE keycloak_id = ?
+            SELECT id, keycloak_id, created_at, updated_at, version, deleted_at,
+                   suspended_at, suspension_reason, last_login_at FROM users
+            WHERE keycloak_id = ? AND tenant_id = ? AND deleted_at IS NULL
             "#,
         )
         .bind(keycloak_id)
-        .fetch_optional(&self.pool)
+        .bind(tenant.to_string())
+        .fetch_optional(&mut *conn)
         .await
         .map_err(UserRepositoryError::from)?;
 
         Ok(user)
     }
 
-    async fn delete_user(&self, user_id: Uuid) -> Result<(), UserRepositoryError> {
-        query(
+    async fn get_user_tenant_by_keycloak_id(
+        &self,
+        keycloak_id: &str,
+    ) -> Result<Option<TenantId>, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
+        let tenant: Option<String> = query_scalar(
             r#"
-            DELETE FROM users WHERE id = ?
+            SELECT tenant_id FROM users WHERE keycloak_id = ? AND deleted_at IS NULL
+            "#,
+        )
+        .bind(keycloak_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(UserRepositoryError::from)?;
+
+        tenant
+            .map(|t| t.parse())
+            .transpose()
+            .map_err(|e: uuid::Error| UserRepositoryError::Sqlx(sqlx::Error::Decode(e.into())))
+    }
+
+    async fn delete_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<(), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
+        let result = query(
+            r#"
+            DELETE FROM users WHERE id = ? AND tenant_id = ? AND (? IS NULL OR version = ?)
             "#,
         )
         .bind(user_id.to_string())
-        .execute(&self.pool)
+        .bind(tenant.to_string())
+        .bind(expected_version)
+        .bind(expected_version)
+        .execute(&mut *conn)
         .await
         .map_err(UserRepositoryError::from)?;
 
+        if result.rows_affected() == 0 && expected_version.is_some() {
+            let exists: i64 =
+                query_scalar("SELECT COUNT(*) FROM users WHERE id = ? AND tenant_id = ?")
+                    .bind(user_id.to_string())
+                    .bind(tenant.to_string())
+                    .fetch_one(&mut *conn)
+                    .await
+                    .map_err(UserRepositoryError::from)?;
+            return Err(if exists > 0 {
+                UserRepositoryError::VersionConflict
+            } else {
+                UserRepositoryError::NotFound
+            });
+        }
+        Ok(())
+    }
+
+    async fn soft_delete_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<bool, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
+        let now = Utc::now();
+        let result = query(
+            r#"
+            UPDATE users SET deleted_at = ?, updated_at = ?, version = version + 1
+            WHERE id = ? AND tenant_id = ? AND deleted_at IS NULL AND (? IS NULL OR version = ?)
+            "#,
+        )
+        .bind(now)
+        .bind(now)
+        .bind(user_id.to_string())
+        .bind(tenant.to_string())
+        .bind(expected_version)
+        .bind(expected_version)
+        .execute(&mut *conn)
+        .await
+        .map_err(UserRepositoryError::from)?;
+        drop(conn);
+
+        if result.rows_affected() > 0 {
+            return Ok(true);
+        }
+        // Nothing deleted although the user is live: its version moved on
+        if expected_version.is_some() && self.get_user(tenant, user_id).await?.is_some() {
+            return Err(UserRepositoryError::VersionConflict);
+        }
+        Ok(false)
+    }
+
+    async fn restore_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<bool, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
+        let result = query(
+            r#"
+            UPDATE users SET deleted_at = NULL, updated_at = ?, version = version + 1
+            WHERE id = ? AND tenant_id = ? AND deleted_at IS NOT NULL
+            "#,
+        )
+        .bind(Utc::now())
+        .bind(user_id.to_string())
+        .bind(tenant.to_string())
+        .execute(&mut *conn)
+        .await
+        .map_err(UserRepositoryError::from)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn touch_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<UserRow, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
+        let result = query(
+            r#"
+            UPDATE users SET updated_at = ?, version = version + 1
+            WHERE id = ? AND tenant_id = ? AND deleted_at IS NULL AND (? IS NULL OR version = ?)
+            "#,
+        )
+        .bind(Utc::now())
+        .bind(user_id.to_string())
+        .bind(tenant.to_string())
+        .bind(expected_version)
+        .bind(expected_version)
+        .execute(&mut *conn)
+        .await
+        .map_err(UserRepositoryError::from)?;
+        drop(conn);
+
+        let user = self
+            .get_user(tenant, user_id)
+            .await?
+            .ok_or(UserRepositoryError::NotFound)?;
+        // Nothing updated although the user is live: its version moved on
+        if result.rows_affected() == 0 {
+            return Err(UserRepositoryError::VersionConflict);
+        }
+        Ok(user)
+    }
+
+    async fn set_user_suspension(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        suspension: Option<UserSuspension>,
+        expected_version: Option<i64>,
+    ) -> Result<UserRow, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
+        let result = query(
+            r#"
+            UPDATE users
+            SET suspended_at = ?, suspension_reason = ?, updated_at = ?, version = version + 1
+            WHERE id = ? AND tenant_id = ? AND deleted_at IS NULL AND (? IS NULL OR version = ?)
+            "#,
+        )
+        .bind(suspension.as_ref().map(|s| s.suspended_at))
+        .bind(suspension.and_then(|s| s.reason))
+        .bind(Utc::now())
+        .bind(user_id.to_string())
+        .bind(tenant.to_string())
+        .bind(expected_version)
+        .bind(expected_version)
+        .execute(&mut *conn)
+        .await
+        .map_err(UserRepositoryError::from)?;
+        drop(conn);
+
+        let user = self
+            .get_user(tenant, user_id)
+            .await?
+            .ok_or(UserRepositoryError::NotFound)?;
+        // Nothing updated although the user is live: its version moved on
+        if result.rows_affected() == 0 {
+            return Err(UserRepositoryError::VersionConflict);
+        }
+        Ok(user)
+    }
+
+    async fn record_user_login(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+        at: DateTime<Utc>,
+    ) -> Result<(), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
+        query(
+            r#"
+            UPDATE users SET last_login_at = ?
+            WHERE keycloak_id = ? AND tenant_id = ? AND deleted_at IS NULL
+              AND (last_login_at IS NULL OR last_login_at < ?)
+            "#,
+        )
+        .bind(at)
+        .bind(keycloak_id)
+        .bind(tenant.to_string())
+        .bind(at)
+        .execute(&mut *conn)
+        .await
+        .map_err(UserRepositoryError::from)?;
         Ok(())
     }
 
     async fn get_users_paginated(
         &self,
+        tenant: TenantId,
         pagination: PaginationParams,
+        filter: &UserFilter,
     ) -> Result<(Vec<UserRow>, u64), UserRepositoryError> {
-        let total: i64 = query_scalar("SELECT COUNT(*) FROM users")
-            .fetch_one(&self.pool)
-            .await
-            .map_err(UserRepositoryError::from)?;
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
+        let total: i64 = query_scalar(
+            r#"
+            SELECT COUNT(*)
+            FROM users u
+            LEFT JOIN user_attributes a ON a.user_id = u.id
+            WHERE u.tenant_id = ? AND u.deleted_at IS NULL
+              AND (? IS NULL OR a.department = ?)
+              AND (? IS NULL OR a.cost_center = ?)
+              AND (? IS NULL OR a.locale = ?)
+              AND (? IS NULL OR (u.suspended_at IS NOT NULL) = ?)
+            "#,
+        )
+        .bind(tenant.to_string())
+        .bind(&filter.department)
+        .bind(&filter.department)
+        .bind(&filter.cost_center)
+        .bind(&filter.cost_center)
+        .bind(&filter.locale)
+        .bind(&filter.locale)
+        .bind(filter.suspended())
+        .bind(filter.suspended())
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(UserRepositoryError::from)?;
 
         let users = query_as::<_, UserRow>(
             r#"
-            SELECT id, keycloak_id FROM users
-            ORDER BY id
+            SELECT u.id, u.keycloak_id, u.created_at, u.updated_at, u.version, u.deleted_at,
+                   u.suspended_at, u.suspension_reason, u.last_login_at
+            FROM users u
+            LEFT JOIN user_attributes a ON a.user_id = u.id
+            WHERE u.tenant_id = ? AND u.deleted_at IS NULL
+              AND (? IS NULL OR a.department = ?)
+              AND (? IS NULL OR a.cost_center = ?)
+              AND (? IS NULL OR a.locale = ?)
+              AND (? IS NULL OR (u.suspended_at IS NOT NULL) = ?)
+            ORDER BY u.id
             LIMIT ? OFFSET ?
             "#,
         )
+        .bind(tenant.to_string())
+        .bind(&filter.department)
+        .bind(&filter.department)
+        .bind(&filter.cost_center)
+        .bind(&filter.cost_center)
+        .bind(&filter.locale)
+        .bind(&filter.locale)
+        .bind(filter.suspended())
+        .bind(filter.suspended())
         .bind(pagination.limit())
         .bind(pagination.offset())
-        .fetch_all(&self.pool)
+        .fetch_all(&mut *conn)
         .await
         .map_err(UserRepositoryError::from)?;
 
@@ -119,39 +390,123 @@ impl UserRepositoryTrait for UserRepository {
 
     async fn get_users_by_role_paginated(
         &self,
+        tenant: TenantId,
         role_id: Uuid,
         pagination: PaginationParams,
     ) -> Result<(Vec<UserRow>, u64), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
+        let now = Utc::now();
         let total: i64 = query_scalar(
             r#"
             SELECT COUNT(*)
             FROM users u
             JOIN user_roles ur ON u.id = ur.user_id
-            WHERE ur.role_id = ?
+            WHERE ur.role_id = ? AND u.tenant_id = ? AND u.deleted_at IS NULL
+              AND (ur.expires_at IS NULL OR ur.expires_at > ?)
             "#,
         )
         .bind(role_id.to_string())
-        .fetch_one(&self.pool)
+        .bind(tenant.to_string())
+        .bind(now)
+        .fetch_one(&mut *conn)
         .await
         .map_err(UserRepositoryError::from)?;
 
         let users = query_as::<_, UserRow>(
             r#"
-            SELECT u.id, u.keycloak_id
+            SELECT u.id, u.keycloak_id, u.created_at, u.updated_at, u.version, u.deleted_at,
+                   u.suspended_at, u.suspension_reason, u.last_login_at
             FROM users u
             JOIN user_roles ur ON u.id = ur.user_id
-            WHERE ur.role_id = ?
+            WHERE ur.role_id = ? AND u.tenant_id = ? AND u.deleted_at IS NULL
+              AND (ur.expires_at IS NULL OR ur.expires_at > ?)
             ORDER BY u.id
             LIMIT ? OFFSET ?
             "#,
         )
         .bind(role_id.to_string())
+        .bind(tenant.to_string())
+        .bind(now)
         .bind(pagination.limit())
         .bind(pagination.offset())
-        .fetch_all(&self.pool)
+        .fetch_all(&mut *conn)
         .await
         .map_err(UserRepositoryError::from)?;
 
         Ok((users, total as u64))
     }
+
+    async fn get_user_attributes(
+        &self,
+        tenant: TenantId,
+        user_ids: &[String],
+    ) -> Result<Vec<UserAttributesRow>, UserRepositoryError> {
+        if user_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
+
+        // Only placeholders are interpolated; the ids themselves are bound
+        let placeholders = user_ids.iter().map(|_| "?").collect::<Vec<_>>().join(", ");
+        let query_str = format!(
+            r#"
+            SELECT a.user_id, a.department, a.cost_center, a.locale, a.metadata, a.updated_at
+            FROM user_attributes a
+            JOIN users u ON u.id = a.user_id
+            WHERE u.tenant_id = ? AND a.user_id IN ({placeholders})
+            "#
+        );
+
+        let mut query = query_as::<_, UserAttributesRow>(&query_str).bind(tenant.to_string());
+        for user_id in user_ids {
+            query = query.bind(user_id);
+        }
+        let rows = query
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(UserRepositoryError::from)?;
+        Ok(rows)
+    }
+
+    async fn upsert_user_attributes(
+        &self,
+        tenant: TenantId,
+        attributes: &UserAttributesRow,
+    ) -> Result<(), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(UserRepositoryError::from)?;
+        let owned: Option<i32> = query_scalar("SELECT 1 FROM users WHERE id = ? AND tenant_id = ?")
+            .bind(&attributes.user_id)
+            .bind(tenant.to_string())
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(UserRepositoryError::from)?;
+        if owned.is_none() {
+            return Err(UserRepositoryError::NotFound);
+        }
+
+        query(
+            r#"
+            INSERT INTO user_attributes
+                (user_id, department, cost_center, locale, metadata, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON DUPLICATE KEY UPDATE
+                department = VALUES(department),
+                cost_center = VALUES(cost_center),
+                locale = VALUES(locale),
+                metadata = VALUES(metadata),
+                updated_at = VALUES(updated_at)
+            "#,
+        )
+        .bind(&attributes.user_id)
+        .bind(&attributes.department)
+        .bind(&attributes.cost_center)
+        .bind(&attributes.locale)
+        .bind(&attributes.metadata)
+        .bind(attributes.updated_at)
+        .execute(&mut *conn)
+        .await
+        .map_err(UserRepositoryError::from)?;
+
+        Ok(())
+    }
 }
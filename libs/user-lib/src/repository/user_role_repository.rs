@@ -1,51 +1,151 @@
 use async_trait::async_trait;
-use sqlx::{query, MySqlPool};
+use chrono::{DateTime, Utc};
+use sqlx::{query, query_as, MySql, MySqlPool};
 
+use crate::entities::{RoleGrant, TenantId};
 use crate::repository::errors::{map_sqlx_error, UserRepositoryError};
+use crate::repository::executor::{DbExecutor, SharedTransaction};
 use crate::repository::traits::UserRoleRepositoryTrait;
 
 #[derive(Debug, Clone)]
 pub struct UserRoleRepository {
-    pub pool: MySqlPool,
+    db: DbExecutor<MySql>,
 }
 
 impl UserRoleRepository {
     pub fn new(pool: MySqlPool) -> Self {
-        Self { pool }
+        Self {
+            db: DbExecutor::Pool(pool),
+        }
+    }
+
+    /// Repository whose queries run inside a shared transaction
+    pub fn in_transaction(tx: SharedTransaction<MySql>) -> Self {
+        Self {
+            db: DbExecutor::Transaction(tx),
+        }
     }
 }
 
 #[async_trait]
 impl UserRoleRepositoryTrait for UserRoleRepository {
-    async fn assign_role(&self, user_id: &str, role_id: &str) -> Result<(), UserRepositoryError> {
-        query(
+    async fn assign_role(
+        &self,
+        tenant: TenantId,
+        user_id: &str,
+        role_id: &str,
+        grant: &RoleGrant,
+    ) -> Result<(), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        // Only insert when both sides are live, so soft-deleted rows can't gain assignments
+        let result = query(
             r#"
-            INSERT INTO user_roles (user_id, role_id)
-            VALUES (?, ?)
+            INSERT INTO user_roles
+                (user_id, role_id, tenant_id, granted_at, expires_at, granted_by)
+            SELECT ?, ?, ?, ?, ?, ? FROM DUAL
+            WHERE EXISTS (
+                SELECT 1 FROM users WHERE id = ? AND tenant_id = ? AND deleted_at IS NULL
+            )
+              AND EXISTS (
+                SELECT 1 FROM roles WHERE id = ? AND tenant_id = ? AND deleted_at IS NULL
+            )
             "#,
         )
         .bind(user_id)
         .bind(role_id)
-        .execute(&self.pool)
+        .bind(tenant.to_string())
+        .bind(Utc::now())
+        .bind(grant.expires_at)
+        .bind(grant.granted_by.as_deref())
+        .bind(user_id)
+        .bind(tenant.to_string())
+        .bind(role_id)
+        .bind(tenant.to_string())
+        .execute(&mut *conn)
         .await
         .map_err(map_sqlx_error)?;
 
+        if result.rows_affected() == 0 {
+            return Err(UserRepositoryError::NotFound);
+        }
         Ok(())
     }
 
-    async fn unassign_role(&self, user_id: &str, role_id: &str) -> Result<(), UserRepositoryError> {
+    async fn unassign_role(
+        &self,
+        tenant: TenantId,
+        user_id: &str,
+        role_id: &str,
+    ) -> Result<(), UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
         query(
             r#"
             DELETE FROM user_roles
-            WHERE user_id = ? AND role_id = ?
+            WHERE user_id = ? AND role_id = ? AND tenant_id = ?
             "#,
         )
         .bind(user_id)
         .bind(role_id)
-        .execute(&self.pool)
+        .bind(tenant.to_string())
+        .execute(&mut *conn)
         .await
         .map_err(map_sqlx_error)?;
 
         Ok(())
     }
+
+    async fn get_users_with_expired_role_grants(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<(String, String)>, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        let users: Vec<(String, String)> = query_as(
+            r#"
+            SELECT DISTINCT tenant_id, user_id FROM user_roles
+            WHERE expires_at IS NOT NULL AND expires_at <= ?
+            "#,
+        )
+        .bind(now)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        Ok(users)
+    }
+
+    async fn delete_expired_role_grants(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<(String, String)>, UserRepositoryError> {
+        let mut conn = self.db.acquire().await.map_err(map_sqlx_error)?;
+        // No RETURNING on MySQL: read the affected users first. Both statements use the
+        // same cutoff, and new grants can't already be expired, so they see the same rows.
+        let users: Vec<(String, String)> = query_as(
+            r#"
+            SELECT DISTINCT tenant_id, user_id FROM user_roles
+            WHERE expires_at IS NOT NULL AND expires_at <= ?
+            "#,
+        )
+        .bind(now)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        if users.is_empty() {
+            return Ok(users);
+        }
+
+        query(
+            r#"
+            DELETE FROM user_roles
+            WHERE expires_at IS NOT NULL AND expires_at <= ?
+            "#,
+        )
+        .bind(now)
+        .execute(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        Ok(users)
+    }
 }
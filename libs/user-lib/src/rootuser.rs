@@ -2,11 +2,12 @@
 ///
 /// Handles creation of the root administrative user in the database.
 /// This module is designed to be called during application initialization.
-use crate::entities::{Role, User};
+use crate::entities::{Role, RoleGrant, TenantId, User, UserAttributes, UserSuspension};
 use crate::errors_service::UserServiceError;
 use crate::repository::traits::{
     RoleRepositoryTrait, UserRepositoryTrait, UserRoleRepositoryTrait,
 };
+use crate::repository::unit_of_work::UnitOfWorkFactory;
 use uuid::Uuid;
 
 /// Configuration for root user initialization
@@ -58,8 +59,8 @@ impl RootUserConfig {
 /// Initialize root user in the database
 ///
 /// This function:
-/// 1. Creates the root user record in the database
-/// 2. Finds or creates the 'admin' role
+/// 1. Creates the root user record in the database, in the default tenant
+/// 2. Finds the default tenant's `admin` system role
 /// 3. Assigns the admin role to the root user
 ///
 /// Note: The Keycloak user must be created BEFORE calling this function,
@@ -71,9 +72,9 @@ pub async fn initialize_root_user<U, R, UR>(
     config: &RootUserConfig,
 ) -> Result<User, UserServiceError>
 where
-    U: UserRepositoryTrait,
-    R: RoleRepositoryTrait,
-    UR: UserRoleRepositoryTrait,
+    U: UserRepositoryTrait + ?Sized,
+    R: RoleRepositoryTrait + ?Sized,
+    UR: UserRoleRepositoryTrait + ?Sized,
 {
     if config.keycloak_id.is_empty() {
         return Err(UserServiceError::Validation(
@@ -83,7 +84,7 @@ where
 
     // Check if root user already exists by keycloak_id
     if let Some(existing) = user_repo
-        .get_user_by_keycloak_id(&config.keycloak_id)
+        .get_user_by_keycloak_id(TenantId::DEFAULT, &config.keycloak_id)
         .await
         .map_err(|e| UserServiceError::Internal(e.into()))?
     {
@@ -99,7 +100,7 @@ where
 
         // Get roles for the user
         let role_rows = role_repo
-            .get_roles_for_user(user_id)
+            .get_roles_for_user(TenantId::DEFAULT, user_id)
             .await
             .map_err(|e| UserServiceError::Internal(e.into()))?;
 
@@ -107,7 +108,15 @@ where
             .into_iter()
             .filter_map(|row| {
                 let id = Uuid::parse_str(&row.id).ok()?;
-                Some(Role { id, name: row.name })
+                Some(Role {
+                    id,
+                    name: row.name,
+                    description: row.description,
+                    is_system: row.is_system,
+                    created_at: row.created_at,
+                    updated_at: row.updated_at,
+                    version: row.version,
+                })
             })
             .collect();
 
@@ -115,6 +124,15 @@ where
             id: user_id,
             keycloak_id: existing.keycloak_id,
             roles,
+            attributes: UserAttributes::default(),
+            suspension: existing.suspended_at.map(|suspended_at| UserSuspension {
+                suspended_at,
+                reason: existing.suspension_reason,
+            }),
+            last_login_at: existing.last_login_at,
+            created_at: existing.created_at,
+            updated_at: existing.updated_at,
+            version: existing.version,
         });
     }
 
@@ -126,7 +144,7 @@ where
     );
 
     let user_row = user_repo
-        .create_user(&config.keycloak_id)
+        .create_user(TenantId::DEFAULT, &config.keycloak_id)
         .await
         .map_err(|e| UserServiceError::Internal(e.into()))?;
 
@@ -135,7 +153,8 @@ where
 
     // Find or verify admin role exists
     tracing::info!("Looking up admin role");
-    let admin_role_id = find_admin_role(role_repo).await?;
+    let admin_role = find_admin_role(role_repo, TenantId::DEFAULT).await?;
+    let admin_role_id = admin_role.id;
 
     // Assign admin role to root user
     tracing::info!(
@@ -145,7 +164,12 @@ where
     );
 
     user_role_repo
-        .assign_role(&user_id.to_string(), &admin_role_id.to_string())
+        .assign_role(
+            TenantId::DEFAULT,
+            &user_id.to_string(),
+            &admin_role_id.to_string(),
+            &RoleGrant::default(),
+        )
         .await
         .map_err(|e| match e {
             crate::repository::errors::UserRepositoryError::UserAlreadyHasRole => {
@@ -165,33 +189,60 @@ where
     Ok(User {
         id: user_id,
         keycloak_id: user_row.keycloak_id,
-        roles: vec![Role {
-            id: admin_role_id,
-            name: "admin".to_string(),
-        }],
+        roles: vec![admin_role],
+        attributes: UserAttributes::default(),
+        suspension: None,
+        last_login_at: None,
+        created_at: user_row.created_at,
+        updated_at: user_row.updated_at,
+        version: user_row.version,
     })
 }
 
-/// Find the admin role ID
-/// The admin role should be seeded during migrations
-async fn find_admin_role<R: RoleRepositoryTrait>(role_repo: &R) -> Result<Uuid, UserServiceError> {
-    use crate::entities::PaginationParams;
-
-    // Get all roles and find admin
-    let (roles, _) = role_repo
-        .get_roles_paginated(PaginationParams {
-            page: 1,
-            page_size: 100,
-        })
-        .await
-        .map_err(|e| UserServiceError::Internal(e.into()))?;
+/// Same as [`initialize_root_user`], but the user record and the admin role assignment
+/// are committed together: a failed assignment leaves no half-initialized root user behind.
+pub async fn initialize_root_user_in_transaction(
+    unit_of_work: &dyn UnitOfWorkFactory,
+    config: &RootUserConfig,
+) -> Result<User, UserServiceError> {
+    let uow = unit_of_work.begin().await?;
 
-    let admin_role = roles
-        .iter()
-        .find(|r| r.name.to_lowercase() == "admin")
-        .ok_or_else(|| UserServiceError::NotFound)?;
+    match initialize_root_user(uow.users(), uow.roles(), uow.user_roles(), config).await {
+        Ok(user) => {
+            uow.commit().await?;
+            Ok(user)
+        }
+        Err(e) => {
+            if let Err(rollback_err) = uow.rollback().await {
+                tracing::warn!(error = %rollback_err, "Failed to roll back root user initialization");
+            }
+            Err(e)
+        }
+    }
+}
 
-    Uuid::parse_str(&admin_role.id).map_err(|e| UserServiceError::InvalidUuid(e.to_string()))
+/// Find the `admin` system role of `tenant`, which every tenant gets when it is created
+/// (the default tenant by migration)
+async fn find_admin_role<R: RoleRepositoryTrait + ?Sized>(
+    role_repo: &R,
+    tenant: TenantId,
+) -> Result<Role, UserServiceError> {
+    let admin_role = role_repo
+        .get_system_role(tenant, "admin")
+        .await
+        .map_err(|e| UserServiceError::Internal(e.into()))?
+        .ok_or(UserServiceError::NotFound)?;
+
+    Ok(Role {
+        id: Uuid::parse_str(&admin_role.id)
+            .map_err(|e| UserServiceError::InvalidUuid(e.to_string()))?,
+        name: admin_role.name,
+        description: admin_role.description,
+        is_system: admin_role.is_system,
+        created_at: admin_role.created_at,
+        updated_at: admin_role.updated_at,
+        version: admin_role.version,
+    })
 }
 
 #[cfg(test)]
@@ -1,10 +1,19 @@
-use crate::entities::{PaginatedResult, PaginationParams, Role, User};
+use crate::entities::{
+    ApiKey, Group, NewApiKey, PaginatedResult, PaginationParams, Role, RoleGrant, Tenant, TenantId,
+    User, UserAttributes, UserAttributesPatch, UserFilter, UserSuspension,
+};
 use crate::errors_service::UserServiceError;
-use crate::repository::models::{RoleRow, UserRoleMapping, UserRow};
+use crate::repository::models::{
+    ApiKeyRow, GroupRoleMapping, GroupRow, RoleRow, TenantRow, UserAttributesRow, UserRoleMapping,
+    UserRow,
+};
 use crate::repository::traits::{
-    RoleRepositoryTrait, UserRepositoryTrait, UserRoleRepositoryTrait,
+    ApiKeyRepositoryTrait, GroupRepositoryTrait, RoleRepositoryTrait, TenantRepositoryTrait,
+    UserRepositoryTrait, UserRoleRepositoryTrait,
 };
+use crate::repository::unit_of_work::{UnitOfWork, UnitOfWorkFactory};
 use crate::repository::{RoleRepository, UserRepository, UserRoleRepository};
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 use std::sync::Arc;
 use uuid::Uuid;
@@ -13,6 +22,19 @@ fn parse_uuid(s: &str) -> Result<Uuid, UserServiceError> {
     Uuid::parse_str(s).map_err(|_| UserServiceError::InvalidUuid(s.to_string()))
 }
 
+/// Parse `(tenant_id, user_id)` rows into sorted, distinct pairs
+fn parse_grant_holders(
+    rows: &[(String, String)],
+) -> Result<Vec<(TenantId, Uuid)>, UserServiceError> {
+    let mut users = rows
+        .iter()
+        .map(|(tenant, user_id)| Ok((TenantId(parse_uuid(tenant)?), parse_uuid(user_id)?)))
+        .collect::<Result<Vec<_>, UserServiceError>>()?;
+    users.sort_unstable();
+    users.dedup();
+    Ok(users)
+}
+
 const MAX_ROLE_NAME_LENGTH: usize = 255;
 
 fn validate_role_name(name: &str) -> Result<(), UserServiceError> {
@@ -30,10 +52,295 @@ fn validate_role_name(name: &str) -> Result<(), UserServiceError> {
     Ok(())
 }
 
+const MAX_GROUP_NAME_LENGTH: usize = 255;
+
+fn validate_group_name(name: &str) -> Result<(), UserServiceError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(UserServiceError::Validation(
+            "group name cannot be empty".to_string(),
+        ));
+    }
+    if name.chars().count() > MAX_GROUP_NAME_LENGTH {
+        return Err(UserServiceError::Validation(format!(
+            "group name cannot exceed {MAX_GROUP_NAME_LENGTH} characters"
+        )));
+    }
+    Ok(())
+}
+
+const MAX_API_KEY_NAME_LENGTH: usize = 255;
+
+fn validate_api_key_name(name: &str) -> Result<(), UserServiceError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(UserServiceError::Validation(
+            "API key name cannot be empty".to_string(),
+        ));
+    }
+    if name.chars().count() > MAX_API_KEY_NAME_LENGTH {
+        return Err(UserServiceError::Validation(format!(
+            "API key name cannot exceed {MAX_API_KEY_NAME_LENGTH} characters"
+        )));
+    }
+    Ok(())
+}
+
+/// Scopes are stored space-separated, so each must be a single non-empty word.
+/// Returns them without duplicates, in the order given.
+fn normalize_api_key_scopes(scopes: &[String]) -> Result<Vec<String>, UserServiceError> {
+    if scopes.is_empty() {
+        return Err(UserServiceError::Validation(
+            "an API key needs at least one scope".to_string(),
+        ));
+    }
+    let mut normalized: Vec<String> = Vec::with_capacity(scopes.len());
+    for scope in scopes {
+        if scope.is_empty() || scope.chars().any(char::is_whitespace) {
+            return Err(UserServiceError::Validation(format!(
+                "invalid scope: {scope:?}"
+            )));
+        }
+        if !normalized.contains(scope) {
+            normalized.push(scope.clone());
+        }
+    }
+    Ok(normalized)
+}
+
+const MAX_ROLE_DESCRIPTION_LENGTH: usize = 1000;
+
+/// Trim a role description; an empty one means no description
+fn normalize_role_description(description: &str) -> Result<Option<String>, UserServiceError> {
+    let description = description.trim();
+    if description.chars().count() > MAX_ROLE_DESCRIPTION_LENGTH {
+        return Err(UserServiceError::Validation(format!(
+            "role description cannot exceed {MAX_ROLE_DESCRIPTION_LENGTH} characters"
+        )));
+    }
+    Ok((!description.is_empty()).then(|| description.to_string()))
+}
+
+const MAX_GRANTED_BY_LENGTH: usize = 255;
+
+/// Reject grants that are already expired; trim `granted_by`, dropping it when empty
+fn normalize_role_grant(grant: &RoleGrant) -> Result<RoleGrant, UserServiceError> {
+    if grant.is_expired_at(Utc::now()) {
+        return Err(UserServiceError::Validation(
+            "expires_at must be in the future".to_string(),
+        ));
+    }
+    let granted_by = grant.granted_by.as_deref().map(str::trim);
+    if granted_by.is_some_and(|g| g.chars().count() > MAX_GRANTED_BY_LENGTH) {
+        return Err(UserServiceError::Validation(format!(
+            "granted_by cannot exceed {MAX_GRANTED_BY_LENGTH} characters"
+        )));
+    }
+    Ok(RoleGrant {
+        expires_at: grant.expires_at,
+        granted_by: granted_by.filter(|g| !g.is_empty()).map(str::to_string),
+    })
+}
+
+const MAX_TENANT_SLUG_LENGTH: usize = 63;
+const MAX_TENANT_NAME_LENGTH: usize = 255;
+
+/// Slugs are DNS-label shaped: lowercase letters, digits and inner hyphens
+fn validate_tenant_slug(slug: &str) -> Result<(), UserServiceError> {
+    let valid_chars = slug
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    if slug.is_empty()
+        || slug.len() > MAX_TENANT_SLUG_LENGTH
+        || !valid_chars
+        || slug.starts_with('-')
+        || slug.ends_with('-')
+    {
+        return Err(UserServiceError::Validation(format!(
+            "tenant slug must be 1-{MAX_TENANT_SLUG_LENGTH} lowercase letters, digits or inner '-'"
+        )));
+    }
+    Ok(())
+}
+
+fn validate_tenant_name(name: &str) -> Result<(), UserServiceError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(UserServiceError::Validation(
+            "tenant name cannot be empty".to_string(),
+        ));
+    }
+    if name.chars().count() > MAX_TENANT_NAME_LENGTH {
+        return Err(UserServiceError::Validation(format!(
+            "tenant name cannot exceed {MAX_TENANT_NAME_LENGTH} characters"
+        )));
+    }
+    Ok(())
+}
+
+const MAX_DEPARTMENT_LENGTH: usize = 255;
+const MAX_COST_CENTER_LENGTH: usize = 64;
+const MAX_LOCALE_LENGTH: usize = 35;
+/// Upper bound on the serialized `metadata` object
+pub const MAX_METADATA_BYTES: usize = 16 * 1024;
+
+fn validate_department(department: &str) -> Result<(), UserServiceError> {
+    if department.trim().is_empty() {
+        return Err(UserServiceError::Validation(
+            "department cannot be empty, use null to clear it".to_string(),
+        ));
+    }
+    if department.trim().chars().count() > MAX_DEPARTMENT_LENGTH {
+        return Err(UserServiceError::Validation(format!(
+            "department cannot exceed {MAX_DEPARTMENT_LENGTH} characters"
+        )));
+    }
+    Ok(())
+}
+
+fn validate_cost_center(cost_center: &str) -> Result<(), UserServiceError> {
+    let valid_chars = cost_center
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if cost_center.is_empty() || cost_center.len() > MAX_COST_CENTER_LENGTH || !valid_chars {
+        return Err(UserServiceError::Validation(format!(
+            "cost center must be 1-{MAX_COST_CENTER_LENGTH} letters, digits, '-', '_' or '.'"
+        )));
+    }
+    Ok(())
+}
+
+/// Check `locale` is shaped like a BCP 47 tag and return it in canonical case
+/// (`en-us` becomes `en-US`), so filters match however clients spell it
+fn normalize_locale(locale: &str) -> Result<String, UserServiceError> {
+    let invalid = || {
+        UserServiceError::Validation(format!(
+            "locale must be a BCP 47 language tag such as en-US, got {locale:?}"
+        ))
+    };
+    if locale.len() > MAX_LOCALE_LENGTH {
+        return Err(invalid());
+    }
+
+    let mut subtags = locale.split('-');
+    let language = subtags.next().unwrap_or_default();
+    if !(2..=3).contains(&language.len()) || !language.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(invalid());
+    }
+    let mut normalized = language.to_ascii_lowercase();
+    for subtag in subtags {
+        if !(1..=8).contains(&subtag.len()) || !subtag.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(invalid());
+        }
+        normalized.push('-');
+        match subtag.len() {
+            // Region, e.g. US
+            2 if subtag.chars().all(|c| c.is_ascii_alphabetic()) => {
+                normalized.push_str(&subtag.to_ascii_uppercase())
+            }
+            // Script, e.g. Latn
+            4 if subtag.chars().all(|c| c.is_ascii_alphabetic()) => {
+                normalized.push_str(&subtag[..1].to_ascii_uppercase());
+                normalized.push_str(&subtag[1..].to_ascii_lowercase());
+            }
+            _ => normalized.push_str(&subtag.to_ascii_lowercase()),
+        }
+    }
+    Ok(normalized)
+}
+
+fn serialize_metadata(
+    metadata: &serde_json::Map<String, serde_json::Value>,
+) -> Result<String, UserServiceError> {
+    let json = serde_json::to_string(metadata).map_err(|e| UserServiceError::Internal(e.into()))?;
+    if json.len() > MAX_METADATA_BYTES {
+        return Err(UserServiceError::Validation(format!(
+            "metadata cannot exceed {MAX_METADATA_BYTES} bytes"
+        )));
+    }
+    Ok(json)
+}
+
+/// Apply a validated `patch` to the stored attributes of `user_id`
+fn apply_attributes_patch(
+    user_id: &str,
+    current: Option<UserAttributesRow>,
+    patch: &UserAttributesPatch,
+) -> Result<UserAttributesRow, UserServiceError> {
+    let mut row = current.unwrap_or_else(|| UserAttributesRow {
+        user_id: user_id.to_string(),
+        ..Default::default()
+    });
+    if let Some(department) = &patch.department {
+        if let Some(department) = department {
+            validate_department(department)?;
+        }
+        row.department = department.as_ref().map(|d| d.trim().to_string());
+    }
+    if let Some(cost_center) = &patch.cost_center {
+        if let Some(cost_center) = cost_center {
+            validate_cost_center(cost_center)?;
+        }
+        row.cost_center = cost_center.clone();
+    }
+    if let Some(locale) = &patch.locale {
+        row.locale = locale.as_deref().map(normalize_locale).transpose()?;
+    }
+    if let Some(metadata) = &patch.metadata {
+        row.metadata = metadata.as_ref().map(serialize_metadata).transpose()?;
+    }
+    row.updated_at = chrono::Utc::now();
+    Ok(row)
+}
+
+fn attributes_from_row(row: UserAttributesRow) -> Result<UserAttributes, UserServiceError> {
+    let metadata = row
+        .metadata
+        .as_deref()
+        .map(serde_json::from_str)
+        .transpose()
+        .map_err(|e| UserServiceError::Internal(e.into()))?;
+    Ok(UserAttributes {
+        department: row.department,
+        cost_center: row.cost_center,
+        locale: row.locale,
+        metadata,
+    })
+}
+
+fn tenant_from_row(row: TenantRow) -> Result<Tenant, UserServiceError> {
+    Ok(Tenant {
+        id: TenantId(parse_uuid(&row.id)?),
+        slug: row.slug,
+        name: row.name,
+        created_at: row.created_at,
+    })
+}
+
+fn api_key_from_row(row: ApiKeyRow) -> Result<ApiKey, UserServiceError> {
+    Ok(ApiKey {
+        id: parse_uuid(&row.id)?,
+        tenant: TenantId(parse_uuid(&row.tenant_id)?),
+        name: row.name,
+        prefix: row.key_prefix,
+        scopes: row.scopes.split_whitespace().map(str::to_string).collect(),
+        created_at: row.created_at,
+        expires_at: row.expires_at,
+        last_used_at: row.last_used_at,
+        rotated_at: row.rotated_at,
+        revoked_at: row.revoked_at,
+    })
+}
+
 fn role_from_row(row: RoleRow) -> Result<Role, UserServiceError> {
     Ok(Role {
         id: parse_uuid(&row.id)?,
         name: row.name,
+        description: row.description,
+        is_system: row.is_system,
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+        version: row.version,
     })
 }
 
@@ -41,18 +348,104 @@ fn role_from_mapping(mapping: UserRoleMapping) -> Result<(String, Role), UserSer
     let role = Role {
         id: parse_uuid(&mapping.role_id)?,
         name: mapping.role_name,
+        description: mapping.role_description,
+        is_system: mapping.role_is_system,
+        created_at: mapping.role_created_at,
+        updated_at: mapping.role_updated_at,
+        version: mapping.role_version,
     };
     Ok((mapping.user_id, role))
 }
 
-fn user_from_row(row: UserRow, roles: Vec<Role>) -> Result<User, UserServiceError> {
+fn role_from_group_mapping(mapping: GroupRoleMapping) -> Result<(String, Role), UserServiceError> {
+    let role = Role {
+        id: parse_uuid(&mapping.role_id)?,
+        name: mapping.role_name,
+        description: mapping.role_description,
+        is_system: mapping.role_is_system,
+        created_at: mapping.role_created_at,
+        updated_at: mapping.role_updated_at,
+        version: mapping.role_version,
+    };
+    Ok((mapping.group_id, role))
+}
+
+fn group_from_row(row: GroupRow, roles: Vec<Role>) -> Result<Group, UserServiceError> {
+    Ok(Group {
+        id: parse_uuid(&row.id)?,
+        keycloak_id: row.keycloak_id,
+        name: row.name,
+        roles,
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+    })
+}
+
+fn user_from_row(
+    row: UserRow,
+    roles: Vec<Role>,
+    attributes: UserAttributes,
+) -> Result<User, UserServiceError> {
     Ok(User {
         id: parse_uuid(&row.id)?,
         keycloak_id: row.keycloak_id,
         roles,
+        attributes,
+        suspension: row.suspended_at.map(|suspended_at| UserSuspension {
+            suspended_at,
+            reason: row.suspension_reason,
+        }),
+        last_login_at: row.last_login_at,
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+        version: row.version,
     })
 }
 
+/// Create the user and assign each role on the unit of work's repositories
+async fn create_user_with_roles_in(
+    uow: &dyn UnitOfWork,
+    tenant: TenantId,
+    keycloak_id: &str,
+    role_ids: &[Uuid],
+) -> Result<User, UserServiceError> {
+    let row = uow.users().create_user(tenant, keycloak_id).await?;
+
+    let mut roles = Vec::with_capacity(role_ids.len());
+    for role_id in role_ids {
+        let role = uow
+            .roles()
+            .get_role(tenant, *role_id)
+            .await?
+            .ok_or(UserServiceError::NotFound)?;
+        uow.user_roles()
+            .assign_role(tenant, &row.id, &role.id, &RoleGrant::default())
+            .await?;
+        roles.push(role_from_row(role)?);
+    }
+
+    user_from_row(row, roles, UserAttributes::default())
+}
+
+/// Commit the unit of work if `result` is a success, roll it back otherwise
+async fn finish<T>(
+    uow: Box<dyn UnitOfWork>,
+    result: Result<T, UserServiceError>,
+) -> Result<T, UserServiceError> {
+    match result {
+        Ok(value) => {
+            uow.commit().await?;
+            Ok(value)
+        }
+        Err(e) => {
+            if let Err(rollback_err) = uow.rollback().await {
+                tracing::warn!(error = %rollback_err, "Failed to roll back unit of work");
+            }
+            Err(e)
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct UserService<U = UserRepository, R = RoleRepository, UR = UserRoleRepository>
 where
@@ -63,6 +456,16 @@ where
     pub user_repo: Arc<U>,
     pub role_repo: Arc<R>,
     pub user_role_repo: Arc<UR>,
+    /// Starts transactions for the multi-step operations; they fail without one
+    pub unit_of_work: Option<Arc<dyn UnitOfWorkFactory>>,
+    /// When set, `delete_user` and `delete_role` mark rows deleted instead of removing them
+    pub soft_delete: bool,
+    /// Reads the tenant registry; tenant lookups fail without one
+    pub tenant_repo: Option<Arc<dyn TenantRepositoryTrait>>,
+    /// Stores groups and their members and roles; group operations fail without one
+    pub group_repo: Option<Arc<dyn GroupRepositoryTrait>>,
+    /// Stores hashed service-account API keys; API key operations fail without one
+    pub api_key_repo: Option<Arc<dyn ApiKeyRepositoryTrait>>,
 }
 
 impl UserService<UserRepository, RoleRepository, UserRoleRepository> {
@@ -75,6 +478,11 @@ impl UserService<UserRepository, RoleRepository, UserRoleRepository> {
             user_repo: Arc::new(user_repo),
             role_repo: Arc::new(role_repo),
             user_role_repo: Arc::new(user_role_repo),
+            unit_of_work: None,
+            soft_delete: false,
+            tenant_repo: None,
+            group_repo: None,
+            api_key_repo: None,
         }
     }
 }
@@ -90,12 +498,106 @@ where
             user_repo,
             role_repo,
             user_role_repo,
+            unit_of_work: None,
+            soft_delete: false,
+            tenant_repo: None,
+            group_repo: None,
+            api_key_repo: None,
         }
     }
 
-    async fn fetch_roles_for_user(&self, user_id: Uuid) -> Result<Vec<Role>, UserServiceError> {
+    /// Use `unit_of_work` to run multi-step operations in a single transaction
+    pub fn with_unit_of_work(mut self, unit_of_work: Arc<dyn UnitOfWorkFactory>) -> Self {
+        self.unit_of_work = Some(unit_of_work);
+        self
+    }
+
+    /// Keep deleted users and roles around (hidden from every read) so they can be restored
+    pub fn with_soft_delete(mut self, soft_delete: bool) -> Self {
+        self.soft_delete = soft_delete;
+        self
+    }
+
+    /// Use `tenant_repo` to look up tenants
+    pub fn with_tenant_repository(mut self, tenant_repo: Arc<dyn TenantRepositoryTrait>) -> Self {
+        self.tenant_repo = Some(tenant_repo);
+        self
+    }
+
+    fn tenant_repo(&self) -> Result<&dyn TenantRepositoryTrait, UserServiceError> {
+        self.tenant_repo.as_deref().ok_or_else(|| {
+            UserServiceError::Internal(anyhow::anyhow!("no tenant repository configured"))
+        })
+    }
+
+    /// Use `group_repo` to store groups
+    pub fn with_group_repository(mut self, group_repo: Arc<dyn GroupRepositoryTrait>) -> Self {
+        self.group_repo = Some(group_repo);
+        self
+    }
+
+    fn group_repo(&self) -> Result<&dyn GroupRepositoryTrait, UserServiceError> {
+        self.group_repo.as_deref().ok_or_else(|| {
+            UserServiceError::Internal(anyhow::anyhow!("no group repository configured"))
+        })
+    }
+
+    /// Use `api_key_repo` to store service-account API keys
+    pub fn with_api_key_repository(mut self, api_key_repo: Arc<dyn ApiKeyRepositoryTrait>) -> Self {
+        self.api_key_repo = Some(api_key_repo);
+        self
+    }
+
+    fn api_key_repo(&self) -> Result<&dyn ApiKeyRepositoryTrait, UserServiceError> {
+        self.api_key_repo.as_deref().ok_or_else(|| {
+            UserServiceError::Internal(anyhow::anyhow!("no API key repository configured"))
+        })
+    }
+
+    /// Load the roles of every group with one query
+    async fn build_groups_with_roles(
+        &self,
+        tenant: TenantId,
+        group_rows: Vec<GroupRow>,
+    ) -> Result<Vec<Group>, UserServiceError> {
+        if group_rows.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let group_ids: Vec<String> = group_rows.iter().map(|r| r.id.clone()).collect();
+        let mut roles_by_group: HashMap<String, Vec<Role>> = HashMap::new();
+        for mapping in self
+            .group_repo()?
+            .get_roles_for_groups(tenant, &group_ids)
+            .await?
+        {
+            let (group_id, role) = role_from_group_mapping(mapping)?;
+            roles_by_group.entry(group_id).or_default().push(role);
+        }
+
+        group_rows
+            .into_iter()
+            .map(|row| {
+                let roles = roles_by_group.remove(&row.id).unwrap_or_default();
+                group_from_row(row, roles)
+            })
+            .collect()
+    }
+
+    async fn begin(&self) -> Result<Box<dyn UnitOfWork>, UserServiceError> {
+        let factory = self.unit_of_work.as_ref().ok_or_else(|| {
+            UserServiceError::Internal(anyhow::anyhow!("no unit of work configured"))
+        })?;
+        Ok(factory.begin().await?)
+    }
+
+    async fn fetch_roles_for_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<Vec<Role>, UserServiceError> {
         self.role_repo
-            .get_roles_for_user(user_id)
+            .get_roles_for_user(tenant, user_id)
             .await
             .map_err(|e| UserServiceError::Internal(e.into()))?
             .into_iter()
@@ -103,8 +605,34 @@ where
             .collect()
     }
 
+    async fn fetch_attributes_for_user(
+        &self,
+        tenant: TenantId,
+        user_id: &str,
+    ) -> Result<UserAttributes, UserServiceError> {
+        let row = self
+            .user_repo
+            .get_user_attributes(tenant, &[user_id.to_string()])
+            .await?
+            .pop();
+        row.map(attributes_from_row)
+            .transpose()
+            .map(Option::unwrap_or_default)
+    }
+
+    /// Load a user's roles and attributes
+    async fn build_user(&self, tenant: TenantId, row: UserRow) -> Result<User, UserServiceError> {
+        let roles = self
+            .fetch_roles_for_user(tenant, parse_uuid(&row.id)?)
+            .await?;
+        let attributes = self.fetch_attributes_for_user(tenant, &row.id).await?;
+        user_from_row(row, roles, attributes)
+    }
+
+    /// Load the roles and attributes of every user with one query each
     async fn build_users_with_roles(
         &self,
+        tenant: TenantId,
         user_rows: Vec<UserRow>,
     ) -> Result<Vec<User>, UserServiceError> {
         if user_rows.is_empty() {
@@ -114,7 +642,7 @@ where
         let user_ids: Vec<String> = user_rows.iter().map(|r| r.id.clone()).collect();
         let role_mappings = self
             .role_repo
-            .get_roles_for_users(&user_ids)
+            .get_roles_for_users(tenant, &user_ids)
             .await
             .map_err(|e| UserServiceError::Internal(e.into()))?;
 
@@ -124,36 +652,67 @@ where
             roles_by_user.entry(user_id).or_default().push(role);
         }
 
+        let mut attributes_by_user: HashMap<String, UserAttributesRow> = self
+            .user_repo
+            .get_user_attributes(tenant, &user_ids)
+            .await?
+            .into_iter()
+            .map(|row| (row.user_id.clone(), row))
+            .collect();
+
         user_rows
             .into_iter()
             .map(|row| {
                 let roles = roles_by_user.remove(&row.id).unwrap_or_default();
-                user_from_row(row, roles)
+                let attributes = attributes_by_user
+                    .remove(&row.id)
+                    .map(attributes_from_row)
+                    .transpose()?
+                    .unwrap_or_default();
+                user_from_row(row, roles, attributes)
             })
             .collect()
     }
 
     /// Create a local user record for a Keycloak user
-    pub async fn create_user(&self, keycloak_id: &str) -> Result<User, UserServiceError> {
+    pub async fn create_user(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+    ) -> Result<User, UserServiceError> {
         let row = self
             .user_repo
-            .create_user(keycloak_id)
+            .create_user(tenant, keycloak_id)
             .await
             .map_err(UserServiceError::from)?;
-        user_from_row(row, vec![])
+        user_from_row(row, vec![], UserAttributes::default())
+    }
+
+    /// Create a local user record and assign `role_ids` atomically: if any role is
+    /// missing or cannot be assigned, no user is created
+    pub async fn create_user_with_roles(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+        role_ids: &[Uuid],
+    ) -> Result<User, UserServiceError> {
+        let uow = self.begin().await?;
+        let result = create_user_with_roles_in(uow.as_ref(), tenant, keycloak_id, role_ids).await;
+        finish(uow, result).await
     }
 
-    pub async fn get_user(&self, user_id: Uuid) -> Result<Option<User>, UserServiceError> {
+    pub async fn get_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<Option<User>, UserServiceError> {
         let user_row = self
             .user_repo
-            .get_user(user_id)
+            .get_user(tenant, user_id)
             .await
             .map_err(UserServiceError::from)?;
         match user_row {
-            Some(row) => {
-                let roles = self.fetch_roles_for_user(parse_uuid(&row.id)?).await?;
-                Ok(Some(user_from_row(row, roles)?))
-            }
+            Some(row) => Ok(Some(self.build_user(tenant, row).await?)),
             None => Ok(None),
         }
     }
@@ -161,97 +720,460 @@ where
     /// Get a user by their Keycloak ID
     pub async fn get_user_by_keycloak_id(
         &self,
+        tenant: TenantId,
         keycloak_id: &str,
     ) -> Result<Option<User>, UserServiceError> {
         let user_row = self
             .user_repo
-            .get_user_by_keycloak_id(keycloak_id)
+            .get_user_by_keycloak_id(tenant, keycloak_id)
             .await
             .map_err(UserServiceError::from)?;
         match user_row {
-            Some(row) => {
-                let roles = self.fetch_roles_for_user(parse_uuid(&row.id)?).await?;
-                Ok(Some(user_from_row(row, roles)?))
-            }
+            Some(row) => Ok(Some(self.build_user(tenant, row).await?)),
             None => Ok(None),
         }
     }
 
-    pub async fn delete_user(&self, user_id: Uuid) -> Result<(), UserServiceError> {
+    /// Tenant of the user with this Keycloak ID, looked up across all tenants
+    pub async fn get_user_tenant_by_keycloak_id(
+        &self,
+        keycloak_id: &str,
+    ) -> Result<Option<TenantId>, UserServiceError> {
         self.user_repo
-            .delete_user(user_id)
+            .get_user_tenant_by_keycloak_id(keycloak_id)
             .await
             .map_err(UserServiceError::from)
     }
 
-    pub async fn assign_role(&self, user_id: Uuid, role_id: Uuid) -> Result<(), UserServiceError> {
+    /// Record a change made to the user outside the local database (its Keycloak
+    /// profile), bumping its version; `VersionConflict` if `expected_version` is stale
+    pub async fn touch_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<User, UserServiceError> {
+        let row = self
+            .user_repo
+            .touch_user(tenant, user_id, expected_version)
+            .await?;
+        self.build_user(tenant, row).await
+    }
+
+    /// Record a Keycloak login of the user with `keycloak_id`; earlier logins than the one
+    /// recorded and unknown users are ignored
+    pub async fn record_user_login(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+        at: DateTime<Utc>,
+    ) -> Result<(), UserServiceError> {
+        self.user_repo
+            .record_user_login(tenant, keycloak_id, at)
+            .await
+            .map_err(UserServiceError::from)
+    }
+
+    /// Validate and apply `patch` to the user's attributes, bumping its version in the
+    /// same transaction; `VersionConflict` if `expected_version` is stale
+    pub async fn update_user_attributes(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        patch: &UserAttributesPatch,
+        expected_version: Option<i64>,
+    ) -> Result<User, UserServiceError> {
+        let uow = self.begin().await?;
+        let result = async {
+            let users = uow.users();
+            let row = users.touch_user(tenant, user_id, expected_version).await?;
+            let current = users
+                .get_user_attributes(tenant, std::slice::from_ref(&row.id))
+                .await?
+                .pop();
+            let attributes = apply_attributes_patch(&row.id, current, patch)?;
+            users.upsert_user_attributes(tenant, &attributes).await?;
+
+            let roles = uow
+                .roles()
+                .get_roles_for_user(tenant, user_id)
+                .await?
+                .into_iter()
+                .map(role_from_row)
+                .collect::<Result<_, _>>()?;
+            user_from_row(row, roles, attributes_from_row(attributes)?)
+        }
+        .await;
+        finish(uow, result).await
+    }
+
+    /// Suspend a user, recording `reason`; suspending again replaces the reason but keeps
+    /// the original time. `VersionConflict` if `expected_version` is stale.
+    pub async fn suspend_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        reason: Option<&str>,
+        expected_version: Option<i64>,
+    ) -> Result<User, UserServiceError> {
+        let current = self
+            .user_repo
+            .get_user(tenant, user_id)
+            .await?
+            .ok_or(UserServiceError::NotFound)?;
+        let suspension = UserSuspension {
+            suspended_at: current.suspended_at.unwrap_or_else(Utc::now),
+            reason: reason.map(str::to_string),
+        };
+        let row = self
+            .user_repo
+            .set_user_suspension(tenant, user_id, Some(suspension), expected_version)
+            .await?;
+        self.build_user(tenant, row).await
+    }
+
+    /// Lift a user's suspension; `VersionConflict` if `expected_version` is stale
+    pub async fn reactivate_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<User, UserServiceError> {
+        let row = self
+            .user_repo
+            .set_user_suspension(tenant, user_id, None, expected_version)
+            .await?;
+        self.build_user(tenant, row).await
+    }
+
+    /// Delete a user: soft when soft delete is enabled, permanent otherwise.
+    /// With `expected_version`, the user must still be at that version.
+    pub async fn delete_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<(), UserServiceError> {
+        if self.soft_delete {
+            let deleted = self
+                .user_repo
+                .soft_delete_user(tenant, user_id, expected_version)
+                .await?;
+            if !deleted && expected_version.is_some() {
+                return Err(UserServiceError::NotFound);
+            }
+            Ok(())
+        } else {
+            self.user_repo
+                .delete_user(tenant, user_id, expected_version)
+                .await
+                .map_err(UserServiceError::from)
+        }
+    }
+
+    /// Soft-delete a user regardless of the soft delete setting, e.g. to take back a
+    /// restore; `Ok(false)` when there is no live user to delete
+    pub async fn soft_delete_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<bool, UserServiceError> {
+        self.user_repo
+            .soft_delete_user(tenant, user_id, expected_version)
+            .await
+            .map_err(UserServiceError::from)
+    }
+
+    /// Bring back a soft-deleted user with the role assignments it had
+    pub async fn restore_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<User, UserServiceError> {
+        if !self.user_repo.restore_user(tenant, user_id).await? {
+            return Err(UserServiceError::NotFound);
+        }
+        self.get_user(tenant, user_id)
+            .await?
+            .ok_or(UserServiceError::NotFound)
+    }
+
+    /// Permanently remove a user, live or soft-deleted, with its role assignments
+    pub async fn purge_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<(), UserServiceError> {
+        self.user_repo
+            .delete_user(tenant, user_id, None)
+            .await
+            .map_err(UserServiceError::from)
+    }
+
+    /// Assign a role on the terms of `grant`; a grant with `expires_at` stops counting
+    /// at that instant and is removed by [`Self::remove_expired_role_grants`]
+    pub async fn assign_role(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+        role_id: Uuid,
+        grant: &RoleGrant,
+    ) -> Result<(), UserServiceError> {
+        let grant = normalize_role_grant(grant)?;
         self.user_role_repo
-            .assign_role(&user_id.to_string(), &role_id.to_string())
+            .assign_role(tenant, &user_id.to_string(), &role_id.to_string(), &grant)
             .await
             .map_err(UserServiceError::from)
     }
 
     pub async fn unassign_role(
         &self,
+        tenant: TenantId,
         user_id: Uuid,
         role_id: Uuid,
     ) -> Result<(), UserServiceError> {
         self.user_role_repo
-            .unassign_role(&user_id.to_string(), &role_id.to_string())
+            .unassign_role(tenant, &user_id.to_string(), &role_id.to_string())
             .await
             .map_err(UserServiceError::from)
     }
 
-    pub async fn get_roles_for_user(&self, user_id: Uuid) -> Result<Vec<Role>, UserServiceError> {
-        self.fetch_roles_for_user(user_id).await
+    /// The users, in every tenant, holding a role grant that expired at or before `now`,
+    /// each once along with its tenant
+    pub async fn get_users_with_expired_role_grants(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<(TenantId, Uuid)>, UserServiceError> {
+        let users = self
+            .user_role_repo
+            .get_users_with_expired_role_grants(now)
+            .await?;
+        parse_grant_holders(&users)
+    }
+
+    /// Delete the role grants that expired at or before `now` in every tenant, returning
+    /// each affected user once along with its tenant
+    pub async fn remove_expired_role_grants(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<(TenantId, Uuid)>, UserServiceError> {
+        let users = self.user_role_repo.delete_expired_role_grants(now).await?;
+        parse_grant_holders(&users)
+    }
+
+    pub async fn get_roles_for_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<Vec<Role>, UserServiceError> {
+        self.fetch_roles_for_user(tenant, user_id).await
+    }
+
+    /// Roles granted to the user itself through unexpired grants, without those of its
+    /// groups
+    pub async fn get_granted_roles_for_user(
+        &self,
+        tenant: TenantId,
+        user_id: Uuid,
+    ) -> Result<Vec<Role>, UserServiceError> {
+        self.role_repo
+            .get_granted_roles_for_user(tenant, user_id)
+            .await?
+            .into_iter()
+            .map(role_from_row)
+            .collect()
     }
 
-    pub async fn create_role(&self, name: &str) -> Result<Role, UserServiceError> {
+    pub async fn create_role(
+        &self,
+        tenant: TenantId,
+        name: &str,
+        description: Option<&str>,
+    ) -> Result<Role, UserServiceError> {
         validate_role_name(name)?;
+        let description = description
+            .map(normalize_role_description)
+            .transpose()?
+            .flatten();
         let row = self
             .role_repo
-            .create_role(name.trim())
+            .create_role(tenant, name.trim(), description)
             .await
             .map_err(UserServiceError::from)?;
         role_from_row(row)
     }
 
-    pub async fn get_role(&self, role_id: Uuid) -> Result<Option<Role>, UserServiceError> {
+    pub async fn get_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+    ) -> Result<Option<Role>, UserServiceError> {
         let role_row = self
             .role_repo
-            .get_role(role_id)
+            .get_role(tenant, role_id)
             .await
             .map_err(|e| UserServiceError::Internal(e.into()))?;
         role_row.map(role_from_row).transpose()
     }
 
-    pub async fn update_role(&self, role_id: Uuid, name: &str) -> Result<Role, UserServiceError> {
+    /// The live role, or `SystemRole` if it is one the application relies on
+    async fn get_role_unless_system(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+    ) -> Result<Option<RoleRow>, UserServiceError> {
+        let row = self.role_repo.get_role(tenant, role_id).await?;
+        if row.as_ref().is_some_and(|r| r.is_system) {
+            return Err(UserServiceError::SystemRole);
+        }
+        Ok(row)
+    }
+
+    /// Rename a role and set its description (`None` keeps it, an empty one clears it).
+    /// `SystemRole` if a system role would be renamed; `VersionConflict` if
+    /// `expected_version` is given and stale.
+    pub async fn update_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+        name: &str,
+        description: Option<&str>,
+        expected_version: Option<i64>,
+    ) -> Result<Role, UserServiceError> {
         validate_role_name(name)?;
+        let current = self
+            .role_repo
+            .get_role(tenant, role_id)
+            .await?
+            .ok_or(UserServiceError::NotFound)?;
+        if current.is_system && name.trim() != current.name {
+            return Err(UserServiceError::SystemRole);
+        }
+        let description = match description {
+            Some(description) => normalize_role_description(description)?,
+            None => current.description,
+        };
+
         let row = self
             .role_repo
-            .update_role(role_id, name.trim())
+            .update_role(tenant, role_id, name.trim(), description, expected_version)
             .await
             .map_err(UserServiceError::from)?;
         role_from_row(row)
     }
 
-    pub async fn delete_role(&self, role_id: Uuid) -> Result<(), UserServiceError> {
+    /// Delete a role: soft when soft delete is enabled, permanent otherwise.
+    /// With `expected_version`, the role must still be at that version.
+    /// System roles cannot be deleted.
+    pub async fn delete_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<(), UserServiceError> {
+        if self
+            .get_role_unless_system(tenant, role_id)
+            .await?
+            .is_none()
+        {
+            return Err(UserServiceError::NotFound);
+        }
+        if self.soft_delete {
+            let deleted = self
+                .role_repo
+                .soft_delete_role(tenant, role_id, expected_version)
+                .await
+                .map_err(UserServiceError::from)?;
+            if !deleted {
+                return Err(UserServiceError::NotFound);
+            }
+            Ok(())
+        } else {
+            self.role_repo
+                .delete_role(tenant, role_id, expected_version)
+                .await
+                .map_err(UserServiceError::from)
+        }
+    }
+
+    /// Bring back a soft-deleted role; users that held it get it back too
+    pub async fn restore_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+    ) -> Result<Role, UserServiceError> {
+        let restored = self.role_repo.restore_role(tenant, role_id).await?;
+        if !restored {
+            return Err(UserServiceError::NotFound);
+        }
+        self.get_role(tenant, role_id)
+            .await?
+            .ok_or(UserServiceError::NotFound)
+    }
+
+    /// Permanently remove a role, live or soft-deleted, with its assignments.
+    /// System roles cannot be purged.
+    pub async fn purge_role(
+        &self,
+        tenant: TenantId,
+        role_id: Uuid,
+    ) -> Result<(), UserServiceError> {
+        self.get_role_unless_system(tenant, role_id).await?;
         self.role_repo
-            .delete_role(role_id)
+            .delete_role(tenant, role_id, None)
             .await
-            .map_err(|e| UserServiceError::Internal(e.into()))
+            .map_err(UserServiceError::from)
+    }
+
+    /// Create a tenant with its own `admin` and `user` system roles. The repository inserts
+    /// the roles with the tenant row; the unit of work commits all three or none.
+    pub async fn create_tenant(&self, slug: &str, name: &str) -> Result<Tenant, UserServiceError> {
+        validate_tenant_slug(slug)?;
+        validate_tenant_name(name)?;
+        let uow = self.begin().await?;
+        let result = async {
+            let row = uow.tenants().create_tenant(slug, name.trim()).await?;
+            tenant_from_row(row)
+        }
+        .await;
+        finish(uow, result).await
+    }
+
+    pub async fn get_tenant(&self, tenant: TenantId) -> Result<Option<Tenant>, UserServiceError> {
+        self.tenant_repo()?
+            .get_tenant(tenant)
+            .await?
+            .map(tenant_from_row)
+            .transpose()
+    }
+
+    pub async fn get_tenants(&self) -> Result<Vec<Tenant>, UserServiceError> {
+        self.tenant_repo()?
+            .get_tenants()
+            .await?
+            .into_iter()
+            .map(tenant_from_row)
+            .collect()
     }
 
+    /// Users matching every attribute set in `filter`
     pub async fn get_users(
         &self,
+        tenant: TenantId,
         pagination: PaginationParams,
+        filter: &UserFilter,
     ) -> Result<PaginatedResult<User>, UserServiceError> {
+        let filter = UserFilter {
+            locale: filter.locale.as_deref().map(normalize_locale).transpose()?,
+            ..filter.clone()
+        };
         let (user_rows, total) = self
             .user_repo
-            .get_users_paginated(pagination)
+            .get_users_paginated(tenant, pagination, &filter)
             .await
             .map_err(UserServiceError::from)?;
-        let users = self.build_users_with_roles(user_rows).await?;
+        let users = self.build_users_with_roles(tenant, user_rows).await?;
         Ok(PaginatedResult {
             items: users,
             total,
@@ -263,11 +1185,12 @@ where
 
     pub async fn get_roles(
         &self,
+        tenant: TenantId,
         pagination: PaginationParams,
     ) -> Result<PaginatedResult<Role>, UserServiceError> {
         let (role_rows, total) = self
             .role_repo
-            .get_roles_paginated(pagination)
+            .get_roles_paginated(tenant, pagination)
             .await
             .map_err(|e| UserServiceError::Internal(e.into()))?;
         let roles: Vec<Role> = role_rows
@@ -285,15 +1208,16 @@ where
 
     pub async fn get_users_by_role(
         &self,
+        tenant: TenantId,
         role_id: Uuid,
         pagination: PaginationParams,
     ) -> Result<PaginatedResult<User>, UserServiceError> {
         let (user_rows, total) = self
             .user_repo
-            .get_users_by_role_paginated(role_id, pagination)
+            .get_users_by_role_paginated(tenant, role_id, pagination)
             .await
             .map_err(UserServiceError::from)?;
-        let users = self.build_users_with_roles(user_rows).await?;
+        let users = self.build_users_with_roles(tenant, user_rows).await?;
         Ok(PaginatedResult {
             items: users,
             total,
@@ -302,4 +1226,252 @@ where
             total_pages: ((total as f64) / (pagination.page_size as f64)).ceil() as u32,
         })
     }
+
+    /// Record a group that was created in Keycloak under `keycloak_id`
+    pub async fn create_group(
+        &self,
+        tenant: TenantId,
+        keycloak_id: &str,
+        name: &str,
+    ) -> Result<Group, UserServiceError> {
+        validate_group_name(name)?;
+        let row = self
+            .group_repo()?
+            .create_group(tenant, keycloak_id, name.trim())
+            .await?;
+        group_from_row(row, vec![])
+    }
+
+    pub async fn get_group(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+    ) -> Result<Option<Group>, UserServiceError> {
+        let Some(row) = self.group_repo()?.get_group(tenant, group_id).await? else {
+            return Ok(None);
+        };
+        Ok(self.build_groups_with_roles(tenant, vec![row]).await?.pop())
+    }
+
+    pub async fn get_groups(
+        &self,
+        tenant: TenantId,
+        pagination: PaginationParams,
+    ) -> Result<PaginatedResult<Group>, UserServiceError> {
+        let (group_rows, total) = self
+            .group_repo()?
+            .get_groups_paginated(tenant, pagination)
+            .await?;
+        let groups = self.build_groups_with_roles(tenant, group_rows).await?;
+        Ok(PaginatedResult {
+            items: groups,
+            total,
+            page: pagination.page,
+            page_size: pagination.page_size,
+            total_pages: ((total as f64) / (pagination.page_size as f64)).ceil() as u32,
+        })
+    }
+
+    pub async fn rename_group(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        name: &str,
+    ) -> Result<Group, UserServiceError> {
+        validate_group_name(name)?;
+        let row = self
+            .group_repo()?
+            .rename_group(tenant, group_id, name.trim())
+            .await?;
+        self.build_groups_with_roles(tenant, vec![row])
+            .await?
+            .pop()
+            .ok_or(UserServiceError::NotFound)
+    }
+
+    /// Delete a group; its members lose the roles they inherited from it
+    pub async fn delete_group(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+    ) -> Result<(), UserServiceError> {
+        if !self.group_repo()?.delete_group(tenant, group_id).await? {
+            return Err(UserServiceError::NotFound);
+        }
+        Ok(())
+    }
+
+    /// Add a user to a group; adding an existing member is a no-op
+    pub async fn add_group_member(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), UserServiceError> {
+        self.group_repo()?
+            .add_group_member(tenant, &group_id.to_string(), &user_id.to_string())
+            .await
+            .map_err(UserServiceError::from)
+    }
+
+    pub async fn remove_group_member(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), UserServiceError> {
+        self.group_repo()?
+            .remove_group_member(tenant, &group_id.to_string(), &user_id.to_string())
+            .await
+            .map_err(UserServiceError::from)
+    }
+
+    pub async fn get_group_members(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        pagination: PaginationParams,
+    ) -> Result<PaginatedResult<User>, UserServiceError> {
+        let (user_rows, total) = self
+            .group_repo()?
+            .get_group_members_paginated(tenant, group_id, pagination)
+            .await?;
+        let users = self.build_users_with_roles(tenant, user_rows).await?;
+        Ok(PaginatedResult {
+            items: users,
+            total,
+            page: pagination.page,
+            page_size: pagination.page_size,
+            total_pages: ((total as f64) / (pagination.page_size as f64)).ceil() as u32,
+        })
+    }
+
+    /// Attach a role to a group so its members inherit it; attaching it again is a no-op
+    pub async fn assign_group_role(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        role_id: Uuid,
+    ) -> Result<(), UserServiceError> {
+        self.group_repo()?
+            .assign_group_role(tenant, &group_id.to_string(), &role_id.to_string())
+            .await
+            .map_err(UserServiceError::from)
+    }
+
+    pub async fn unassign_group_role(
+        &self,
+        tenant: TenantId,
+        group_id: Uuid,
+        role_id: Uuid,
+    ) -> Result<(), UserServiceError> {
+        self.group_repo()?
+            .unassign_group_role(tenant, &group_id.to_string(), &role_id.to_string())
+            .await
+            .map_err(UserServiceError::from)
+    }
+
+    /// Store a new API key of `tenant`. Only the hash of the key reaches the database;
+    /// `key_prefix` is kept so the key can be recognized in listings.
+    pub async fn create_api_key(
+        &self,
+        tenant: TenantId,
+        new_key: &NewApiKey,
+        key_prefix: &str,
+        key_hash: &str,
+    ) -> Result<ApiKey, UserServiceError> {
+        validate_api_key_name(&new_key.name)?;
+        let scopes = normalize_api_key_scopes(&new_key.scopes)?;
+        if new_key
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= Utc::now())
+        {
+            return Err(UserServiceError::Validation(
+                "expires_at must be in the future".to_string(),
+            ));
+        }
+        let row = self
+            .api_key_repo()?
+            .create_api_key(
+                tenant,
+                new_key.name.trim(),
+                key_prefix,
+                key_hash,
+                &scopes.join(" "),
+                new_key.expires_at,
+            )
+            .await?;
+        api_key_from_row(row)
+    }
+
+    pub async fn get_api_key(
+        &self,
+        tenant: TenantId,
+        key_id: Uuid,
+    ) -> Result<Option<ApiKey>, UserServiceError> {
+        self.api_key_repo()?
+            .get_api_key(tenant, key_id)
+            .await?
+            .map(api_key_from_row)
+            .transpose()
+    }
+
+    /// Every API key of the tenant, revoked and expired ones included, newest first
+    pub async fn get_api_keys(&self, tenant: TenantId) -> Result<Vec<ApiKey>, UserServiceError> {
+        self.api_key_repo()?
+            .get_api_keys(tenant)
+            .await?
+            .into_iter()
+            .map(api_key_from_row)
+            .collect()
+    }
+
+    /// The API key with this hash, in whichever tenant it belongs to
+    pub async fn get_api_key_by_hash(
+        &self,
+        key_hash: &str,
+    ) -> Result<Option<ApiKey>, UserServiceError> {
+        self.api_key_repo()?
+            .get_api_key_by_hash(key_hash)
+            .await?
+            .map(api_key_from_row)
+            .transpose()
+    }
+
+    /// Replace the key of an unrevoked API key; the old key stops working at once
+    pub async fn rotate_api_key(
+        &self,
+        tenant: TenantId,
+        key_id: Uuid,
+        key_prefix: &str,
+        key_hash: &str,
+    ) -> Result<ApiKey, UserServiceError> {
+        let row = self
+            .api_key_repo()?
+            .rotate_api_key(tenant, key_id, key_prefix, key_hash)
+            .await?;
+        api_key_from_row(row)
+    }
+
+    pub async fn revoke_api_key(
+        &self,
+        tenant: TenantId,
+        key_id: Uuid,
+    ) -> Result<(), UserServiceError> {
+        if !self.api_key_repo()?.revoke_api_key(tenant, key_id).await? {
+            return Err(UserServiceError::NotFound);
+        }
+        Ok(())
+    }
+
+    pub async fn record_api_key_use(
+        &self,
+        key_id: Uuid,
+        used_at: DateTime<Utc>,
+    ) -> Result<(), UserServiceError> {
+        self.api_key_repo()?
+            .record_api_key_use(key_id, used_at)
+            .await
+            .map_err(UserServiceError::from)
+    }
 }
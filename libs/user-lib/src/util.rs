@@ -1,11 +1,34 @@
-use std::{str::FromStr, time::Duration};
+use async_trait::async_trait;
+use std::{str::FromStr, sync::Arc, time::Duration};
 
 use sqlx::{
     mysql::{MySqlConnectOptions, MySqlPoolOptions},
     MySqlPool,
 };
+#[cfg(feature = "postgres")]
+use sqlx::{
+    postgres::{PgConnectOptions, PgPoolOptions},
+    PgPool,
+};
+#[cfg(feature = "sqlite")]
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+    SqlitePool,
+};
 use tokio::time::sleep;
 
+use crate::repository::errors::UserRepositoryError;
+use crate::repository::traits::{
+    ApiKeyRepositoryTrait, EventCheckpointRepositoryTrait, GroupRepositoryTrait,
+    IdempotencyRepositoryTrait, RoleRepositoryTrait, TenantRepositoryTrait, UserRepositoryTrait,
+    UserRoleRepositoryTrait,
+};
+use crate::repository::unit_of_work::{UnitOfWork, UnitOfWorkFactory};
+use crate::repository::{
+    ApiKeyRepository, EventCheckpointRepository, GroupRepository, IdempotencyRepository,
+    RoleRepository, TenantRepository, UserRepository, UserRoleRepository,
+};
+
 /// Database pool configuration
 #[derive(Debug, Clone)]
 pub struct PoolConfig {
@@ -135,3 +158,329 @@ pub async fn connect_with_retry_and_config(
         }
     }
 }
+
+/// Database backend, selected by the `DATABASE_URL` scheme
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatabaseKind {
+    MySql,
+    Postgres,
+    Sqlite,
+}
+
+impl DatabaseKind {
+    pub fn from_url(database_url: &str) -> Option<Self> {
+        // SQLite URLs may omit the slashes (`sqlite::memory:`, `sqlite:data.db`)
+        let scheme = database_url.split_once(':')?.0;
+        match scheme.to_ascii_lowercase().as_str() {
+            "mysql" | "mariadb" => Some(Self::MySql),
+            "postgres" | "postgresql" => Some(Self::Postgres),
+            "sqlite" => Some(Self::Sqlite),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::MySql => "mysql",
+            Self::Postgres => "postgres",
+            Self::Sqlite => "sqlite",
+        }
+    }
+}
+
+/// Repositories built from a [`DbPool`], erased so callers don't depend on the backend
+pub type DynRepositories = (
+    Arc<dyn UserRepositoryTrait>,
+    Arc<dyn RoleRepositoryTrait>,
+    Arc<dyn UserRoleRepositoryTrait>,
+);
+
+/// Connection pool for whichever backend `DATABASE_URL` points at.
+/// Variants depend on enabled features, so matches outside this crate need a fallback arm.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum DbPool {
+    MySql(MySqlPool),
+    #[cfg(feature = "postgres")]
+    Postgres(PgPool),
+    #[cfg(feature = "sqlite")]
+    Sqlite(SqlitePool),
+}
+
+impl DbPool {
+    pub fn kind(&self) -> DatabaseKind {
+        match self {
+            Self::MySql(_) => DatabaseKind::MySql,
+            #[cfg(feature = "postgres")]
+            Self::Postgres(_) => DatabaseKind::Postgres,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(_) => DatabaseKind::Sqlite,
+        }
+    }
+
+    /// User, role and user-role repositories backed by this pool
+    pub fn repositories(&self) -> DynRepositories {
+        match self {
+            Self::MySql(pool) => (
+                Arc::new(UserRepository::new(pool.clone())),
+                Arc::new(RoleRepository::new(pool.clone())),
+                Arc::new(UserRoleRepository::new(pool.clone())),
+            ),
+            #[cfg(feature = "postgres")]
+            Self::Postgres(pool) => {
+                use crate::repository::postgres::{
+                    PgRoleRepository, PgUserRepository, PgUserRoleRepository,
+                };
+                (
+                    Arc::new(PgUserRepository::new(pool.clone())),
+                    Arc::new(PgRoleRepository::new(pool.clone())),
+                    Arc::new(PgUserRoleRepository::new(pool.clone())),
+                )
+            }
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => {
+                use crate::repository::sqlite::{
+                    SqliteRoleRepository, SqliteUserRepository, SqliteUserRoleRepository,
+                };
+                (
+                    Arc::new(SqliteUserRepository::new(pool.clone())),
+                    Arc::new(SqliteRoleRepository::new(pool.clone())),
+                    Arc::new(SqliteUserRoleRepository::new(pool.clone())),
+                )
+            }
+        }
+    }
+
+    /// Repository for stored `Idempotency-Key` responses, backed by this pool
+    pub fn idempotency_repository(&self) -> Arc<dyn IdempotencyRepositoryTrait> {
+        match self {
+            Self::MySql(pool) => Arc::new(IdempotencyRepository::new(pool.clone())),
+            #[cfg(feature = "postgres")]
+            Self::Postgres(pool) => Arc::new(
+                crate::repository::postgres::PgIdempotencyRepository::new(pool.clone()),
+            ),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => Arc::new(
+                crate::repository::sqlite::SqliteIdempotencyRepository::new(pool.clone()),
+            ),
+        }
+    }
+
+    /// Repository for event feed checkpoints, backed by this pool
+    pub fn event_checkpoint_repository(&self) -> Arc<dyn EventCheckpointRepositoryTrait> {
+        match self {
+            Self::MySql(pool) => Arc::new(EventCheckpointRepository::new(pool.clone())),
+            #[cfg(feature = "postgres")]
+            Self::Postgres(pool) => Arc::new(
+                crate::repository::postgres::PgEventCheckpointRepository::new(pool.clone()),
+            ),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => Arc::new(
+                crate::repository::sqlite::SqliteEventCheckpointRepository::new(pool.clone()),
+            ),
+        }
+    }
+
+    /// Repository for service-account API keys, backed by this pool
+    pub fn api_key_repository(&self) -> Arc<dyn ApiKeyRepositoryTrait> {
+        match self {
+            Self::MySql(pool) => Arc::new(ApiKeyRepository::new(pool.clone())),
+            #[cfg(feature = "postgres")]
+            Self::Postgres(pool) => Arc::new(crate::repository::postgres::PgApiKeyRepository::new(
+                pool.clone(),
+            )),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => Arc::new(crate::repository::sqlite::SqliteApiKeyRepository::new(
+                pool.clone(),
+            )),
+        }
+    }
+
+    /// Group repository backed by this pool
+    pub fn group_repository(&self) -> Arc<dyn GroupRepositoryTrait> {
+        match self {
+            Self::MySql(pool) => Arc::new(GroupRepository::new(pool.clone())),
+            #[cfg(feature = "postgres")]
+            Self::Postgres(pool) => Arc::new(crate::repository::postgres::PgGroupRepository::new(
+                pool.clone(),
+            )),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => Arc::new(crate::repository::sqlite::SqliteGroupRepository::new(
+                pool.clone(),
+            )),
+        }
+    }
+
+    /// Tenant repository backed by this pool
+    pub fn tenant_repository(&self) -> Arc<dyn TenantRepositoryTrait> {
+        match self {
+            Self::MySql(pool) => Arc::new(TenantRepository::new(pool.clone())),
+            #[cfg(feature = "postgres")]
+            Self::Postgres(pool) => Arc::new(crate::repository::postgres::PgTenantRepository::new(
+                pool.clone(),
+            )),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => Arc::new(crate::repository::sqlite::SqliteTenantRepository::new(
+                pool.clone(),
+            )),
+        }
+    }
+}
+
+// Calls go through the trait explicitly: the pools' inherent `begin` opens a bare transaction
+#[async_trait]
+impl UnitOfWorkFactory for DbPool {
+    async fn begin(&self) -> Result<Box<dyn UnitOfWork>, UserRepositoryError> {
+        match self {
+            Self::MySql(pool) => UnitOfWorkFactory::begin(pool).await,
+            #[cfg(feature = "postgres")]
+            Self::Postgres(pool) => UnitOfWorkFactory::begin(pool).await,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => UnitOfWorkFactory::begin(pool).await,
+        }
+    }
+}
+
+/// Connect to the database named by `database_url`, picking the backend from its scheme
+pub async fn connect(database_url: &str, max_retries: u32) -> Result<DbPool, ConnectionError> {
+    let config = PoolConfig::from_env();
+    match DatabaseKind::from_url(database_url) {
+        Some(DatabaseKind::MySql) => Ok(DbPool::MySql(
+            connect_with_retry_and_config(database_url, max_retries, config).await?,
+        )),
+        #[cfg(feature = "postgres")]
+        Some(DatabaseKind::Postgres) => Ok(DbPool::Postgres(
+            connect_postgres_with_retry_and_config(database_url, max_retries, config).await?,
+        )),
+        #[cfg(not(feature = "postgres"))]
+        Some(DatabaseKind::Postgres) => Err(ConnectionError {
+            message: "DATABASE_URL points at PostgreSQL but the `postgres` feature is not enabled"
+                .to_string(),
+            retries: 0,
+        }),
+        #[cfg(feature = "sqlite")]
+        Some(DatabaseKind::Sqlite) => Ok(DbPool::Sqlite(
+            connect_sqlite_with_config(database_url, config).await?,
+        )),
+        #[cfg(not(feature = "sqlite"))]
+        Some(DatabaseKind::Sqlite) => Err(ConnectionError {
+            message: "DATABASE_URL points at SQLite but the `sqlite` feature is not enabled"
+                .to_string(),
+            retries: 0,
+        }),
+        None => Err(ConnectionError {
+            message:
+                "Invalid DATABASE_URL: unsupported scheme (expected mysql://, postgres:// or sqlite:)"
+                    .to_string(),
+            retries: 0,
+        }),
+    }
+}
+
+/// Connect to PostgreSQL with retry logic and explicit pool configuration
+#[cfg(feature = "postgres")]
+pub async fn connect_postgres_with_retry_and_config(
+    database_url: &str,
+    max_retries: u32,
+    config: PoolConfig,
+) -> Result<PgPool, ConnectionError> {
+    let mut retries = 0;
+
+    let connect_options =
+        PgConnectOptions::from_str(database_url).map_err(|e| ConnectionError {
+            message: format!("Invalid DATABASE_URL: {e}"),
+            retries: 0,
+        })?;
+
+    loop {
+        match PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .min_connections(config.min_connections)
+            .acquire_timeout(config.acquire_timeout)
+            .idle_timeout(config.idle_timeout)
+            .max_lifetime(config.max_lifetime)
+            .connect_with(connect_options.clone())
+            .await
+        {
+            Ok(pool) => return Ok(pool),
+            Err(e) if retries < max_retries => {
+                tracing::warn!(
+                    attempt = retries + 1,
+                    max_retries = max_retries,
+                    error = %e,
+                    "PostgreSQL not ready yet, retrying..."
+                );
+                retries += 1;
+                sleep(Duration::from_secs(1)).await;
+            }
+            Err(e) => {
+                return Err(ConnectionError {
+                    message: format!(
+                        "Failed to connect to PostgreSQL after {max_retries} retries: {e}"
+                    ),
+                    retries,
+                });
+            }
+        }
+    }
+}
+
+/// Open a SQLite database, creating the file if needed. There is no server to wait for,
+/// so unlike the networked backends this does not retry.
+#[cfg(feature = "sqlite")]
+pub async fn connect_sqlite_with_config(
+    database_url: &str,
+    config: PoolConfig,
+) -> Result<SqlitePool, ConnectionError> {
+    let connect_options = SqliteConnectOptions::from_str(database_url)
+        .map_err(|e| ConnectionError {
+            message: format!("Invalid DATABASE_URL: {e}"),
+            retries: 0,
+        })?
+        .create_if_missing(true)
+        .foreign_keys(true);
+
+    SqlitePoolOptions::new()
+        .max_connections(config.max_connections)
+        .min_connections(config.min_connections)
+        .acquire_timeout(config.acquire_timeout)
+        .idle_timeout(config.idle_timeout)
+        .max_lifetime(config.max_lifetime)
+        .connect_with(connect_options)
+        .await
+        .map_err(|e| ConnectionError {
+            message: format!("Failed to open SQLite database: {e}"),
+            retries: 0,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_database_kind_from_url() {
+        assert_eq!(
+            DatabaseKind::from_url("mysql://root:pw@localhost/users"),
+            Some(DatabaseKind::MySql)
+        );
+        assert_eq!(
+            DatabaseKind::from_url("postgres://app@db:5432/users"),
+            Some(DatabaseKind::Postgres)
+        );
+        assert_eq!(
+            DatabaseKind::from_url("PostgreSQL://app@db/users"),
+            Some(DatabaseKind::Postgres)
+        );
+        assert_eq!(
+            DatabaseKind::from_url("sqlite::memory:"),
+            Some(DatabaseKind::Sqlite)
+        );
+        assert_eq!(
+            DatabaseKind::from_url("sqlite://data/users.db"),
+            Some(DatabaseKind::Sqlite)
+        );
+        assert_eq!(DatabaseKind::from_url("localhost:3306"), None);
+        assert_eq!(DatabaseKind::from_url("users.db"), None);
+    }
+}
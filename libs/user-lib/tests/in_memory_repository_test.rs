@@ -0,0 +1,891 @@
+use std::sync::Arc;
+use uuid::Uuid;
+
+use user_lib::entities::{
+    NewApiKey, PaginationParams, RoleGrant, TenantId, UserAttributesPatch, UserFilter, UserStatus,
+};
+use user_lib::errors_service::UserServiceError;
+use user_lib::repository::unit_of_work::UnitOfWorkFactory;
+use user_lib::repository::{
+    EventCheckpointRepositoryTrait, IdempotencyRepositoryTrait, InMemoryApiKeyRepository,
+    InMemoryEventCheckpointRepository, InMemoryIdempotencyRepository, InMemoryRoleRepository,
+    InMemoryStore, InMemoryUserRepository, InMemoryUserRoleRepository,
+};
+use user_lib::user_service::UserService;
+
+type InMemoryService =
+    UserService<InMemoryUserRepository, InMemoryRoleRepository, InMemoryUserRoleRepository>;
+
+fn create_service(store: &InMemoryStore) -> InMemoryService {
+    let (users, roles, user_roles) = store.repositories();
+    UserService::with_repos(Arc::new(users), Arc::new(roles), Arc::new(user_roles))
+        .with_unit_of_work(Arc::new(store.clone()))
+        .with_tenant_repository(Arc::new(store.tenant_repository()))
+        .with_group_repository(Arc::new(store.group_repository()))
+        .with_api_key_repository(Arc::new(InMemoryApiKeyRepository::new()))
+}
+
+#[tokio::test]
+async fn test_default_roles_are_seeded() {
+    let service = create_service(&InMemoryStore::with_default_roles());
+
+    let roles = service
+        .get_roles(TenantId::DEFAULT, PaginationParams::default())
+        .await
+        .unwrap();
+    let names: Vec<_> = roles.items.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, vec!["admin", "user"]);
+}
+
+#[tokio::test]
+async fn test_role_name_unique_constraint() {
+    let service = create_service(&InMemoryStore::new());
+    let editor = service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+    let viewer = service
+        .create_role(TenantId::DEFAULT, "viewer", None)
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        service.create_role(TenantId::DEFAULT, "Editor", None).await,
+        Err(UserServiceError::RoleNameAlreadyExists)
+    ));
+    assert!(matches!(
+        service
+            .update_role(TenantId::DEFAULT, viewer.id, "editor", None, None)
+            .await,
+        Err(UserServiceError::RoleNameAlreadyExists)
+    ));
+    // Renaming a role to its own name is not a conflict
+    assert!(service
+        .update_role(TenantId::DEFAULT, editor.id, "editor", None, None)
+        .await
+        .is_ok());
+
+    // Only live roles hold their name
+    let service = service.with_soft_delete(true);
+    service
+        .delete_role(TenantId::DEFAULT, editor.id, None)
+        .await
+        .unwrap();
+    service
+        .create_role(TenantId::DEFAULT, "Editor", None)
+        .await
+        .unwrap();
+    assert!(matches!(
+        service.restore_role(TenantId::DEFAULT, editor.id).await,
+        Err(UserServiceError::RoleNameAlreadyExists)
+    ));
+}
+
+#[tokio::test]
+async fn test_assign_role_twice_is_rejected() {
+    let service = create_service(&InMemoryStore::new());
+    let user = service
+        .create_user(TenantId::DEFAULT, "kc-1")
+        .await
+        .unwrap();
+    let role = service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+
+    service
+        .assign_role(TenantId::DEFAULT, user.id, role.id, &RoleGrant::default())
+        .await
+        .unwrap();
+    assert!(matches!(
+        service
+            .assign_role(TenantId::DEFAULT, user.id, role.id, &RoleGrant::default())
+            .await,
+        Err(UserServiceError::UserAlreadyHasRole)
+    ));
+}
+
+#[tokio::test]
+async fn test_assign_role_requires_existing_user_and_role() {
+    let service = create_service(&InMemoryStore::new());
+    let role = service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        service
+            .assign_role(
+                TenantId::DEFAULT,
+                Uuid::new_v4(),
+                role.id,
+                &RoleGrant::default()
+            )
+            .await,
+        Err(UserServiceError::NotFound)
+    ));
+}
+
+#[tokio::test]
+async fn test_soft_delete_hides_rows_until_restored() {
+    let service = create_service(&InMemoryStore::new()).with_soft_delete(true);
+    let user = service
+        .create_user(TenantId::DEFAULT, "kc-1")
+        .await
+        .unwrap();
+    let role = service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+    service
+        .assign_role(TenantId::DEFAULT, user.id, role.id, &RoleGrant::default())
+        .await
+        .unwrap();
+
+    service
+        .delete_user(TenantId::DEFAULT, user.id, None)
+        .await
+        .unwrap();
+    service
+        .delete_role(TenantId::DEFAULT, role.id, None)
+        .await
+        .unwrap();
+    assert!(service
+        .get_user(TenantId::DEFAULT, user.id)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(service
+        .get_role(TenantId::DEFAULT, role.id)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(matches!(
+        service
+            .assign_role(TenantId::DEFAULT, user.id, role.id, &RoleGrant::default())
+            .await,
+        Err(UserServiceError::NotFound)
+    ));
+
+    let restored_role = service
+        .restore_role(TenantId::DEFAULT, role.id)
+        .await
+        .unwrap();
+    assert!(restored_role.updated_at > role.updated_at);
+    let restored = service
+        .restore_user(TenantId::DEFAULT, user.id)
+        .await
+        .unwrap();
+    assert_eq!(restored.roles, vec![restored_role]);
+}
+
+#[tokio::test]
+async fn test_purge_removes_soft_deleted_user() {
+    let service = create_service(&InMemoryStore::new()).with_soft_delete(true);
+    let user = service
+        .create_user(TenantId::DEFAULT, "kc-1")
+        .await
+        .unwrap();
+
+    service
+        .delete_user(TenantId::DEFAULT, user.id, None)
+        .await
+        .unwrap();
+    service
+        .purge_user(TenantId::DEFAULT, user.id)
+        .await
+        .unwrap();
+    assert!(matches!(
+        service.restore_user(TenantId::DEFAULT, user.id).await,
+        Err(UserServiceError::NotFound)
+    ));
+}
+
+#[tokio::test]
+async fn test_deleting_a_missing_role_is_not_found() {
+    let service = create_service(&InMemoryStore::new()).with_soft_delete(true);
+    let role = service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+
+    service
+        .delete_role(TenantId::DEFAULT, role.id, None)
+        .await
+        .unwrap();
+    // Already deleted, with or without a version
+    assert!(matches!(
+        service.delete_role(TenantId::DEFAULT, role.id, None).await,
+        Err(UserServiceError::NotFound)
+    ));
+    assert!(matches!(
+        service
+            .delete_role(TenantId::DEFAULT, role.id, Some(role.version))
+            .await,
+        Err(UserServiceError::NotFound)
+    ));
+}
+
+#[tokio::test]
+async fn test_deletes_cascade_to_assignments() {
+    let store = InMemoryStore::new();
+    let service = create_service(&store);
+    let alice = service
+        .create_user(TenantId::DEFAULT, "kc-alice")
+        .await
+        .unwrap();
+    let bob = service
+        .create_user(TenantId::DEFAULT, "kc-bob")
+        .await
+        .unwrap();
+    let editor = service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+    let viewer = service
+        .create_role(TenantId::DEFAULT, "viewer", None)
+        .await
+        .unwrap();
+
+    service
+        .assign_role(
+            TenantId::DEFAULT,
+            alice.id,
+            editor.id,
+            &RoleGrant::default(),
+        )
+        .await
+        .unwrap();
+    service
+        .assign_role(TenantId::DEFAULT, bob.id, editor.id, &RoleGrant::default())
+        .await
+        .unwrap();
+    service
+        .assign_role(TenantId::DEFAULT, bob.id, viewer.id, &RoleGrant::default())
+        .await
+        .unwrap();
+
+    service
+        .delete_role(TenantId::DEFAULT, editor.id, None)
+        .await
+        .unwrap();
+    let bob_roles = service
+        .get_roles_for_user(TenantId::DEFAULT, bob.id)
+        .await
+        .unwrap();
+    assert_eq!(bob_roles.len(), 1);
+    assert_eq!(bob_roles[0].name, "viewer");
+
+    service
+        .delete_user(TenantId::DEFAULT, bob.id, None)
+        .await
+        .unwrap();
+    let viewers = service
+        .get_users_by_role(TenantId::DEFAULT, viewer.id, PaginationParams::default())
+        .await
+        .unwrap();
+    assert_eq!(viewers.total, 0);
+}
+
+#[tokio::test]
+async fn test_users_are_paginated() {
+    let service = create_service(&InMemoryStore::new());
+    for i in 0..5 {
+        service
+            .create_user(TenantId::DEFAULT, &format!("kc-{i}"))
+            .await
+            .unwrap();
+    }
+
+    let page = service
+        .get_users(
+            TenantId::DEFAULT,
+            PaginationParams::new(Some(2), Some(2)),
+            &UserFilter::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(page.total, 5);
+    assert_eq!(page.total_pages, 3);
+    assert_eq!(page.items.len(), 2);
+
+    let last = service
+        .get_users(
+            TenantId::DEFAULT,
+            PaginationParams::new(Some(3), Some(2)),
+            &UserFilter::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(last.items.len(), 1);
+}
+
+#[tokio::test]
+async fn test_create_user_with_roles_rolls_back_on_duplicate_assignment() {
+    let service = create_service(&InMemoryStore::new());
+    let editor = service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+
+    let result = service
+        .create_user_with_roles(TenantId::DEFAULT, "kc-1", &[editor.id, editor.id])
+        .await;
+    assert!(matches!(result, Err(UserServiceError::UserAlreadyHasRole)));
+
+    let users = service
+        .get_users(
+            TenantId::DEFAULT,
+            PaginationParams::default(),
+            &UserFilter::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(users.total, 0);
+}
+
+#[tokio::test]
+async fn test_atomic_operations_require_a_unit_of_work() {
+    let (users, roles, user_roles) = InMemoryStore::new().repositories();
+    let service: InMemoryService =
+        UserService::with_repos(Arc::new(users), Arc::new(roles), Arc::new(user_roles));
+
+    assert!(matches!(
+        service
+            .create_user_with_roles(TenantId::DEFAULT, "kc-1", &[])
+            .await,
+        Err(UserServiceError::Internal(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_commit_keeps_writes_made_while_the_unit_of_work_ran() {
+    let store = InMemoryStore::new();
+    let service = create_service(&store);
+    let alice = service
+        .create_user(TenantId::DEFAULT, "kc-alice")
+        .await
+        .unwrap();
+
+    let work = store.begin().await.unwrap();
+    work.users()
+        .create_user(TenantId::DEFAULT, "kc-bob")
+        .await
+        .unwrap();
+    // Written outside the unit of work before it commits
+    let carol = service
+        .create_user(TenantId::DEFAULT, "kc-carol")
+        .await
+        .unwrap();
+    service
+        .delete_user(TenantId::DEFAULT, alice.id, None)
+        .await
+        .unwrap();
+    work.commit().await.unwrap();
+
+    let users = service
+        .get_users(
+            TenantId::DEFAULT,
+            PaginationParams::default(),
+            &UserFilter::default(),
+        )
+        .await
+        .unwrap();
+    let mut keycloak_ids: Vec<_> = users.items.iter().map(|u| u.keycloak_id.as_str()).collect();
+    keycloak_ids.sort_unstable();
+    assert_eq!(keycloak_ids, vec!["kc-bob", "kc-carol"]);
+    assert!(service
+        .get_user(TenantId::DEFAULT, carol.id)
+        .await
+        .unwrap()
+        .is_some());
+}
+
+#[tokio::test]
+async fn test_update_role_checks_version_before_name() {
+    let service = create_service(&InMemoryStore::new());
+    service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+    let viewer = service
+        .create_role(TenantId::DEFAULT, "viewer", None)
+        .await
+        .unwrap();
+
+    // Like the SQL backends, a stale version wins over the unique name check
+    assert!(matches!(
+        service
+            .update_role(TenantId::DEFAULT, viewer.id, "editor", None, Some(0))
+            .await,
+        Err(UserServiceError::VersionConflict)
+    ));
+    let renamed = service
+        .update_role(
+            TenantId::DEFAULT,
+            viewer.id,
+            "reader",
+            None,
+            Some(viewer.version),
+        )
+        .await
+        .unwrap();
+    assert_eq!(renamed.version, viewer.version + 1);
+}
+
+#[tokio::test]
+async fn test_idempotency_release_keeps_completed_keys() {
+    let repo = InMemoryIdempotencyRepository::new();
+    let expires_at = chrono::Utc::now() + chrono::Duration::hours(1);
+
+    assert!(repo
+        .reserve_idempotency_key("pending", "fp", expires_at)
+        .await
+        .unwrap());
+    assert!(repo
+        .reserve_idempotency_key("done", "fp", expires_at)
+        .await
+        .unwrap());
+    repo.complete_idempotency_key("done", 201, "{}", "{}", expires_at)
+        .await
+        .unwrap();
+
+    repo.release_idempotency_key("pending").await.unwrap();
+    repo.release_idempotency_key("done").await.unwrap();
+
+    assert!(repo.get_idempotency_key("pending").await.unwrap().is_none());
+    assert!(repo.get_idempotency_key("done").await.unwrap().is_some());
+    assert!(!repo
+        .reserve_idempotency_key("done", "other", expires_at)
+        .await
+        .unwrap());
+}
+
+#[tokio::test]
+async fn test_invalid_user_attributes_are_rejected_without_a_version_bump() {
+    let service = create_service(&InMemoryStore::new());
+    let user = service
+        .create_user(TenantId::DEFAULT, "kc-1")
+        .await
+        .unwrap();
+
+    let invalid = [
+        UserAttributesPatch {
+            department: Some(Some("  ".to_string())),
+            ..Default::default()
+        },
+        UserAttributesPatch {
+            cost_center: Some(Some("CC 100".to_string())),
+            ..Default::default()
+        },
+        UserAttributesPatch {
+            locale: Some(Some("english".to_string())),
+            ..Default::default()
+        },
+        UserAttributesPatch {
+            metadata: Some(
+                serde_json::json!({"blob": "x".repeat(20_000)})
+                    .as_object()
+                    .cloned(),
+            ),
+            ..Default::default()
+        },
+    ];
+    for patch in &invalid {
+        assert!(matches!(
+            service
+                .update_user_attributes(TenantId::DEFAULT, user.id, patch, None)
+                .await,
+            Err(UserServiceError::Validation(_))
+        ));
+    }
+
+    let stale = UserAttributesPatch {
+        department: Some(Some("Sales".to_string())),
+        ..Default::default()
+    };
+    assert!(matches!(
+        service
+            .update_user_attributes(TenantId::DEFAULT, user.id, &stale, Some(user.version + 1))
+            .await,
+        Err(UserServiceError::VersionConflict)
+    ));
+
+    let unchanged = service
+        .get_user(TenantId::DEFAULT, user.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(unchanged.version, user.version);
+    assert_eq!(unchanged.attributes, Default::default());
+}
+
+#[tokio::test]
+async fn test_system_roles_cannot_be_purged() {
+    let service = create_service(&InMemoryStore::with_default_roles());
+    let roles = service
+        .get_roles(TenantId::DEFAULT, PaginationParams::default())
+        .await
+        .unwrap();
+    let user_role = roles.items.iter().find(|r| r.name == "user").unwrap();
+
+    assert!(matches!(
+        service.purge_role(TenantId::DEFAULT, user_role.id).await,
+        Err(UserServiceError::SystemRole)
+    ));
+    assert!(service
+        .get_role(TenantId::DEFAULT, user_role.id)
+        .await
+        .unwrap()
+        .is_some());
+}
+
+#[tokio::test]
+async fn test_tenants_do_not_see_each_other() {
+    let service = create_service(&InMemoryStore::with_default_roles());
+    let acme = service.create_tenant("acme", "Acme Corp").await.unwrap().id;
+    let user = service
+        .create_user(TenantId::DEFAULT, "kc-default")
+        .await
+        .unwrap();
+    let role = service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+
+    assert!(service.get_user(acme, user.id).await.unwrap().is_none());
+    assert!(service.get_role(acme, role.id).await.unwrap().is_none());
+    assert!(matches!(
+        service
+            .assign_role(acme, user.id, role.id, &RoleGrant::default())
+            .await,
+        Err(UserServiceError::NotFound)
+    ));
+    service.delete_user(acme, user.id, None).await.unwrap();
+    assert!(service
+        .get_user(TenantId::DEFAULT, user.id)
+        .await
+        .unwrap()
+        .is_some());
+    let page = service
+        .get_users(acme, PaginationParams::default(), &UserFilter::default())
+        .await
+        .unwrap();
+    assert_eq!(page.total, 0);
+
+    // Role names only need to be unique within a tenant, and each tenant has its own system roles
+    service.create_role(acme, "editor", None).await.unwrap();
+    let roles = service
+        .get_roles(acme, PaginationParams::default())
+        .await
+        .unwrap();
+    let names: Vec<_> = roles.items.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, vec!["admin", "editor", "user"]);
+    assert!(roles.items.iter().all(|r| r.id != role.id));
+}
+
+#[tokio::test]
+async fn test_tenant_slugs_are_unique_and_validated() {
+    let service = create_service(&InMemoryStore::with_default_roles());
+    service.create_tenant("acme", "Acme Corp").await.unwrap();
+
+    assert!(matches!(
+        service.create_tenant("acme", "Other").await,
+        Err(UserServiceError::TenantSlugAlreadyExists)
+    ));
+    assert!(matches!(
+        service.create_tenant("Not A Slug", "Other").await,
+        Err(UserServiceError::Validation(_))
+    ));
+
+    let slugs: Vec<_> = service
+        .get_tenants()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|t| t.slug)
+        .collect();
+    assert_eq!(slugs, vec!["acme", "default"]);
+}
+
+#[tokio::test]
+async fn test_group_members_inherit_group_roles() {
+    let service = create_service(&InMemoryStore::with_default_roles());
+    let tenant = TenantId::DEFAULT;
+    let user = service.create_user(tenant, "kc-member").await.unwrap();
+    let editor = service.create_role(tenant, "editor", None).await.unwrap();
+    let group = service
+        .create_group(tenant, "kc-group", "Editors")
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        service.create_group(tenant, "kc-other", "editors").await,
+        Err(UserServiceError::GroupNameAlreadyExists)
+    ));
+
+    service
+        .assign_group_role(tenant, group.id, editor.id)
+        .await
+        .unwrap();
+    service
+        .add_group_member(tenant, group.id, user.id)
+        .await
+        .unwrap();
+    // Adding a member twice is a no-op
+    service
+        .add_group_member(tenant, group.id, user.id)
+        .await
+        .unwrap();
+
+    let roles = service.get_roles_for_user(tenant, user.id).await.unwrap();
+    assert_eq!(roles.len(), 1);
+    assert_eq!(roles[0].id, editor.id);
+
+    // A role held directly and through the group is listed once
+    service
+        .assign_role(tenant, user.id, editor.id, &RoleGrant::default())
+        .await
+        .unwrap();
+    let user_roles = service
+        .get_user(tenant, user.id)
+        .await
+        .unwrap()
+        .unwrap()
+        .roles;
+    assert_eq!(user_roles.len(), 1);
+    service
+        .unassign_role(tenant, user.id, editor.id)
+        .await
+        .unwrap();
+
+    let members = service
+        .get_group_members(tenant, group.id, PaginationParams::default())
+        .await
+        .unwrap();
+    assert_eq!(members.total, 1);
+    assert_eq!(members.items[0].id, user.id);
+
+    // Deleting the group takes the inherited role away
+    service.delete_group(tenant, group.id).await.unwrap();
+    assert!(service
+        .get_roles_for_user(tenant, user.id)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(matches!(
+        service.delete_group(tenant, group.id).await,
+        Err(UserServiceError::NotFound)
+    ));
+}
+
+#[tokio::test]
+async fn test_suspended_users_are_filtered_by_status() {
+    let service = create_service(&InMemoryStore::new());
+    let tenant = TenantId::DEFAULT;
+    let alice = service.create_user(tenant, "kc-alice").await.unwrap();
+    service.create_user(tenant, "kc-bob").await.unwrap();
+
+    let suspended = service
+        .suspend_user(tenant, alice.id, Some("fraud review"), None)
+        .await
+        .unwrap();
+    assert_eq!(
+        suspended.suspension.unwrap().reason.as_deref(),
+        Some("fraud review")
+    );
+
+    let filter = UserFilter {
+        status: Some(UserStatus::Suspended),
+        ..Default::default()
+    };
+    let page = service
+        .get_users(tenant, PaginationParams::default(), &filter)
+        .await
+        .unwrap();
+    assert_eq!(page.total, 1);
+    assert_eq!(page.items[0].id, alice.id);
+
+    service
+        .reactivate_user(tenant, alice.id, None)
+        .await
+        .unwrap();
+    let page = service
+        .get_users(tenant, PaginationParams::default(), &filter)
+        .await
+        .unwrap();
+    assert_eq!(page.total, 0);
+
+    assert!(matches!(
+        service
+            .suspend_user(tenant, Uuid::new_v4(), None, None)
+            .await,
+        Err(UserServiceError::NotFound)
+    ));
+}
+
+#[tokio::test]
+async fn test_latest_login_is_kept_without_a_version_bump() {
+    let service = create_service(&InMemoryStore::new());
+    let tenant = TenantId::DEFAULT;
+    let user = service.create_user(tenant, "kc-login").await.unwrap();
+    let latest = chrono::Utc::now();
+
+    service
+        .record_user_login(tenant, "kc-login", latest)
+        .await
+        .unwrap();
+    service
+        .record_user_login(tenant, "kc-login", latest - chrono::Duration::hours(1))
+        .await
+        .unwrap();
+
+    let reloaded = service.get_user(tenant, user.id).await.unwrap().unwrap();
+    assert_eq!(reloaded.last_login_at, Some(latest));
+    assert_eq!(reloaded.version, user.version);
+
+    // Other tenants' users are not touched
+    let other = TenantId(Uuid::new_v4());
+    service
+        .record_user_login(other, "kc-login", latest + chrono::Duration::hours(1))
+        .await
+        .unwrap();
+    let reloaded = service.get_user(tenant, user.id).await.unwrap().unwrap();
+    assert_eq!(reloaded.last_login_at, Some(latest));
+}
+
+#[tokio::test]
+async fn test_event_checkpoints_are_kept_per_tenant_and_source() {
+    let repo = InMemoryEventCheckpointRepository::new();
+    let other = TenantId(Uuid::new_v4());
+
+    repo.save_event_checkpoint(TenantId::DEFAULT, "events", 10)
+        .await
+        .unwrap();
+    repo.save_event_checkpoint(TenantId::DEFAULT, "events", 20)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        repo.get_event_checkpoint(TenantId::DEFAULT, "events")
+            .await
+            .unwrap(),
+        Some(20)
+    );
+    assert_eq!(
+        repo.get_event_checkpoint(TenantId::DEFAULT, "admin-events")
+            .await
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        repo.get_event_checkpoint(other, "events").await.unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn test_api_keys_are_found_by_hash_until_rotated_or_revoked() {
+    let service = create_service(&InMemoryStore::new());
+    let tenant = TenantId::DEFAULT;
+    let new_key = NewApiKey {
+        name: " nightly-sync ".to_string(),
+        scopes: vec![
+            "users:read".to_string(),
+            "roles:read".to_string(),
+            "users:read".to_string(),
+        ],
+        expires_at: None,
+    };
+
+    let key = service
+        .create_api_key(tenant, &new_key, "uak_aaaa", "hash-1")
+        .await
+        .unwrap();
+    assert_eq!(key.name, "nightly-sync");
+    assert_eq!(key.scopes, vec!["users:read", "roles:read"]);
+    assert_eq!(key.tenant, tenant);
+    assert!(key.is_active_at(chrono::Utc::now()));
+
+    let found = service.get_api_key_by_hash("hash-1").await.unwrap();
+    assert_eq!(found.map(|k| k.id), Some(key.id));
+
+    let rotated = service
+        .rotate_api_key(tenant, key.id, "uak_bbbb", "hash-2")
+        .await
+        .unwrap();
+    assert_eq!(rotated.prefix, "uak_bbbb");
+    assert!(rotated.rotated_at.is_some());
+    assert!(service
+        .get_api_key_by_hash("hash-1")
+        .await
+        .unwrap()
+        .is_none());
+
+    // Keys of other tenants cannot be touched
+    let other = TenantId(Uuid::new_v4());
+    assert!(matches!(
+        service.revoke_api_key(other, key.id).await,
+        Err(UserServiceError::NotFound)
+    ));
+
+    service.revoke_api_key(tenant, key.id).await.unwrap();
+    let revoked = service
+        .get_api_key_by_hash("hash-2")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!revoked.is_active_at(chrono::Utc::now()));
+    assert!(matches!(
+        service.revoke_api_key(tenant, key.id).await,
+        Err(UserServiceError::NotFound)
+    ));
+    assert!(matches!(
+        service
+            .rotate_api_key(tenant, key.id, "uak_cccc", "hash-3")
+            .await,
+        Err(UserServiceError::NotFound)
+    ));
+}
+
+#[tokio::test]
+async fn test_api_key_needs_a_name_scopes_and_a_future_expiry() {
+    let service = create_service(&InMemoryStore::new());
+    let valid = NewApiKey {
+        name: "batch".to_string(),
+        scopes: vec!["users:read".to_string()],
+        expires_at: None,
+    };
+    let invalid = [
+        NewApiKey {
+            name: "  ".to_string(),
+            ..valid.clone()
+        },
+        NewApiKey {
+            scopes: vec![],
+            ..valid.clone()
+        },
+        NewApiKey {
+            scopes: vec!["users:read roles:write".to_string()],
+            ..valid.clone()
+        },
+        NewApiKey {
+            expires_at: Some(chrono::Utc::now() - chrono::Duration::minutes(1)),
+            ..valid.clone()
+        },
+    ];
+
+    for new_key in &invalid {
+        assert!(matches!(
+            service
+                .create_api_key(TenantId::DEFAULT, new_key, "uak_x", "hash-x")
+                .await,
+            Err(UserServiceError::Validation(_))
+        ));
+    }
+    assert!(service
+        .get_api_keys(TenantId::DEFAULT)
+        .await
+        .unwrap()
+        .is_empty());
+}
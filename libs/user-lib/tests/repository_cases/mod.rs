@@ -0,0 +1,1393 @@
+//! Repository cases shared by the backend suites. Each case takes a fresh database
+//! with the backend's migrations applied, and `repository_tests!` turns every case
+//! into its own test so one failure doesn't hide the others.
+
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+
+use user_lib::entities::{
+    NewApiKey, PaginationParams, RoleGrant, TenantId, UserAttributesPatch, UserFilter, UserStatus,
+};
+use user_lib::errors_service::UserServiceError;
+use user_lib::repository::traits::{
+    RoleRepositoryTrait, UserRepositoryTrait, UserRoleRepositoryTrait,
+};
+use user_lib::rootuser::{initialize_root_user_in_transaction, RootUserConfig};
+use user_lib::user_service::UserService;
+use user_lib::util::DbPool;
+use uuid::Uuid;
+
+/// Defines a `#[tokio::test]` per case, running it against the database `$database()` opens.
+/// `$database` returns the pool and a guard that keeps its server alive until the case ends.
+macro_rules! repository_tests {
+    ($database:path) => {
+        repository_tests!(
+            $database;
+            test_default_roles_are_seeded,
+            test_unique_violations_are_mapped,
+            test_deletes_cascade_to_assignments,
+            test_users_are_paginated_with_roles,
+            test_create_user_with_roles_commits_atomically,
+            test_create_user_with_roles_rolls_back_on_missing_role,
+            test_initialize_root_user_in_transaction,
+            test_system_roles_are_found_by_tenant_and_name,
+            test_timestamps_are_set_and_bumped_on_update,
+            test_soft_deleted_rows_are_hidden_and_restorable,
+            test_deleted_role_names_can_be_reused,
+            test_assign_role_to_soft_deleted_user_is_not_found,
+            test_purge_removes_soft_deleted_rows,
+            test_stale_version_is_rejected,
+            test_delete_with_stale_version_is_refused_by_the_statement,
+            test_idempotency_key_lifecycle,
+            test_expired_idempotency_key_can_be_reserved_again,
+            test_user_attributes_are_patched_and_filterable,
+            test_seeded_roles_are_protected_system_roles,
+            test_role_description_is_kept_or_cleared,
+            test_expired_role_grants_are_ignored_and_swept,
+            test_tenants_are_isolated,
+            test_groups_grant_roles_to_their_members,
+            test_purges_cascade_to_group_rows,
+            test_groups_only_take_members_and_roles_of_their_tenant,
+            test_suspension_is_recorded_and_filterable,
+            test_logins_and_event_checkpoints_are_recorded,
+            test_api_keys_are_stored_by_hash,
+        );
+    };
+    ($database:path; $($case:ident),* $(,)?) => {
+        $(
+            #[tokio::test]
+            async fn $case() {
+                let (db, _server) = $database().await;
+                repository_cases::$case(db).await;
+            }
+        )*
+    };
+}
+
+type DynService = UserService<
+    Arc<dyn UserRepositoryTrait>,
+    Arc<dyn RoleRepositoryTrait>,
+    Arc<dyn UserRoleRepositoryTrait>,
+>;
+
+/// Stand-in for the SHA-256 hex digest the middleware stores in the `CHAR(64)` fingerprint column
+fn digest(fill: char) -> String {
+    fill.to_string().repeat(64)
+}
+
+fn create_service(db: &DbPool) -> DynService {
+    let (users, roles, user_roles) = db.repositories();
+
+    UserService::with_repos(Arc::new(users), Arc::new(roles), Arc::new(user_roles))
+        .with_tenant_repository(db.tenant_repository())
+        .with_group_repository(db.group_repository())
+        .with_api_key_repository(db.api_key_repository())
+        .with_unit_of_work(Arc::new(db.clone()))
+}
+
+pub async fn test_default_roles_are_seeded(db: DbPool) {
+    let service = create_service(&db);
+
+    let roles = service
+        .get_roles(TenantId::DEFAULT, PaginationParams::default())
+        .await
+        .unwrap();
+    let names: Vec<_> = roles.items.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, vec!["admin", "user"]);
+}
+
+pub async fn test_unique_violations_are_mapped(db: DbPool) {
+    let service = create_service(&db);
+    let user = service
+        .create_user(TenantId::DEFAULT, "kc-1")
+        .await
+        .unwrap();
+    let role = service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        service.create_role(TenantId::DEFAULT, "Editor", None).await,
+        Err(UserServiceError::RoleNameAlreadyExists)
+    ));
+
+    service
+        .assign_role(TenantId::DEFAULT, user.id, role.id, &RoleGrant::default())
+        .await
+        .unwrap();
+    assert!(matches!(
+        service
+            .assign_role(TenantId::DEFAULT, user.id, role.id, &RoleGrant::default())
+            .await,
+        Err(UserServiceError::UserAlreadyHasRole)
+    ));
+}
+
+pub async fn test_deletes_cascade_to_assignments(db: DbPool) {
+    let service = create_service(&db);
+    let alice = service
+        .create_user(TenantId::DEFAULT, "kc-alice")
+        .await
+        .unwrap();
+    let bob = service
+        .create_user(TenantId::DEFAULT, "kc-bob")
+        .await
+        .unwrap();
+    let editor = service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+    let viewer = service
+        .create_role(TenantId::DEFAULT, "viewer", None)
+        .await
+        .unwrap();
+
+    service
+        .assign_role(
+            TenantId::DEFAULT,
+            alice.id,
+            editor.id,
+            &RoleGrant::default(),
+        )
+        .await
+        .unwrap();
+    service
+        .assign_role(TenantId::DEFAULT, bob.id, editor.id, &RoleGrant::default())
+        .await
+        .unwrap();
+    service
+        .assign_role(TenantId::DEFAULT, bob.id, viewer.id, &RoleGrant::default())
+        .await
+        .unwrap();
+
+    service
+        .delete_role(TenantId::DEFAULT, editor.id, None)
+        .await
+        .unwrap();
+    let bob_roles = service
+        .get_roles_for_user(TenantId::DEFAULT, bob.id)
+        .await
+        .unwrap();
+    assert_eq!(bob_roles.len(), 1);
+    assert_eq!(bob_roles[0].name, "viewer");
+
+    service
+        .delete_user(TenantId::DEFAULT, bob.id, None)
+        .await
+        .unwrap();
+    let viewers = service
+        .get_users_by_role(TenantId::DEFAULT, viewer.id, PaginationParams::default())
+        .await
+        .unwrap();
+    assert_eq!(viewers.total, 0);
+}
+
+pub async fn test_users_are_paginated_with_roles(db: DbPool) {
+    let service = create_service(&db);
+    let role = service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+    for i in 0..5 {
+        let user = service
+            .create_user(TenantId::DEFAULT, &format!("kc-{i}"))
+            .await
+            .unwrap();
+        service
+            .assign_role(TenantId::DEFAULT, user.id, role.id, &RoleGrant::default())
+            .await
+            .unwrap();
+    }
+
+    let page = service
+        .get_users(
+            TenantId::DEFAULT,
+            PaginationParams::new(Some(2), Some(2)),
+            &UserFilter::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(page.total, 5);
+    assert_eq!(page.items.len(), 2);
+    assert!(page.items.iter().all(|u| u.roles.len() == 1));
+}
+
+pub async fn test_create_user_with_roles_commits_atomically(db: DbPool) {
+    let service = create_service(&db);
+    let editor = service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+    let viewer = service
+        .create_role(TenantId::DEFAULT, "viewer", None)
+        .await
+        .unwrap();
+
+    let user = service
+        .create_user_with_roles(TenantId::DEFAULT, "kc-1", &[editor.id, viewer.id])
+        .await
+        .unwrap();
+    assert_eq!(user.roles.len(), 2);
+    assert_eq!(
+        service
+            .get_roles_for_user(TenantId::DEFAULT, user.id)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+}
+
+pub async fn test_create_user_with_roles_rolls_back_on_missing_role(db: DbPool) {
+    let service = create_service(&db);
+    let editor = service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+
+    let result = service
+        .create_user_with_roles(TenantId::DEFAULT, "kc-1", &[editor.id, Uuid::new_v4()])
+        .await;
+    assert!(matches!(result, Err(UserServiceError::NotFound)));
+    assert!(service
+        .get_user_by_keycloak_id(TenantId::DEFAULT, "kc-1")
+        .await
+        .unwrap()
+        .is_none());
+}
+
+pub async fn test_initialize_root_user_in_transaction(db: DbPool) {
+    let config = RootUserConfig {
+        email: "root@example.com".to_string(),
+        first_name: "Root".to_string(),
+        last_name: "User".to_string(),
+        keycloak_id: "kc-root".to_string(),
+    };
+
+    let user = initialize_root_user_in_transaction(&db, &config)
+        .await
+        .unwrap();
+    assert_eq!(user.roles.len(), 1);
+    assert_eq!(user.roles[0].name, "admin");
+
+    // Running it again finds the committed user instead of creating another
+    let again = initialize_root_user_in_transaction(&db, &config)
+        .await
+        .unwrap();
+    assert_eq!(again.id, user.id);
+}
+
+pub async fn test_system_roles_are_found_by_tenant_and_name(db: DbPool) {
+    let (_, roles, _) = db.repositories();
+    let editor = roles
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+
+    let admin = roles
+        .get_system_role(TenantId::DEFAULT, "admin")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(admin.is_system);
+    // Names compare like the role name index does
+    let upper = roles
+        .get_system_role(TenantId::DEFAULT, "ADMIN")
+        .await
+        .unwrap();
+    assert_eq!(upper.map(|r| r.id), Some(admin.id));
+    assert!(roles
+        .get_system_role(TenantId::DEFAULT, &editor.name)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(roles
+        .get_system_role(TenantId::from(Uuid::new_v4()), "admin")
+        .await
+        .unwrap()
+        .is_none());
+}
+
+pub async fn test_timestamps_are_set_and_bumped_on_update(db: DbPool) {
+    let service = create_service(&db);
+    let role = service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+    assert_eq!(role.created_at, role.updated_at);
+
+    let renamed = service
+        .update_role(TenantId::DEFAULT, role.id, "writer", None, None)
+        .await
+        .unwrap();
+    assert_eq!(renamed.created_at, role.created_at);
+    assert!(renamed.updated_at > role.updated_at);
+
+    // Seeded roles got their timestamps from the migration backfill
+    let seeded = service
+        .get_roles(TenantId::DEFAULT, PaginationParams::default())
+        .await
+        .unwrap();
+    assert!(seeded.items.iter().all(|r| r.created_at.timestamp() > 0));
+}
+
+pub async fn test_soft_deleted_rows_are_hidden_and_restorable(db: DbPool) {
+    let service = create_service(&db).with_soft_delete(true);
+    let user = service
+        .create_user(TenantId::DEFAULT, "kc-1")
+        .await
+        .unwrap();
+    let role = service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+    service
+        .assign_role(TenantId::DEFAULT, user.id, role.id, &RoleGrant::default())
+        .await
+        .unwrap();
+
+    service
+        .delete_role(TenantId::DEFAULT, role.id, None)
+        .await
+        .unwrap();
+    assert!(service
+        .get_role(TenantId::DEFAULT, role.id)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(service
+        .get_roles_for_user(TenantId::DEFAULT, user.id)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(matches!(
+        service
+            .assign_role(TenantId::DEFAULT, user.id, role.id, &RoleGrant::default())
+            .await,
+        Err(UserServiceError::NotFound)
+    ));
+
+    service
+        .delete_user(TenantId::DEFAULT, user.id, None)
+        .await
+        .unwrap();
+    assert!(service
+        .get_user(TenantId::DEFAULT, user.id)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(service
+        .get_user_by_keycloak_id(TenantId::DEFAULT, "kc-1")
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        service
+            .get_users(
+                TenantId::DEFAULT,
+                PaginationParams::default(),
+                &UserFilter::default()
+            )
+            .await
+            .unwrap()
+            .total,
+        0
+    );
+
+    // Restoring brings back the assignment the rows had before deletion
+    service
+        .restore_role(TenantId::DEFAULT, role.id)
+        .await
+        .unwrap();
+    let restored = service
+        .restore_user(TenantId::DEFAULT, user.id)
+        .await
+        .unwrap();
+    assert_eq!(restored.roles.len(), 1);
+    assert_eq!(restored.roles[0].id, role.id);
+
+    assert!(matches!(
+        service.restore_user(TenantId::DEFAULT, user.id).await,
+        Err(UserServiceError::NotFound)
+    ));
+}
+
+pub async fn test_deleted_role_names_can_be_reused(db: DbPool) {
+    let service = create_service(&db).with_soft_delete(true);
+    let old = service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+    service
+        .delete_role(TenantId::DEFAULT, old.id, None)
+        .await
+        .unwrap();
+
+    let new = service
+        .create_role(TenantId::DEFAULT, "Editor", None)
+        .await
+        .unwrap();
+    // Two live roles can't share the name, so the old one stays deleted
+    assert!(matches!(
+        service.restore_role(TenantId::DEFAULT, old.id).await,
+        Err(UserServiceError::RoleNameAlreadyExists)
+    ));
+
+    service.purge_role(TenantId::DEFAULT, new.id).await.unwrap();
+    service
+        .restore_role(TenantId::DEFAULT, old.id)
+        .await
+        .unwrap();
+}
+
+pub async fn test_assign_role_to_soft_deleted_user_is_not_found(db: DbPool) {
+    let service = create_service(&db).with_soft_delete(true);
+    let user = service
+        .create_user(TenantId::DEFAULT, "kc-1")
+        .await
+        .unwrap();
+    let role = service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+
+    service
+        .delete_user(TenantId::DEFAULT, user.id, None)
+        .await
+        .unwrap();
+    assert!(matches!(
+        service
+            .assign_role(TenantId::DEFAULT, user.id, role.id, &RoleGrant::default())
+            .await,
+        Err(UserServiceError::NotFound)
+    ));
+}
+
+pub async fn test_purge_removes_soft_deleted_rows(db: DbPool) {
+    let service = create_service(&db).with_soft_delete(true);
+    let user = service
+        .create_user(TenantId::DEFAULT, "kc-1")
+        .await
+        .unwrap();
+    let role = service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+
+    service
+        .delete_user(TenantId::DEFAULT, user.id, None)
+        .await
+        .unwrap();
+    service
+        .delete_role(TenantId::DEFAULT, role.id, None)
+        .await
+        .unwrap();
+    service
+        .purge_user(TenantId::DEFAULT, user.id)
+        .await
+        .unwrap();
+    service
+        .purge_role(TenantId::DEFAULT, role.id)
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        service.restore_user(TenantId::DEFAULT, user.id).await,
+        Err(UserServiceError::NotFound)
+    ));
+    assert!(matches!(
+        service.restore_role(TenantId::DEFAULT, role.id).await,
+        Err(UserServiceError::NotFound)
+    ));
+    // The unique keycloak_id and role name are free again
+    service
+        .create_user(TenantId::DEFAULT, "kc-1")
+        .await
+        .unwrap();
+    service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+}
+
+pub async fn test_stale_version_is_rejected(db: DbPool) {
+    let service = create_service(&db);
+    let role = service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+    assert_eq!(role.version, 1);
+
+    let renamed = service
+        .update_role(
+            TenantId::DEFAULT,
+            role.id,
+            "writer",
+            None,
+            Some(role.version),
+        )
+        .await
+        .unwrap();
+    assert_eq!(renamed.version, 2);
+
+    // A second writer still holding version 1 loses
+    assert!(matches!(
+        service
+            .update_role(
+                TenantId::DEFAULT,
+                role.id,
+                "author",
+                None,
+                Some(role.version)
+            )
+            .await,
+        Err(UserServiceError::VersionConflict)
+    ));
+    assert!(matches!(
+        service
+            .delete_role(TenantId::DEFAULT, role.id, Some(role.version))
+            .await,
+        Err(UserServiceError::VersionConflict)
+    ));
+    service
+        .delete_role(TenantId::DEFAULT, role.id, Some(renamed.version))
+        .await
+        .unwrap();
+
+    let user = service
+        .create_user(TenantId::DEFAULT, "kc-1")
+        .await
+        .unwrap();
+    let touched = service
+        .touch_user(TenantId::DEFAULT, user.id, Some(user.version))
+        .await
+        .unwrap();
+    assert_eq!(touched.version, user.version + 1);
+    assert!(matches!(
+        service
+            .touch_user(TenantId::DEFAULT, user.id, Some(user.version))
+            .await,
+        Err(UserServiceError::VersionConflict)
+    ));
+}
+
+pub async fn test_delete_with_stale_version_is_refused_by_the_statement(db: DbPool) {
+    let service = create_service(&db).with_soft_delete(true);
+    let user = service
+        .create_user(TenantId::DEFAULT, "kc-1")
+        .await
+        .unwrap();
+    // Another writer moves the user on after our read
+    let touched = service
+        .touch_user(TenantId::DEFAULT, user.id, None)
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        service
+            .delete_user(TenantId::DEFAULT, user.id, Some(user.version))
+            .await,
+        Err(UserServiceError::VersionConflict)
+    ));
+    assert!(service
+        .get_user(TenantId::DEFAULT, user.id)
+        .await
+        .unwrap()
+        .is_some());
+    service
+        .delete_user(TenantId::DEFAULT, user.id, Some(touched.version))
+        .await
+        .unwrap();
+    assert!(matches!(
+        service
+            .delete_user(TenantId::DEFAULT, user.id, Some(touched.version))
+            .await,
+        Err(UserServiceError::NotFound)
+    ));
+
+    let service = service.with_soft_delete(false);
+    let role = service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+    assert!(matches!(
+        service
+            .delete_role(TenantId::DEFAULT, role.id, Some(role.version + 1))
+            .await,
+        Err(UserServiceError::VersionConflict)
+    ));
+    service
+        .delete_role(TenantId::DEFAULT, role.id, Some(role.version))
+        .await
+        .unwrap();
+    assert!(matches!(
+        service
+            .delete_role(TenantId::DEFAULT, role.id, Some(role.version))
+            .await,
+        Err(UserServiceError::NotFound)
+    ));
+}
+
+pub async fn test_idempotency_key_lifecycle(db: DbPool) {
+    let repo = db.idempotency_repository();
+    let fp = digest('f');
+    let expires_at = chrono::Utc::now() + chrono::Duration::hours(1);
+
+    assert!(repo
+        .reserve_idempotency_key("key-1", &fp, expires_at)
+        .await
+        .unwrap());
+    assert!(!repo
+        .reserve_idempotency_key("key-1", &fp, expires_at)
+        .await
+        .unwrap());
+
+    let pending = repo.get_idempotency_key("key-1").await.unwrap().unwrap();
+    assert_eq!(pending.fingerprint, fp);
+    assert_eq!(pending.status_code, None);
+
+    let headers = r#"{"location":"/users/1"}"#;
+    repo.complete_idempotency_key("key-1", 201, "{}", headers, expires_at)
+        .await
+        .unwrap();
+    // Completed records survive a release
+    repo.release_idempotency_key("key-1").await.unwrap();
+    let done = repo.get_idempotency_key("key-1").await.unwrap().unwrap();
+    assert_eq!(
+        (
+            done.status_code,
+            done.response_body.as_deref(),
+            done.response_headers.as_deref()
+        ),
+        (Some(201), Some("{}"), Some(headers))
+    );
+}
+
+pub async fn test_expired_idempotency_key_can_be_reserved_again(db: DbPool) {
+    let repo = db.idempotency_repository();
+    let expired = chrono::Utc::now() - chrono::Duration::seconds(1);
+
+    assert!(repo
+        .reserve_idempotency_key("key-1", &digest('0'), expired)
+        .await
+        .unwrap());
+    assert!(repo.get_idempotency_key("key-1").await.unwrap().is_none());
+
+    let expires_at = chrono::Utc::now() + chrono::Duration::hours(1);
+    assert!(repo
+        .reserve_idempotency_key("key-1", &digest('1'), expires_at)
+        .await
+        .unwrap());
+    let record = repo.get_idempotency_key("key-1").await.unwrap().unwrap();
+    assert_eq!(record.fingerprint, digest('1'));
+}
+
+pub async fn test_user_attributes_are_patched_and_filterable(db: DbPool) {
+    let service = create_service(&db);
+    let alice = service
+        .create_user(TenantId::DEFAULT, "kc-alice")
+        .await
+        .unwrap();
+    service
+        .create_user(TenantId::DEFAULT, "kc-bob")
+        .await
+        .unwrap();
+
+    let metadata = serde_json::json!({"badge": 42, "tags": ["oncall"]});
+    let patch = UserAttributesPatch {
+        department: Some(Some(" Engineering ".to_string())),
+        locale: Some(Some("en-us".to_string())),
+        metadata: Some(metadata.as_object().cloned()),
+        ..Default::default()
+    };
+    let updated = service
+        .update_user_attributes(TenantId::DEFAULT, alice.id, &patch, Some(alice.version))
+        .await
+        .unwrap();
+    assert_eq!(updated.version, alice.version + 1);
+    assert_eq!(
+        updated.attributes.department.as_deref(),
+        Some("Engineering")
+    );
+    assert_eq!(updated.attributes.locale.as_deref(), Some("en-US"));
+    assert_eq!(updated.attributes.metadata, metadata.as_object().cloned());
+
+    // Omitted fields are kept, explicit None clears
+    let patch = UserAttributesPatch {
+        cost_center: Some(Some("CC-100".to_string())),
+        metadata: Some(None),
+        ..Default::default()
+    };
+    service
+        .update_user_attributes(TenantId::DEFAULT, alice.id, &patch, None)
+        .await
+        .unwrap();
+    let reloaded = service
+        .get_user(TenantId::DEFAULT, alice.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        reloaded.attributes.department.as_deref(),
+        Some("Engineering")
+    );
+    assert_eq!(reloaded.attributes.cost_center.as_deref(), Some("CC-100"));
+    assert_eq!(reloaded.attributes.metadata, None);
+
+    let filter = UserFilter {
+        department: Some("Engineering".to_string()),
+        locale: Some("EN-us".to_string()),
+        ..Default::default()
+    };
+    let page = service
+        .get_users(TenantId::DEFAULT, PaginationParams::default(), &filter)
+        .await
+        .unwrap();
+    assert_eq!(page.total, 1);
+    assert_eq!(page.items[0].id, alice.id);
+    assert_eq!(
+        page.items[0].attributes.cost_center.as_deref(),
+        Some("CC-100")
+    );
+
+    // Purging the user removes its attributes along with it
+    service
+        .purge_user(TenantId::DEFAULT, alice.id)
+        .await
+        .unwrap();
+    let page = service
+        .get_users(TenantId::DEFAULT, PaginationParams::default(), &filter)
+        .await
+        .unwrap();
+    assert_eq!(page.total, 0);
+}
+
+pub async fn test_seeded_roles_are_protected_system_roles(db: DbPool) {
+    let service = create_service(&db);
+    let roles = service
+        .get_roles(TenantId::DEFAULT, PaginationParams::default())
+        .await
+        .unwrap();
+    let admin = roles.items.iter().find(|r| r.name == "admin").unwrap();
+    assert!(admin.is_system);
+    assert_eq!(
+        admin.description.as_deref(),
+        Some("Full administrative access")
+    );
+
+    assert!(matches!(
+        service
+            .update_role(TenantId::DEFAULT, admin.id, "root", None, None)
+            .await,
+        Err(UserServiceError::SystemRole)
+    ));
+    assert!(matches!(
+        service.delete_role(TenantId::DEFAULT, admin.id, None).await,
+        Err(UserServiceError::SystemRole)
+    ));
+
+    // Keeping the name, a system role's description can still be changed
+    let updated = service
+        .update_role(
+            TenantId::DEFAULT,
+            admin.id,
+            "admin",
+            Some("Operators"),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(updated.description.as_deref(), Some("Operators"));
+    assert!(updated.is_system);
+}
+
+pub async fn test_role_description_is_kept_or_cleared(db: DbPool) {
+    let service = create_service(&db);
+    let role = service
+        .create_role(TenantId::DEFAULT, "editor", Some("  Edits content  "))
+        .await
+        .unwrap();
+    assert_eq!(role.description.as_deref(), Some("Edits content"));
+    assert!(!role.is_system);
+
+    let renamed = service
+        .update_role(TenantId::DEFAULT, role.id, "writer", None, None)
+        .await
+        .unwrap();
+    assert_eq!(renamed.description.as_deref(), Some("Edits content"));
+
+    let cleared = service
+        .update_role(TenantId::DEFAULT, role.id, "writer", Some(""), None)
+        .await
+        .unwrap();
+    assert_eq!(cleared.description, None);
+
+    service
+        .delete_role(TenantId::DEFAULT, role.id, None)
+        .await
+        .unwrap();
+}
+
+pub async fn test_expired_role_grants_are_ignored_and_swept(db: DbPool) {
+    let (users, roles, user_roles) = db.repositories();
+    let service: DynService = UserService::with_repos(
+        Arc::new(users),
+        Arc::new(roles),
+        Arc::new(user_roles.clone()),
+    );
+
+    let user = service
+        .create_user(TenantId::DEFAULT, "kc-1")
+        .await
+        .unwrap();
+    let editor = service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+    let viewer = service
+        .create_role(TenantId::DEFAULT, "viewer", None)
+        .await
+        .unwrap();
+    let temporary = RoleGrant {
+        expires_at: Some(Utc::now() + Duration::hours(1)),
+        granted_by: Some("  admin@example.com ".to_string()),
+    };
+    service
+        .assign_role(TenantId::DEFAULT, user.id, viewer.id, &temporary)
+        .await
+        .unwrap();
+
+    // The service refuses grants that are already expired, so backdate one directly
+    let expired = RoleGrant {
+        expires_at: Some(Utc::now() - Duration::minutes(1)),
+        granted_by: None,
+    };
+    assert!(matches!(
+        service
+            .assign_role(TenantId::DEFAULT, user.id, editor.id, &expired)
+            .await,
+        Err(UserServiceError::Validation(_))
+    ));
+    user_roles
+        .assign_role(
+            TenantId::DEFAULT,
+            &user.id.to_string(),
+            &editor.id.to_string(),
+            &expired,
+        )
+        .await
+        .unwrap();
+
+    let roles = service
+        .get_roles_for_user(TenantId::DEFAULT, user.id)
+        .await
+        .unwrap();
+    let names: Vec<_> = roles.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, vec!["viewer"]);
+    let page = service
+        .get_users(
+            TenantId::DEFAULT,
+            PaginationParams::default(),
+            &UserFilter::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(page.items[0].roles.len(), 1);
+    let holders = service
+        .get_users_by_role(TenantId::DEFAULT, editor.id, PaginationParams::default())
+        .await
+        .unwrap();
+    assert_eq!(holders.total, 0);
+
+    assert_eq!(
+        service
+            .get_users_with_expired_role_grants(Utc::now())
+            .await
+            .unwrap(),
+        vec![(TenantId::DEFAULT, user.id)]
+    );
+    assert_eq!(
+        service
+            .remove_expired_role_grants(Utc::now())
+            .await
+            .unwrap(),
+        vec![(TenantId::DEFAULT, user.id)]
+    );
+    assert!(service
+        .remove_expired_role_grants(Utc::now())
+        .await
+        .unwrap()
+        .is_empty());
+    // The grant row is gone, so the role can be granted again
+    service
+        .assign_role(TenantId::DEFAULT, user.id, editor.id, &RoleGrant::default())
+        .await
+        .unwrap();
+}
+
+pub async fn test_tenants_are_isolated(db: DbPool) {
+    let service = create_service(&db);
+    let acme = service.create_tenant("acme", "Acme Corp").await.unwrap().id;
+    assert_eq!(
+        service.get_tenant(acme).await.unwrap().unwrap().name,
+        "Acme Corp"
+    );
+
+    let user = service.create_user(acme, "kc-acme").await.unwrap();
+    let role = service.create_role(acme, "editor", None).await.unwrap();
+    service
+        .assign_role(acme, user.id, role.id, &RoleGrant::default())
+        .await
+        .unwrap();
+    assert_eq!(
+        service
+            .get_user(acme, user.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .roles[0]
+            .name,
+        "editor"
+    );
+
+    // Nothing from acme is visible from the default tenant
+    assert!(service
+        .get_user(TenantId::DEFAULT, user.id)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(service
+        .get_role(TenantId::DEFAULT, role.id)
+        .await
+        .unwrap()
+        .is_none());
+    service
+        .unassign_role(TenantId::DEFAULT, user.id, role.id)
+        .await
+        .unwrap();
+    assert_eq!(
+        service
+            .get_user(acme, user.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .roles
+            .len(),
+        1
+    );
+    let users = service
+        .get_users(
+            TenantId::DEFAULT,
+            PaginationParams::default(),
+            &UserFilter::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(users.total, 0);
+
+    // Same role name in another tenant; a new tenant brings its own system roles
+    service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+    let roles = service
+        .get_roles(acme, PaginationParams::default())
+        .await
+        .unwrap();
+    let names: Vec<_> = roles.items.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, vec!["admin", "editor", "user"]);
+    assert!(roles
+        .items
+        .iter()
+        .all(|r| r.is_system == (r.name != "editor")));
+}
+
+pub async fn test_groups_grant_roles_to_their_members(db: DbPool) {
+    let service = create_service(&db);
+    let tenant = TenantId::DEFAULT;
+    let alice = service.create_user(tenant, "kc-alice").await.unwrap();
+    let bob = service.create_user(tenant, "kc-bob").await.unwrap();
+    let editor = service.create_role(tenant, "editor", None).await.unwrap();
+    let group = service
+        .create_group(tenant, "kc-group", "Editors")
+        .await
+        .unwrap();
+    assert!(matches!(
+        service.create_group(tenant, "kc-other", "EDITORS").await,
+        Err(UserServiceError::GroupNameAlreadyExists)
+    ));
+
+    service
+        .assign_group_role(tenant, group.id, editor.id)
+        .await
+        .unwrap();
+    service
+        .add_group_member(tenant, group.id, alice.id)
+        .await
+        .unwrap();
+    service
+        .add_group_member(tenant, group.id, bob.id)
+        .await
+        .unwrap();
+    let fetched = service.get_group(tenant, group.id).await.unwrap().unwrap();
+    assert_eq!(fetched.roles.len(), 1);
+
+    // Inherited roles show up next to direct grants, once per role
+    service
+        .assign_role(tenant, alice.id, editor.id, &RoleGrant::default())
+        .await
+        .unwrap();
+    let users = service
+        .get_users(tenant, PaginationParams::default(), &UserFilter::default())
+        .await
+        .unwrap();
+    assert!(users
+        .items
+        .iter()
+        .filter(|u| u.id == alice.id || u.id == bob.id)
+        .all(|u| u.roles.len() == 1 && u.roles[0].id == editor.id));
+    // Granted roles leave out the inherited ones
+    let granted = service
+        .get_granted_roles_for_user(tenant, alice.id)
+        .await
+        .unwrap();
+    assert_eq!(granted.len(), 1);
+    assert_eq!(granted[0].id, editor.id);
+    assert!(service
+        .get_granted_roles_for_user(tenant, bob.id)
+        .await
+        .unwrap()
+        .is_empty());
+
+    service
+        .remove_group_member(tenant, group.id, bob.id)
+        .await
+        .unwrap();
+    assert!(service
+        .get_roles_for_user(tenant, bob.id)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let renamed = service
+        .rename_group(tenant, group.id, "Writers")
+        .await
+        .unwrap();
+    assert_eq!(renamed.name, "Writers");
+    let members = service
+        .get_group_members(tenant, group.id, PaginationParams::default())
+        .await
+        .unwrap();
+    assert_eq!(members.total, 1);
+
+    // Groups are scoped to their tenant
+    let acme = service.create_tenant("acme", "Acme Corp").await.unwrap().id;
+    assert!(service.get_group(acme, group.id).await.unwrap().is_none());
+
+    // Deleting the group removes its memberships and role attachments
+    service.delete_group(tenant, group.id).await.unwrap();
+    assert_eq!(
+        service
+            .get_roles_for_user(tenant, alice.id)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+pub async fn test_purges_cascade_to_group_rows(db: DbPool) {
+    let service = create_service(&db);
+    let tenant = TenantId::DEFAULT;
+    let alice = service.create_user(tenant, "kc-alice").await.unwrap();
+    let editor = service.create_role(tenant, "editor", None).await.unwrap();
+    let group = service
+        .create_group(tenant, "kc-group", "Editors")
+        .await
+        .unwrap();
+    service
+        .add_group_member(tenant, group.id, alice.id)
+        .await
+        .unwrap();
+    service
+        .assign_group_role(tenant, group.id, editor.id)
+        .await
+        .unwrap();
+
+    service.purge_role(tenant, editor.id).await.unwrap();
+    let fetched = service.get_group(tenant, group.id).await.unwrap().unwrap();
+    assert!(fetched.roles.is_empty());
+
+    service.purge_user(tenant, alice.id).await.unwrap();
+    let members = service
+        .get_group_members(tenant, group.id, PaginationParams::default())
+        .await
+        .unwrap();
+    assert_eq!(members.total, 0);
+    // The group itself is left alone
+    assert!(service.get_group(tenant, group.id).await.unwrap().is_some());
+}
+
+pub async fn test_groups_only_take_members_and_roles_of_their_tenant(db: DbPool) {
+    let service = create_service(&db);
+    let tenant = TenantId::DEFAULT;
+    let acme = service.create_tenant("acme", "Acme Corp").await.unwrap().id;
+    let group = service
+        .create_group(tenant, "kc-group", "Editors")
+        .await
+        .unwrap();
+    let carol = service.create_user(acme, "kc-carol").await.unwrap();
+    let auditor = service.create_role(acme, "auditor", None).await.unwrap();
+
+    assert!(matches!(
+        service.add_group_member(tenant, group.id, carol.id).await,
+        Err(UserServiceError::NotFound)
+    ));
+    assert!(matches!(
+        service.add_group_member(acme, group.id, carol.id).await,
+        Err(UserServiceError::NotFound)
+    ));
+    assert!(matches!(
+        service
+            .assign_group_role(tenant, group.id, auditor.id)
+            .await,
+        Err(UserServiceError::NotFound)
+    ));
+    assert!(service
+        .get_roles_for_user(acme, carol.id)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+pub async fn test_suspension_is_recorded_and_filterable(db: DbPool) {
+    let service = create_service(&db);
+    let tenant = TenantId::DEFAULT;
+    let alice = service.create_user(tenant, "kc-alice").await.unwrap();
+    let bob = service.create_user(tenant, "kc-bob").await.unwrap();
+
+    let suspended = service
+        .suspend_user(
+            tenant,
+            alice.id,
+            Some("left the company"),
+            Some(alice.version),
+        )
+        .await
+        .unwrap();
+    assert_eq!(suspended.version, alice.version + 1);
+    let suspension = suspended.suspension.clone().unwrap();
+    assert_eq!(suspension.reason.as_deref(), Some("left the company"));
+
+    // Suspending again replaces the reason but keeps the original time
+    let resuspended = service
+        .suspend_user(tenant, alice.id, None, None)
+        .await
+        .unwrap();
+    let again = resuspended.suspension.unwrap();
+    assert_eq!(again.suspended_at, suspension.suspended_at);
+    assert_eq!(again.reason, None);
+
+    let status = |status| UserFilter {
+        status: Some(status),
+        ..Default::default()
+    };
+    let page = service
+        .get_users(
+            tenant,
+            PaginationParams::default(),
+            &status(UserStatus::Suspended),
+        )
+        .await
+        .unwrap();
+    assert_eq!(page.total, 1);
+    assert_eq!(page.items[0].id, alice.id);
+    let page = service
+        .get_users(
+            tenant,
+            PaginationParams::default(),
+            &status(UserStatus::Active),
+        )
+        .await
+        .unwrap();
+    assert_eq!(page.total, 1);
+    assert_eq!(page.items[0].id, bob.id);
+
+    assert!(matches!(
+        service
+            .reactivate_user(tenant, alice.id, Some(suspended.version))
+            .await,
+        Err(UserServiceError::VersionConflict)
+    ));
+    let reactivated = service
+        .reactivate_user(tenant, alice.id, Some(resuspended.version))
+        .await
+        .unwrap();
+    assert_eq!(reactivated.suspension, None);
+    let page = service
+        .get_users(
+            tenant,
+            PaginationParams::default(),
+            &status(UserStatus::Suspended),
+        )
+        .await
+        .unwrap();
+    assert_eq!(page.total, 0);
+}
+
+pub async fn test_logins_and_event_checkpoints_are_recorded(db: DbPool) {
+    let service = create_service(&db);
+    let tenant = TenantId::DEFAULT;
+    let user = service.create_user(tenant, "kc-login").await.unwrap();
+
+    let first = Utc::now() - Duration::hours(2);
+    let latest = Utc::now() - Duration::hours(1);
+    service
+        .record_user_login(tenant, "kc-login", latest)
+        .await
+        .unwrap();
+    // An older login arriving late does not move the time back
+    service
+        .record_user_login(tenant, "kc-login", first)
+        .await
+        .unwrap();
+    service
+        .record_user_login(tenant, "kc-unknown", latest)
+        .await
+        .unwrap();
+
+    let reloaded = service.get_user(tenant, user.id).await.unwrap().unwrap();
+    assert_eq!(
+        reloaded.last_login_at.map(|t| t.timestamp_millis()),
+        Some(latest.timestamp_millis())
+    );
+    assert_eq!(reloaded.version, user.version);
+
+    // Events of a shared realm are matched to users of any tenant
+    let acme = service.create_tenant("acme", "Acme").await.unwrap();
+    service.create_user(acme.id, "kc-acme").await.unwrap();
+    assert_eq!(
+        service
+            .get_user_tenant_by_keycloak_id("kc-acme")
+            .await
+            .unwrap(),
+        Some(acme.id)
+    );
+    assert_eq!(
+        service
+            .get_user_tenant_by_keycloak_id("kc-login")
+            .await
+            .unwrap(),
+        Some(tenant)
+    );
+    assert_eq!(
+        service
+            .get_user_tenant_by_keycloak_id("kc-unknown")
+            .await
+            .unwrap(),
+        None
+    );
+
+    let repo = db.event_checkpoint_repository();
+    assert_eq!(
+        repo.get_event_checkpoint(tenant, "events").await.unwrap(),
+        None
+    );
+    repo.save_event_checkpoint(tenant, "events", 1_700_000_000_000)
+        .await
+        .unwrap();
+    repo.save_event_checkpoint(tenant, "events", 1_700_000_060_000)
+        .await
+        .unwrap();
+    assert_eq!(
+        repo.get_event_checkpoint(tenant, "events").await.unwrap(),
+        Some(1_700_000_060_000)
+    );
+    assert_eq!(
+        repo.get_event_checkpoint(tenant, "admin-events")
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+pub async fn test_api_keys_are_stored_by_hash(db: DbPool) {
+    let service = create_service(&db);
+    let tenant = TenantId::DEFAULT;
+    let expires_at = Utc::now() + Duration::days(30);
+    let new_key = NewApiKey {
+        name: "nightly-sync".to_string(),
+        scopes: vec!["users:read".to_string(), "groups:write".to_string()],
+        expires_at: Some(expires_at),
+    };
+
+    let older = service
+        .create_api_key(tenant, &new_key, "uak_aaaa", "hash-1")
+        .await
+        .unwrap();
+    let newer = service
+        .create_api_key(tenant, &new_key, "uak_bbbb", "hash-2")
+        .await
+        .unwrap();
+    assert_eq!(newer.scopes, vec!["users:read", "groups:write"]);
+    assert_eq!(
+        newer.expires_at.map(|t| t.timestamp_millis()),
+        Some(expires_at.timestamp_millis())
+    );
+
+    // Hashes are unique: a collision is an error, not a second key
+    assert!(service
+        .create_api_key(tenant, &new_key, "uak_cccc", "hash-1")
+        .await
+        .is_err());
+
+    let ids: Vec<_> = service
+        .get_api_keys(tenant)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|k| k.id)
+        .collect();
+    assert_eq!(ids, vec![newer.id, older.id]);
+
+    let used_at = Utc::now();
+    service.record_api_key_use(older.id, used_at).await.unwrap();
+    let found = service
+        .get_api_key_by_hash("hash-1")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        found.last_used_at.map(|t| t.timestamp_millis()),
+        Some(used_at.timestamp_millis())
+    );
+
+    let rotated = service
+        .rotate_api_key(tenant, older.id, "uak_dddd", "hash-3")
+        .await
+        .unwrap();
+    assert_eq!(rotated.prefix, "uak_dddd");
+    assert!(service
+        .get_api_key_by_hash("hash-1")
+        .await
+        .unwrap()
+        .is_none());
+
+    service.revoke_api_key(tenant, older.id).await.unwrap();
+    let revoked = service
+        .get_api_key(tenant, older.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!revoked.is_active_at(Utc::now()));
+    assert!(matches!(
+        service.revoke_api_key(tenant, older.id).await,
+        Err(UserServiceError::NotFound)
+    ));
+    assert!(service
+        .get_api_key(TenantId(Uuid::new_v4()), newer.id)
+        .await
+        .unwrap()
+        .is_none());
+}
@@ -0,0 +1,21 @@
+//! The repository cases against SQLite. Every case gets its own in-memory database.
+
+#[macro_use]
+mod repository_cases;
+
+use user_lib::util::{connect, DbPool};
+
+/// Fresh in-memory database with the SQLite migrations applied
+async fn database() -> (DbPool, ()) {
+    let db = connect("sqlite::memory:", 0).await.unwrap();
+    let DbPool::Sqlite(pool) = &db else {
+        panic!("sqlite URL should open a SQLite pool");
+    };
+    sqlx::migrate!("./migrations_sqlite")
+        .run(pool)
+        .await
+        .unwrap();
+    (db, ())
+}
+
+repository_tests!(database);
@@ -19,10 +19,12 @@ pub async fn user_exists(world: &mut TestWorld, keycloak_id: String) {
         id: user_id,
         keycloak_id: keycloak_id.clone(),
         roles: vec![],
+        ..Default::default()
     });
     world.stored_users.push(UserRow {
         id: user_id.to_string(),
         keycloak_id,
+        ..Default::default()
     });
 }
 
@@ -33,10 +35,12 @@ pub async fn role_exists(world: &mut TestWorld, name: String) {
     world.current_role = Some(Role {
         id: role_id,
         name: name.clone(),
+        ..Default::default()
     });
     world.stored_roles.push(RoleRow {
         id: role_id.to_string(),
         name,
+        ..Default::default()
     });
 }
 
@@ -46,6 +50,7 @@ pub async fn user_has_role(world: &mut TestWorld, role_name: String) {
         user.roles.push(Role {
             id: role_id,
             name: role_name,
+            ..Default::default()
         });
     }
 }
@@ -58,6 +63,7 @@ pub async fn users_exist(world: &mut TestWorld, step: &cucumber::gherkin::Step)
             world.stored_users.push(UserRow {
                 id: Uuid::new_v4().to_string(),
                 keycloak_id: keycloak_id.to_string(),
+                ..Default::default()
             });
         }
     }
@@ -72,10 +78,12 @@ pub async fn roles_exist(world: &mut TestWorld, step: &cucumber::gherkin::Step)
             world.stored_roles.push(RoleRow {
                 id: role_id.to_string(),
                 name: name.to_string(),
+                ..Default::default()
             });
             world.roles.push(Role {
                 id: role_id,
                 name: name.to_string(),
+                ..Default::default()
             });
         }
     }
@@ -95,6 +103,7 @@ pub async fn users_have_role(
             let user_row = UserRow {
                 id: Uuid::new_v4().to_string(),
                 keycloak_id: keycloak_id.to_string(),
+                ..Default::default()
             };
             world.stored_users.push(user_row.clone());
             world.users_with_current_role.push(user_row);
@@ -1,7 +1,7 @@
 use cucumber::when;
 use uuid::Uuid;
 
-use user_lib::entities::PaginationParams;
+use user_lib::entities::{PaginationParams, RoleGrant, TenantId};
 use user_lib::repository::errors::UserRepositoryError;
 use user_lib::repository::models::RoleRow;
 
@@ -16,17 +16,18 @@ pub async fn create_role(world: &mut TestWorld, name: String) {
         |_| {},
         move |role_repo| {
             let n = name_clone.clone();
-            role_repo.expect_create_role().returning(move |_| {
+            role_repo.expect_create_role().returning(move |_, _, _| {
                 Ok(RoleRow {
                     id: role_id.to_string(),
                     name: n.clone(),
+                    ..Default::default()
                 })
             });
         },
         |_| {},
     );
 
-    world.role_result = Some(service.create_role(&name).await);
+    world.role_result = Some(service.create_role(TenantId::DEFAULT, &name, None).await);
 }
 
 #[when(expr = "I try to create a role with name {string}")]
@@ -36,12 +37,12 @@ pub async fn try_create_role(world: &mut TestWorld, name: String) {
         |role_repo| {
             role_repo
                 .expect_create_role()
-                .returning(|_| Err(UserRepositoryError::RoleNameAlreadyExists));
+                .returning(|_, _, _| Err(UserRepositoryError::RoleNameAlreadyExists));
         },
         |_| {},
     );
 
-    let result = service.create_role(&name).await;
+    let result = service.create_role(TenantId::DEFAULT, &name, None).await;
     if let Err(e) = result {
         world.error = Some(e);
     }
@@ -57,11 +58,17 @@ pub async fn assign_role(world: &mut TestWorld) {
         |_| {},
         |_| {},
         |user_role_repo| {
-            user_role_repo.expect_assign_role().returning(|_, _| Ok(()));
+            user_role_repo
+                .expect_assign_role()
+                .returning(|_, _, _, _| Ok(()));
         },
     );
 
-    world.delete_result = Some(service.assign_role(user_id, role_id).await);
+    world.delete_result = Some(
+        service
+            .assign_role(TenantId::DEFAULT, user_id, role_id, &RoleGrant::default())
+            .await,
+    );
 
     if let Some(user) = &mut world.current_user {
         user.roles.push(role);
@@ -79,11 +86,13 @@ pub async fn try_assign_role_again(world: &mut TestWorld, _role_name: String) {
         |user_role_repo| {
             user_role_repo
                 .expect_assign_role()
-                .returning(|_, _| Err(UserRepositoryError::UserAlreadyHasRole));
+                .returning(|_, _, _, _| Err(UserRepositoryError::UserAlreadyHasRole));
         },
     );
 
-    let result = service.assign_role(user_id, role_id).await;
+    let result = service
+        .assign_role(TenantId::DEFAULT, user_id, role_id, &RoleGrant::default())
+        .await;
     if let Err(e) = result {
         world.error = Some(e);
     }
@@ -100,11 +109,15 @@ pub async fn unassign_role(world: &mut TestWorld) {
         |user_role_repo| {
             user_role_repo
                 .expect_unassign_role()
-                .returning(|_, _| Ok(()));
+                .returning(|_, _, _| Ok(()));
         },
     );
 
-    world.delete_result = Some(service.unassign_role(user_id, role_id).await);
+    world.delete_result = Some(
+        service
+            .unassign_role(TenantId::DEFAULT, user_id, role_id)
+            .await,
+    );
 
     if let Some(user) = &mut world.current_user {
         user.roles.clear();
@@ -122,11 +135,15 @@ pub async fn assign_all_roles(world: &mut TestWorld) {
             |_| {},
             |_| {},
             |user_role_repo| {
-                user_role_repo.expect_assign_role().returning(|_, _| Ok(()));
+                user_role_repo
+                    .expect_assign_role()
+                    .returning(|_, _, _, _| Ok(()));
             },
         );
 
-        let _ = service.assign_role(user_id, role_id).await;
+        let _ = service
+            .assign_role(TenantId::DEFAULT, user_id, role_id, &RoleGrant::default())
+            .await;
         if let Some(user) = &mut world.current_user {
             user.roles.push(role.clone());
         }
@@ -144,7 +161,7 @@ pub async fn request_roles_page(world: &mut TestWorld, page: u32, page_size: u32
             let r = roles.clone();
             role_repo
                 .expect_get_roles_paginated()
-                .returning(move |pagination| {
+                .returning(move |_, pagination| {
                     let start = pagination.offset() as usize;
                     let end = (start + pagination.limit() as usize).min(r.len());
                     let page_roles = if start < r.len() {
@@ -159,7 +176,7 @@ pub async fn request_roles_page(world: &mut TestWorld, page: u32, page_size: u32
     );
 
     let pagination = PaginationParams::new(Some(page), Some(page_size));
-    world.paginated_roles_result = Some(service.get_roles(pagination).await);
+    world.paginated_roles_result = Some(service.get_roles(TenantId::DEFAULT, pagination).await);
 }
 
 #[when("I delete the role")]
@@ -168,11 +185,17 @@ pub async fn delete_role(world: &mut TestWorld) {
 
     let service = world.create_service_with_mocks(
         |_| {},
-        |role_repo| {
-            role_repo.expect_delete_role().returning(|_| Ok(()));
+        move |role_repo| {
+            role_repo.expect_get_role().returning(move |_, _| {
+                Ok(Some(RoleRow {
+                    id: role_id.to_string(),
+                    ..Default::default()
+                }))
+            });
+            role_repo.expect_delete_role().returning(|_, _, _| Ok(()));
         },
         |_| {},
     );
 
-    world.delete_result = Some(service.delete_role(role_id).await);
+    world.delete_result = Some(service.delete_role(TenantId::DEFAULT, role_id, None).await);
 }
@@ -1,7 +1,7 @@
 use cucumber::when;
 use uuid::Uuid;
 
-use user_lib::entities::PaginationParams;
+use user_lib::entities::{PaginationParams, TenantId, UserFilter};
 use user_lib::repository::models::UserRow;
 
 use crate::support::world::TestWorld;
@@ -14,10 +14,11 @@ pub async fn create_user(world: &mut TestWorld, keycloak_id: String) {
     let service = world.create_service_with_mocks(
         move |user_repo| {
             let kc = kc_id_clone.clone();
-            user_repo.expect_create_user().returning(move |_| {
+            user_repo.expect_create_user().returning(move |_, _| {
                 Ok(UserRow {
                     id: user_id.to_string(),
                     keycloak_id: kc.clone(),
+                    ..Default::default()
                 })
             });
         },
@@ -25,7 +26,7 @@ pub async fn create_user(world: &mut TestWorld, keycloak_id: String) {
         |_| {},
     );
 
-    world.user_result = Some(service.create_user(&keycloak_id).await);
+    world.user_result = Some(service.create_user(TenantId::DEFAULT, &keycloak_id).await);
 }
 
 #[when("I retrieve the user by their ID")]
@@ -36,22 +37,23 @@ pub async fn retrieve_user_by_id(world: &mut TestWorld) {
     let service = world.create_service_with_mocks(
         move |user_repo| {
             let u = user.clone();
-            user_repo.expect_get_user().returning(move |_| {
+            user_repo.expect_get_user().returning(move |_, _| {
                 Ok(Some(UserRow {
                     id: u.id.to_string(),
                     keycloak_id: u.keycloak_id.clone(),
+                    ..Default::default()
                 }))
             });
         },
         |role_repo| {
             role_repo
                 .expect_get_roles_for_user()
-                .returning(|_| Ok(vec![]));
+                .returning(|_, _| Ok(vec![]));
         },
         |_| {},
     );
 
-    world.optional_user_result = Some(service.get_user(user_id).await);
+    world.optional_user_result = Some(service.get_user(TenantId::DEFAULT, user_id).await);
 }
 
 #[when("I try to retrieve a user with a random ID")]
@@ -60,13 +62,13 @@ pub async fn retrieve_random_user(world: &mut TestWorld) {
 
     let service = world.create_service_with_mocks(
         |user_repo| {
-            user_repo.expect_get_user().returning(|_| Ok(None));
+            user_repo.expect_get_user().returning(|_, _| Ok(None));
         },
         |_| {},
         |_| {},
     );
 
-    world.optional_user_result = Some(service.get_user(random_id).await);
+    world.optional_user_result = Some(service.get_user(TenantId::DEFAULT, random_id).await);
 }
 
 #[when("I delete the user")]
@@ -75,13 +77,13 @@ pub async fn delete_user(world: &mut TestWorld) {
 
     let service = world.create_service_with_mocks(
         |user_repo| {
-            user_repo.expect_delete_user().returning(|_| Ok(()));
+            user_repo.expect_delete_user().returning(|_, _, _| Ok(()));
         },
         |_| {},
         |_| {},
     );
 
-    world.delete_result = Some(service.delete_user(user_id).await);
+    world.delete_result = Some(service.delete_user(TenantId::DEFAULT, user_id, None).await);
 }
 
 #[when(expr = "I request page {int} with page size {int}")]
@@ -94,7 +96,7 @@ pub async fn request_users_page(world: &mut TestWorld, page: u32, page_size: u32
             let u = users.clone();
             user_repo
                 .expect_get_users_paginated()
-                .returning(move |pagination| {
+                .returning(move |_, pagination, _| {
                     let start = pagination.offset() as usize;
                     let end = (start + pagination.limit() as usize).min(u.len());
                     let page_users = if start < u.len() {
@@ -108,13 +110,17 @@ pub async fn request_users_page(world: &mut TestWorld, page: u32, page_size: u32
         |role_repo| {
             role_repo
                 .expect_get_roles_for_users()
-                .returning(|_| Ok(vec![]));
+                .returning(|_, _| Ok(vec![]));
         },
         |_| {},
     );
 
     let pagination = PaginationParams::new(Some(page), Some(page_size));
-    world.paginated_users_result = Some(service.get_users(pagination).await);
+    world.paginated_users_result = Some(
+        service
+            .get_users(TenantId::DEFAULT, pagination, &UserFilter::default())
+            .await,
+    );
 }
 
 #[when(expr = "I get users with role {string}")]
@@ -128,16 +134,20 @@ pub async fn get_users_with_role(world: &mut TestWorld, _role_name: String) {
             let u = users.clone();
             user_repo
                 .expect_get_users_by_role_paginated()
-                .returning(move |_, _| Ok((u.clone(), total)));
+                .returning(move |_, _, _| Ok((u.clone(), total)));
         },
         |role_repo| {
             role_repo
                 .expect_get_roles_for_users()
-                .returning(|_| Ok(vec![]));
+                .returning(|_, _| Ok(vec![]));
         },
         |_| {},
     );
 
     let pagination = PaginationParams::default();
-    world.paginated_users_result = Some(service.get_users_by_role(role_id, pagination).await);
+    world.paginated_users_result = Some(
+        service
+            .get_users_by_role(TenantId::DEFAULT, role_id, pagination)
+            .await,
+    );
 }
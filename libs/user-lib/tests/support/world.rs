@@ -1,13 +1,16 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use cucumber::World;
 use mockall::mock;
 use std::sync::Arc;
 use uuid::Uuid;
 
-use user_lib::entities::{PaginatedResult, PaginationParams, Role, User};
+use user_lib::entities::{
+    PaginatedResult, PaginationParams, Role, RoleGrant, TenantId, User, UserFilter, UserSuspension,
+};
 use user_lib::errors_service::UserServiceError;
 use user_lib::repository::errors::UserRepositoryError;
-use user_lib::repository::models::{RoleRow, UserRoleMapping, UserRow};
+use user_lib::repository::models::{RoleRow, UserAttributesRow, UserRoleMapping, UserRow};
 use user_lib::repository::traits::{
     RoleRepositoryTrait, UserRepositoryTrait, UserRoleRepositoryTrait,
 };
@@ -20,12 +23,20 @@ mock! {
 
     #[async_trait]
     impl UserRepositoryTrait for UserRepo {
-        async fn create_user(&self, keycloak_id: &str) -> Result<UserRow, UserRepositoryError>;
-        async fn get_user(&self, user_id: Uuid) -> Result<Option<UserRow>, UserRepositoryError>;
-        async fn get_user_by_keycloak_id(&self, keycloak_id: &str) -> Result<Option<UserRow>, UserRepositoryError>;
-        async fn delete_user(&self, user_id: Uuid) -> Result<(), UserRepositoryError>;
-        async fn get_users_paginated(&self, pagination: PaginationParams) -> Result<(Vec<UserRow>, u64), UserRepositoryError>;
-        async fn get_users_by_role_paginated(&self, role_id: Uuid, pagination: PaginationParams) -> Result<(Vec<UserRow>, u64), UserRepositoryError>;
+        async fn create_user(&self, tenant: TenantId, keycloak_id: &str) -> Result<UserRow, UserRepositoryError>;
+        async fn get_user(&self, tenant: TenantId, user_id: Uuid) -> Result<Option<UserRow>, UserRepositoryError>;
+        async fn get_user_by_keycloak_id(&self, tenant: TenantId, keycloak_id: &str) -> Result<Option<UserRow>, UserRepositoryError>;
+        async fn get_user_tenant_by_keycloak_id(&self, keycloak_id: &str) -> Result<Option<TenantId>, UserRepositoryError>;
+        async fn delete_user(&self, tenant: TenantId, user_id: Uuid, expected_version: Option<i64>) -> Result<(), UserRepositoryError>;
+        async fn soft_delete_user(&self, tenant: TenantId, user_id: Uuid, expected_version: Option<i64>) -> Result<bool, UserRepositoryError>;
+        async fn restore_user(&self, tenant: TenantId, user_id: Uuid) -> Result<bool, UserRepositoryError>;
+        async fn touch_user(&self, tenant: TenantId, user_id: Uuid, expected_version: Option<i64>) -> Result<UserRow, UserRepositoryError>;
+        async fn set_user_suspension(&self, tenant: TenantId, user_id: Uuid, suspension: Option<UserSuspension>, expected_version: Option<i64>) -> Result<UserRow, UserRepositoryError>;
+        async fn record_user_login(&self, tenant: TenantId, keycloak_id: &str, at: DateTime<Utc>) -> Result<(), UserRepositoryError>;
+        async fn get_users_paginated(&self, tenant: TenantId, pagination: PaginationParams, filter: &UserFilter) -> Result<(Vec<UserRow>, u64), UserRepositoryError>;
+        async fn get_users_by_role_paginated(&self, tenant: TenantId, role_id: Uuid, pagination: PaginationParams) -> Result<(Vec<UserRow>, u64), UserRepositoryError>;
+        async fn get_user_attributes(&self, tenant: TenantId, user_ids: &[String]) -> Result<Vec<UserAttributesRow>, UserRepositoryError>;
+        async fn upsert_user_attributes(&self, tenant: TenantId, attributes: &UserAttributesRow) -> Result<(), UserRepositoryError>;
     }
 }
 
@@ -35,13 +46,17 @@ mock! {
 
     #[async_trait]
     impl RoleRepositoryTrait for RoleRepo {
-        async fn create_role(&self, name: &str) -> Result<RoleRow, UserRepositoryError>;
-        async fn get_role(&self, role_id: Uuid) -> Result<Option<RoleRow>, UserRepositoryError>;
-        async fn update_role(&self, role_id: Uuid, name: &str) -> Result<RoleRow, UserRepositoryError>;
-        async fn delete_role(&self, role_id: Uuid) -> Result<(), UserRepositoryError>;
-        async fn get_roles_for_user(&self, user_id: Uuid) -> Result<Vec<RoleRow>, UserRepositoryError>;
-        async fn get_roles_for_users(&self, user_ids: &[String]) -> Result<Vec<UserRoleMapping>, UserRepositoryError>;
-        async fn get_roles_paginated(&self, pagination: PaginationParams) -> Result<(Vec<RoleRow>, u64), UserRepositoryError>;
+        async fn create_role(&self, tenant: TenantId, name: &str, description: Option<String>) -> Result<RoleRow, UserRepositoryError>;
+        async fn get_role(&self, tenant: TenantId, role_id: Uuid) -> Result<Option<RoleRow>, UserRepositoryError>;
+        async fn get_system_role(&self, tenant: TenantId, name: &str) -> Result<Option<RoleRow>, UserRepositoryError>;
+        async fn update_role(&self, tenant: TenantId, role_id: Uuid, name: &str, description: Option<String>, expected_version: Option<i64>) -> Result<RoleRow, UserRepositoryError>;
+        async fn delete_role(&self, tenant: TenantId, role_id: Uuid, expected_version: Option<i64>) -> Result<(), UserRepositoryError>;
+        async fn soft_delete_role(&self, tenant: TenantId, role_id: Uuid, expected_version: Option<i64>) -> Result<bool, UserRepositoryError>;
+        async fn restore_role(&self, tenant: TenantId, role_id: Uuid) -> Result<bool, UserRepositoryError>;
+        async fn get_roles_for_user(&self, tenant: TenantId, user_id: Uuid) -> Result<Vec<RoleRow>, UserRepositoryError>;
+        async fn get_granted_roles_for_user(&self, tenant: TenantId, user_id: Uuid) -> Result<Vec<RoleRow>, UserRepositoryError>;
+        async fn get_roles_for_users(&self, tenant: TenantId, user_ids: &[String]) -> Result<Vec<UserRoleMapping>, UserRepositoryError>;
+        async fn get_roles_paginated(&self, tenant: TenantId, pagination: PaginationParams) -> Result<(Vec<RoleRow>, u64), UserRepositoryError>;
     }
 }
 
@@ -51,8 +66,10 @@ mock! {
 
     #[async_trait]
     impl UserRoleRepositoryTrait for UserRoleRepo {
-        async fn assign_role(&self, user_id: &str, role_id: &str) -> Result<(), UserRepositoryError>;
-        async fn unassign_role(&self, user_id: &str, role_id: &str) -> Result<(), UserRepositoryError>;
+        async fn assign_role(&self, tenant: TenantId, user_id: &str, role_id: &str, grant: &RoleGrant) -> Result<(), UserRepositoryError>;
+        async fn unassign_role(&self, tenant: TenantId, user_id: &str, role_id: &str) -> Result<(), UserRepositoryError>;
+        async fn get_users_with_expired_role_grants(&self, now: DateTime<Utc>) -> Result<Vec<(String, String)>, UserRepositoryError>;
+        async fn delete_expired_role_grants(&self, now: DateTime<Utc>) -> Result<Vec<(String, String)>, UserRepositoryError>;
     }
 }
 
@@ -90,6 +107,10 @@ impl TestWorld {
         let mut user_role_repo = MockUserRoleRepo::new();
 
         setup_user_repo(&mut user_repo);
+        // No scenario sets user attributes
+        user_repo
+            .expect_get_user_attributes()
+            .returning(|_, _| Ok(vec![]));
         setup_role_repo(&mut role_repo);
         setup_user_role_repo(&mut user_role_repo);
 
@@ -4,7 +4,7 @@ use testcontainers::{
     runners::AsyncRunner,
     GenericImage, ImageExt,
 };
-use user_lib::entities::PaginationParams;
+use user_lib::entities::{PaginationParams, RoleGrant, TenantId, UserFilter};
 use user_lib::util::*;
 use user_lib::{
     repository::{RoleRepository, UserRepository, UserRoleRepository},
@@ -47,7 +47,7 @@ async fn integration_user_service_flow() {
 
     // Verify seeded data exists
     let seeded_roles = user_service
-        .get_roles(PaginationParams::default())
+        .get_roles(TenantId::DEFAULT, PaginationParams::default())
         .await
         .unwrap();
     assert_eq!(
@@ -65,7 +65,11 @@ async fn integration_user_service_flow() {
     );
 
     let seeded_users = user_service
-        .get_users(PaginationParams::default())
+        .get_users(
+            TenantId::DEFAULT,
+            PaginationParams::default(),
+            &UserFilter::default(),
+        )
         .await
         .unwrap();
     assert_eq!(seeded_users.items.len(), 1, "Should have seeded root user");
@@ -78,46 +82,85 @@ async fn integration_user_service_flow() {
     );
 
     // Create additional users (with keycloak_id only - profile data is in Keycloak)
-    let user1 = user_service.create_user("kc-alice-12345").await.unwrap();
-    let user2 = user_service.create_user("kc-bob-67890").await.unwrap();
-    let user3 = user_service.create_user("kc-charlie-11111").await.unwrap();
+    let user1 = user_service
+        .create_user(TenantId::DEFAULT, "kc-alice-12345")
+        .await
+        .unwrap();
+    let user2 = user_service
+        .create_user(TenantId::DEFAULT, "kc-bob-67890")
+        .await
+        .unwrap();
+    let user3 = user_service
+        .create_user(TenantId::DEFAULT, "kc-charlie-11111")
+        .await
+        .unwrap();
 
     // Create additional roles (different names from seeded ones)
-    let role_editor = user_service.create_role("editor").await.unwrap();
-    let _role_viewer = user_service.create_role("viewer").await.unwrap();
+    let role_editor = user_service
+        .create_role(TenantId::DEFAULT, "editor", None)
+        .await
+        .unwrap();
+    let _role_viewer = user_service
+        .create_role(TenantId::DEFAULT, "viewer", None)
+        .await
+        .unwrap();
 
     // Assign users to editor role
     user_service
-        .assign_role(user1.id, role_editor.id)
+        .assign_role(
+            TenantId::DEFAULT,
+            user1.id,
+            role_editor.id,
+            &RoleGrant::default(),
+        )
         .await
         .unwrap();
     user_service
-        .assign_role(user2.id, role_editor.id)
+        .assign_role(
+            TenantId::DEFAULT,
+            user2.id,
+            role_editor.id,
+            &RoleGrant::default(),
+        )
         .await
         .unwrap();
 
     // Should have 4 users total (1 seeded root + 3 created)
     let list_users = user_service
-        .get_users(PaginationParams::default())
+        .get_users(
+            TenantId::DEFAULT,
+            PaginationParams::default(),
+            &UserFilter::default(),
+        )
         .await
         .unwrap();
     assert_eq!(list_users.items.len(), 4);
 
     // Delete user3
-    user_service.delete_user(user3.id).await.unwrap();
-    let deleted = user_service.get_user(user3.id).await.unwrap();
+    user_service
+        .delete_user(TenantId::DEFAULT, user3.id, None)
+        .await
+        .unwrap();
+    let deleted = user_service
+        .get_user(TenantId::DEFAULT, user3.id)
+        .await
+        .unwrap();
     assert!(deleted.is_none());
 
     // Should have 3 users remaining (1 seeded root + 2 created)
     let list_users = user_service
-        .get_users(PaginationParams::default())
+        .get_users(
+            TenantId::DEFAULT,
+            PaginationParams::default(),
+            &UserFilter::default(),
+        )
         .await
         .unwrap();
     assert_eq!(list_users.items.len(), 3);
 
     // Verify total roles (2 seeded + 2 created)
     let list_roles = user_service
-        .get_roles(PaginationParams::default())
+        .get_roles(TenantId::DEFAULT, PaginationParams::default())
         .await
         .unwrap();
     assert_eq!(list_roles.items.len(), 4);
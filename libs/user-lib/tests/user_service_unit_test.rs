@@ -1,12 +1,13 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use mockall::mock;
 use std::sync::Arc;
 use uuid::Uuid;
 
-use user_lib::entities::PaginationParams;
+use user_lib::entities::{PaginationParams, RoleGrant, TenantId, UserFilter, UserSuspension};
 use user_lib::errors_service::UserServiceError;
 use user_lib::repository::errors::UserRepositoryError;
-use user_lib::repository::models::{RoleRow, UserRoleMapping, UserRow};
+use user_lib::repository::models::{RoleRow, UserAttributesRow, UserRoleMapping, UserRow};
 use user_lib::repository::traits::{
     RoleRepositoryTrait, UserRepositoryTrait, UserRoleRepositoryTrait,
 };
@@ -17,12 +18,20 @@ mock! {
 
     #[async_trait]
     impl UserRepositoryTrait for UserRepo {
-        async fn create_user(&self, keycloak_id: &str) -> Result<UserRow, UserRepositoryError>;
-        async fn get_user(&self, user_id: Uuid) -> Result<Option<UserRow>, UserRepositoryError>;
-        async fn get_user_by_keycloak_id(&self, keycloak_id: &str) -> Result<Option<UserRow>, UserRepositoryError>;
-        async fn delete_user(&self, user_id: Uuid) -> Result<(), UserRepositoryError>;
-        async fn get_users_paginated(&self, pagination: PaginationParams) -> Result<(Vec<UserRow>, u64), UserRepositoryError>;
-        async fn get_users_by_role_paginated(&self, role_id: Uuid, pagination: PaginationParams) -> Result<(Vec<UserRow>, u64), UserRepositoryError>;
+        async fn create_user(&self, tenant: TenantId, keycloak_id: &str) -> Result<UserRow, UserRepositoryError>;
+        async fn get_user(&self, tenant: TenantId, user_id: Uuid) -> Result<Option<UserRow>, UserRepositoryError>;
+        async fn get_user_by_keycloak_id(&self, tenant: TenantId, keycloak_id: &str) -> Result<Option<UserRow>, UserRepositoryError>;
+        async fn get_user_tenant_by_keycloak_id(&self, keycloak_id: &str) -> Result<Option<TenantId>, UserRepositoryError>;
+        async fn delete_user(&self, tenant: TenantId, user_id: Uuid, expected_version: Option<i64>) -> Result<(), UserRepositoryError>;
+        async fn soft_delete_user(&self, tenant: TenantId, user_id: Uuid, expected_version: Option<i64>) -> Result<bool, UserRepositoryError>;
+        async fn restore_user(&self, tenant: TenantId, user_id: Uuid) -> Result<bool, UserRepositoryError>;
+        async fn touch_user(&self, tenant: TenantId, user_id: Uuid, expected_version: Option<i64>) -> Result<UserRow, UserRepositoryError>;
+        async fn set_user_suspension(&self, tenant: TenantId, user_id: Uuid, suspension: Option<UserSuspension>, expected_version: Option<i64>) -> Result<UserRow, UserRepositoryError>;
+        async fn record_user_login(&self, tenant: TenantId, keycloak_id: &str, at: DateTime<Utc>) -> Result<(), UserRepositoryError>;
+        async fn get_users_paginated(&self, tenant: TenantId, pagination: PaginationParams, filter: &UserFilter) -> Result<(Vec<UserRow>, u64), UserRepositoryError>;
+        async fn get_users_by_role_paginated(&self, tenant: TenantId, role_id: Uuid, pagination: PaginationParams) -> Result<(Vec<UserRow>, u64), UserRepositoryError>;
+        async fn get_user_attributes(&self, tenant: TenantId, user_ids: &[String]) -> Result<Vec<UserAttributesRow>, UserRepositoryError>;
+        async fn upsert_user_attributes(&self, tenant: TenantId, attributes: &UserAttributesRow) -> Result<(), UserRepositoryError>;
     }
 }
 
@@ -31,13 +40,17 @@ mock! {
 
     #[async_trait]
     impl RoleRepositoryTrait for RoleRepo {
-        async fn create_role(&self, name: &str) -> Result<RoleRow, UserRepositoryError>;
-        async fn get_role(&self, role_id: Uuid) -> Result<Option<RoleRow>, UserRepositoryError>;
-        async fn update_role(&self, role_id: Uuid, name: &str) -> Result<RoleRow, UserRepositoryError>;
-        async fn delete_role(&self, role_id: Uuid) -> Result<(), UserRepositoryError>;
-        async fn get_roles_for_user(&self, user_id: Uuid) -> Result<Vec<RoleRow>, UserRepositoryError>;
-        async fn get_roles_for_users(&self, user_ids: &[String]) -> Result<Vec<UserRoleMapping>, UserRepositoryError>;
-        async fn get_roles_paginated(&self, pagination: PaginationParams) -> Result<(Vec<RoleRow>, u64), UserRepositoryError>;
+        async fn create_role(&self, tenant: TenantId, name: &str, description: Option<String>) -> Result<RoleRow, UserRepositoryError>;
+        async fn get_role(&self, tenant: TenantId, role_id: Uuid) -> Result<Option<RoleRow>, UserRepositoryError>;
+        async fn get_system_role(&self, tenant: TenantId, name: &str) -> Result<Option<RoleRow>, UserRepositoryError>;
+        async fn update_role(&self, tenant: TenantId, role_id: Uuid, name: &str, description: Option<String>, expected_version: Option<i64>) -> Result<RoleRow, UserRepositoryError>;
+        async fn delete_role(&self, tenant: TenantId, role_id: Uuid, expected_version: Option<i64>) -> Result<(), UserRepositoryError>;
+        async fn soft_delete_role(&self, tenant: TenantId, role_id: Uuid, expected_version: Option<i64>) -> Result<bool, UserRepositoryError>;
+        async fn restore_role(&self, tenant: TenantId, role_id: Uuid) -> Result<bool, UserRepositoryError>;
+        async fn get_roles_for_user(&self, tenant: TenantId, user_id: Uuid) -> Result<Vec<RoleRow>, UserRepositoryError>;
+        async fn get_granted_roles_for_user(&self, tenant: TenantId, user_id: Uuid) -> Result<Vec<RoleRow>, UserRepositoryError>;
+        async fn get_roles_for_users(&self, tenant: TenantId, user_ids: &[String]) -> Result<Vec<UserRoleMapping>, UserRepositoryError>;
+        async fn get_roles_paginated(&self, tenant: TenantId, pagination: PaginationParams) -> Result<(Vec<RoleRow>, u64), UserRepositoryError>;
     }
 }
 
@@ -46,8 +59,10 @@ mock! {
 
     #[async_trait]
     impl UserRoleRepositoryTrait for UserRoleRepo {
-        async fn assign_role(&self, user_id: &str, role_id: &str) -> Result<(), UserRepositoryError>;
-        async fn unassign_role(&self, user_id: &str, role_id: &str) -> Result<(), UserRepositoryError>;
+        async fn assign_role(&self, tenant: TenantId, user_id: &str, role_id: &str, grant: &RoleGrant) -> Result<(), UserRepositoryError>;
+        async fn unassign_role(&self, tenant: TenantId, user_id: &str, role_id: &str) -> Result<(), UserRepositoryError>;
+        async fn get_users_with_expired_role_grants(&self, now: DateTime<Utc>) -> Result<Vec<(String, String)>, UserRepositoryError>;
+        async fn delete_expired_role_grants(&self, now: DateTime<Utc>) -> Result<Vec<(String, String)>, UserRepositoryError>;
     }
 }
 
@@ -76,17 +91,18 @@ async fn test_create_user_success() {
 
     user_repo
         .expect_create_user()
-        .withf(|kc_id| kc_id == "kc-user-12345")
+        .withf(|_, kc_id| kc_id == "kc-user-12345")
         .times(1)
-        .returning(move |kc_id| {
+        .returning(move |_, kc_id| {
             Ok(UserRow {
                 id: user_id.to_string(),
                 keycloak_id: kc_id.to_string(),
+                ..Default::default()
             })
         });
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
-    let result = service.create_user(keycloak_id).await;
+    let result = service.create_user(TenantId::DEFAULT, keycloak_id).await;
 
     assert!(result.is_ok());
     let user = result.unwrap();
@@ -100,6 +116,9 @@ async fn test_create_user_success() {
 #[tokio::test]
 async fn test_get_user_success() {
     let mut user_repo = MockUserRepo::new();
+    user_repo
+        .expect_get_user_attributes()
+        .returning(|_, _| Ok(vec![]));
     let mut role_repo = MockRoleRepo::new();
     let user_role_repo = MockUserRoleRepo::new();
 
@@ -109,27 +128,29 @@ async fn test_get_user_success() {
 
     user_repo
         .expect_get_user()
-        .withf(move |id| *id == user_id)
+        .withf(move |_, id| *id == user_id)
         .times(1)
-        .returning(move |_| {
+        .returning(move |_, _| {
             Ok(Some(UserRow {
                 id: user_id.to_string(),
                 keycloak_id: keycloak_id.to_string(),
+                ..Default::default()
             }))
         });
 
     role_repo
         .expect_get_roles_for_user()
         .times(1)
-        .returning(move |_| {
+        .returning(move |_, _| {
             Ok(vec![RoleRow {
                 id: role_id.to_string(),
                 name: "admin".to_string(),
+                ..Default::default()
             }])
         });
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
-    let result = service.get_user(user_id).await;
+    let result = service.get_user(TenantId::DEFAULT, user_id).await;
 
     assert!(result.is_ok());
     let user = result.unwrap().unwrap();
@@ -147,10 +168,13 @@ async fn test_get_user_not_found() {
 
     let user_id = Uuid::new_v4();
 
-    user_repo.expect_get_user().times(1).returning(|_| Ok(None));
+    user_repo
+        .expect_get_user()
+        .times(1)
+        .returning(|_, _| Ok(None));
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
-    let result = service.get_user(user_id).await;
+    let result = service.get_user(TenantId::DEFAULT, user_id).await;
 
     assert!(result.is_ok());
     assert!(result.unwrap().is_none());
@@ -159,6 +183,9 @@ async fn test_get_user_not_found() {
 #[tokio::test]
 async fn test_get_user_by_keycloak_id_success() {
     let mut user_repo = MockUserRepo::new();
+    user_repo
+        .expect_get_user_attributes()
+        .returning(|_, _| Ok(vec![]));
     let mut role_repo = MockRoleRepo::new();
     let user_role_repo = MockUserRoleRepo::new();
 
@@ -167,22 +194,25 @@ async fn test_get_user_by_keycloak_id_success() {
 
     user_repo
         .expect_get_user_by_keycloak_id()
-        .withf(|kc_id| kc_id == "kc-user-lookup")
+        .withf(|_, kc_id| kc_id == "kc-user-lookup")
         .times(1)
-        .returning(move |kc_id| {
+        .returning(move |_, kc_id| {
             Ok(Some(UserRow {
                 id: user_id.to_string(),
                 keycloak_id: kc_id.to_string(),
+                ..Default::default()
             }))
         });
 
     role_repo
         .expect_get_roles_for_user()
         .times(1)
-        .returning(|_| Ok(vec![]));
+        .returning(|_, _| Ok(vec![]));
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
-    let result = service.get_user_by_keycloak_id(keycloak_id).await;
+    let result = service
+        .get_user_by_keycloak_id(TenantId::DEFAULT, keycloak_id)
+        .await;
 
     assert!(result.is_ok());
     let user = result.unwrap().unwrap();
@@ -201,12 +231,12 @@ async fn test_delete_user_success() {
 
     user_repo
         .expect_delete_user()
-        .withf(move |id| *id == user_id)
+        .withf(move |_, id, _| *id == user_id)
         .times(1)
-        .returning(|_| Ok(()));
+        .returning(|_, _, _| Ok(()));
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
-    let result = service.delete_user(user_id).await;
+    let result = service.delete_user(TenantId::DEFAULT, user_id, None).await;
 
     assert!(result.is_ok());
 }
@@ -224,12 +254,14 @@ async fn test_assign_role_success() {
 
     user_role_repo
         .expect_assign_role()
-        .withf(move |uid, rid| uid == user_id.to_string() && rid == role_id.to_string())
+        .withf(move |_, uid, rid, _| uid == user_id.to_string() && rid == role_id.to_string())
         .times(1)
-        .returning(|_, _| Ok(()));
+        .returning(|_, _, _, _| Ok(()));
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
-    let result = service.assign_role(user_id, role_id).await;
+    let result = service
+        .assign_role(TenantId::DEFAULT, user_id, role_id, &RoleGrant::default())
+        .await;
 
     assert!(result.is_ok());
 }
@@ -246,10 +278,12 @@ async fn test_assign_role_already_assigned() {
     user_role_repo
         .expect_assign_role()
         .times(1)
-        .returning(|_, _| Err(UserRepositoryError::UserAlreadyHasRole));
+        .returning(|_, _, _, _| Err(UserRepositoryError::UserAlreadyHasRole));
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
-    let result = service.assign_role(user_id, role_id).await;
+    let result = service
+        .assign_role(TenantId::DEFAULT, user_id, role_id, &RoleGrant::default())
+        .await;
 
     assert!(result.is_err());
     assert!(matches!(
@@ -269,12 +303,14 @@ async fn test_unassign_role_success() {
 
     user_role_repo
         .expect_unassign_role()
-        .withf(move |uid, rid| uid == user_id.to_string() && rid == role_id.to_string())
+        .withf(move |_, uid, rid| uid == user_id.to_string() && rid == role_id.to_string())
         .times(1)
-        .returning(|_, _| Ok(()));
+        .returning(|_, _, _| Ok(()));
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
-    let result = service.unassign_role(user_id, role_id).await;
+    let result = service
+        .unassign_role(TenantId::DEFAULT, user_id, role_id)
+        .await;
 
     assert!(result.is_ok());
 }
@@ -291,17 +327,18 @@ async fn test_create_role_success() {
 
     role_repo
         .expect_create_role()
-        .withf(|name| name == "editor")
+        .withf(|_, name, _| name == "editor")
         .times(1)
-        .returning(move |_| {
+        .returning(move |_, _, _| {
             Ok(RoleRow {
                 id: role_id.to_string(),
                 name: "editor".to_string(),
+                ..Default::default()
             })
         });
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
-    let result = service.create_role("editor").await;
+    let result = service.create_role(TenantId::DEFAULT, "editor", None).await;
 
     assert!(result.is_ok());
     let role = result.unwrap();
@@ -318,10 +355,10 @@ async fn test_create_role_name_already_exists() {
     role_repo
         .expect_create_role()
         .times(1)
-        .returning(|_| Err(UserRepositoryError::RoleNameAlreadyExists));
+        .returning(|_, _, _| Err(UserRepositoryError::RoleNameAlreadyExists));
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
-    let result = service.create_role("admin").await;
+    let result = service.create_role(TenantId::DEFAULT, "admin", None).await;
 
     assert!(result.is_err());
     assert!(matches!(
@@ -342,17 +379,18 @@ async fn test_get_role_success() {
 
     role_repo
         .expect_get_role()
-        .withf(move |id| *id == role_id)
+        .withf(move |_, id| *id == role_id)
         .times(1)
-        .returning(move |_| {
+        .returning(move |_, _| {
             Ok(Some(RoleRow {
                 id: role_id.to_string(),
                 name: "viewer".to_string(),
+                ..Default::default()
             }))
         });
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
-    let result = service.get_role(role_id).await;
+    let result = service.get_role(TenantId::DEFAULT, role_id).await;
 
     assert!(result.is_ok());
     let role = result.unwrap().unwrap();
@@ -366,10 +404,13 @@ async fn test_get_role_not_found() {
     let mut role_repo = MockRoleRepo::new();
     let user_role_repo = MockUserRoleRepo::new();
 
-    role_repo.expect_get_role().times(1).returning(|_| Ok(None));
+    role_repo
+        .expect_get_role()
+        .times(1)
+        .returning(|_, _| Ok(None));
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
-    let result = service.get_role(Uuid::new_v4()).await;
+    let result = service.get_role(TenantId::DEFAULT, Uuid::new_v4()).await;
 
     assert!(result.is_ok());
     assert!(result.unwrap().is_none());
@@ -385,19 +426,29 @@ async fn test_update_role_success() {
 
     let role_id = Uuid::new_v4();
 
+    role_repo.expect_get_role().returning(move |_, _| {
+        Ok(Some(RoleRow {
+            id: role_id.to_string(),
+            name: "admin".to_string(),
+            ..Default::default()
+        }))
+    });
     role_repo
         .expect_update_role()
-        .withf(move |id, name| *id == role_id && name == "super-admin")
+        .withf(move |_, id, name, _, _| *id == role_id && name == "super-admin")
         .times(1)
-        .returning(move |_, _| {
+        .returning(move |_, _, _, _, _| {
             Ok(RoleRow {
                 id: role_id.to_string(),
                 name: "super-admin".to_string(),
+                ..Default::default()
             })
         });
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
-    let result = service.update_role(role_id, "super-admin").await;
+    let result = service
+        .update_role(TenantId::DEFAULT, role_id, "super-admin", None, None)
+        .await;
 
     assert!(result.is_ok());
     let role = result.unwrap();
@@ -414,14 +465,21 @@ async fn test_delete_role_success() {
 
     let role_id = Uuid::new_v4();
 
+    role_repo.expect_get_role().returning(move |_, _| {
+        Ok(Some(RoleRow {
+            id: role_id.to_string(),
+            name: "admin".to_string(),
+            ..Default::default()
+        }))
+    });
     role_repo
         .expect_delete_role()
-        .withf(move |id| *id == role_id)
+        .withf(move |_, id, _| *id == role_id)
         .times(1)
-        .returning(|_| Ok(()));
+        .returning(|_, _, _| Ok(()));
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
-    let result = service.delete_role(role_id).await;
+    let result = service.delete_role(TenantId::DEFAULT, role_id, None).await;
 
     assert!(result.is_ok());
 }
@@ -431,6 +489,9 @@ async fn test_delete_role_success() {
 #[tokio::test]
 async fn test_get_users_success() {
     let mut user_repo = MockUserRepo::new();
+    user_repo
+        .expect_get_user_attributes()
+        .returning(|_, _| Ok(vec![]));
     let mut role_repo = MockRoleRepo::new();
     let user_role_repo = MockUserRoleRepo::new();
 
@@ -442,16 +503,18 @@ async fn test_get_users_success() {
     user_repo
         .expect_get_users_paginated()
         .times(1)
-        .returning(move |_| {
+        .returning(move |_, _, _| {
             Ok((
                 vec![
                     UserRow {
                         id: user1_id.to_string(),
                         keycloak_id: "kc-user-1".to_string(),
+                        ..Default::default()
                     },
                     UserRow {
                         id: user2_id.to_string(),
                         keycloak_id: "kc-user-2".to_string(),
+                        ..Default::default()
                     },
                 ],
                 2,
@@ -461,16 +524,23 @@ async fn test_get_users_success() {
     role_repo
         .expect_get_roles_for_users()
         .times(1)
-        .returning(move |_| {
+        .returning(move |_, _| {
             Ok(vec![UserRoleMapping {
                 user_id: user1_id_clone.to_string(),
                 role_id: role_id.to_string(),
                 role_name: "admin".to_string(),
+                ..Default::default()
             }])
         });
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
-    let result = service.get_users(PaginationParams::default()).await;
+    let result = service
+        .get_users(
+            TenantId::DEFAULT,
+            PaginationParams::default(),
+            &UserFilter::default(),
+        )
+        .await;
 
     assert!(result.is_ok());
     let paginated = result.unwrap();
@@ -493,10 +563,16 @@ async fn test_get_users_empty() {
     user_repo
         .expect_get_users_paginated()
         .times(1)
-        .returning(|_| Ok((vec![], 0)));
+        .returning(|_, _, _| Ok((vec![], 0)));
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
-    let result = service.get_users(PaginationParams::default()).await;
+    let result = service
+        .get_users(
+            TenantId::DEFAULT,
+            PaginationParams::default(),
+            &UserFilter::default(),
+        )
+        .await;
 
     assert!(result.is_ok());
     let paginated = result.unwrap();
@@ -518,16 +594,18 @@ async fn test_get_roles_success() {
     role_repo
         .expect_get_roles_paginated()
         .times(1)
-        .returning(move |_| {
+        .returning(move |_, _| {
             Ok((
                 vec![
                     RoleRow {
                         id: role1_id.to_string(),
                         name: "admin".to_string(),
+                        ..Default::default()
                     },
                     RoleRow {
                         id: role2_id.to_string(),
                         name: "user".to_string(),
+                        ..Default::default()
                     },
                 ],
                 2,
@@ -535,7 +613,9 @@ async fn test_get_roles_success() {
         });
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
-    let result = service.get_roles(PaginationParams::default()).await;
+    let result = service
+        .get_roles(TenantId::DEFAULT, PaginationParams::default())
+        .await;
 
     assert!(result.is_ok());
     let paginated = result.unwrap();
@@ -558,17 +638,18 @@ async fn test_get_roles_for_user_success() {
 
     role_repo
         .expect_get_roles_for_user()
-        .withf(move |id| *id == user_id)
+        .withf(move |_, id| *id == user_id)
         .times(1)
-        .returning(move |_| {
+        .returning(move |_, _| {
             Ok(vec![RoleRow {
                 id: role_id.to_string(),
                 name: "member".to_string(),
+                ..Default::default()
             }])
         });
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
-    let result = service.get_roles_for_user(user_id).await;
+    let result = service.get_roles_for_user(TenantId::DEFAULT, user_id).await;
 
     assert!(result.is_ok());
     let roles = result.unwrap();
@@ -581,6 +662,9 @@ async fn test_get_roles_for_user_success() {
 #[tokio::test]
 async fn test_get_users_by_role_success() {
     let mut user_repo = MockUserRepo::new();
+    user_repo
+        .expect_get_user_attributes()
+        .returning(|_, _| Ok(vec![]));
     let mut role_repo = MockRoleRepo::new();
     let user_role_repo = MockUserRoleRepo::new();
 
@@ -589,13 +673,14 @@ async fn test_get_users_by_role_success() {
 
     user_repo
         .expect_get_users_by_role_paginated()
-        .withf(move |id, _| *id == role_id)
+        .withf(move |_, id, _| *id == role_id)
         .times(1)
-        .returning(move |_, _| {
+        .returning(move |_, _, _| {
             Ok((
                 vec![UserRow {
                     id: user_id.to_string(),
                     keycloak_id: "kc-admin-user".to_string(),
+                    ..Default::default()
                 }],
                 1,
             ))
@@ -604,17 +689,18 @@ async fn test_get_users_by_role_success() {
     role_repo
         .expect_get_roles_for_users()
         .times(1)
-        .returning(move |_| {
+        .returning(move |_, _| {
             Ok(vec![UserRoleMapping {
                 user_id: user_id.to_string(),
                 role_id: role_id.to_string(),
                 role_name: "admin".to_string(),
+                ..Default::default()
             }])
         });
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
     let result = service
-        .get_users_by_role(role_id, PaginationParams::default())
+        .get_users_by_role(TenantId::DEFAULT, role_id, PaginationParams::default())
         .await;
 
     assert!(result.is_ok());
@@ -634,7 +720,7 @@ async fn test_create_role_empty_name() {
     let user_role_repo = MockUserRoleRepo::new();
 
     let service = create_test_service(user_repo, role_repo, user_role_repo);
-    let result = service.create_role("").await;
+    let result = service.create_role(TenantId::DEFAULT, "", None).await;
 
     assert!(result.is_err());
     assert!(matches!(
@@ -651,7 +737,9 @@ async fn test_update_role_empty_name() {
 
     let role_id = Uuid::new_v4();
     let service = create_test_service(user_repo, role_repo, user_role_repo);
-    let result = service.update_role(role_id, "   ").await;
+    let result = service
+        .update_role(TenantId::DEFAULT, role_id, "   ", None, None)
+        .await;
 
     assert!(result.is_err());
     assert!(matches!(